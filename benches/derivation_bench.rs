@@ -0,0 +1,115 @@
+//! Derivation benchmarks
+//!
+//! Run with `cargo bench`.
+
+use bip32::XPrv;
+use bip_keychain::{
+    canonicalize, derive_key_from_entity, hash_entity, Canonicalization, HashFunction,
+    KeyDerivation, Keychain,
+};
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+
+const TEST_MNEMONIC: &str =
+    "abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon about";
+
+const ENTITY_JSON: &str = include_str!("../examples/person-identity.json");
+
+/// The entity from `ENTITY_JSON`, pretty-printed as users typically write it
+fn pretty_entity() -> String {
+    let value: serde_json::Value = serde_json::from_str(ENTITY_JSON).unwrap();
+    serde_json::to_string_pretty(&value["entity"]).unwrap()
+}
+
+/// The pre-caching derivation: all three levels from the master key
+fn derive_from_master(master: &XPrv, entity_index: u32) -> XPrv {
+    master
+        .derive_child(83696968u32.wrapping_add(1 << 31).into())
+        .and_then(|key| key.derive_child(67797668u32.wrapping_add(1 << 31).into()))
+        .and_then(|key| key.derive_child(entity_index.wrapping_add(1 << 31).into()))
+        .unwrap()
+}
+
+fn bench_keychain_path(c: &mut Criterion) {
+    let keychain = Keychain::from_mnemonic(TEST_MNEMONIC).unwrap();
+    let master = keychain.master_key().clone();
+
+    let mut group = c.benchmark_group("bip_keychain_path");
+    group.bench_function("cached_application_key", |b| {
+        b.iter(|| keychain.derive_bip_keychain_path(black_box(42)).unwrap())
+    });
+    group.bench_function("from_master_key", |b| {
+        b.iter(|| derive_from_master(&master, black_box(42)))
+    });
+    group.finish();
+}
+
+fn bench_canonicalization(c: &mut Criterion) {
+    let pretty = pretty_entity();
+    let entity = KeyDerivation::from_json(ENTITY_JSON).unwrap();
+
+    let mut group = c.benchmark_group("canonicalize");
+    for (name, mode) in [
+        ("serde", Canonicalization::Serde),
+        ("jcs", Canonicalization::Jcs),
+        ("dcbor", Canonicalization::Dcbor),
+    ] {
+        group.bench_function(name, |b| {
+            b.iter(|| canonicalize(black_box(&pretty), mode, false).unwrap())
+        });
+    }
+    // Input that is already in serde canonical form is hashed without copying
+    let compact =
+        String::from_utf8(canonicalize(&pretty, Canonicalization::Serde, true).unwrap()).unwrap();
+    group.bench_function("serde_already_canonical", |b| {
+        b.iter(|| canonicalize(black_box(&compact), Canonicalization::Serde, false).unwrap())
+    });
+    group.bench_function("canonical_entity", |b| {
+        b.iter(|| black_box(&entity).canonical_entity().unwrap())
+    });
+    group.finish();
+}
+
+fn bench_hash_entity(c: &mut Criterion) {
+    let pretty = pretty_entity();
+    let parent_entropy = [0u8; 64];
+
+    let mut group = c.benchmark_group("hash_entity");
+    for (name, hash_fn) in [
+        ("hmac_sha512", HashFunction::HmacSha512),
+        ("blake2b", HashFunction::Blake2b),
+        ("sha256", HashFunction::Sha256),
+        ("blake3", HashFunction::Blake3),
+    ] {
+        group.bench_function(name, |b| {
+            b.iter(|| hash_entity(black_box(&pretty), &parent_entropy, hash_fn).unwrap())
+        });
+    }
+    group.finish();
+
+    // Argon2id is deliberately slow; a handful of samples is enough
+    let mut group = c.benchmark_group("hash_entity_slow");
+    group.sample_size(10);
+    group.bench_function("argon2id", |b| {
+        b.iter(|| hash_entity(black_box(&pretty), &parent_entropy, HashFunction::Argon2id).unwrap())
+    });
+    group.finish();
+}
+
+fn bench_full_derivation(c: &mut Criterion) {
+    let keychain = Keychain::from_mnemonic(TEST_MNEMONIC).unwrap();
+    let entity = KeyDerivation::from_json(ENTITY_JSON).unwrap();
+    let parent_entropy = keychain.master_key().to_bytes();
+
+    c.bench_function("derive_key_from_entity", |b| {
+        b.iter(|| derive_key_from_entity(&keychain, black_box(&entity), &parent_entropy).unwrap())
+    });
+}
+
+criterion_group!(
+    benches,
+    bench_keychain_path,
+    bench_canonicalization,
+    bench_hash_entity,
+    bench_full_derivation
+);
+criterion_main!(benches);
@@ -0,0 +1,26 @@
+//! Build script
+//!
+//! - `ffi`: regenerates the C header for `src/ffi.rs` at
+//!   `include/bip_keychain.h` (configured by `cbindgen.toml`)
+//! - `uniffi`: generates the scaffolding for `src/bip_keychain.udl`
+
+fn main() {
+    println!("cargo:rerun-if-changed=build.rs");
+
+    #[cfg(feature = "ffi")]
+    generate_header();
+
+    #[cfg(feature = "uniffi")]
+    uniffi::generate_scaffolding("src/bip_keychain.udl").expect("invalid UniFFI interface");
+}
+
+#[cfg(feature = "ffi")]
+fn generate_header() {
+    println!("cargo:rerun-if-changed=src/ffi.rs");
+    println!("cargo:rerun-if-changed=cbindgen.toml");
+
+    let crate_dir = std::env::var("CARGO_MANIFEST_DIR").expect("set by cargo");
+    cbindgen::generate(&crate_dir)
+        .expect("failed to generate C bindings")
+        .write_to_file(format!("{}/include/bip_keychain.h", crate_dir));
+}
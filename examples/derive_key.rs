@@ -55,7 +55,10 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
     // Extract Ed25519 seed
     println!("Step 4: Extracting Ed25519 seed...");
     let ed25519_seed = derived_key.to_seed();
-    println!("✓ Ed25519 seed (32 bytes): {}\n", hex::encode(ed25519_seed));
+    println!(
+        "✓ Ed25519 seed (32 bytes): {}\n",
+        hex::encode(&ed25519_seed)
+    );
 
     // Show that derivation is deterministic
     println!("Step 5: Verifying determinism...");
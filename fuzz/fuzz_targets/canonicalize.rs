@@ -0,0 +1,17 @@
+//! Entity canonicalization in every mode, strict and lenient
+#![no_main]
+
+use bip_keychain::{canonicalize, Canonicalization};
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|input: &str| {
+    for mode in [
+        Canonicalization::Serde,
+        Canonicalization::Jcs,
+        Canonicalization::Dcbor,
+        Canonicalization::None,
+    ] {
+        let _ = canonicalize(input, mode, true);
+        let _ = canonicalize(input, mode, false);
+    }
+});
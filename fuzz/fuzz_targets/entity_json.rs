@@ -0,0 +1,12 @@
+//! Entity files, as read by `derive` and from manifests
+#![no_main]
+
+use bip_keychain::KeyDerivation;
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|json: &str| {
+    if let Ok(key_derivation) = KeyDerivation::from_json(json) {
+        let _ = key_derivation.canonical_entity();
+        let _ = key_derivation.to_ur_string(false);
+    }
+});
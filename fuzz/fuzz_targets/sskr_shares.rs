@@ -0,0 +1,13 @@
+//! SSKR shares, as read by `verify-shares` and seed recovery
+#![no_main]
+
+use bip_keychain::sskr;
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|shares: Vec<Vec<u8>>| {
+    for share in &shares {
+        let _ = sskr::inspect_share(share);
+    }
+    let _ = sskr::verify_shares(&shares);
+    let _ = sskr::combine_shares(&shares);
+});
@@ -0,0 +1,16 @@
+//! UR strings and the dCBOR they carry, as scanned from QR codes
+#![no_main]
+
+use bip_keychain::{dcbor, ur, KeyDerivation};
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: &[u8]| {
+    // Arbitrary text rarely passes the Bytewords checksum, so also wrap the
+    // input in a valid UR to reach the CBOR decoders
+    if let Ok(text) = std::str::from_utf8(data) {
+        let _ = ur::decode(text);
+        let _ = KeyDerivation::from_ur_string(text);
+    }
+    let _ = dcbor::decode_json(data);
+    let _ = KeyDerivation::from_ur_string(&ur::encode("crypto-entity", data));
+});
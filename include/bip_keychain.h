@@ -0,0 +1,83 @@
+#ifndef BIP_KEYCHAIN_H
+#define BIP_KEYCHAIN_H
+
+/* Generated by cbindgen from src/ffi.rs. Do not edit. */
+
+#include <stddef.h>
+#include <stdint.h>
+
+// Result code returned by every fallible FFI function
+typedef enum {
+  // Success
+  BIPKC_STATUS_OK = 0,
+  // A required pointer argument was NULL
+  BIPKC_STATUS_NULL_POINTER = 1,
+  // A string argument was not valid UTF-8
+  BIPKC_STATUS_INVALID_UTF8 = 2,
+  // The seed phrase could not be parsed
+  BIPKC_STATUS_INVALID_SEED = 3,
+  // The entity JSON could not be parsed
+  BIPKC_STATUS_INVALID_ENTITY = 4,
+  // Hashing or BIP-32 derivation failed
+  BIPKC_STATUS_DERIVATION = 5,
+  // Unknown output format, or formatting failed
+  BIPKC_STATUS_FORMAT = 6,
+  // Any other library error
+  BIPKC_STATUS_INTERNAL = 98,
+  // A Rust panic was caught at the FFI boundary
+  BIPKC_STATUS_PANIC = 99,
+} BipkcStatus;
+
+// A derived key together with the entity it was derived from
+//
+// Opaque to C. Free with [`bipkc_key_free`].
+typedef struct BipkcKey BipkcKey;
+
+// Derive the key for an entity from a BIP-39 mnemonic
+//
+// `parent_entropy` may be NULL to use the CLI's default parent entropy.
+// On success `*out_key` receives a key to release with [`bipkc_key_free`].
+//
+// # Safety
+//
+// `mnemonic` and `entity_json` must be NULL or NUL-terminated strings.
+// `parent_entropy` must be NULL or point to `parent_entropy_len` readable
+// bytes. `out_key` must be NULL or valid for writes.
+BipkcStatus bipkc_derive(const char *mnemonic,
+                         const char *entity_json,
+                         const uint8_t *parent_entropy,
+                         size_t parent_entropy_len,
+                         BipkcKey **out_key);
+
+// Format a derived key (`seed`, `public-key`, `private-key`, `ssh`, `gpg`, or `json`)
+//
+// On success `*out_string` receives a NUL-terminated string to release
+// with [`bipkc_string_free`].
+//
+// # Safety
+//
+// `key` must be NULL or a live key from [`bipkc_derive`]. `format` must be
+// NULL or a NUL-terminated string. `out_string` must be NULL or valid for
+// writes.
+BipkcStatus bipkc_format(const BipkcKey *key, const char *format, char **out_string);
+
+// Free a key returned by [`bipkc_derive`]; NULL is ignored
+//
+// # Safety
+//
+// `key` must be NULL or a key from [`bipkc_derive`] not already freed.
+void bipkc_key_free(BipkcKey *key);
+
+// Wipe and free a string returned by [`bipkc_format`]; NULL is ignored
+//
+// # Safety
+//
+// `string` must be NULL or a string from [`bipkc_format`] not already freed.
+void bipkc_string_free(char *string);
+
+// Message for the last failed call on this thread, or NULL
+//
+// The pointer is valid until the next `bipkc_*` call on the same thread.
+const char *bipkc_last_error(void);
+
+#endif  /* BIP_KEYCHAIN_H */
@@ -0,0 +1,93 @@
+//! ACME account keys as JWKs
+//!
+//! An ACME (RFC 8555) account is identified by its key, so deriving the
+//! account key from an entity makes the account recoverable from the seed.
+//! The key is exported as an OKP JSON Web Key (RFC 8037) for ACME clients
+//! that import account keys, together with its RFC 7638 thumbprint, which
+//! appears in every challenge's key authorization.
+
+use crate::output::Ed25519Keypair;
+use sha2::{Digest, Sha256};
+use zeroize::Zeroizing;
+
+pub(crate) fn base64url(data: &[u8]) -> String {
+    base64::Engine::encode(&base64::engine::general_purpose::URL_SAFE_NO_PAD, data)
+}
+
+/// An Ed25519 ACME account key
+pub struct AcmeAccountKey<'a> {
+    keypair: &'a Ed25519Keypair,
+}
+
+impl<'a> AcmeAccountKey<'a> {
+    /// Use `keypair` as the account key
+    pub fn new(keypair: &'a Ed25519Keypair) -> Self {
+        Self { keypair }
+    }
+
+    /// Public JWK, with members in the RFC 7638 canonical order
+    pub fn jwk(&self) -> String {
+        format!(
+            r#"{{"crv":"Ed25519","kty":"OKP","x":"{}"}}"#,
+            base64url(&self.keypair.public_key_bytes())
+        )
+    }
+
+    /// Private JWK (adds the `d` member), for importing into an ACME client
+    pub fn private_jwk(&self) -> Zeroizing<String> {
+        let d = Zeroizing::new(base64url(&*self.keypair.private_key_bytes()));
+        Zeroizing::new(format!(
+            r#"{{"crv":"Ed25519","d":"{}","kty":"OKP","x":"{}"}}"#,
+            *d,
+            base64url(&self.keypair.public_key_bytes())
+        ))
+    }
+
+    /// JWK thumbprint (RFC 7638, SHA-256), base64url-encoded
+    pub fn thumbprint(&self) -> String {
+        base64url(&Sha256::digest(self.jwk().as_bytes()))
+    }
+
+    /// Key authorization for a challenge token (RFC 8555 section 8.1)
+    pub fn key_authorization(&self, token: &str) -> String {
+        format!("{}.{}", token, self.thumbprint())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// RFC 8037 Appendix A.1 key
+    fn rfc8037_keypair() -> Ed25519Keypair {
+        let d = base64::Engine::decode(
+            &base64::engine::general_purpose::URL_SAFE_NO_PAD,
+            "nWGxne_9WmC6hEr0kuwsxERJxWl7MmkZcDusAxyuf2A",
+        )
+        .unwrap();
+        Ed25519Keypair::from_seed(d.try_into().unwrap())
+    }
+
+    #[test]
+    fn test_rfc8037_vectors() {
+        let keypair = rfc8037_keypair();
+        let key = AcmeAccountKey::new(&keypair);
+        assert_eq!(
+            key.jwk(),
+            r#"{"crv":"Ed25519","kty":"OKP","x":"11qYAYKxCrfVS_7TyWQHOg7hcvPapiMlrwIaaPcHURo"}"#
+        );
+        // RFC 8037 Appendix A.3
+        assert_eq!(
+            key.thumbprint(),
+            "kPrK_qmxVWaYVA9wwBF6Iuo3vVzz7TxHCTwXBygrS4k"
+        );
+        assert_eq!(
+            *key.private_jwk(),
+            r#"{"crv":"Ed25519","d":"nWGxne_9WmC6hEr0kuwsxERJxWl7MmkZcDusAxyuf2A","kty":"OKP","x":"11qYAYKxCrfVS_7TyWQHOg7hcvPapiMlrwIaaPcHURo"}"#
+        );
+        assert_eq!(
+            key.key_authorization("evaGxfADs6pSRb2LAv9IZf17Dt3juxGJ-PCt92wr-oA"),
+            "evaGxfADs6pSRb2LAv9IZf17Dt3juxGJ-PCt92wr-oA.kPrK_qmxVWaYVA9wwBF6Iuo3vVzz7TxHCTwXBygrS4k"
+        );
+    }
+}
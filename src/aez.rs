@@ -0,0 +1,272 @@
+//! AEZ v5 authenticated encryption for messages under 32 bytes
+//!
+//! LND's aezeed format enciphers its 19-byte seed with AEZ (Hoang, Krovetz,
+//! Rogaway), so only the AEZ-tiny path is implemented: the tweakable block
+//! cipher, AEZ-hash over the nonce and associated data, and the Feistel
+//! network for short strings. Messages that expand to 32 bytes or more are
+//! rejected.
+
+use crate::{
+    error::{BipKeychainError, Result},
+    hash::blake2b_into,
+};
+use aes::hazmat::cipher_round;
+use zeroize::{Zeroize, Zeroizing};
+
+type Block = [u8; 16];
+
+fn aez_error(message: &str) -> BipKeychainError {
+    BipKeychainError::EncryptionError(message.to_string())
+}
+
+fn xor(a: &Block, b: &Block) -> Block {
+    std::array::from_fn(|i| a[i] ^ b[i])
+}
+
+/// Multiply by x in GF(2^128) with the AEZ (big-endian) convention
+fn double(block: &Block) -> Block {
+    let value = u128::from_be_bytes(*block);
+    let carry = if value >> 127 != 0 { 0x87 } else { 0 };
+    ((value << 1) ^ carry).to_be_bytes()
+}
+
+/// Multiply by a small integer in GF(2^128)
+fn times(mut factor: u32, block: &Block) -> Block {
+    let mut result = [0u8; 16];
+    let mut power = *block;
+    while factor != 0 {
+        if factor & 1 != 0 {
+            result = xor(&result, &power);
+        }
+        power = double(&power);
+        factor >>= 1;
+    }
+    result
+}
+
+/// The three 128-bit subkeys I, J, L
+struct Aez {
+    i: Block,
+    j: Block,
+    l: Block,
+}
+
+impl Drop for Aez {
+    fn drop(&mut self) {
+        self.i.zeroize();
+        self.j.zeroize();
+        self.l.zeroize();
+    }
+}
+
+impl Aez {
+    /// Extract the subkeys: a 48-byte key is used as is, any other length
+    /// is hashed with BLAKE2b-384
+    fn new(key: &[u8]) -> Result<Self> {
+        let mut extracted = Zeroizing::new([0u8; 48]);
+        if key.len() == 48 {
+            extracted.copy_from_slice(key);
+        } else {
+            blake2b_into(key, &mut extracted[..])?;
+        }
+        let part = |n: usize| -> Block { extracted[16 * n..16 * (n + 1)].try_into().unwrap() };
+        Ok(Self {
+            i: part(0),
+            j: part(1),
+            l: part(2),
+        })
+    }
+
+    /// Four full AES rounds keyed J, I, L, 0
+    fn aes4(&self, input: &Block) -> Block {
+        let mut state = aes::Block::from(*input);
+        for key in [&self.j, &self.i, &self.l, &[0u8; 16]] {
+            cipher_round(&mut state, &aes::Block::from(*key));
+        }
+        state.into()
+    }
+
+    /// The tweakable block cipher E_K^{j,i} for j >= 0
+    fn e(&self, j: u32, i: u32, input: &Block) -> Block {
+        let mut i_term = self.i;
+        for _ in 0..i.div_ceil(8) {
+            i_term = double(&i_term);
+        }
+        let delta = xor(&xor(&times(j, &self.j), &times(i % 8, &self.l)), &i_term);
+        self.aes4(&xor(input, &delta))
+    }
+
+    /// AEZ-hash of the tweak vector (tau in bits, nonce, associated data)
+    fn hash(&self, tau_bits: u32, nonce: &[u8], ad: &[&[u8]]) -> Block {
+        let mut tau = [0u8; 16];
+        tau[12..].copy_from_slice(&tau_bits.to_be_bytes());
+        let mut delta = self.e(3, 1, &tau);
+
+        for (j, component) in (4..).zip(std::iter::once(nonce).chain(ad.iter().copied())) {
+            let mut blocks = component.chunks_exact(16);
+            for (i, block) in (1..).zip(blocks.by_ref()) {
+                delta = xor(&delta, &self.e(j, i, block.try_into().unwrap()));
+            }
+            let rest = blocks.remainder();
+            if !rest.is_empty() || component.is_empty() {
+                delta = xor(&delta, &self.e(j, 0, &pad(rest)));
+            }
+        }
+        delta
+    }
+
+    /// AEZ-tiny: a Feistel network over the two halves of a short string
+    fn tiny(&self, delta: &Block, input: &[u8], decipher: bool) -> Vec<u8> {
+        let bits = 8 * input.len();
+        let n = bits / 2;
+        let (rounds, j) = match input.len() {
+            1 => (24, 7),
+            2 => (16, 7),
+            len if len < 16 => (10, 7),
+            _ => (8, 6),
+        };
+
+        let mut data = input.to_vec();
+        if decipher {
+            self.tiny_flip(delta, &mut data);
+        }
+        let mut left = read_bits(&data, 0, n);
+        let mut right = read_bits(&data, n, n);
+        let round = |i: u32, left: u128, right: u128| -> u128 {
+            let mut block = ((right << (128 - n)) | (1 << (127 - n))).to_be_bytes();
+            block = xor(&block, delta);
+            block[15] ^= i as u8;
+            left ^ (u128::from_be_bytes(self.e(0, j, &block)) >> (128 - n))
+        };
+        let order: Vec<u32> = if decipher {
+            (0..rounds).rev().collect()
+        } else {
+            (0..rounds).collect()
+        };
+        for i in order {
+            (left, right) = (right, round(i, left, right));
+        }
+
+        let mut output = vec![0u8; input.len()];
+        write_bits(&mut output, 0, n, right);
+        write_bits(&mut output, n, n, left);
+        if !decipher {
+            self.tiny_flip(delta, &mut output);
+        }
+        output
+    }
+
+    /// The extra step for strings under 128 bits, which mixes the first bit
+    fn tiny_flip(&self, delta: &Block, data: &mut [u8]) {
+        if data.len() < 16 {
+            let mut block = [0u8; 16];
+            block[..data.len()].copy_from_slice(data);
+            block[0] |= 0x80;
+            let mask = self.e(0, 3, &xor(&block, delta));
+            data[0] ^= mask[0] & 0x80;
+        }
+    }
+}
+
+/// `data` followed by a 1 bit and zeros, as one block
+fn pad(data: &[u8]) -> Block {
+    let mut block = [0u8; 16];
+    block[..data.len()].copy_from_slice(data);
+    block[data.len()] = 0x80;
+    block
+}
+
+/// `len` (at most 128) bits of `data` from bit `start`, most significant first
+fn read_bits(data: &[u8], start: usize, len: usize) -> u128 {
+    (start..start + len).fold(0, |value, bit| {
+        value << 1 | u128::from(data[bit / 8] >> (7 - bit % 8) & 1)
+    })
+}
+
+fn write_bits(data: &mut [u8], start: usize, len: usize, value: u128) {
+    for (k, bit) in (start..start + len).enumerate() {
+        if value >> (len - 1 - k) & 1 != 0 {
+            data[bit / 8] |= 0x80 >> (bit % 8);
+        }
+    }
+}
+
+/// Encrypt `plaintext` with `tau` bytes of authentication
+#[cfg(test)]
+pub(crate) fn encrypt(
+    key: &[u8],
+    nonce: &[u8],
+    ad: &[&[u8]],
+    tau: usize,
+    plaintext: &[u8],
+) -> Result<Vec<u8>> {
+    if plaintext.is_empty() || plaintext.len() + tau >= 32 {
+        return Err(aez_error(
+            "AEZ messages must be 1 to 31 bytes with their tag",
+        ));
+    }
+    let aez = Aez::new(key)?;
+    let delta = aez.hash(8 * tau as u32, nonce, ad);
+    let mut input = Zeroizing::new(plaintext.to_vec());
+    input.resize(plaintext.len() + tau, 0);
+    Ok(aez.tiny(&delta, &input, false))
+}
+
+/// Decrypt and authenticate a ciphertext from [`encrypt`]
+pub(crate) fn decrypt(
+    key: &[u8],
+    nonce: &[u8],
+    ad: &[&[u8]],
+    tau: usize,
+    ciphertext: &[u8],
+) -> Result<Zeroizing<Vec<u8>>> {
+    if ciphertext.len() <= tau || ciphertext.len() >= 32 {
+        return Err(aez_error(
+            "AEZ messages must be 1 to 31 bytes with their tag",
+        ));
+    }
+    let aez = Aez::new(key)?;
+    let delta = aez.hash(8 * tau as u32, nonce, ad);
+    let mut output = Zeroizing::new(aez.tiny(&delta, ciphertext, true));
+    let tag = output.split_off(ciphertext.len() - tau);
+    if tag.iter().fold(0, |acc, byte| acc | byte) != 0 {
+        return Err(aez_error("AEZ authentication failed"));
+    }
+    Ok(output)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_gf_multiplication() {
+        let mut block = [0u8; 16];
+        block[0] = 0x80;
+        let doubled = double(&block);
+        assert_eq!(doubled[15], 0x87);
+        assert_eq!(times(3, &block), xor(&block, &doubled));
+    }
+
+    #[test]
+    fn test_round_trip_and_authentication() {
+        let key = [9u8; 32];
+        for len in [1, 2, 7, 15, 16, 19, 27] {
+            let plaintext: Vec<u8> = (0..len as u8).collect();
+            let ciphertext = encrypt(&key, b"", &[b"ad"], 4, &plaintext).unwrap();
+            assert_eq!(ciphertext.len(), len + 4);
+            assert_eq!(
+                *decrypt(&key, b"", &[b"ad"], 4, &ciphertext).unwrap(),
+                plaintext
+            );
+
+            // Odd lengths split mid-byte; every bit must still be covered
+            let mut tampered = ciphertext.clone();
+            tampered[len + 3] ^= 1;
+            assert!(decrypt(&key, b"", &[b"ad"], 4, &tampered).is_err());
+            assert!(decrypt(&key, b"", &[b"ae"], 4, &ciphertext).is_err());
+            assert!(decrypt(&[8u8; 32], b"", &[b"ad"], 4, &ciphertext).is_err());
+        }
+        assert!(encrypt(&key, b"", &[], 4, &[0u8; 28]).is_err());
+    }
+}
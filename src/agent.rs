@@ -0,0 +1,455 @@
+//! SSH agent serving entity-derived keys
+//!
+//! Implements the subset of the SSH agent protocol
+//! (draft-miller-ssh-agent) needed for authentication:
+//! - `SSH_AGENTC_REQUEST_IDENTITIES` lists the Ed25519 key of every loaded entity
+//! - `SSH_AGENTC_SIGN_REQUEST` derives the matching key, signs, and drops it
+//!
+//! Everything else (adding/removing keys, locking, extensions) is answered
+//! with `SSH_AGENT_FAILURE`. Private keys are derived on demand for each sign
+//! request and are never written to disk. An entity past its
+//! `metadata.not_after` is neither listed nor used, unless
+//! [`Agent::allow_expired`] is set, so a long-running agent stops serving
+//! keys as they expire.
+//!
+//! [`add_identity`] is the client side: it pushes a single derived key into
+//! an already-running agent (e.g. OpenSSH's `ssh-agent`), like `ssh-add -t`.
+
+use crate::{
+    bip32_wrapper::Keychain,
+    derivation::derive_key_from_entity,
+    entity::KeyDerivation,
+    error::{BipKeychainError, Result},
+    output::Ed25519Keypair,
+    ssh_wire::{ed25519_key_blob, put_string, WireReader, ED25519_KEY_TYPE},
+};
+use ed25519_dalek::Signer;
+use std::io::{Read, Write};
+use zeroize::Zeroizing;
+
+// Message numbers from draft-miller-ssh-agent, section 5.1
+const SSH_AGENT_FAILURE: u8 = 5;
+const SSH_AGENT_SUCCESS: u8 = 6;
+const SSH_AGENTC_REQUEST_IDENTITIES: u8 = 11;
+const SSH_AGENT_IDENTITIES_ANSWER: u8 = 12;
+const SSH_AGENTC_SIGN_REQUEST: u8 = 13;
+const SSH_AGENT_SIGN_RESPONSE: u8 = 14;
+const SSH_AGENTC_ADD_ID_CONSTRAINED: u8 = 25;
+const SSH_AGENT_CONSTRAIN_LIFETIME: u8 = 1;
+
+/// Upper bound on a single agent message, matching OpenSSH's limit
+const MAX_MESSAGE_LEN: usize = 256 * 1024;
+
+/// An entity whose key the agent can present and sign with
+struct AgentIdentity {
+    key_derivation: KeyDerivation,
+    comment: String,
+    /// SSH wire-format public key blob, used to match sign requests
+    key_blob: Vec<u8>,
+}
+
+/// SSH agent holding a keychain and a set of entities
+pub struct Agent {
+    keychain: Keychain,
+    parent_entropy: Zeroizing<Vec<u8>>,
+    identities: Vec<AgentIdentity>,
+    allow_expired: bool,
+}
+
+impl Agent {
+    /// Create an agent with no identities
+    pub fn new(keychain: Keychain, parent_entropy: &[u8]) -> Self {
+        Self {
+            keychain,
+            parent_entropy: Zeroizing::new(parent_entropy.to_vec()),
+            identities: Vec::new(),
+            allow_expired: false,
+        }
+    }
+
+    /// Keep listing and signing with entities past their `not_after`
+    pub fn allow_expired(&mut self, allow: bool) {
+        self.allow_expired = allow;
+    }
+
+    /// Add an entity to the agent under the given comment
+    ///
+    /// The key is derived once to compute its public key, then dropped.
+    pub fn add_entity(&mut self, key_derivation: KeyDerivation, comment: String) -> Result<()> {
+        let keypair = self.derive_keypair(&key_derivation)?;
+        let key_blob = ed25519_key_blob(&keypair.public_key_bytes());
+
+        self.identities.push(AgentIdentity {
+            key_derivation,
+            comment,
+            key_blob,
+        });
+
+        Ok(())
+    }
+
+    /// Number of entities loaded
+    pub fn len(&self) -> usize {
+        self.identities.len()
+    }
+
+    /// Whether no entities are loaded
+    pub fn is_empty(&self) -> bool {
+        self.identities.is_empty()
+    }
+
+    /// Handle one agent request (without the length prefix) and return the
+    /// response body
+    pub fn handle_message(&self, request: &[u8]) -> Vec<u8> {
+        let response = match request.split_first() {
+            Some((&SSH_AGENTC_REQUEST_IDENTITIES, _)) => Some(self.identities_answer()),
+            Some((&SSH_AGENTC_SIGN_REQUEST, body)) => self.sign_response(body),
+            _ => None,
+        };
+
+        response.unwrap_or_else(|| vec![SSH_AGENT_FAILURE])
+    }
+
+    /// Serve requests on a single client connection until it closes
+    pub fn serve_connection<S: Read + Write>(&self, stream: &mut S) -> Result<()> {
+        loop {
+            let mut len_bytes = [0u8; 4];
+            match stream.read_exact(&mut len_bytes) {
+                Ok(()) => {}
+                Err(e) if e.kind() == std::io::ErrorKind::UnexpectedEof => return Ok(()),
+                Err(e) => return Err(e.into()),
+            }
+
+            let len = u32::from_be_bytes(len_bytes) as usize;
+            if len == 0 || len > MAX_MESSAGE_LEN {
+                return Err(BipKeychainError::AgentError(format!(
+                    "Message length {} out of range",
+                    len
+                )));
+            }
+
+            let mut request = vec![0u8; len];
+            stream.read_exact(&mut request)?;
+
+            let response = self.handle_message(&request);
+            stream.write_all(&(response.len() as u32).to_be_bytes())?;
+            stream.write_all(&response)?;
+            stream.flush()?;
+        }
+    }
+
+    /// Accept connections on a unix socket and serve each on its own thread
+    ///
+    /// Runs until the listener fails. Per-connection errors are reported on
+    /// stderr and do not stop the agent.
+    #[cfg(unix)]
+    pub fn serve(&self, listener: std::os::unix::net::UnixListener) -> Result<()> {
+        std::thread::scope(|scope| {
+            for stream in listener.incoming() {
+                let mut stream = stream?;
+                scope.spawn(move || {
+                    if let Err(e) = self.serve_connection(&mut stream) {
+                        eprintln!("agent: connection error: {}", e);
+                    }
+                });
+            }
+            Ok(())
+        })
+    }
+
+    /// Identities the agent currently serves
+    fn active_identities(&self) -> impl Iterator<Item = &AgentIdentity> {
+        let now = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map_or(0, |elapsed| elapsed.as_secs());
+        self.identities.iter().filter(move |identity| {
+            self.allow_expired
+                || identity
+                    .key_derivation
+                    .is_expired_at(now)
+                    .is_ok_and(|expired| !expired)
+        })
+    }
+
+    fn identities_answer(&self) -> Vec<u8> {
+        let identities: Vec<&AgentIdentity> = self.active_identities().collect();
+        let mut response = vec![SSH_AGENT_IDENTITIES_ANSWER];
+        response.extend_from_slice(&(identities.len() as u32).to_be_bytes());
+
+        for identity in identities {
+            put_string(&mut response, &identity.key_blob);
+            put_string(&mut response, identity.comment.as_bytes());
+        }
+
+        response
+    }
+
+    fn sign_response(&self, body: &[u8]) -> Option<Vec<u8>> {
+        let mut reader = WireReader::new(body);
+        let key_blob = reader.string()?;
+        let data = reader.string()?;
+        // The flags field only selects RSA hash variants; Ed25519 ignores it.
+        let _flags = reader.u32()?;
+
+        let identity = self.active_identities().find(|i| i.key_blob == key_blob)?;
+        let keypair = self.derive_keypair(&identity.key_derivation).ok()?;
+        let signature = keypair.signing_key().sign(data);
+
+        let mut signature_blob = Vec::new();
+        put_string(&mut signature_blob, ED25519_KEY_TYPE);
+        put_string(&mut signature_blob, &signature.to_bytes());
+
+        let mut response = vec![SSH_AGENT_SIGN_RESPONSE];
+        put_string(&mut response, &signature_blob);
+        Some(response)
+    }
+
+    fn derive_keypair(&self, key_derivation: &KeyDerivation) -> Result<Ed25519Keypair> {
+        let derived = derive_key_from_entity(&self.keychain, key_derivation, &self.parent_entropy)?;
+        Ok(Ed25519Keypair::from_derived_key(&derived))
+    }
+}
+
+/// Add an Ed25519 key to a running SSH agent with a lifetime constraint
+///
+/// Sends `SSH_AGENTC_ADD_ID_CONSTRAINED` over `stream`; the agent forgets the
+/// key after `lifetime_secs` seconds. The request buffer holding the private
+/// key is zeroized once sent.
+///
+/// # Example
+///
+/// ```ignore
+/// let mut stream = UnixStream::connect(env::var("SSH_AUTH_SOCK")?)?;
+/// add_identity(&mut stream, &keypair, "github", 3600)?;
+/// ```
+pub fn add_identity<S: Read + Write>(
+    stream: &mut S,
+    keypair: &Ed25519Keypair,
+    comment: &str,
+    lifetime_secs: u32,
+) -> Result<()> {
+    let public_key = keypair.public_key_bytes();
+
+    // OpenSSH encodes Ed25519 private keys as seed || public key
+    let mut private_key = Zeroizing::new(Vec::with_capacity(64));
+    private_key.extend_from_slice(&*keypair.private_key_bytes());
+    private_key.extend_from_slice(&public_key);
+
+    let mut request = Zeroizing::new(vec![SSH_AGENTC_ADD_ID_CONSTRAINED]);
+    put_string(&mut request, ED25519_KEY_TYPE);
+    put_string(&mut request, &public_key);
+    put_string(&mut request, &private_key);
+    put_string(&mut request, comment.as_bytes());
+    request.push(SSH_AGENT_CONSTRAIN_LIFETIME);
+    request.extend_from_slice(&lifetime_secs.to_be_bytes());
+
+    stream.write_all(&(request.len() as u32).to_be_bytes())?;
+    stream.write_all(&request)?;
+    stream.flush()?;
+
+    let mut len_bytes = [0u8; 4];
+    stream.read_exact(&mut len_bytes)?;
+    let len = u32::from_be_bytes(len_bytes) as usize;
+    if len == 0 || len > MAX_MESSAGE_LEN {
+        return Err(BipKeychainError::AgentError(format!(
+            "Reply length {} out of range",
+            len
+        )));
+    }
+
+    let mut reply = vec![0u8; len];
+    stream.read_exact(&mut reply)?;
+
+    match reply[0] {
+        SSH_AGENT_SUCCESS => Ok(()),
+        _ => Err(BipKeychainError::AgentError(
+            "Agent refused the key (it may not support lifetime constraints)".to_string(),
+        )),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ed25519_dalek::{Signature, Verifier, VerifyingKey};
+
+    const TEST_MNEMONIC: &str = "abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon about";
+
+    fn test_agent() -> Agent {
+        let keychain = Keychain::from_mnemonic(TEST_MNEMONIC).unwrap();
+        let mut agent = Agent::new(keychain, b"test_entropy");
+
+        let entity_json = r#"{
+            "schema_type": "dns",
+            "entity": {"name": "prod.example.com"},
+            "derivation_config": {"hash_function": "hmac_sha512", "hardened": true}
+        }"#;
+        agent
+            .add_entity(
+                KeyDerivation::from_json(entity_json).unwrap(),
+                "prod".to_string(),
+            )
+            .unwrap();
+        agent
+    }
+
+    fn sign_request(key_blob: &[u8], data: &[u8]) -> Vec<u8> {
+        let mut request = vec![SSH_AGENTC_SIGN_REQUEST];
+        put_string(&mut request, key_blob);
+        put_string(&mut request, data);
+        request.extend_from_slice(&0u32.to_be_bytes());
+        request
+    }
+
+    #[test]
+    fn test_request_identities() {
+        let agent = test_agent();
+        let response = agent.handle_message(&[SSH_AGENTC_REQUEST_IDENTITIES]);
+
+        assert_eq!(response[0], SSH_AGENT_IDENTITIES_ANSWER);
+        let mut reader = WireReader::new(&response[1..]);
+        assert_eq!(reader.u32(), Some(1));
+
+        let key_blob = reader.string().unwrap();
+        assert_eq!(key_blob, agent.identities[0].key_blob.as_slice());
+        assert_eq!(reader.string(), Some(&b"prod"[..]));
+    }
+
+    #[test]
+    fn test_sign_request_produces_valid_signature() {
+        let agent = test_agent();
+        let key_blob = agent.identities[0].key_blob.clone();
+        let data = b"session-id and userauth request";
+
+        let response = agent.handle_message(&sign_request(&key_blob, data));
+        assert_eq!(response[0], SSH_AGENT_SIGN_RESPONSE);
+
+        let mut reader = WireReader::new(&response[1..]);
+        let mut signature_blob = WireReader::new(reader.string().unwrap());
+        assert_eq!(signature_blob.string(), Some(ED25519_KEY_TYPE));
+        let signature = Signature::from_slice(signature_blob.string().unwrap()).unwrap();
+
+        let mut key_reader = WireReader::new(&key_blob);
+        key_reader.string().unwrap();
+        let public_key: [u8; 32] = key_reader.string().unwrap().try_into().unwrap();
+        let verifying_key = VerifyingKey::from_bytes(&public_key).unwrap();
+        assert!(verifying_key.verify(data, &signature).is_ok());
+    }
+
+    #[test]
+    fn test_unknown_key_and_message_fail() {
+        let agent = test_agent();
+
+        let unknown_blob = ed25519_key_blob(&[7u8; 32]);
+        let response = agent.handle_message(&sign_request(&unknown_blob, b"data"));
+        assert_eq!(response, vec![SSH_AGENT_FAILURE]);
+
+        // SSH_AGENTC_ADD_IDENTITY is not supported
+        assert_eq!(agent.handle_message(&[17]), vec![SSH_AGENT_FAILURE]);
+        assert_eq!(agent.handle_message(&[]), vec![SSH_AGENT_FAILURE]);
+    }
+
+    #[test]
+    fn test_expired_entity_refused() {
+        let mut agent = test_agent();
+        let entity_json = r#"{
+            "schema_type": "dns",
+            "entity": {"name": "old.example.com"},
+            "derivation_config": {"hash_function": "hmac_sha512", "hardened": true},
+            "metadata": {"not_after": "2020-01-01"}
+        }"#;
+        agent
+            .add_entity(
+                KeyDerivation::from_json(entity_json).unwrap(),
+                "old".to_string(),
+            )
+            .unwrap();
+        let key_blob = agent.identities[1].key_blob.clone();
+
+        let response = agent.handle_message(&[SSH_AGENTC_REQUEST_IDENTITIES]);
+        assert_eq!(WireReader::new(&response[1..]).u32(), Some(1));
+        let response = agent.handle_message(&sign_request(&key_blob, b"data"));
+        assert_eq!(response, vec![SSH_AGENT_FAILURE]);
+
+        agent.allow_expired(true);
+        let response = agent.handle_message(&[SSH_AGENTC_REQUEST_IDENTITIES]);
+        assert_eq!(WireReader::new(&response[1..]).u32(), Some(2));
+        let response = agent.handle_message(&sign_request(&key_blob, b"data"));
+        assert_eq!(response[0], SSH_AGENT_SIGN_RESPONSE);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_serve_connection_framing() {
+        use std::os::unix::net::UnixStream;
+
+        let agent = test_agent();
+        let (mut client, mut server) = UnixStream::pair().unwrap();
+
+        std::thread::scope(|scope| {
+            scope.spawn(|| agent.serve_connection(&mut server).unwrap());
+
+            client.write_all(&1u32.to_be_bytes()).unwrap();
+            client.write_all(&[SSH_AGENTC_REQUEST_IDENTITIES]).unwrap();
+
+            let mut len_bytes = [0u8; 4];
+            client.read_exact(&mut len_bytes).unwrap();
+            let mut response = vec![0u8; u32::from_be_bytes(len_bytes) as usize];
+            client.read_exact(&mut response).unwrap();
+            assert_eq!(response[0], SSH_AGENT_IDENTITIES_ANSWER);
+
+            client.shutdown(std::net::Shutdown::Write).unwrap();
+        });
+    }
+
+    #[cfg(unix)]
+    fn add_identity_with_reply(reply: u8) -> (Result<()>, Vec<u8>) {
+        use std::os::unix::net::UnixStream;
+
+        let agent = test_agent();
+        let keypair = agent
+            .derive_keypair(&agent.identities[0].key_derivation)
+            .unwrap();
+        let (mut client, mut server) = UnixStream::pair().unwrap();
+
+        std::thread::scope(|scope| {
+            let fake_agent = scope.spawn(move || {
+                let mut len_bytes = [0u8; 4];
+                server.read_exact(&mut len_bytes).unwrap();
+                let mut request = vec![0u8; u32::from_be_bytes(len_bytes) as usize];
+                server.read_exact(&mut request).unwrap();
+                server.write_all(&1u32.to_be_bytes()).unwrap();
+                server.write_all(&[reply]).unwrap();
+                request
+            });
+
+            let result = add_identity(&mut client, &keypair, "prod", 600);
+            (result, fake_agent.join().unwrap())
+        })
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_add_identity_request_encoding() {
+        let (result, request) = add_identity_with_reply(SSH_AGENT_SUCCESS);
+        assert!(result.is_ok());
+
+        assert_eq!(request[0], SSH_AGENTC_ADD_ID_CONSTRAINED);
+        let mut reader = WireReader::new(&request[1..]);
+        assert_eq!(reader.string(), Some(ED25519_KEY_TYPE));
+        let public_key = reader.string().unwrap();
+        let private_key = reader.string().unwrap();
+        assert_eq!(private_key.len(), 64);
+        assert_eq!(&private_key[32..], public_key);
+        assert_eq!(reader.string(), Some(&b"prod"[..]));
+
+        assert_eq!(reader.data, &[SSH_AGENT_CONSTRAIN_LIFETIME, 0, 0, 2, 88]);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_add_identity_failure_reply() {
+        let (result, _) = add_identity_with_reply(SSH_AGENT_FAILURE);
+        assert!(matches!(result, Err(BipKeychainError::AgentError(_))));
+    }
+}
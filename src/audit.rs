@@ -0,0 +1,272 @@
+//! Hash-chained derivation audit log
+//!
+//! An opt-in, append-only record of key derivations. Each line of the log
+//! is one JSON record:
+//!
+//! ```json
+//! {"timestamp":1760572800,"entity_hash":"9f1c...","path":"m/83696968'/67797668'/1234567'","format":"ssh","prev_hash":"0000...","hash":"5be2..."}
+//! ```
+//!
+//! `hash` is the SHA-256 of the JCS (RFC 8785) encoding of the record
+//! without its `hash` field, and `prev_hash` is the previous record's
+//! `hash` (all zeros for the first record). Editing, removing, or
+//! reordering records breaks the chain. Truncating the end of the log does
+//! not, so keep a copy of [`AuditLog::verify`]'s head hash elsewhere if
+//! that matters.
+//!
+//! Records never contain entity contents or key material.
+
+use crate::{
+    error::{BipKeychainError, Result},
+    jcs,
+};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::fs;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+/// `prev_hash` of the first record
+pub const GENESIS_HASH: &str = "0000000000000000000000000000000000000000000000000000000000000000";
+
+/// One derivation in the audit log
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+pub struct AuditRecord {
+    /// When the key was derived, in seconds since the Unix epoch
+    pub timestamp: u64,
+
+    /// Entity hash as 128 hex characters
+    pub entity_hash: String,
+
+    /// BIP-32 derivation path (absent for entities derived below a parent)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub path: Option<String>,
+
+    /// What the key was used for, e.g. an output format name or `sign`
+    pub format: String,
+
+    /// `hash` of the previous record
+    pub prev_hash: String,
+
+    /// SHA-256 over this record's other fields
+    pub hash: String,
+}
+
+impl AuditRecord {
+    /// The hash this record should carry
+    pub fn compute_hash(&self) -> String {
+        let mut value = serde_json::to_value(self).expect("audit records serialize to JSON");
+        if let Some(object) = value.as_object_mut() {
+            object.remove("hash");
+        }
+        hex::encode(Sha256::digest(jcs::canonicalize(&value).as_bytes()))
+    }
+}
+
+/// Result of a successful [`AuditLog::verify`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AuditSummary {
+    /// Number of records in the log
+    pub records: usize,
+    /// `hash` of the last record ([`GENESIS_HASH`] for an empty log)
+    pub head: String,
+}
+
+/// Stable ID of an entry in an audit listing (index registry, key
+/// manifest)
+///
+/// The first 16 hex characters of SHA-256 over the lowercase entity digest,
+/// a zero byte, and `qualifier`, which tells apart entries for the same
+/// entity (its path, or its public key). The ID depends only on the entry
+/// itself, so it is the same in every run regardless of listing order.
+pub fn entry_id(entity_digest: &str, qualifier: &str) -> String {
+    let mut hasher = Sha256::new();
+    hasher.update(entity_digest.to_ascii_lowercase().as_bytes());
+    hasher.update([0]);
+    hasher.update(qualifier.as_bytes());
+    hex::encode(&hasher.finalize()[..8])
+}
+
+fn audit_error(message: String) -> BipKeychainError {
+    BipKeychainError::AuditError(message)
+}
+
+/// An audit log file (JSON lines)
+#[derive(Debug, Clone)]
+pub struct AuditLog {
+    path: PathBuf,
+}
+
+impl AuditLog {
+    /// Use the log at `path`; the file is created on first append
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self { path: path.into() }
+    }
+
+    /// Path of the log file
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Parse every record, without checking the chain
+    pub fn records(&self) -> Result<Vec<AuditRecord>> {
+        let contents = match fs::read_to_string(&self.path) {
+            Ok(contents) => contents,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(e) => return Err(e.into()),
+        };
+
+        contents
+            .lines()
+            .enumerate()
+            .filter(|(_, line)| !line.trim().is_empty())
+            .map(|(i, line)| {
+                serde_json::from_str(line).map_err(|e| {
+                    audit_error(format!("Line {} is not a valid record: {}", i + 1, e))
+                })
+            })
+            .collect()
+    }
+
+    /// Append a record chained to the last one in the log
+    pub fn append(
+        &self,
+        entity_hash: &[u8; 64],
+        path: Option<String>,
+        format: impl Into<String>,
+        timestamp: u64,
+    ) -> Result<AuditRecord> {
+        let prev_hash = self
+            .records()?
+            .pop()
+            .map_or_else(|| GENESIS_HASH.to_string(), |last| last.hash);
+
+        let mut record = AuditRecord {
+            timestamp,
+            entity_hash: hex::encode(entity_hash),
+            path,
+            format: format.into(),
+            prev_hash,
+            hash: String::new(),
+        };
+        record.hash = record.compute_hash();
+
+        let line = serde_json::to_string(&record)
+            .map_err(|e| audit_error(format!("Failed to serialize record: {}", e)))?;
+
+        let mut options = fs::OpenOptions::new();
+        options.append(true).create(true);
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::OpenOptionsExt;
+            options.mode(0o600);
+        }
+        options
+            .open(&self.path)
+            .and_then(|mut file| writeln!(file, "{}", line))?;
+
+        Ok(record)
+    }
+
+    /// Check every record's hash and link to its predecessor
+    pub fn verify(&self) -> Result<AuditSummary> {
+        let records = self.records()?;
+        let mut head = GENESIS_HASH.to_string();
+
+        for (i, record) in records.iter().enumerate() {
+            if record.prev_hash != head {
+                return Err(audit_error(format!(
+                    "Record {} does not follow record {} (chain broken)",
+                    i + 1,
+                    i
+                )));
+            }
+            if record.hash != record.compute_hash() {
+                return Err(audit_error(format!(
+                    "Record {} has been modified (hash mismatch)",
+                    i + 1
+                )));
+            }
+            head = record.hash.clone();
+        }
+
+        Ok(AuditSummary {
+            records: records.len(),
+            head,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_log(name: &str) -> AuditLog {
+        let path = std::env::temp_dir().join(format!(
+            "bip-keychain-audit-{}-{}.jsonl",
+            name,
+            std::process::id()
+        ));
+        let _ = fs::remove_file(&path);
+        AuditLog::new(path)
+    }
+
+    #[test]
+    fn test_append_chains_records() {
+        let log = temp_log("chain");
+        assert_eq!(log.verify().unwrap().records, 0);
+
+        let first = log
+            .append(
+                &[1u8; 64],
+                Some("m/83696968'/67797668'/1'".into()),
+                "ssh",
+                10,
+            )
+            .unwrap();
+        let second = log.append(&[2u8; 64], None, "sign", 20).unwrap();
+
+        assert_eq!(first.prev_hash, GENESIS_HASH);
+        assert_eq!(second.prev_hash, first.hash);
+        assert_eq!(
+            log.verify().unwrap(),
+            AuditSummary {
+                records: 2,
+                head: second.hash
+            }
+        );
+        fs::remove_file(log.path()).unwrap();
+    }
+
+    #[test]
+    fn test_verify_detects_tampering() {
+        let log = temp_log("tamper");
+        log.append(&[1u8; 64], None, "ssh", 10).unwrap();
+        log.append(&[2u8; 64], None, "ssh", 20).unwrap();
+        log.append(&[3u8; 64], None, "ssh", 30).unwrap();
+        let contents = fs::read_to_string(log.path()).unwrap();
+        let lines: Vec<&str> = contents.lines().collect();
+
+        // Edited field
+        fs::write(
+            log.path(),
+            contents.replace("\"timestamp\":20", "\"timestamp\":21"),
+        )
+        .unwrap();
+        assert!(log.verify().is_err());
+
+        // Removed record
+        fs::write(log.path(), format!("{}\n{}\n", lines[0], lines[2])).unwrap();
+        assert!(log.verify().is_err());
+
+        // Reordered records
+        fs::write(
+            log.path(),
+            format!("{}\n{}\n{}\n", lines[1], lines[0], lines[2]),
+        )
+        .unwrap();
+        assert!(log.verify().is_err());
+
+        fs::remove_file(log.path()).unwrap();
+    }
+}
@@ -0,0 +1,67 @@
+//! Pluggable derivation backends
+//!
+//! A [`KeychainBackend`] holds the derivation root and performs the BIP-32
+//! step at m/83696968'/67797668'/{index}'. Entity hashing and index
+//! computation always happen in this library, so a backend that keeps the
+//! master key elsewhere (an HSM, a hardware wallet) only needs to answer for
+//! an index.
+//!
+//! Backends return public material only. Callers that need private key
+//! bytes (e.g. to build an [`Ed25519Keypair`](crate::Ed25519Keypair)) use
+//! [`Keychain`] directly.
+
+use crate::{bip32_wrapper::Keychain, error::Result};
+
+/// A source of BIP-Keychain keys whose root may live outside this process
+pub trait KeychainBackend {
+    /// Short human-readable name for error messages and logs
+    fn name(&self) -> &str;
+
+    /// BIP-32 fingerprint of the master key
+    fn master_fingerprint(&self) -> Result<[u8; 4]>;
+
+    /// Compressed secp256k1 public key at m/83696968'/67797668'/{entity_index}'
+    fn public_key(&self, entity_index: u32) -> Result<[u8; 33]>;
+}
+
+/// In-process software backend backed by a seed-derived [`Keychain`]
+impl KeychainBackend for Keychain {
+    fn name(&self) -> &str {
+        "software"
+    }
+
+    fn master_fingerprint(&self) -> Result<[u8; 4]> {
+        Ok(self.fingerprint())
+    }
+
+    fn public_key(&self, entity_index: u32) -> Result<[u8; 33]> {
+        Ok(self
+            .derive_bip_keychain_path(entity_index)?
+            .secp256k1_public_key())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TEST_MNEMONIC: &str = "abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon about";
+
+    #[test]
+    fn test_software_backend_matches_keychain() {
+        let keychain = Keychain::from_mnemonic(TEST_MNEMONIC).unwrap();
+        let backend: &dyn KeychainBackend = &keychain;
+
+        assert_eq!(backend.name(), "software");
+        assert_eq!(
+            backend.master_fingerprint().unwrap(),
+            keychain.fingerprint()
+        );
+
+        let derived = keychain.derive_bip_keychain_path(42).unwrap();
+        assert_eq!(
+            backend.public_key(42).unwrap(),
+            derived.secp256k1_public_key()
+        );
+    }
+}
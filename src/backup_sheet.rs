@@ -0,0 +1,379 @@
+//! Printable seed and SSKR share backup sheets
+//!
+//! A sheet holds everything needed to recover from one piece of paper: the
+//! words (BIP-39 for a seed, standard Bytewords for a share), the same data
+//! as a UR and its QR code, the recovery policy, and blank fields to fill in
+//! by hand. The seed's BIP-32 master fingerprint identifies which seed a
+//! sheet belongs to without revealing anything about it.
+//!
+//! Sheets are rendered as plain text (monospace, 72 columns) or as a
+//! self-contained HTML page with the QR code inlined as SVG.
+
+use crate::{
+    error::Result,
+    qr::QrCode,
+    seed_ur,
+    sskr::{self, ShareInfo},
+    ur,
+};
+use zeroize::{Zeroize, Zeroizing};
+
+/// Fields left blank for writing in by hand
+const RECORD_FIELDS: [&str; 4] = ["Created", "Location", "Custodian", "Notes"];
+
+/// Width of a plain-text sheet
+const TEXT_WIDTH: usize = 72;
+
+/// What a sheet backs up
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SheetKind {
+    /// The whole seed
+    Seed,
+    /// One SSKR share of a single-group split
+    Share {
+        /// The share's metadata
+        info: ShareInfo,
+        /// Number of shares in the group
+        member_count: u8,
+    },
+}
+
+/// One printable backup sheet
+pub struct BackupSheet {
+    kind: SheetKind,
+    name: Option<String>,
+    fingerprint: [u8; 4],
+    words: Vec<String>,
+    checksum: u32,
+    ur: String,
+}
+
+impl Drop for BackupSheet {
+    fn drop(&mut self) {
+        self.words.zeroize();
+        self.ur.zeroize();
+    }
+}
+
+/// Wrap `text` to [`TEXT_WIDTH`], indenting continuation lines by `indent`
+fn wrap(text: &str, indent: usize) -> String {
+    let mut lines = vec![String::new()];
+    for word in text.split_whitespace() {
+        let line = lines.last_mut().expect("at least one line");
+        if !line.trim().is_empty() && line.len() + 1 + word.len() > TEXT_WIDTH - indent {
+            lines.push(word.to_string());
+        } else {
+            if !line.is_empty() {
+                line.push(' ');
+            }
+            line.push_str(word);
+        }
+    }
+    lines.join(&format!("\n{}", " ".repeat(indent)))
+}
+
+/// Escape text for HTML
+fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+impl BackupSheet {
+    /// Sheet for the seed itself, written as its BIP-39 words
+    ///
+    /// `fingerprint` is the seed's BIP-32 master fingerprint.
+    pub fn seed(mnemonic: &bip39::Mnemonic, fingerprint: [u8; 4], name: Option<&str>) -> Self {
+        let entropy = Zeroizing::new(mnemonic.to_entropy());
+        let ur = seed_ur::encode_seed(&entropy, name);
+        Self::new(
+            SheetKind::Seed,
+            name,
+            fingerprint,
+            mnemonic.words().map(str::to_string).collect(),
+            &ur,
+        )
+    }
+
+    /// Sheet for one SSKR share, written as standard Bytewords
+    ///
+    /// `member_count` is the number of shares in the share's group, which
+    /// the share itself does not record.
+    pub fn share(
+        share: &[u8],
+        member_count: u8,
+        fingerprint: [u8; 4],
+        name: Option<&str>,
+    ) -> Result<Self> {
+        let info = sskr::inspect_share(share)?;
+        let ur = Zeroizing::new(seed_ur::encode_share(share));
+        let (_, cbor) = ur::decode(&ur)?;
+        let words = ur::bytewords(&cbor);
+        Ok(Self::new(
+            SheetKind::Share { info, member_count },
+            name,
+            fingerprint,
+            words.split(' ').map(str::to_string).collect(),
+            &ur,
+        ))
+    }
+
+    fn new(
+        kind: SheetKind,
+        name: Option<&str>,
+        fingerprint: [u8; 4],
+        words: Vec<String>,
+        ur_string: &str,
+    ) -> Self {
+        let checksum = ur::decode(ur_string)
+            .map(|(_, cbor)| crc32fast::hash(&cbor))
+            .unwrap_or_default();
+        Self {
+            kind,
+            name: name.map(str::to_string),
+            fingerprint,
+            words,
+            checksum,
+            ur: ur_string.to_string(),
+        }
+    }
+
+    /// Heading, e.g. `SSKR share 2 of 3`
+    pub fn title(&self) -> String {
+        match self.kind {
+            SheetKind::Seed => "Seed".to_string(),
+            SheetKind::Share { info, member_count } => {
+                format!("SSKR share {} of {}", info.member_index + 1, member_count)
+            }
+        }
+    }
+
+    fn policy(&self) -> String {
+        match self.kind {
+            SheetKind::Seed => "This sheet alone recovers every derived key".to_string(),
+            SheetKind::Share { info, member_count } => format!(
+                "Split {:04x}: any {} of these {} shares recover the seed",
+                info.identifier, info.member_threshold, member_count
+            ),
+        }
+    }
+
+    /// Words per row of the word grid
+    fn words_per_row(&self) -> usize {
+        match self.kind {
+            SheetKind::Seed => 4,
+            SheetKind::Share { .. } => 6,
+        }
+    }
+
+    fn word_kind(&self) -> &'static str {
+        match self.kind {
+            SheetKind::Seed => "BIP-39 words",
+            SheetKind::Share { .. } => "Bytewords",
+        }
+    }
+
+    fn instructions(&self) -> Vec<String> {
+        let fingerprint = hex::encode(self.fingerprint);
+        let mut steps = match self.kind {
+            SheetKind::Seed => vec![
+                "Enter the words as the seed (BIP_KEYCHAIN_SEED or `store-seed`), or \
+                 scan the QR code into `bip-keychain restore-seed`."
+                    .to_string(),
+            ],
+            SheetKind::Share { info, .. } => vec![
+                format!(
+                    "Collect at least {} shares of split {:04x}.",
+                    info.member_threshold, info.identifier
+                ),
+                "Scan each QR code, or type each share's words, into `bip-keychain \
+                 restore-seed` (or any SSKR wallet, such as Blockchain Commons Seedtool)."
+                    .to_string(),
+            ],
+        };
+        steps.push(format!(
+            "Check that the restored seed's fingerprint (`bip-keychain fingerprint`) is {}.",
+            fingerprint
+        ));
+        steps
+    }
+
+    fn warning(&self) -> &'static str {
+        match self.kind {
+            SheetKind::Seed => {
+                "Keep this sheet secret. Anyone who reads it can derive all of your keys."
+            }
+            SheetKind::Share { .. } => {
+                "Keep this sheet secret, and store each share in a different place. \
+                 Fewer shares than the threshold reveal nothing about the seed."
+            }
+        }
+    }
+
+    /// The sheet as plain text
+    pub fn to_text(&self) -> Result<Zeroizing<String>> {
+        let qr = QrCode::encode(self.ur.to_ascii_uppercase().as_bytes())?;
+        let mut text = Zeroizing::new(String::new());
+        let rule = "=".repeat(TEXT_WIDTH);
+        let heading = format!("BIP-KEYCHAIN BACKUP - {}", self.title().to_uppercase());
+        text.push_str(&format!("{}\n{}\n\n", heading, rule));
+
+        if let Some(name) = &self.name {
+            text.push_str(&format!("Name:              {}\n", name));
+        }
+        text.push_str(&format!(
+            "Seed fingerprint:  {}\n",
+            hex::encode(self.fingerprint)
+        ));
+        text.push_str(&format!("Policy:            {}\n", self.policy()));
+        text.push_str(&format!(
+            "Checksum:          crc32 {:08x}\n\n",
+            self.checksum
+        ));
+
+        text.push_str(&format!("{}\n", self.word_kind().to_uppercase()));
+        let per_row = self.words_per_row();
+        let width = self.words.iter().map(String::len).max().unwrap_or(0);
+        for (row, words) in self.words.chunks(per_row).enumerate() {
+            let line: Vec<String> = words
+                .iter()
+                .enumerate()
+                .map(|(i, word)| format!("{:>3}. {:<width$}", row * per_row + i + 1, word))
+                .collect();
+            text.push_str(&format!("{}\n", line.join(" ").trim_end()));
+        }
+
+        text.push_str(&format!("\nUR\n{}\n\n", self.ur));
+        text.push_str(&qr.to_terminal_string());
+
+        text.push_str("\nRECOVERY\n");
+        for (i, step) in self.instructions().iter().enumerate() {
+            text.push_str(&format!("{:>3}. {}\n", i + 1, wrap(step, 5)));
+        }
+        text.push_str(&format!("\n{}\n\nRECORD\n", wrap(self.warning(), 0)));
+        for field in RECORD_FIELDS {
+            text.push_str(&format!(
+                "  {:<10} {}\n",
+                format!("{}:", field),
+                "_".repeat(40)
+            ));
+        }
+        Ok(text)
+    }
+
+    /// The sheet as a self-contained HTML page
+    pub fn to_html(&self) -> Result<Zeroizing<String>> {
+        let qr = QrCode::encode(self.ur.to_ascii_uppercase().as_bytes())?;
+        let title = escape_html(&self.title());
+        let mut html = Zeroizing::new(String::new());
+        html.push_str(&format!(
+            "<!DOCTYPE html>\n<html lang=\"en\">\n<head>\n<meta charset=\"utf-8\">\n\
+             <title>BIP-Keychain backup - {title}</title>\n<style>\n\
+             @page {{ size: A4; margin: 15mm; }}\n\
+             body {{ font-family: sans-serif; max-width: 180mm; margin: auto; }}\n\
+             table.words {{ border-collapse: collapse; font-family: monospace; font-size: 13pt; }}\n\
+             table.words td {{ padding: 3px 10px; border: 1px solid #999; }}\n\
+             table.words td b {{ color: #666; font-weight: normal; margin-right: 6px; }}\n\
+             .ur {{ font-family: monospace; word-break: break-all; }}\n\
+             .record td {{ padding: 8px 4px; }}\n\
+             .record td + td {{ border-bottom: 1px solid #000; width: 120mm; }}\n\
+             .warning {{ border: 2px solid #000; padding: 6px; font-weight: bold; }}\n\
+             </style>\n</head>\n<body>\n<h1>BIP-Keychain backup &mdash; {title}</h1>\n<table>\n",
+        ));
+        if let Some(name) = &self.name {
+            html.push_str(&format!(
+                "<tr><th align=\"left\">Name</th><td>{}</td></tr>\n",
+                escape_html(name)
+            ));
+        }
+        html.push_str(&format!(
+            "<tr><th align=\"left\">Seed fingerprint</th><td><code>{}</code></td></tr>\n\
+             <tr><th align=\"left\">Policy</th><td>{}</td></tr>\n\
+             <tr><th align=\"left\">Checksum</th><td><code>crc32 {:08x}</code></td></tr>\n\
+             </table>\n<h2>{}</h2>\n<table class=\"words\">\n",
+            hex::encode(self.fingerprint),
+            self.policy(),
+            self.checksum,
+            self.word_kind()
+        ));
+        let per_row = self.words_per_row();
+        for (row, words) in self.words.chunks(per_row).enumerate() {
+            html.push_str("<tr>");
+            for (i, word) in words.iter().enumerate() {
+                html.push_str(&format!(
+                    "<td><b>{}</b>{}</td>",
+                    row * per_row + i + 1,
+                    word
+                ));
+            }
+            html.push_str("</tr>\n");
+        }
+        html.push_str(&format!(
+            "</table>\n<h2>UR</h2>\n<p class=\"ur\">{}</p>\n{}<h2>Recovery</h2>\n<ol>\n",
+            self.ur,
+            qr.to_svg("60mm")
+        ));
+        for step in self.instructions() {
+            html.push_str(&format!("<li>{}</li>\n", escape_html(&step)));
+        }
+        html.push_str(&format!(
+            "</ol>\n<p class=\"warning\">{}</p>\n<h2>Record</h2>\n<table class=\"record\">\n",
+            self.warning()
+        ));
+        for field in RECORD_FIELDS {
+            html.push_str(&format!("<tr><td>{}</td><td></td></tr>\n", field));
+        }
+        html.push_str("</table>\n</body>\n</html>\n");
+        Ok(html)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sskr::GroupSpec;
+
+    const FINGERPRINT: [u8; 4] = [0x73, 0xc5, 0xda, 0x0a];
+
+    #[test]
+    fn test_seed_sheet() {
+        let mnemonic = bip39::Mnemonic::from_entropy(&[0u8; 16]).unwrap();
+        let sheet = BackupSheet::seed(&mnemonic, FINGERPRINT, Some("Work <main>"));
+        let text = sheet.to_text().unwrap();
+        assert!(text.starts_with("BIP-KEYCHAIN BACKUP - SEED\n"));
+        assert!(text.contains("Name:              Work <main>\n"));
+        assert!(text.contains("Seed fingerprint:  73c5da0a\n"));
+        assert!(text.contains("  1. abandon   2. abandon"));
+        assert!(text.contains(" 12. about\n"));
+        assert!(text
+            .lines()
+            .filter(|line| !line.starts_with("ur:"))
+            .all(|line| line.chars().count() <= TEXT_WIDTH));
+        assert!(text.contains("\nur:crypto-seed/"));
+        assert!(text.contains("  Custodian: ____"));
+
+        let html = sheet.to_html().unwrap();
+        assert!(html.contains("<td>Work &lt;main&gt;</td>"));
+        assert!(html.contains("<td><b>12</b>about</td>"));
+        assert!(html.contains("<svg "));
+    }
+
+    #[test]
+    fn test_share_sheet_words_recover_the_share() {
+        let group = GroupSpec::parse("2of3").unwrap();
+        let shares = sskr::generate_shares(&[5u8; 16], 1, &[group]).unwrap();
+        let sheet = BackupSheet::share(&shares[0][1], 3, FINGERPRINT, None).unwrap();
+        assert_eq!(sheet.title(), "SSKR share 2 of 3");
+
+        let text = sheet.to_text().unwrap();
+        assert!(text.contains("any 2 of these 3 shares recover the seed"));
+        assert!(text.contains(&format!("crc32 {:08x}", sheet.checksum)));
+        assert!(!text.contains("Name:"));
+
+        // The words are the crypto-sskr payload
+        let cbor = ur::bytewords_decode(&sheet.words.join(" ")).unwrap();
+        assert_eq!(ur::encode(seed_ur::SSKR_UR_TYPE, &cbor), sheet.ur);
+        assert_eq!(seed_ur::decode_share(&sheet.ur).unwrap(), shares[0][1]);
+    }
+}
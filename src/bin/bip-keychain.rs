@@ -3,11 +3,34 @@
 //! Command-line interface for deriving cryptographic keys from semantic entities.
 
 use anyhow::{Context, Result};
-use bip_keychain::{derive_key_from_entity, format_key, KeyDerivation, Keychain, OutputFormat};
+use bip_keychain::bip32_wrapper::Language;
+use bip_keychain::config::{Config, ParentEntropyPolicy, SeedFormatConfig, SeedSourceConfig};
+use bip_keychain::entity::NOT_AFTER_FIELD;
+use bip_keychain::signing::SignatureFormat;
+use bip_keychain::template;
+#[cfg(unix)]
+use bip_keychain::Agent;
+use bip_keychain::{
+    agent, bip85, check_mnemonic, check_mnemonic_in, cosign, derive_key_from_entity,
+    entity_derivation_path, entity_hash, entity_indices, eth, foreign_mnemonic, format_key,
+    format_keys, frost, generate_mnemonic_in, generate_vectors, parse_mnemonic, sealed_box,
+    seed_ur, signing, ssh_config, sshsig, transparency, AuditLog, BipKeychainError, CurveKey,
+    DerivedKey, Ed25519Keypair, Entity, EntityParent, ErrorKind, FrostGroup, IndexRegistry,
+    KeyDerivation, KeyManifest, KeyPackage, KeyTypeConfig, Keychain, LogPublicKey, LogRecord,
+    OnionService, OutputFormat, ParentReference, PasswordCharset, PublishedKey, Receipt,
+    ReferenceVectors, RevocationList, SecretString, SignatureShare, SignedKeyManifest,
+    SignedRevocationList, SigningCommitment, SigningNonces, SshHost, SshKeyFile, TlsaMatchingType,
+    TlsaSelector, Totp, DEFAULT_PARENT_ENTROPY,
+};
+use bip_keychain::{Manifest, ManifestEntry};
 use clap::{Parser, Subcommand};
 use std::env;
 use std::fs;
-use std::path::PathBuf;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::process::{Command, ExitCode};
+use std::sync::OnceLock;
+use zeroize::Zeroizing;
 
 /// BIP-Keychain: Semantic hierarchical key derivation
 ///
@@ -19,6 +42,285 @@ use std::path::PathBuf;
 struct Cli {
     #[command(subcommand)]
     command: Commands,
+
+    #[command(flatten)]
+    seed: SeedArgs,
+
+    #[command(flatten)]
+    key_use: KeyUseArgs,
+
+    /// Config file (default: ~/.config/bip-keychain/config.toml)
+    #[arg(long, global = true, value_name = "PATH")]
+    config: Option<PathBuf>,
+}
+
+/// Where to read the seed phrase from
+#[derive(clap::Args)]
+struct SeedArgs {
+    /// Seed phrase source [default: env]
+    #[arg(long, global = true, value_enum)]
+    seed_source: Option<SeedSource>,
+
+    /// Account name for --seed-source os-keychain [default: default]
+    #[arg(long, global = true, value_name = "NAME")]
+    keychain_account: Option<String>,
+
+    /// Read the seed phrase from this file instead (e.g. a systemd credential)
+    #[arg(
+        long,
+        global = true,
+        value_name = "PATH",
+        conflicts_with_all = ["seed_source", "seed_fd"]
+    )]
+    seed_file: Option<PathBuf>,
+
+    /// Read the seed phrase from this open file descriptor instead (e.g. a pipe)
+    #[arg(long, global = true, value_name = "FD", conflicts_with = "seed_source")]
+    seed_fd: Option<i32>,
+
+    /// BIP-39 wordlist of the seed phrase [default: detected; english for
+    /// generate-seed]
+    #[arg(long, global = true, value_enum)]
+    language: Option<MnemonicLanguage>,
+
+    /// Mnemonic format of the seed phrase, for seeds from other wallets
+    /// [default: bip39]
+    #[arg(long, global = true, value_enum)]
+    seed_format: Option<SeedFormat>,
+
+    /// Monero wordlist (1626 words, one per line) for --seed-format monero
+    /// [default: Monero's English list]
+    #[arg(long, global = true, value_name = "PATH")]
+    monero_wordlist: Option<PathBuf>,
+}
+
+/// Overrides for the entity's `password` settings
+#[derive(clap::Args)]
+struct PasswordArgs {
+    /// Password length for --format password [default: 20]
+    #[arg(long, value_name = "N")]
+    password_length: Option<usize>,
+
+    /// Password characters for --format password: base64, base85,
+    /// alphanumeric, digits, or custom:<characters> [default: base64]
+    #[arg(long, value_name = "CHARSET")]
+    password_charset: Option<PasswordCharset>,
+}
+
+impl PasswordArgs {
+    /// Apply the overrides to an entity's password settings
+    fn apply(self, key_derivation: &mut KeyDerivation) {
+        if self.password_length.is_none() && self.password_charset.is_none() {
+            return;
+        }
+        let password = key_derivation.password.get_or_insert_with(Default::default);
+        if let Some(length) = self.password_length {
+            password.length = length;
+        }
+        if let Some(charset) = self.password_charset {
+            password.charset = charset;
+        }
+    }
+}
+
+/// Overrides for the entity's `tlsa` settings
+#[derive(clap::Args)]
+struct TlsaArgs {
+    /// TLSA selector for --format tlsa: spki (1) [default: spki]
+    #[arg(long, value_name = "SELECTOR")]
+    tlsa_selector: Option<TlsaSelector>,
+
+    /// TLSA matching type for --format tlsa: full (0), sha256 (1), or
+    /// sha512 (2) [default: sha256]
+    #[arg(long, value_name = "TYPE")]
+    tlsa_matching_type: Option<TlsaMatchingType>,
+}
+
+impl TlsaArgs {
+    /// Apply the overrides to an entity's TLSA settings
+    fn apply(self, key_derivation: &mut KeyDerivation) {
+        if self.tlsa_selector.is_none() && self.tlsa_matching_type.is_none() {
+            return;
+        }
+        let tlsa = key_derivation.tlsa.get_or_insert_with(Default::default);
+        if let Some(selector) = self.tlsa_selector {
+            tlsa.selector = selector;
+        }
+        if let Some(matching_type) = self.tlsa_matching_type {
+            tlsa.matching_type = matching_type;
+        }
+    }
+}
+
+/// Checks and records applied whenever a key is derived for use
+#[derive(clap::Args)]
+struct KeyUseArgs {
+    /// Revocation list consulted by derive, derive-all, sign, and agent
+    /// [default: revocations.json]
+    #[arg(long, global = true, value_name = "FILE")]
+    revocations: Option<PathBuf>,
+
+    /// Warn instead of failing when a derived key has been revoked
+    #[arg(long, global = true)]
+    allow_revoked: bool,
+
+    /// Warn instead of failing when the entity is past its metadata.not_after
+    #[arg(long, global = true)]
+    allow_expired: bool,
+
+    /// Append a hash-chained record of every derivation to this file
+    #[arg(long, global = true, value_name = "FILE")]
+    audit_log: Option<PathBuf>,
+
+    /// Record each entity's derivation path in this file and warn when a
+    /// different entity maps to a path already in it
+    #[arg(long, global = true, value_name = "FILE")]
+    index_registry: Option<PathBuf>,
+
+    /// Fail instead of warning on an --index-registry collision
+    #[arg(long, global = true)]
+    strict: bool,
+
+    /// Submit receipts and key manifests written with -o to this Rekor
+    /// log, saving the log record as <FILE>.tlog.json
+    #[arg(long, global = true, value_name = "URL")]
+    transparency_log: Option<String>,
+}
+
+impl SeedArgs {
+    /// Fill in seed options not given on the command line from the config
+    fn apply_config(&mut self, config: &Config) {
+        if self.seed_source.is_none() && self.seed_file.is_none() && self.seed_fd.is_none() {
+            self.seed_source = config.seed_source.map(Into::into);
+            if self.seed_source.is_none() {
+                self.seed_file = config.seed_file.clone();
+            }
+        }
+        if self.keychain_account.is_none() {
+            self.keychain_account = config.keychain_account.clone();
+        }
+        if self.seed_format.is_none() {
+            self.seed_format = config.seed_format.map(Into::into);
+        }
+        if self.monero_wordlist.is_none() {
+            self.monero_wordlist = config.monero_wordlist.clone();
+        }
+    }
+
+    fn source(&self) -> SeedSource {
+        self.seed_source.unwrap_or(SeedSource::Env)
+    }
+
+    fn format(&self) -> SeedFormat {
+        self.seed_format.unwrap_or(SeedFormat::Bip39)
+    }
+
+    #[cfg(feature = "os-keychain")]
+    fn account(&self) -> &str {
+        self.keychain_account.as_deref().unwrap_or("default")
+    }
+}
+
+impl KeyUseArgs {
+    /// Fill in options not given on the command line from the config
+    fn apply_config(&mut self, config: &Config) {
+        if self.revocations.is_none() {
+            self.revocations = config.revocations.clone();
+        }
+        if self.audit_log.is_none() {
+            self.audit_log = config.audit_log.clone();
+        }
+        if self.index_registry.is_none() {
+            self.index_registry = config.index_registry.clone();
+        }
+        if self.transparency_log.is_none() {
+            self.transparency_log = config.transparency_log.clone();
+        }
+    }
+
+    fn revocations_path(&self) -> &Path {
+        self.revocations
+            .as_deref()
+            .unwrap_or(Path::new("revocations.json"))
+    }
+}
+
+#[derive(Debug, Clone, Copy, clap::ValueEnum)]
+enum SeedSource {
+    /// BIP_KEYCHAIN_SEED environment variable
+    Env,
+    /// OS credential store (requires the os-keychain feature)
+    OsKeychain,
+}
+
+impl From<SeedSourceConfig> for SeedSource {
+    fn from(source: SeedSourceConfig) -> Self {
+        match source {
+            SeedSourceConfig::Env => SeedSource::Env,
+            SeedSourceConfig::OsKeychain => SeedSource::OsKeychain,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+enum SeedFormat {
+    /// BIP-39 mnemonic
+    Bip39,
+    /// Electrum 2.0+ seed (English)
+    Electrum,
+    /// LND aezeed (24 words)
+    Aezeed,
+    /// Monero 25-word seed
+    Monero,
+}
+
+impl From<SeedFormatConfig> for SeedFormat {
+    fn from(format: SeedFormatConfig) -> Self {
+        match format {
+            SeedFormatConfig::Bip39 => SeedFormat::Bip39,
+            SeedFormatConfig::Electrum => SeedFormat::Electrum,
+            SeedFormatConfig::Aezeed => SeedFormat::Aezeed,
+            SeedFormatConfig::Monero => SeedFormat::Monero,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, clap::ValueEnum)]
+enum MnemonicLanguage {
+    English,
+    ChineseSimplified,
+    ChineseTraditional,
+    Czech,
+    French,
+    Italian,
+    Japanese,
+    Korean,
+    Portuguese,
+    Spanish,
+}
+
+impl From<MnemonicLanguage> for Language {
+    fn from(language: MnemonicLanguage) -> Self {
+        match language {
+            MnemonicLanguage::English => Language::English,
+            MnemonicLanguage::ChineseSimplified => Language::SimplifiedChinese,
+            MnemonicLanguage::ChineseTraditional => Language::TraditionalChinese,
+            MnemonicLanguage::Czech => Language::Czech,
+            MnemonicLanguage::French => Language::French,
+            MnemonicLanguage::Italian => Language::Italian,
+            MnemonicLanguage::Japanese => Language::Japanese,
+            MnemonicLanguage::Korean => Language::Korean,
+            MnemonicLanguage::Portuguese => Language::Portuguese,
+            MnemonicLanguage::Spanish => Language::Spanish,
+        }
+    }
+}
+
+/// Config file settings, loaded once at startup
+static CONFIG: OnceLock<Config> = OnceLock::new();
+
+fn config() -> &'static Config {
+    CONFIG.get_or_init(Config::default)
 }
 
 #[derive(Subcommand)]
@@ -44,9 +346,101 @@ enum Commands {
         #[arg(long, value_name = "HEX")]
         parent_entropy: Option<String>,
 
-        /// Output format
-        #[arg(long, value_enum, default_value = "ssh")]
-        format: CliOutputFormat,
+        /// Output format [default: the entity's output_format, else ssh];
+        /// repeat (or separate with commas) to print several formats as one
+        /// JSON document keyed by format name
+        #[arg(
+            long,
+            value_enum,
+            value_delimiter = ',',
+            conflicts_with = "add_to_agent"
+        )]
+        format: Vec<CliOutputFormat>,
+
+        /// Write the key to this file instead of stdout (mode 0600, or 0644
+        /// for public-only formats). With --format tor-hidden-service, this is
+        /// the onion service directory to write the key files into
+        #[arg(short, long, value_name = "PATH", conflicts_with = "add_to_agent")]
+        output: Option<PathBuf>,
+
+        /// Overwrite an existing --output file
+        #[arg(long, requires = "output")]
+        force: bool,
+
+        /// Add the derived key to the running SSH agent (SSH_AUTH_SOCK)
+        /// instead of printing it
+        #[arg(long)]
+        add_to_agent: bool,
+
+        /// Seconds the agent keeps the key with --add-to-agent
+        #[arg(
+            long,
+            value_name = "SECONDS",
+            default_value = "3600",
+            requires = "add_to_agent"
+        )]
+        lifetime: u32,
+
+        #[command(flatten)]
+        password: PasswordArgs,
+
+        #[command(flatten)]
+        tlsa: TlsaArgs,
+    },
+
+    /// Derive every entity in a manifest
+    ///
+    /// Writes each key to the entry's `output` file in its `format`
+    /// (default: ssh); entries without an `output` are printed. Existing
+    /// files with different contents are left alone unless --force is given.
+    ///
+    /// Example:
+    ///   bip-keychain derive-all provision.json
+    DeriveAll {
+        /// Path to manifest JSON listing entity files
+        #[arg(value_name = "MANIFEST_JSON")]
+        manifest: PathBuf,
+
+        /// Parent entropy (hex encoded, optional)
+        #[arg(long, value_name = "HEX")]
+        parent_entropy: Option<String>,
+
+        /// Overwrite output files whose contents differ
+        #[arg(long)]
+        force: bool,
+    },
+
+    /// Derive every entity file in a directory tree
+    ///
+    /// Finds `*.json` entity files below DIR (skipping hidden entries and
+    /// the output directory) and writes each key to the same relative path
+    /// under --out, named after the format: `servers/web.json` becomes
+    /// `keys/servers/web.ssh`. Every file must be a valid entity; nothing
+    /// is written unless all of them derive.
+    ///
+    /// Example:
+    ///   bip-keychain derive-dir ./entities/ --out ./keys/
+    DeriveDir {
+        /// Directory to search for entity JSON files
+        #[arg(value_name = "DIR")]
+        dir: PathBuf,
+
+        /// Directory the keys are written to, mirroring DIR
+        #[arg(long, value_name = "DIR")]
+        out: PathBuf,
+
+        /// Output format for every entity [default: each entity's
+        /// output_format, else ssh]
+        #[arg(long, value_enum)]
+        format: Option<CliOutputFormat>,
+
+        /// Parent entropy (hex encoded, optional)
+        #[arg(long, value_name = "HEX")]
+        parent_entropy: Option<String>,
+
+        /// Overwrite output files whose contents differ
+        #[arg(long)]
+        force: bool,
     },
 
     /// Generate a new BIP-39 seed phrase
@@ -58,114 +452,4013 @@ enum Commands {
         /// Number of words (12, 15, 18, 21, or 24)
         #[arg(short = 'w', long, default_value = "24")]
         words: usize,
+
+        /// Shard the new seed into SSKR shares instead of printing it (e.g. 2of3)
+        ///
+        /// The mnemonic entropy is split immediately and each share is written
+        /// to its own file in --output-dir. The phrase itself is not printed.
+        #[arg(long, value_name = "MofN", requires = "output_dir")]
+        sskr: Option<String>,
+
+        /// Directory to write SSKR share files into
+        #[arg(long, value_name = "DIR", requires = "sskr")]
+        output_dir: Option<PathBuf>,
+
+        /// Also print the mnemonic when sharding (off by default)
+        #[arg(long, requires = "sskr")]
+        reveal: bool,
     },
-}
 
-#[derive(Debug, Clone, clap::ValueEnum)]
-enum CliOutputFormat {
-    /// Raw 32-byte seed as hex
-    Seed,
-    /// Ed25519 public key as hex
-    PublicKey,
-    /// Ed25519 private key as hex (use with caution!)
-    PrivateKey,
-    /// OpenSSH public key format (default, most useful)
-    Ssh,
-    /// GPG-compatible public key info (for Git signing)
-    Gpg,
-    /// JSON with all key data and metadata
-    Json,
-}
+    /// Check a seed phrase for typos
+    ///
+    /// Reads a mnemonic from stdin and checks each word against the BIP-39
+    /// wordlist and the phrase's checksum. Unknown words get the closest
+    /// wordlist entries as suggestions, and a failed checksum lists the
+    /// single-word changes that would fix it. Useful when recovering from a
+    /// paper backup; nothing is derived or stored.
+    ///
+    /// Exits with status 0 if the phrase is valid.
+    ///
+    /// Example:
+    ///   bip-keychain check-seed < /dev/tty
+    CheckSeed,
 
-impl From<CliOutputFormat> for OutputFormat {
-    fn from(cli_format: CliOutputFormat) -> Self {
-        match cli_format {
-            CliOutputFormat::Seed => OutputFormat::HexSeed,
-            CliOutputFormat::PublicKey => OutputFormat::Ed25519PublicHex,
-            CliOutputFormat::PrivateKey => OutputFormat::Ed25519PrivateHex,
-            CliOutputFormat::Ssh => OutputFormat::SshPublicKey,
-            CliOutputFormat::Gpg => OutputFormat::GpgPublicKey,
-            CliOutputFormat::Json => OutputFormat::Json,
-        }
+    /// Quiz yourself on a few words of your paper backup
+    ///
+    /// Asks for the words at randomly chosen positions and checks them
+    /// against the seed from the configured seed source, without ever
+    /// displaying the phrase. Answers are read from stdin, one per line.
+    ///
+    /// Exits with status 0 if every answer matches.
+    ///
+    /// Example:
+    ///   bip-keychain verify-backup
+    ///   bip-keychain --seed-source os-keychain verify-backup --count 5
+    VerifyBackup {
+        /// Number of words to ask for
+        #[arg(short = 'n', long, default_value = "3")]
+        count: usize,
+    },
+
+    /// Print key fingerprints without any secret material
+    ///
+    /// Without an entity, prints the BIP-32 master fingerprint of the seed in
+    /// BIP_KEYCHAIN_SEED. With an entity, also prints the BIP-32 and OpenSSH
+    /// (SHA256) fingerprints of the key derived for it and its derivation
+    /// path.
+    ///
+    /// Example:
+    ///   bip-keychain fingerprint
+    ///   bip-keychain fingerprint entity.json
+    Fingerprint {
+        /// Path to entity JSON file (optional)
+        #[arg(value_name = "ENTITY_JSON")]
+        entity_file: Option<PathBuf>,
+
+        /// Parent entropy (hex encoded, optional)
+        #[arg(long, value_name = "HEX")]
+        parent_entropy: Option<String>,
+    },
+
+    /// Show each step of an entity's derivation without deriving a key
+    ///
+    /// Prints the canonical entity bytes, hash function, 64-byte entity
+    /// hash, extracted indices, and final BIP-32 path. No seed is needed and
+    /// no key material is printed; compare the output across machines to
+    /// find where two derivations diverge.
+    ///
+    /// Example:
+    ///   bip-keychain inspect examples/github-repo.json
+    Inspect {
+        /// Path to entity JSON file
+        #[arg(value_name = "ENTITY_JSON")]
+        entity_file: PathBuf,
+
+        /// Parent entropy (hex encoded, optional)
+        #[arg(long, value_name = "HEX")]
+        parent_entropy: Option<String>,
+    },
+
+    /// Find the entity file that derives a given public key
+    ///
+    /// Derives every `*.json` entity below the --entities directories (or
+    /// the files themselves) and prints the ones whose key matches, e.g. to
+    /// identify an old deploy key before removing it. Files that are not
+    /// entities are skipped with a warning.
+    ///
+    /// Example:
+    ///   bip-keychain which-entity --pubkey ~/.ssh/old_deploy.pub --entities ./entities
+    WhichEntity {
+        /// Public key: an ssh-ed25519 line, hex (Ed25519, X25519, or
+        /// compressed secp256k1 / P-256), or a file holding either
+        #[arg(long, value_name = "KEY")]
+        pubkey: String,
+
+        /// Entity file or directory to search (repeatable)
+        #[arg(long, value_name = "PATH", required = true)]
+        entities: Vec<PathBuf>,
+
+        /// Parent entropy (hex encoded, optional)
+        #[arg(long, value_name = "HEX")]
+        parent_entropy: Option<String>,
+    },
+
+    /// Check that an entity derives a given public key
+    ///
+    /// Re-derives the entity's key and exits with status 1 unless it
+    /// matches, so CI can assert that deployed keys still belong to the
+    /// entities they are declared for.
+    ///
+    /// Example:
+    ///   bip-keychain verify-binding examples/server-prod.json deploy/prod.pub
+    VerifyBinding {
+        /// Path to entity JSON file
+        #[arg(value_name = "ENTITY_JSON")]
+        entity_file: PathBuf,
+
+        /// Public key: an ssh-ed25519 line, hex, or a file holding either
+        #[arg(value_name = "PUBKEY")]
+        pubkey: String,
+
+        /// Parent entropy (hex encoded, optional)
+        #[arg(long, value_name = "HEX")]
+        parent_entropy: Option<String>,
+    },
+
+    /// Publish a signed listing of a manifest's public keys
+    ///
+    /// Derives every entity in the manifest and writes a JSON document of
+    /// {entity_hash, purpose, public_key, fingerprint} per entity, signed
+    /// with the key of --signing-entity. Anyone holding an entity file can
+    /// then check which published key belongs to it without the seed.
+    ///
+    /// Example:
+    ///   bip-keychain export-manifest provision.json --signing-entity authority.json -o keys.json
+    ExportManifest {
+        /// Path to manifest JSON listing entity files
+        #[arg(value_name = "MANIFEST_JSON")]
+        manifest: PathBuf,
+
+        /// Entity whose derived key signs the listing
+        #[arg(long, value_name = "ENTITY_JSON")]
+        signing_entity: PathBuf,
+
+        /// Write to this file instead of stdout
+        #[arg(short, long, value_name = "FILE")]
+        output: Option<PathBuf>,
+
+        /// Parent entropy (hex encoded, optional)
+        #[arg(long, value_name = "HEX")]
+        parent_entropy: Option<String>,
+    },
+
+    /// Check a published key manifest and print its keys
+    ///
+    /// With --entity, also checks that the manifest lists that entity and
+    /// prints only its key.
+    ///
+    /// Example:
+    ///   bip-keychain verify-manifest keys.json authority.pub --entity examples/server-prod.json
+    VerifyManifest {
+        /// Signed manifest from `export-manifest`
+        #[arg(value_name = "SIGNED_JSON")]
+        signed_manifest: PathBuf,
+
+        /// Expected signer public key (or a file containing it)
+        #[arg(value_name = "PUBKEY")]
+        public_key: String,
+
+        /// Entity file to look up in the manifest
+        #[arg(long, value_name = "ENTITY_JSON")]
+        entity: Option<PathBuf>,
+    },
+
+    /// Regenerate the published derivation test vectors
+    ///
+    /// Derives a fixed set of entities, one or more per hash function and
+    /// derivation option, from BIP-39 test mnemonics, and prints every
+    /// intermediate value as JSON. The seed source is not used. The output
+    /// is checked into vectors/bip-keychain.json for other implementations
+    /// to test against.
+    ///
+    /// Example:
+    ///   bip-keychain gen-vectors -o vectors/bip-keychain.json
+    GenVectors {
+        /// Write the vectors to FILE instead of stdout
+        #[arg(short, long, value_name = "FILE")]
+        output: Option<PathBuf>,
+    },
+
+    /// Compare derivations against another implementation's outputs
+    ///
+    /// Reads a JSON file of vectors produced by another BIP-Keychain
+    /// implementation (such as the reference implementation), re-derives
+    /// each one, and reports every step that differs: canonical entity
+    /// bytes, hash, indices, path, seed, and public key. Outputs missing
+    /// from the file are not compared. The file format is that of
+    /// vectors/bip-keychain.json with every output optional.
+    ///
+    /// Exits with status 0 if every listed output matches.
+    ///
+    /// Example:
+    ///   bip-keychain compat reference-outputs.json
+    Compat {
+        /// Path to the reference outputs
+        #[arg(value_name = "REFERENCE_JSON")]
+        reference: PathBuf,
+    },
+
+    /// Print the current TOTP code for an entity
+    ///
+    /// The shared secret is derived from the entity, so a 2FA enrollment can
+    /// be recreated from the seed alone. Settings come from the entity's
+    /// optional "totp" object (issuer, account, digits, period, algorithm).
+    /// With --enroll, prints the otpauth:// URI and a QR code to scan with
+    /// an authenticator app instead.
+    ///
+    /// Example:
+    ///   bip-keychain totp examples/github-totp.json --enroll
+    ///   bip-keychain totp examples/github-totp.json
+    Totp {
+        /// Path to entity JSON file
+        #[arg(value_name = "ENTITY_JSON")]
+        entity_file: PathBuf,
+
+        /// Print the otpauth:// URI and QR code instead of a code
+        #[arg(long)]
+        enroll: bool,
+
+        /// Compute the code at this Unix time instead of now
+        #[arg(long, value_name = "UNIX_SECONDS", conflicts_with = "enroll")]
+        at: Option<u64>,
+
+        /// Parent entropy (hex encoded, optional)
+        #[arg(long, value_name = "HEX")]
+        parent_entropy: Option<String>,
+    },
+
+    /// Store a seed phrase in the OS credential store
+    ///
+    /// Reads the mnemonic from stdin, validates it, and saves it under
+    /// --keychain-account so later commands can use --seed-source os-keychain.
+    ///
+    /// Example:
+    ///   bip-keychain store-seed --keychain-account work < /dev/tty
+    #[cfg(feature = "os-keychain")]
+    StoreSeed {
+        /// Remove the stored seed instead of writing one
+        #[arg(long)]
+        delete: bool,
+    },
+
+    /// Check that a set of SSKR shares belongs to the same split
+    ///
+    /// Parses each share's metadata (group, member, thresholds) and verifies
+    /// the shares are consistent with each other. The secret is never
+    /// reconstructed or printed.
+    ///
+    /// Example:
+    ///   bip-keychain verify-shares <share1-hex> <share2-hex>
+    VerifyShares {
+        /// Hex-encoded SSKR shares
+        #[arg(value_name = "SHARE_HEX", required = true)]
+        shares: Vec<String>,
+    },
+
+    /// Back up the seed as ur:crypto-seed and SSKR share QR codes
+    ///
+    /// Splits the configured seed into SSKR shares and shows the seed and
+    /// each share as a QR code in turn, for scanning into airgapped
+    /// devices or printing. In a terminal the screen is cleared between
+    /// codes, so only one is visible at a time. Check the backup with
+    /// `restore-seed --check`. With `--qr png`, each code is written to
+    /// its own PNG image in --output-dir for printing.
+    ///
+    /// Example:
+    ///   bip-keychain backup-seed --sskr 2of3
+    ///   bip-keychain backup-seed --sskr 3of5 --output-dir /media/airgap/backup
+    ///   bip-keychain backup-seed --sskr 2of3 --qr png --output-dir /media/airgap/backup
+    BackupSeed {
+        /// SSKR policy for the shares (e.g. 2of3)
+        #[arg(long, value_name = "MofN")]
+        sskr: String,
+
+        /// Name stored in the crypto-seed UR
+        #[arg(long)]
+        name: Option<String>,
+
+        /// How to draw the QR codes
+        #[arg(long, value_enum, default_value_t = BackupQrOutput::Terminal)]
+        qr: BackupQrOutput,
+
+        /// Write each QR code and UR to its own file in DIR instead
+        #[arg(long, value_name = "DIR", required_if_eq("qr", "png"))]
+        output_dir: Option<PathBuf>,
+    },
+
+    /// Recover the seed from a ur:crypto-seed or ur:crypto-sskr shares
+    ///
+    /// Takes a crypto-seed UR, or enough crypto-sskr share URs to meet the
+    /// threshold, as arguments, files holding them, or lines on stdin, and
+    /// prints the recovered mnemonic. Shares typed in from a `print-backup`
+    /// sheet are read as Bytewords, one share per argument or per
+    /// blank-line-separated block. With --check the mnemonic is not
+    /// printed; it is compared with the configured seed instead.
+    ///
+    /// Example:
+    ///   bip-keychain restore-seed --check ur:crypto-sskr/... ur:crypto-sskr/...
+    ///   bip-keychain restore-seed /media/airgap/backup/share-1.txt /media/airgap/backup/share-3.txt
+    RestoreSeed {
+        /// UR strings, Bytewords, or files containing them [default: read stdin]
+        #[arg(value_name = "UR")]
+        urs: Vec<String>,
+
+        /// Check that the URs restore the configured seed, without printing it
+        #[arg(long)]
+        check: bool,
+    },
+
+    /// Show the seed as a SeedQR code for hardware wallets
+    ///
+    /// SeedQR (from SeedSigner) carries a 12- or 24-word seed as a QR code:
+    /// standard SeedQR holds the word indices as digits, compact SeedQR the
+    /// raw entropy. Prints the code to the terminal, or writes an SVG for
+    /// printing and hand transcription. Files are created owner-only and
+    /// never overwritten.
+    ///
+    /// Example:
+    ///   bip-keychain export-seedqr
+    ///   bip-keychain export-seedqr --compact --format svg -o seedqr.svg
+    ExportSeedqr {
+        /// Use compact SeedQR (smaller code, binary data)
+        #[arg(long)]
+        compact: bool,
+
+        /// Output format
+        #[arg(long, value_enum, default_value = "terminal")]
+        format: SeedQrOutput,
+
+        /// Write to FILE instead of stdout
+        #[arg(short, long, value_name = "FILE")]
+        output: Option<PathBuf>,
+    },
+
+    /// Recover the seed from a scanned SeedQR
+    ///
+    /// Takes the data of a standard SeedQR (48 or 96 digits) or a compact
+    /// SeedQR (16 or 32 bytes, raw or as hex), as an argument, a file, or
+    /// stdin from a QR scanner, and prints the mnemonic. With --check the
+    /// mnemonic is not printed; it is compared with the configured seed
+    /// instead.
+    ///
+    /// Example:
+    ///   zbarcam --raw --oneshot | bip-keychain import-seedqr --check
+    ///   bip-keychain import-seedqr 011513251154012711900771041507421289190620080870026613431420201617920614089619290300152408010643
+    ImportSeedqr {
+        /// SeedQR data, or a file containing it [default: read stdin]
+        #[arg(value_name = "SEEDQR")]
+        input: Option<String>,
+
+        /// Check that the SeedQR holds the configured seed, without printing it
+        #[arg(long)]
+        check: bool,
+    },
+
+    /// Write printable backup sheets for the seed or its SSKR shares
+    ///
+    /// Without --sskr, writes one sheet holding the seed's BIP-39 words.
+    /// With --sskr, splits the seed and writes one sheet per share holding
+    /// the share as Bytewords. Each sheet also carries the same data as a
+    /// UR and QR code, the seed fingerprint, the recovery policy, a
+    /// checksum, recovery instructions, and blank fields to fill in by
+    /// hand. Shares can be typed back in or scanned with `restore-seed`.
+    ///
+    /// Example:
+    ///   bip-keychain print-backup --sskr 2of3 --output-dir /media/airgap/sheets
+    ///   bip-keychain print-backup --format html --name Work --output-dir sheets
+    PrintBackup {
+        /// SSKR policy for share sheets (e.g. 2of3) [default: one seed sheet]
+        #[arg(long, value_name = "MofN")]
+        sskr: Option<String>,
+
+        /// Directory to write the sheets to
+        #[arg(long, value_name = "DIR")]
+        output_dir: PathBuf,
+
+        /// Sheet format
+        #[arg(long, value_enum, default_value = "text")]
+        format: SheetFormat,
+
+        /// Name printed on each sheet (and stored in the crypto-seed UR)
+        #[arg(long)]
+        name: Option<String>,
+    },
+
+    /// Derive BIP-85 child mnemonics, hex entropy, or WIF keys
+    ///
+    /// Uses the standard BIP-85 applications under the seed's master key, so
+    /// the output matches other BIP-85 tools (Coldcard, SeedSigner,
+    /// bipentropy) for the same seed and index. The result is printed to
+    /// stdout and its derivation path to stderr.
+    ///
+    /// Example:
+    ///   bip-keychain bip85 mnemonic --words 24 --index 1
+    ///   bip-keychain bip85 hex --bytes 32
+    ///   bip-keychain bip85 wif --index 3
+    Bip85 {
+        #[command(subcommand)]
+        command: Bip85Command,
+    },
+
+    /// Sign a file with an entity-derived Ed25519 key
+    ///
+    /// Writes a detached signature to stdout (or --output). The private key
+    /// never leaves the process.
+    ///
+    /// Example:
+    ///   bip-keychain sign entity.json release.tar.gz --format sshsig > release.tar.gz.sig
+    Sign {
+        /// Path to entity JSON file
+        #[arg(value_name = "ENTITY_JSON")]
+        entity_file: PathBuf,
+
+        /// File to sign
+        #[arg(value_name = "FILE")]
+        file: PathBuf,
+
+        /// Signature encoding
+        #[arg(long, value_enum, default_value = "sshsig")]
+        format: CliSignatureFormat,
+
+        /// SSHSIG namespace (e.g. "file", "git")
+        #[arg(long, default_value = signing::DEFAULT_NAMESPACE)]
+        namespace: String,
+
+        /// Write the signature here instead of stdout
+        #[arg(short, long, value_name = "PATH")]
+        output: Option<PathBuf>,
+
+        /// Parent entropy (hex encoded, optional)
+        #[arg(long, value_name = "HEX")]
+        parent_entropy: Option<String>,
+    },
+
+    /// Verify a detached signature against a public key
+    ///
+    /// The signature may be raw, hex, or SSHSIG; the encoding is detected
+    /// automatically. PUBKEY is an ssh-ed25519 line, 64 hex characters, or a
+    /// file containing either.
+    ///
+    /// Example:
+    ///   bip-keychain verify key.pub release.tar.gz release.tar.gz.sig
+    Verify {
+        /// Signer public key (or a file containing it)
+        #[arg(value_name = "PUBKEY")]
+        public_key: String,
+
+        /// Signed file
+        #[arg(value_name = "FILE")]
+        file: PathBuf,
+
+        /// Signature file
+        #[arg(value_name = "SIGNATURE")]
+        signature: PathBuf,
+
+        /// SSHSIG namespace the signature must use
+        #[arg(long, default_value = signing::DEFAULT_NAMESPACE)]
+        namespace: String,
+    },
+
+    /// Sign a message as an Ethereum account (EIP-191 personal_sign)
+    ///
+    /// Uses the secp256k1 key of the entity's BIP-32 node, proving control
+    /// of the entity's Ethereum address. Prints the 65-byte signature as
+    /// 0x-prefixed hex.
+    ///
+    /// Example:
+    ///   bip-keychain sign-eth entity.json "I control this address" --json
+    SignEth {
+        /// Path to entity JSON file
+        #[arg(value_name = "ENTITY_JSON")]
+        entity_file: PathBuf,
+
+        /// Message text to sign
+        #[arg(value_name = "MESSAGE", required_unless_present = "file")]
+        message: Option<String>,
+
+        /// Sign the contents of this file instead of MESSAGE
+        #[arg(long, value_name = "PATH", conflicts_with = "message")]
+        file: Option<PathBuf>,
+
+        /// Print address, message, and signature as JSON (the format
+        /// accepted by signature verifiers such as Etherscan's)
+        #[arg(long)]
+        json: bool,
+
+        /// Parent entropy (hex encoded, optional)
+        #[arg(long, value_name = "HEX")]
+        parent_entropy: Option<String>,
+    },
+
+    /// Encrypt a file so only an entity's key can decrypt it
+    ///
+    /// Uses a libsodium sealed box (crypto_box_seal) to the X25519 form of
+    /// the entity's derived key. Encrypting to --to ENTITY_JSON needs the
+    /// seed; --recipient takes the entity's public key instead.
+    ///
+    /// Example:
+    ///   bip-keychain encrypt --to examples/server-prod.json secrets.env
+    ///   bip-keychain encrypt --recipient server-prod.pub secrets.env -o secrets.env.enc
+    Encrypt {
+        /// File to encrypt
+        #[arg(value_name = "FILE")]
+        file: PathBuf,
+
+        /// Entity whose derived key can decrypt the file
+        #[arg(
+            long,
+            value_name = "ENTITY_JSON",
+            required_unless_present = "recipient",
+            conflicts_with = "recipient"
+        )]
+        to: Option<PathBuf>,
+
+        /// Recipient's Ed25519 public key (ssh-ed25519 line, 64 hex
+        /// characters, or a file containing either)
+        #[arg(long, value_name = "PUBKEY")]
+        recipient: Option<String>,
+
+        /// Output file [default: FILE.enc]
+        #[arg(short, long, value_name = "FILE")]
+        output: Option<PathBuf>,
+
+        /// Overwrite an existing output file
+        #[arg(long)]
+        force: bool,
+
+        /// Parent entropy (hex encoded, optional)
+        #[arg(long, value_name = "HEX", requires = "to")]
+        parent_entropy: Option<String>,
+    },
+
+    /// Decrypt a file encrypted to an entity with `encrypt`
+    ///
+    /// Example:
+    ///   bip-keychain decrypt examples/server-prod.json secrets.env.enc
+    Decrypt {
+        /// Path to entity JSON file
+        #[arg(value_name = "ENTITY_JSON")]
+        entity_file: PathBuf,
+
+        /// Encrypted file
+        #[arg(value_name = "FILE")]
+        file: PathBuf,
+
+        /// Output file, written with mode 0600 [default: FILE without .enc]
+        #[arg(short, long, value_name = "FILE")]
+        output: Option<PathBuf>,
+
+        /// Overwrite an existing output file
+        #[arg(long)]
+        force: bool,
+
+        /// Parent entropy (hex encoded, optional)
+        #[arg(long, value_name = "HEX")]
+        parent_entropy: Option<String>,
+    },
+
+    /// Threshold signing with FROST shares of an entity's Ed25519 key
+    ///
+    /// The entity's `frost` object sets the threshold and the number of
+    /// participants. `share` issues a participant's key package from the
+    /// seed; signers then run `commit` and `sign`, and anyone can
+    /// `aggregate` the shares into a signature for the entity's public key.
+    ///
+    /// Example:
+    ///   bip-keychain frost share treasury.json --participant 1 -o alice.json
+    ///   bip-keychain frost commit alice.json --nonces alice.nonces > alice.commit
+    ///   bip-keychain frost sign alice.json --nonces alice.nonces --message tx.bin \
+    ///     --commitment alice.commit --commitment bob.commit > alice.share
+    ///   bip-keychain frost aggregate group.pub --message tx.bin \
+    ///     --commitment alice.commit --commitment bob.commit \
+    ///     --share alice.share --share bob.share
+    Frost {
+        #[command(subcommand)]
+        command: FrostCommand,
+    },
+
+    /// Re-emit a gordian_envelope entity's envelope, optionally signed
+    ///
+    /// Decodes the entity's envelope, optionally signs its subject with the
+    /// entity's derived Ed25519 key, and prints ur:envelope.
+    ///
+    /// Example:
+    ///   bip-keychain envelope examples/gordian-envelope.json --sign
+    Envelope {
+        /// Path to entity JSON file (schema_type: gordian_envelope)
+        #[arg(value_name = "ENTITY_JSON")]
+        entity_file: PathBuf,
+
+        /// Add a 'signed' assertion made with the derived key
+        #[arg(long)]
+        sign: bool,
+
+        /// Parent entropy (hex encoded, optional)
+        #[arg(long, value_name = "HEX")]
+        parent_entropy: Option<String>,
+    },
+
+    /// Move entities between devices as ur:crypto-entity strings
+    ///
+    /// The entity is encoded as deterministic CBOR, optionally deflated, so
+    /// it fits in fewer QR codes than its JSON. Decoding prints entity JSON
+    /// that derives the same key.
+    ///
+    /// Example:
+    ///   bip-keychain ur encode examples/server-prod.json --deflate | qrencode -t ansiutf8
+    ///   bip-keychain ur decode UR:CRYPTO-ENTITY/... -o server-prod.json
+    Ur {
+        #[command(subcommand)]
+        command: UrCommand,
+    },
+
+    /// Generate entity JSON from a built-in template
+    ///
+    /// Example:
+    ///   bip-keychain template list
+    ///   bip-keychain template new git-signing \
+    ///     --set codeRepository=https://github.com/me/project --set name=project
+    Template {
+        #[command(subcommand)]
+        command: TemplateCommand,
+    },
+
+    /// Record revoked keys and publish signed revocation lists
+    ///
+    /// Revocations are stored in --revocations (default: revocations.json).
+    /// derive, derive-all, and sign refuse revoked keys unless
+    /// --allow-revoked is given.
+    ///
+    /// Example:
+    ///   bip-keychain revoke add examples/server-key.json --reason "host decommissioned"
+    ///   bip-keychain revoke export --signing-entity authority.json -o revoked.json
+    Revoke {
+        #[command(subcommand)]
+        command: RevokeCommand,
+    },
+
+    /// Issue and check signed derivation receipts
+    ///
+    /// A receipt binds an entity's hash, derivation path, and public key
+    /// with a timestamp, signed by the derived key, so an auditor holding
+    /// the entity file can check that a published key belongs to it.
+    ///
+    /// Example:
+    ///   bip-keychain receipt issue examples/server-prod.json -o server-prod.receipt.json
+    ///   bip-keychain receipt verify server-prod.receipt.json --entity examples/server-prod.json
+    Receipt {
+        #[command(subcommand)]
+        command: ReceiptCommand,
+    },
+
+    /// Submit documents to a transparency log and check log records
+    ///
+    /// Logs a receipt or key manifest in a Rekor transparency log, signed
+    /// by an entity's key, and checks the saved record's inclusion proof
+    /// offline. Submitting requires the transparency-log feature.
+    ///
+    /// Example:
+    ///   bip-keychain transparency submit keys.json --signing-entity authority.json
+    ///   bip-keychain transparency verify keys.json --public-key authority.pub
+    Transparency {
+        #[command(subcommand)]
+        command: TransparencyCommand,
+    },
+
+    /// Inspect the derivation audit log
+    ///
+    /// Example:
+    ///   bip-keychain --audit-log audit.jsonl derive entity.json
+    ///   bip-keychain audit verify audit.jsonl
+    Audit {
+        #[command(subcommand)]
+        command: AuditCommand,
+    },
+
+    /// Manage authorized_keys, known_hosts, and ssh_config for derived keys
+    ///
+    /// Entries are matched by key, so installing twice changes nothing and
+    /// removal finds the key whatever its comment. The comment is the
+    /// entity's purpose.
+    ///
+    /// Example:
+    ///   bip-keychain ssh install deploy.json --authorized-keys ~/.ssh/authorized_keys
+    ///   bip-keychain ssh install db1-host.json --known-hosts ~/.ssh/known_hosts --host db1.example.com
+    ///   bip-keychain ssh remove deploy.json --authorized-keys ~/.ssh/authorized_keys
+    ///   bip-keychain ssh config hosts.json --ssh-config ~/.ssh/config
+    Ssh {
+        #[command(subcommand)]
+        command: SshCommand,
+    },
+
+    /// Configure Git to sign commits with an entity-derived SSH key
+    ///
+    /// Writes the entity's SSH public key, adds it to an allowed_signers
+    /// file, and sets gpg.format=ssh, user.signingkey,
+    /// gpg.ssh.allowedSignersFile, and commit.gpgsign. The private key is
+    /// never written; load it with `derive --add-to-agent` before committing.
+    ///
+    /// Example:
+    ///   bip-keychain git-setup examples/github-repo.json --email dev@example.com
+    GitSetup {
+        /// Path to entity JSON file
+        #[arg(value_name = "ENTITY_JSON")]
+        entity_file: PathBuf,
+
+        /// Signer identity for allowed_signers (default: git config user.email)
+        #[arg(long)]
+        email: Option<String>,
+
+        /// Write to global Git config instead of the current repository
+        #[arg(long)]
+        global: bool,
+
+        /// Directory for the public key and allowed_signers file
+        /// (default: ~/.config/bip-keychain)
+        #[arg(long, value_name = "DIR")]
+        key_dir: Option<PathBuf>,
+
+        /// Parent entropy (hex encoded, optional)
+        #[arg(long, value_name = "HEX")]
+        parent_entropy: Option<String>,
+    },
+
+    /// Write a sigstore cosign key pair derived from an entity
+    ///
+    /// Writes <PREFIX>.key, the private key encrypted with the password in
+    /// COSIGN_PASSWORD (as `cosign generate-key-pair` does), and
+    /// <PREFIX>.pub. Both can be deleted after signing and written again
+    /// from the seed.
+    ///
+    /// Example:
+    ///   COSIGN_PASSWORD=... bip-keychain cosign examples/software-app-signing.json
+    ///   cosign sign --key cosign.key registry.example.com/app:1.0
+    Cosign {
+        /// Path to entity JSON file
+        #[arg(value_name = "ENTITY_JSON")]
+        entity_file: PathBuf,
+
+        /// File name prefix for the .key and .pub files
+        #[arg(long, value_name = "PREFIX", default_value = "cosign")]
+        output_key_prefix: PathBuf,
+
+        /// Overwrite existing key files
+        #[arg(long)]
+        force: bool,
+
+        /// Parent entropy (hex encoded, optional)
+        #[arg(long, value_name = "HEX")]
+        parent_entropy: Option<String>,
+    },
+
+    /// Run an SSH agent serving keys for the entities in a manifest
+    ///
+    /// Listens on a unix socket and answers identity and sign requests.
+    /// Keys are derived on demand and never written to disk.
+    ///
+    /// Example:
+    ///   bip-keychain agent manifest.json --socket /tmp/bip-keychain.sock &
+    ///   SSH_AUTH_SOCK=/tmp/bip-keychain.sock ssh host
+    #[cfg(unix)]
+    Agent {
+        /// Path to manifest JSON listing entity files
+        #[arg(value_name = "MANIFEST_JSON")]
+        manifest: PathBuf,
+
+        /// Unix socket path to listen on (must not exist)
+        #[arg(long, value_name = "PATH")]
+        socket: PathBuf,
+
+        /// Parent entropy (hex encoded, optional)
+        #[arg(long, value_name = "HEX")]
+        parent_entropy: Option<String>,
+    },
+}
+
+#[derive(Subcommand)]
+enum TemplateCommand {
+    /// List the available templates
+    List,
+
+    /// Print entity JSON for a template
+    New {
+        /// Template kind (see `template list`)
+        #[arg(value_name = "KIND")]
+        kind: String,
+
+        /// Replace an entity property (string value); repeatable
+        #[arg(long = "set", value_name = "KEY=VALUE", value_parser = parse_property)]
+        properties: Vec<(String, String)>,
+
+        /// Spread the entity across this many path levels (2 uses 8 hash
+        /// bytes, about 62 bits, instead of 31)
+        #[arg(long, value_name = "N", value_parser = clap::value_parser!(u8).range(1..=3))]
+        index_levels: Option<u8>,
+
+        /// Write to this file instead of stdout (must not exist)
+        #[arg(short, long, value_name = "FILE")]
+        output: Option<PathBuf>,
+    },
+}
+
+#[derive(Subcommand)]
+enum UrCommand {
+    /// Print an entity as a ur:crypto-entity string
+    Encode {
+        /// Path to entity JSON file
+        #[arg(value_name = "ENTITY_JSON")]
+        entity_file: PathBuf,
+
+        /// Compress the CBOR with DEFLATE
+        #[arg(long)]
+        deflate: bool,
+    },
+
+    /// Print the entity JSON in a ur:crypto-entity string
+    Decode {
+        /// ur:crypto-entity string
+        #[arg(value_name = "UR")]
+        ur: String,
+
+        /// Write to this file instead of stdout (must not exist)
+        #[arg(short, long, value_name = "FILE")]
+        output: Option<PathBuf>,
+    },
+}
+
+#[derive(Subcommand)]
+enum Bip85Command {
+    /// Child BIP-39 mnemonic (application 39')
+    Mnemonic {
+        /// Number of words (12, 15, 18, 21, or 24)
+        #[arg(long, default_value_t = 12, value_parser = parse_word_count)]
+        words: usize,
+
+        /// Wordlist of the child mnemonic
+        #[arg(long, value_enum, default_value = "english")]
+        child_language: MnemonicLanguage,
+
+        /// Child index, below 2^31
+        #[arg(long, default_value_t = 0, value_parser = clap::value_parser!(u32).range(..1 << 31))]
+        index: u32,
+    },
+
+    /// Hex entropy (application 128169')
+    Hex {
+        /// Number of bytes, 16 to 64
+        #[arg(long, default_value_t = 64, value_parser = clap::value_parser!(u8).range(16..=64))]
+        bytes: u8,
+
+        /// Child index, below 2^31
+        #[arg(long, default_value_t = 0, value_parser = clap::value_parser!(u32).range(..1 << 31))]
+        index: u32,
+    },
+
+    /// Compressed mainnet WIF key for a Bitcoin Core HD seed (application 2')
+    Wif {
+        /// Child index, below 2^31
+        #[arg(long, default_value_t = 0, value_parser = clap::value_parser!(u32).range(..1 << 31))]
+        index: u32,
+    },
+}
+
+#[derive(Subcommand)]
+enum RevokeCommand {
+    /// Revoke the key for an entity, or a public key
+    Add {
+        /// Entity whose derived key is revoked
+        #[arg(
+            value_name = "ENTITY_JSON",
+            required_unless_present = "public_key",
+            conflicts_with = "public_key"
+        )]
+        entity_file: Option<PathBuf>,
+
+        /// Revoke this public key instead (ssh-ed25519 line or 64 hex characters)
+        #[arg(long, value_name = "PUBKEY")]
+        public_key: Option<String>,
+
+        /// Why the key is revoked
+        #[arg(long)]
+        reason: String,
+
+        /// Parent entropy (hex encoded, optional)
+        #[arg(long, value_name = "HEX", requires = "entity_file")]
+        parent_entropy: Option<String>,
+    },
+
+    /// List revoked keys
+    List,
+
+    /// Sign the revocation list for publication
+    Export {
+        /// Entity whose derived key signs the list
+        #[arg(long, value_name = "ENTITY_JSON")]
+        signing_entity: PathBuf,
+
+        /// Write to this file instead of stdout
+        #[arg(short, long, value_name = "FILE")]
+        output: Option<PathBuf>,
+
+        /// Parent entropy (hex encoded, optional)
+        #[arg(long, value_name = "HEX")]
+        parent_entropy: Option<String>,
+    },
+
+    /// Check a published revocation list and print its entries
+    Verify {
+        /// Signed revocation list from `revoke export`
+        #[arg(value_name = "SIGNED_JSON")]
+        signed_list: PathBuf,
+
+        /// Expected signer public key (or a file containing it)
+        #[arg(value_name = "PUBKEY")]
+        public_key: String,
+    },
+}
+
+#[derive(Subcommand)]
+enum ReceiptCommand {
+    /// Derive the entity's key and print a receipt signed by it
+    Issue {
+        /// Path to entity JSON file
+        #[arg(value_name = "ENTITY_JSON")]
+        entity_file: PathBuf,
+
+        /// Write to this file instead of stdout
+        #[arg(short, long, value_name = "FILE")]
+        output: Option<PathBuf>,
+
+        /// Parent entropy (hex encoded, optional)
+        #[arg(long, value_name = "HEX")]
+        parent_entropy: Option<String>,
+    },
+
+    /// Check a receipt's signature, and optionally its entity and key
+    Verify {
+        /// Receipt from `receipt issue`
+        #[arg(value_name = "RECEIPT_JSON")]
+        receipt: PathBuf,
+
+        /// Entity JSON file the receipt must have been issued for
+        #[arg(long, value_name = "ENTITY_JSON")]
+        entity: Option<PathBuf>,
+
+        /// Public key the receipt must name (or a file containing it)
+        #[arg(long, value_name = "PUBKEY")]
+        public_key: Option<String>,
+
+        /// Parent entropy used to compute the entity's path (hex encoded)
+        #[arg(long, value_name = "HEX", requires = "entity")]
+        parent_entropy: Option<String>,
+    },
+}
+
+#[derive(Subcommand)]
+enum SshCommand {
+    /// Add the entity's public key unless it is already listed
+    Install {
+        #[command(flatten)]
+        target: SshTargetArgs,
+    },
+
+    /// Remove the entity's public key wherever it is listed
+    Remove {
+        #[command(flatten)]
+        target: SshTargetArgs,
+    },
+
+    /// Generate ssh_config Host blocks for the entities in a manifest
+    ///
+    /// Writes each entity's public key to --key-dir and a Host block with
+    /// IdentityFile pointing at it, for the entry's `host` (default: the
+    /// entity's fqdn). Load the private keys into an agent (`derive
+    /// --add-to-agent`, or `agent` with --identity-agent).
+    Config {
+        /// Path to manifest JSON listing host entities
+        #[arg(value_name = "MANIFEST_JSON")]
+        manifest: PathBuf,
+
+        /// ssh_config file whose bip-keychain section is replaced (created
+        /// if missing, e.g. ~/.ssh/config); without it the section is printed
+        #[arg(long, value_name = "FILE")]
+        ssh_config: Option<PathBuf>,
+
+        /// Directory for the public key files (default: ~/.config/bip-keychain)
+        #[arg(long, value_name = "DIR")]
+        key_dir: Option<PathBuf>,
+
+        /// Agent socket for IdentityAgent, e.g. a `bip-keychain agent --socket`
+        #[arg(long, value_name = "SOCKET")]
+        identity_agent: Option<String>,
+
+        /// Parent entropy (hex encoded, optional)
+        #[arg(long, value_name = "HEX")]
+        parent_entropy: Option<String>,
+    },
+}
+
+#[derive(clap::Args)]
+struct SshTargetArgs {
+    /// Path to entity JSON file
+    #[arg(value_name = "ENTITY_JSON")]
+    entity_file: PathBuf,
+
+    /// authorized_keys file to edit (created if missing)
+    #[arg(
+        long,
+        value_name = "FILE",
+        required_unless_present = "known_hosts",
+        conflicts_with = "known_hosts"
+    )]
+    authorized_keys: Option<PathBuf>,
+
+    /// known_hosts file to edit, treating the key as a host key
+    #[arg(long, value_name = "FILE", requires = "host")]
+    known_hosts: Option<PathBuf>,
+
+    /// Host name for --known-hosts (repeatable, e.g. name and address)
+    #[arg(long, value_name = "HOST", requires = "known_hosts")]
+    host: Vec<String>,
+
+    /// Parent entropy (hex encoded, optional)
+    #[arg(long, value_name = "HEX")]
+    parent_entropy: Option<String>,
+}
+
+#[derive(Subcommand)]
+enum FrostCommand {
+    /// Issue a participant's key package (secret; mode 0600 with --output)
+    Share {
+        /// Group entity JSON file (with a `frost` object)
+        #[arg(value_name = "ENTITY_JSON")]
+        entity_file: PathBuf,
+
+        /// Participant number, 1 to max_signers
+        #[arg(long, value_name = "N")]
+        participant: u16,
+
+        /// Write the key package here instead of stdout (must not exist)
+        #[arg(short, long, value_name = "FILE")]
+        output: Option<PathBuf>,
+
+        /// Parent entropy (hex encoded, optional)
+        #[arg(long, value_name = "HEX")]
+        parent_entropy: Option<String>,
+    },
+
+    /// Round one: create nonces and print the commitment to share
+    Commit {
+        /// Key package from `frost share`
+        #[arg(value_name = "KEY_PACKAGE")]
+        key_package: PathBuf,
+
+        /// Write the secret nonces here (must not exist)
+        #[arg(long, value_name = "FILE")]
+        nonces: PathBuf,
+    },
+
+    /// Round two: print this participant's signature share
+    ///
+    /// The nonces file is deleted, since nonces must never be reused.
+    Sign {
+        /// Key package from `frost share`
+        #[arg(value_name = "KEY_PACKAGE")]
+        key_package: PathBuf,
+
+        /// Nonces from `frost commit`
+        #[arg(long, value_name = "FILE")]
+        nonces: PathBuf,
+
+        /// File to sign
+        #[arg(long, value_name = "FILE")]
+        message: PathBuf,
+
+        /// Commitment of each signer, including this one (repeatable)
+        #[arg(long = "commitment", value_name = "FILE", required = true)]
+        commitments: Vec<PathBuf>,
+    },
+
+    /// Combine signature shares into an Ed25519 signature (hex)
+    Aggregate {
+        /// Group public key (the entity's key; or a file containing it)
+        #[arg(value_name = "PUBKEY")]
+        public_key: String,
+
+        /// Signed file
+        #[arg(long, value_name = "FILE")]
+        message: PathBuf,
+
+        /// Commitment of each signer (repeatable)
+        #[arg(long = "commitment", value_name = "FILE", required = true)]
+        commitments: Vec<PathBuf>,
+
+        /// Signature share of each signer (repeatable)
+        #[arg(long = "share", value_name = "FILE", required = true)]
+        shares: Vec<PathBuf>,
+    },
+}
+
+#[derive(Subcommand)]
+enum TransparencyCommand {
+    /// Sign a document and submit it to the log
+    Submit {
+        /// Document to log, e.g. a receipt or signed key manifest
+        #[arg(value_name = "FILE")]
+        document: PathBuf,
+
+        /// Entity whose derived key signs the log entry
+        #[arg(long, value_name = "ENTITY_JSON")]
+        signing_entity: PathBuf,
+
+        /// Rekor server [default: --transparency-log, else
+        /// https://rekor.sigstore.dev]
+        #[arg(long, value_name = "URL")]
+        log_url: Option<String>,
+
+        /// Write the log record here [default: <FILE>.tlog.json]
+        #[arg(short, long, value_name = "FILE")]
+        output: Option<PathBuf>,
+
+        /// Parent entropy (hex encoded, optional)
+        #[arg(long, value_name = "HEX")]
+        parent_entropy: Option<String>,
+    },
+
+    /// Check that a log record covers a document and its inclusion proof
+    Verify {
+        /// Document the record was saved for
+        #[arg(value_name = "FILE")]
+        document: PathBuf,
+
+        /// Log record from `submit` [default: <FILE>.tlog.json]
+        #[arg(long, value_name = "RECORD_JSON")]
+        record: Option<PathBuf>,
+
+        /// Public key the entry must be signed by (or a file containing it)
+        #[arg(long, value_name = "PUBKEY")]
+        public_key: Option<String>,
+
+        /// PEM public key of the log [default: the key of rekor.sigstore.dev]
+        #[arg(long, value_name = "PEM_FILE")]
+        log_key: Option<PathBuf>,
+    },
+}
+
+#[derive(Subcommand)]
+enum AuditCommand {
+    /// Check that no record has been modified, removed, or reordered
+    Verify {
+        /// Audit log written with --audit-log
+        #[arg(value_name = "LOG")]
+        log: PathBuf,
+    },
+}
+
+fn parse_property(arg: &str) -> std::result::Result<(String, String), String> {
+    arg.split_once('=')
+        .map(|(key, value)| (key.to_string(), value.to_string()))
+        .ok_or_else(|| format!("expected KEY=VALUE, got '{}'", arg))
+}
+
+fn parse_word_count(arg: &str) -> std::result::Result<usize, String> {
+    match arg.parse() {
+        Ok(words @ (12 | 15 | 18 | 21 | 24)) => Ok(words),
+        _ => Err(format!("expected 12, 15, 18, 21, or 24, got '{}'", arg)),
+    }
+}
+
+#[derive(Debug, Clone, clap::ValueEnum)]
+enum CliOutputFormat {
+    /// Raw 32-byte seed as hex
+    Seed,
+    /// Public key as hex (Ed25519 unless the entity sets key_type)
+    PublicKey,
+    /// Private key as hex (use with caution!)
+    PrivateKey,
+    /// Public key as PEM (SubjectPublicKeyInfo, for OpenSSL)
+    PublicKeyPem,
+    /// Private key as PEM (PKCS#8, for OpenSSL)
+    PrivateKeyPem,
+    /// OpenSSH public key format (default for Ed25519 entities)
+    Ssh,
+    /// SSHFP DNS record (SHA-256) for a host key
+    Sshfp,
+    /// TLSA record pinning the key for DANE (see --tlsa-selector)
+    Tlsa,
+    /// PKCS#10 certificate signing request (PEM) for the entity
+    Csr,
+    /// Public key as a JWK (e.g. an ACME account key)
+    Jwk,
+    /// Private key as a JWK, for ACME clients (use with caution!)
+    JwkPrivate,
+    /// JWK thumbprint, as in ACME key authorizations
+    JwkThumbprint,
+    /// TUF / in-toto public key object with its key ID
+    TufKey,
+    /// TUF / in-toto key object with the private key (use with caution!)
+    TufKeyPrivate,
+    /// TUF / in-toto key ID
+    TufKeyid,
+    /// XMPP OMEMO identity key and fingerprint
+    Omemo,
+    /// OMEMO identity key with its private keys (use with caution!)
+    OmemoPrivate,
+    /// Tor v3 onion address; with --output DIR, writes the onion service key files
+    TorHiddenService,
+    /// libp2p / IPFS PeerId (CIDv1)
+    PeerId,
+    /// Kubo Identity JSON with the libp2p private key (use with caution!)
+    Libp2pPrivate,
+    /// DNSSEC DNSKEY record for the entity's zone
+    Dnskey,
+    /// DNSSEC DS record for the parent zone
+    Ds,
+    /// BIND private key file for the DNSSEC key (use with caution!)
+    DnssecPrivate,
+    /// GPG-compatible public key info (for Git signing)
+    Gpg,
+    /// JSON with all key data and metadata
+    Json,
+    /// Deterministic password (see --password-length, --password-charset)
+    Password,
+    /// otpauth:// URI for a TOTP secret (see the totp command)
+    Otpauth,
+}
+
+impl From<CliOutputFormat> for OutputFormat {
+    fn from(cli_format: CliOutputFormat) -> Self {
+        match cli_format {
+            CliOutputFormat::Seed => OutputFormat::HexSeed,
+            CliOutputFormat::PublicKey => OutputFormat::Ed25519PublicHex,
+            CliOutputFormat::PrivateKey => OutputFormat::Ed25519PrivateHex,
+            CliOutputFormat::PublicKeyPem => OutputFormat::Ed25519PublicPem,
+            CliOutputFormat::PrivateKeyPem => OutputFormat::Ed25519PrivatePem,
+            CliOutputFormat::Ssh => OutputFormat::SshPublicKey,
+            CliOutputFormat::Sshfp => OutputFormat::SshfpRecord,
+            CliOutputFormat::Tlsa => OutputFormat::TlsaRecord,
+            CliOutputFormat::Csr => OutputFormat::Csr,
+            CliOutputFormat::Jwk => OutputFormat::Jwk,
+            CliOutputFormat::JwkPrivate => OutputFormat::JwkPrivate,
+            CliOutputFormat::JwkThumbprint => OutputFormat::JwkThumbprint,
+            CliOutputFormat::TufKey => OutputFormat::TufKey,
+            CliOutputFormat::TufKeyPrivate => OutputFormat::TufKeyPrivate,
+            CliOutputFormat::TufKeyid => OutputFormat::TufKeyid,
+            CliOutputFormat::Omemo => OutputFormat::OmemoIdentity,
+            CliOutputFormat::OmemoPrivate => OutputFormat::OmemoPrivate,
+            CliOutputFormat::TorHiddenService => OutputFormat::TorHiddenService,
+            CliOutputFormat::PeerId => OutputFormat::Libp2pPeerId,
+            CliOutputFormat::Libp2pPrivate => OutputFormat::Libp2pPrivate,
+            CliOutputFormat::Dnskey => OutputFormat::DnssecDnskey,
+            CliOutputFormat::Ds => OutputFormat::DnssecDs,
+            CliOutputFormat::DnssecPrivate => OutputFormat::DnssecPrivate,
+            CliOutputFormat::Gpg => OutputFormat::GpgPublicKey,
+            CliOutputFormat::Json => OutputFormat::Json,
+            CliOutputFormat::Password => OutputFormat::Password,
+            CliOutputFormat::Otpauth => OutputFormat::Otpauth,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+enum SeedQrOutput {
+    /// QR code drawn with Unicode blocks
+    Terminal,
+    /// SVG image, 4mm per module
+    Svg,
+    /// The encoded data: digits (standard) or hex (compact)
+    Payload,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+enum BackupQrOutput {
+    /// Unicode blocks, shown one at a time or written to .txt files
+    Terminal,
+    /// One PNG image per code (needs --output-dir)
+    Png,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+enum SheetFormat {
+    /// Plain text for a monospace font
+    Text,
+    /// Self-contained HTML page, ready to print
+    Html,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+enum CliSignatureFormat {
+    /// Raw 64-byte signature (binary)
+    Raw,
+    /// Signature as 128 hex characters
+    Hex,
+    /// Armored OpenSSH signature (ssh-keygen -Y verify compatible)
+    Sshsig,
+}
+
+impl From<CliSignatureFormat> for SignatureFormat {
+    fn from(cli_format: CliSignatureFormat) -> Self {
+        match cli_format {
+            CliSignatureFormat::Raw => SignatureFormat::Raw,
+            CliSignatureFormat::Hex => SignatureFormat::Hex,
+            CliSignatureFormat::Sshsig => SignatureFormat::SshSig,
+        }
+    }
+}
+
+fn main() -> ExitCode {
+    match run() {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(error) => {
+            eprintln!("Error: {:?}", error);
+            ExitCode::from(exit_code(&error))
+        }
+    }
+}
+
+/// Exit code for a failed command: the [`ErrorKind`] exit code of the
+/// underlying library error, or 1
+fn exit_code(error: &anyhow::Error) -> u8 {
+    error
+        .chain()
+        .find_map(|cause| {
+            cause
+                .downcast_ref::<BipKeychainError>()
+                .map(BipKeychainError::kind)
+                .or_else(|| cause.is::<std::io::Error>().then_some(ErrorKind::Io))
+        })
+        .map_or(1, ErrorKind::exit_code)
+}
+
+fn run() -> Result<()> {
+    let mut cli = Cli::parse();
+
+    let config = match &cli.config {
+        Some(path) => Config::load(path)?,
+        None => Config::load_default()?,
+    };
+    cli.seed.apply_config(&config);
+    cli.key_use.apply_config(&config);
+    let _ = CONFIG.set(config);
+
+    match cli.command {
+        Commands::Derive {
+            entity_file,
+            parent_entropy,
+            format,
+            output,
+            force,
+            add_to_agent,
+            lifetime,
+            password,
+            tlsa,
+        } => derive_command(
+            &cli.seed,
+            &cli.key_use,
+            entity_file,
+            parent_entropy,
+            format,
+            output.map(|path| (path, force)),
+            add_to_agent.then_some(lifetime),
+            password,
+            tlsa,
+        ),
+        Commands::DeriveAll {
+            manifest,
+            parent_entropy,
+            force,
+        } => derive_all_command(&cli.seed, &cli.key_use, manifest, parent_entropy, force),
+        Commands::DeriveDir {
+            dir,
+            out,
+            format,
+            parent_entropy,
+            force,
+        } => derive_dir_command(
+            &cli.seed,
+            &cli.key_use,
+            &dir,
+            &out,
+            format.map(Into::into),
+            parent_entropy,
+            force,
+        ),
+        Commands::GenerateSeed {
+            words,
+            sskr,
+            output_dir,
+            reveal,
+        } => generate_seed_command(&cli.seed, words, sskr, output_dir, reveal),
+        Commands::CheckSeed => check_seed_command(&cli.seed),
+        Commands::VerifyBackup { count } => verify_backup_command(&cli.seed, count),
+        Commands::Fingerprint {
+            entity_file,
+            parent_entropy,
+        } => fingerprint_command(&cli.seed, entity_file, parent_entropy),
+        Commands::Inspect {
+            entity_file,
+            parent_entropy,
+        } => inspect_command(entity_file, parent_entropy),
+        Commands::WhichEntity {
+            pubkey,
+            entities,
+            parent_entropy,
+        } => which_entity_command(&cli.seed, &pubkey, &entities, parent_entropy),
+        Commands::VerifyBinding {
+            entity_file,
+            pubkey,
+            parent_entropy,
+        } => verify_binding_command(&cli.seed, &entity_file, &pubkey, parent_entropy),
+        Commands::ExportManifest {
+            manifest,
+            signing_entity,
+            output,
+            parent_entropy,
+        } => export_manifest_command(
+            &cli.seed,
+            &cli.key_use,
+            &manifest,
+            &signing_entity,
+            output,
+            parent_entropy,
+        ),
+        Commands::VerifyManifest {
+            signed_manifest,
+            public_key,
+            entity,
+        } => verify_manifest_command(&signed_manifest, &public_key, entity),
+        Commands::GenVectors { output } => gen_vectors_command(output),
+        Commands::Compat { reference } => compat_command(reference),
+        Commands::Totp {
+            entity_file,
+            enroll,
+            at,
+            parent_entropy,
+        } => totp_command(
+            &cli.seed,
+            &cli.key_use,
+            entity_file,
+            enroll,
+            at,
+            parent_entropy,
+        ),
+        #[cfg(feature = "os-keychain")]
+        Commands::StoreSeed { delete } => store_seed_command(&cli.seed, delete),
+        Commands::VerifyShares { shares } => verify_shares_command(shares),
+        Commands::BackupSeed {
+            sskr,
+            name,
+            qr,
+            output_dir,
+        } => backup_seed_command(&cli.seed, &sskr, name, qr, output_dir),
+        Commands::RestoreSeed { urs, check } => restore_seed_command(&cli.seed, &urs, check),
+        Commands::ExportSeedqr {
+            compact,
+            format,
+            output,
+        } => export_seedqr_command(&cli.seed, compact, format, output),
+        Commands::ImportSeedqr { input, check } => {
+            import_seedqr_command(&cli.seed, input.as_deref(), check)
+        }
+        Commands::PrintBackup {
+            sskr,
+            output_dir,
+            format,
+            name,
+        } => print_backup_command(&cli.seed, sskr.as_deref(), &output_dir, format, name),
+        Commands::Bip85 { command } => bip85_command(&cli.seed, command),
+        Commands::Sign {
+            entity_file,
+            file,
+            format,
+            namespace,
+            output,
+            parent_entropy,
+        } => sign_command(
+            &cli.seed,
+            &cli.key_use,
+            entity_file,
+            file,
+            format,
+            &namespace,
+            output,
+            parent_entropy,
+        ),
+        Commands::Verify {
+            public_key,
+            file,
+            signature,
+            namespace,
+        } => verify_command(&public_key, file, signature, &namespace),
+        Commands::SignEth {
+            entity_file,
+            message,
+            file,
+            json,
+            parent_entropy,
+        } => sign_eth_command(
+            &cli.seed,
+            &cli.key_use,
+            entity_file,
+            message,
+            file,
+            json,
+            parent_entropy,
+        ),
+        Commands::Encrypt {
+            file,
+            to,
+            recipient,
+            output,
+            force,
+            parent_entropy,
+        } => encrypt_command(
+            &cli.seed,
+            &cli.key_use,
+            file,
+            to,
+            recipient,
+            output,
+            force,
+            parent_entropy,
+        ),
+        Commands::Decrypt {
+            entity_file,
+            file,
+            output,
+            force,
+            parent_entropy,
+        } => decrypt_command(
+            &cli.seed,
+            &cli.key_use,
+            entity_file,
+            file,
+            output,
+            force,
+            parent_entropy,
+        ),
+        Commands::Envelope {
+            entity_file,
+            sign,
+            parent_entropy,
+        } => envelope_command(&cli.seed, entity_file, sign, parent_entropy),
+        Commands::Ur { command } => ur_command(command),
+        Commands::Template { command } => template_command(command),
+        Commands::Transparency { command } => {
+            transparency_command(&cli.seed, &cli.key_use, command)
+        }
+        Commands::Audit { command } => audit_command(command),
+        Commands::Frost { command } => frost_command(&cli.seed, &cli.key_use, command),
+        Commands::Ssh { command } => ssh_command(&cli.seed, &cli.key_use, command),
+        Commands::Revoke { command } => revoke_command(&cli.seed, &cli.key_use, command),
+        Commands::Receipt { command } => receipt_command(&cli.seed, &cli.key_use, command),
+        Commands::GitSetup {
+            entity_file,
+            email,
+            global,
+            key_dir,
+            parent_entropy,
+        } => git_setup_command(
+            &cli.seed,
+            entity_file,
+            email,
+            global,
+            key_dir,
+            parent_entropy,
+        ),
+        Commands::Cosign {
+            entity_file,
+            output_key_prefix,
+            force,
+            parent_entropy,
+        } => cosign_command(
+            &cli.seed,
+            &cli.key_use,
+            entity_file,
+            output_key_prefix,
+            force,
+            parent_entropy,
+        ),
+        #[cfg(unix)]
+        Commands::Agent {
+            manifest,
+            socket,
+            parent_entropy,
+        } => agent_command(&cli.seed, &cli.key_use, manifest, socket, parent_entropy),
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+fn derive_command(
+    seed: &SeedArgs,
+    key_use: &KeyUseArgs,
+    entity_file: PathBuf,
+    parent_entropy_hex: Option<String>,
+    formats: Vec<CliOutputFormat>,
+    output_file: Option<(PathBuf, bool)>,
+    agent_lifetime: Option<u32>,
+    password: PasswordArgs,
+    tlsa: TlsaArgs,
+) -> Result<()> {
+    // Read and parse entity JSON file
+    let mut key_derivation = load_entity(&entity_file)?;
+    password.apply(&mut key_derivation);
+    tlsa.apply(&mut key_derivation);
+
+    // Create keychain from seed phrase
+    let keychain = load_keychain(seed)?;
+
+    // Parse parent entropy (or use default)
+    let parent_entropy = parse_parent_entropy(parent_entropy_hex)?;
+
+    // Derive key
+    let derived_key = derive_key_from_entity(&keychain, &key_derivation, &parent_entropy)
+        .context("Failed to derive key from entity")?;
+
+    let mut formats: Vec<OutputFormat> = formats.into_iter().map(Into::into).collect();
+    if formats.is_empty() {
+        formats.extend(key_derivation.output_format);
+    }
+    if formats.is_empty() {
+        formats = config().formats();
+    }
+    if formats.is_empty() {
+        formats.push(
+            key_derivation
+                .derivation_config
+                .key_type
+                .default_output_format(),
+        );
+    }
+    let usage = match agent_lifetime {
+        Some(_) => "agent".to_string(),
+        None => formats.iter().map(serde_name).collect::<Vec<_>>().join(","),
+    };
+    check_key_use(
+        key_use,
+        &key_derivation,
+        &parent_entropy,
+        &derived_key,
+        &entity_file,
+        &usage,
+    )?;
+
+    if let Some(lifetime) = agent_lifetime {
+        return add_to_agent(&derived_key, &key_derivation, lifetime);
+    }
+
+    if let (Some((dir, force)), [OutputFormat::TorHiddenService]) = (&output_file, &formats[..]) {
+        return write_onion_service_dir(&derived_key, dir, *force);
+    }
+
+    // Format and output
+    let output = match formats[..] {
+        [format] => format_key(&derived_key, &key_derivation, format),
+        _ => format_keys(&derived_key, &key_derivation, &formats),
+    }
+    .context("Failed to format key output")?;
+    let output = Zeroizing::new(output + "\n");
+
+    match output_file {
+        Some((path, force)) => {
+            let path = match &config().output_dir {
+                Some(output_dir) if path.is_relative() => output_dir.join(path),
+                _ => path,
+            };
+            let secret = formats.iter().any(|format| format.is_secret());
+            write_key_file(&path, output.as_bytes(), secret, force)?;
+            eprintln!("wrote {}", path.display());
+        }
+        None => print!("{}", output.as_str()),
+    }
+
+    Ok(())
+}
+
+fn derive_all_command(
+    seed: &SeedArgs,
+    key_use: &KeyUseArgs,
+    manifest_file: PathBuf,
+    parent_entropy_hex: Option<String>,
+    force: bool,
+) -> Result<()> {
+    let manifest = Manifest::load(&manifest_file)
+        .with_context(|| format!("Failed to load manifest: {}", manifest_file.display()))?;
+    let keychain = load_keychain(seed)?;
+    let parent_entropy = parse_parent_entropy(parent_entropy_hex)?;
+    provision_entries(
+        key_use,
+        &keychain,
+        &parent_entropy,
+        &manifest.entities,
+        force,
+    )?;
+    Ok(())
+}
+
+fn derive_dir_command(
+    seed: &SeedArgs,
+    key_use: &KeyUseArgs,
+    dir: &Path,
+    out: &Path,
+    format: Option<OutputFormat>,
+    parent_entropy_hex: Option<String>,
+    force: bool,
+) -> Result<()> {
+    let mut files = Vec::new();
+    let skip = fs::canonicalize(out).ok();
+    find_entity_files(dir, skip.as_deref(), &mut files)
+        .with_context(|| format!("Failed to read directory: {}", dir.display()))?;
+    if files.is_empty() {
+        anyhow::bail!("No entity files (*.json) found in {}", dir.display());
+    }
+
+    // Other JSON files (manifests, package.json, ...) are skipped, as in
+    // which-entity
+    let mut entries = Vec::new();
+    let mut skipped = 0;
+    for file in files {
+        let mut entry = ManifestEntry {
+            file,
+            comment: None,
+            format,
+            output: None,
+            host: None,
+        };
+        let key_derivation = match entry.load_derivation() {
+            Ok(key_derivation) => key_derivation,
+            Err(e) => {
+                let reason = e.to_string();
+                let reason = reason.lines().next().unwrap_or_default();
+                eprintln!("skipping {}: {}", entry.file.display(), reason);
+                skipped += 1;
+                continue;
+            }
+        };
+        let relative = entry.file.strip_prefix(dir).unwrap_or(&entry.file);
+        let extension = serde_name(&entry.output_format(&key_derivation));
+        entry.output = Some(out.join(relative).with_extension(extension));
+        entries.push(entry);
+    }
+    if entries.is_empty() {
+        anyhow::bail!(
+            "No entity files found in {} ({} JSON file(s) skipped)",
+            dir.display(),
+            skipped
+        );
+    }
+
+    let keychain = load_keychain(seed)?;
+    let parent_entropy = parse_parent_entropy(parent_entropy_hex)?;
+    let summary = provision_entries(key_use, &keychain, &parent_entropy, &entries, force)?;
+    eprintln!(
+        "{} entities: {} written, {} unchanged, {} skipped",
+        entries.len(),
+        summary.written,
+        summary.unchanged,
+        skipped
+    );
+    Ok(())
+}
+
+/// `*.json` files below `dir` in sorted order, skipping hidden entries and
+/// the (canonical) directory `skip`
+fn find_entity_files(dir: &Path, skip: Option<&Path>, files: &mut Vec<PathBuf>) -> Result<()> {
+    let mut paths = fs::read_dir(dir)?
+        .map(|entry| entry.map(|entry| entry.path()))
+        .collect::<std::io::Result<Vec<_>>>()?;
+    paths.sort();
+
+    for path in paths {
+        let hidden = path
+            .file_name()
+            .is_some_and(|name| name.to_string_lossy().starts_with('.'));
+        if hidden {
+            continue;
+        }
+        if path.is_dir() {
+            if skip.is_none() || fs::canonicalize(&path).ok().as_deref() != skip {
+                find_entity_files(&path, skip, files)?;
+            }
+        } else if path
+            .extension()
+            .is_some_and(|extension| extension == "json")
+        {
+            files.push(path);
+        }
+    }
+    Ok(())
+}
+
+/// Files written and left unchanged by [`provision_entries`]
+struct ProvisionSummary {
+    written: usize,
+    unchanged: usize,
+}
+
+/// Derive manifest entries and write each to its `output` (or stdout)
+///
+/// Everything is derived, and every existing output checked, before
+/// anything is written, so a bad entry leaves no partial provisioning
+/// behind.
+fn provision_entries(
+    key_use: &KeyUseArgs,
+    keychain: &Keychain,
+    parent_entropy: &[u8],
+    entries: &[ManifestEntry],
+    force: bool,
+) -> Result<ProvisionSummary> {
+    let mut rendered = Vec::new();
+    for entry in entries {
+        let key_derivation = entry
+            .load_derivation()
+            .with_context(|| format!("Failed to load entity: {}", entry.file.display()))?;
+        let derived_key = derive_key_from_entity(keychain, &key_derivation, parent_entropy)
+            .with_context(|| format!("Failed to derive key for {}", entry.file.display()))?;
+        let format = entry.output_format(&key_derivation);
+        check_key_use(
+            key_use,
+            &key_derivation,
+            parent_entropy,
+            &derived_key,
+            &entry.file,
+            &serde_name(&format),
+        )?;
+
+        let output = entry
+            .render(keychain, parent_entropy)
+            .with_context(|| format!("Failed to derive key for {}", entry.file.display()))?;
+        rendered.push((entry, format, Zeroizing::new(output + "\n")));
+    }
+
+    let mut summary = ProvisionSummary {
+        written: 0,
+        unchanged: 0,
+    };
+    let mut writes = Vec::new();
+    for (entry, format, output) in rendered {
+        let Some(path) = &entry.output else {
+            print!("{}", output.as_str());
+            continue;
+        };
+
+        match fs::read(path) {
+            Ok(existing) if existing == output.as_bytes() => {
+                eprintln!("unchanged {}", path.display());
+                summary.unchanged += 1;
+                continue;
+            }
+            Ok(_) if !force => anyhow::bail!(
+                "{} exists with different contents (pass --force to overwrite)",
+                path.display()
+            ),
+            _ => {}
+        }
+        writes.push((path, format, output));
+    }
+
+    for (path, format, output) in writes {
+        write_key_file(path, output.as_bytes(), format.is_secret(), force)?;
+        eprintln!("wrote {}", path.display());
+        summary.written += 1;
+    }
+
+    Ok(summary)
+}
+
+/// Write a Tor `HiddenServiceDir` (mode 0700) holding the onion service
+/// key files and hostname
+fn write_onion_service_dir(derived_key: &DerivedKey, dir: &Path, force: bool) -> Result<()> {
+    let dir = match &config().output_dir {
+        Some(output_dir) if dir.is_relative() => output_dir.join(dir),
+        _ => dir.to_path_buf(),
+    };
+    let keypair = Ed25519Keypair::from_derived_key(derived_key);
+    let service = OnionService::new(&keypair);
+    let hostname = service.hostname();
+    let files = [
+        ("hs_ed25519_secret_key", service.secret_key_file(), true),
+        (
+            "hs_ed25519_public_key",
+            Zeroizing::new(service.public_key_file()),
+            false,
+        ),
+        (
+            "hostname",
+            Zeroizing::new(format!("{}\n", hostname).into_bytes()),
+            false,
+        ),
+    ];
+
+    for (name, _, _) in &files {
+        let path = dir.join(name);
+        if path.exists() && !force {
+            anyhow::bail!(
+                "{} already exists (pass --force to overwrite)",
+                path.display()
+            );
+        }
+    }
+
+    fs::create_dir_all(&dir)
+        .with_context(|| format!("Failed to create directory: {}", dir.display()))?;
+    // Tor refuses to use a HiddenServiceDir readable by other users
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        fs::set_permissions(&dir, fs::Permissions::from_mode(0o700))
+            .with_context(|| format!("Failed to restrict {}", dir.display()))?;
+    }
+
+    for (name, contents, secret) in &files {
+        write_key_file(&dir.join(name), contents, *secret, force)?;
+    }
+    eprintln!("wrote {}", dir.display());
+    println!("{}", hostname);
+    Ok(())
+}
+
+/// Write a key file, creating parent directories; secret files are 0600
+///
+/// Without `overwrite` the file is created exclusively, so an existing file,
+/// or a symlink planted at `path`, is never written through.
+fn write_key_file(path: &Path, contents: &[u8], secret: bool, overwrite: bool) -> Result<()> {
+    if let Some(parent) = path
+        .parent()
+        .filter(|parent| !parent.as_os_str().is_empty())
+    {
+        fs::create_dir_all(parent)
+            .with_context(|| format!("Failed to create directory: {}", parent.display()))?;
+    }
+
+    let mut options = fs::OpenOptions::new();
+    if overwrite {
+        options.write(true).create(true).truncate(true);
+    } else {
+        options.write(true).create_new(true);
+    }
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::{OpenOptionsExt, PermissionsExt};
+        options.mode(if secret { 0o600 } else { 0o644 });
+        // mode() only applies to new files; tighten existing ones as well
+        if overwrite && secret && path.exists() {
+            fs::set_permissions(path, fs::Permissions::from_mode(0o600))
+                .with_context(|| format!("Failed to restrict {}", path.display()))?;
+        }
+    }
+    #[cfg(not(unix))]
+    let _ = secret;
+
+    let mut file = match options.open(path) {
+        Ok(file) => file,
+        Err(e) if e.kind() == std::io::ErrorKind::AlreadyExists => anyhow::bail!(
+            "{} already exists (pass --force to overwrite)",
+            path.display()
+        ),
+        Err(e) => return Err(e).with_context(|| format!("Failed to write {}", path.display())),
+    };
+    file.write_all(contents)
+        .with_context(|| format!("Failed to write {}", path.display()))
+}
+
+/// Push a derived key into the agent at SSH_AUTH_SOCK
+#[cfg(unix)]
+fn add_to_agent(
+    derived_key: &DerivedKey,
+    key_derivation: &KeyDerivation,
+    lifetime: u32,
+) -> Result<()> {
+    use std::os::unix::net::UnixStream;
+
+    let socket = env::var("SSH_AUTH_SOCK")
+        .context("SSH_AUTH_SOCK is not set. Start an agent first: eval $(ssh-agent)")?;
+    let mut stream = UnixStream::connect(&socket)
+        .with_context(|| format!("Failed to connect to SSH agent at {}", socket))?;
+
+    let keypair = Ed25519Keypair::from_derived_key(derived_key);
+    let comment = key_derivation.purpose.as_deref().unwrap_or("bip-keychain");
+    agent::add_identity(&mut stream, &keypair, comment, lifetime)
+        .context("Failed to add key to SSH agent")?;
+
+    eprintln!("Added \"{}\" to agent (expires in {}s)", comment, lifetime);
+    println!("{}", keypair.to_ssh_public_key(Some(comment)));
+
+    Ok(())
+}
+
+#[cfg(not(unix))]
+fn add_to_agent(_: &DerivedKey, _: &KeyDerivation, _: u32) -> Result<()> {
+    anyhow::bail!("--add-to-agent is only supported on unix platforms")
+}
+
+fn fingerprint_command(
+    seed: &SeedArgs,
+    entity_file: Option<PathBuf>,
+    parent_entropy_hex: Option<String>,
+) -> Result<()> {
+    let keychain = load_keychain(seed)?;
+
+    println!("master: {}", hex::encode(keychain.fingerprint()));
+
+    if let Some(entity_file) = entity_file {
+        let key_derivation = load_entity(&entity_file)?;
+        let parent_entropy = parse_parent_entropy(parent_entropy_hex)?;
+
+        let derived_key = derive_key_from_entity(&keychain, &key_derivation, &parent_entropy)
+            .context("Failed to derive key from entity")?;
+
+        let keypair = Ed25519Keypair::from_derived_key(&derived_key);
+
+        println!("entity: {}", hex::encode(derived_key.fingerprint()));
+        println!("ssh:    {}", keypair.ssh_fingerprint());
+        // Entities with a parent have no standalone path
+        if let Ok(path) = entity_derivation_path(&key_derivation, &parent_entropy) {
+            println!("path:   {}", path);
+        }
+    }
+
+    Ok(())
+}
+
+fn totp_command(
+    seed: &SeedArgs,
+    key_use: &KeyUseArgs,
+    entity_file: PathBuf,
+    enroll: bool,
+    at: Option<u64>,
+    parent_entropy_hex: Option<String>,
+) -> Result<()> {
+    let key_derivation = load_entity(&entity_file)?;
+    let keychain = load_keychain(seed)?;
+    let parent_entropy = parse_parent_entropy(parent_entropy_hex)?;
+
+    let derived_key = derive_key_from_entity(&keychain, &key_derivation, &parent_entropy)
+        .context("Failed to derive key from entity")?;
+    check_key_use(
+        key_use,
+        &key_derivation,
+        &parent_entropy,
+        &derived_key,
+        &entity_file,
+        "totp",
+    )?;
+
+    let config = key_derivation.totp.clone().unwrap_or_default();
+    let totp = Totp::derive(&derived_key.bip85_entropy(), &config)?;
+
+    if enroll {
+        let account = key_derivation.purpose.as_deref().unwrap_or("bip-keychain");
+        let uri = totp.to_uri(account);
+        let qr = bip_keychain::qr::QrCode::encode(uri.as_bytes())?;
+        eprintln!(
+            "⚠️  This QR code and URI contain the TOTP secret. Scan it, then clear the screen."
+        );
+        print!("{}", qr.to_terminal_string());
+        println!("{}", *uri);
+        return Ok(());
+    }
+
+    let now = match at {
+        Some(time) => time,
+        None => unix_time()?,
+    };
+    println!("{}", totp.code_at(now));
+    eprintln!("(valid for {}s)", totp.seconds_remaining(now));
+    Ok(())
+}
+
+fn which_entity_command(
+    seed: &SeedArgs,
+    pubkey: &str,
+    entities: &[PathBuf],
+    parent_entropy_hex: Option<String>,
+) -> Result<()> {
+    let target = read_any_public_key(pubkey)?;
+
+    let mut files = Vec::new();
+    for path in entities {
+        if path.is_dir() {
+            find_entity_files(path, None, &mut files)
+                .with_context(|| format!("Failed to read directory: {}", path.display()))?;
+        } else {
+            files.push(path.clone());
+        }
+    }
+    let keychain = load_keychain(seed)?;
+    let parent_entropy = parse_parent_entropy(parent_entropy_hex)?;
+
+    let mut found = 0;
+    for file in &files {
+        let key_derivation = match load_entity(file) {
+            Ok(key_derivation) => key_derivation,
+            Err(e) => {
+                let reason = format!("{:#}", e);
+                let reason = reason.lines().next().unwrap_or_default();
+                eprintln!("skipping {}: {}", file.display(), reason);
+                continue;
+            }
+        };
+        let derived_key = derive_key_from_entity(&keychain, &key_derivation, &parent_entropy)
+            .with_context(|| format!("Failed to derive key for {}", file.display()))?;
+
+        if derives_public_key(&key_derivation, &derived_key, &target) {
+            println!("{}", file.display());
+            found += 1;
+        }
+    }
+
+    if found == 0 {
+        anyhow::bail!(
+            "None of the {} entity file(s) searched derives this key",
+            files.len()
+        );
+    }
+    Ok(())
+}
+
+fn verify_binding_command(
+    seed: &SeedArgs,
+    entity_file: &Path,
+    pubkey: &str,
+    parent_entropy_hex: Option<String>,
+) -> Result<()> {
+    let target = read_any_public_key(pubkey)?;
+    let key_derivation = load_entity(entity_file)?;
+    let keychain = load_keychain(seed)?;
+    let parent_entropy = parse_parent_entropy(parent_entropy_hex)?;
+    let derived_key = derive_key_from_entity(&keychain, &key_derivation, &parent_entropy)
+        .context("Failed to derive key from entity")?;
+
+    if !derives_public_key(&key_derivation, &derived_key, &target) {
+        anyhow::bail!(
+            "{} does not derive public key {} (was the entity edited, or derived \
+             with another --parent-entropy?)",
+            entity_file.display(),
+            hex::encode(&target)
+        );
+    }
+    println!(
+        "✓ {} derives public key {}",
+        entity_file.display(),
+        hex::encode(&target)
+    );
+    Ok(())
+}
+
+fn export_manifest_command(
+    seed: &SeedArgs,
+    key_use: &KeyUseArgs,
+    manifest_file: &Path,
+    signing_entity: &Path,
+    output: Option<PathBuf>,
+    parent_entropy_hex: Option<String>,
+) -> Result<()> {
+    let record_path = transparency_log_record(key_use, output.as_deref())?;
+    let manifest = Manifest::load(manifest_file)
+        .with_context(|| format!("Failed to load manifest: {}", manifest_file.display()))?;
+    let signing_derivation = load_entity(signing_entity)?;
+    let keychain = load_keychain(seed)?;
+    let parent_entropy = parse_parent_entropy(parent_entropy_hex)?;
+
+    let mut keys = Vec::new();
+    for entry in &manifest.entities {
+        let key_derivation = entry
+            .load_derivation()
+            .with_context(|| format!("Failed to load entity: {}", entry.file.display()))?;
+        let derived_key = derive_key_from_entity(&keychain, &key_derivation, &parent_entropy)
+            .with_context(|| format!("Failed to derive key for {}", entry.file.display()))?;
+        check_key_use(
+            key_use,
+            &key_derivation,
+            &parent_entropy,
+            &derived_key,
+            &entry.file,
+            "export-manifest",
+        )?;
+        keys.push(
+            PublishedKey::new(&key_derivation, &derived_key, entry.label(&key_derivation))
+                .with_context(|| format!("Failed to derive key for {}", entry.file.display()))?,
+        );
+    }
+
+    let signing_key = derive_key_from_entity(&keychain, &signing_derivation, &parent_entropy)
+        .context("Failed to derive signing key from entity")?;
+    let keypair = Ed25519Keypair::from_derived_key(&signing_key);
+    let signed = KeyManifest::new(keys, unix_time()?)
+        .sign(&keypair)
+        .context("Failed to sign key manifest")?;
+    let json = serde_json::to_string_pretty(&signed)? + "\n";
+
+    match output {
+        Some(path) => {
+            fs::write(&path, &json)
+                .with_context(|| format!("Failed to write {}", path.display()))?;
+            eprintln!("Wrote {} key(s) to {}", signed.keys.len(), path.display());
+        }
+        None => print!("{}", json),
+    }
+    if let (Some(log_url), Some(record_path)) = (&key_use.transparency_log, record_path) {
+        submit_to_log(log_url, json.as_bytes(), &keypair, &record_path)?;
+    }
+    Ok(())
+}
+
+fn verify_manifest_command(
+    signed_manifest: &Path,
+    public_key: &str,
+    entity: Option<PathBuf>,
+) -> Result<()> {
+    let public_key = read_public_key(public_key)?;
+    let json = fs::read_to_string(signed_manifest)
+        .with_context(|| format!("Failed to read {}", signed_manifest.display()))?;
+    let manifest = SignedKeyManifest::from_json(&json)?
+        .verify(&public_key)
+        .context("Key manifest verification failed")?;
+    eprintln!("✓ Good signature on {}", signed_manifest.display());
+
+    let keys: Vec<&PublishedKey> = match entity {
+        Some(entity_file) => {
+            let key_derivation = load_entity(&entity_file)?;
+            match manifest.find(&key_derivation)? {
+                Some(key) => vec![key],
+                None => anyhow::bail!(
+                    "{} is not listed in {}",
+                    entity_file.display(),
+                    signed_manifest.display()
+                ),
+            }
+        }
+        None => manifest.keys.iter().collect(),
+    };
+    for key in keys {
+        println!(
+            "{}  {}  {}  {}",
+            key.entity_hash, key.public_key, key.fingerprint, key.purpose
+        );
+    }
+    Ok(())
+}
+
+/// Parse a public key of any key type: an ssh-ed25519 line or hex, given
+/// literally or as a file
+fn read_any_public_key(public_key: &str) -> Result<Vec<u8>> {
+    let text = fs::read_to_string(public_key).unwrap_or_else(|_| public_key.to_string());
+    match signing::parse_public_key(&text) {
+        Ok(ed25519) => Ok(ed25519.to_vec()),
+        Err(_) => hex::decode(text.trim()).map_err(|_| {
+            anyhow::anyhow!("Public key must be an ssh-ed25519 line or hex, or a file holding one")
+        }),
+    }
+}
+
+/// Whether `public_key` belongs to the entity's derived key: its Ed25519
+/// key (used for SSH and revocation), or the key of its own `key_type` if
+/// it sets another (compressed for secp256k1 and P-256)
+fn derives_public_key(
+    key_derivation: &KeyDerivation,
+    derived_key: &DerivedKey,
+    public_key: &[u8],
+) -> bool {
+    let ed25519 = Ed25519Keypair::from_derived_key(derived_key).public_key_bytes();
+    let key_type = key_derivation.derivation_config.key_type;
+    ed25519[..] == *public_key
+        || (key_type != KeyTypeConfig::Ed25519
+            && CurveKey::from_derived_key(derived_key, key_type)
+                .is_ok_and(|key| key.public_key_bytes() == public_key))
+}
+
+fn inspect_command(entity_file: PathBuf, parent_entropy_hex: Option<String>) -> Result<()> {
+    let key_derivation = load_entity(&entity_file)?;
+    let parent_entropy = parse_parent_entropy(parent_entropy_hex)?;
+    let config = &key_derivation.derivation_config;
+
+    let canonical = key_derivation
+        .canonical_entity()
+        .context("Failed to canonicalize entity")?;
+    println!("schema_type:      {}", key_derivation.schema_type);
+    println!("canonicalization: {}", serde_name(&config.canonicalization));
+    match std::str::from_utf8(&canonical) {
+        Ok(text) if !matches!(key_derivation.entity, Entity::GordianEnvelope(_)) => {
+            println!("canonical:        {}", text)
+        }
+        // Envelope entities hash their dCBOR encoding
+        _ => println!("canonical (hex):  {}", hex::encode(&canonical)),
+    }
+    println!("domain_tag:       {}", serde_name(&config.domain_tag));
+    if let Some(salt) = &config.salt {
+        println!("salt:             {}", salt);
+    }
+    println!("hash_function:    {}", serde_name(&config.hash_function));
+    if let Some(expansion) = &config.index_expansion {
+        println!("index_expansion:  {}", serde_json::to_string(expansion)?);
+    }
+
+    if key_derivation.parent.is_some() {
+        println!();
+        println!("This entity is derived below a parent: its hash is keyed with the");
+        println!("parent's derived key, so the hash, index, and path need the seed.");
+        return Ok(());
+    }
+
+    let hash = entity_hash(&key_derivation, &parent_entropy).context("Failed to hash entity")?;
+    let indices = entity_indices(&key_derivation, &parent_entropy)?;
+    let path = entity_derivation_path(&key_derivation, &parent_entropy)?;
+    println!("parent_entropy:   {}", hex::encode(&parent_entropy));
+    println!("hash:             {}", hex::encode(hash));
+    println!(
+        "indices:          {}",
+        indices
+            .iter()
+            .map(u32::to_string)
+            .collect::<Vec<_>>()
+            .join(", ")
+    );
+    println!("hardened:         {}", config.hardened);
+    println!("path:             {}", path);
+    // What to write in a child's `parent` field to reference this entity
+    let as_parent = if config.has_default_path() {
+        serde_json::to_string(&hex::encode(hash))?
+    } else {
+        serde_json::to_string(&EntityParent::Reference(ParentReference {
+            hash: hex::encode(hash),
+            derivation_config: config.clone(),
+            parent: None,
+        }))?
+    };
+    println!("as_parent:        {}", as_parent);
+    println!("key_entropy:      {}", serde_name(&config.key_entropy));
+    println!("key_type:         {}", config.key_type.name());
+    if let Some(not_after) = key_derivation.not_after()? {
+        let status = if key_derivation.is_expired_at(unix_time()?)? {
+            " (expired)"
+        } else {
+            ""
+        };
+        println!("not_after:        {}{}", not_after, status);
+    }
+
+    Ok(())
+}
+
+fn gen_vectors_command(output: Option<PathBuf>) -> Result<()> {
+    let vectors = generate_vectors().context("Failed to generate test vectors")?;
+    let json = serde_json::to_string_pretty(&vectors)? + "\n";
+
+    match output {
+        Some(path) => {
+            fs::write(&path, json)
+                .with_context(|| format!("Failed to write {}", path.display()))?;
+            eprintln!(
+                "Wrote {} test vectors to {}",
+                vectors.vectors.len(),
+                path.display()
+            );
+        }
+        None => print!("{}", json),
+    }
+    Ok(())
+}
+
+fn compat_command(reference_file: PathBuf) -> Result<()> {
+    let json = fs::read_to_string(&reference_file)
+        .with_context(|| format!("Failed to read {}", reference_file.display()))?;
+    let reference: ReferenceVectors = serde_json::from_str(&json)
+        .with_context(|| format!("Invalid reference file {}", reference_file.display()))?;
+    if let Some(implementation) = &reference.implementation {
+        println!("Comparing against {}", implementation);
+    }
+
+    let mut diverged = 0;
+    for (number, vector) in reference.vectors.iter().enumerate() {
+        let label = if vector.description.is_empty() {
+            format!("vector {}", number + 1)
+        } else {
+            vector.description.clone()
+        };
+        let divergences = vector
+            .compare()
+            .with_context(|| format!("Failed to derive {}", label))?;
+        if divergences.is_empty() {
+            println!("ok        {}", label);
+            continue;
+        }
+        let steps: Vec<_> = divergences
+            .iter()
+            .map(|divergence| divergence.step)
+            .collect();
+        if steps == vector.known_divergences {
+            println!("known     {} ({})", label, steps.join(", "));
+            continue;
+        }
+
+        diverged += 1;
+        println!("DIVERGES  {}", label);
+        for divergence in divergences {
+            println!("  {}:", divergence.step);
+            println!("    reference: {}", divergence.expected);
+            println!("    ours:      {}", divergence.actual);
+        }
+    }
+
+    if diverged > 0 {
+        anyhow::bail!(
+            "{} of {} vectors diverge from the reference",
+            diverged,
+            reference.vectors.len()
+        );
+    }
+    println!("All {} vectors match", reference.vectors.len());
+    Ok(())
+}
+
+#[allow(clippy::too_many_arguments)]
+fn sign_command(
+    seed: &SeedArgs,
+    key_use: &KeyUseArgs,
+    entity_file: PathBuf,
+    file: PathBuf,
+    format: CliSignatureFormat,
+    namespace: &str,
+    output: Option<PathBuf>,
+    parent_entropy_hex: Option<String>,
+) -> Result<()> {
+    let key_derivation = load_entity(&entity_file)?;
+    let keychain = load_keychain(seed)?;
+    let parent_entropy = parse_parent_entropy(parent_entropy_hex)?;
+
+    let message =
+        fs::read(&file).with_context(|| format!("Failed to read file: {}", file.display()))?;
+
+    let derived_key = derive_key_from_entity(&keychain, &key_derivation, &parent_entropy)
+        .context("Failed to derive key from entity")?;
+    check_key_use(
+        key_use,
+        &key_derivation,
+        &parent_entropy,
+        &derived_key,
+        &entity_file,
+        "sign",
+    )?;
+    let keypair = Ed25519Keypair::from_derived_key(&derived_key);
+
+    let mut signature = signing::sign_message(&keypair, &message, format.into(), namespace)
+        .context("Failed to sign file")?;
+    if format == CliSignatureFormat::Hex {
+        signature.push(b'\n');
+    }
+
+    match output {
+        Some(path) => fs::write(&path, &signature)
+            .with_context(|| format!("Failed to write signature: {}", path.display()))?,
+        None => std::io::stdout()
+            .write_all(&signature)
+            .context("Failed to write signature")?,
+    }
+
+    Ok(())
+}
+
+fn sign_eth_command(
+    seed: &SeedArgs,
+    key_use: &KeyUseArgs,
+    entity_file: PathBuf,
+    message: Option<String>,
+    file: Option<PathBuf>,
+    json: bool,
+    parent_entropy_hex: Option<String>,
+) -> Result<()> {
+    let key_derivation = load_entity(&entity_file)?;
+    let keychain = load_keychain(seed)?;
+    let parent_entropy = parse_parent_entropy(parent_entropy_hex)?;
+
+    let message = match (message, file) {
+        (Some(message), _) => message.into_bytes(),
+        (None, Some(file)) => {
+            fs::read(&file).with_context(|| format!("Failed to read file: {}", file.display()))?
+        }
+        (None, None) => anyhow::bail!("Pass a MESSAGE or --file"),
+    };
+
+    let derived_key = derive_key_from_entity(&keychain, &key_derivation, &parent_entropy)
+        .context("Failed to derive key from entity")?;
+    check_key_use(
+        key_use,
+        &key_derivation,
+        &parent_entropy,
+        &derived_key,
+        &entity_file,
+        "sign-eth",
+    )?;
+
+    let signature = format!(
+        "0x{}",
+        hex::encode(eth::personal_sign(&derived_key, &message).context("Failed to sign message")?)
+    );
+    if json {
+        // Verifiers read a 0x-prefixed msg as hex-encoded bytes
+        let msg = match std::str::from_utf8(&message) {
+            Ok(text) if !text.starts_with("0x") => text.to_string(),
+            _ => format!("0x{}", hex::encode(&message)),
+        };
+        let output = serde_json::json!({
+            "address": eth::eth_address(&derived_key),
+            "msg": msg,
+            "sig": signature,
+            "version": "2",
+        });
+        println!("{}", serde_json::to_string_pretty(&output)?);
+    } else {
+        println!("{}", signature);
+    }
+
+    Ok(())
+}
+
+#[allow(clippy::too_many_arguments)]
+fn encrypt_command(
+    seed: &SeedArgs,
+    key_use: &KeyUseArgs,
+    file: PathBuf,
+    to: Option<PathBuf>,
+    recipient: Option<String>,
+    output: Option<PathBuf>,
+    force: bool,
+    parent_entropy_hex: Option<String>,
+) -> Result<()> {
+    let recipient = match (to, recipient) {
+        (Some(entity_file), _) => {
+            let key_derivation = load_entity(&entity_file)?;
+            let keychain = load_keychain(seed)?;
+            let parent_entropy = parse_parent_entropy(parent_entropy_hex)?;
+            let derived_key = derive_key_from_entity(&keychain, &key_derivation, &parent_entropy)
+                .context("Failed to derive key from entity")?;
+            check_key_use(
+                key_use,
+                &key_derivation,
+                &parent_entropy,
+                &derived_key,
+                &entity_file,
+                "encrypt",
+            )?;
+            sealed_box::x25519_public_key(&Ed25519Keypair::from_derived_key(&derived_key))
+        }
+        (None, Some(public_key)) => {
+            sealed_box::x25519_from_ed25519(&read_public_key(&public_key)?)?
+        }
+        (None, None) => unreachable!("clap requires --to or --recipient"),
+    };
+
+    let plaintext = Zeroizing::new(
+        fs::read(&file).with_context(|| format!("Failed to read file: {}", file.display()))?,
+    );
+    let output = output.unwrap_or_else(|| {
+        let mut name = file.clone().into_os_string();
+        name.push(".enc");
+        PathBuf::from(name)
+    });
+    if output.exists() && !force {
+        anyhow::bail!(
+            "{} already exists (pass --force to overwrite)",
+            output.display()
+        );
+    }
+
+    let sealed = sealed_box::seal(&recipient, &plaintext).context("Failed to encrypt")?;
+    write_key_file(&output, &sealed, false, force)?;
+    eprintln!("Encrypted {} to {}", file.display(), output.display());
+    Ok(())
+}
+
+fn decrypt_command(
+    seed: &SeedArgs,
+    key_use: &KeyUseArgs,
+    entity_file: PathBuf,
+    file: PathBuf,
+    output: Option<PathBuf>,
+    force: bool,
+    parent_entropy_hex: Option<String>,
+) -> Result<()> {
+    let output = match output {
+        Some(output) => output,
+        None => match file.extension() {
+            Some(extension) if extension == "enc" => file.with_extension(""),
+            _ => anyhow::bail!("{} does not end in .enc; pass --output", file.display()),
+        },
+    };
+    if output.exists() && !force {
+        anyhow::bail!(
+            "{} already exists (pass --force to overwrite)",
+            output.display()
+        );
+    }
+
+    let key_derivation = load_entity(&entity_file)?;
+    let keychain = load_keychain(seed)?;
+    let parent_entropy = parse_parent_entropy(parent_entropy_hex)?;
+    let derived_key = derive_key_from_entity(&keychain, &key_derivation, &parent_entropy)
+        .context("Failed to derive key from entity")?;
+    check_key_use(
+        key_use,
+        &key_derivation,
+        &parent_entropy,
+        &derived_key,
+        &entity_file,
+        "decrypt",
+    )?;
+
+    let sealed =
+        fs::read(&file).with_context(|| format!("Failed to read file: {}", file.display()))?;
+    let plaintext = sealed_box::open(&Ed25519Keypair::from_derived_key(&derived_key), &sealed)
+        .with_context(|| format!("Failed to decrypt {}", file.display()))?;
+    write_key_file(&output, &plaintext, true, force)?;
+    eprintln!("Decrypted {} to {}", file.display(), output.display());
+    Ok(())
+}
+
+fn verify_command(
+    public_key: &str,
+    file: PathBuf,
+    signature_file: PathBuf,
+    namespace: &str,
+) -> Result<()> {
+    // Accept either a literal key or a path to a .pub / hex file
+    let public_key = read_public_key(public_key)?;
+
+    let message =
+        fs::read(&file).with_context(|| format!("Failed to read file: {}", file.display()))?;
+    let signature = fs::read(&signature_file)
+        .with_context(|| format!("Failed to read signature: {}", signature_file.display()))?;
+
+    signing::verify_message(&public_key, &message, &signature, namespace)
+        .context("Signature verification failed")?;
+
+    println!("✓ Good signature for {}", file.display());
+    Ok(())
+}
+
+fn envelope_command(
+    seed: &SeedArgs,
+    entity_file: PathBuf,
+    sign: bool,
+    parent_entropy_hex: Option<String>,
+) -> Result<()> {
+    let key_derivation = load_entity(&entity_file)?;
+    let Entity::GordianEnvelope(entity) = &key_derivation.entity else {
+        anyhow::bail!(
+            "Entity schema_type is \"{}\"; envelope output requires \"gordian_envelope\"",
+            key_derivation.schema_type
+        );
+    };
+
+    let mut envelope = entity.decode()?;
+
+    if sign {
+        let keychain = load_keychain(seed)?;
+        let parent_entropy = parse_parent_entropy(parent_entropy_hex)?;
+        let derived_key = derive_key_from_entity(&keychain, &key_derivation, &parent_entropy)
+            .context("Failed to derive key from entity")?;
+        envelope = envelope.add_signature(&Ed25519Keypair::from_derived_key(&derived_key));
+    }
+
+    println!("{}", envelope.to_ur_string());
+    Ok(())
+}
+
+fn ur_command(command: UrCommand) -> Result<()> {
+    match command {
+        UrCommand::Encode {
+            entity_file,
+            deflate,
+        } => {
+            let key_derivation = load_entity(&entity_file)?;
+            let ur = key_derivation
+                .to_ur_string(deflate)
+                .context("Failed to encode entity")?;
+            println!("{}", ur);
+            Ok(())
+        }
+        UrCommand::Decode { ur, output } => {
+            let key_derivation =
+                KeyDerivation::from_ur_string(&ur).context("Failed to decode entity")?;
+            let json = serde_json::to_string_pretty(&key_derivation)? + "\n";
+            match output {
+                Some(path) => {
+                    fs::OpenOptions::new()
+                        .write(true)
+                        .create_new(true)
+                        .open(&path)
+                        .and_then(|mut file| file.write_all(json.as_bytes()))
+                        .with_context(|| format!("Failed to write {}", path.display()))?;
+                    eprintln!("Wrote {}", path.display());
+                }
+                None => print!("{}", json),
+            }
+            Ok(())
+        }
+    }
+}
+
+fn template_command(command: TemplateCommand) -> Result<()> {
+    match command {
+        TemplateCommand::List => {
+            for template in template::TEMPLATES {
+                println!("{:<16} {}", template.kind, template.description);
+            }
+            Ok(())
+        }
+        TemplateCommand::New {
+            kind,
+            properties,
+            index_levels,
+            output,
+        } => {
+            let template = template::find(&kind).with_context(|| {
+                format!(
+                    "Unknown template '{}'. Run `bip-keychain template list` to see the options",
+                    kind
+                )
+            })?;
+            let overrides: Vec<_> = properties
+                .into_iter()
+                .map(|(key, value)| (key, value.into()))
+                .collect();
+            let mut key_derivation = template.key_derivation(&overrides)?;
+            if let Some(levels) = index_levels {
+                key_derivation.derivation_config.index_levels = levels;
+            }
+            let json = serde_json::to_string_pretty(&key_derivation)? + "\n";
+
+            match output {
+                Some(path) => fs::OpenOptions::new()
+                    .write(true)
+                    .create_new(true)
+                    .open(&path)
+                    .and_then(|mut file| file.write_all(json.as_bytes()))
+                    .with_context(|| format!("Failed to write {}", path.display()))?,
+                None => print!("{}", json),
+            }
+            Ok(())
+        }
+    }
+}
+
+fn revoke_command(seed: &SeedArgs, key_use: &KeyUseArgs, command: RevokeCommand) -> Result<()> {
+    let path = key_use.revocations_path();
+    let mut list =
+        RevocationList::load(path).with_context(|| format!("Failed to load {}", path.display()))?;
+
+    match command {
+        RevokeCommand::Add {
+            entity_file,
+            public_key,
+            reason,
+            parent_entropy,
+        } => {
+            let (public_key, label) = match (entity_file, public_key) {
+                (Some(entity_file), _) => {
+                    let key_derivation = load_entity(&entity_file)?;
+                    let keychain = load_keychain(seed)?;
+                    let parent_entropy = parse_parent_entropy(parent_entropy)?;
+                    let derived_key =
+                        derive_key_from_entity(&keychain, &key_derivation, &parent_entropy)
+                            .context("Failed to derive key from entity")?;
+                    let label = key_derivation
+                        .purpose
+                        .clone()
+                        .unwrap_or_else(|| entity_file.display().to_string());
+                    (
+                        Ed25519Keypair::from_derived_key(&derived_key).public_key_bytes(),
+                        Some(label),
+                    )
+                }
+                (None, Some(public_key)) => (read_public_key(&public_key)?, None),
+                (None, None) => unreachable!("clap requires ENTITY_JSON or --public-key"),
+            };
+
+            if !list.revoke(&public_key, label, reason, unix_time()?) {
+                eprintln!("{} is already revoked", hex::encode(public_key));
+                return Ok(());
+            }
+            list.save(path)
+                .with_context(|| format!("Failed to write {}", path.display()))?;
+            eprintln!("Revoked {} in {}", hex::encode(public_key), path.display());
+            Ok(())
+        }
+        RevokeCommand::List => {
+            print_revocations(&list);
+            Ok(())
+        }
+        RevokeCommand::Export {
+            signing_entity,
+            output,
+            parent_entropy,
+        } => {
+            let key_derivation = load_entity(&signing_entity)?;
+            let keychain = load_keychain(seed)?;
+            let parent_entropy = parse_parent_entropy(parent_entropy)?;
+            let derived_key = derive_key_from_entity(&keychain, &key_derivation, &parent_entropy)
+                .context("Failed to derive key from entity")?;
+
+            let signed = list
+                .sign(&Ed25519Keypair::from_derived_key(&derived_key))
+                .context("Failed to sign revocation list")?;
+            let json = serde_json::to_string_pretty(&signed)? + "\n";
+
+            match output {
+                Some(path) => fs::write(&path, json)
+                    .with_context(|| format!("Failed to write {}", path.display()))?,
+                None => print!("{}", json),
+            }
+            Ok(())
+        }
+        RevokeCommand::Verify {
+            signed_list,
+            public_key,
+        } => {
+            let public_key = read_public_key(&public_key)?;
+            let json = fs::read_to_string(&signed_list)
+                .with_context(|| format!("Failed to read {}", signed_list.display()))?;
+            let list = SignedRevocationList::from_json(&json)?
+                .verify(&public_key)
+                .context("Revocation list verification failed")?;
+
+            eprintln!("✓ Good signature on {}", signed_list.display());
+            print_revocations(&list);
+            Ok(())
+        }
+    }
+}
+
+fn print_revocations(list: &RevocationList) {
+    for revocation in &list.revocations {
+        println!(
+            "{}  {}  {}  {}",
+            revocation.public_key,
+            revocation.revoked_at,
+            revocation.label.as_deref().unwrap_or("-"),
+            revocation.reason
+        );
+    }
+}
+
+fn receipt_command(seed: &SeedArgs, key_use: &KeyUseArgs, command: ReceiptCommand) -> Result<()> {
+    match command {
+        ReceiptCommand::Issue {
+            entity_file,
+            output,
+            parent_entropy,
+        } => {
+            let record_path = transparency_log_record(key_use, output.as_deref())?;
+            let key_derivation = load_entity(&entity_file)?;
+            let keychain = load_keychain(seed)?;
+            let parent_entropy = parse_parent_entropy(parent_entropy)?;
+            let derived_key = derive_key_from_entity(&keychain, &key_derivation, &parent_entropy)
+                .context("Failed to derive key from entity")?;
+            check_key_use(
+                key_use,
+                &key_derivation,
+                &parent_entropy,
+                &derived_key,
+                &entity_file,
+                "receipt",
+            )?;
+
+            // Entities with a parent have no standalone path
+            let path = entity_derivation_path(&key_derivation, &parent_entropy)
+                .ok()
+                .map(|path| path.to_string());
+            let keypair = Ed25519Keypair::from_derived_key(&derived_key);
+            let receipt = Receipt::issue(&key_derivation, path, &keypair, unix_time()?)
+                .context("Failed to sign receipt")?;
+            let json = serde_json::to_string_pretty(&receipt)? + "\n";
+
+            match output {
+                Some(path) => fs::write(&path, &json)
+                    .with_context(|| format!("Failed to write {}", path.display()))?,
+                None => print!("{}", json),
+            }
+            if let (Some(log_url), Some(record_path)) = (&key_use.transparency_log, record_path) {
+                submit_to_log(log_url, json.as_bytes(), &keypair, &record_path)?;
+            }
+            Ok(())
+        }
+        ReceiptCommand::Verify {
+            receipt: receipt_file,
+            entity,
+            public_key,
+            parent_entropy,
+        } => {
+            let json = fs::read_to_string(&receipt_file)
+                .with_context(|| format!("Failed to read {}", receipt_file.display()))?;
+            let receipt = Receipt::from_json(&json)?;
+            receipt.verify().context("Receipt verification failed")?;
+
+            if let Some(public_key) = public_key {
+                if read_public_key(&public_key)? != receipt.public_key_bytes()? {
+                    anyhow::bail!(
+                        "Receipt is for public key {}, not the expected key",
+                        receipt.public_key
+                    );
+                }
+            }
+            if let Some(entity_file) = &entity {
+                let key_derivation = load_entity(entity_file)?;
+                receipt
+                    .verify_entity(&key_derivation)
+                    .context("Receipt verification failed")?;
+                let parent_entropy = parse_parent_entropy(parent_entropy)?;
+                let path = entity_derivation_path(&key_derivation, &parent_entropy)
+                    .ok()
+                    .map(|path| path.to_string());
+                if receipt.path.is_some() && receipt.path != path {
+                    anyhow::bail!(
+                        "Receipt path {} does not match {}",
+                        receipt.path.as_deref().unwrap_or_default(),
+                        entity_file.display()
+                    );
+                }
+            }
+
+            eprintln!("✓ Good receipt {}", receipt_file.display());
+            println!("entity_hash: {}", receipt.entity_hash);
+            println!("path:        {}", receipt.path.as_deref().unwrap_or("-"));
+            println!("public_key:  {}", receipt.public_key);
+            println!("issued_at:   {}", receipt.issued_at);
+            Ok(())
+        }
+    }
+}
+
+/// Refuse expired entities and revoked keys (or warn with --allow-expired
+/// and --allow-revoked) and append to the audit log, if one is configured
+fn check_key_use(
+    key_use: &KeyUseArgs,
+    key_derivation: &KeyDerivation,
+    parent_entropy: &[u8],
+    derived_key: &DerivedKey,
+    source: &Path,
+    usage: &str,
+) -> Result<()> {
+    if key_derivation.is_expired_at(unix_time()?)? {
+        let not_after = key_derivation
+            .metadata
+            .as_ref()
+            .and_then(|metadata| metadata.get(NOT_AFTER_FIELD))
+            .map(|value| {
+                value
+                    .as_str()
+                    .map_or_else(|| value.to_string(), str::to_string)
+            })
+            .unwrap_or_default();
+        let message = format!("{} expired after {}", source.display(), not_after);
+        if !key_use.allow_expired {
+            return Err(BipKeychainError::ExpiredEntity(message).into());
+        }
+        eprintln!("warning: {}", message);
+    }
+
+    let path = key_use.revocations_path();
+    let list =
+        RevocationList::load(path).with_context(|| format!("Failed to load {}", path.display()))?;
+    let public_key = Ed25519Keypair::from_derived_key(derived_key).public_key_bytes();
+
+    if let Some(revocation) = list.find(&public_key) {
+        let message = format!(
+            "The key for {} was revoked at {} (Unix time): {}",
+            source.display(),
+            revocation.revoked_at,
+            revocation.reason
+        );
+        if !key_use.allow_revoked {
+            return Err(BipKeychainError::RevocationError(message).into());
+        }
+        eprintln!("warning: {}", message);
+    }
+
+    if key_use.index_registry.is_none() && key_use.audit_log.is_none() {
+        return Ok(());
+    }
+    let hash = entity_hash(key_derivation, parent_entropy)?;
+    // Entities with a parent have no standalone path
+    let path = entity_derivation_path(key_derivation, parent_entropy)
+        .ok()
+        .map(|path| path.to_string());
+
+    if let (Some(registry_path), Some(path)) = (&key_use.index_registry, &path) {
+        let mut registry = IndexRegistry::load(registry_path)
+            .with_context(|| format!("Failed to load {}", registry_path.display()))?;
+        let source_name = source.display().to_string();
+        match registry.register(path, &hash, Some(source_name), unix_time()?) {
+            Ok(true) => registry
+                .save(registry_path)
+                .with_context(|| format!("Failed to write {}", registry_path.display()))?,
+            Ok(false) => {}
+            Err(existing) => {
+                let message = format!(
+                    "{} maps to {}, already used by {} (entity hash {}...), so both derive the same key",
+                    source.display(),
+                    path,
+                    existing.source.as_deref().unwrap_or("another entity"),
+                    &existing.entity_hash[..16]
+                );
+                if key_use.strict {
+                    return Err(BipKeychainError::IndexRegistryError(message).into());
+                }
+                eprintln!("warning: {}", message);
+            }
+        }
+    }
+
+    if let Some(audit_log) = &key_use.audit_log {
+        AuditLog::new(audit_log)
+            .append(&hash, path, usage, unix_time()?)
+            .with_context(|| format!("Failed to write audit log: {}", audit_log.display()))?;
+    }
+
+    Ok(())
+}
+
+/// Name of a unit enum variant as it appears in JSON (e.g. an output
+/// format's --format name)
+fn serde_name<T: serde::Serialize>(value: &T) -> String {
+    serde_json::to_value(value)
+        .ok()
+        .and_then(|value| value.as_str().map(str::to_string))
+        .unwrap_or_default()
+}
+
+/// Current time in seconds since the Unix epoch
+fn unix_time() -> Result<u64> {
+    Ok(std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .context("System clock is before 1970")?
+        .as_secs())
+}
+
+const NO_TRANSPARENCY_LOG: &str = "This build does not include transparency log support.\n\
+     Rebuild with: cargo install bip-keychain --features transparency-log";
+
+/// `<document>.tlog.json`, where a document's log record is saved
+fn log_record_path(document: &Path) -> PathBuf {
+    let mut path = document.as_os_str().to_owned();
+    path.push(".tlog.json");
+    PathBuf::from(path)
+}
+
+/// Where --transparency-log saves the record for a document written to
+/// `output`, or `None` without --transparency-log
+///
+/// Checked before anything is written.
+fn transparency_log_record(key_use: &KeyUseArgs, output: Option<&Path>) -> Result<Option<PathBuf>> {
+    if key_use.transparency_log.is_none() {
+        return Ok(None);
+    }
+    if !cfg!(feature = "transparency-log") {
+        anyhow::bail!(NO_TRANSPARENCY_LOG);
+    }
+    match output {
+        Some(output) => Ok(Some(log_record_path(output))),
+        None => anyhow::bail!(
+            "--transparency-log needs -o: the log record is saved next to the document"
+        ),
+    }
+}
+
+/// Log `document` signed by `keypair` and save the record to `record_path`
+#[cfg(feature = "transparency-log")]
+fn submit_to_log(
+    log_url: &str,
+    document: &[u8],
+    keypair: &Ed25519Keypair,
+    record_path: &Path,
+) -> Result<()> {
+    let record = bip_keychain::transparency::submit(log_url, document, keypair)
+        .with_context(|| format!("Failed to submit to {}", log_url))?;
+    record
+        .verify(document)
+        .with_context(|| format!("{} returned an invalid log record", log_url))?;
+    let json = serde_json::to_string_pretty(&record)? + "\n";
+    fs::write(record_path, json)
+        .with_context(|| format!("Failed to write {}", record_path.display()))?;
+    eprintln!(
+        "Logged as entry {} in {}; record saved to {}",
+        record.log_index,
+        record.log_url,
+        record_path.display()
+    );
+    Ok(())
+}
+
+#[cfg(not(feature = "transparency-log"))]
+fn submit_to_log(_: &str, _: &[u8], _: &Ed25519Keypair, _: &Path) -> Result<()> {
+    anyhow::bail!(NO_TRANSPARENCY_LOG)
+}
+
+fn transparency_command(
+    seed: &SeedArgs,
+    key_use: &KeyUseArgs,
+    command: TransparencyCommand,
+) -> Result<()> {
+    match command {
+        TransparencyCommand::Submit {
+            document,
+            signing_entity,
+            log_url,
+            output,
+            parent_entropy,
+        } => {
+            let contents = fs::read(&document)
+                .with_context(|| format!("Failed to read {}", document.display()))?;
+            let key_derivation = load_entity(&signing_entity)?;
+            let keychain = load_keychain(seed)?;
+            let parent_entropy = parse_parent_entropy(parent_entropy)?;
+            let derived_key = derive_key_from_entity(&keychain, &key_derivation, &parent_entropy)
+                .context("Failed to derive key from entity")?;
+            check_key_use(
+                key_use,
+                &key_derivation,
+                &parent_entropy,
+                &derived_key,
+                &signing_entity,
+                "transparency",
+            )?;
+
+            let log_url = log_url
+                .or_else(|| key_use.transparency_log.clone())
+                .unwrap_or_else(|| transparency::DEFAULT_LOG_URL.to_string());
+            let record_path = output.unwrap_or_else(|| log_record_path(&document));
+            submit_to_log(
+                &log_url,
+                &contents,
+                &Ed25519Keypair::from_derived_key(&derived_key),
+                &record_path,
+            )
+        }
+        TransparencyCommand::Verify {
+            document,
+            record,
+            public_key,
+            log_key,
+        } => {
+            let contents = fs::read(&document)
+                .with_context(|| format!("Failed to read {}", document.display()))?;
+            let record_path = record.unwrap_or_else(|| log_record_path(&document));
+            let json = fs::read_to_string(&record_path)
+                .with_context(|| format!("Failed to read {}", record_path.display()))?;
+            let record = LogRecord::from_json(&json)?;
+            let log_key = match log_key {
+                Some(path) => LogPublicKey::from_pem(
+                    &fs::read_to_string(&path)
+                        .with_context(|| format!("Failed to read {}", path.display()))?,
+                )?,
+                None if record.log_url.trim_end_matches('/') == transparency::DEFAULT_LOG_URL => {
+                    LogPublicKey::sigstore()
+                }
+                None => anyhow::bail!(
+                    "{} is not {}; pass its public key with --log-key",
+                    record.log_url,
+                    transparency::DEFAULT_LOG_URL
+                ),
+            };
+            let signer = record
+                .verify(&contents, &log_key)
+                .context("Transparency log record verification failed")?;
+            if let Some(public_key) = public_key {
+                if signer != read_public_key(&public_key)? {
+                    anyhow::bail!(
+                        "{} was logged by {}, not the expected key",
+                        document.display(),
+                        hex::encode(signer)
+                    );
+                }
+            }
+
+            println!(
+                "✓ {} is entry {} in {} (logged {}, signed by {})",
+                document.display(),
+                record.log_index,
+                record.log_url,
+                record.integrated_time,
+                hex::encode(signer)
+            );
+            Ok(())
+        }
+    }
+}
+
+fn audit_command(command: AuditCommand) -> Result<()> {
+    match command {
+        AuditCommand::Verify { log } => {
+            if !log.exists() {
+                anyhow::bail!("Audit log not found: {}", log.display());
+            }
+            let summary = AuditLog::new(&log)
+                .verify()
+                .with_context(|| format!("Audit log verification failed: {}", log.display()))?;
+            println!(
+                "✓ {} record(s), chain intact (head {})",
+                summary.records, summary.head
+            );
+            Ok(())
+        }
+    }
+}
+
+fn frost_command(seed: &SeedArgs, key_use: &KeyUseArgs, command: FrostCommand) -> Result<()> {
+    let read = |path: &Path| {
+        fs::read_to_string(path).with_context(|| format!("Failed to read {}", path.display()))
+    };
+    let read_commitments = |paths: &[PathBuf]| -> Result<Vec<SigningCommitment>> {
+        paths
+            .iter()
+            .map(|path| Ok(SigningCommitment::from_json(&read(path)?)?))
+            .collect()
+    };
+
+    match command {
+        FrostCommand::Share {
+            entity_file,
+            participant,
+            output,
+            parent_entropy,
+        } => {
+            let key_derivation = load_entity(&entity_file)?;
+            let config = key_derivation.frost.with_context(|| {
+                format!(
+                    "{} has no frost object, e.g. \"frost\": {{\"threshold\": 2, \"max_signers\": 3}}",
+                    entity_file.display()
+                )
+            })?;
+            let keychain = load_keychain(seed)?;
+            let parent_entropy = parse_parent_entropy(parent_entropy)?;
+            let derived_key = derive_key_from_entity(&keychain, &key_derivation, &parent_entropy)
+                .context("Failed to derive key from entity")?;
+            check_key_use(
+                key_use,
+                &key_derivation,
+                &parent_entropy,
+                &derived_key,
+                &entity_file,
+                "frost",
+            )?;
+
+            let keypair = Ed25519Keypair::from_derived_key(&derived_key);
+            let group = FrostGroup::derive(&keypair, config)?;
+            let package = group.key_package(participant)?;
+            let json = Zeroizing::new(format!("{}\n", package.to_json().as_str()));
+            match output {
+                Some(path) => {
+                    if path.exists() {
+                        anyhow::bail!("{} already exists", path.display());
+                    }
+                    write_key_file(&path, json.as_bytes(), true, false)?;
+                    eprintln!("wrote {}", path.display());
+                }
+                None => print!("{}", json.as_str()),
+            }
+            eprintln!("group public key: {}", hex::encode(group.public_key()));
+            Ok(())
+        }
+        FrostCommand::Commit {
+            key_package,
+            nonces,
+        } => {
+            let package = KeyPackage::from_json(&Zeroizing::new(read(&key_package)?))?;
+            if nonces.exists() {
+                anyhow::bail!(
+                    "{} already exists; nonces must not be reused",
+                    nonces.display()
+                );
+            }
+            let (signing_nonces, commitment) = package.commit()?;
+            let json = Zeroizing::new(format!("{}\n", signing_nonces.to_json().as_str()));
+            write_key_file(&nonces, json.as_bytes(), true, false)?;
+            println!("{}", commitment.to_json());
+            Ok(())
+        }
+        FrostCommand::Sign {
+            key_package,
+            nonces,
+            message,
+            commitments,
+        } => {
+            let package = KeyPackage::from_json(&Zeroizing::new(read(&key_package)?))?;
+            let signing_nonces = SigningNonces::from_json(&Zeroizing::new(read(&nonces)?))?;
+            let commitments = read_commitments(&commitments)?;
+            let message = fs::read(&message)
+                .with_context(|| format!("Failed to read file: {}", message.display()))?;
+
+            // Remove the nonces before any share leaves the process
+            fs::remove_file(&nonces)
+                .with_context(|| format!("Failed to remove {}", nonces.display()))?;
+            let share = package.sign(&signing_nonces, &commitments, &message)?;
+            println!("{}", share.to_json());
+            Ok(())
+        }
+        FrostCommand::Aggregate {
+            public_key,
+            message,
+            commitments,
+            shares,
+        } => {
+            let public_key = read_public_key(&public_key)?;
+            let message = fs::read(&message)
+                .with_context(|| format!("Failed to read file: {}", message.display()))?;
+            let commitments = read_commitments(&commitments)?;
+            let shares = shares
+                .iter()
+                .map(|path| Ok(SignatureShare::from_json(&read(path)?)?))
+                .collect::<Result<Vec<_>>>()?;
+
+            let signature = frost::aggregate(&public_key, &commitments, &shares, &message)?;
+            println!("{}", hex::encode(signature));
+            Ok(())
+        }
+    }
+}
+
+fn ssh_command(seed: &SeedArgs, key_use: &KeyUseArgs, command: SshCommand) -> Result<()> {
+    let (target, install) = match command {
+        SshCommand::Install { target } => (target, true),
+        SshCommand::Remove { target } => (target, false),
+        SshCommand::Config {
+            manifest,
+            ssh_config,
+            key_dir,
+            identity_agent,
+            parent_entropy,
+        } => {
+            return ssh_config_command(
+                seed,
+                key_use,
+                &manifest,
+                ssh_config,
+                key_dir,
+                identity_agent,
+                parent_entropy,
+            )
+        }
+    };
+    let (path, file) = match (target.authorized_keys, target.known_hosts) {
+        (Some(path), _) => (path, SshKeyFile::AuthorizedKeys),
+        (None, Some(path)) => (path, SshKeyFile::KnownHosts { hosts: target.host }),
+        (None, None) => unreachable!("clap requires --authorized-keys or --known-hosts"),
+    };
+
+    let key_derivation = load_entity(&target.entity_file)?;
+    let keychain = load_keychain(seed)?;
+    let parent_entropy = parse_parent_entropy(target.parent_entropy)?;
+    let derived_key = derive_key_from_entity(&keychain, &key_derivation, &parent_entropy)
+        .context("Failed to derive key from entity")?;
+    let keypair = Ed25519Keypair::from_derived_key(&derived_key);
+    let public_key = keypair.public_key_bytes();
+
+    let contents = match fs::read_to_string(&path) {
+        Ok(contents) => contents,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => String::new(),
+        Err(e) => return Err(e).with_context(|| format!("Failed to read {}", path.display())),
+    };
+
+    let updated = if install {
+        // Revoked keys must not be granted access
+        check_key_use(
+            key_use,
+            &key_derivation,
+            &parent_entropy,
+            &derived_key,
+            &target.entity_file,
+            "ssh-install",
+        )?;
+        let comment = key_derivation.purpose.as_deref().unwrap_or("bip-keychain");
+        file.install(&contents, &public_key, comment)
+    } else {
+        file.remove(&contents, &public_key)
+    };
+
+    match updated {
+        Some(updated) => {
+            // sshd ignores authorized_keys files others can write
+            let private = file == SshKeyFile::AuthorizedKeys;
+            write_key_file(&path, updated.as_bytes(), private, true)?;
+            eprintln!(
+                "{} {} {} {}",
+                if install { "Added" } else { "Removed" },
+                keypair.ssh_fingerprint(),
+                if install { "to" } else { "from" },
+                path.display()
+            );
+        }
+        None => eprintln!(
+            "{} is {} {}, unchanged",
+            keypair.ssh_fingerprint(),
+            if install { "already in" } else { "not in" },
+            path.display()
+        ),
+    }
+    Ok(())
+}
+
+fn ssh_config_command(
+    seed: &SeedArgs,
+    key_use: &KeyUseArgs,
+    manifest_file: &Path,
+    ssh_config_file: Option<PathBuf>,
+    key_dir: Option<PathBuf>,
+    identity_agent: Option<String>,
+    parent_entropy_hex: Option<String>,
+) -> Result<()> {
+    let manifest = Manifest::load(manifest_file)
+        .with_context(|| format!("Failed to load manifest: {}", manifest_file.display()))?;
+    let keychain = load_keychain(seed)?;
+    let parent_entropy = parse_parent_entropy(parent_entropy_hex)?;
+    let key_dir = match key_dir {
+        Some(dir) => dir,
+        None => PathBuf::from(env::var("HOME").context("HOME is not set; pass --key-dir")?)
+            .join(".config")
+            .join("bip-keychain"),
+    };
+    // ssh resolves a relative IdentityFile against its own working directory
+    let key_dir = env::current_dir()
+        .context("Failed to read the current directory")?
+        .join(key_dir);
+
+    // Derive every key before writing anything
+    let mut hosts = Vec::new();
+    let mut key_files = Vec::new();
+    for entry in &manifest.entities {
+        let mut key_derivation = entry
+            .load_derivation()
+            .with_context(|| format!("Failed to load entity: {}", entry.file.display()))?;
+        let host = entry.ssh_host(&key_derivation).with_context(|| {
+            format!(
+                "{} has no fqdn; set \"host\" in its manifest entry",
+                entry.file.display()
+            )
+        })?;
+        let derived_key = derive_key_from_entity(&keychain, &key_derivation, &parent_entropy)
+            .with_context(|| format!("Failed to derive key for {}", entry.file.display()))?;
+        check_key_use(
+            key_use,
+            &key_derivation,
+            &parent_entropy,
+            &derived_key,
+            &entry.file,
+            "ssh-config",
+        )?;
+
+        let label = entry.label(&key_derivation);
+        key_derivation.purpose = Some(label.clone());
+        let public_key = format_key(&derived_key, &key_derivation, OutputFormat::SshPublicKey)
+            .with_context(|| format!("Failed to derive key for {}", entry.file.display()))?;
+        let stem = entry
+            .file
+            .file_stem()
+            .map(|stem| stem.to_string_lossy().into_owned())
+            .unwrap_or_else(|| "host".to_string());
+        let key_path = key_dir.join(format!("{}.pub", stem));
+        hosts.push(SshHost {
+            host,
+            identity_file: key_path.to_string_lossy().into_owned(),
+            comment: format!(
+                "{} ({})",
+                label,
+                Ed25519Keypair::from_derived_key(&derived_key).ssh_fingerprint()
+            ),
+        });
+        key_files.push((key_path, public_key + "\n"));
+    }
+
+    let section = ssh_config::render_section(&hosts, identity_agent.as_deref())?;
+    let Some(config_path) = ssh_config_file else {
+        print!("{}", section);
+        return Ok(());
+    };
+
+    for (path, contents) in &key_files {
+        if fs::read(path).ok().as_deref() != Some(contents.as_bytes()) {
+            write_key_file(path, contents.as_bytes(), false, true)?;
+            eprintln!("wrote {}", path.display());
+        }
+    }
+    let contents = match fs::read_to_string(&config_path) {
+        Ok(contents) => contents,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => String::new(),
+        Err(e) => {
+            return Err(e).with_context(|| format!("Failed to read {}", config_path.display()))
+        }
+    };
+    match ssh_config::update_config(&contents, &section)
+        .with_context(|| format!("Failed to update {}", config_path.display()))?
+    {
+        // ssh refuses a config file others can write
+        Some(updated) => {
+            write_key_file(&config_path, updated.as_bytes(), true, true)?;
+            eprintln!("Wrote {} host(s) to {}", hosts.len(), config_path.display());
+        }
+        None => eprintln!("{} is up to date", config_path.display()),
+    }
+    Ok(())
+}
+
+fn git_setup_command(
+    seed: &SeedArgs,
+    entity_file: PathBuf,
+    email: Option<String>,
+    global: bool,
+    key_dir: Option<PathBuf>,
+    parent_entropy_hex: Option<String>,
+) -> Result<()> {
+    let key_derivation = load_entity(&entity_file)?;
+    let keychain = load_keychain(seed)?;
+    let parent_entropy = parse_parent_entropy(parent_entropy_hex)?;
+
+    let email = match email {
+        Some(email) => email,
+        None => git_config_get("user.email")
+            .context("No --email given and git config user.email is not set")?,
+    };
+
+    let key_dir = match key_dir {
+        Some(dir) => dir,
+        None => PathBuf::from(env::var("HOME").context("HOME is not set; pass --key-dir")?)
+            .join(".config")
+            .join("bip-keychain"),
+    };
+    fs::create_dir_all(&key_dir)
+        .with_context(|| format!("Failed to create {}", key_dir.display()))?;
+
+    let derived_key = derive_key_from_entity(&keychain, &key_derivation, &parent_entropy)
+        .context("Failed to derive key from entity")?;
+    let ssh_public_key = format_key(&derived_key, &key_derivation, OutputFormat::SshPublicKey)?;
+
+    // Public key file referenced by user.signingkey
+    let stem = entity_file
+        .file_stem()
+        .map(|s| s.to_string_lossy().into_owned())
+        .unwrap_or_else(|| "signing".to_string());
+    let pubkey_path = key_dir.join(format!("{}.pub", stem));
+    fs::write(&pubkey_path, format!("{}\n", ssh_public_key))
+        .with_context(|| format!("Failed to write {}", pubkey_path.display()))?;
+
+    // allowed_signers: append unless this principal/key pair is already listed
+    let allowed_signers_path = key_dir.join("allowed_signers");
+    let line = sshsig::allowed_signers_line(&email, &ssh_public_key);
+    let existing = fs::read_to_string(&allowed_signers_path).unwrap_or_default();
+    let key_material = ssh_public_key.split_whitespace().nth(1).unwrap_or_default();
+    let already_listed = existing
+        .lines()
+        .any(|l| l.split_whitespace().next() == Some(email.as_str()) && l.contains(key_material));
+    if !already_listed {
+        let mut contents = existing;
+        if !contents.is_empty() && !contents.ends_with('\n') {
+            contents.push('\n');
+        }
+        contents.push_str(&line);
+        contents.push('\n');
+        fs::write(&allowed_signers_path, contents)
+            .with_context(|| format!("Failed to write {}", allowed_signers_path.display()))?;
+    }
+
+    let pubkey_str = pubkey_path.to_string_lossy();
+    let allowed_str = allowed_signers_path.to_string_lossy();
+    for (key, value) in [
+        ("gpg.format", "ssh"),
+        ("user.signingkey", pubkey_str.as_ref()),
+        ("gpg.ssh.allowedSignersFile", allowed_str.as_ref()),
+        ("commit.gpgsign", "true"),
+    ] {
+        git_config_set(global, key, value)?;
+    }
+
+    println!("Public key:       {}", pubkey_path.display());
+    println!("Allowed signers:  {}", allowed_signers_path.display());
+    println!(
+        "Git config:       {} (gpg.format=ssh, commit.gpgsign=true)",
+        if global { "global" } else { "repository" }
+    );
+    println!();
+    println!("Before committing, load the signing key into your SSH agent:");
+    println!(
+        "  bip-keychain derive {} --add-to-agent",
+        entity_file.display()
+    );
+
+    Ok(())
+}
+
+fn cosign_command(
+    seed: &SeedArgs,
+    key_use: &KeyUseArgs,
+    entity_file: PathBuf,
+    prefix: PathBuf,
+    force: bool,
+    parent_entropy_hex: Option<String>,
+) -> Result<()> {
+    let key_derivation = load_entity(&entity_file)?;
+    let password = SecretString::new(env::var("COSIGN_PASSWORD").context(
+        "COSIGN_PASSWORD is not set; export the password for the private key \
+         (it may be empty)",
+    )?);
+
+    let with_suffix = |suffix: &str| {
+        let mut path = prefix.clone().into_os_string();
+        path.push(suffix);
+        PathBuf::from(path)
+    };
+    let key_path = with_suffix(".key");
+    let pub_path = with_suffix(".pub");
+    for path in [&key_path, &pub_path] {
+        if path.exists() && !force {
+            anyhow::bail!(
+                "{} already exists (pass --force to overwrite)",
+                path.display()
+            );
+        }
+    }
+
+    let keychain = load_keychain(seed)?;
+    let parent_entropy = parse_parent_entropy(parent_entropy_hex)?;
+    let derived_key = derive_key_from_entity(&keychain, &key_derivation, &parent_entropy)
+        .context("Failed to derive key from entity")?;
+    check_key_use(
+        key_use,
+        &key_derivation,
+        &parent_entropy,
+        &derived_key,
+        &entity_file,
+        "cosign",
+    )?;
+
+    let keypair = Ed25519Keypair::from_derived_key(&derived_key);
+    let private_key = cosign::encrypt_private_key(&keypair, password.expose_secret().as_bytes())?;
+    write_key_file(&key_path, private_key.as_bytes(), true, force)?;
+    write_key_file(
+        &pub_path,
+        keypair.to_public_key_pem().as_bytes(),
+        false,
+        force,
+    )?;
+    eprintln!("wrote {} and {}", key_path.display(), pub_path.display());
+    Ok(())
+}
+
+/// Read a Git config value
+fn git_config_get(key: &str) -> Result<String> {
+    let output = Command::new("git")
+        .args(["config", "--get", key])
+        .output()
+        .context("Failed to run git")?;
+
+    let value = String::from_utf8_lossy(&output.stdout).trim().to_string();
+    if !output.status.success() || value.is_empty() {
+        anyhow::bail!("git config {} is not set", key);
     }
+    Ok(value)
 }
 
-fn main() -> Result<()> {
-    let cli = Cli::parse();
+/// Set a Git config value in the repository or global config
+fn git_config_set(global: bool, key: &str, value: &str) -> Result<()> {
+    let mut command = Command::new("git");
+    command.arg("config");
+    if global {
+        command.arg("--global");
+    }
 
-    match cli.command {
-        Commands::Derive {
-            entity_file,
-            parent_entropy,
-            format,
-        } => derive_command(entity_file, parent_entropy, format),
-        Commands::GenerateSeed { words } => generate_seed_command(words),
+    let status = command
+        .args([key, value])
+        .status()
+        .context("Failed to run git")?;
+    if !status.success() {
+        anyhow::bail!(
+            "git config {} failed (run inside a repository or pass --global)",
+            key
+        );
     }
+    Ok(())
 }
 
-fn derive_command(
-    entity_file: PathBuf,
+#[cfg(unix)]
+fn agent_command(
+    seed: &SeedArgs,
+    key_use: &KeyUseArgs,
+    manifest_file: PathBuf,
+    socket: PathBuf,
     parent_entropy_hex: Option<String>,
-    format: CliOutputFormat,
 ) -> Result<()> {
-    // Read entity JSON file
-    let entity_json = fs::read_to_string(&entity_file)
+    use std::os::unix::fs::PermissionsExt;
+    use std::os::unix::net::UnixListener;
+
+    let manifest = Manifest::load(&manifest_file)
+        .with_context(|| format!("Failed to load manifest: {}", manifest_file.display()))?;
+    let keychain = load_keychain(seed)?;
+    let parent_entropy = parse_parent_entropy(parent_entropy_hex)?;
+
+    // Every entity is checked before the agent serves any of them
+    let mut identities = Vec::new();
+    for entry in &manifest.entities {
+        let key_derivation = entry
+            .load_derivation()
+            .with_context(|| format!("Failed to load entity: {}", entry.file.display()))?;
+        let derived_key = derive_key_from_entity(&keychain, &key_derivation, &parent_entropy)
+            .with_context(|| format!("Failed to derive key for {}", entry.file.display()))?;
+        check_key_use(
+            key_use,
+            &key_derivation,
+            &parent_entropy,
+            &derived_key,
+            &entry.file,
+            "agent",
+        )?;
+        let comment = entry.label(&key_derivation);
+        identities.push((entry, key_derivation, comment));
+    }
+
+    let mut agent = Agent::new(keychain, &parent_entropy);
+    agent.allow_expired(key_use.allow_expired);
+    for (entry, key_derivation, comment) in identities {
+        agent
+            .add_entity(key_derivation, comment)
+            .with_context(|| format!("Failed to derive key for {}", entry.file.display()))?;
+    }
+
+    let listener = UnixListener::bind(&socket)
+        .with_context(|| format!("Failed to bind agent socket: {}", socket.display()))?;
+    fs::set_permissions(&socket, fs::Permissions::from_mode(0o600))
+        .context("Failed to restrict agent socket permissions")?;
+
+    eprintln!("Serving {} key(s)", agent.len());
+    println!("SSH_AUTH_SOCK={}; export SSH_AUTH_SOCK;", socket.display());
+
+    agent.serve(listener).context("SSH agent stopped")
+}
+
+/// Parse a public key given literally or as a path to a .pub / hex file
+fn read_public_key(public_key: &str) -> Result<[u8; 32]> {
+    let public_key_text = match fs::read_to_string(public_key) {
+        Ok(contents) => contents,
+        Err(_) => public_key.to_string(),
+    };
+    Ok(signing::parse_public_key(&public_key_text)?)
+}
+
+/// Read and parse an entity JSON file
+fn load_entity(entity_file: &Path) -> Result<KeyDerivation> {
+    let entity_json = fs::read_to_string(entity_file)
         .with_context(|| format!("Failed to read entity file: {}", entity_file.display()))?;
 
-    // Parse entity
-    let key_derivation =
-        KeyDerivation::from_json(&entity_json).context("Failed to parse entity JSON")?;
+    KeyDerivation::from_json(&entity_json).context("Failed to parse entity JSON")
+}
 
-    // Get seed phrase from environment variable
-    let seed_phrase = env::var("BIP_KEYCHAIN_SEED").context(
-        "BIP_KEYCHAIN_SEED environment variable not set.\n\
-         Set your BIP-39 seed phrase: export BIP_KEYCHAIN_SEED=\"your twelve word phrase...\"\n\
-         \n\
-         For security reasons, we require the seed phrase to be passed via environment variable\n\
-         rather than command-line arguments (which would be visible in process listings).",
-    )?;
+/// Create the keychain from the configured seed source
+fn load_keychain(seed: &SeedArgs) -> Result<Keychain> {
+    let seed_phrase = load_seed_phrase(seed)?;
+    if seed.format() != SeedFormat::Bip39 {
+        return Ok(decode_foreign_seed(seed, seed_phrase.expose_secret())?.keychain()?);
+    }
 
     // Create keychain from seed phrase
-    let keychain = Keychain::from_mnemonic(&seed_phrase).context(
+    let keychain = match seed.language {
+        Some(language) => Keychain::from_mnemonic_in(seed_phrase.expose_secret(), language.into()),
+        None => Keychain::from_mnemonic(seed_phrase.expose_secret()),
+    };
+    keychain.context(
         "Failed to create keychain from seed phrase.\n\
-                  Ensure BIP_KEYCHAIN_SEED contains a valid BIP-39 mnemonic (12-24 words).",
-    )?;
+                  Ensure the seed source contains a valid BIP-39 mnemonic (12-24 words).",
+    )
+}
 
-    // Parse parent entropy (or use default)
-    let parent_entropy = if let Some(hex_str) = parent_entropy_hex {
-        hex::decode(&hex_str).context("Failed to decode parent entropy hex string")?
-    } else {
-        // Default parent entropy (in production, this should be derived from the master seed)
-        b"bip-keychain-default-entropy-32!".to_vec()
+/// Recover a seed in another wallet's mnemonic format (`--seed-format`)
+///
+/// Electrum and aezeed passphrases are read from
+/// `BIP_KEYCHAIN_SEED_PASSPHRASE`.
+fn decode_foreign_seed(seed: &SeedArgs, phrase: &str) -> Result<foreign_mnemonic::ForeignSeed> {
+    let passphrase = Zeroizing::new(env::var("BIP_KEYCHAIN_SEED_PASSPHRASE").unwrap_or_default());
+    let decoded = match seed.format() {
+        SeedFormat::Bip39 => unreachable!("BIP-39 seeds are not a foreign format"),
+        SeedFormat::Electrum => foreign_mnemonic::decode_electrum(phrase, &passphrase),
+        SeedFormat::Aezeed => foreign_mnemonic::decode_aezeed(phrase, &passphrase),
+        SeedFormat::Monero => {
+            let wordlist = match seed.monero_wordlist.as_deref() {
+                Some(path) => {
+                    let text = fs::read_to_string(path).with_context(|| {
+                        format!("Failed to read Monero wordlist: {}", path.display())
+                    })?;
+                    foreign_mnemonic::MoneroWordlist::from_text(&text, 3)?
+                }
+                None => foreign_mnemonic::MoneroWordlist::english(),
+            };
+            foreign_mnemonic::decode_monero(phrase, &wordlist)
+        }
     };
+    decoded.context("Failed to recover the seed from the seed phrase")
+}
 
-    // Derive key
-    let derived_key = derive_key_from_entity(&keychain, &key_derivation, &parent_entropy)
-        .context("Failed to derive key from entity")?;
+/// Read the configured seed as a BIP-39 mnemonic, for commands that back
+/// up or compare the mnemonic itself
+fn load_bip39_seed(seed: &SeedArgs) -> Result<(SecretString, bip39::Mnemonic)> {
+    if seed.format() != SeedFormat::Bip39 {
+        anyhow::bail!("This command works on BIP-39 seeds only (--seed-format bip39)");
+    }
+    let phrase = load_seed_phrase(seed)?;
+    let mnemonic = parse_mnemonic(phrase.expose_secret(), seed.language.map(Into::into))
+        .context("The configured seed is not a valid BIP-39 mnemonic")?;
+    Ok((phrase, mnemonic))
+}
 
-    // Format and output
-    let output_format: OutputFormat = format.into();
-    let output = format_key(&derived_key, &key_derivation, output_format)
-        .context("Failed to format key output")?;
+/// Read the seed phrase from the configured source
+fn load_seed_phrase(seed: &SeedArgs) -> Result<SecretString> {
+    if let Some(path) = &seed.seed_file {
+        warn_if_readable_by_others(path);
+        let file = fs::File::open(path).map_err(|e| {
+            seed_source_error(format!(
+                "Failed to open seed file {}: {}",
+                path.display(),
+                e
+            ))
+        })?;
+        return read_seed_phrase(file, &format!("seed file {}", path.display()));
+    }
+    if let Some(fd) = seed.seed_fd {
+        return read_seed_phrase(open_seed_fd(fd)?, &format!("file descriptor {}", fd));
+    }
+
+    match seed.source() {
+        SeedSource::Env => {}
+        #[cfg(feature = "os-keychain")]
+        SeedSource::OsKeychain => {
+            return bip_keychain::os_keychain::load_seed(seed.account())
+                .context("Failed to read seed from the OS keychain");
+        }
+        #[cfg(not(feature = "os-keychain"))]
+        SeedSource::OsKeychain => {
+            return Err(seed_source_error(
+                "This build does not include OS keychain support.\n\
+                 Rebuild with: cargo install bip-keychain --features os-keychain"
+                    .to_string(),
+            ))
+        }
+    }
+
+    // Get seed phrase from environment variable
+    let seed_phrase = env::var("BIP_KEYCHAIN_SEED").map_err(|_| {
+        seed_source_error(
+            "BIP_KEYCHAIN_SEED environment variable not set.\n\
+             Set your BIP-39 seed phrase: export BIP_KEYCHAIN_SEED=\"your twelve word phrase...\"\n\
+             \n\
+             For security reasons, we require the seed phrase to be passed via environment variable\n\
+             rather than command-line arguments (which would be visible in process listings)."
+                .to_string(),
+        )
+    })?;
+
+    Ok(SecretString::new(seed_phrase))
+}
+
+/// A seed-loading failure, which exits with the seed source exit code
+fn seed_source_error(message: String) -> anyhow::Error {
+    BipKeychainError::SeedSourceError(message).into()
+}
+
+/// Read a whitespace-separated mnemonic from `source`, normalizing it to
+/// single spaces
+fn read_seed_phrase(mut reader: impl std::io::Read, source: &str) -> Result<SecretString> {
+    let mut input = Zeroizing::new(String::new());
+    reader
+        .read_to_string(&mut input)
+        .map_err(|e| seed_source_error(format!("Failed to read seed from {}: {}", source, e)))?;
+
+    let phrase = SecretString::new(input.split_whitespace().collect::<Vec<_>>().join(" "));
+    if phrase.expose_secret().is_empty() {
+        return Err(seed_source_error(format!(
+            "No seed phrase found in {}",
+            source
+        )));
+    }
+    Ok(phrase)
+}
+
+/// Take ownership of an inherited file descriptor
+#[cfg(unix)]
+fn open_seed_fd(fd: i32) -> Result<fs::File> {
+    use std::os::unix::io::FromRawFd;
+
+    if fd < 0 {
+        return Err(seed_source_error(format!(
+            "Invalid file descriptor: {}",
+            fd
+        )));
+    }
+    if fd == 1 || fd == 2 {
+        return Err(seed_source_error(format!(
+            "Refusing to read the seed from stdout/stderr (fd {})",
+            fd
+        )));
+    }
+    if !Path::new(&format!("/dev/fd/{}", fd)).exists() {
+        return Err(seed_source_error(format!(
+            "File descriptor {} is not open",
+            fd
+        )));
+    }
+    // SAFETY: the caller passed this descriptor to us to read the seed from,
+    // it was checked to be open, and nothing else in the process uses it. The
+    // returned File closes it once the seed has been read.
+    Ok(unsafe { fs::File::from_raw_fd(fd) })
+}
+
+#[cfg(not(unix))]
+fn open_seed_fd(_: i32) -> Result<fs::File> {
+    Err(seed_source_error(
+        "--seed-fd is only supported on unix platforms; use --seed-file".to_string(),
+    ))
+}
+
+/// Warn when a seed file can be read by other users
+fn warn_if_readable_by_others(path: &Path) {
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        if let Ok(metadata) = fs::metadata(path) {
+            if metadata.is_file() && metadata.permissions().mode() & 0o077 != 0 {
+                eprintln!(
+                    "warning: seed file {} is accessible by other users (chmod 600 it)",
+                    path.display()
+                );
+            }
+        }
+    }
+    #[cfg(not(unix))]
+    let _ = path;
+}
+
+#[cfg(feature = "os-keychain")]
+fn store_seed_command(seed: &SeedArgs, delete: bool) -> Result<()> {
+    use bip_keychain::os_keychain;
+
+    if delete {
+        os_keychain::delete_seed(seed.account())
+            .context("Failed to delete seed from the OS keychain")?;
+        eprintln!(
+            "Removed seed for account '{}' from the OS keychain",
+            seed.account()
+        );
+        return Ok(());
+    }
 
-    println!("{}", output);
+    let phrase = read_seed_phrase(std::io::stdin(), "stdin")?;
+
+    os_keychain::store_seed(seed.account(), &phrase)
+        .context("Failed to store seed in the OS keychain")?;
+
+    eprintln!(
+        "Stored seed for account '{}' in the OS keychain",
+        seed.account()
+    );
+    eprintln!(
+        "Use it with: bip-keychain --seed-source os-keychain --keychain-account {} derive ...",
+        seed.account()
+    );
 
     Ok(())
 }
 
-fn generate_seed_command(words: usize) -> Result<()> {
-    use bip39::Mnemonic;
+/// Decode hex parent entropy, falling back to the config's policy
+fn parse_parent_entropy(parent_entropy_hex: Option<String>) -> Result<Vec<u8>> {
+    if let Some(hex_str) = parent_entropy_hex {
+        return hex::decode(&hex_str).context("Failed to decode parent entropy hex string");
+    }
+
+    match &config().parent_entropy {
+        Some(ParentEntropyPolicy::Require) => anyhow::bail!(
+            "No --parent-entropy given, and the config file requires one (parent-entropy = \"require\")"
+        ),
+        Some(ParentEntropyPolicy::Fixed(parent_entropy)) => Ok(parent_entropy.clone()),
+        // Default parent entropy (in production, this should be derived from the master seed)
+        Some(ParentEntropyPolicy::Default) | None => Ok(DEFAULT_PARENT_ENTROPY.to_vec()),
+    }
+}
 
-    // Validate word count and calculate entropy size
-    // BIP-39 spec: each word encodes 11 bits
-    // Total bits = words * 11 = entropy bits + checksum bits
-    let entropy_bytes = match words {
-        12 => 16, // 128 bits entropy + 4 bits checksum = 132 bits / 11 = 12 words
-        15 => 20, // 160 bits entropy + 5 bits checksum = 165 bits / 11 = 15 words
-        18 => 24, // 192 bits entropy + 6 bits checksum = 198 bits / 11 = 18 words
-        21 => 28, // 224 bits entropy + 7 bits checksum = 231 bits / 11 = 21 words
-        24 => 32, // 256 bits entropy + 8 bits checksum = 264 bits / 11 = 24 words
-        _ => anyhow::bail!(
+fn generate_seed_command(
+    seed: &SeedArgs,
+    words: usize,
+    sskr_policy: Option<String>,
+    output_dir: Option<PathBuf>,
+    reveal: bool,
+) -> Result<()> {
+    // Validate word count up front for a friendlier message
+    if ![12, 15, 18, 21, 24].contains(&words) {
+        anyhow::bail!(
             "Invalid word count: {}\n\
              \n\
              Word count must be one of: 12, 15, 18, 21, or 24\n\
@@ -174,23 +4467,22 @@ fn generate_seed_command(words: usize) -> Result<()> {
              - 24 words: Maximum security (256 bits entropy)\n\
              - 12 words: Good security, easier to write down (128 bits entropy)",
             words
-        ),
-    };
+        );
+    }
 
-    // Generate cryptographically secure random entropy
-    // Uses getrandom crate which uses OS-provided CSPRNG (ChaCha20, /dev/urandom, etc.)
-    let mut entropy = vec![0u8; entropy_bytes];
-    getrandom::getrandom(&mut entropy).context(
+    // Entropy comes from the OS CSPRNG (see generate_mnemonic)
+    let language = seed.language.map_or(Language::English, Into::into);
+    let mnemonic = generate_mnemonic_in(language, words).context(
         "Failed to generate secure random entropy.\n\
                   This usually indicates a problem with the system's random number generator.",
     )?;
 
-    // Create mnemonic from entropy
-    let mnemonic =
-        Mnemonic::from_entropy(&entropy).context("Failed to generate mnemonic from entropy")?;
+    if let (Some(policy), Some(dir)) = (sskr_policy, output_dir) {
+        return write_seed_shares(&mnemonic, &policy, &dir, reveal);
+    }
 
     // Display the mnemonic
-    println!("{}", mnemonic);
+    println!("{}", *mnemonic_phrase(&mnemonic));
 
     // Print security warnings to stderr so they don't interfere with piping the mnemonic
     eprintln!();
@@ -223,3 +4515,706 @@ fn generate_seed_command(words: usize) -> Result<()> {
 
     Ok(())
 }
+
+fn check_seed_command(seed: &SeedArgs) -> Result<()> {
+    use bip_keychain::mnemonic::VALID_WORD_COUNTS;
+    use std::io::IsTerminal;
+
+    if std::io::stdin().is_terminal() {
+        eprintln!("Enter the seed phrase, then press Ctrl-D:");
+    }
+    let phrase = read_seed_phrase(std::io::stdin(), "stdin")?;
+    if seed.format() != SeedFormat::Bip39 {
+        return check_foreign_seed(seed, phrase.expose_secret());
+    }
+    let check = match seed.language {
+        Some(language) => check_mnemonic_in(phrase.expose_secret(), language.into()),
+        None => check_mnemonic(phrase.expose_secret()),
+    };
+
+    if !check.valid_word_count() {
+        println!(
+            "✗ {} words; a BIP-39 phrase has {}",
+            check.word_count,
+            VALID_WORD_COUNTS.map(|n| n.to_string()).join(", ")
+        );
+    }
+    for unknown in &check.unknown_words {
+        if unknown.suggestions.is_empty() {
+            println!(
+                "✗ Word {} '{}' is not in the BIP-39 wordlist",
+                unknown.position, unknown.word
+            );
+        } else {
+            println!(
+                "✗ Word {} '{}' is not in the BIP-39 wordlist (did you mean: {}?)",
+                unknown.position,
+                unknown.word,
+                unknown.suggestions.join(", ")
+            );
+        }
+    }
+    if check.checksum_valid == Some(false) {
+        println!("✗ Checksum does not match; a word is probably wrong");
+        if !check.repairs.is_empty() {
+            println!("  Changing one of these words would fix it:");
+            for repair in &check.repairs {
+                println!(
+                    "    word {}: {} -> {}",
+                    repair.position, repair.word, repair.replacement
+                );
+            }
+        }
+    }
+
+    if !check.is_valid() {
+        return Err(BipKeychainError::InvalidSeedPhrase(
+            "Seed phrase failed validation".to_string(),
+        )
+        .into());
+    }
+
+    println!(
+        "✓ Valid BIP-39 seed phrase ({} words, {:?})",
+        check.word_count, check.language
+    );
+    Ok(())
+}
+
+/// check-seed for --seed-format other than bip39: decode it and show what
+/// it holds
+fn check_foreign_seed(seed: &SeedArgs, phrase: &str) -> Result<()> {
+    use foreign_mnemonic::ForeignSeedKind;
+
+    let decoded = match decode_foreign_seed(seed, phrase) {
+        Ok(decoded) => decoded,
+        Err(e) => {
+            match e.downcast_ref::<BipKeychainError>() {
+                Some(BipKeychainError::InvalidSeedPhrase(reason)) => println!("✗ {}", reason),
+                _ => println!("✗ {:#}", e),
+            }
+            return Err(BipKeychainError::InvalidSeedPhrase(
+                "Seed phrase failed validation".to_string(),
+            )
+            .into());
+        }
+    };
+    match decoded.kind {
+        ForeignSeedKind::Electrum(seed_type) => {
+            println!("✓ Valid Electrum seed ({} wallet)", seed_type.name())
+        }
+        ForeignSeedKind::Aezeed { version, .. } => {
+            println!("✓ Valid aezeed (internal version {})", version);
+            if let Some(date) = decoded.birthday_date() {
+                println!("  Created: {} (rescan from here)", date);
+            }
+        }
+        ForeignSeedKind::Monero => println!("✓ Valid Monero seed"),
+    }
+    println!(
+        "  Master fingerprint: {}",
+        hex::encode(decoded.keychain()?.fingerprint())
+    );
+    Ok(())
+}
+
+fn verify_backup_command(seed: &SeedArgs, count: usize) -> Result<()> {
+    use bip_keychain::mnemonic::{random_positions, word_matches};
+    use std::io::BufRead;
+
+    if count == 0 {
+        anyhow::bail!("--count must be at least 1");
+    }
+
+    let (phrase, mnemonic) = load_bip39_seed(seed)?;
+    let positions = random_positions(mnemonic.word_count(), count)?;
+
+    eprintln!(
+        "Enter the requested words from your backup ({} of {}):",
+        positions.len(),
+        mnemonic.word_count()
+    );
+    let mut lines = std::io::stdin().lock().lines();
+    let mut mismatches = Vec::new();
+    for &position in &positions {
+        eprint!("Word #{}: ", position);
+        std::io::stderr().flush()?;
+        let answer = Zeroizing::new(
+            lines
+                .next()
+                .context("Input ended before all words were entered")?
+                .context("Failed to read answer from stdin")?,
+        );
+        if !word_matches(phrase.expose_secret(), position, &answer) {
+            mismatches.push(position);
+        }
+    }
+
+    let checked = positions
+        .iter()
+        .map(|p| p.to_string())
+        .collect::<Vec<_>>()
+        .join(", ");
+    if !mismatches.is_empty() {
+        for position in &mismatches {
+            println!("✗ Word #{} does not match", position);
+        }
+        anyhow::bail!(
+            "Backup does not match the seed ({} of {} words wrong)",
+            mismatches.len(),
+            positions.len()
+        );
+    }
+
+    println!("✓ Backup matches the seed (checked words {})", checked);
+    Ok(())
+}
+
+/// The phrase as it should be written down
+///
+/// BIP-39 separates Japanese words with ideographic spaces (U+3000).
+fn mnemonic_phrase(mnemonic: &bip39::Mnemonic) -> Zeroizing<String> {
+    let separator = match mnemonic.language() {
+        Language::Japanese => "\u{3000}",
+        _ => " ",
+    };
+    Zeroizing::new(mnemonic.words().collect::<Vec<_>>().join(separator))
+}
+
+fn write_seed_shares(
+    mnemonic: &bip39::Mnemonic,
+    policy: &str,
+    output_dir: &Path,
+    reveal: bool,
+) -> Result<()> {
+    use bip_keychain::sskr::{self, GroupSpec};
+    use std::io::Write;
+
+    let group = GroupSpec::parse(policy).context("Invalid --sskr policy")?;
+    let entropy = Zeroizing::new(mnemonic.to_entropy());
+    let groups = sskr::generate_shares(&entropy, 1, &[group])
+        .context("Failed to split seed into SSKR shares")?;
+
+    fs::create_dir_all(output_dir).with_context(|| {
+        format!(
+            "Failed to create output directory: {}",
+            output_dir.display()
+        )
+    })?;
+
+    let mut paths = Vec::new();
+    for (i, share) in groups[0].iter().enumerate() {
+        let path = output_dir.join(format!("share-{}.hex", i + 1));
+        let mut file = create_share_file(&path)?;
+        writeln!(file, "{}", hex::encode(share))
+            .with_context(|| format!("Failed to write share file: {}", path.display()))?;
+        paths.push(path);
+    }
+
+    if reveal {
+        println!("{}", *mnemonic_phrase(mnemonic));
+    }
+
+    eprintln!(
+        "Wrote {} SSKR shares ({} of {} required to recover):",
+        paths.len(),
+        group.member_threshold,
+        group.member_count
+    );
+    for path in &paths {
+        eprintln!("  {}", path.display());
+    }
+    eprintln!();
+    eprintln!("⚠️  Move each share to a separate secure location, then delete this directory.");
+    eprintln!("   Check a set of shares with: bip-keychain verify-shares <share-hex>...");
+    if !reveal {
+        eprintln!("   The mnemonic was not displayed (pass --reveal to print it).");
+    }
+
+    Ok(())
+}
+
+/// Create a new owner-only file for a share; never overwrites
+fn create_share_file(path: &Path) -> Result<fs::File> {
+    let mut options = fs::OpenOptions::new();
+    options.write(true).create_new(true);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::OpenOptionsExt;
+        options.mode(0o600);
+    }
+    options.open(path).with_context(|| {
+        format!(
+            "Failed to create share file (refusing to overwrite): {}",
+            path.display()
+        )
+    })
+}
+
+fn backup_seed_command(
+    seed: &SeedArgs,
+    policy: &str,
+    name: Option<String>,
+    qr_output: BackupQrOutput,
+    output_dir: Option<PathBuf>,
+) -> Result<()> {
+    use bip_keychain::qr::QrCode;
+    use bip_keychain::sskr::{self, GroupSpec};
+    use std::io::{BufRead, IsTerminal, Write};
+
+    let group = GroupSpec::parse(policy).context("Invalid --sskr policy")?;
+    let (_, mnemonic) = load_bip39_seed(seed)?;
+    let entropy = Zeroizing::new(mnemonic.to_entropy());
+    let groups = sskr::generate_shares(&entropy, 1, &[group])
+        .context("Failed to split seed into SSKR shares")?;
+
+    let mut codes = vec![(
+        "seed".to_string(),
+        "Seed (ur:crypto-seed)".to_string(),
+        seed_ur::encode_seed(&entropy, name.as_deref()),
+    )];
+    for (i, share) in groups[0].iter().enumerate() {
+        codes.push((
+            format!("share-{}", i + 1),
+            format!(
+                "SSKR share {} of {} ({} required to recover)",
+                i + 1,
+                group.member_count,
+                group.member_threshold
+            ),
+            Zeroizing::new(seed_ur::encode_share(share)),
+        ));
+    }
+
+    if let Some(dir) = output_dir {
+        fs::create_dir_all(&dir)
+            .with_context(|| format!("Failed to create output directory: {}", dir.display()))?;
+        for (stem, title, ur) in &codes {
+            let qr = QrCode::encode(ur.to_ascii_uppercase().as_bytes())?;
+            let path = match qr_output {
+                BackupQrOutput::Terminal => {
+                    let path = dir.join(format!("{}.txt", stem));
+                    let mut file = create_share_file(&path)?;
+                    write!(file, "{}\n\n{}{}\n", title, qr.to_terminal_string(), **ur)
+                        .with_context(|| format!("Failed to write {}", path.display()))?;
+                    path
+                }
+                BackupQrOutput::Png => {
+                    let path = dir.join(format!("{}.png", stem));
+                    create_share_file(&path)?
+                        .write_all(&qr.to_png(8)?)
+                        .with_context(|| format!("Failed to write {}", path.display()))?;
+                    path
+                }
+            };
+            eprintln!("  {}", path.display());
+        }
+        eprintln!();
+        eprintln!("⚠️  Move the seed and each share to separate secure locations, then");
+        eprintln!("   delete {}.", dir.display());
+    } else {
+        // Show one code at a time when someone is at the terminal
+        let interactive = std::io::stdout().is_terminal() && std::io::stdin().is_terminal();
+        let mut lines = std::io::stdin().lock().lines();
+        for (i, (_, title, ur)) in codes.iter().enumerate() {
+            let qr = QrCode::encode(ur.to_ascii_uppercase().as_bytes())?;
+            println!("{}\n", title);
+            print!("{}", qr.to_terminal_string());
+            println!("{}\n", **ur);
+            if interactive {
+                let next = match codes.get(i + 1) {
+                    Some((_, next, _)) => format!("show: {}", next),
+                    None => "clear the screen".to_string(),
+                };
+                eprint!("Press Enter to {}...", next);
+                std::io::stderr().flush()?;
+                lines.next();
+                print!("\x1b[2J\x1b[3J\x1b[H");
+                std::io::stdout().flush()?;
+            }
+        }
+    }
+
+    eprintln!(
+        "Backed up the seed and {} SSKR shares ({} of {} required to recover).",
+        group.member_count, group.member_threshold, group.member_count
+    );
+    eprintln!("   Check the backup with: bip-keychain restore-seed --check <UR>...");
+    Ok(())
+}
+
+fn export_seedqr_command(
+    seed: &SeedArgs,
+    compact: bool,
+    format: SeedQrOutput,
+    output: Option<PathBuf>,
+) -> Result<()> {
+    use bip_keychain::seed_qr::{self, SeedQrFormat};
+    use std::io::Write;
+
+    let (_, mnemonic) = load_bip39_seed(seed)?;
+    let seed_qr_format = if compact {
+        SeedQrFormat::Compact
+    } else {
+        SeedQrFormat::Standard
+    };
+
+    let contents = Zeroizing::new(match format {
+        SeedQrOutput::Terminal => seed_qr::to_qr(&mnemonic, seed_qr_format)?.to_terminal_string(),
+        SeedQrOutput::Svg => {
+            let qr = seed_qr::to_qr(&mnemonic, seed_qr_format)?;
+            qr.to_svg(&format!("{}mm", 4 * (qr.size() + 8)))
+        }
+        SeedQrOutput::Payload => {
+            let payload = seed_qr::encode(&mnemonic, seed_qr_format)?;
+            match seed_qr_format {
+                SeedQrFormat::Standard => format!("{}\n", String::from_utf8_lossy(&payload)),
+                SeedQrFormat::Compact => format!("{}\n", hex::encode(&*payload)),
+            }
+        }
+    });
+
+    match output {
+        Some(path) => {
+            create_share_file(&path)?
+                .write_all(contents.as_bytes())
+                .with_context(|| format!("Failed to write {}", path.display()))?;
+            eprintln!("Wrote SeedQR to {}", path.display());
+            eprintln!(
+                "⚠️  The SeedQR is the whole seed: print or transcribe it, then delete the file."
+            );
+        }
+        None => print!("{}", *contents),
+    }
+    Ok(())
+}
+
+fn import_seedqr_command(seed: &SeedArgs, input: Option<&str>, check: bool) -> Result<()> {
+    use bip_keychain::seed_qr;
+
+    let mut data = Zeroizing::new(Vec::new());
+    match input {
+        None => {
+            std::io::Read::read_to_end(&mut std::io::stdin(), &mut data)
+                .context("Failed to read SeedQR from stdin")?;
+        }
+        Some(input) if !Path::new(input).exists() => data.extend_from_slice(input.as_bytes()),
+        Some(path) => {
+            data.extend(fs::read(path).with_context(|| format!("Failed to read {}", path))?);
+        }
+    }
+    // Scanners end their output with a newline, even after binary data
+    if matches!(data.len(), 17 | 33) && data.ends_with(b"\n") {
+        data.pop();
+    }
+    // Compact SeedQR written as hex
+    let trimmed = data.trim_ascii();
+    if matches!(trimmed.len(), 32 | 64) && trimmed.iter().all(u8::is_ascii_hexdigit) {
+        *data = hex::decode(trimmed).expect("checked hex digits");
+    }
+
+    let language = seed.language.map_or(Language::English, Into::into);
+    let (mnemonic, _) = seed_qr::decode(&data, language)?;
+
+    if check {
+        if !is_configured_seed(seed, &mnemonic)? {
+            anyhow::bail!("The SeedQR does not hold the configured seed");
+        }
+        println!(
+            "✓ The SeedQR holds the configured seed ({} words)",
+            mnemonic.word_count()
+        );
+        return Ok(());
+    }
+
+    println!("{}", *mnemonic_phrase(&mnemonic));
+    Ok(())
+}
+
+fn print_backup_command(
+    seed: &SeedArgs,
+    policy: Option<&str>,
+    output_dir: &Path,
+    format: SheetFormat,
+    name: Option<String>,
+) -> Result<()> {
+    use bip_keychain::backup_sheet::BackupSheet;
+    use bip_keychain::sskr::{self, GroupSpec};
+    use std::io::Write;
+
+    let group = policy
+        .map(|policy| GroupSpec::parse(policy).context("Invalid --sskr policy"))
+        .transpose()?;
+    let (phrase, mnemonic) = load_bip39_seed(seed)?;
+    let fingerprint =
+        Keychain::from_mnemonic_in(phrase.expose_secret(), mnemonic.language())?.fingerprint();
+
+    let sheets = match group {
+        None => vec![(
+            "seed".to_string(),
+            BackupSheet::seed(&mnemonic, fingerprint, name.as_deref()),
+        )],
+        Some(group) => {
+            let entropy = Zeroizing::new(mnemonic.to_entropy());
+            let groups = sskr::generate_shares(&entropy, 1, &[group])
+                .context("Failed to split seed into SSKR shares")?;
+            groups[0]
+                .iter()
+                .enumerate()
+                .map(|(i, share)| {
+                    let sheet = BackupSheet::share(
+                        share,
+                        group.member_count,
+                        fingerprint,
+                        name.as_deref(),
+                    )?;
+                    Ok((format!("share-{}", i + 1), sheet))
+                })
+                .collect::<Result<Vec<_>>>()?
+        }
+    };
+
+    fs::create_dir_all(output_dir).with_context(|| {
+        format!(
+            "Failed to create output directory: {}",
+            output_dir.display()
+        )
+    })?;
+    for (stem, sheet) in &sheets {
+        let (extension, contents) = match format {
+            SheetFormat::Text => ("txt", sheet.to_text()?),
+            SheetFormat::Html => ("html", sheet.to_html()?),
+        };
+        let path = output_dir.join(format!("{}.{}", stem, extension));
+        create_share_file(&path)?
+            .write_all(contents.as_bytes())
+            .with_context(|| format!("Failed to write {}", path.display()))?;
+        eprintln!("  {}", path.display());
+    }
+
+    eprintln!();
+    match group {
+        None => eprintln!("Wrote the seed backup sheet."),
+        Some(group) => eprintln!(
+            "Wrote {} SSKR share sheets ({} of {} required to recover).",
+            group.member_count, group.member_threshold, group.member_count
+        ),
+    }
+    eprintln!("⚠️  Print the sheets on an offline printer, store them in separate secure");
+    eprintln!("   locations, then delete {}.", output_dir.display());
+    eprintln!("   Check the sheets with: bip-keychain restore-seed --check <sheet>...");
+    Ok(())
+}
+
+fn bip85_command(seed: &SeedArgs, command: Bip85Command) -> Result<()> {
+    let keychain = load_keychain(seed)?;
+    let (secret, path) = match command {
+        Bip85Command::Mnemonic {
+            words,
+            child_language,
+            index,
+        } => {
+            let language = Language::from(child_language);
+            let mnemonic = bip85::mnemonic(&keychain, language, words, index)?;
+            let path = format!(
+                "m/83696968'/{}'/{}'/{}'/{}'",
+                bip85::BIP39_APP,
+                bip85::language_code(language),
+                words,
+                index
+            );
+            (mnemonic_phrase(&mnemonic), path)
+        }
+        Bip85Command::Hex { bytes, index } => (
+            bip85::hex(&keychain, bytes.into(), index)?,
+            format!("m/83696968'/{}'/{}'/{}'", bip85::HEX_APP, bytes, index),
+        ),
+        Bip85Command::Wif { index } => (
+            bip85::wif(&keychain, index)?,
+            format!("m/83696968'/{}'/{}'", bip85::WIF_APP, index),
+        ),
+    };
+
+    eprintln!("BIP-85 {}", path);
+    println!("{}", *secret);
+    Ok(())
+}
+
+fn is_ur(text: &str) -> bool {
+    text.get(..3).is_some_and(|p| p.eq_ignore_ascii_case("ur:"))
+}
+
+/// The words of a typed-in share or seed, skipping the numbers printed
+/// beside them on a backup sheet; `None` unless every word is a Byteword
+fn bytewords_of(text: &str) -> Option<Vec<&str>> {
+    let words: Vec<&str> = text
+        .split_whitespace()
+        .filter(|word| {
+            !word
+                .trim_end_matches(['.', ')'])
+                .chars()
+                .all(|c| c.is_ascii_digit())
+        })
+        .collect();
+    (!words.is_empty() && words.iter().all(|word| bip_keychain::ur::is_byteword(word)))
+        .then_some(words)
+}
+
+/// Collect the URs in `text`: UR lines if there are any, otherwise each
+/// blank-line-separated block of Bytewords
+fn collect_urs(text: &str, urs: &mut Vec<Zeroizing<String>>) -> Result<()> {
+    let lines: Vec<&str> = text
+        .lines()
+        .map(str::trim)
+        .filter(|line| is_ur(line))
+        .collect();
+    if !lines.is_empty() {
+        urs.extend(
+            lines
+                .iter()
+                .map(|line| Zeroizing::new(line.to_ascii_lowercase())),
+        );
+        return Ok(());
+    }
+    for block in text.split("\n\n").filter(|block| !block.trim().is_empty()) {
+        let words = bytewords_of(block)
+            .context("Expected UR lines or blocks of Bytewords separated by blank lines")?;
+        urs.push(
+            seed_ur::from_bytewords(&words.join(" ")).context("Failed to decode the Bytewords")?,
+        );
+    }
+    Ok(())
+}
+
+/// The URs in `inputs` (literal URs or Bytewords, or files holding them),
+/// or on stdin without inputs
+fn read_urs(inputs: &[String]) -> Result<Vec<Zeroizing<String>>> {
+    let mut urs = Vec::new();
+    if inputs.is_empty() {
+        let mut text = Zeroizing::new(String::new());
+        std::io::Read::read_to_string(&mut std::io::stdin(), &mut text)
+            .context("Failed to read URs from stdin")?;
+        collect_urs(&text.replace("\r\n", "\n"), &mut urs)?;
+    }
+    for input in inputs {
+        if is_ur(input) || (!Path::new(input).exists() && bytewords_of(input).is_some()) {
+            collect_urs(input, &mut urs)?;
+        } else {
+            let text = Zeroizing::new(
+                fs::read_to_string(input).with_context(|| format!("Failed to read {}", input))?,
+            );
+            collect_urs(&text.replace("\r\n", "\n"), &mut urs)
+                .with_context(|| format!("No URs found in {}", input))?;
+        }
+    }
+    urs.sort_by(|a, b| a.as_str().cmp(b.as_str()));
+    urs.dedup();
+    Ok(urs)
+}
+
+/// Whether `mnemonic` holds the same entropy as the configured seed
+fn is_configured_seed(seed: &SeedArgs, mnemonic: &bip39::Mnemonic) -> Result<bool> {
+    let (_, configured) = load_bip39_seed(seed)?;
+    Ok(*Zeroizing::new(configured.to_entropy()) == *Zeroizing::new(mnemonic.to_entropy()))
+}
+
+fn restore_seed_command(seed: &SeedArgs, inputs: &[String], check: bool) -> Result<()> {
+    use bip_keychain::sskr;
+
+    let urs = read_urs(inputs)?;
+    let (seeds, shares): (Vec<_>, Vec<_>) = urs.iter().partition(|ur| {
+        ur.strip_prefix("ur:")
+            .is_some_and(|rest| rest.starts_with(&format!("{}/", seed_ur::SEED_UR_TYPE)))
+    });
+
+    let entropy = match seeds.first() {
+        Some(first) => {
+            let (entropy, _) = seed_ur::decode_seed(first)?;
+            for other in &seeds[1..] {
+                if seed_ur::decode_seed(other)?.0 != entropy {
+                    anyhow::bail!("The crypto-seed URs hold different seeds");
+                }
+            }
+            entropy
+        }
+        None if shares.is_empty() => {
+            anyhow::bail!("No ur:crypto-seed or ur:crypto-sskr found in the input")
+        }
+        None => {
+            let shares = shares
+                .iter()
+                .enumerate()
+                .map(|(i, ur)| {
+                    seed_ur::decode_share(ur).with_context(|| format!("Invalid share UR {}", i + 1))
+                })
+                .collect::<Result<Vec<_>>>()?;
+            Zeroizing::new(sskr::combine_shares(&shares).context("Failed to recover the seed")?)
+        }
+    };
+
+    let language = seed.language.map_or(Language::English, Into::into);
+    let mnemonic = bip39::Mnemonic::from_entropy_in(language, &entropy)
+        .context("Recovered entropy is not a valid BIP-39 seed length")?;
+
+    if check {
+        if !is_configured_seed(seed, &mnemonic)? {
+            anyhow::bail!("The backup does not restore the configured seed");
+        }
+        println!(
+            "✓ The backup restores the configured seed ({} words)",
+            mnemonic.word_count()
+        );
+        return Ok(());
+    }
+
+    println!("{}", *mnemonic_phrase(&mnemonic));
+    Ok(())
+}
+
+fn verify_shares_command(shares_hex: Vec<String>) -> Result<()> {
+    use bip_keychain::sskr;
+
+    let shares = shares_hex
+        .iter()
+        .enumerate()
+        .map(|(i, s)| {
+            hex::decode(s.trim()).with_context(|| format!("Share {} is not valid hex", i + 1))
+        })
+        .collect::<Result<Vec<_>>>()?;
+
+    for (i, share) in shares.iter().enumerate() {
+        let info = sskr::inspect_share(share)
+            .with_context(|| format!("Failed to parse share {}", i + 1))?;
+        println!(
+            "Share {}: id {:04x}, group {}/{} (member threshold {}), member {}",
+            i + 1,
+            info.identifier,
+            info.group_index + 1,
+            info.group_count,
+            info.member_threshold,
+            info.member_index + 1
+        );
+    }
+
+    let report = sskr::verify_shares(&shares).context("Shares are not consistent")?;
+
+    println!();
+    println!(
+        "All {} shares belong to split {:04x} ({} of {} groups required)",
+        shares.len(),
+        report.identifier,
+        report.group_threshold,
+        report.group_count
+    );
+    if report.sufficient {
+        println!("✓ These shares meet the recovery threshold");
+    } else {
+        println!(
+            "✗ Not enough shares to recover: {} of {} required groups complete",
+            report.complete_groups.len(),
+            report.group_threshold
+        );
+    }
+
+    Ok(())
+}
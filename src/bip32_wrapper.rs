@@ -2,10 +2,17 @@
 //!
 //! Simplifies BIP-32 operations with sensible defaults for BIP-Keychain.
 //! Derives keys at the path: m/83696968'/67797668'/{index}'
+//!
+//! Secret material is wiped on drop: private scalars are zeroized by `k256`,
+//! and seeds and key bytes handed out by this module are wrapped in
+//! [`Zeroizing`].
 
 use crate::error::{BipKeychainError, Result};
-use bip32::XPrv;
+use bip32::{ChildNumber, Prefix, XPrv, XPub};
 use bip39::Mnemonic;
+use std::borrow::Cow;
+use std::fmt;
+use zeroize::Zeroizing;
 
 /// BIP-Keychain path constants
 ///
@@ -16,10 +23,83 @@ use bip39::Mnemonic;
 pub const BIP85_APP: u32 = 83696968;
 pub const BIPKEYCHAIN_APP: u32 = 67797668;
 
+/// BIP-39 wordlist languages
+pub use bip39::Language;
+
+/// Generate a new random English BIP-39 mnemonic
+///
+/// `word_count` must be 12, 15, 18, 21, or 24. Entropy comes from the
+/// operating system's CSPRNG and is zeroized after use.
+pub fn generate_mnemonic(word_count: usize) -> Result<Mnemonic> {
+    generate_mnemonic_in(Language::English, word_count)
+}
+
+/// Generate a new random BIP-39 mnemonic from `language`'s wordlist
+pub fn generate_mnemonic_in(language: Language, word_count: usize) -> Result<Mnemonic> {
+    // Each word encodes 11 bits: entropy plus a checksum of entropy/32 bits
+    let entropy_bytes = match word_count {
+        12 => 16,
+        15 => 20,
+        18 => 24,
+        21 => 28,
+        24 => 32,
+        _ => {
+            return Err(BipKeychainError::InvalidSeedPhrase(format!(
+                "Invalid word count: {}",
+                word_count
+            )))
+        }
+    };
+
+    let mut entropy = Zeroizing::new(vec![0u8; entropy_bytes]);
+    getrandom::getrandom(&mut entropy).map_err(|e| {
+        BipKeychainError::SeedSourceError(format!(
+            "Failed to generate secure random entropy: {}",
+            e
+        ))
+    })?;
+
+    Mnemonic::from_entropy_in(language, &entropy).map_err(|e| {
+        BipKeychainError::InvalidSeedPhrase(format!("Failed to build mnemonic: {}", e))
+    })
+}
+
+/// Parse a BIP-39 mnemonic, detecting its language unless one is given
+///
+/// The phrase is NFKD-normalized first, so words typed with composed
+/// accents (e.g. French or Spanish) and Japanese phrases separated by
+/// ideographic spaces parse as the spec requires. If the words appear in
+/// more than one wordlist, the first language whose checksum matches wins.
+pub fn parse_mnemonic(phrase: &str, language: Option<Language>) -> Result<Mnemonic> {
+    let mut normalized = Cow::Borrowed(phrase);
+    Mnemonic::normalize_utf8_cow(&mut normalized);
+
+    let result = match language {
+        Some(language) => Mnemonic::parse_in_normalized(language, &normalized),
+        None => match Mnemonic::language_of(&*normalized) {
+            Err(bip39::Error::AmbiguousLanguages(candidates)) => candidates
+                .iter()
+                .map(|language| Mnemonic::parse_in_normalized(language, &normalized))
+                .find(|result| result.is_ok())
+                .unwrap_or(Err(bip39::Error::AmbiguousLanguages(candidates))),
+            detected => {
+                detected.and_then(|language| Mnemonic::parse_in_normalized(language, &normalized))
+            }
+        },
+    };
+
+    if let Cow::Owned(mut normalized) = normalized {
+        zeroize::Zeroize::zeroize(&mut normalized);
+    }
+    result.map_err(|e| BipKeychainError::InvalidSeedPhrase(format!("Invalid mnemonic: {}", e)))
+}
+
 /// Keychain wrapper for BIP-32 hierarchical deterministic key derivation
 pub struct Keychain {
     /// Master extended private key derived from seed
     master_key: XPrv,
+    /// Cached application-level key at m/83696968'/67797668'
+    app_key: XPrv,
 }
 
 impl Keychain {
@@ -30,20 +110,114 @@ impl Keychain {
     /// ```ignore
     /// let keychain = Keychain::from_mnemonic("your twelve word seed phrase...")?;
     /// ```
+    ///
+    /// The phrase may be in any BIP-39 language; it is NFKD-normalized and
+    /// its wordlist detected (see [`parse_mnemonic`]).
     pub fn from_mnemonic(phrase: &str) -> Result<Self> {
-        // Parse the mnemonic phrase
-        let mnemonic = Mnemonic::parse(phrase)
-            .map_err(|e| BipKeychainError::InvalidSeedPhrase(format!("Invalid mnemonic: {}", e)))?;
+        Self::from_parsed_mnemonic(parse_mnemonic(phrase, None)?)
+    }
+
+    /// Create a keychain from a mnemonic in a known language
+    pub fn from_mnemonic_in(phrase: &str, language: Language) -> Result<Self> {
+        Self::from_parsed_mnemonic(parse_mnemonic(phrase, Some(language))?)
+    }
 
+    fn from_parsed_mnemonic(mnemonic: Mnemonic) -> Result<Self> {
         // Convert mnemonic to seed (no password)
-        let seed = mnemonic.to_seed("");
+        let seed = Zeroizing::new(mnemonic.to_seed(""));
 
         // Derive master key from seed
+        let master_key = XPrv::new(&seed[..]).map_err(|e| {
+            BipKeychainError::Bip32Error(format!("Failed to derive master key: {}", e))
+        })?;
+
+        Self::from_master_key(master_key)
+    }
+
+    /// Create a keychain directly from a BIP-32 seed
+    ///
+    /// Use this when the master secret is not a BIP-39 mnemonic (for example,
+    /// a seed produced by other tooling). BIP-32 accepts 16 to 64 bytes.
+    ///
+    /// # Example
+    ///
+    /// ```ignore
+    /// let keychain = Keychain::from_seed_bytes(&seed)?;
+    /// ```
+    pub fn from_seed_bytes(seed: &[u8]) -> Result<Self> {
+        if !(16..=64).contains(&seed.len()) {
+            return Err(BipKeychainError::InvalidSeedPhrase(format!(
+                "Seed must be 16 to 64 bytes, got {}",
+                seed.len()
+            )));
+        }
+
         let master_key = XPrv::new(seed).map_err(|e| {
             BipKeychainError::Bip32Error(format!("Failed to derive master key: {}", e))
         })?;
 
-        Ok(Self { master_key })
+        Self::from_master_key(master_key)
+    }
+
+    /// Create a keychain from hex-encoded BIP-39 entropy
+    ///
+    /// The entropy (16, 20, 24, 28, or 32 bytes, e.g. from a hardware RNG) is
+    /// treated exactly like the mnemonic it encodes, so the resulting keys
+    /// match those from `from_mnemonic` for the equivalent phrase.
+    ///
+    /// # Example
+    ///
+    /// ```ignore
+    /// let keychain = Keychain::from_entropy_hex("00000000000000000000000000000000")?;
+    /// ```
+    pub fn from_entropy_hex(entropy_hex: &str) -> Result<Self> {
+        let entropy = Zeroizing::new(hex::decode(entropy_hex.trim()).map_err(|e| {
+            BipKeychainError::InvalidSeedPhrase(format!("Invalid entropy hex: {}", e))
+        })?);
+
+        let mnemonic = Mnemonic::from_entropy(&entropy)
+            .map_err(|e| BipKeychainError::InvalidSeedPhrase(format!("Invalid entropy: {}", e)))?;
+
+        let seed = Zeroizing::new(mnemonic.to_seed(""));
+        Self::from_seed_bytes(&seed[..])
+    }
+
+    /// Create a keychain from a Base58-encoded extended private key (`xprv...`)
+    ///
+    /// The key is used as the root of the BIP-Keychain path, so users who
+    /// already manage a BIP-32 master key can derive entity keys without
+    /// exposing a mnemonic. Normally this is the master (depth 0) key.
+    ///
+    /// # Example
+    ///
+    /// ```ignore
+    /// let keychain = Keychain::from_xprv("xprv9s21ZrQH143K...")?;
+    /// ```
+    pub fn from_xprv(xprv: &str) -> Result<Self> {
+        let master_key = xprv.trim().parse::<XPrv>().map_err(|e| {
+            BipKeychainError::Bip32Error(format!("Invalid extended private key: {}", e))
+        })?;
+
+        Self::from_master_key(master_key)
+    }
+
+    /// Build a keychain from its master key, deriving the application level
+    /// once so that per-entity derivation is a single BIP-32 step
+    fn from_master_key(master_key: XPrv) -> Result<Self> {
+        let hardened_bip85 = BIP85_APP.wrapping_add(1 << 31);
+        let hardened_bipkeychain = BIPKEYCHAIN_APP.wrapping_add(1 << 31);
+
+        let app_key = master_key
+            .derive_child(hardened_bip85.into())
+            .and_then(|key| key.derive_child(hardened_bipkeychain.into()))
+            .map_err(|e| {
+                BipKeychainError::Bip32Error(format!("Failed to derive BIP-Keychain level: {}", e))
+            })?;
+
+        Ok(Self {
+            master_key,
+            app_key,
+        })
     }
 
     /// Derive a key at the BIP-Keychain path for a given entity index
@@ -60,37 +234,80 @@ impl Keychain {
     /// let seed = derived.to_seed();  // 32 bytes for Ed25519
     /// ```
     pub fn derive_bip_keychain_path(&self, entity_index: u32) -> Result<DerivedKey> {
-        // Build derivation path: m/83696968'/67797668'/{entity_index}'
+        // The application level m/83696968'/67797668' is cached; only the
+        // entity level is derived here.
         // Note: bip32 crate uses hardened indices by adding 2^31
         // We use wrapping_add to prevent overflow when entity_index is large
-        let hardened_bip85 = BIP85_APP.wrapping_add(1 << 31);
-        let hardened_bipkeychain = BIPKEYCHAIN_APP.wrapping_add(1 << 31);
         let hardened_index = entity_index.wrapping_add(1 << 31);
 
-        // Derive step by step
-        // m/83696968'
-        let key_bip85 = self
-            .master_key
-            .derive_child(hardened_bip85.into())
+        // m/83696968'/67797668'/{entity_index}'
+        let derived_key = self
+            .app_key
+            .derive_child(hardened_index.into())
             .map_err(|e| {
-                BipKeychainError::Bip32Error(format!("Failed to derive BIP-85 level: {}", e))
+                BipKeychainError::Bip32Error(format!("Failed to derive entity level: {}", e))
             })?;
 
-        // m/83696968'/67797668'
-        let key_bipkeychain = key_bip85
-            .derive_child(hardened_bipkeychain.into())
-            .map_err(|e| {
-                BipKeychainError::Bip32Error(format!("Failed to derive BIP-Keychain level: {}", e))
-            })?;
+        Ok(DerivedKey::new(derived_key))
+    }
 
-        // m/83696968'/67797668'/{entity_index}'
-        let derived_key = key_bipkeychain
-            .derive_child(hardened_index.into())
-            .map_err(|e| {
+    /// Derive a non-hardened key at m/83696968'/67797668'/{index}
+    ///
+    /// Only the entity level is non-hardened, so anyone holding the
+    /// application-level xpub (see [`Keychain::application_xpub`]) can compute
+    /// the matching public key. The index must be below 2^31.
+    pub fn derive_bip_keychain_path_unhardened(&self, entity_index: u32) -> Result<DerivedKey> {
+        let child = ChildNumber::new(entity_index, false).map_err(|e| {
+            BipKeychainError::Bip32Error(format!(
+                "Non-hardened index {} out of range: {}",
+                entity_index, e
+            ))
+        })?;
+
+        let derived_key = self.app_key.derive_child(child).map_err(|e| {
+            BipKeychainError::Bip32Error(format!("Failed to derive entity level: {}", e))
+        })?;
+
+        Ok(DerivedKey::new(derived_key))
+    }
+
+    /// Derive a key at m/83696968'/67797668' followed by `children`
+    ///
+    /// Used for entity paths with more than one level. With a single child
+    /// equal to `entity_index + 2^31`, this matches
+    /// [`Keychain::derive_bip_keychain_path`].
+    pub fn derive_entity_children(&self, children: &[ChildNumber]) -> Result<DerivedKey> {
+        let mut key = self.app_key.clone();
+        for child in children {
+            key = key.derive_child(*child).map_err(|e| {
                 BipKeychainError::Bip32Error(format!("Failed to derive entity level: {}", e))
             })?;
+        }
+
+        Ok(DerivedKey::new(key))
+    }
 
-        Ok(DerivedKey { key: derived_key })
+    /// Extended public key at the application level m/83696968'/67797668'
+    ///
+    /// Share this with a [`PublicKeychain`] to compute public keys for
+    /// non-hardened entities without any secret material.
+    pub fn application_xpub(&self) -> Result<XPub> {
+        Ok(self.app_key.public_key())
+    }
+
+    /// Build a watch-only keychain for this keychain's application level
+    pub fn public_keychain(&self) -> Result<PublicKeychain> {
+        Ok(PublicKeychain {
+            app_key: self.application_xpub()?,
+        })
+    }
+
+    /// BIP-32 master key fingerprint
+    ///
+    /// The first 4 bytes of HASH160 of the master public key, as used in
+    /// PSBT and UR key origin fields.
+    pub fn fingerprint(&self) -> [u8; 4] {
+        self.master_key.public_key().fingerprint()
     }
 
     /// Get a reference to the master extended key
@@ -99,28 +316,190 @@ impl Keychain {
     }
 }
 
+impl fmt::Debug for Keychain {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Keychain")
+            .field("fingerprint", &hex::encode(self.fingerprint()))
+            .finish_non_exhaustive()
+    }
+}
+
+/// Watch-only keychain built from the application-level xpub
+///
+/// Computes BIP-32 (secp256k1) public keys at m/83696968'/67797668'/{index}
+/// for entities configured with `hardened: false`. Hardened entities and the
+/// Ed25519 keys produced by `output` require the private keychain, because
+/// Ed25519 keys are seeded from private key bytes.
+pub struct PublicKeychain {
+    /// Extended public key at m/83696968'/67797668'
+    app_key: XPub,
+}
+
+impl PublicKeychain {
+    /// Create a watch-only keychain from a Base58 `xpub...` string
+    ///
+    /// The xpub must be the application-level key exported by
+    /// [`Keychain::application_xpub`], not the master xpub.
+    pub fn from_xpub(xpub: &str) -> Result<Self> {
+        let app_key = xpub.trim().parse::<XPub>().map_err(|e| {
+            BipKeychainError::Bip32Error(format!("Invalid extended public key: {}", e))
+        })?;
+
+        Ok(Self { app_key })
+    }
+
+    /// Compressed secp256k1 public key (33 bytes) for a non-hardened index
+    pub fn derive_public_key(&self, entity_index: u32) -> Result<[u8; 33]> {
+        let child = ChildNumber::new(entity_index, false).map_err(|e| {
+            BipKeychainError::Bip32Error(format!(
+                "Non-hardened index {} out of range: {}",
+                entity_index, e
+            ))
+        })?;
+
+        let derived = self.app_key.derive_child(child).map_err(|e| {
+            BipKeychainError::Bip32Error(format!("Failed to derive public key: {}", e))
+        })?;
+
+        Ok(derived.to_bytes())
+    }
+
+    /// Compressed public key at m/83696968'/67797668' followed by `children`
+    ///
+    /// All children must be non-hardened.
+    pub fn derive_entity_public_key(&self, children: &[ChildNumber]) -> Result<[u8; 33]> {
+        let mut key = self.app_key.clone();
+        for child in children {
+            key = key.derive_child(*child).map_err(|e| {
+                BipKeychainError::Bip32Error(format!("Failed to derive public key: {}", e))
+            })?;
+        }
+
+        Ok(key.to_bytes())
+    }
+
+    /// Base58 encoding of the application-level xpub
+    pub fn to_xpub_string(&self) -> String {
+        self.app_key.to_string(Prefix::XPUB)
+    }
+}
+
+/// HMAC key for BIP-85 entropy derivation
+pub const BIP85_ENTROPY_KEY: &[u8] = b"bip-entropy-from-k";
+
+/// BIP-85 entropy for a derived private key `k`
+///
+/// Computes HMAC-SHA512(key="bip-entropy-from-k", msg=k). The returned
+/// buffer is zeroized when dropped.
+pub fn bip85_entropy(private_key: &[u8; 32]) -> Zeroizing<[u8; 64]> {
+    use hmac::{Hmac, Mac};
+    use sha2::Sha512;
+
+    let mut mac =
+        Hmac::<Sha512>::new_from_slice(BIP85_ENTROPY_KEY).expect("HMAC accepts any key length");
+    mac.update(private_key);
+
+    let mut entropy = Zeroizing::new([0u8; 64]);
+    entropy.copy_from_slice(&mac.finalize().into_bytes());
+    entropy
+}
+
 /// A derived key at a specific BIP-Keychain path
 pub struct DerivedKey {
     key: XPrv,
+    /// Produce seeds from BIP-85 entropy instead of raw private key bytes
+    bip85_seed: bool,
+}
+
+impl fmt::Debug for DerivedKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("DerivedKey")
+            .field("fingerprint", &hex::encode(self.fingerprint()))
+            .field("depth", &self.key.attrs().depth)
+            .finish_non_exhaustive()
+    }
 }
 
 impl DerivedKey {
+    fn new(key: XPrv) -> Self {
+        Self {
+            key,
+            bip85_seed: false,
+        }
+    }
+
+    /// Make [`DerivedKey::to_seed`] return BIP-85 entropy
+    ///
+    /// After this, seeds are the first 32 bytes of
+    /// [`DerivedKey::bip85_entropy`], matching other BIP-85 tooling, instead
+    /// of the raw private key bytes.
+    pub fn with_bip85_seed(mut self) -> Self {
+        self.bip85_seed = true;
+        self
+    }
+
     /// Extract 32 bytes from the derived key as a seed for Ed25519
     ///
     /// This follows the BIP-85 pattern: use BIP-32 derivation to generate
     /// entropy, then use that entropy as a seed for other cryptographic operations.
-    pub fn to_seed(&self) -> [u8; 32] {
-        // Extract private key bytes (32 bytes)
-        let private_key_bytes = self.key.private_key().to_bytes();
+    /// By default the seed is the private key itself; see
+    /// [`DerivedKey::with_bip85_seed`].
+    ///
+    /// The returned buffer is zeroized when dropped.
+    pub fn to_seed(&self) -> Zeroizing<[u8; 32]> {
+        if self.bip85_seed {
+            let entropy = self.bip85_entropy();
+            let mut seed = Zeroizing::new([0u8; 32]);
+            seed.copy_from_slice(&entropy[..32]);
+            return seed;
+        }
+
+        // Private key bytes (32 bytes)
+        Zeroizing::new(self.key.to_bytes())
+    }
 
-        let mut seed = [0u8; 32];
-        seed.copy_from_slice(&private_key_bytes);
-        seed
+    /// BIP-85 derived entropy: HMAC-SHA512(key="bip-entropy-from-k", msg=k)
+    ///
+    /// `k` is the derived private key. The returned buffer is zeroized when
+    /// dropped.
+    pub fn bip85_entropy(&self) -> Zeroizing<[u8; 64]> {
+        bip85_entropy(&Zeroizing::new(self.key.to_bytes()))
     }
 
     /// Get the raw bytes of the derived private key
-    pub fn to_bytes(&self) -> Vec<u8> {
-        self.key.to_bytes().to_vec()
+    ///
+    /// The returned buffer is zeroized when dropped.
+    pub fn to_bytes(&self) -> Zeroizing<Vec<u8>> {
+        Zeroizing::new(self.key.to_bytes().to_vec())
+    }
+
+    /// Derive a descendant of this key
+    ///
+    /// Used for chained entity derivation, where a sub-entity's key lives
+    /// below its parent entity's node. The descendant uses raw key bytes as
+    /// its seed until [`DerivedKey::with_bip85_seed`] is applied.
+    pub fn derive_children(&self, children: &[ChildNumber]) -> Result<DerivedKey> {
+        let mut key = self.key.clone();
+        for child in children {
+            key = key.derive_child(*child).map_err(|e| {
+                BipKeychainError::Bip32Error(format!("Failed to derive child entity: {}", e))
+            })?;
+        }
+
+        Ok(DerivedKey::new(key))
+    }
+
+    /// BIP-32 key identifier fingerprint of the derived node
+    ///
+    /// The first 4 bytes of HASH160 of the node's public key. This identifies
+    /// the derived key without revealing it.
+    pub fn fingerprint(&self) -> [u8; 4] {
+        self.key.public_key().fingerprint()
+    }
+
+    /// Compressed secp256k1 public key (33 bytes) of the derived BIP-32 node
+    pub fn secp256k1_public_key(&self) -> [u8; 33] {
+        self.key.public_key().to_bytes()
     }
 
     /// Get the extended private key
@@ -133,6 +512,89 @@ impl DerivedKey {
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_bip85_entropy_vectors() {
+        // BIP-85 test cases 1 and 2: DERIVED KEY -> DERIVED ENTROPY
+        let vectors = [
+            (
+                "cca20ccb0e9a90feb0912870c3323b24874b0ca3d8018c4b96d0b97c0e82ded0",
+                "efecfbccffea313214232d29e71563d941229afb4338c21f9517c41aaa0d16f0\
+                 0b83d2a09ef747e7a64e8e2bd5a14869e693da66ce94ac2da570ab7ee48618f7",
+            ),
+            (
+                "503776919131758bb7de7beb6c0ae24894f4ec042c26032890c29359216e21ba",
+                "70c6e3e8ebee8dc4c0dbba66076819bb8c09672527c4277ca8729532ad711872\
+                 218f826919f6b67218adde99018a6df9095ab2b58d803b5b93ec9802085a690e",
+            ),
+        ];
+
+        for (derived_key, entropy) in vectors {
+            let key: [u8; 32] = hex::decode(derived_key).unwrap().try_into().unwrap();
+            assert_eq!(hex::encode(*bip85_entropy(&key)), entropy);
+        }
+    }
+
+    #[test]
+    fn test_bip85_seed_mode() {
+        let mnemonic = "abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon about";
+        let keychain = Keychain::from_mnemonic(mnemonic).unwrap();
+
+        let raw = keychain.derive_bip_keychain_path(42).unwrap();
+        let private_key = raw.to_seed();
+        assert_eq!(raw.bip85_entropy(), bip85_entropy(&private_key));
+
+        let bip85 = raw.with_bip85_seed();
+        assert_eq!(bip85.to_seed()[..], bip85.bip85_entropy()[..32]);
+        assert_ne!(*bip85.to_seed(), *private_key);
+        // The BIP-32 key itself is unchanged
+        assert_eq!(*bip85.to_bytes(), private_key.to_vec());
+    }
+
+    #[test]
+    fn test_generate_mnemonic_word_counts() {
+        for words in [12, 15, 18, 21, 24] {
+            let mnemonic = generate_mnemonic(words).unwrap();
+            assert_eq!(mnemonic.word_count(), words);
+            assert!(Keychain::from_mnemonic(&mnemonic.to_string()).is_ok());
+        }
+        assert!(generate_mnemonic(13).is_err());
+    }
+
+    #[test]
+    fn test_non_english_mnemonics() {
+        // Japanese phrase for all-zero entropy, separated by ideographic
+        // spaces; the seed is PBKDF2 over the NFKD form of the phrase
+        let japanese = ["あいこくしん"; 11].join("\u{3000}") + "\u{3000}あおぞら";
+        let expected_seed = hex::decode(
+            "646f1a38134c556e948e6daef213609a62915ef568edb07ffa6046c87638b4b1\
+             40fef2e0c6d7233af640c4a63de6d1a293288058c8ac1d113255d0504e63f301",
+        )
+        .unwrap();
+        assert_eq!(
+            Keychain::from_mnemonic(&japanese).unwrap().fingerprint(),
+            Keychain::from_seed_bytes(&expected_seed)
+                .unwrap()
+                .fingerprint()
+        );
+        assert_eq!(
+            parse_mnemonic(&japanese, None).unwrap().language(),
+            Language::Japanese
+        );
+
+        // Spanish words typed with precomposed accents (NFC)
+        let spanish = ["\u{e1}baco"; 11].join(" ") + " abierto";
+        let keychain = Keychain::from_mnemonic_in(&spanish, Language::Spanish).unwrap();
+        assert_eq!(
+            keychain.fingerprint(),
+            Keychain::from_mnemonic(&spanish).unwrap().fingerprint()
+        );
+        assert!(Keychain::from_mnemonic_in(&spanish, Language::French).is_err());
+
+        let generated = generate_mnemonic_in(Language::French, 12).unwrap();
+        assert_eq!(generated.language(), Language::French);
+        assert!(Keychain::from_mnemonic(&generated.to_string()).is_ok());
+    }
+
     #[test]
     fn test_constants() {
         assert_eq!(BIP85_APP, 83_696_968);
@@ -149,6 +611,14 @@ mod tests {
         assert!(keychain.master_key().private_key().to_bytes().len() == 32);
     }
 
+    #[test]
+    fn test_from_seed_bytes_length() {
+        assert!(Keychain::from_seed_bytes(&[0u8; 15]).is_err());
+        assert!(Keychain::from_seed_bytes(&[0u8; 16]).is_ok());
+        assert!(Keychain::from_seed_bytes(&[0u8; 64]).is_ok());
+        assert!(Keychain::from_seed_bytes(&[0u8; 65]).is_err());
+    }
+
     #[test]
     fn test_deterministic() {
         let mnemonic = "abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon about";
@@ -173,6 +643,54 @@ mod tests {
         assert_ne!(derived_0.to_bytes(), derived_1.to_bytes());
     }
 
+    #[test]
+    fn test_fingerprints() {
+        let mnemonic = "abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon about";
+        let keychain = Keychain::from_mnemonic(mnemonic).unwrap();
+
+        // Well-known master fingerprint of the BIP-39 test mnemonic
+        assert_eq!(hex::encode(keychain.fingerprint()), "73c5da0a");
+
+        let derived_0 = keychain.derive_bip_keychain_path(0).unwrap();
+        let derived_1 = keychain.derive_bip_keychain_path(1).unwrap();
+        assert_ne!(derived_0.fingerprint(), derived_1.fingerprint());
+    }
+
+    #[test]
+    fn test_debug_redacts_secrets() {
+        let mnemonic = "abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon about";
+        let keychain = Keychain::from_mnemonic(mnemonic).unwrap();
+        let derived = keychain.derive_bip_keychain_path(0).unwrap();
+
+        let keychain_debug = format!("{:?}", keychain);
+        assert_eq!(
+            keychain_debug,
+            r#"Keychain { fingerprint: "73c5da0a", .. }"#
+        );
+
+        let derived_debug = format!("{:?}", derived);
+        assert!(!derived_debug.contains(&hex::encode(derived.to_seed())));
+        assert!(derived_debug.contains(&hex::encode(derived.fingerprint())));
+    }
+
+    #[test]
+    fn test_public_keychain_matches_private() {
+        let mnemonic = "abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon about";
+        let keychain = Keychain::from_mnemonic(mnemonic).unwrap();
+
+        let xpub = keychain.public_keychain().unwrap().to_xpub_string();
+        let watch_only = PublicKeychain::from_xpub(&xpub).unwrap();
+
+        let derived = keychain.derive_bip_keychain_path_unhardened(42).unwrap();
+        assert_eq!(
+            watch_only.derive_public_key(42).unwrap(),
+            derived.secp256k1_public_key()
+        );
+
+        // Hardened range is not reachable from an xpub
+        assert!(watch_only.derive_public_key(1 << 31).is_err());
+    }
+
     #[test]
     fn test_seed_extraction() {
         let mnemonic = "abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon about";
@@ -0,0 +1,192 @@
+//! BIP-85 deterministic entropy for other wallets
+//!
+//! Derives the standard BIP-85 applications from the keychain's master key,
+//! so child seeds and keys made by other BIP-85 tools (Coldcard, SeedSigner,
+//! `bipentropy`) can be recreated from the same seed:
+//!
+//! | Application | Path | Output |
+//! |---|---|---|
+//! | BIP39 | m/83696968'/39'/{language}'/{words}'/{index}' | child mnemonic |
+//! | HD-Seed WIF | m/83696968'/2'/{index}' | mainnet WIF private key |
+//! | HEX | m/83696968'/128169'/{num_bytes}'/{index}' | 16 to 64 bytes of hex |
+//!
+//! Entity keys live under their own application number (67797668') and are
+//! unaffected.
+
+use crate::bip32_wrapper::{bip85_entropy, Keychain, Language, BIP85_APP};
+use crate::error::{BipKeychainError, Result};
+use bip32::ChildNumber;
+use bip39::Mnemonic;
+use zeroize::Zeroizing;
+
+/// BIP39 application number
+pub const BIP39_APP: u32 = 39;
+
+/// HD-Seed WIF application number
+pub const WIF_APP: u32 = 2;
+
+/// HEX application number
+pub const HEX_APP: u32 = 128169;
+
+/// Version byte of a mainnet WIF private key
+const WIF_MAINNET: u8 = 0x80;
+
+fn bip85_error(message: String) -> BipKeychainError {
+    BipKeychainError::OutputError(message)
+}
+
+/// BIP-85 code of a BIP-39 wordlist
+pub fn language_code(language: Language) -> u32 {
+    match language {
+        Language::English => 0,
+        Language::Japanese => 1,
+        Language::Korean => 2,
+        Language::Spanish => 3,
+        Language::SimplifiedChinese => 4,
+        Language::TraditionalChinese => 5,
+        Language::French => 6,
+        Language::Italian => 7,
+        Language::Czech => 8,
+        Language::Portuguese => 9,
+    }
+}
+
+/// 64 bytes of entropy for the hardened path m/83696968'/`path`
+///
+/// Every level of `path` is hardened and must be below 2^31.
+pub fn derive_entropy(keychain: &Keychain, path: &[u32]) -> Result<Zeroizing<[u8; 64]>> {
+    let mut key = keychain.master_key().clone();
+    for &index in std::iter::once(&BIP85_APP).chain(path) {
+        let child = ChildNumber::new(index, true).map_err(|_| {
+            BipKeychainError::Bip32Error(format!(
+                "BIP-85 path indices must be below 2^31, got {}",
+                index
+            ))
+        })?;
+        key = key.derive_child(child).map_err(|e| {
+            BipKeychainError::Bip32Error(format!("Failed to derive BIP-85 path: {}", e))
+        })?;
+    }
+    Ok(bip85_entropy(&Zeroizing::new(key.to_bytes())))
+}
+
+/// Child BIP-39 mnemonic of `word_count` words (12, 15, 18, 21, or 24)
+pub fn mnemonic(
+    keychain: &Keychain,
+    language: Language,
+    word_count: usize,
+    index: u32,
+) -> Result<Mnemonic> {
+    if !matches!(word_count, 12 | 15 | 18 | 21 | 24) {
+        return Err(bip85_error(format!(
+            "BIP-85 mnemonics have 12, 15, 18, 21, or 24 words, got {}",
+            word_count
+        )));
+    }
+    let entropy = derive_entropy(
+        keychain,
+        &[BIP39_APP, language_code(language), word_count as u32, index],
+    )?;
+    Mnemonic::from_entropy_in(language, &entropy[..word_count * 4 / 3])
+        .map_err(|e| bip85_error(format!("Failed to encode BIP-85 mnemonic: {}", e)))
+}
+
+/// Compressed mainnet WIF private key for a Bitcoin Core HD seed
+pub fn wif(keychain: &Keychain, index: u32) -> Result<Zeroizing<String>> {
+    let entropy = derive_entropy(keychain, &[WIF_APP, index])?;
+
+    let mut payload = Zeroizing::new(Vec::with_capacity(34));
+    payload.push(WIF_MAINNET);
+    payload.extend_from_slice(&entropy[..32]);
+    payload.push(0x01);
+    Ok(Zeroizing::new(
+        bs58::encode(&payload[..]).with_check().into_string(),
+    ))
+}
+
+/// `num_bytes` (16 to 64) bytes of entropy as lowercase hex
+pub fn hex(keychain: &Keychain, num_bytes: usize, index: u32) -> Result<Zeroizing<String>> {
+    if !(16..=64).contains(&num_bytes) {
+        return Err(bip85_error(format!(
+            "BIP-85 HEX entropy is 16 to 64 bytes, got {}",
+            num_bytes
+        )));
+    }
+    let entropy = derive_entropy(keychain, &[HEX_APP, num_bytes as u32, index])?;
+    Ok(Zeroizing::new(hex::encode(&entropy[..num_bytes])))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Master key of the BIP-85 test vectors
+    const MASTER_XPRV: &str = "xprv9s21ZrQH143K2LBWUUQRFXhucrQqBpKdRRxNVq2zBqsx8HVqFk2uYo8kmbaLLHRdqtQpUm98uKfu3vca1LqdGhUtyoFnCNkfmXRyPXLjbKb";
+
+    fn keychain() -> Keychain {
+        Keychain::from_xprv(MASTER_XPRV).unwrap()
+    }
+
+    #[test]
+    fn test_entropy_vectors() {
+        let keychain = keychain();
+        assert_eq!(
+            hex::encode(*derive_entropy(&keychain, &[0, 0]).unwrap()),
+            "efecfbccffea313214232d29e71563d941229afb4338c21f9517c41aaa0d16f0\
+             0b83d2a09ef747e7a64e8e2bd5a14869e693da66ce94ac2da570ab7ee48618f7"
+        );
+        assert_eq!(
+            hex::encode(*derive_entropy(&keychain, &[0, 1]).unwrap()),
+            "70c6e3e8ebee8dc4c0dbba66076819bb8c09672527c4277ca8729532ad711872\
+             218f826919f6b67218adde99018a6df9095ab2b58d803b5b93ec9802085a690e"
+        );
+        assert!(derive_entropy(&keychain, &[1 << 31]).is_err());
+    }
+
+    #[test]
+    fn test_bip39_vectors() {
+        let keychain = keychain();
+        let cases = [
+            (
+                12,
+                "girl mad pet galaxy egg matter matrix prison refuse sense ordinary nose",
+            ),
+            (
+                18,
+                "near account window bike charge season chef number sketch tomorrow excuse \
+                 sniff circle vital hockey outdoor supply token",
+            ),
+            (
+                24,
+                "puppy ocean match cereal symbol another shed magic wrap hammer bulb intact \
+                 gadget divorce twin tonight reason outdoor destroy simple truth cigar social \
+                 volcano",
+            ),
+        ];
+        for (words, expected) in cases {
+            let child = mnemonic(&keychain, Language::English, words, 0).unwrap();
+            assert_eq!(child.to_string(), expected);
+        }
+        assert!(mnemonic(&keychain, Language::English, 13, 0).is_err());
+    }
+
+    #[test]
+    fn test_wif_vector() {
+        assert_eq!(
+            *wif(&keychain(), 0).unwrap(),
+            "Kzyv4uF39d4Jrw2W7UryTHwZr1zQVNk4dAFyqE6BuMrMh1Za7uhp"
+        );
+    }
+
+    #[test]
+    fn test_hex_vector() {
+        let keychain = keychain();
+        assert_eq!(
+            *hex(&keychain, 64, 0).unwrap(),
+            "492db4698cf3b73a5a24998aa3e9d7fa96275d85724a91e71aa2d645442f8785\
+             55d078fd1f1f67e368976f04137b1f7a0d19232136ca50c44614af72b5582a5c"
+        );
+        assert!(hex(&keychain, 15, 0).is_err());
+        assert!(hex(&keychain, 65, 0).is_err());
+    }
+}
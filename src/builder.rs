@@ -0,0 +1,523 @@
+//! Fluent builders for entities and key derivation specifications
+//!
+//! Lets Rust callers construct entities without assembling JSON by hand:
+//!
+//! ```
+//! use bip_keychain::{HashFunctionConfig, KeyDerivationBuilder};
+//!
+//! let key_derivation = KeyDerivationBuilder::new()
+//!     .schema_org()
+//!     .type_("SoftwareSourceCode")
+//!     .name("BIP-Keychain Core")
+//!     .property("codeRepository", "https://github.com/DAOgora-xyz/bip-keychain-core")
+//!     .hash_function(HashFunctionConfig::Blake2b)
+//!     .purpose("Git commit signing key")
+//!     .build()?;
+//! # Ok::<(), bip_keychain::BipKeychainError>(())
+//! ```
+//!
+//! Built entities are validated exactly like parsed ones, and hash the
+//! same as the equivalent entity file.
+
+use crate::{
+    dnssec::DnssecConfig,
+    entity::{
+        CanonicalizationConfig, DerivationConfig, DomainTagConfig, Entity, EntityParent,
+        HashFunctionConfig, IndexExpansionConfig, KeyDerivation, KeyEntropyConfig, KeyTypeConfig,
+        MAX_INDEX_LEVELS,
+    },
+    error::{BipKeychainError, Result},
+    frost::FrostConfig,
+    output::OutputFormat,
+    password::PasswordConfig,
+    tlsa::TlsaConfig,
+    totp::TotpConfig,
+};
+use serde_json::{Map, Value};
+
+/// Builder for an [`Entity`]
+#[derive(Debug, Clone)]
+pub struct EntityBuilder {
+    schema_type: String,
+    properties: Map<String, Value>,
+}
+
+impl EntityBuilder {
+    /// Start an entity of any schema type
+    pub fn new(schema_type: impl Into<String>) -> Self {
+        Self {
+            schema_type: schema_type.into(),
+            properties: Map::new(),
+        }
+    }
+
+    /// Start a schema.org entity with `@context: https://schema.org`
+    pub fn schema_org() -> Self {
+        Self::new("schema_org").property("@context", "https://schema.org")
+    }
+
+    /// Start a DID entity
+    pub fn did() -> Self {
+        Self::new("did")
+    }
+
+    /// Start a Gordian Envelope entity
+    pub fn gordian_envelope() -> Self {
+        Self::new("gordian_envelope")
+    }
+
+    /// Schema type this builder produces
+    pub fn schema_type(&self) -> &str {
+        &self.schema_type
+    }
+
+    /// Set a property, replacing any previous value
+    pub fn property(mut self, key: impl Into<String>, value: impl Into<Value>) -> Self {
+        self.properties.insert(key.into(), value.into());
+        self
+    }
+
+    /// Set the schema.org `@type`
+    pub fn type_(self, type_: impl Into<String>) -> Self {
+        self.property("@type", type_.into())
+    }
+
+    /// Set `name`
+    pub fn name(self, name: impl Into<String>) -> Self {
+        self.property("name", name.into())
+    }
+
+    /// Set the DID string (`did`)
+    pub fn did_string(self, did: impl Into<String>) -> Self {
+        self.property("did", did.into())
+    }
+
+    /// Set the DID `method`
+    pub fn method(self, method: impl Into<String>) -> Self {
+        self.property("method", method.into())
+    }
+
+    /// Set the DID method-specific `identifier`
+    pub fn identifier(self, identifier: impl Into<String>) -> Self {
+        self.property("identifier", identifier.into())
+    }
+
+    /// Set the UR-encoded `envelope`
+    pub fn envelope(self, envelope: impl Into<String>) -> Self {
+        self.property("envelope", envelope.into())
+    }
+
+    /// Build and validate the entity against its schema type
+    pub fn build(self) -> Result<Entity> {
+        Entity::from_value(&self.schema_type, Value::Object(self.properties))
+    }
+}
+
+/// Builder for a [`KeyDerivation`]
+///
+/// Defaults to `hmac_sha512` with hardened derivation and an empty
+/// `custom` entity. Entity methods apply to the entity being built.
+#[derive(Debug, Clone)]
+pub struct KeyDerivationBuilder {
+    entity: EntityBuilder,
+    derivation_config: DerivationConfig,
+    purpose: Option<String>,
+    metadata: Option<Value>,
+    parent: Option<EntityParent>,
+    password: Option<PasswordConfig>,
+    totp: Option<TotpConfig>,
+    dnssec: Option<DnssecConfig>,
+    tlsa: Option<TlsaConfig>,
+    frost: Option<FrostConfig>,
+    output_format: Option<OutputFormat>,
+}
+
+impl Default for KeyDerivationBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl KeyDerivationBuilder {
+    /// Start a key derivation with default configuration
+    pub fn new() -> Self {
+        Self {
+            entity: EntityBuilder::new("custom"),
+            derivation_config: DerivationConfig {
+                hash_function: HashFunctionConfig::HmacSha512,
+                hardened: true,
+                canonicalization: CanonicalizationConfig::default(),
+                hash_exclude: Vec::new(),
+                hash_include: Vec::new(),
+                salt: None,
+                domain_tag: DomainTagConfig::default(),
+                index_levels: 1,
+                application: None,
+                tenant: None,
+                key_index: None,
+                key_entropy: KeyEntropyConfig::default(),
+                key_type: KeyTypeConfig::default(),
+                index_expansion: None,
+                lowercase_did_method: false,
+            },
+            purpose: None,
+            metadata: None,
+            parent: None,
+            password: None,
+            totp: None,
+            dnssec: None,
+            tlsa: None,
+            frost: None,
+            output_format: None,
+        }
+    }
+
+    /// Replace the entity with `entity`
+    pub fn entity(mut self, entity: EntityBuilder) -> Self {
+        self.entity = entity;
+        self
+    }
+
+    /// Start a schema.org entity (see [`EntityBuilder::schema_org`])
+    pub fn schema_org(self) -> Self {
+        self.entity(EntityBuilder::schema_org())
+    }
+
+    /// Start a DID entity
+    pub fn did(self) -> Self {
+        self.entity(EntityBuilder::did())
+    }
+
+    /// Start a Gordian Envelope entity
+    pub fn gordian_envelope(self) -> Self {
+        self.entity(EntityBuilder::gordian_envelope())
+    }
+
+    /// Start an entity of any other schema type
+    pub fn custom(self, schema_type: impl Into<String>) -> Self {
+        self.entity(EntityBuilder::new(schema_type))
+    }
+
+    /// Set an entity property
+    pub fn property(mut self, key: impl Into<String>, value: impl Into<Value>) -> Self {
+        self.entity = self.entity.property(key, value);
+        self
+    }
+
+    /// Set the entity's schema.org `@type`
+    pub fn type_(self, type_: impl Into<String>) -> Self {
+        self.property("@type", type_.into())
+    }
+
+    /// Set the entity's `name`
+    pub fn name(self, name: impl Into<String>) -> Self {
+        self.property("name", name.into())
+    }
+
+    /// Replace the whole derivation configuration
+    pub fn derivation_config(mut self, config: DerivationConfig) -> Self {
+        self.derivation_config = config;
+        self
+    }
+
+    /// Hash function for entity→index conversion
+    pub fn hash_function(mut self, hash_function: HashFunctionConfig) -> Self {
+        self.derivation_config.hash_function = hash_function;
+        self
+    }
+
+    /// Whether to use hardened derivation
+    pub fn hardened(mut self, hardened: bool) -> Self {
+        self.derivation_config.hardened = hardened;
+        self
+    }
+
+    /// Encoding of the entity before hashing
+    pub fn canonicalization(mut self, canonicalization: CanonicalizationConfig) -> Self {
+        self.derivation_config.canonicalization = canonicalization;
+        self
+    }
+
+    /// Leave an entity field out of the hash (a JSON pointer, e.g. `/comment`)
+    pub fn hash_exclude(mut self, pointer: impl Into<String>) -> Self {
+        self.derivation_config.hash_exclude.push(pointer.into());
+        self
+    }
+
+    /// Hash only the named entity fields (a JSON pointer, e.g. `/name`)
+    pub fn hash_include(mut self, pointer: impl Into<String>) -> Self {
+        self.derivation_config.hash_include.push(pointer.into());
+        self
+    }
+
+    /// Salt mixed into the hash input (non-empty, checked by `build`)
+    pub fn salt(mut self, salt: impl Into<String>) -> Self {
+        self.derivation_config.salt = Some(salt.into());
+        self
+    }
+
+    /// Domain tag prefixed to the hash input (`DomainTagConfig::None` for
+    /// the untagged hashing of version 1 entity files)
+    pub fn domain_tag(mut self, domain_tag: DomainTagConfig) -> Self {
+        self.derivation_config.domain_tag = domain_tag;
+        self
+    }
+
+    /// Number of entity-level path components (1-3, checked by `build`)
+    pub fn index_levels(mut self, levels: u8) -> Self {
+        self.derivation_config.index_levels = levels;
+        self
+    }
+
+    /// Sub-application level below m/83696968'/67797668' (below 2^31, checked by `build`)
+    pub fn application(mut self, application: u32) -> Self {
+        self.derivation_config.application = Some(application);
+        self
+    }
+
+    /// Tenant subtree the key is derived in (non-empty, checked by `build`)
+    pub fn tenant(mut self, tenant: impl Into<String>) -> Self {
+        self.derivation_config.tenant = Some(tenant.into());
+        self
+    }
+
+    /// Key number appended after the entity indices (below 2^31, checked by `build`)
+    pub fn key_index(mut self, key_index: u32) -> Self {
+        self.derivation_config.key_index = Some(key_index);
+        self
+    }
+
+    /// Where the derived seed comes from
+    pub fn key_entropy(mut self, key_entropy: KeyEntropyConfig) -> Self {
+        self.derivation_config.key_entropy = key_entropy;
+        self
+    }
+
+    /// Kind of key the entity produces
+    pub fn key_type(mut self, key_type: KeyTypeConfig) -> Self {
+        self.derivation_config.key_type = key_type;
+        self
+    }
+
+    /// Lowercase a DID entity's method before validation and hashing
+    pub fn lowercase_did_method(mut self, lowercase: bool) -> Self {
+        self.derivation_config.lowercase_did_method = lowercase;
+        self
+    }
+
+    /// Expansion of the hash before the index is extracted
+    pub fn index_expansion(mut self, expansion: IndexExpansionConfig) -> Self {
+        self.derivation_config.index_expansion = Some(expansion);
+        self
+    }
+
+    /// Human-readable purpose
+    pub fn purpose(mut self, purpose: impl Into<String>) -> Self {
+        self.purpose = Some(purpose.into());
+        self
+    }
+
+    /// Additional metadata
+    pub fn metadata(mut self, metadata: Value) -> Self {
+        self.metadata = Some(metadata);
+        self
+    }
+
+    /// Parent entity; the key is derived below the parent's key
+    pub fn parent(mut self, parent: EntityParent) -> Self {
+        self.parent = Some(parent);
+        self
+    }
+
+    /// Settings for the `password` output format
+    pub fn password(mut self, password: PasswordConfig) -> Self {
+        self.password = Some(password);
+        self
+    }
+
+    /// Settings for the `otpauth` output format and `totp` command
+    pub fn totp(mut self, totp: TotpConfig) -> Self {
+        self.totp = Some(totp);
+        self
+    }
+
+    /// Zone and role for the DNSSEC output formats
+    pub fn dnssec(mut self, dnssec: DnssecConfig) -> Self {
+        self.dnssec = Some(dnssec);
+        self
+    }
+
+    /// Service and record fields for the `tlsa` output format
+    pub fn tlsa(mut self, tlsa: TlsaConfig) -> Self {
+        self.tlsa = Some(tlsa);
+        self
+    }
+
+    /// Threshold and group size for the `frost` command
+    pub fn frost(mut self, frost: FrostConfig) -> Self {
+        self.frost = Some(frost);
+        self
+    }
+
+    /// Preferred output format when none is requested
+    pub fn output_format(mut self, format: OutputFormat) -> Self {
+        self.output_format = Some(format);
+        self
+    }
+
+    /// Build and validate the key derivation
+    pub fn build(self) -> Result<KeyDerivation> {
+        let levels = self.derivation_config.index_levels;
+        if !(1..=MAX_INDEX_LEVELS).contains(&levels) {
+            return Err(BipKeychainError::InvalidEntity(serde::de::Error::custom(
+                format!(
+                    "index_levels must be between 1 and {}, got {}",
+                    MAX_INDEX_LEVELS, levels
+                ),
+            )));
+        }
+        let child_indices = [
+            ("application", self.derivation_config.application),
+            ("key_index", self.derivation_config.key_index),
+        ];
+        for (field, index) in child_indices {
+            if let Some(index) = index.filter(|index| *index >= 1 << 31) {
+                return Err(BipKeychainError::InvalidEntity(serde::de::Error::custom(
+                    format!("{} must be below 2^31, got {}", field, index),
+                )));
+            }
+        }
+        let strings = [
+            ("tenant", &self.derivation_config.tenant),
+            ("salt", &self.derivation_config.salt),
+        ];
+        for (field, value) in strings {
+            if value.as_deref() == Some("") {
+                return Err(BipKeychainError::InvalidEntity(serde::de::Error::custom(
+                    format!("{} must not be empty", field),
+                )));
+            }
+        }
+
+        let entity = Entity::from_value_with_config(
+            &self.entity.schema_type,
+            Value::Object(self.entity.properties),
+            &self.derivation_config,
+        )?;
+        let mut key_derivation =
+            KeyDerivation::new(self.entity.schema_type, entity, self.derivation_config);
+        key_derivation.purpose = self.purpose;
+        key_derivation.metadata = self.metadata;
+        key_derivation.parent = self.parent;
+        key_derivation.password = self.password;
+        key_derivation.totp = self.totp;
+        key_derivation.dnssec = self.dnssec;
+        key_derivation.tlsa = self.tlsa;
+        key_derivation.frost = self.frost;
+        key_derivation.output_format = self.output_format;
+        Ok(key_derivation)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_builder_matches_parsed_entity() {
+        let built = KeyDerivationBuilder::new()
+            .schema_org()
+            .type_("SoftwareSourceCode")
+            .name("BIP-Keychain Core")
+            .property(
+                "codeRepository",
+                "https://github.com/DAOgora-xyz/bip-keychain-core",
+            )
+            .purpose("Git commit signing key")
+            .build()
+            .unwrap();
+
+        let parsed = KeyDerivation::from_json(
+            r#"{
+                "version": 2,
+                "schema_type": "schema_org",
+                "entity": {
+                    "@context": "https://schema.org",
+                    "@type": "SoftwareSourceCode",
+                    "codeRepository": "https://github.com/DAOgora-xyz/bip-keychain-core",
+                    "name": "BIP-Keychain Core"
+                },
+                "derivation_config": {"hash_function": "hmac_sha512", "hardened": true},
+                "purpose": "Git commit signing key"
+            }"#,
+        )
+        .unwrap();
+
+        assert_eq!(built.version, parsed.version);
+        assert_eq!(built.entity, parsed.entity);
+        assert_eq!(built.derivation_config, parsed.derivation_config);
+        assert_eq!(
+            built.canonical_entity().unwrap(),
+            parsed.canonical_entity().unwrap()
+        );
+        assert_eq!(built.purpose, parsed.purpose);
+    }
+
+    #[test]
+    fn test_builder_validates_entity() {
+        // schema.org requires @type
+        assert!(KeyDerivationBuilder::new()
+            .schema_org()
+            .name("x")
+            .build()
+            .is_err());
+
+        let did = EntityBuilder::did()
+            .did_string("did:github:alice")
+            .method("github")
+            .identifier("alice")
+            .build()
+            .unwrap();
+        assert!(matches!(did, Entity::Did(ref d) if d.method == "github"));
+    }
+
+    #[test]
+    fn test_builder_validates_index_levels() {
+        let builder = KeyDerivationBuilder::new()
+            .custom("dns")
+            .property("fqdn", "a.example.com");
+        assert!(builder.clone().index_levels(3).build().is_ok());
+        assert!(builder.index_levels(4).build().is_err());
+    }
+
+    #[test]
+    fn test_builder_validates_application() {
+        let builder = KeyDerivationBuilder::new()
+            .custom("dns")
+            .property("fqdn", "a.example.com");
+        let built = builder.clone().application(1).build().unwrap();
+        assert_eq!(built.derivation_config.application, Some(1));
+        assert!(builder.application(1 << 31).build().is_err());
+    }
+
+    #[test]
+    fn test_builder_validates_tenant() {
+        let builder = KeyDerivationBuilder::new()
+            .custom("dns")
+            .property("fqdn", "a.example.com");
+        let built = builder.clone().tenant("acme").build().unwrap();
+        assert_eq!(built.derivation_config.tenant.as_deref(), Some("acme"));
+        assert!(builder.clone().tenant("").build().is_err());
+        assert!(builder.salt("").build().is_err());
+    }
+
+    #[test]
+    fn test_builder_validates_key_index() {
+        let builder = KeyDerivationBuilder::new()
+            .custom("dns")
+            .property("fqdn", "a.example.com");
+        let built = builder.clone().key_index(2).build().unwrap();
+        assert_eq!(built.derivation_config.key_index, Some(2));
+        assert!(builder.key_index(1 << 31).build().is_err());
+    }
+}
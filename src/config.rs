@@ -0,0 +1,275 @@
+//! CLI configuration file
+//!
+//! Defaults for `bip-keychain` options, read from
+//! `$XDG_CONFIG_HOME/bip-keychain/config.toml` (or
+//! `~/.config/bip-keychain/config.toml`). Keys are the long option names:
+//!
+//! ```toml
+//! seed-source = "os-keychain"
+//! keychain-account = "work"
+//! format = ["ssh", "public-key"]
+//! parent-entropy = "require"   # "default", "require", or hex
+//! output-dir = "~/.ssh/bip-keychain"
+//! revocations = "~/.config/bip-keychain/revocations.json"
+//! audit-log = "~/.local/state/bip-keychain/audit.jsonl"
+//! index-registry = "~/.local/state/bip-keychain/index-registry.json"
+//! ```
+//!
+//! Options given on the command line override the file. Relative paths
+//! are resolved against the config file's directory, and a leading `~/`
+//! against `$HOME`.
+
+use crate::{
+    error::{BipKeychainError, Result},
+    output::OutputFormat,
+};
+use serde::Deserialize;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Where the seed phrase is read from
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum SeedSourceConfig {
+    /// `BIP_KEYCHAIN_SEED` environment variable
+    Env,
+    /// OS credential store
+    OsKeychain,
+}
+
+/// Mnemonic format of the seed phrase
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum SeedFormatConfig {
+    /// BIP-39 mnemonic
+    Bip39,
+    /// Electrum 2.0+ seed
+    Electrum,
+    /// LND aezeed
+    Aezeed,
+    /// Monero 25-word seed
+    Monero,
+}
+
+/// What to use when no `--parent-entropy` is given
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(try_from = "String")]
+pub enum ParentEntropyPolicy {
+    /// Use [`DEFAULT_PARENT_ENTROPY`](crate::DEFAULT_PARENT_ENTROPY)
+    Default,
+    /// Refuse to derive without an explicit `--parent-entropy`
+    Require,
+    /// Use these bytes (written as hex)
+    Fixed(Vec<u8>),
+}
+
+impl TryFrom<String> for ParentEntropyPolicy {
+    type Error = String;
+
+    fn try_from(value: String) -> std::result::Result<Self, String> {
+        match value.as_str() {
+            "default" => Ok(Self::Default),
+            "require" => Ok(Self::Require),
+            hex_str => hex::decode(hex_str).map(Self::Fixed).map_err(|_| {
+                format!(
+                    "parent-entropy must be \"default\", \"require\", or hex, got '{}'",
+                    value
+                )
+            }),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(untagged)]
+enum Formats {
+    One(OutputFormat),
+    Many(Vec<OutputFormat>),
+}
+
+/// Parsed `config.toml`
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "kebab-case", deny_unknown_fields)]
+pub struct Config {
+    /// Default `--seed-source`
+    #[serde(default)]
+    pub seed_source: Option<SeedSourceConfig>,
+
+    /// Default `--keychain-account`
+    #[serde(default)]
+    pub keychain_account: Option<String>,
+
+    /// Default `--seed-file`
+    #[serde(default)]
+    pub seed_file: Option<PathBuf>,
+
+    /// Default `--seed-format`
+    #[serde(default)]
+    pub seed_format: Option<SeedFormatConfig>,
+
+    /// Default `--monero-wordlist`
+    #[serde(default)]
+    pub monero_wordlist: Option<PathBuf>,
+
+    /// Default `derive --format` (one name or a list)
+    #[serde(default)]
+    format: Option<Formats>,
+
+    /// Parent entropy when `--parent-entropy` is not given
+    #[serde(default)]
+    pub parent_entropy: Option<ParentEntropyPolicy>,
+
+    /// Directory relative `derive --output` paths are written to
+    #[serde(default)]
+    pub output_dir: Option<PathBuf>,
+
+    /// Default `--revocations`
+    #[serde(default)]
+    pub revocations: Option<PathBuf>,
+
+    /// Default `--audit-log`
+    #[serde(default)]
+    pub audit_log: Option<PathBuf>,
+
+    /// Default `--index-registry`
+    #[serde(default)]
+    pub index_registry: Option<PathBuf>,
+
+    /// Default `--transparency-log` (a Rekor URL)
+    #[serde(default)]
+    pub transparency_log: Option<String>,
+}
+
+fn config_error(message: String) -> BipKeychainError {
+    BipKeychainError::ConfigError(message)
+}
+
+impl Config {
+    /// Parse a config file's contents; paths are kept as written
+    pub fn from_toml(toml: &str) -> Result<Self> {
+        toml::from_str(toml).map_err(|e| config_error(e.to_string()))
+    }
+
+    /// Read a config file and resolve its paths
+    pub fn load(path: &Path) -> Result<Self> {
+        let contents = fs::read_to_string(path)
+            .map_err(|e| config_error(format!("Failed to read {}: {}", path.display(), e)))?;
+        let mut config: Self = toml::from_str(&contents)
+            .map_err(|e| config_error(format!("{}: {}", path.display(), e)))?;
+
+        let base_dir = path.parent().unwrap_or_else(|| Path::new("."));
+        for field in [
+            &mut config.seed_file,
+            &mut config.monero_wordlist,
+            &mut config.output_dir,
+            &mut config.revocations,
+            &mut config.audit_log,
+            &mut config.index_registry,
+        ] {
+            if let Some(path) = field.as_mut() {
+                *path = resolve_path(path, base_dir);
+            }
+        }
+
+        Ok(config)
+    }
+
+    /// Read the config at [`Config::default_path`], if there is one
+    pub fn load_default() -> Result<Self> {
+        match Self::default_path() {
+            Some(path) if path.exists() => Self::load(&path),
+            _ => Ok(Self::default()),
+        }
+    }
+
+    /// `$XDG_CONFIG_HOME/bip-keychain/config.toml`, falling back to
+    /// `~/.config`
+    pub fn default_path() -> Option<PathBuf> {
+        let config_home = std::env::var_os("XDG_CONFIG_HOME")
+            .filter(|dir| !dir.is_empty())
+            .map(PathBuf::from)
+            .or_else(|| std::env::var_os("HOME").map(|home| Path::new(&home).join(".config")))?;
+        Some(config_home.join("bip-keychain").join("config.toml"))
+    }
+
+    /// Default output formats (empty if unset)
+    pub fn formats(&self) -> Vec<OutputFormat> {
+        match &self.format {
+            Some(Formats::One(format)) => vec![*format],
+            Some(Formats::Many(formats)) => formats.clone(),
+            None => Vec::new(),
+        }
+    }
+}
+
+/// Expand `~/` and make relative paths relative to `base_dir`
+fn resolve_path(path: &Path, base_dir: &Path) -> PathBuf {
+    if let Ok(rest) = path.strip_prefix("~") {
+        if let Some(home) = std::env::var_os("HOME") {
+            return Path::new(&home).join(rest);
+        }
+    }
+    if path.is_relative() {
+        base_dir.join(path)
+    } else {
+        path.to_path_buf()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_config() {
+        let config = Config::from_toml(
+            r#"
+seed-source = "os-keychain"
+keychain-account = "work"
+seed-format = "aezeed"
+format = ["ssh", "public-key"]
+parent-entropy = "00ff"
+output-dir = "keys"
+"#,
+        )
+        .unwrap();
+
+        assert_eq!(config.seed_source, Some(SeedSourceConfig::OsKeychain));
+        assert_eq!(config.keychain_account.as_deref(), Some("work"));
+        assert_eq!(config.seed_format, Some(SeedFormatConfig::Aezeed));
+        assert_eq!(
+            config.formats(),
+            [OutputFormat::SshPublicKey, OutputFormat::Ed25519PublicHex]
+        );
+        assert_eq!(
+            config.parent_entropy,
+            Some(ParentEntropyPolicy::Fixed(vec![0x00, 0xff]))
+        );
+
+        let single = Config::from_toml("format = \"json\"\nparent-entropy = \"require\"").unwrap();
+        assert_eq!(single.formats(), [OutputFormat::Json]);
+        assert_eq!(single.parent_entropy, Some(ParentEntropyPolicy::Require));
+        assert!(Config::from_toml("").unwrap().formats().is_empty());
+    }
+
+    #[test]
+    fn test_rejects_invalid_config() {
+        assert!(Config::from_toml("seed-sauce = \"env\"").is_err());
+        assert!(Config::from_toml("format = \"pem\"").is_err());
+        assert!(Config::from_toml("seed-format = \"slip39\"").is_err());
+        assert!(Config::from_toml("parent-entropy = \"sometimes\"").is_err());
+    }
+
+    #[test]
+    fn test_resolve_path() {
+        let base = Path::new("/etc/bip-keychain");
+        assert_eq!(
+            resolve_path(Path::new("keys"), base),
+            Path::new("/etc/bip-keychain/keys")
+        );
+        assert_eq!(
+            resolve_path(Path::new("/var/keys"), base),
+            Path::new("/var/keys")
+        );
+    }
+}
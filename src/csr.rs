@@ -0,0 +1,313 @@
+//! PKCS#10 certificate signing requests (RFC 2986)
+//!
+//! Builds a CSR for a derived Ed25519 key (RFC 8410) so a CA can issue a
+//! certificate for it. The subject and subjectAltName extension come from
+//! the entity:
+//!
+//! - X.509 DN fields (`C`, `ST`, `L`, `O`, `OU`, `CN`, `emailAddress`) form
+//!   the subject, as in `examples/x509-distinguished-name.json`
+//! - A host name (`fqdn`, `hostname`, or host-like `name`) becomes a DNS
+//!   SAN, and the CN when the entity has none
+//! - `emailAddress` or `email` becomes an email SAN; `url` a URI SAN
+//!
+//! Entities with none of these get `CN=<purpose>`.
+
+use crate::error::{BipKeychainError, Result};
+use crate::output::Ed25519Keypair;
+use ed25519_dalek::Signer;
+use serde_json::Value;
+
+/// id-Ed25519 (1.3.101.112)
+const OID_ED25519: &[u8] = &[0x2b, 0x65, 0x70];
+
+/// pkcs-9 extensionRequest (1.2.840.113549.1.9.14)
+const OID_EXTENSION_REQUEST: &[u8] = &[0x2a, 0x86, 0x48, 0x86, 0xf7, 0x0d, 0x01, 0x09, 0x0e];
+
+/// id-ce-subjectAltName (2.5.29.17)
+const OID_SUBJECT_ALT_NAME: &[u8] = &[0x55, 0x1d, 0x11];
+
+/// Subject attributes in the order they appear in the DN, with their
+/// entity field name, OID, and string type
+const SUBJECT_ATTRIBUTES: &[(&str, &[u8], u8)] = &[
+    ("C", &[0x55, 0x04, 0x06], PRINTABLE_STRING),
+    ("ST", &[0x55, 0x04, 0x08], UTF8_STRING),
+    ("L", &[0x55, 0x04, 0x07], UTF8_STRING),
+    ("O", &[0x55, 0x04, 0x0a], UTF8_STRING),
+    ("OU", &[0x55, 0x04, 0x0b], UTF8_STRING),
+    ("CN", &[0x55, 0x04, 0x03], UTF8_STRING),
+    (
+        "emailAddress",
+        &[0x2a, 0x86, 0x48, 0x86, 0xf7, 0x0d, 0x01, 0x09, 0x01],
+        IA5_STRING,
+    ),
+];
+
+const INTEGER: u8 = 0x02;
+const BIT_STRING: u8 = 0x03;
+const OCTET_STRING: u8 = 0x04;
+const OBJECT_IDENTIFIER: u8 = 0x06;
+const UTF8_STRING: u8 = 0x0c;
+const PRINTABLE_STRING: u8 = 0x13;
+const IA5_STRING: u8 = 0x16;
+const SEQUENCE: u8 = 0x30;
+const SET: u8 = 0x31;
+
+/// GeneralName tags (context-specific, primitive)
+const SAN_EMAIL: u8 = 0x81;
+const SAN_DNS: u8 = 0x82;
+const SAN_URI: u8 = 0x86;
+
+fn csr_error(message: String) -> BipKeychainError {
+    BipKeychainError::OutputError(message)
+}
+
+/// Subject and subjectAltNames for a certificate request
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct CsrSubject {
+    /// DN attributes as (field name, value), e.g. `("CN", "example.com")`
+    pub attributes: Vec<(String, String)>,
+    /// DNS names
+    pub dns_names: Vec<String>,
+    /// Email addresses
+    pub emails: Vec<String>,
+    /// URIs
+    pub uris: Vec<String>,
+}
+
+impl CsrSubject {
+    /// Collect the subject from entity fields (see the module docs)
+    ///
+    /// `host` is the entity's host name, if it has one; `fallback_cn` is
+    /// used when nothing else names the subject.
+    pub fn from_entity(entity: &Value, host: Option<&str>, fallback_cn: &str) -> Self {
+        let field = |name: &str| entity.get(name).and_then(Value::as_str);
+        let mut subject = CsrSubject::default();
+
+        for (name, _, _) in SUBJECT_ATTRIBUTES {
+            if let Some(value) = field(name) {
+                subject
+                    .attributes
+                    .push((name.to_string(), value.to_string()));
+            }
+        }
+        let host = host.map(|host| host.trim_end_matches('.'));
+        if field("CN").is_none() {
+            let cn = host.unwrap_or(fallback_cn);
+            let at = subject
+                .attributes
+                .iter()
+                .position(|(name, _)| name == "emailAddress")
+                .unwrap_or(subject.attributes.len());
+            subject
+                .attributes
+                .insert(at, ("CN".to_string(), cn.to_string()));
+        }
+
+        subject.dns_names.extend(host.map(str::to_string));
+        subject.emails.extend(
+            field("emailAddress")
+                .or_else(|| field("email"))
+                .map(str::to_string),
+        );
+        subject.uris.extend(field("url").map(str::to_string));
+        subject
+    }
+}
+
+/// Build a PEM-encoded PKCS#10 request signed by `keypair`
+pub fn certificate_request(keypair: &Ed25519Keypair, subject: &CsrSubject) -> Result<String> {
+    let info = der(
+        SEQUENCE,
+        &[
+            der(INTEGER, &[0]),
+            name(subject)?,
+            keypair.to_spki_der(),
+            attributes(subject)?,
+        ]
+        .concat(),
+    );
+    let signature = keypair.signing_key().sign(&info);
+
+    let mut bit_string = vec![0u8];
+    bit_string.extend_from_slice(&signature.to_bytes());
+    let request = der(
+        SEQUENCE,
+        &[info, ed25519_algorithm(), der(BIT_STRING, &bit_string)].concat(),
+    );
+    Ok(crate::output::pem_armor("CERTIFICATE REQUEST", &request).to_string())
+}
+
+/// Name: SEQUENCE OF RelativeDistinguishedName, one attribute each
+fn name(subject: &CsrSubject) -> Result<Vec<u8>> {
+    let mut rdns = Vec::new();
+    for (field, value) in &subject.attributes {
+        let (_, oid, string_type) = SUBJECT_ATTRIBUTES
+            .iter()
+            .find(|(name, _, _)| name == field)
+            .ok_or_else(|| csr_error(format!("Unsupported CSR subject field '{}'", field)))?;
+        check_string(*string_type, field, value)?;
+        let attribute = der(
+            SEQUENCE,
+            &[
+                der(OBJECT_IDENTIFIER, oid),
+                der(*string_type, value.as_bytes()),
+            ]
+            .concat(),
+        );
+        rdns.extend(der(SET, &attribute));
+    }
+    Ok(der(SEQUENCE, &rdns))
+}
+
+/// `[0] IMPLICIT SET OF Attribute`, with an extensionRequest for the SANs
+fn attributes(subject: &CsrSubject) -> Result<Vec<u8>> {
+    let mut names = Vec::new();
+    for (tag, values, field) in [
+        (SAN_DNS, &subject.dns_names, "DNS name"),
+        (SAN_EMAIL, &subject.emails, "email"),
+        (SAN_URI, &subject.uris, "URI"),
+    ] {
+        for value in values {
+            check_string(IA5_STRING, field, value)?;
+            names.extend(der(tag, value.as_bytes()));
+        }
+    }
+    if names.is_empty() {
+        return Ok(der(0xa0, &[]));
+    }
+
+    let extension = der(
+        SEQUENCE,
+        &[
+            der(OBJECT_IDENTIFIER, OID_SUBJECT_ALT_NAME),
+            der(OCTET_STRING, &der(SEQUENCE, &names)),
+        ]
+        .concat(),
+    );
+    let request = der(
+        SEQUENCE,
+        &[
+            der(OBJECT_IDENTIFIER, OID_EXTENSION_REQUEST),
+            der(SET, &der(SEQUENCE, &extension)),
+        ]
+        .concat(),
+    );
+    Ok(der(0xa0, &request))
+}
+
+fn ed25519_algorithm() -> Vec<u8> {
+    der(SEQUENCE, &der(OBJECT_IDENTIFIER, OID_ED25519))
+}
+
+/// Reject values the ASN.1 string type cannot hold
+fn check_string(string_type: u8, field: &str, value: &str) -> Result<()> {
+    let valid = match string_type {
+        PRINTABLE_STRING => value
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || " '()+,-./:=?".contains(c)),
+        IA5_STRING => value.is_ascii(),
+        _ => true,
+    };
+    if value.is_empty() || !valid {
+        return Err(csr_error(format!(
+            "Invalid CSR {} '{}': empty, or characters the field does not allow",
+            field, value
+        )));
+    }
+    Ok(())
+}
+
+/// DER tag-length-value
+fn der(tag: u8, content: &[u8]) -> Vec<u8> {
+    let mut out = vec![tag];
+    let len = content.len();
+    if len < 0x80 {
+        out.push(len as u8);
+    } else {
+        let bytes = len.to_be_bytes();
+        let skip = bytes.iter().take_while(|&&b| b == 0).count();
+        out.push(0x80 | (bytes.len() - skip) as u8);
+        out.extend_from_slice(&bytes[skip..]);
+    }
+    out.extend_from_slice(content);
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_subject_from_entity() {
+        let dn = json!({"CN": "Alice", "O": "ACME", "C": "US", "emailAddress": "alice@acme.com"});
+        let subject = CsrSubject::from_entity(&dn, None, "unused");
+        let names: Vec<&str> = subject.attributes.iter().map(|(n, _)| n.as_str()).collect();
+        assert_eq!(names, ["C", "O", "CN", "emailAddress"]);
+        assert_eq!(subject.emails, ["alice@acme.com"]);
+        assert!(subject.dns_names.is_empty());
+
+        let host = json!({"fqdn": "db1.example.com"});
+        let subject = CsrSubject::from_entity(&host, Some("db1.example.com."), "unused");
+        assert_eq!(
+            subject.attributes,
+            [("CN".to_string(), "db1.example.com".to_string())]
+        );
+        assert_eq!(subject.dns_names, ["db1.example.com"]);
+
+        let other = json!({"@type": "WebSite", "url": "https://example.com"});
+        let subject = CsrSubject::from_entity(&other, None, "Login key");
+        assert_eq!(subject.attributes[0].1, "Login key");
+        assert_eq!(subject.uris, ["https://example.com"]);
+    }
+
+    #[test]
+    fn test_certificate_request_structure() {
+        let keypair = Ed25519Keypair::from_seed([1u8; 32]);
+        let subject = CsrSubject {
+            attributes: vec![("CN".to_string(), "example.com".to_string())],
+            dns_names: vec!["example.com".to_string()],
+            ..CsrSubject::default()
+        };
+        let pem = certificate_request(&keypair, &subject).unwrap();
+        assert!(pem.starts_with("-----BEGIN CERTIFICATE REQUEST-----\n"));
+
+        let body: String = pem.lines().filter(|l| !l.starts_with("-----")).collect();
+        let request =
+            base64::Engine::decode(&base64::engine::general_purpose::STANDARD, body).unwrap();
+        // Outer SEQUENCE, then the signature: BIT STRING of 65 bytes at the end
+        assert_eq!(request[0], SEQUENCE);
+        assert_eq!(
+            &request[request.len() - 67..request.len() - 64],
+            &[0x03, 0x41, 0x00]
+        );
+
+        // Signature verifies over CertificationRequestInfo, which sits
+        // between the outer header and the algorithm identifier
+        let header = 2 + (request[1] & 0x7f) as usize * (request[1] >> 7) as usize;
+        let algorithm = ed25519_algorithm();
+        let info = &request[header..request.len() - 67 - algorithm.len()];
+        assert_eq!(
+            &request[request.len() - 67 - algorithm.len()..][..7],
+            algorithm
+        );
+        let signature: [u8; 64] = request[request.len() - 64..].try_into().unwrap();
+        keypair
+            .verifying_key()
+            .verify_strict(info, &ed25519_dalek::Signature::from_bytes(&signature))
+            .unwrap();
+
+        let bad = CsrSubject {
+            attributes: vec![("C".to_string(), "U*".to_string())],
+            ..CsrSubject::default()
+        };
+        assert!(certificate_request(&keypair, &bad).is_err());
+    }
+
+    #[test]
+    fn test_der_lengths() {
+        assert_eq!(der(OCTET_STRING, &[0; 3])[..2], [0x04, 0x03]);
+        assert_eq!(der(OCTET_STRING, &[0; 200])[..3], [0x04, 0x81, 200]);
+        assert_eq!(der(OCTET_STRING, &[0; 300])[..4], [0x04, 0x82, 0x01, 0x2c]);
+    }
+}
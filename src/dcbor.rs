@@ -0,0 +1,505 @@
+//! Deterministic CBOR (dCBOR) encoding
+//!
+//! Encodes JSON values following the Blockchain Commons dCBOR profile
+//! (draft-mcnally-deterministic-cbor), so the same entity always produces
+//! the same bytes:
+//! - integers, lengths, and tags use the shortest encoding
+//! - map keys are sorted by their encoded bytes
+//! - integral floats are reduced to integers, other floats use the shortest
+//!   exact width (f16, f32, or f64)
+//! - text strings are NFC-normalized
+//!
+//! [`Decoder`] reads dCBOR back for envelope ingestion. It checks the
+//! encoding rules that keep bytes canonical (shortest heads, definite
+//! lengths, sorted unique map keys) but does not interpret values;
+//! [`decode_json`] turns items that [`encode_json`] could produce back
+//! into JSON.
+
+use serde_json::Value;
+use unicode_normalization::UnicodeNormalization;
+
+pub(crate) const MAJOR_UNSIGNED: u8 = 0;
+pub(crate) const MAJOR_NEGATIVE: u8 = 1;
+pub(crate) const MAJOR_BYTES: u8 = 2;
+pub(crate) const MAJOR_TEXT: u8 = 3;
+pub(crate) const MAJOR_ARRAY: u8 = 4;
+pub(crate) const MAJOR_MAP: u8 = 5;
+pub(crate) const MAJOR_TAG: u8 = 6;
+
+const SIMPLE_FALSE: u8 = 0xf4;
+const SIMPLE_TRUE: u8 = 0xf5;
+const SIMPLE_NULL: u8 = 0xf6;
+
+/// Maximum nesting depth accepted by [`Decoder`]
+pub(crate) const MAX_DEPTH: usize = 64;
+
+/// 2^64: CBOR integers span [-2^64, 2^64 - 1]
+const CBOR_INT_LIMIT: f64 = 18_446_744_073_709_551_616.0;
+
+/// Append a CBOR head (major type + argument) in its shortest form
+pub fn encode_head(buf: &mut Vec<u8>, major: u8, value: u64) {
+    let major = major << 5;
+    if value < 24 {
+        buf.push(major | value as u8);
+    } else if value <= u8::MAX as u64 {
+        buf.push(major | 24);
+        buf.push(value as u8);
+    } else if value <= u16::MAX as u64 {
+        buf.push(major | 25);
+        buf.extend_from_slice(&(value as u16).to_be_bytes());
+    } else if value <= u32::MAX as u64 {
+        buf.push(major | 26);
+        buf.extend_from_slice(&(value as u32).to_be_bytes());
+    } else {
+        buf.push(major | 27);
+        buf.extend_from_slice(&value.to_be_bytes());
+    }
+}
+
+/// Append an unsigned integer
+pub fn encode_unsigned(buf: &mut Vec<u8>, value: u64) {
+    encode_head(buf, MAJOR_UNSIGNED, value);
+}
+
+/// Append a byte string
+pub fn encode_bytes(buf: &mut Vec<u8>, bytes: &[u8]) {
+    encode_head(buf, MAJOR_BYTES, bytes.len() as u64);
+    buf.extend_from_slice(bytes);
+}
+
+/// Append an NFC-normalized text string
+pub fn encode_text(buf: &mut Vec<u8>, text: &str) {
+    let normalized: String = text.nfc().collect();
+    encode_head(buf, MAJOR_TEXT, normalized.len() as u64);
+    buf.extend_from_slice(normalized.as_bytes());
+}
+
+/// Append an array header for `len` items
+pub fn encode_array_header(buf: &mut Vec<u8>, len: usize) {
+    encode_head(buf, MAJOR_ARRAY, len as u64);
+}
+
+/// Append a map header for `len` key/value pairs
+pub fn encode_map_header(buf: &mut Vec<u8>, len: usize) {
+    encode_head(buf, MAJOR_MAP, len as u64);
+}
+
+/// Append a tag number; the tagged item must follow
+pub fn encode_tag(buf: &mut Vec<u8>, tag: u64) {
+    encode_head(buf, MAJOR_TAG, tag);
+}
+
+/// Encode a JSON value as dCBOR
+pub fn encode_json(value: &Value) -> Vec<u8> {
+    let mut buf = Vec::new();
+    append_json(&mut buf, value);
+    buf
+}
+
+fn append_json(buf: &mut Vec<u8>, value: &Value) {
+    match value {
+        Value::Null => buf.push(SIMPLE_NULL),
+        Value::Bool(false) => buf.push(SIMPLE_FALSE),
+        Value::Bool(true) => buf.push(SIMPLE_TRUE),
+        Value::Number(n) => {
+            if let Some(u) = n.as_u64() {
+                encode_unsigned(buf, u);
+            } else if let Some(i) = n.as_i64() {
+                append_integer(buf, i as i128);
+            } else if let Some(f) = n.as_f64() {
+                append_float(buf, f);
+            }
+        }
+        Value::String(s) => encode_text(buf, s),
+        Value::Array(items) => {
+            encode_array_header(buf, items.len());
+            for item in items {
+                append_json(buf, item);
+            }
+        }
+        Value::Object(map) => {
+            // dCBOR orders map entries by the bytewise order of encoded keys
+            let mut entries: Vec<(Vec<u8>, &Value)> = map
+                .iter()
+                .map(|(key, value)| {
+                    let mut encoded_key = Vec::new();
+                    encode_text(&mut encoded_key, key);
+                    (encoded_key, value)
+                })
+                .collect();
+            entries.sort_by(|a, b| a.0.cmp(&b.0));
+
+            encode_map_header(buf, entries.len());
+            for (encoded_key, value) in entries {
+                buf.extend_from_slice(&encoded_key);
+                append_json(buf, value);
+            }
+        }
+    }
+}
+
+/// Decode a single dCBOR item into JSON
+///
+/// Only items [`encode_json`] produces are accepted: byte strings, tags,
+/// non-text map keys, and non-finite floats have no JSON form.
+pub fn decode_json(data: &[u8]) -> Result<Value, String> {
+    // Check the canonical encoding rules before interpreting anything
+    let mut decoder = Decoder::new(data);
+    decoder.read_item()?;
+    if !decoder.is_empty() {
+        return Err("trailing bytes after CBOR item".into());
+    }
+    Decoder::new(data).read_json()
+}
+
+/// Reader for dCBOR-encoded bytes
+///
+/// Errors are plain messages; callers wrap them in their own error variant.
+pub struct Decoder<'a> {
+    data: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> Decoder<'a> {
+    /// Start reading at the beginning of `data`
+    pub fn new(data: &'a [u8]) -> Self {
+        Self { data, pos: 0 }
+    }
+
+    /// Whether all input has been read
+    pub fn is_empty(&self) -> bool {
+        self.pos == self.data.len()
+    }
+
+    /// Read a head, returning its major type and argument
+    ///
+    /// Rejects indefinite lengths and arguments not in their shortest form.
+    /// For major type 7 the argument is the raw simple value or float bits.
+    pub fn read_head(&mut self) -> Result<(u8, u64), String> {
+        let initial = *self.data.get(self.pos).ok_or("unexpected end of CBOR")?;
+        self.pos += 1;
+
+        let major = initial >> 5;
+        let info = initial & 0x1f;
+        let width = match info {
+            0..=23 => return Ok((major, info as u64)),
+            24 => 1,
+            25 => 2,
+            26 => 4,
+            27 => 8,
+            31 => return Err("indefinite-length items are not allowed in dCBOR".into()),
+            _ => return Err(format!("reserved CBOR additional info {}", info)),
+        };
+
+        let bytes = self
+            .data
+            .get(self.pos..self.pos + width)
+            .ok_or("unexpected end of CBOR")?;
+        self.pos += width;
+        let value = bytes.iter().fold(0u64, |acc, b| (acc << 8) | *b as u64);
+
+        // Floats (major 7) carry their own width; everything else must be
+        // in the shortest form encode_head would produce
+        if major != 7 {
+            let mut shortest = Vec::new();
+            encode_head(&mut shortest, major, value);
+            if shortest.len() != 1 + width {
+                return Err("CBOR head is not in its shortest form".into());
+            }
+        } else if width == 1 {
+            return Err("two-byte simple values are not allowed in dCBOR".into());
+        }
+
+        Ok((major, value))
+    }
+
+    /// Read one complete data item and return its encoded bytes
+    pub fn read_item(&mut self) -> Result<&'a [u8], String> {
+        let start = self.pos;
+        self.skip_item(0)?;
+        Ok(&self.data[start..self.pos])
+    }
+
+    /// Read `len` raw bytes, such as the contents of a byte string
+    pub fn read_bytes(&mut self, len: u64) -> Result<&'a [u8], String> {
+        let end = usize::try_from(len)
+            .ok()
+            .and_then(|len| self.pos.checked_add(len))
+            .filter(|end| *end <= self.data.len())
+            .ok_or("unexpected end of CBOR")?;
+        let bytes = &self.data[self.pos..end];
+        self.pos = end;
+        Ok(bytes)
+    }
+
+    /// Read an already-validated item as JSON
+    fn read_json(&mut self) -> Result<Value, String> {
+        let info = self.data.get(self.pos).map_or(0, |b| b & 0x1f);
+        let (major, value) = self.read_head()?;
+        Ok(match major {
+            MAJOR_UNSIGNED => Value::from(value),
+            MAJOR_NEGATIVE => i64::try_from(value)
+                .map(|value| Value::from(-1 - value))
+                .map_err(|_| "CBOR negative integer is out of JSON range")?,
+            MAJOR_TEXT => {
+                let text = std::str::from_utf8(self.read_bytes(value)?)
+                    .map_err(|_| "CBOR text string is not valid UTF-8")?;
+                Value::String(text.to_string())
+            }
+            MAJOR_ARRAY => Value::Array(
+                (0..value)
+                    .map(|_| self.read_json())
+                    .collect::<Result<_, _>>()?,
+            ),
+            MAJOR_MAP => {
+                let mut map = serde_json::Map::new();
+                for _ in 0..value {
+                    let Value::String(key) = self.read_json()? else {
+                        return Err("CBOR map key is not a text string".into());
+                    };
+                    map.insert(key, self.read_json()?);
+                }
+                Value::Object(map)
+            }
+            MAJOR_BYTES => return Err("CBOR byte strings have no JSON form".into()),
+            MAJOR_TAG => return Err("CBOR tags have no JSON form".into()),
+            _ => {
+                let float = match info {
+                    25 => f16_to_f64(value as u16),
+                    26 => f32::from_bits(value as u32) as f64,
+                    27 => f64::from_bits(value),
+                    _ => {
+                        return Ok(match (value as u8) | 0xe0 {
+                            SIMPLE_FALSE => Value::Bool(false),
+                            SIMPLE_TRUE => Value::Bool(true),
+                            _ => Value::Null,
+                        })
+                    }
+                };
+                serde_json::Number::from_f64(float)
+                    .map(Value::Number)
+                    .ok_or("CBOR float is not finite")?
+            }
+        })
+    }
+
+    fn skip_item(&mut self, depth: usize) -> Result<(), String> {
+        if depth > MAX_DEPTH {
+            return Err("CBOR nesting is too deep".into());
+        }
+
+        let info = self.data.get(self.pos).map_or(0, |b| b & 0x1f);
+        let (major, value) = self.read_head()?;
+        match major {
+            MAJOR_UNSIGNED | MAJOR_NEGATIVE => {}
+            MAJOR_BYTES => {
+                self.read_bytes(value)?;
+            }
+            MAJOR_TEXT => {
+                let text = std::str::from_utf8(self.read_bytes(value)?)
+                    .map_err(|_| "CBOR text string is not valid UTF-8")?;
+                if !text.nfc().eq(text.chars()) {
+                    return Err("CBOR text string is not NFC-normalized".into());
+                }
+            }
+            MAJOR_ARRAY => {
+                for _ in 0..value {
+                    self.skip_item(depth + 1)?;
+                }
+            }
+            MAJOR_MAP => {
+                let mut previous: Option<&[u8]> = None;
+                for _ in 0..value {
+                    let start = self.pos;
+                    self.skip_item(depth + 1)?;
+                    let key = &self.data[start..self.pos];
+                    if previous.is_some_and(|previous| previous >= key) {
+                        return Err("CBOR map keys are not sorted and unique".into());
+                    }
+                    previous = Some(key);
+                    self.skip_item(depth + 1)?;
+                }
+            }
+            MAJOR_TAG => self.skip_item(depth + 1)?,
+            _ => {
+                // Floats are 25-27; of the simple values only false, true,
+                // and null are valid dCBOR
+                let simple = [SIMPLE_FALSE, SIMPLE_TRUE, SIMPLE_NULL].map(|b| (b & 0x1f) as u64);
+                if info < 25 && !simple.contains(&value) {
+                    return Err(format!("unsupported CBOR simple value {}", value));
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+fn append_integer(buf: &mut Vec<u8>, value: i128) {
+    if value >= 0 {
+        encode_head(buf, MAJOR_UNSIGNED, value as u64);
+    } else {
+        encode_head(buf, MAJOR_NEGATIVE, (-1 - value) as u64);
+    }
+}
+
+fn append_float(buf: &mut Vec<u8>, value: f64) {
+    // Numeric reduction: integral values in the 65-bit CBOR integer range
+    // are encoded as integers
+    if value.fract() == 0.0 && (-CBOR_INT_LIMIT..CBOR_INT_LIMIT).contains(&value) {
+        append_integer(buf, value as i128);
+        return;
+    }
+
+    let as_f32 = value as f32;
+    if as_f32 as f64 == value {
+        if let Some(half) = f32_to_f16_exact(as_f32) {
+            buf.push(0xf9);
+            buf.extend_from_slice(&half.to_be_bytes());
+        } else {
+            buf.push(0xfa);
+            buf.extend_from_slice(&as_f32.to_be_bytes());
+        }
+    } else {
+        buf.push(0xfb);
+        buf.extend_from_slice(&value.to_be_bytes());
+    }
+}
+
+/// Widen an IEEE 754 half-precision value
+fn f16_to_f64(bits: u16) -> f64 {
+    let sign = if bits & 0x8000 != 0 { -1.0 } else { 1.0 };
+    let exponent = ((bits >> 10) & 0x1f) as i32;
+    let mantissa = (bits & 0x3ff) as f64;
+    sign * match exponent {
+        0 => mantissa * 2f64.powi(-24),
+        31 if mantissa == 0.0 => f64::INFINITY,
+        31 => f64::NAN,
+        _ => (1.0 + mantissa / 1024.0) * 2f64.powi(exponent - 15),
+    }
+}
+
+/// Convert a finite, non-zero f32 to IEEE 754 half precision if exact
+fn f32_to_f16_exact(value: f32) -> Option<u16> {
+    let bits = value.to_bits();
+    let sign = ((bits >> 16) & 0x8000) as u16;
+    let exponent = ((bits >> 23) & 0xff) as i32 - 127;
+    let mantissa = bits & 0x7f_ffff;
+
+    if (-14..=15).contains(&exponent) {
+        // Normal half: 10 mantissa bits, the low 13 must be zero
+        if mantissa & 0x1fff != 0 {
+            return None;
+        }
+        let half_exponent = (exponent + 15) as u16;
+        Some(sign | (half_exponent << 10) | (mantissa >> 13) as u16)
+    } else if (-24..-14).contains(&exponent) {
+        // Subnormal half: value = m * 2^-24 with m < 1024
+        let full_mantissa = mantissa | 0x80_0000;
+        let shift = (-exponent - 14 + 13) as u32;
+        if full_mantissa & ((1 << shift) - 1) != 0 {
+            return None;
+        }
+        Some(sign | (full_mantissa >> shift) as u16)
+    } else {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn hex_of(value: Value) -> String {
+        hex::encode(encode_json(&value))
+    }
+
+    #[test]
+    fn test_integers_use_shortest_form() {
+        assert_eq!(hex_of(json!(0)), "00");
+        assert_eq!(hex_of(json!(23)), "17");
+        assert_eq!(hex_of(json!(24)), "1818");
+        assert_eq!(hex_of(json!(1000)), "1903e8");
+        assert_eq!(hex_of(json!(-1)), "20");
+        assert_eq!(hex_of(json!(-1000)), "3903e7");
+    }
+
+    #[test]
+    fn test_float_reduction() {
+        // Integral floats become integers
+        assert_eq!(hex_of(json!(2.0)), "02");
+        assert_eq!(hex_of(json!(-0.0)), "00");
+        // Shortest exact float width (RFC 8949 Appendix A values)
+        assert_eq!(hex_of(json!(1.5)), "f93e00");
+        assert_eq!(hex_of(json!(5.960464477539063e-8)), "f90001");
+        assert_eq!(hex_of(json!(100000.5)), "fa47c35040");
+        assert_eq!(hex_of(json!(1.1)), "fb3ff199999999999a");
+    }
+
+    #[test]
+    fn test_map_keys_sorted_by_encoding() {
+        // Shorter keys sort first because their length byte is smaller
+        let encoded = hex_of(json!({"bb": 1, "a": 2, "c": 3}));
+        assert_eq!(encoded, "a361610261630362626201");
+    }
+
+    #[test]
+    fn test_text_is_nfc_normalized() {
+        // "e" + combining acute accent normalizes to U+00E9
+        assert_eq!(hex_of(json!("e\u{301}")), "62c3a9");
+    }
+
+    #[test]
+    fn test_decoder_reads_encoded_items() {
+        let encoded = encode_json(&json!({"bb": [1, -1000, 1.5], "a": {"x": null}}));
+        let mut decoder = Decoder::new(&encoded);
+        assert_eq!(decoder.read_item().unwrap(), encoded.as_slice());
+        assert!(decoder.is_empty());
+
+        let mut decoder = Decoder::new(&[0xd8, 0xc9, 0x18, 0x2a]);
+        assert_eq!(decoder.read_head().unwrap(), (MAJOR_TAG, 201));
+        assert_eq!(decoder.read_head().unwrap(), (MAJOR_UNSIGNED, 42));
+    }
+
+    #[test]
+    fn test_decoder_rejects_non_canonical_cbor() {
+        for bytes in [
+            &[0x18, 0x17][..],                           // 23 in two bytes
+            &[0x9f, 0x01, 0xff],                         // indefinite-length array
+            &[0xa2, 0x61, 0x62, 0x01, 0x61, 0x61, 0x02], // unsorted map keys
+            &[0xa2, 0x61, 0x61, 0x01, 0x61, 0x61, 0x02], // duplicate map keys
+            &[0x63, 0x65, 0xcc, 0x81],                   // "e" + combining accent (not NFC)
+            &[0xf7],                                     // undefined
+            &[0x82, 0x01],                               // truncated array
+        ] {
+            assert!(
+                Decoder::new(bytes).read_item().is_err(),
+                "accepted {}",
+                hex::encode(bytes)
+            );
+        }
+    }
+
+    #[test]
+    fn test_decode_json_round_trip() {
+        let value = json!({
+            "name": "caf\u{e9}",
+            "n": [0, 24, -1000, 1.5, 5.960464477539063e-8, 100000.5, 1.1],
+            "flags": [true, false, null],
+            "nested": {"bb": {}, "a": []}
+        });
+        assert_eq!(decode_json(&encode_json(&value)).unwrap(), value);
+
+        assert!(decode_json(&[0x41, 0x00]).is_err()); // byte string
+        assert!(decode_json(&[0xc1, 0x00]).is_err()); // tag
+        assert!(decode_json(&[0xa1, 0x01, 0x02]).is_err()); // integer key
+        assert!(decode_json(&[0xf9, 0x7c, 0x00]).is_err()); // infinity
+        assert!(decode_json(&[0x01, 0x02]).is_err()); // trailing item
+    }
+
+    #[test]
+    fn test_simple_values_and_arrays() {
+        assert_eq!(hex_of(json!([true, false, null])), "83f5f4f6");
+        assert_eq!(hex_of(json!("Hello.")), "6648656c6c6f2e");
+    }
+}
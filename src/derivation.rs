@@ -1,18 +1,33 @@
 //! Core BIP-Keychain derivation algorithm
 //!
 //! Implements the entity → derived key pipeline:
-//! 1. Canonicalize entity JSON
+//! 1. Canonicalize entity (serde JSON, JCS, dCBOR, or as written)
 //! 2. Hash with configured function
-//! 3. Extract first 4 bytes as u32 index
-//! 4. Derive BIP-32 key at m/83696968'/67797668'/{index}'
-//! 5. Return derived key
+//! 3. Optionally expand the hash with HKDF-SHA512
+//! 4. Extract first 4 bytes as u32 index (or 8/12 bytes for multi-level paths)
+//! 5. Derive BIP-32 key at m/83696968'/67797668'/{index}'
+//! 6. Return derived key (seeding from raw key bytes or BIP-85 entropy)
 
 use crate::{
-    bip32_wrapper::{DerivedKey, Keychain},
-    entity::{HashFunctionConfig, KeyDerivation},
+    backend::KeychainBackend,
+    bip32_wrapper::{DerivedKey, Keychain, PublicKeychain, BIP85_APP, BIPKEYCHAIN_APP},
+    entity::{
+        DerivationConfig, EntityParent, HashFunctionConfig, IndexExpansionConfig, KeyDerivation,
+        KeyEntropyConfig, ParentReference,
+    },
     error::{BipKeychainError, Result},
-    hash::{hash_entity, HashFunction},
+    hash::{hash_bytes, hkdf_sha512_expand, HashFunction},
 };
+use bip32::{ChildNumber, DerivationPath};
+
+/// Parent entropy used by the CLI and bindings when none is supplied
+///
+/// Keys derived with it depend only on the seed and the entity. Supply
+/// your own parent entropy to namespace keys further.
+pub const DEFAULT_PARENT_ENTROPY: &[u8] = b"bip-keychain-default-entropy-32!";
+
+/// HMAC-SHA512 key used to hash tenant identifiers to path indices
+pub const TENANT_HMAC_KEY: &[u8] = b"bip-keychain-tenant";
 
 /// Derive a key from an entity using BIP-Keychain
 ///
@@ -23,6 +38,10 @@ use crate::{
 ///
 /// And returns a derived key that can be used for Ed25519 key generation.
 ///
+/// With `hardened: false` the entity-level components are non-hardened and
+/// clamped to 31 bits, so the matching public key can be computed from the
+/// application xpub with [`derive_public_key_from_entity`].
+///
 /// # Example
 ///
 /// ```ignore
@@ -38,26 +57,400 @@ pub fn derive_key_from_entity(
     key_derivation: &KeyDerivation,
     parent_entropy: &[u8],
 ) -> Result<DerivedKey> {
-    // Step 1: Get entity as canonical JSON string
-    let entity_json = key_derivation.entity_json()?;
+    // Entities with a parent are derived below the parent's key
+    if let Some(parent) = &key_derivation.parent {
+        let parent_key = derive_parent_key(keychain, parent, parent_entropy)?;
+        return derive_child_entity(&parent_key, key_derivation);
+    }
+
+    // Steps 1-4: Canonicalize, hash, and extract the entity index(es)
+    let children = entity_children(key_derivation, parent_entropy)?;
+
+    // Step 5: Derive BIP-32 key at BIP-Keychain path with entity-specific index
+    let derived_key = keychain.derive_entity_children(&children)?;
+
+    // Step 6: Select the seed source
+    Ok(apply_key_entropy(
+        &key_derivation.derivation_config,
+        derived_key,
+    ))
+}
 
-    // Step 2: Select hash function based on config
+/// Derive a sub-entity's key below a parent entity's key
+///
+/// The parent's seed ([`DerivedKey::to_seed`]) is used as parent entropy
+/// when hashing `child_entity`, and the resulting index is derived from the
+/// parent's BIP-32 node. Chaining calls builds semantic hierarchies such as
+/// organization → project → service, where each level's key depends on
+/// every entity above it:
+///
+/// `m/83696968'/67797668'/{org}'/{project}'/{service}'`
+///
+/// # Example
+///
+/// ```ignore
+/// let org = derive_key_from_entity(&keychain, &org_entity, parent_entropy)?;
+/// let project = derive_child_entity(&org, &project_entity)?;
+/// let service = derive_child_entity(&project, &service_entity)?;
+/// ```
+///
+/// The `parent` field of `child_entity` is not consulted; `parent_derived`
+/// takes its place. [`derive_key_from_entity`] resolves `parent` fields.
+pub fn derive_child_entity(
+    parent_derived: &DerivedKey,
+    child_entity: &KeyDerivation,
+) -> Result<DerivedKey> {
+    let parent_entropy = parent_derived.to_seed();
+    let children = entity_children(child_entity, &parent_entropy[..])?;
+    let derived_key = parent_derived.derive_children(&children)?;
+
+    Ok(apply_key_entropy(
+        &child_entity.derivation_config,
+        derived_key,
+    ))
+}
+
+/// Derive the key of a `parent` reference from the keychain root
+fn derive_parent_key(
+    keychain: &Keychain,
+    parent: &EntityParent,
+    parent_entropy: &[u8],
+) -> Result<DerivedKey> {
+    match parent {
+        EntityParent::Inline(parent_derivation) => {
+            derive_key_from_entity(keychain, parent_derivation, parent_entropy)
+        }
+        EntityParent::Hash(hash_hex) => {
+            keychain.derive_bip_keychain_path(hash_to_index(&parse_parent_hash(hash_hex)?)?)
+        }
+        EntityParent::Reference(reference) => {
+            let config = &reference.derivation_config;
+            let children = hash_children(config, &parse_parent_hash(&reference.hash)?)?;
+            let derived_key = match &reference.parent {
+                Some(grandparent) => derive_parent_key(keychain, grandparent, parent_entropy)?
+                    .derive_children(&children)?,
+                None => keychain.derive_entity_children(&children)?,
+            };
+            Ok(apply_key_entropy(config, derived_key))
+        }
+    }
+}
+
+fn parse_parent_hash(hash_hex: &str) -> Result<[u8; 64]> {
+    hex::decode(hash_hex.trim())
+        .ok()
+        .and_then(|bytes| bytes.try_into().ok())
+        .ok_or_else(|| {
+            BipKeychainError::HashError(
+                "Parent hash must be 128 hex characters (a 64-byte entity hash)".to_string(),
+            )
+        })
+}
+
+/// Reference to an entity for use as another entity's `parent`
+///
+/// Returns the bare hash for entities with the default path settings and no
+/// parent, and otherwise a [`ParentReference`] carrying the entity's
+/// derivation configuration and its own parent, so the referenced key is
+/// the one [`derive_key_from_entity`] derives for the entity itself. The
+/// keychain is only used when the entity has a parent, whose seed keys
+/// the entity's hash.
+///
+/// # Example
+///
+/// ```ignore
+/// let reference = parent_reference(&keychain, &org, parent_entropy)?;
+/// println!("{}", serde_json::to_string(&reference)?);
+/// ```
+pub fn parent_reference(
+    keychain: &Keychain,
+    key_derivation: &KeyDerivation,
+    parent_entropy: &[u8],
+) -> Result<EntityParent> {
+    let (hash, parent) = match &key_derivation.parent {
+        Some(parent) => {
+            let parent_seed = derive_parent_key(keychain, parent, parent_entropy)?.to_seed();
+            let parent = match parent {
+                EntityParent::Inline(inline) => parent_reference(keychain, inline, parent_entropy)?,
+                reference => reference.clone(),
+            };
+            (
+                entity_hash(key_derivation, &parent_seed[..])?,
+                Some(Box::new(parent)),
+            )
+        }
+        None => (entity_hash(key_derivation, parent_entropy)?, None),
+    };
+
+    let config = &key_derivation.derivation_config;
+    if parent.is_none() && config.has_default_path() {
+        return Ok(EntityParent::Hash(hex::encode(hash)));
+    }
+    Ok(EntityParent::Reference(ParentReference {
+        hash: hex::encode(hash),
+        derivation_config: config.clone(),
+        parent,
+    }))
+}
+
+fn apply_key_entropy(config: &DerivationConfig, derived_key: DerivedKey) -> DerivedKey {
+    match config.key_entropy {
+        KeyEntropyConfig::Raw => derived_key,
+        KeyEntropyConfig::Bip85 => derived_key.with_bip85_seed(),
+    }
+}
+
+/// Operations that only see the keychain root cannot follow `parent`
+/// references, because child indices depend on the parent's private seed
+fn reject_parent(key_derivation: &KeyDerivation, operation: &str) -> Result<()> {
+    if key_derivation.parent.is_some() {
+        return Err(BipKeychainError::Bip32Error(format!(
+            "{} is not available for entities with a parent: their path depends on \
+             the parent's private key",
+            operation
+        )));
+    }
+    Ok(())
+}
+
+/// Compute the public key for a non-hardened entity from a watch-only keychain
+///
+/// Returns the compressed secp256k1 public key at
+/// m/83696968'/67797668'/{index} where `index` is the entity index clamped
+/// to the non-hardened range (below 2^31), matching the private key from
+/// [`derive_key_from_entity`]. Entities with `hardened: true` are rejected,
+/// since their keys cannot be derived from an xpub.
+///
+/// # Example
+///
+/// ```ignore
+/// let watch_only = PublicKeychain::from_xpub(app_xpub)?;
+/// let pubkey = derive_public_key_from_entity(&watch_only, &key_deriv, parent_entropy)?;
+/// ```
+pub fn derive_public_key_from_entity(
+    public_keychain: &PublicKeychain,
+    key_derivation: &KeyDerivation,
+    parent_entropy: &[u8],
+) -> Result<[u8; 33]> {
+    if key_derivation.derivation_config.hardened {
+        return Err(BipKeychainError::Bip32Error(
+            "Entity uses hardened derivation; public keys require the private keychain. \
+             Set derivation_config.hardened to false for watch-only use."
+                .to_string(),
+        ));
+    }
+    reject_parent(key_derivation, "Watch-only derivation")?;
+    let config = &key_derivation.derivation_config;
+    if config.application.is_some() || config.tenant.is_some() {
+        return Err(BipKeychainError::Bip32Error(
+            "Entity uses an application or tenant level, which is hardened; public keys \
+             require the private keychain"
+                .to_string(),
+        ));
+    }
+
+    let children = entity_children(key_derivation, parent_entropy)?;
+    public_keychain.derive_entity_public_key(&children)
+}
+
+/// Compute an entity's public key through a [`KeychainBackend`]
+///
+/// The entity is hashed locally and only the resulting index is handed to
+/// the backend, so the derivation root never has to be loaded into this
+/// process.
+///
+/// # Example
+///
+/// ```ignore
+/// let pubkey = derive_public_key_with_backend(&backend, &key_deriv, parent_entropy)?;
+/// ```
+pub fn derive_public_key_with_backend<B: KeychainBackend + ?Sized>(
+    backend: &B,
+    key_derivation: &KeyDerivation,
+    parent_entropy: &[u8],
+) -> Result<[u8; 33]> {
+    let config = &key_derivation.derivation_config;
+    if !config.hardened
+        || config.index_levels > 1
+        || config.application.is_some()
+        || config.tenant.is_some()
+        || config.key_index.is_some()
+    {
+        return Err(BipKeychainError::Bip32Error(
+            "Backends derive hardened single-level entity paths without application, \
+             tenant, or key index levels only"
+                .to_string(),
+        ));
+    }
+    reject_parent(key_derivation, "Backend derivation")?;
+
+    let index = entity_index(key_derivation, parent_entropy)?;
+    backend.public_key(index)
+}
+
+/// Full BIP-32 derivation path for an entity
+///
+/// Returns the exact path used by [`derive_key_from_entity`], i.e.
+/// `m/83696968'/67797668'/{index}'` with the same child-number mapping as
+/// [`Keychain::derive_bip_keychain_path`], plus further components when
+/// `index_levels` is greater than 1, `{application}'` and `{tenant}'`
+/// components before the index, and a `{key_index}` component after it when
+/// those are set. External signers (e.g. hardware
+/// wallets) can derive this path on-device while the host only performs
+/// entity hashing and index computation.
+///
+/// # Example
+///
+/// ```ignore
+/// let path = entity_derivation_path(&key_deriv, parent_entropy)?;
+/// println!("{}", path); // m/83696968'/67797668'/1234567'
+/// ```
+pub fn entity_derivation_path(
+    key_derivation: &KeyDerivation,
+    parent_entropy: &[u8],
+) -> Result<DerivationPath> {
+    reject_parent(key_derivation, "Path computation")?;
+
+    let mut path = DerivationPath::default();
+    path.push(ChildNumber(BIP85_APP | ChildNumber::HARDENED_FLAG));
+    path.push(ChildNumber(BIPKEYCHAIN_APP | ChildNumber::HARDENED_FLAG));
+    for child in entity_children(key_derivation, parent_entropy)? {
+        path.push(child);
+    }
+
+    Ok(path)
+}
+
+/// Compute the BIP-32 child index for an entity
+///
+/// Canonicalizes the entity JSON, hashes it with the configured hash
+/// function, applies the configured index expansion (if any), and extracts
+/// the first 4 bytes as a big-endian u32.
+pub fn entity_index(key_derivation: &KeyDerivation, parent_entropy: &[u8]) -> Result<u32> {
+    let hash_output = entity_hash(key_derivation, parent_entropy)?;
+
+    // Extract first 4 bytes as big-endian u32 for BIP-32 child index
+    hash_to_index(&hash_output)
+}
+
+/// Compute the entity-level indices for an entity, one per `index_levels`
+///
+/// Level `n` is read from hash bytes `4n..4n+4` (big-endian), so the first
+/// element always equals [`entity_index`].
+pub fn entity_indices(key_derivation: &KeyDerivation, parent_entropy: &[u8]) -> Result<Vec<u32>> {
+    let hash_output = entity_hash(key_derivation, parent_entropy)?;
+    Ok(hash_indices(
+        &hash_output,
+        key_derivation.derivation_config.index_levels,
+    ))
+}
+
+/// The first `levels` big-endian u32s of an entity hash
+fn hash_indices(hash_output: &[u8; 64], levels: u8) -> Vec<u32> {
+    hash_output
+        .chunks_exact(4)
+        .take(levels as usize)
+        .map(|bytes| u32::from_be_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+        .collect()
+}
+
+/// Path index of a tenant identifier
+///
+/// The first 4 bytes (big-endian) of HMAC-SHA512 keyed with
+/// [`TENANT_HMAC_KEY`] over the UTF-8 identifier, clamped below 2^31. The
+/// tenant level is always hardened and does not depend on parent entropy,
+/// so a tenant's subtree is the same for all of its entities.
+pub fn tenant_index(tenant: &str) -> Result<u32> {
+    let hash = hash_bytes(tenant.as_bytes(), TENANT_HMAC_KEY, HashFunction::HmacSha512)?;
+    Ok(hash_to_index(&hash)? & !ChildNumber::HARDENED_FLAG)
+}
+
+/// Entity-level child numbers below m/83696968'/67797668', preceded by the
+/// hardened application and tenant levels and followed by the key index if
+/// the entity has them
+fn entity_children(
+    key_derivation: &KeyDerivation,
+    parent_entropy: &[u8],
+) -> Result<Vec<ChildNumber>> {
+    let hash_output = entity_hash(key_derivation, parent_entropy)?;
+    hash_children(&key_derivation.derivation_config, &hash_output)
+}
+
+/// Child numbers for an entity hash under the path settings of `config`
+fn hash_children(config: &DerivationConfig, hash_output: &[u8; 64]) -> Result<Vec<ChildNumber>> {
+    let indices = hash_indices(hash_output, config.index_levels);
+
+    let mut children = Vec::new();
+    if let Some(application) = config.application {
+        children.push(child_number("application", application, true)?);
+    }
+    if let Some(tenant) = &config.tenant {
+        children.push(ChildNumber(
+            tenant_index(tenant)? | ChildNumber::HARDENED_FLAG,
+        ));
+    }
+
+    if !config.hardened {
+        // Non-hardened children must be below 2^31; keep the low 31 bits
+        children.extend(
+            indices
+                .into_iter()
+                .map(|index| ChildNumber(index & !ChildNumber::HARDENED_FLAG)),
+        );
+    } else if let [index] = indices[..] {
+        // Single-level paths keep the mapping of derive_bip_keychain_path
+        children.push(ChildNumber(index.wrapping_add(ChildNumber::HARDENED_FLAG)));
+    } else {
+        // Multi-level paths use 31 bits per level so every component is hardened
+        children.extend(
+            indices
+                .into_iter()
+                .map(|index| ChildNumber(index | ChildNumber::HARDENED_FLAG)),
+        );
+    }
+
+    if let Some(key_index) = config.key_index {
+        children.push(child_number("key_index", key_index, config.hardened)?);
+    }
+
+    Ok(children)
+}
+
+/// Child number for a configured path level, which must be below 2^31
+fn child_number(field: &str, index: u32, hardened: bool) -> Result<ChildNumber> {
+    ChildNumber::new(index, hardened).map_err(|_| {
+        BipKeychainError::Bip32Error(format!("{} must be below 2^31, got {}", field, index))
+    })
+}
+
+/// Compute the 64-byte hash of an entity, including any index expansion
+///
+/// The index is read from this hash. To name the entity as another
+/// entity's `parent` without including it, use [`parent_reference`]: the
+/// bare hash only stands in for entities with the default path settings.
+pub fn entity_hash(key_derivation: &KeyDerivation, parent_entropy: &[u8]) -> Result<[u8; 64]> {
+    // Step 1: Select hash function based on config
     let hash_function = match key_derivation.derivation_config.hash_function {
         HashFunctionConfig::HmacSha512 => HashFunction::HmacSha512,
         HashFunctionConfig::Blake2b => HashFunction::Blake2b,
         HashFunctionConfig::Sha256 => HashFunction::Sha256,
+        HashFunctionConfig::Blake3 => HashFunction::Blake3,
+        HashFunctionConfig::Argon2id => HashFunction::Argon2id,
     };
 
-    // Step 3: Hash the entity JSON
-    let hash_output = hash_entity(&entity_json, parent_entropy, hash_function)?;
+    // Step 2: Hash the entity in its configured canonical form, salted if
+    // the entity sets a salt
+    let input = key_derivation.hash_input()?;
+    let mut hash_output = hash_bytes(&input, parent_entropy, hash_function)?;
 
-    // Step 4: Extract first 4 bytes as big-endian u32 for BIP-32 child index
-    let index = hash_to_index(&hash_output)?;
-
-    // Step 5: Derive BIP-32 key at BIP-Keychain path with entity-specific index
-    let derived_key = keychain.derive_bip_keychain_path(index)?;
+    // Step 3: Optionally expand the hash for domain separation
+    if let Some(IndexExpansionConfig::HkdfSha512 { info }) =
+        &key_derivation.derivation_config.index_expansion
+    {
+        hash_output = hkdf_sha512_expand(&hash_output, info.as_bytes())?;
+    }
 
-    Ok(derived_key)
+    Ok(hash_output)
 }
 
 /// Convert hash output to BIP-32 child index
@@ -183,11 +576,444 @@ mod tests {
         assert_ne!(derived1.to_bytes(), derived2.to_bytes());
     }
 
+    #[test]
+    fn test_entity_derivation_path_matches_derivation() {
+        let entity_json = r#"{
+  "schema_type": "schema_org",
+  "entity": {"@type": "Thing", "name": "Test"},
+  "derivation_config": {"hash_function": "hmac_sha512", "hardened": true}
+}"#;
+
+        let mnemonic = "abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon about";
+        let parent_entropy = b"test_entropy";
+
+        let key_deriv = KeyDerivation::from_json(entity_json).unwrap();
+        let keychain = Keychain::from_mnemonic(mnemonic).unwrap();
+
+        let path = entity_derivation_path(&key_deriv, parent_entropy).unwrap();
+        assert!(path.to_string().starts_with("m/83696968'/67797668'/"));
+
+        let seed = bip39::Mnemonic::parse(mnemonic).unwrap().to_seed("");
+        let by_path = bip32::XPrv::derive_from_path(seed, &path).unwrap();
+        let derived = derive_key_from_entity(&keychain, &key_deriv, parent_entropy).unwrap();
+        assert_eq!(by_path.to_bytes().to_vec(), *derived.to_bytes());
+    }
+
+    #[test]
+    fn test_multi_level_path() {
+        let entity_json = r#"{
+  "schema_type": "schema_org",
+  "entity": {"@type": "Thing", "name": "Test"},
+  "derivation_config": {"hash_function": "hmac_sha512", "hardened": true, "index_levels": 3}
+}"#;
+
+        let mnemonic = "abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon about";
+        let parent_entropy = b"test_entropy";
+
+        let key_deriv = KeyDerivation::from_json(entity_json).unwrap();
+        let keychain = Keychain::from_mnemonic(mnemonic).unwrap();
+
+        let indices = entity_indices(&key_deriv, parent_entropy).unwrap();
+        assert_eq!(indices.len(), 3);
+        assert_eq!(
+            indices[0],
+            entity_index(&key_deriv, parent_entropy).unwrap()
+        );
+
+        let path = entity_derivation_path(&key_deriv, parent_entropy).unwrap();
+        let components: Vec<ChildNumber> = path.iter().collect();
+        assert_eq!(components.len(), 5);
+        assert!(components.iter().all(|child| child.is_hardened()));
+        assert_eq!(components[4].index(), indices[2] & 0x7fff_ffff);
+
+        let seed = bip39::Mnemonic::parse(mnemonic).unwrap().to_seed("");
+        let by_path = bip32::XPrv::derive_from_path(seed, &path).unwrap();
+        let derived = derive_key_from_entity(&keychain, &key_deriv, parent_entropy).unwrap();
+        assert_eq!(by_path.to_bytes().to_vec(), *derived.to_bytes());
+
+        // Single-level derivation of the same entity is a different key
+        let mut single = key_deriv.clone();
+        single.derivation_config.index_levels = 1;
+        let single_key = derive_key_from_entity(&keychain, &single, parent_entropy).unwrap();
+        assert_ne!(single_key.to_bytes(), derived.to_bytes());
+    }
+
+    #[test]
+    fn test_application_level() {
+        let entity_json = r#"{
+  "schema_type": "schema_org",
+  "entity": {"@type": "Thing", "name": "Test"},
+  "derivation_config": {"hash_function": "hmac_sha512", "hardened": true, "application": 7}
+}"#;
+
+        let mnemonic = "abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon about";
+        let parent_entropy = b"test_entropy";
+
+        let key_deriv = KeyDerivation::from_json(entity_json).unwrap();
+        let keychain = Keychain::from_mnemonic(mnemonic).unwrap();
+        let index = entity_index(&key_deriv, parent_entropy).unwrap();
+
+        let path = entity_derivation_path(&key_deriv, parent_entropy).unwrap();
+        let components: Vec<ChildNumber> = path.iter().collect();
+        assert_eq!(components.len(), 4);
+        assert_eq!(components[2], ChildNumber::new(7, true).unwrap());
+        assert_eq!(components[3], ChildNumber(index.wrapping_add(1 << 31)));
+
+        let seed = bip39::Mnemonic::parse(mnemonic).unwrap().to_seed("");
+        let by_path = bip32::XPrv::derive_from_path(seed, &path).unwrap();
+        let derived = derive_key_from_entity(&keychain, &key_deriv, parent_entropy).unwrap();
+        assert_eq!(by_path.to_bytes().to_vec(), *derived.to_bytes());
+
+        // Each application is its own namespace
+        let mut other = key_deriv.clone();
+        other.derivation_config.application = Some(8);
+        let other_key = derive_key_from_entity(&keychain, &other, parent_entropy).unwrap();
+        assert_ne!(other_key.to_bytes(), derived.to_bytes());
+        other.derivation_config.application = None;
+        let plain_key = derive_key_from_entity(&keychain, &other, parent_entropy).unwrap();
+        assert_ne!(plain_key.to_bytes(), derived.to_bytes());
+
+        // The hardened application level cannot be crossed from an xpub
+        let mut unhardened = key_deriv;
+        unhardened.derivation_config.hardened = false;
+        let watch_only = keychain.public_keychain().unwrap();
+        assert!(derive_public_key_from_entity(&watch_only, &unhardened, parent_entropy).is_err());
+    }
+
+    #[test]
+    fn test_tenant_level() {
+        let entity_json = r#"{
+  "schema_type": "schema_org",
+  "entity": {"@type": "Thing", "name": "Test"},
+  "derivation_config": {"hash_function": "hmac_sha512", "hardened": true, "application": 7, "tenant": "acme"}
+}"#;
+
+        let mnemonic = "abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon about";
+        let parent_entropy = b"test_entropy";
+
+        let key_deriv = KeyDerivation::from_json(entity_json).unwrap();
+        let keychain = Keychain::from_mnemonic(mnemonic).unwrap();
+
+        // HMAC-SHA512("bip-keychain-tenant", "acme"), first 4 bytes, low 31 bits
+        let tenant = tenant_index("acme").unwrap();
+        assert_eq!(tenant, 0x7444_361f);
+
+        let path = entity_derivation_path(&key_deriv, parent_entropy).unwrap();
+        let components: Vec<ChildNumber> = path.iter().collect();
+        assert_eq!(components.len(), 5);
+        assert_eq!(components[2], ChildNumber::new(7, true).unwrap());
+        assert_eq!(components[3], ChildNumber::new(tenant, true).unwrap());
+
+        let seed = bip39::Mnemonic::parse(mnemonic).unwrap().to_seed("");
+        let by_path = bip32::XPrv::derive_from_path(seed, &path).unwrap();
+        let derived = derive_key_from_entity(&keychain, &key_deriv, parent_entropy).unwrap();
+        assert_eq!(by_path.to_bytes().to_vec(), *derived.to_bytes());
+
+        // The same entity under another tenant is a different key
+        let mut other = key_deriv.clone();
+        other.derivation_config.tenant = Some("globex".to_string());
+        let other_key = derive_key_from_entity(&keychain, &other, parent_entropy).unwrap();
+        assert_ne!(other_key.to_bytes(), derived.to_bytes());
+    }
+
+    #[test]
+    fn test_key_index_level() {
+        let entity_json = r#"{
+  "schema_type": "schema_org",
+  "entity": {"@type": "Thing", "name": "Test"},
+  "derivation_config": {"hash_function": "hmac_sha512", "hardened": false, "key_index": 1}
+}"#;
+
+        let mnemonic = "abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon about";
+        let parent_entropy = b"test_entropy";
+
+        let key_deriv = KeyDerivation::from_json(entity_json).unwrap();
+        let keychain = Keychain::from_mnemonic(mnemonic).unwrap();
+
+        let path = entity_derivation_path(&key_deriv, parent_entropy).unwrap();
+        let components: Vec<ChildNumber> = path.iter().collect();
+        assert_eq!(components.len(), 4);
+        assert_eq!(components[3], ChildNumber(1));
+
+        // Non-hardened key indices stay reachable from the xpub
+        let watch_only = keychain.public_keychain().unwrap();
+        let pubkey =
+            derive_public_key_from_entity(&watch_only, &key_deriv, parent_entropy).unwrap();
+        let derived = derive_key_from_entity(&keychain, &key_deriv, parent_entropy).unwrap();
+        assert_eq!(pubkey, derived.secp256k1_public_key());
+
+        // Each key index is a different key, and differs from no key index
+        let mut keys = Vec::new();
+        for key_index in [None, Some(0), Some(1), Some(2)] {
+            let mut kd = key_deriv.clone();
+            kd.derivation_config.hardened = true;
+            kd.derivation_config.key_index = key_index;
+            let path = entity_derivation_path(&kd, parent_entropy).unwrap();
+            if let Some(key_index) = key_index {
+                assert_eq!(
+                    path.iter().last().unwrap(),
+                    ChildNumber::new(key_index, true).unwrap()
+                );
+            }
+            keys.push(
+                derive_key_from_entity(&keychain, &kd, parent_entropy)
+                    .unwrap()
+                    .to_bytes(),
+            );
+        }
+        for (i, key) in keys.iter().enumerate() {
+            assert!(keys[i + 1..].iter().all(|other| other != key));
+        }
+    }
+
+    #[test]
+    fn test_chained_derivation() {
+        let entity = |name: &str| {
+            KeyDerivation::from_json(&format!(
+                r#"{{
+  "schema_type": "schema_org",
+  "entity": {{"@type": "Organization", "name": "{}"}},
+  "derivation_config": {{"hash_function": "hmac_sha512", "hardened": true}}
+}}"#,
+                name
+            ))
+            .unwrap()
+        };
+
+        let mnemonic = "abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon about";
+        let parent_entropy = b"test_entropy";
+        let keychain = Keychain::from_mnemonic(mnemonic).unwrap();
+
+        let org = derive_key_from_entity(&keychain, &entity("Acme"), parent_entropy).unwrap();
+        let project = derive_child_entity(&org, &entity("Rocket")).unwrap();
+        let service = derive_child_entity(&project, &entity("Telemetry")).unwrap();
+
+        // The service key sits three entity levels below the application key
+        let mut path = entity_derivation_path(&entity("Acme"), parent_entropy).unwrap();
+        let project_index = entity_index(&entity("Rocket"), &org.to_seed()[..]).unwrap();
+        let service_index = entity_index(&entity("Telemetry"), &project.to_seed()[..]).unwrap();
+        path.push(ChildNumber(
+            project_index.wrapping_add(ChildNumber::HARDENED_FLAG),
+        ));
+        path.push(ChildNumber(
+            service_index.wrapping_add(ChildNumber::HARDENED_FLAG),
+        ));
+
+        let seed = bip39::Mnemonic::parse(mnemonic).unwrap().to_seed("");
+        let by_path = bip32::XPrv::derive_from_path(seed, &path).unwrap();
+        assert_eq!(by_path.to_bytes().to_vec(), *service.to_bytes());
+
+        // The same sub-entity under a different parent gets a different key
+        let other_org =
+            derive_key_from_entity(&keychain, &entity("Globex"), parent_entropy).unwrap();
+        let other_project = derive_child_entity(&other_org, &entity("Rocket")).unwrap();
+        assert_ne!(project.to_bytes(), other_project.to_bytes());
+    }
+
+    #[test]
+    fn test_parent_field_resolution() {
+        let parent_json = r#"{
+  "schema_type": "schema_org",
+  "entity": {"@type": "Organization", "name": "Acme"},
+  "derivation_config": {"hash_function": "hmac_sha512", "hardened": true}
+}"#;
+        let child_with_parent = |parent: &str| {
+            KeyDerivation::from_json(&format!(
+                r#"{{
+  "schema_type": "schema_org",
+  "entity": {{"@type": "Project", "name": "Rocket"}},
+  "derivation_config": {{"hash_function": "hmac_sha512", "hardened": true}},
+  "parent": {}
+}}"#,
+                parent
+            ))
+            .unwrap()
+        };
+
+        let mnemonic = "abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon about";
+        let parent_entropy = b"test_entropy";
+        let keychain = Keychain::from_mnemonic(mnemonic).unwrap();
+
+        let parent = KeyDerivation::from_json(parent_json).unwrap();
+        let parent_key = derive_key_from_entity(&keychain, &parent, parent_entropy).unwrap();
+        let inline = child_with_parent(parent_json);
+        let expected = derive_child_entity(&parent_key, &inline).unwrap();
+
+        let derived = derive_key_from_entity(&keychain, &inline, parent_entropy).unwrap();
+        assert_eq!(derived.to_bytes(), expected.to_bytes());
+
+        // A parent given by hash resolves to the same key
+        let hash = hex::encode(entity_hash(&parent, parent_entropy).unwrap());
+        let by_hash = child_with_parent(&format!("\"{}\"", hash));
+        let derived = derive_key_from_entity(&keychain, &by_hash, parent_entropy).unwrap();
+        assert_eq!(derived.to_bytes(), expected.to_bytes());
+
+        assert!(entity_derivation_path(&inline, parent_entropy).is_err());
+        assert!(
+            derive_key_from_entity(&keychain, &child_with_parent("\"abcd\""), parent_entropy)
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn test_parent_reference_matches_inline_parent() {
+        let mnemonic = "abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon about";
+        let parent_entropy = b"test_entropy";
+        let keychain = Keychain::from_mnemonic(mnemonic).unwrap();
+
+        let org = r#"{
+  "schema_type": "schema_org",
+  "entity": {"@type": "Organization", "name": "Acme"},
+  "derivation_config": {"hash_function": "hmac_sha512", "hardened": true, "tenant": "acme"}
+}"#;
+        let project = format!(
+            r#"{{
+  "schema_type": "schema_org",
+  "entity": {{"@type": "Project", "name": "Rocket"}},
+  "derivation_config": {{
+    "hash_function": "hmac_sha512",
+    "hardened": false,
+    "application": 7,
+    "index_levels": 2,
+    "key_index": 1,
+    "key_entropy": "bip85"
+  }},
+  "parent": {}
+}}"#,
+            org
+        );
+        let child_with_parent = |parent: &str| {
+            KeyDerivation::from_json(&format!(
+                r#"{{
+  "schema_type": "schema_org",
+  "entity": {{"@type": "SoftwareApplication", "name": "telemetry"}},
+  "derivation_config": {{"hash_function": "hmac_sha512", "hardened": true}},
+  "parent": {}
+}}"#,
+                parent
+            ))
+            .unwrap()
+        };
+
+        let inline =
+            derive_key_from_entity(&keychain, &child_with_parent(&project), parent_entropy)
+                .unwrap();
+
+        let reference = parent_reference(
+            &keychain,
+            &KeyDerivation::from_json(&project).unwrap(),
+            parent_entropy,
+        )
+        .unwrap();
+        assert!(matches!(reference, EntityParent::Reference(_)));
+        let reference_json = serde_json::to_string(&reference).unwrap();
+        let by_reference = child_with_parent(&reference_json);
+        assert!(matches!(
+            by_reference.parent,
+            Some(EntityParent::Reference(_))
+        ));
+        let derived = derive_key_from_entity(&keychain, &by_reference, parent_entropy).unwrap();
+        assert_eq!(derived.to_bytes(), inline.to_bytes());
+        assert_eq!(
+            derived.secp256k1_public_key(),
+            inline.secp256k1_public_key()
+        );
+
+        // The bare hash would name a different key, so it is not offered
+        let hash_only = match &reference {
+            EntityParent::Reference(reference) => reference.hash.clone(),
+            other => panic!("expected a reference, got {:?}", other),
+        };
+        let by_hash = child_with_parent(&format!("\"{}\"", hash_only));
+        let derived = derive_key_from_entity(&keychain, &by_hash, parent_entropy).unwrap();
+        assert_ne!(derived.to_bytes(), inline.to_bytes());
+
+        // Parents with the default path settings are referenced by hash alone
+        let plain = r#"{
+  "schema_type": "schema_org",
+  "entity": {"@type": "Organization", "name": "Acme"},
+  "derivation_config": {"hash_function": "hmac_sha512", "hardened": true}
+}"#;
+        let reference = parent_reference(
+            &keychain,
+            &KeyDerivation::from_json(plain).unwrap(),
+            parent_entropy,
+        )
+        .unwrap();
+        let EntityParent::Hash(hash) = reference else {
+            panic!("expected a bare hash, got {:?}", reference);
+        };
+        assert_eq!(
+            derive_key_from_entity(
+                &keychain,
+                &child_with_parent(&format!("\"{}\"", hash)),
+                parent_entropy
+            )
+            .unwrap()
+            .to_bytes(),
+            derive_key_from_entity(&keychain, &child_with_parent(plain), parent_entropy)
+                .unwrap()
+                .to_bytes()
+        );
+    }
+
+    #[test]
+    fn test_public_derivation_requires_non_hardened() {
+        let hardened = r#"{
+  "schema_type": "schema_org",
+  "entity": {"@type": "Thing", "name": "Test"},
+  "derivation_config": {"hash_function": "hmac_sha512", "hardened": true}
+}"#;
+        let unhardened = hardened.replace(r#""hardened": true"#, r#""hardened": false"#);
+
+        let mnemonic = "abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon about";
+        let parent_entropy = b"test_entropy";
+
+        let keychain = Keychain::from_mnemonic(mnemonic).unwrap();
+        let watch_only = keychain.public_keychain().unwrap();
+
+        let kd = KeyDerivation::from_json(hardened).unwrap();
+        assert!(derive_public_key_from_entity(&watch_only, &kd, parent_entropy).is_err());
+
+        let kd = KeyDerivation::from_json(&unhardened).unwrap();
+        let pubkey = derive_public_key_from_entity(&watch_only, &kd, parent_entropy).unwrap();
+
+        let index = entity_index(&kd, parent_entropy).unwrap() & 0x7fff_ffff;
+        let derived = keychain.derive_bip_keychain_path_unhardened(index).unwrap();
+        assert_eq!(pubkey, derived.secp256k1_public_key());
+
+        // The private derivation honors hardened: false
+        let private = derive_key_from_entity(&keychain, &kd, parent_entropy).unwrap();
+        assert_eq!(pubkey, private.secp256k1_public_key());
+        let path = entity_derivation_path(&kd, parent_entropy).unwrap();
+        assert_eq!(path.iter().last().unwrap(), ChildNumber(index));
+    }
+
+    #[test]
+    fn test_watch_only_multi_level() {
+        let entity_json = r#"{
+  "schema_type": "schema_org",
+  "entity": {"@type": "Thing", "name": "Test"},
+  "derivation_config": {"hash_function": "hmac_sha512", "hardened": false, "index_levels": 2}
+}"#;
+
+        let mnemonic = "abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon about";
+        let parent_entropy = b"test_entropy";
+
+        let kd = KeyDerivation::from_json(entity_json).unwrap();
+        let keychain = Keychain::from_mnemonic(mnemonic).unwrap();
+        let watch_only = keychain.public_keychain().unwrap();
+
+        let pubkey = derive_public_key_from_entity(&watch_only, &kd, parent_entropy).unwrap();
+        let private = derive_key_from_entity(&keychain, &kd, parent_entropy).unwrap();
+        assert_eq!(pubkey, private.secp256k1_public_key());
+    }
+
     #[test]
     fn test_blake2b_derivation() {
         let entity_json = r#"{
   "schema_type": "gordian_envelope",
-  "entity": {"envelope": "ur:envelope/example"},
+  "entity": {"envelope": "ur:envelope/tpsoiyfdihjzjzjldmksbaoede"},
   "derivation_config": {"hash_function": "blake2b", "hardened": true}
 }"#;
 
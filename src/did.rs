@@ -0,0 +1,245 @@
+//! Decentralized Identifier (DID) parsing and validation
+//!
+//! Parses DIDs per the W3C DID Core syntax:
+//!
+//! ```text
+//! did                = "did:" method-name ":" method-specific-id
+//! method-name        = 1*method-char
+//! method-char        = %x61-7A / DIGIT
+//! method-specific-id = *( *idchar ":" ) 1*idchar
+//! idchar             = ALPHA / DIGIT / "." / "-" / "_" / pct-encoded
+//! ```
+//!
+//! DID URLs (with a path, query, or fragment) are not DIDs and are
+//! rejected. A few well-known methods get extra checks on their
+//! method-specific identifier; other methods only need valid syntax.
+
+use crate::error::{BipKeychainError, Result};
+use std::fmt;
+use std::str::FromStr;
+
+/// A parsed DID
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Did {
+    /// DID method, e.g. `web`
+    pub method: String,
+    /// Method-specific identifier, e.g. `example.com:alice`
+    pub method_specific_id: String,
+}
+
+fn invalid(did: &str, reason: impl fmt::Display) -> BipKeychainError {
+    BipKeychainError::InvalidEntity(serde::de::Error::custom(format!(
+        "invalid DID '{}': {}",
+        did, reason
+    )))
+}
+
+impl Did {
+    /// Parse and validate a DID, including method-specific rules
+    pub fn parse(did: &str) -> Result<Self> {
+        let rest = did
+            .strip_prefix("did:")
+            .ok_or_else(|| invalid(did, "must start with 'did:'"))?;
+        let (method, id) = rest
+            .split_once(':')
+            .ok_or_else(|| invalid(did, "missing method-specific identifier"))?;
+
+        if method.is_empty() {
+            return Err(invalid(did, "empty method name"));
+        }
+        if let Some(c) = method
+            .chars()
+            .find(|c| !c.is_ascii_lowercase() && !c.is_ascii_digit())
+        {
+            return Err(invalid(
+                did,
+                format!("method name may only contain a-z and 0-9, found '{}'", c),
+            ));
+        }
+
+        validate_idchars(id).map_err(|reason| invalid(did, reason))?;
+        validate_method_specific_id(method, id).map_err(|reason| invalid(did, reason))?;
+
+        Ok(Self {
+            method: method.to_string(),
+            method_specific_id: id.to_string(),
+        })
+    }
+
+    /// `did` with its method name lowercased, leaving the identifier as-is
+    ///
+    /// Strings that don't look like a DID are returned unchanged so that
+    /// [`Did::parse`] reports the error.
+    pub fn lowercase_method(did: &str) -> String {
+        match did
+            .strip_prefix("did:")
+            .and_then(|rest| rest.split_once(':'))
+        {
+            Some((method, id)) => format!("did:{}:{}", method.to_ascii_lowercase(), id),
+            None => did.to_string(),
+        }
+    }
+}
+
+impl FromStr for Did {
+    type Err = BipKeychainError;
+
+    fn from_str(s: &str) -> Result<Self> {
+        Self::parse(s)
+    }
+}
+
+impl fmt::Display for Did {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "did:{}:{}", self.method, self.method_specific_id)
+    }
+}
+
+/// Check the generic `method-specific-id` grammar
+fn validate_idchars(id: &str) -> std::result::Result<(), String> {
+    if id.is_empty() || id.ends_with(':') {
+        return Err("method-specific identifier must not be empty or end with ':'".into());
+    }
+
+    let bytes = id.as_bytes();
+    let mut i = 0;
+    while i < bytes.len() {
+        match bytes[i] {
+            b'%' => {
+                let valid = bytes.len() > i + 2
+                    && bytes[i + 1].is_ascii_hexdigit()
+                    && bytes[i + 2].is_ascii_hexdigit();
+                if !valid {
+                    return Err("invalid percent-encoding".into());
+                }
+                i += 3;
+            }
+            b if b.is_ascii_alphanumeric() || matches!(b, b'.' | b'-' | b'_' | b':') => i += 1,
+            _ => {
+                let c = id[i..].chars().next().unwrap_or_default();
+                return Err(format!(
+                    "character '{}' is not allowed in a DID (DID URLs are not accepted)",
+                    c
+                ));
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Extra rules for well-known methods
+fn validate_method_specific_id(method: &str, id: &str) -> std::result::Result<(), String> {
+    match method {
+        // Multibase base58btc-encoded multicodec public key
+        "key" => {
+            let encoded = id
+                .strip_prefix('z')
+                .ok_or("did:key identifiers must be base58btc multibase ('z' prefix)")?;
+            if encoded.is_empty() || !encoded.chars().all(is_base58_char) {
+                return Err("did:key identifier is not valid base58btc".into());
+            }
+            Ok(())
+        }
+        // Domain name, optionally followed by ':'-separated path segments
+        "web" => {
+            let host = id.split(':').next().unwrap_or_default();
+            let host = host.replace("%3A", ":").replace("%3a", ":");
+            let domain = host.split(':').next().unwrap_or_default();
+            let valid = !domain.is_empty()
+                && domain.split('.').all(|label| {
+                    !label.is_empty()
+                        && !label.starts_with('-')
+                        && !label.ends_with('-')
+                        && label.chars().all(|c| c.is_ascii_alphanumeric() || c == '-')
+                });
+            if !valid {
+                return Err(format!(
+                    "did:web host '{}' is not a valid domain name",
+                    host
+                ));
+            }
+            Ok(())
+        }
+        // Optional network prefix, then an Ethereum address or public key
+        "ethr" => {
+            let key = id.rsplit(':').next().unwrap_or_default();
+            let hex = key.strip_prefix("0x").unwrap_or("");
+            if !matches!(hex.len(), 40 | 66) || !hex.chars().all(|c| c.is_ascii_hexdigit()) {
+                return Err("did:ethr identifiers must end in a 0x-prefixed address".into());
+            }
+            Ok(())
+        }
+        // GitHub usernames: 1-39 alphanumerics or single hyphens
+        "github" => {
+            let valid = (1..=39).contains(&id.len())
+                && !id.starts_with('-')
+                && !id.ends_with('-')
+                && !id.contains("--")
+                && id.chars().all(|c| c.is_ascii_alphanumeric() || c == '-');
+            if !valid {
+                return Err(format!("'{}' is not a valid GitHub username", id));
+            }
+            Ok(())
+        }
+        _ => Ok(()),
+    }
+}
+
+fn is_base58_char(c: char) -> bool {
+    c.is_ascii_alphanumeric() && !matches!(c, '0' | 'O' | 'I' | 'l')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_valid_dids() {
+        let did = Did::parse("did:web:example.com:users:alice").unwrap();
+        assert_eq!(did.method, "web");
+        assert_eq!(did.method_specific_id, "example.com:users:alice");
+        assert_eq!(did.to_string(), "did:web:example.com:users:alice");
+
+        for valid in [
+            "did:github:lessuselesss",
+            "did:key:z6MkhaXgBZDvotDkL5257faiztiGiC2QtKLGpbnnEGta2doK",
+            "did:web:localhost%3A8443",
+            "did:ethr:0x5:0xb9c5714089478a327f09197987f16f9e5d936e8a",
+            "did:example:123456789abcdefghi",
+            "did:example:a%20b",
+        ] {
+            assert!(Did::parse(valid).is_ok(), "rejected {}", valid);
+        }
+    }
+
+    #[test]
+    fn test_parse_rejects_malformed_dids() {
+        for malformed in [
+            "github:alice",
+            "did:github",
+            "did::alice",
+            "did:GitHub:alice",
+            "did:example:",
+            "did:example:abc:",
+            "did:example:a b",
+            "did:example:abc/path",
+            "did:example:abc#key-1",
+            "did:example:%zz",
+            "did:key:6Mkabc",
+            "did:web:-bad-.com",
+            "did:ethr:0x1234",
+            "did:github:-alice",
+        ] {
+            assert!(Did::parse(malformed).is_err(), "accepted {}", malformed);
+        }
+    }
+
+    #[test]
+    fn test_lowercase_method() {
+        assert_eq!(
+            Did::lowercase_method("did:GitHub:Alice"),
+            "did:github:Alice"
+        );
+        assert_eq!(Did::lowercase_method("not-a-did"), "not-a-did");
+    }
+}
@@ -0,0 +1,241 @@
+//! DNSSEC keys from derived keys
+//!
+//! Formats an entity's Ed25519 key as a DNSSEC key (algorithm 15,
+//! RFC 8080): the DNSKEY record for the zone, the DS record for the parent
+//! zone, and the private key file read by BIND's `dnssec-signzone`. A
+//! zone's signing keys can then be recreated from the seed at any time.
+//!
+//! The zone is taken from the entity's `zone` property, or from an
+//! optional `dnssec` object, which also chooses the key's role:
+//!
+//! ```json
+//! "dnssec": {"zone": "example.com", "role": "zsk"}
+//! ```
+
+use crate::error::{BipKeychainError, Result};
+use crate::output::Ed25519Keypair;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use zeroize::Zeroizing;
+
+/// DNSSEC algorithm number for Ed25519 (RFC 8080)
+const ALGORITHM_ED25519: u8 = 15;
+
+/// DNSKEY protocol field, always 3 (RFC 4034 section 2.1.2)
+const PROTOCOL: u8 = 3;
+
+/// DS digest type for SHA-256 (RFC 4509)
+const DIGEST_SHA256: u8 = 2;
+
+/// What a DNSSEC key signs
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum DnssecRole {
+    /// Key-signing key (flags 257, Secure Entry Point); referenced by DS
+    #[default]
+    Ksk,
+    /// Zone-signing key (flags 256)
+    Zsk,
+}
+
+impl DnssecRole {
+    /// DNSKEY flags field
+    pub fn flags(self) -> u16 {
+        match self {
+            DnssecRole::Ksk => 257,
+            DnssecRole::Zsk => 256,
+        }
+    }
+}
+
+/// Zone and role of a DNSSEC key
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct DnssecConfig {
+    /// Zone apex, e.g. `example.com` (default: the entity's `zone`)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub zone: Option<String>,
+    /// Key-signing or zone-signing key
+    pub role: DnssecRole,
+}
+
+fn dnssec_error(message: String) -> BipKeychainError {
+    BipKeychainError::OutputError(message)
+}
+
+/// An Ed25519 DNSSEC key for a zone
+pub struct DnssecKey {
+    zone: String,
+    flags: u16,
+    public_key: [u8; 32],
+    private_key: Zeroizing<[u8; 32]>,
+}
+
+impl DnssecKey {
+    /// Use `keypair` as the `role` key for `zone`
+    pub fn new(zone: &str, role: DnssecRole, keypair: &Ed25519Keypair) -> Result<Self> {
+        let zone = format!("{}.", zone.trim_end_matches('.').to_ascii_lowercase());
+        // Validates the name before any record is formatted
+        owner_wire_format(&zone)?;
+        Ok(Self {
+            zone,
+            flags: role.flags(),
+            public_key: keypair.public_key_bytes(),
+            private_key: keypair.private_key_bytes(),
+        })
+    }
+
+    /// Absolute zone name, with trailing dot
+    pub fn zone(&self) -> &str {
+        &self.zone
+    }
+
+    /// DNSKEY RDATA: flags, protocol, algorithm, public key
+    pub fn dnskey_rdata(&self) -> Vec<u8> {
+        let mut rdata = Vec::with_capacity(36);
+        rdata.extend_from_slice(&self.flags.to_be_bytes());
+        rdata.push(PROTOCOL);
+        rdata.push(ALGORITHM_ED25519);
+        rdata.extend_from_slice(&self.public_key);
+        rdata
+    }
+
+    /// Key tag (RFC 4034 Appendix B)
+    pub fn key_tag(&self) -> u16 {
+        let mut sum: u32 = self
+            .dnskey_rdata()
+            .iter()
+            .enumerate()
+            .map(|(i, &byte)| {
+                if i % 2 == 0 {
+                    (byte as u32) << 8
+                } else {
+                    byte as u32
+                }
+            })
+            .sum();
+        sum += sum >> 16;
+        sum as u16
+    }
+
+    /// DNSKEY record for the zone
+    pub fn dnskey_record(&self) -> String {
+        format!(
+            "{} IN DNSKEY {} {} {} {}",
+            self.zone,
+            self.flags,
+            PROTOCOL,
+            ALGORITHM_ED25519,
+            base64::Engine::encode(&base64::engine::general_purpose::STANDARD, self.public_key)
+        )
+    }
+
+    /// DS record (SHA-256) for the parent zone
+    pub fn ds_record(&self) -> String {
+        let mut hasher = Sha256::new();
+        hasher.update(owner_wire_format(&self.zone).expect("validated in new"));
+        hasher.update(self.dnskey_rdata());
+        format!(
+            "{} IN DS {} {} {} {}",
+            self.zone,
+            self.key_tag(),
+            ALGORITHM_ED25519,
+            DIGEST_SHA256,
+            hex::encode(hasher.finalize())
+        )
+    }
+
+    /// BIND private key file (`K<zone>+015+<tag>.private`)
+    pub fn bind_private_key(&self) -> Zeroizing<String> {
+        let encoded = Zeroizing::new(base64::Engine::encode(
+            &base64::engine::general_purpose::STANDARD,
+            *self.private_key,
+        ));
+        Zeroizing::new(format!(
+            "Private-key-format: v1.3\nAlgorithm: {} (ED25519)\nPrivateKey: {}\n",
+            ALGORITHM_ED25519, *encoded
+        ))
+    }
+
+    /// File name stem BIND expects for this key, e.g. `Kexample.com.+015+03613`
+    pub fn bind_file_stem(&self) -> String {
+        format!(
+            "K{}+{:03}+{:05}",
+            self.zone,
+            ALGORITHM_ED25519,
+            self.key_tag()
+        )
+    }
+}
+
+/// Canonical wire format of an absolute, lowercase domain name
+fn owner_wire_format(name: &str) -> Result<Vec<u8>> {
+    let labels: Vec<&str> = name.trim_end_matches('.').split('.').collect();
+    let mut wire = Vec::with_capacity(name.len() + 1);
+    for label in &labels {
+        if label.is_empty() || label.len() > 63 {
+            return Err(dnssec_error(format!(
+                "Invalid DNSSEC zone '{}': labels must be 1 to 63 characters",
+                name
+            )));
+        }
+        wire.push(label.len() as u8);
+        wire.extend_from_slice(label.as_bytes());
+    }
+    wire.push(0);
+    if wire.len() > 255 {
+        return Err(dnssec_error(format!(
+            "Invalid DNSSEC zone '{}': longer than 255 bytes",
+            name
+        )));
+    }
+    Ok(wire)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// RFC 8080 section 6.1 example key
+    fn rfc8080_key() -> DnssecKey {
+        let seed = base64::Engine::decode(
+            &base64::engine::general_purpose::STANDARD,
+            "ODIyNjAzODQ2MjgwODAxMjI2NDUxOTAyMDQxNDIyNjI=",
+        )
+        .unwrap();
+        let keypair = Ed25519Keypair::from_seed(seed.try_into().unwrap());
+        DnssecKey::new("Example.com", DnssecRole::Ksk, &keypair).unwrap()
+    }
+
+    #[test]
+    fn test_rfc8080_vectors() {
+        let key = rfc8080_key();
+        assert_eq!(
+            key.dnskey_record(),
+            "example.com. IN DNSKEY 257 3 15 l02Woi0iS8Aa25FQkUd9RMzZHJpBoRQwAQEX1SxZJA4="
+        );
+        assert_eq!(key.key_tag(), 3613);
+        assert_eq!(
+            key.ds_record(),
+            "example.com. IN DS 3613 15 2 \
+             3aa5ab37efce57f737fc1627013fee07bdf241bd10f3b1964ab55c78e79a304b"
+        );
+        assert_eq!(
+            *key.bind_private_key(),
+            "Private-key-format: v1.3\nAlgorithm: 15 (ED25519)\n\
+             PrivateKey: ODIyNjAzODQ2MjgwODAxMjI2NDUxOTAyMDQxNDIyNjI=\n"
+        );
+        assert_eq!(key.bind_file_stem(), "Kexample.com.+015+03613");
+    }
+
+    #[test]
+    fn test_zone_names() {
+        let keypair = Ed25519Keypair::from_seed([1u8; 32]);
+        let zsk = DnssecKey::new("example.com.", DnssecRole::Zsk, &keypair).unwrap();
+        assert_eq!(zsk.zone(), "example.com.");
+        assert!(zsk.dnskey_record().contains(" DNSKEY 256 3 15 "));
+
+        assert!(DnssecKey::new("example..com", DnssecRole::Ksk, &keypair).is_err());
+        assert!(DnssecKey::new(&"a".repeat(64), DnssecRole::Ksk, &keypair).is_err());
+    }
+}
@@ -1,12 +1,96 @@
 //! Entity type definitions for BIP-Keychain
 //!
 //! Parses Nickel-exported JSON into type-safe Rust structs.
-//! For MVP, we store the entity as a generic JSON value and will add
-//! type-safe parsing for specific schema types later.
+//! Entities for `schema_org`, `did`, and `gordian_envelope` are validated
+//! against their required fields at parse time; other schema types are
+//! kept as generic JSON.
 
-use crate::error::{BipKeychainError, Result};
+use crate::{
+    dcbor,
+    did::Did,
+    dnssec::DnssecConfig,
+    envelope::Envelope,
+    error::{BipKeychainError, Result},
+    frost::FrostConfig,
+    hash::{canonicalize_value, Canonicalization},
+    output::OutputFormat,
+    password::PasswordConfig,
+    tlsa::TlsaConfig,
+    totp::TotpConfig,
+    ur,
+};
 use serde::{Deserialize, Serialize};
-use serde_json::Value;
+use serde_json::{value::RawValue, Map, Value};
+
+/// UR type of entities encoded with [`KeyDerivation::to_ur_string`]
+pub const ENTITY_UR_TYPE: &str = "crypto-entity";
+
+/// Newest `crypto-entity` version
+///
+/// Decoders accept every version up to this one:
+/// - 0: the entity JSON as a dCBOR map with text keys
+/// - 1: [`ENTITY_UR_FIELDS`] keys, with the version under key 0 (maps
+///   without key 0 are version 1)
+/// - 2: adds the entity file `version` (key 12)
+/// - 3: adds `output_format` (key 13)
+///
+/// [`KeyDerivation::to_ur_string`] writes the oldest version that has keys
+/// for all of the entity's fields, so older decoders can read it.
+pub const ENTITY_UR_VERSION: u64 = 3;
+
+/// `crypto-entity` map key holding the version
+const ENTITY_UR_VERSION_KEY: u64 = 0;
+
+/// `crypto-entity` map keys, the entity JSON fields they carry, and the
+/// version that introduced them (see `spec/crypto-entity.md` for the CDDL)
+const ENTITY_UR_FIELDS: [(u64, &str, u64); 13] = [
+    (1, "schema_type", 1),
+    (2, "entity", 1),
+    (3, "derivation_config", 1),
+    (4, "purpose", 1),
+    (5, "metadata", 1),
+    (6, "parent", 1),
+    (7, "password", 1),
+    (8, "totp", 1),
+    (9, "dnssec", 1),
+    (10, "tlsa", 1),
+    (11, "frost", 1),
+    (12, "version", 2),
+    (13, "output_format", 3),
+];
+
+/// Entity file format version understood by this build
+///
+/// Files without a `version` are version 1. A change that would alter how
+/// existing files are read (a renamed field, a new default that affects
+/// hashing) bumps the version and adds a step to [`ENTITY_MIGRATIONS`] that
+/// rewrites older files into the new form, so their keys stay the same.
+///
+/// | Version | Change |
+/// |---------|--------|
+/// | 1 | Initial format |
+/// | 2 | Hash input starts with a domain tag (`derivation_config.domain_tag`) |
+pub const ENTITY_VERSION: u32 = 2;
+
+/// Migration steps; `ENTITY_MIGRATIONS[n]` upgrades a version `n + 1`
+/// entity file to version `n + 2`
+///
+/// Steps must leave `entity` and `parent` untouched: their source text is
+/// hashed as written for `canonicalization: none`.
+const ENTITY_MIGRATIONS: [fn(&mut Map<String, Value>); ENTITY_VERSION as usize - 1] =
+    [migrate_untagged];
+
+/// Version 1 → 2: version 1 hashed the entity without a domain tag
+fn migrate_untagged(value: &mut Map<String, Value>) {
+    if let Some(Value::Object(config)) = value.get_mut("derivation_config") {
+        config
+            .entry("domain_tag")
+            .or_insert_with(|| Value::from("none"));
+    }
+}
+
+/// Largest inflated `crypto-entity` payload accepted
+const MAX_ENTITY_CBOR: usize = 1 << 20;
 
 /// Hash function configuration for entity derivation
 #[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
@@ -18,6 +102,224 @@ pub enum HashFunctionConfig {
     Blake2b,
     #[serde(rename = "sha256")]
     Sha256,
+    #[serde(rename = "blake3")]
+    Blake3,
+    #[serde(rename = "argon2id")]
+    Argon2id,
+}
+
+/// Canonical byte encoding of the entity before hashing
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum CanonicalizationConfig {
+    /// Compact JSON with sorted keys (serde_json)
+    #[default]
+    Serde,
+    /// JSON Canonicalization Scheme (RFC 8785)
+    Jcs,
+    /// Deterministic CBOR (Blockchain Commons dCBOR)
+    Dcbor,
+    /// The entity text exactly as written in the source JSON
+    None,
+}
+
+/// Source of the 32-byte seed taken from the derived BIP-32 key
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum KeyEntropyConfig {
+    /// The derived private key bytes
+    #[default]
+    Raw,
+    /// BIP-85 entropy: HMAC-SHA512("bip-entropy-from-k", private key)
+    Bip85,
+}
+
+/// Domain tag starting the hash input (see [`KeyDerivation::hash_input`])
+///
+/// Tagging keeps the entity hash from ever equalling a hash computed by
+/// another protocol over the same JSON or CBOR bytes.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum DomainTagConfig {
+    /// `BIP-KEYCHAIN/v1`
+    #[default]
+    V1,
+    /// No tag: the hash input of version 1 entity files
+    None,
+}
+
+impl DomainTagConfig {
+    /// Bytes prefixed to the hash input
+    pub fn tag(self) -> &'static [u8] {
+        match self {
+            Self::V1 => b"BIP-KEYCHAIN/v1",
+            Self::None => b"",
+        }
+    }
+}
+
+/// Kind of key formatted from the derived seed (see [`crate::curve_key`])
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum KeyTypeConfig {
+    /// Ed25519 signing key (RFC 8032)
+    #[default]
+    Ed25519,
+    /// X25519 key agreement key, the Curve25519 form of the Ed25519 key
+    X25519,
+    /// secp256k1 key (Bitcoin, Ethereum, Nostr)
+    Secp256k1,
+    /// NIST P-256 key (WebAuthn, FIDO, PIV)
+    P256,
+}
+
+impl KeyTypeConfig {
+    /// The `key_type` name in entity files
+    pub fn name(self) -> &'static str {
+        match self {
+            KeyTypeConfig::Ed25519 => "ed25519",
+            KeyTypeConfig::X25519 => "x25519",
+            KeyTypeConfig::Secp256k1 => "secp256k1",
+            KeyTypeConfig::P256 => "p256",
+        }
+    }
+
+    /// Format used when neither the command line nor the entity picks one:
+    /// an SSH public key for Ed25519, else the public key as hex
+    pub fn default_output_format(self) -> OutputFormat {
+        match self {
+            KeyTypeConfig::Ed25519 => OutputFormat::SshPublicKey,
+            _ => OutputFormat::Ed25519PublicHex,
+        }
+    }
+}
+
+/// Expansion step applied to the entity hash before index extraction
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(tag = "type")]
+pub enum IndexExpansionConfig {
+    /// HKDF-SHA512 with the entity hash as input keying material
+    #[serde(rename = "hkdf_sha512")]
+    HkdfSha512 {
+        /// HKDF `info` string used for domain separation
+        #[serde(default = "default_hkdf_info")]
+        info: String,
+    },
+}
+
+/// Default HKDF `info` string for index expansion
+pub const DEFAULT_HKDF_INFO: &str = "bip-keychain/index";
+
+fn default_hkdf_info() -> String {
+    DEFAULT_HKDF_INFO.to_string()
+}
+
+/// Maximum number of entity-level path components
+pub const MAX_INDEX_LEVELS: u8 = 3;
+
+fn default_index_levels() -> u8 {
+    1
+}
+
+fn is_single_level(levels: &u8) -> bool {
+    *levels == 1
+}
+
+fn deserialize_index_levels<'de, D>(deserializer: D) -> std::result::Result<u8, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    let levels = u8::deserialize(deserializer)?;
+    if !(1..=MAX_INDEX_LEVELS).contains(&levels) {
+        return Err(serde::de::Error::custom(format!(
+            "index_levels must be between 1 and {}, got {}",
+            MAX_INDEX_LEVELS, levels
+        )));
+    }
+    Ok(levels)
+}
+
+fn deserialize_child_index<'de, D>(
+    deserializer: D,
+    field: &str,
+) -> std::result::Result<Option<u32>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    let index = u32::deserialize(deserializer)?;
+    if index >= 1 << 31 {
+        return Err(serde::de::Error::custom(format!(
+            "{} must be below 2^31, got {}",
+            field, index
+        )));
+    }
+    Ok(Some(index))
+}
+
+fn deserialize_application<'de, D>(deserializer: D) -> std::result::Result<Option<u32>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    deserialize_child_index(deserializer, "application")
+}
+
+fn deserialize_key_index<'de, D>(deserializer: D) -> std::result::Result<Option<u32>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    deserialize_child_index(deserializer, "key_index")
+}
+
+/// Deserialize an optional string setting that must not be empty
+fn deserialize_non_empty<'de, D>(
+    deserializer: D,
+    field: &str,
+) -> std::result::Result<Option<String>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    let value = String::deserialize(deserializer)?;
+    if value.is_empty() {
+        return Err(serde::de::Error::custom(format!(
+            "{} must not be empty",
+            field
+        )));
+    }
+    Ok(Some(value))
+}
+
+fn deserialize_tenant<'de, D>(deserializer: D) -> std::result::Result<Option<String>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    deserialize_non_empty(deserializer, "tenant")
+}
+
+fn deserialize_salt<'de, D>(deserializer: D) -> std::result::Result<Option<String>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    deserialize_non_empty(deserializer, "salt")
+}
+
+fn is_default_key_entropy(value: &KeyEntropyConfig) -> bool {
+    *value == KeyEntropyConfig::default()
+}
+
+fn is_default_key_type(value: &KeyTypeConfig) -> bool {
+    *value == KeyTypeConfig::default()
+}
+
+fn is_default_domain_tag(value: &DomainTagConfig) -> bool {
+    *value == DomainTagConfig::default()
+}
+
+fn is_default_canonicalization(value: &CanonicalizationConfig) -> bool {
+    *value == CanonicalizationConfig::default()
+}
+
+fn is_false(value: &bool) -> bool {
+    !*value
 }
 
 /// Derivation configuration
@@ -28,6 +330,726 @@ pub struct DerivationConfig {
 
     /// Whether to use hardened derivation (default: true)
     pub hardened: bool,
+
+    /// Encoding of the entity before hashing (default: serde)
+    #[serde(default, skip_serializing_if = "is_default_canonicalization")]
+    pub canonicalization: CanonicalizationConfig,
+
+    /// JSON pointers (RFC 6901) to entity fields left out of the hash
+    ///
+    /// Lets non-semantic fields such as `/dateModified` or `/comment` change
+    /// without changing the key. The sorted list itself is hashed along with
+    /// the pruned entity (see [`KeyDerivation::canonical_entity`]), so
+    /// entities that differ only in their exclusions get different keys.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub hash_exclude: Vec<String>,
+
+    /// JSON pointers to the only entity fields that are hashed
+    ///
+    /// The allowlist counterpart of `hash_exclude` (the two cannot be
+    /// combined): it states which fields carry the entity's identity, and
+    /// every other field can change freely. The fields must be present, and
+    /// the sorted list is hashed along with them.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub hash_include: Vec<String>,
+
+    /// Optional salt mixed into the hash input
+    ///
+    /// Deployments that share entity definitions but set different salts
+    /// derive unrelated keys from the same seed (see
+    /// [`KeyDerivation::hash_input`]).
+    #[serde(
+        default,
+        deserialize_with = "deserialize_salt",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub salt: Option<String>,
+
+    /// Domain tag prefixed to the hash input (default: `v1`)
+    ///
+    /// Version 1 entity files are read with `none`, their original untagged
+    /// hashing, so their keys are unchanged.
+    #[serde(default, skip_serializing_if = "is_default_domain_tag")]
+    pub domain_tag: DomainTagConfig,
+
+    /// Number of entity-level path components (1-3, default: 1)
+    ///
+    /// Each level consumes 4 more bytes of the hash, giving paths
+    /// m/83696968'/67797668'/{i1}'[/{i2}'[/{i3}']].
+    #[serde(
+        default = "default_index_levels",
+        deserialize_with = "deserialize_index_levels",
+        skip_serializing_if = "is_single_level"
+    )]
+    pub index_levels: u8,
+
+    /// Optional sub-application level inserted before the entity indices
+    ///
+    /// Fences off the entity namespace of one product from another's:
+    /// m/83696968'/67797668'/{application}'/{index}'. Always hardened; must
+    /// be below 2^31.
+    #[serde(
+        default,
+        deserialize_with = "deserialize_application",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub application: Option<u32>,
+
+    /// Optional tenant whose subtree the entity is derived in
+    ///
+    /// The tenant identifier is hashed to a hardened level after the
+    /// application level (see `tenant_index`), so each tenant of a
+    /// multi-tenant deployment gets its own subtree of the same seed:
+    /// m/83696968'/67797668'/[{application}'/]{tenant}'/{index}'.
+    #[serde(
+        default,
+        deserialize_with = "deserialize_tenant",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub tenant: Option<String>,
+
+    /// Optional key number appended after the entity indices
+    ///
+    /// Lets one entity yield several keys, e.g. 0 for signing, 1 for
+    /// encryption, 2 for backup: m/83696968'/67797668'/{index}'/{key_index}'.
+    /// Hardened like the entity level (per `hardened`); must be below 2^31.
+    #[serde(
+        default,
+        deserialize_with = "deserialize_key_index",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub key_index: Option<u32>,
+
+    /// Where the derived seed comes from (default: raw)
+    #[serde(default, skip_serializing_if = "is_default_key_entropy")]
+    pub key_entropy: KeyEntropyConfig,
+
+    /// Kind of key the entity produces (default: ed25519)
+    ///
+    /// Selects the curve used by the key formats (`public-key`, `jwk`,
+    /// PEM, ...); formats specific to Ed25519 such as `ssh` are refused for
+    /// other key types.
+    #[serde(default, skip_serializing_if = "is_default_key_type")]
+    pub key_type: KeyTypeConfig,
+
+    /// Optional expansion of the hash before the index is extracted
+    ///
+    /// When absent, the index is taken from the raw hash output.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub index_expansion: Option<IndexExpansionConfig>,
+
+    /// Lowercase the method of a `did` entity before validation and hashing
+    ///
+    /// Without this, a DID such as `did:GitHub:alice` is rejected.
+    #[serde(default, skip_serializing_if = "is_false")]
+    pub lowercase_did_method: bool,
+}
+
+impl DerivationConfig {
+    /// Whether the entity is derived at the plain hardened single-level
+    /// path m/83696968'/67797668'/{index}' with a raw seed
+    ///
+    /// Only such entities can be named by their bare hash as a `parent`.
+    pub fn has_default_path(&self) -> bool {
+        self.hardened
+            && self.index_levels == 1
+            && self.application.is_none()
+            && self.tenant.is_none()
+            && self.key_index.is_none()
+            && self.key_entropy == KeyEntropyConfig::Raw
+    }
+
+    /// The field-selection setting in use, `hash_exclude` or `hash_include`,
+    /// and its pointers
+    fn hash_selection(&self) -> Option<(&'static str, &[String])> {
+        if !self.hash_include.is_empty() {
+            Some(("hash_include", &self.hash_include))
+        } else if !self.hash_exclude.is_empty() {
+            Some(("hash_exclude", &self.hash_exclude))
+        } else {
+            None
+        }
+    }
+
+    /// The part of `entity` that is hashed under `hash_include` or
+    /// `hash_exclude`
+    fn select_hashed_fields(&self, entity: &Value) -> std::result::Result<Value, String> {
+        if !self.hash_include.is_empty() {
+            return include_fields(entity, &self.hash_include);
+        }
+        let mut entity = entity.clone();
+        exclude_fields(&mut entity, &self.hash_exclude)?;
+        Ok(entity)
+    }
+}
+
+/// Parent of an entity in a semantic hierarchy
+///
+/// The entity's key is derived below the parent's key, with the parent's
+/// seed as parent entropy (see `derive_child_entity`).
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(untagged)]
+pub enum EntityParent {
+    /// Parent identified by its 64-byte entity hash (128 hex characters)
+    /// alone. Only stands in for a parent with the default path settings
+    /// (see [`DerivationConfig::has_default_path`]) and no parent of its
+    /// own; use [`EntityParent::Reference`] for any other parent.
+    Hash(String),
+    /// Parent identified by its hash together with its path settings,
+    /// which keeps the parent entity itself out of the file
+    Reference(ParentReference),
+    /// Inline parent specification, which may have its own parent
+    Inline(Box<KeyDerivation>),
+}
+
+/// A parent given by hash, with what is needed to rebuild its path
+///
+/// ```json
+/// "parent": {
+///   "hash": "<128 hex characters>",
+///   "derivation_config": {"hash_function": "hmac_sha512", "hardened": true, "application": 7}
+/// }
+/// ```
+///
+/// `derivation_config` is the parent's own; only its path settings
+/// (`hardened`, `index_levels`, `application`, `tenant`, `key_index`, and
+/// `key_entropy`) are used, since the hash already reflects the rest.
+/// Build one with `parent_reference`.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct ParentReference {
+    /// The parent's 64-byte entity hash (128 hex characters), computed with
+    /// its own parent's seed as parent entropy if it has a parent
+    pub hash: String,
+
+    /// The parent's derivation configuration
+    pub derivation_config: DerivationConfig,
+
+    /// The parent's own parent, if any
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub parent: Option<Box<EntityParent>>,
+}
+
+/// A schema.org entity (`schema_type: "schema_org"`)
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+pub struct SchemaOrgEntity {
+    /// Schema.org type, e.g. `SoftwareSourceCode`
+    #[serde(rename = "@type")]
+    pub type_: String,
+
+    /// All other properties, including `@context`
+    #[serde(flatten)]
+    pub properties: Map<String, Value>,
+}
+
+/// A W3C Decentralized Identifier entity (`schema_type: "did"`)
+///
+/// `did` must be a valid DID (see [`Did::parse`]), and `method` and
+/// `identifier` must match its parts.
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+pub struct DidEntity {
+    /// Full DID, e.g. `did:github:username`
+    pub did: String,
+
+    /// DID method, e.g. `github`
+    pub method: String,
+
+    /// Method-specific identifier
+    pub identifier: String,
+
+    /// Any additional properties
+    #[serde(flatten)]
+    pub properties: Map<String, Value>,
+}
+
+impl DidEntity {
+    /// Check the DID syntax and that `method`/`identifier` match it
+    pub fn validate(&self) -> Result<Did> {
+        let did = Did::parse(&self.did)?;
+        let mismatch = |field: &str, value: &str, expected: &str| {
+            BipKeychainError::InvalidEntity(serde::de::Error::custom(format!(
+                "invalid did entity: {} '{}' does not match '{}' from {}",
+                field, value, expected, self.did
+            )))
+        };
+
+        if self.method != did.method {
+            return Err(mismatch("method", &self.method, &did.method));
+        }
+        if self.identifier != did.method_specific_id {
+            return Err(mismatch(
+                "identifier",
+                &self.identifier,
+                &did.method_specific_id,
+            ));
+        }
+        Ok(did)
+    }
+}
+
+/// A Gordian Envelope entity (`schema_type: "gordian_envelope"`)
+///
+/// The envelope is decoded and checked at parse time, and its CBOR (not
+/// this JSON wrapper) is what gets hashed.
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+pub struct GordianEnvelopeEntity {
+    /// UR-encoded envelope (`ur:envelope/...`)
+    pub envelope: String,
+
+    /// Expected envelope digest as 64 hex characters, checked if present
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub digest: Option<String>,
+
+    /// All other properties, such as `format` and `elision_proof`
+    #[serde(flatten)]
+    pub properties: Map<String, Value>,
+}
+
+impl GordianEnvelopeEntity {
+    /// Decode the envelope and check it against `digest`, if given
+    pub fn decode(&self) -> Result<Envelope> {
+        let envelope = Envelope::from_ur_string(&self.envelope)?;
+
+        if let Some(expected) = &self.digest {
+            let actual = hex::encode(envelope.digest());
+            if !expected.eq_ignore_ascii_case(&actual) {
+                return Err(BipKeychainError::EnvelopeError(format!(
+                    "digest is {}, expected {}",
+                    actual, expected
+                )));
+            }
+        }
+        Ok(envelope)
+    }
+}
+
+/// Entity data, typed by `schema_type`
+///
+/// Optional properties stay in each variant's `properties` map, so an
+/// entity serializes back to exactly the JSON it was parsed from and its
+/// hash is unchanged.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Entity {
+    /// `schema_type: "schema_org"`; requires `@type`
+    SchemaOrg(SchemaOrgEntity),
+    /// `schema_type: "did"`; requires `did`, `method`, and `identifier`
+    Did(DidEntity),
+    /// `schema_type: "gordian_envelope"`; requires `envelope`
+    GordianEnvelope(GordianEnvelopeEntity),
+    /// Any other schema type, kept as generic JSON
+    Custom(Value),
+}
+
+fn invalid(schema_type: &str, reason: impl std::fmt::Display) -> BipKeychainError {
+    BipKeychainError::InvalidEntity(serde::de::Error::custom(format!(
+        "invalid {} entity: {}",
+        schema_type, reason
+    )))
+}
+
+impl Entity {
+    /// Parse entity JSON according to its schema type
+    pub fn from_value(schema_type: &str, value: Value) -> Result<Self> {
+        let typed = match schema_type {
+            "schema_org" => serde_json::from_value(value).map(Entity::SchemaOrg),
+            "did" => {
+                let entity: DidEntity =
+                    serde_json::from_value(value).map_err(|e| invalid(schema_type, e))?;
+                entity.validate()?;
+                return Ok(Entity::Did(entity));
+            }
+            "gordian_envelope" => {
+                let entity: GordianEnvelopeEntity =
+                    serde_json::from_value(value).map_err(|e| invalid(schema_type, e))?;
+                entity.decode().map_err(|e| match e {
+                    BipKeychainError::EnvelopeError(message)
+                    | BipKeychainError::UrError(message) => invalid(schema_type, message),
+                    other => other,
+                })?;
+                return Ok(Entity::GordianEnvelope(entity));
+            }
+            _ => return Ok(Entity::Custom(value)),
+        };
+
+        typed.map_err(|e| invalid(schema_type, e))
+    }
+
+    /// Parse entity JSON, applying the configuration's normalizations first
+    pub(crate) fn from_value_with_config(
+        schema_type: &str,
+        mut value: Value,
+        config: &DerivationConfig,
+    ) -> Result<Self> {
+        if let Some((field, _)) = config.hash_selection() {
+            let config_error =
+                |reason: String| BipKeychainError::InvalidEntity(serde::de::Error::custom(reason));
+            if !config.hash_exclude.is_empty() && !config.hash_include.is_empty() {
+                return Err(config_error(
+                    "hash_exclude and hash_include cannot be combined".to_string(),
+                ));
+            }
+            // Field selection needs the entity as JSON: `none` hashes the
+            // source text, and envelopes hash their CBOR
+            if config.canonicalization == CanonicalizationConfig::None {
+                return Err(config_error(format!(
+                    "{} cannot be combined with canonicalization: none",
+                    field
+                )));
+            }
+            if schema_type == "gordian_envelope" {
+                return Err(config_error(format!(
+                    "{} is not supported for gordian_envelope entities; elide assertions instead",
+                    field
+                )));
+            }
+            config.select_hashed_fields(&value).map_err(config_error)?;
+        }
+        if schema_type == "did" && config.lowercase_did_method {
+            // `none` hashes the source text, which can't be normalized
+            if config.canonicalization == CanonicalizationConfig::None {
+                return Err(BipKeychainError::InvalidEntity(serde::de::Error::custom(
+                    "lowercase_did_method cannot be combined with canonicalization: none",
+                )));
+            }
+            if let Some(Value::String(did)) = value.get_mut("did") {
+                *did = Did::lowercase_method(did);
+            }
+            if let Some(Value::String(method)) = value.get_mut("method") {
+                *method = method.to_ascii_lowercase();
+            }
+        }
+        Self::from_value(schema_type, value)
+    }
+
+    /// The entity as generic JSON, exactly as it is hashed
+    pub fn to_value(&self) -> Value {
+        match self {
+            Entity::Custom(value) => value.clone(),
+            typed => serde_json::to_value(typed).expect("entities always serialize to JSON"),
+        }
+    }
+}
+
+impl Serialize for Entity {
+    fn serialize<S>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        // Untagged: the variant is implied by the sibling `schema_type`
+        match self {
+            Entity::SchemaOrg(entity) => entity.serialize(serializer),
+            Entity::Did(entity) => entity.serialize(serializer),
+            Entity::GordianEnvelope(entity) => entity.serialize(serializer),
+            Entity::Custom(value) => value.serialize(serializer),
+        }
+    }
+}
+
+/// Wire form of [`KeyDerivation`], before the entity is typed
+#[derive(Deserialize)]
+struct KeyDerivationRepr {
+    #[serde(default)]
+    version: Option<u32>,
+    schema_type: String,
+    entity: Value,
+    derivation_config: DerivationConfig,
+    purpose: Option<String>,
+    metadata: Option<Value>,
+    #[serde(default)]
+    parent: Option<EntityParent>,
+    #[serde(default)]
+    password: Option<PasswordConfig>,
+    #[serde(default)]
+    totp: Option<TotpConfig>,
+    #[serde(default)]
+    dnssec: Option<DnssecConfig>,
+    #[serde(default)]
+    tlsa: Option<TlsaConfig>,
+    #[serde(default)]
+    frost: Option<FrostConfig>,
+    #[serde(default)]
+    output_format: Option<OutputFormat>,
+}
+
+/// Upgrade an entity file from an older version, returning the rewritten
+/// JSON, or `None` if it is already current
+fn migrate_entity_json(json: &str) -> Result<Option<String>> {
+    #[derive(Deserialize)]
+    struct VersionProbe {
+        #[serde(default)]
+        version: Option<u32>,
+    }
+
+    let unsupported =
+        |message: String| BipKeychainError::InvalidEntity(serde::de::Error::custom(message));
+    let probe: VersionProbe =
+        serde_json::from_str(json).map_err(BipKeychainError::InvalidEntity)?;
+    let version = probe.version.unwrap_or(1);
+    if version == 0 {
+        return Err(unsupported("entity version must be at least 1".to_string()));
+    }
+    if version > ENTITY_VERSION {
+        return Err(unsupported(format!(
+            "entity version {} is newer than this build supports ({}); upgrade bip-keychain",
+            version, ENTITY_VERSION
+        )));
+    }
+    if version == ENTITY_VERSION {
+        return Ok(None);
+    }
+
+    let mut value: Map<String, Value> =
+        serde_json::from_str(json).map_err(BipKeychainError::InvalidEntity)?;
+    for migration in &ENTITY_MIGRATIONS[version as usize - 1..] {
+        migration(&mut value);
+    }
+    value.insert("version".to_string(), Value::from(ENTITY_VERSION));
+    Ok(Some(Value::Object(value).to_string()))
+}
+
+/// Split a `hash_exclude` / `hash_include` JSON pointer (RFC 6901) into its
+/// unescaped reference tokens
+fn pointer_tokens(field: &str, pointer: &str) -> std::result::Result<Vec<String>, String> {
+    let tokens = pointer.strip_prefix('/').ok_or_else(|| {
+        format!(
+            "{} entry '{}' is not a JSON pointer (e.g. \"/comment\")",
+            field, pointer
+        )
+    })?;
+    let escapes_valid = pointer
+        .split('~')
+        .skip(1)
+        .all(|rest| rest.starts_with('0') || rest.starts_with('1'));
+    if !escapes_valid {
+        return Err(format!(
+            "{} entry '{}' has an invalid '~' escape (use ~0 for '~' and ~1 for '/')",
+            field, pointer
+        ));
+    }
+    Ok(tokens
+        .split('/')
+        .map(|token| token.replace("~1", "/").replace("~0", "~"))
+        .collect())
+}
+
+/// Remove the fields named by `hash_exclude` pointers from an entity
+///
+/// Pointers must name an object member; a member that is absent is
+/// skipped, so an excluded field can be added later without changing the
+/// key.
+fn exclude_fields(value: &mut Value, pointers: &[String]) -> std::result::Result<(), String> {
+    for pointer in pointers {
+        let tokens = pointer_tokens("hash_exclude", pointer)?;
+        let (member, parents) = tokens.split_last().expect("split yields a token");
+        let parent = parents
+            .iter()
+            .try_fold(&mut *value, |node, token| match node {
+                Value::Object(object) => object.get_mut(token),
+                Value::Array(array) => array.get_mut(token.parse::<usize>().ok()?),
+                _ => None,
+            });
+
+        match parent {
+            Some(Value::Object(object)) => {
+                object.remove(member);
+            }
+            Some(Value::Array(_)) => {
+                return Err(format!(
+                    "hash_exclude entry '{}' names an array element; exclude the whole array",
+                    pointer
+                ))
+            }
+            _ => {}
+        }
+    }
+    Ok(())
+}
+
+/// Copy only the fields named by `hash_include` pointers out of an entity
+///
+/// Each pointer must name an object member reached through objects, and
+/// the member must be present: an allowlisted identity field that is
+/// missing is an error rather than silently hashed as absent.
+fn include_fields(value: &Value, pointers: &[String]) -> std::result::Result<Value, String> {
+    let mut included = Value::Object(Map::new());
+    for pointer in pointers {
+        let tokens = pointer_tokens("hash_include", pointer)?;
+        let mut source = value;
+        let mut target = &mut included;
+        for token in &tokens {
+            let (Value::Object(from), Value::Object(to)) = (source, target) else {
+                return Err(format!(
+                    "hash_include entry '{}' must name an object member, not an array element",
+                    pointer
+                ));
+            };
+            source = from.get(token).ok_or_else(|| {
+                format!(
+                    "hash_include entry '{}' is missing from the entity",
+                    pointer
+                )
+            })?;
+            target = to
+                .entry(token.clone())
+                .or_insert_with(|| Value::Object(Map::new()));
+        }
+        *target = source.clone();
+    }
+    Ok(included)
+}
+
+/// Domain tag starting a salted hash input (see [`KeyDerivation::hash_input`])
+pub const SALT_DOMAIN: &[u8] = b"bip-keychain-salt";
+
+/// Metadata field holding an entity's expiry (see [`KeyDerivation::not_after`])
+pub const NOT_AFTER_FIELD: &str = "not_after";
+
+/// Days from 1970-01-01 to a proleptic Gregorian date
+fn days_from_civil(year: i64, month: i64, day: i64) -> i64 {
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let year_of_era = year - era * 400;
+    let day_of_year = (153 * ((month + 9) % 12) + 2) / 5 + day - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+    era * 146_097 + day_of_era - 719_468
+}
+
+/// Parse a `not_after` value into Unix seconds
+///
+/// Accepts Unix seconds, an RFC 3339 full-date (`2026-06-30`, valid through
+/// the end of that UTC day), or an RFC 3339 date-time
+/// (`2026-06-30T12:00:00Z`, `2026-06-30T14:00:00+02:00`). Fractional
+/// seconds are truncated.
+fn parse_not_after(value: &Value) -> std::result::Result<u64, String> {
+    let invalid = || {
+        format!(
+            "metadata.{} must be Unix seconds or an RFC 3339 date or date-time \
+             (e.g. \"2026-06-30\"), got {}",
+            NOT_AFTER_FIELD, value
+        )
+    };
+    let text = match value {
+        Value::Number(number) => return number.as_u64().ok_or_else(invalid),
+        Value::String(text) => text.as_str(),
+        _ => return Err(invalid()),
+    };
+
+    let field = |range: std::ops::Range<usize>| -> std::result::Result<i64, String> {
+        text.get(range)
+            .filter(|digits| digits.bytes().all(|b| b.is_ascii_digit()))
+            .and_then(|digits| digits.parse().ok())
+            .ok_or_else(invalid)
+    };
+    let separator = |index: usize, allowed: &[u8]| {
+        text.as_bytes()
+            .get(index)
+            .filter(|byte| allowed.contains(byte))
+            .map(|_| ())
+            .ok_or_else(invalid)
+    };
+
+    separator(4, b"-")?;
+    separator(7, b"-")?;
+    let (year, month, day) = (field(0..4)?, field(5..7)?, field(8..10)?);
+    let leap = year % 4 == 0 && (year % 100 != 0 || year % 400 == 0);
+    let month_days = [
+        31,
+        if leap { 29 } else { 28 },
+        31,
+        30,
+        31,
+        30,
+        31,
+        31,
+        30,
+        31,
+        30,
+        31,
+    ];
+    if !(1..=12).contains(&month) || day < 1 || day > month_days[month as usize - 1] {
+        return Err(invalid());
+    }
+    let midnight = days_from_civil(year, month, day) * 86_400;
+
+    let seconds = if text.len() == 10 {
+        midnight + 86_399
+    } else {
+        separator(10, b"Tt ")?;
+        separator(13, b":")?;
+        separator(16, b":")?;
+        let (hour, minute, second) = (field(11..13)?, field(14..16)?, field(17..19)?);
+        if hour > 23 || minute > 59 || second > 60 {
+            return Err(invalid());
+        }
+
+        let mut rest = &text[19..];
+        if let Some(fraction) = rest.strip_prefix('.') {
+            let digits = fraction.bytes().take_while(u8::is_ascii_digit).count();
+            if digits == 0 {
+                return Err(invalid());
+            }
+            rest = &fraction[digits..];
+        }
+        let offset = match rest {
+            "Z" | "z" => 0,
+            _ if rest.len() == 6 && rest.as_bytes()[3] == b':' => {
+                let sign = match rest.as_bytes()[0] {
+                    b'+' => 1,
+                    b'-' => -1,
+                    _ => return Err(invalid()),
+                };
+                let (hours, minutes) = (&rest[1..3], &rest[4..6]);
+                let parse = |digits: &str| {
+                    digits
+                        .bytes()
+                        .all(|b| b.is_ascii_digit())
+                        .then(|| digits.parse::<i64>().ok())
+                        .flatten()
+                        .ok_or_else(invalid)
+                };
+                sign * (parse(hours)? * 3600 + parse(minutes)? * 60)
+            }
+            _ => return Err(invalid()),
+        };
+        midnight + hour * 3600 + minute * 60 + second.min(59) - offset
+    };
+
+    u64::try_from(seconds).map_err(|_| invalid())
+}
+
+impl TryFrom<KeyDerivationRepr> for KeyDerivation {
+    type Error = String;
+
+    fn try_from(repr: KeyDerivationRepr) -> std::result::Result<Self, Self::Error> {
+        if let Some(not_after) = repr.metadata.as_ref().and_then(|m| m.get(NOT_AFTER_FIELD)) {
+            parse_not_after(not_after)?;
+        }
+
+        // Report the inner serde error; from_json adds the help text
+        let entity =
+            Entity::from_value_with_config(&repr.schema_type, repr.entity, &repr.derivation_config)
+                .map_err(|e| match e {
+                    BipKeychainError::InvalidEntity(e) => e.to_string(),
+                    other => other.to_string(),
+                })?;
+
+        Ok(Self {
+            version: repr.version,
+            schema_type: repr.schema_type,
+            entity,
+            derivation_config: repr.derivation_config,
+            purpose: repr.purpose,
+            metadata: repr.metadata,
+            parent: repr.parent,
+            password: repr.password,
+            totp: repr.totp,
+            dnssec: repr.dnssec,
+            tlsa: repr.tlsa,
+            frost: repr.frost,
+            output_format: repr.output_format,
+            entity_source: None,
+        })
+    }
 }
 
 /// A complete key derivation specification
@@ -35,13 +1057,17 @@ pub struct DerivationConfig {
 /// This is the top-level struct that represents a Nickel-exported entity
 /// ready for BIP-Keychain derivation.
 #[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(try_from = "KeyDerivationRepr")]
 pub struct KeyDerivation {
+    /// Entity file format version (absent means 1; see [`ENTITY_VERSION`])
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub version: Option<u32>,
+
     /// Schema type identifier (e.g., "schema_org", "did", "gordian_envelope")
     pub schema_type: String,
 
-    /// The actual entity data (stored as generic JSON for now)
-    /// In the future, this could be an enum with type-safe variants
-    pub entity: Value,
+    /// The entity data, validated against `schema_type`
+    pub entity: Entity,
 
     /// Derivation configuration
     pub derivation_config: DerivationConfig,
@@ -53,12 +1079,127 @@ pub struct KeyDerivation {
     /// Optional additional metadata
     #[serde(skip_serializing_if = "Option::is_none")]
     pub metadata: Option<Value>,
+
+    /// Optional parent entity; the key is derived below the parent's key
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub parent: Option<EntityParent>,
+
+    /// Optional settings for the `password` output format
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub password: Option<PasswordConfig>,
+
+    /// Optional settings for the `otpauth` output format and `totp` command
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub totp: Option<TotpConfig>,
+
+    /// Optional zone and role for the DNSSEC output formats
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub dnssec: Option<DnssecConfig>,
+
+    /// Optional service and record fields for the `tlsa` output format
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tlsa: Option<TlsaConfig>,
+
+    /// Optional threshold and group size for the `frost` command
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub frost: Option<FrostConfig>,
+
+    /// Optional preferred output format, used by `derive` without `--format`
+    /// and by manifest entries without a `format`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub output_format: Option<OutputFormat>,
+
+    /// Entity text as written in the source JSON (for `canonicalization: none`)
+    #[serde(skip)]
+    entity_source: Option<String>,
 }
 
 impl KeyDerivation {
+    /// Assemble a key derivation from an already-validated entity
+    pub(crate) fn new(
+        schema_type: String,
+        entity: Entity,
+        derivation_config: DerivationConfig,
+    ) -> Self {
+        Self {
+            version: Some(ENTITY_VERSION),
+            schema_type,
+            entity,
+            derivation_config,
+            purpose: None,
+            metadata: None,
+            parent: None,
+            password: None,
+            totp: None,
+            dnssec: None,
+            tlsa: None,
+            frost: None,
+            output_format: None,
+            entity_source: None,
+        }
+    }
+
     /// Parse a KeyDerivation from JSON string
+    ///
+    /// Files from older entity versions are migrated to [`ENTITY_VERSION`]
+    /// first; files from newer versions are rejected.
     pub fn from_json(json: &str) -> Result<Self> {
-        serde_json::from_str(json).map_err(BipKeychainError::InvalidEntity)
+        let migrated = migrate_entity_json(json)?;
+        let mut key_derivation: Self = serde_json::from_str(migrated.as_deref().unwrap_or(json))
+            .map_err(BipKeychainError::InvalidEntity)?;
+
+        // Source text below comes from the file as written, before migration
+
+        if key_derivation.derivation_config.canonicalization == CanonicalizationConfig::None {
+            #[derive(Deserialize)]
+            struct EntitySource<'a> {
+                #[serde(borrow)]
+                entity: &'a RawValue,
+            }
+
+            let source: EntitySource =
+                serde_json::from_str(json).map_err(BipKeychainError::InvalidEntity)?;
+            key_derivation.entity_source = Some(source.entity.get().to_string());
+        }
+
+        // Re-parse inline parents with from_json so they keep their source text
+        if let Some(EntityParent::Inline(_)) = key_derivation.parent {
+            #[derive(Deserialize)]
+            struct ParentSource<'a> {
+                #[serde(borrow)]
+                parent: &'a RawValue,
+            }
+
+            let source: ParentSource =
+                serde_json::from_str(json).map_err(BipKeychainError::InvalidEntity)?;
+            key_derivation.parent = Some(EntityParent::Inline(Box::new(Self::from_json(
+                source.parent.get(),
+            )?)));
+        }
+
+        Ok(key_derivation)
+    }
+
+    /// When the entity expires, from `metadata.not_after`, in seconds since
+    /// the Unix epoch
+    ///
+    /// `not_after` is Unix seconds, an RFC 3339 date (the entity is valid
+    /// through the end of that day, UTC), or an RFC 3339 date-time. Entity
+    /// files with a malformed `not_after` are rejected by [`Self::from_json`].
+    pub fn not_after(&self) -> Result<Option<u64>> {
+        self.metadata
+            .as_ref()
+            .and_then(|metadata| metadata.get(NOT_AFTER_FIELD))
+            .map(parse_not_after)
+            .transpose()
+            .map_err(|e| BipKeychainError::InvalidEntity(serde::de::Error::custom(e)))
+    }
+
+    /// Whether the entity's `not_after` is before `unix_time`
+    pub fn is_expired_at(&self, unix_time: u64) -> Result<bool> {
+        Ok(self
+            .not_after()?
+            .is_some_and(|not_after| unix_time > not_after))
     }
 
     /// Get the entity as a canonical JSON string for hashing
@@ -66,6 +1207,230 @@ impl KeyDerivation {
         serde_json::to_string(&self.entity)
             .map_err(|e| BipKeychainError::HashError(format!("Failed to serialize entity: {}", e)))
     }
+
+    /// Get the entity bytes to hash, in the configured canonical form
+    ///
+    /// With `canonicalization: none` this is the entity text exactly as it
+    /// appeared in the JSON passed to [`KeyDerivation::from_json`].
+    /// Gordian Envelope entities always hash the envelope's tagged CBOR,
+    /// whatever the canonicalization.
+    ///
+    /// With `hash_exclude` (or `hash_include`), the canonical form is that
+    /// of `{"entity": <entity without the excluded fields>, "hash_exclude":
+    /// <sorted pointers>}` (or the included fields under `hash_include`).
+    pub fn canonical_entity(&self) -> Result<Vec<u8>> {
+        if let Entity::GordianEnvelope(entity) = &self.entity {
+            return Ok(entity.decode()?.to_cbor());
+        }
+
+        let canonicalization = match self.derivation_config.canonicalization {
+            CanonicalizationConfig::Serde => Canonicalization::Serde,
+            CanonicalizationConfig::Jcs => Canonicalization::Jcs,
+            CanonicalizationConfig::Dcbor => Canonicalization::Dcbor,
+            CanonicalizationConfig::None => Canonicalization::None,
+        };
+
+        if let Some((field, pointers)) = self.derivation_config.hash_selection() {
+            let entity = self
+                .derivation_config
+                .select_hashed_fields(&self.entity.to_value())
+                .map_err(BipKeychainError::HashError)?;
+            let mut pointers = pointers.to_vec();
+            pointers.sort();
+            pointers.dedup();
+            let wrapped = serde_json::json!({"entity": entity, field: pointers});
+            return canonicalize_value(&wrapped, canonicalization);
+        }
+
+        match &self.entity_source {
+            // Already validated as JSON by from_json; use it verbatim
+            Some(source) => Ok(source.as_bytes().to_vec()),
+            None => canonicalize_value(&self.entity.to_value(), canonicalization),
+        }
+    }
+
+    /// Bytes fed to the hash function: the domain tag, then the salt if
+    /// one is set, then the canonical entity
+    ///
+    /// The input is `domain tag || canonical entity`, with `domain tag` the
+    /// ASCII `BIP-KEYCHAIN/v1` (empty for `domain_tag: none`). A salted
+    /// input puts `"bip-keychain-salt" || u32 big-endian length of the
+    /// salt || UTF-8 salt` after the tag, so no salt is a prefix of
+    /// another's input and an unsalted entity (always JSON or CBOR) never
+    /// collides with a salted one.
+    pub fn hash_input(&self) -> Result<Vec<u8>> {
+        let canonical = self.canonical_entity()?;
+        let mut input = self.derivation_config.domain_tag.tag().to_vec();
+        if let Some(salt) = &self.derivation_config.salt {
+            let length = u32::try_from(salt.len())
+                .map_err(|_| BipKeychainError::HashError("salt is too long".to_string()))?;
+            input.extend_from_slice(SALT_DOMAIN);
+            input.extend_from_slice(&length.to_be_bytes());
+            input.extend_from_slice(salt.as_bytes());
+        }
+        input.extend_from_slice(&canonical);
+        Ok(input)
+    }
+
+    /// Encode as a `ur:crypto-entity` string for transfer by QR code
+    ///
+    /// The payload is a dCBOR map from small integer keys to the entity's
+    /// fields (schema type, entity, derivation config, purpose, ...), or
+    /// with `deflate` a byte string holding that map's raw DEFLATE
+    /// compression. Entities whose key would change on decoding (e.g.
+    /// `canonicalization: none`, which hashes the exact JSON text) are
+    /// rejected.
+    pub fn to_ur_string(&self, deflate: bool) -> Result<String> {
+        let value = serde_json::to_value(self).map_err(BipKeychainError::InvalidEntity)?;
+        let Value::Object(fields) = value else {
+            unreachable!("key derivations serialize to JSON objects");
+        };
+        if let Some(name) = fields
+            .keys()
+            .find(|name| !ENTITY_UR_FIELDS.iter().any(|(_, field, _)| field == name))
+        {
+            return Err(BipKeychainError::UrError(format!(
+                "entity field '{}' has no crypto-entity key",
+                name
+            )));
+        }
+
+        let present: Vec<_> = ENTITY_UR_FIELDS
+            .iter()
+            .filter_map(|(key, name, since)| fields.get(*name).map(|value| (*key, value, *since)))
+            .collect();
+        let version = Value::from(
+            present
+                .iter()
+                .map(|(_, _, since)| *since)
+                .max()
+                .unwrap_or(1),
+        );
+
+        // Keys 0-23 encode as single bytes, so ascending order is dCBOR order
+        let present: Vec<_> = std::iter::once((ENTITY_UR_VERSION_KEY, &version))
+            .chain(present.into_iter().map(|(key, value, _)| (key, value)))
+            .collect();
+        let mut cbor = Vec::new();
+        dcbor::encode_map_header(&mut cbor, present.len());
+        for (key, value) in present {
+            dcbor::encode_unsigned(&mut cbor, key);
+            cbor.extend_from_slice(&dcbor::encode_json(value));
+        }
+
+        let decoded = Self::from_cbor(&cbor)?;
+        if decoded.canonical_entity()? != self.canonical_entity()? {
+            return Err(BipKeychainError::UrError(
+                "entity would hash differently after a CBOR round trip (check canonicalization)"
+                    .to_string(),
+            ));
+        }
+
+        if !deflate {
+            return Ok(ur::encode(ENTITY_UR_TYPE, &cbor));
+        }
+        let mut payload = Vec::new();
+        dcbor::encode_bytes(
+            &mut payload,
+            &miniz_oxide::deflate::compress_to_vec(&cbor, 10),
+        );
+        Ok(ur::encode(ENTITY_UR_TYPE, &payload))
+    }
+
+    /// Decode a `ur:crypto-entity` string, compressed or not, of any
+    /// version up to [`ENTITY_UR_VERSION`]
+    pub fn from_ur_string(ur: &str) -> Result<Self> {
+        let (ur_type, payload) = ur::decode(ur)?;
+        if ur_type != ENTITY_UR_TYPE {
+            return Err(BipKeychainError::UrError(format!(
+                "expected ur:{}, got ur:{}",
+                ENTITY_UR_TYPE, ur_type
+            )));
+        }
+
+        let mut decoder = dcbor::Decoder::new(&payload);
+        let (major, len) = decoder.read_head().map_err(BipKeychainError::UrError)?;
+        if major != dcbor::MAJOR_BYTES {
+            return Self::from_cbor(&payload);
+        }
+        let compressed = decoder.read_bytes(len).map_err(BipKeychainError::UrError)?;
+        if !decoder.is_empty() {
+            return Err(BipKeychainError::UrError(
+                "trailing bytes after compressed entity".to_string(),
+            ));
+        }
+        let cbor = miniz_oxide::inflate::decompress_to_vec_with_limit(compressed, MAX_ENTITY_CBOR)
+            .map_err(|e| BipKeychainError::UrError(format!("invalid compressed entity: {}", e)))?;
+        Self::from_cbor(&cbor)
+    }
+
+    /// Parse a `crypto-entity` map, migrating older versions
+    fn from_cbor(cbor: &[u8]) -> Result<Self> {
+        let error = |message: String| BipKeychainError::UrError(message);
+
+        // Validates dCBOR rules (shortest heads, sorted unique keys) first
+        let mut decoder = dcbor::Decoder::new(cbor);
+        decoder.read_item().map_err(error)?;
+        if !decoder.is_empty() {
+            return Err(error("trailing bytes after entity".to_string()));
+        }
+
+        let mut decoder = dcbor::Decoder::new(cbor);
+        let (major, len) = decoder.read_head().map_err(error)?;
+        if major != dcbor::MAJOR_MAP {
+            return Err(error("crypto-entity is not a CBOR map".to_string()));
+        }
+        let mut entries = Vec::new();
+        for _ in 0..len {
+            let key = decoder.read_item().map_err(error)?;
+            let value = decoder.read_item().map_err(error)?;
+            entries.push((key, value));
+        }
+
+        // Version 0 keyed fields by their JSON names
+        let text_keys = entries
+            .first()
+            .is_some_and(|(key, _)| key[0] >> 5 == dcbor::MAJOR_TEXT);
+        if text_keys {
+            return Self::from_json(&dcbor::decode_json(cbor).map_err(error)?.to_string());
+        }
+
+        let mut version = 1;
+        let mut fields = Map::new();
+        for (key, value) in entries {
+            let key = match dcbor::decode_json(key).map_err(error)? {
+                Value::Number(key) => key.as_u64(),
+                _ => None,
+            }
+            .ok_or_else(|| error("crypto-entity keys must be unsigned integers".to_string()))?;
+            let value = dcbor::decode_json(value).map_err(error)?;
+
+            if key == ENTITY_UR_VERSION_KEY {
+                version = value
+                    .as_u64()
+                    .ok_or_else(|| error("crypto-entity version is not an integer".to_string()))?;
+                if version > ENTITY_UR_VERSION {
+                    return Err(error(format!(
+                        "crypto-entity version {} is newer than this build supports ({}); upgrade bip-keychain",
+                        version, ENTITY_UR_VERSION
+                    )));
+                }
+                continue;
+            }
+            let name = ENTITY_UR_FIELDS
+                .iter()
+                .find(|(field_key, _, since)| *field_key == key && *since <= version)
+                .map(|(_, name, _)| *name)
+                .ok_or_else(|| {
+                    error(format!(
+                        "unknown key {} in version {} crypto-entity",
+                        key, version
+                    ))
+                })?;
+            fields.insert(name.to_string(), value);
+        }
+        Self::from_json(&Value::Object(fields).to_string())
+    }
 }
 
 #[cfg(test)]
@@ -98,5 +1463,604 @@ mod tests {
         let config: DerivationConfig = serde_json::from_str(json).unwrap();
         assert_eq!(config.hash_function, HashFunctionConfig::Blake2b);
         assert!(!config.hardened);
+        assert_eq!(config.canonicalization, CanonicalizationConfig::Serde);
+        assert_eq!(config.index_levels, 1);
+        assert_eq!(config.key_entropy, KeyEntropyConfig::Raw);
+        assert_eq!(config.index_expansion, None);
+    }
+
+    #[test]
+    fn test_canonicalization_none_uses_source_text() {
+        let json = r#"{
+            "schema_type": "dns",
+            "entity": { "fqdn": "a.example.com",  "zone": "example.com" },
+            "derivation_config": {
+                "hash_function": "hmac_sha512",
+                "hardened": true,
+                "canonicalization": "none"
+            }
+        }"#;
+
+        let kd = KeyDerivation::from_json(json).unwrap();
+        assert_eq!(
+            kd.canonical_entity().unwrap(),
+            br#"{ "fqdn": "a.example.com",  "zone": "example.com" }"#
+        );
+    }
+
+    #[test]
+    fn test_parse_parent_variants() {
+        let inline = r#"{
+            "schema_type": "custom",
+            "entity": {"name": "Telemetry"},
+            "derivation_config": {"hash_function": "hmac_sha512", "hardened": true},
+            "parent": {
+                "schema_type": "custom",
+                "entity": { "name":  "Acme" },
+                "derivation_config": {
+                    "hash_function": "hmac_sha512",
+                    "hardened": true,
+                    "canonicalization": "none"
+                }
+            }
+        }"#;
+        let kd = KeyDerivation::from_json(inline).unwrap();
+        match kd.parent {
+            Some(EntityParent::Inline(parent)) => {
+                assert_eq!(
+                    parent.entity,
+                    Entity::Custom(serde_json::json!({"name": "Acme"}))
+                );
+                assert_eq!(
+                    parent.canonical_entity().unwrap(),
+                    br#"{ "name":  "Acme" }"#
+                );
+            }
+            other => panic!("expected inline parent, got {:?}", other),
+        }
+
+        let by_hash = r#"{
+            "schema_type": "custom",
+            "entity": {"name": "Telemetry"},
+            "derivation_config": {"hash_function": "hmac_sha512", "hardened": true},
+            "parent": "00ff"
+        }"#;
+        let kd = KeyDerivation::from_json(by_hash).unwrap();
+        assert!(matches!(kd.parent, Some(EntityParent::Hash(ref h)) if h == "00ff"));
+
+        let by_reference = r#"{
+            "schema_type": "custom",
+            "entity": {"name": "Telemetry"},
+            "derivation_config": {"hash_function": "hmac_sha512", "hardened": true},
+            "parent": {
+                "hash": "00ff",
+                "derivation_config": {"hash_function": "hmac_sha512", "hardened": true, "application": 7}
+            }
+        }"#;
+        let kd = KeyDerivation::from_json(by_reference).unwrap();
+        match kd.parent {
+            Some(EntityParent::Reference(reference)) => {
+                assert_eq!(reference.hash, "00ff");
+                assert_eq!(reference.derivation_config.application, Some(7));
+                assert!(reference.parent.is_none());
+            }
+            other => panic!("expected parent reference, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_index_levels_validated() {
+        let with_levels = |levels: u8| {
+            format!(
+                r#"{{"hash_function": "hmac_sha512", "hardened": true, "index_levels": {}}}"#,
+                levels
+            )
+        };
+
+        let config: DerivationConfig = serde_json::from_str(&with_levels(3)).unwrap();
+        assert_eq!(config.index_levels, 3);
+        assert!(serde_json::from_str::<DerivationConfig>(&with_levels(0)).is_err());
+        assert!(serde_json::from_str::<DerivationConfig>(&with_levels(4)).is_err());
+    }
+
+    #[test]
+    fn test_application_validated() {
+        let with_application = |application: u64| {
+            format!(
+                r#"{{"hash_function": "hmac_sha512", "hardened": true, "application": {}}}"#,
+                application
+            )
+        };
+
+        let config: DerivationConfig = serde_json::from_str(&with_application(42)).unwrap();
+        assert_eq!(config.application, Some(42));
+        assert!(serde_json::to_string(&config)
+            .unwrap()
+            .contains(r#""application":42"#));
+        assert!(serde_json::from_str::<DerivationConfig>(&with_application(1 << 31)).is_err());
+
+        let config: DerivationConfig =
+            serde_json::from_str(r#"{"hash_function": "hmac_sha512", "hardened": true}"#).unwrap();
+        assert_eq!(config.application, None);
+        assert!(!serde_json::to_string(&config)
+            .unwrap()
+            .contains("application"));
+    }
+
+    #[test]
+    fn test_tenant_validated() {
+        let config: DerivationConfig = serde_json::from_str(
+            r#"{"hash_function": "hmac_sha512", "hardened": true, "tenant": "acme"}"#,
+        )
+        .unwrap();
+        assert_eq!(config.tenant.as_deref(), Some("acme"));
+        assert!(serde_json::from_str::<DerivationConfig>(
+            r#"{"hash_function": "hmac_sha512", "hardened": true, "tenant": ""}"#
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn test_key_index_validated() {
+        let config: DerivationConfig = serde_json::from_str(
+            r#"{"hash_function": "hmac_sha512", "hardened": true, "key_index": 2}"#,
+        )
+        .unwrap();
+        assert_eq!(config.key_index, Some(2));
+        assert!(serde_json::from_str::<DerivationConfig>(
+            r#"{"hash_function": "hmac_sha512", "hardened": true, "key_index": 2147483648}"#
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn test_index_expansion_config_deserialize() {
+        let json = r#"{
+            "hash_function": "hmac_sha512",
+            "hardened": true,
+            "index_expansion": {"type": "hkdf_sha512"}
+        }"#;
+        let config: DerivationConfig = serde_json::from_str(json).unwrap();
+        assert_eq!(
+            config.index_expansion,
+            Some(IndexExpansionConfig::HkdfSha512 {
+                info: DEFAULT_HKDF_INFO.to_string()
+            })
+        );
+    }
+
+    #[test]
+    fn test_entity_ur_round_trip() {
+        let json = include_str!("../examples/server-prod.json");
+        let key_derivation = KeyDerivation::from_json(json).unwrap();
+
+        for deflate in [false, true] {
+            let ur = key_derivation.to_ur_string(deflate).unwrap();
+            assert!(ur.starts_with("ur:crypto-entity/"));
+            let decoded = KeyDerivation::from_ur_string(&ur.to_uppercase()).unwrap();
+            assert_eq!(
+                decoded.canonical_entity().unwrap(),
+                key_derivation.canonical_entity().unwrap()
+            );
+            assert_eq!(decoded.purpose, key_derivation.purpose);
+            assert_eq!(decoded.metadata, key_derivation.metadata);
+        }
+
+        // CBOR is smaller than the JSON text, and deflate shrinks it further
+        let plain = key_derivation.to_ur_string(false).unwrap();
+        let deflated = key_derivation.to_ur_string(true).unwrap();
+        let compact_json = serde_json::to_string(&key_derivation).unwrap();
+        assert!(plain.len() < ur::encode(ENTITY_UR_TYPE, compact_json.as_bytes()).len());
+        assert!(deflated.len() < plain.len());
+
+        assert!(KeyDerivation::from_ur_string("ur:envelope/tpsoiyfdihjzjzjldmksbaoede").is_err());
+    }
+
+    #[test]
+    fn test_entity_ur_map_layout() {
+        let key_derivation = KeyDerivation::from_json(
+            r#"{
+  "version": 2,
+  "schema_type": "schema_org",
+  "entity": {"@type": "Organization", "name": "x"},
+  "derivation_config": {"hash_function": "hmac_sha512", "hardened": true},
+  "purpose": "demo"
+}"#,
+        )
+        .unwrap();
+        let (_, cbor) = ur::decode(&key_derivation.to_ur_string(false).unwrap()).unwrap();
+        // {0: 2, 1: "schema_org", 2: {...}, 3: {...}, 4: "demo", 12: 2}
+        assert_eq!(
+            hex::encode(&cbor),
+            "a60002016a736368656d615f6f726702a2646e616d6561786540747970656c4f7267616e697a61\
+             74696f6e03a26868617264656e6564f56d686173685f66756e6374696f6e6b686d61635f736861\
+             353132046464656d6f0c02"
+        );
+
+        // Unknown keys and non-integer keys are rejected
+        assert!(KeyDerivation::from_cbor(&[0xa1, 0x0c, 0x00]).is_err());
+        assert!(KeyDerivation::from_cbor(&[0xa1, 0x20, 0x00]).is_err());
+    }
+
+    #[test]
+    fn test_entity_ur_decodes_past_versions() {
+        let json = r#"{
+  "schema_type": "schema_org",
+  "entity": {"@type": "Organization", "name": "x"},
+  "derivation_config": {"hash_function": "hmac_sha512", "hardened": true},
+  "purpose": "demo"
+}"#;
+        let expected = KeyDerivation::from_json(json).unwrap();
+        let versions = [
+            // Version 0: the entity JSON with text keys
+            dcbor::encode_json(&serde_json::from_str(json).unwrap()),
+            // Version 1 before the version key was written
+            hex::decode(
+                "a4016a736368656d615f6f726702a2646e616d6561786540747970656c4f7267616e697a6174\
+                 696f6e03a26868617264656e6564f56d686173685f66756e6374696f6e6b686d61635f73686135\
+                 3132046464656d6f",
+            )
+            .unwrap(),
+        ];
+        for cbor in versions {
+            let decoded =
+                KeyDerivation::from_ur_string(&ur::encode(ENTITY_UR_TYPE, &cbor)).unwrap();
+            assert_eq!(
+                decoded.canonical_entity().unwrap(),
+                expected.canonical_entity().unwrap()
+            );
+            assert_eq!(decoded.purpose.as_deref(), Some("demo"));
+        }
+
+        // Versions from the future are refused rather than misread
+        let error = KeyDerivation::from_cbor(&[0xa1, 0x00, 0x04]).unwrap_err();
+        assert!(error.to_string().contains("version 4"));
+    }
+
+    #[test]
+    fn test_entity_output_format() {
+        let json = r#"{
+  "schema_type": "schema_org",
+  "entity": {"@type": "Organization", "name": "x"},
+  "derivation_config": {"hash_function": "hmac_sha512", "hardened": true},
+  "output_format": "jwk"
+}"#;
+        let kd = KeyDerivation::from_json(json).unwrap();
+        assert_eq!(kd.output_format, Some(OutputFormat::Jwk));
+        assert!(serde_json::to_string(&kd)
+            .unwrap()
+            .contains(r#""output_format":"jwk""#));
+        assert!(KeyDerivation::from_json(&json.replace("jwk", "pgp-armor")).is_err());
+
+        // Carried in crypto-entity version 3 under key 13
+        let ur = kd.to_ur_string(false).unwrap();
+        let (_, cbor) = ur::decode(&ur).unwrap();
+        assert_eq!(&cbor[..3], &[0xa6, 0x00, 0x03]);
+        let decoded = KeyDerivation::from_ur_string(&ur).unwrap();
+        assert_eq!(decoded.output_format, Some(OutputFormat::Jwk));
+    }
+
+    #[test]
+    fn test_key_type() {
+        let json = r#"{
+  "schema_type": "schema_org",
+  "entity": {"@type": "Organization", "name": "x"},
+  "derivation_config": {"hash_function": "hmac_sha512", "hardened": true}
+}"#;
+        let kd = KeyDerivation::from_json(json).unwrap();
+        assert_eq!(kd.derivation_config.key_type, KeyTypeConfig::Ed25519);
+        assert!(!serde_json::to_string(&kd).unwrap().contains("key_type"));
+
+        for (name, key_type) in [
+            ("x25519", KeyTypeConfig::X25519),
+            ("secp256k1", KeyTypeConfig::Secp256k1),
+            ("p256", KeyTypeConfig::P256),
+        ] {
+            let with_type = json.replace(
+                r#""hardened": true"#,
+                &format!(r#""hardened": true, "key_type": "{}""#, name),
+            );
+            let kd = KeyDerivation::from_json(&with_type).unwrap();
+            assert_eq!(kd.derivation_config.key_type, key_type);
+            assert_eq!(key_type.name(), name);
+        }
+        assert!(KeyDerivation::from_json(&json.replace(
+            r#""hardened": true"#,
+            r#""hardened": true, "key_type": "rsa""#
+        ))
+        .is_err());
+    }
+
+    #[test]
+    fn test_hash_exclude() {
+        let entity = |fields: &str, exclude: &str| {
+            KeyDerivation::from_json(&format!(
+                r#"{{
+  "schema_type": "schema_org",
+  "entity": {{"@type": "Organization", "name": "x"{}}},
+  "derivation_config": {{"hash_function": "hmac_sha512", "hardened": true, "hash_exclude": [{}]}}
+}}"#,
+                fields, exclude
+            ))
+        };
+        let canonical = |fields: &str, exclude: &str| {
+            String::from_utf8(entity(fields, exclude).unwrap().canonical_entity().unwrap()).unwrap()
+        };
+
+        // Excluded fields, present or not, don't reach the hash
+        let base = canonical("", r#""/comment", "/about/date""#);
+        assert_eq!(
+            base,
+            r#"{"entity":{"@type":"Organization","name":"x"},"hash_exclude":["/about/date","/comment"]}"#
+        );
+        assert_eq!(
+            canonical(r#", "comment": "v2""#, r#""/comment", "/about/date""#),
+            base
+        );
+        assert_eq!(
+            canonical(
+                r#", "about": {"date": "2026-01-01", "id": 7}"#,
+                r#""/about/date", "/comment", "/comment""#
+            ),
+            r#"{"entity":{"@type":"Organization","about":{"id":7},"name":"x"},"hash_exclude":["/about/date","/comment"]}"#
+        );
+        assert_eq!(
+            canonical(r#", "a/b~": 1"#, r#""/a~1b~0""#),
+            canonical("", r#""/a~1b~0""#)
+        );
+
+        // The exclusion list is part of the hash; an empty list is no list
+        assert_ne!(canonical("", r#""/comment""#), canonical("", ""));
+        assert_eq!(canonical("", ""), r#"{"@type":"Organization","name":"x"}"#);
+        assert!(!serde_json::to_string(&entity("", "").unwrap())
+            .unwrap()
+            .contains("hash_exclude"));
+
+        for invalid in [r#""comment""#, r#""""#, r#""/a~2""#] {
+            assert!(entity("", invalid).is_err(), "{}", invalid);
+        }
+        assert!(entity(r#", "tags": ["a"]"#, r#""/tags/0""#).is_err());
+
+        let none = r#"{
+  "schema_type": "schema_org",
+  "entity": {"@type": "Organization", "name": "x"},
+  "derivation_config": {"hash_function": "hmac_sha512", "hardened": true,
+                        "canonicalization": "none", "hash_exclude": ["/comment"]}
+}"#;
+        assert!(KeyDerivation::from_json(none).is_err());
+    }
+
+    #[test]
+    fn test_hash_include() {
+        let entity = |fields: &str, config: &str| {
+            KeyDerivation::from_json(&format!(
+                r#"{{
+  "schema_type": "schema_org",
+  "entity": {{"@type": "Organization", "name": "x"{}}},
+  "derivation_config": {{"hash_function": "hmac_sha512", "hardened": true{}}}
+}}"#,
+                fields, config
+            ))
+        };
+        let canonical = |fields: &str, config: &str| {
+            String::from_utf8(entity(fields, config).unwrap().canonical_entity().unwrap()).unwrap()
+        };
+
+        let include = r#", "hash_include": ["/name", "/about/id", "/@type"]"#;
+        let base = canonical(r#", "about": {"id": 7, "date": "2026"}"#, include);
+        assert_eq!(
+            base,
+            r#"{"entity":{"@type":"Organization","about":{"id":7},"name":"x"},"hash_include":["/@type","/about/id","/name"]}"#
+        );
+        // Fields outside the allowlist don't affect the hash
+        assert_eq!(
+            canonical(
+                r#", "about": {"id": 7, "date": "2027"}, "comment": "new""#,
+                include
+            ),
+            base
+        );
+        assert_ne!(
+            canonical(r#", "about": {"id": 8, "date": "2026"}"#, include),
+            base
+        );
+
+        // Included fields must be present and reached through objects
+        assert!(entity("", include).is_err());
+        assert!(entity(
+            r#", "tags": [{"id": 1}]"#,
+            r#", "hash_include": ["/tags/0/id"]"#
+        )
+        .is_err());
+        assert!(entity("", r#", "hash_include": ["name"]"#).is_err());
+        assert!(entity(
+            "",
+            r#", "hash_include": ["/name"], "hash_exclude": ["/comment"]"#
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn test_salt() {
+        let entity = |config: &str| {
+            KeyDerivation::from_json(&format!(
+                r#"{{
+  "schema_type": "schema_org",
+  "entity": {{"@type": "Organization", "name": "x"}},
+  "derivation_config": {{"hash_function": "hmac_sha512", "hardened": true{}}}
+}}"#,
+                config
+            ))
+        };
+
+        let unsalted = entity("").unwrap();
+        assert_eq!(
+            unsalted.hash_input().unwrap(),
+            unsalted.canonical_entity().unwrap()
+        );
+
+        let salted = entity(r#", "salt": "tenant-a""#).unwrap();
+        let mut expected = b"bip-keychain-salt\x00\x00\x00\x08tenant-a".to_vec();
+        expected.extend_from_slice(&unsalted.canonical_entity().unwrap());
+        assert_eq!(salted.hash_input().unwrap(), expected);
+        // The salt changes only the hash input, not the entity's identity
+        assert_eq!(
+            salted.canonical_entity().unwrap(),
+            unsalted.canonical_entity().unwrap()
+        );
+        assert_ne!(
+            entity(r#", "salt": "tenant-b""#)
+                .unwrap()
+                .hash_input()
+                .unwrap(),
+            salted.hash_input().unwrap()
+        );
+
+        assert!(entity(r#", "salt": """#).is_err());
+        assert!(serde_json::to_string(&salted)
+            .unwrap()
+            .contains(r#""salt":"tenant-a""#));
+    }
+
+    #[test]
+    fn test_not_after() {
+        let with_not_after = |not_after: &str| {
+            KeyDerivation::from_json(&format!(
+                r#"{{
+  "schema_type": "schema_org",
+  "entity": {{"@type": "Organization", "name": "x"}},
+  "derivation_config": {{"hash_function": "hmac_sha512", "hardened": true}},
+  "metadata": {{"owner": "ops", "not_after": {}}}
+}}"#,
+                not_after
+            ))
+        };
+
+        // Expected values from Python's datetime
+        for (not_after, seconds) in [
+            (r#""2025-06-30""#, 1_751_327_999),
+            (r#""2026-06-30T14:00:00+02:00""#, 1_782_820_800),
+            (r#""2024-02-29T00:00:00.250Z""#, 1_709_164_800),
+            (r#""1970-01-01t00:00:00-05:00""#, 18_000),
+            ("1751327999", 1_751_327_999),
+        ] {
+            let kd = with_not_after(not_after).unwrap();
+            assert_eq!(kd.not_after().unwrap(), Some(seconds), "{}", not_after);
+            assert!(!kd.is_expired_at(seconds).unwrap());
+            assert!(kd.is_expired_at(seconds + 1).unwrap());
+        }
+
+        for invalid in [
+            r#""2025-13-01""#,
+            r#""2025-02-29""#,
+            r#""2025-6-30""#,
+            r#""2025-06-30T24:00:00Z""#,
+            r#""2025-06-30T12:00:00""#,
+            r#""2025-06-30T12:00:00+0200""#,
+            r#""1969-12-31""#,
+            "-1",
+            "true",
+        ] {
+            assert!(with_not_after(invalid).is_err(), "{}", invalid);
+        }
+
+        let kd = KeyDerivation::from_json(
+            r#"{
+  "schema_type": "schema_org",
+  "entity": {"@type": "Organization", "name": "x"},
+  "derivation_config": {"hash_function": "hmac_sha512", "hardened": true}
+}"#,
+        )
+        .unwrap();
+        assert_eq!(kd.not_after().unwrap(), None);
+        assert!(!kd.is_expired_at(u64::MAX).unwrap());
+    }
+
+    #[test]
+    fn test_entity_version() {
+        let with_version = |version: &str| {
+            format!(
+                r#"{{{}
+  "schema_type": "schema_org",
+  "entity": {{"@type": "Organization", "name": "x"}},
+  "derivation_config": {{"hash_function": "hmac_sha512", "hardened": true}}
+}}"#,
+                version
+            )
+        };
+
+        let unversioned = KeyDerivation::from_json(&with_version("")).unwrap();
+        let v1 = KeyDerivation::from_json(&with_version(r#""version": 1,"#)).unwrap();
+        let current = KeyDerivation::from_json(&with_version(r#""version": 2,"#)).unwrap();
+        assert_eq!(unversioned.version, Some(ENTITY_VERSION));
+        assert_eq!(current.version, Some(ENTITY_VERSION));
+        assert_eq!(
+            current.canonical_entity().unwrap(),
+            unversioned.canonical_entity().unwrap()
+        );
+
+        // Version 1 files are migrated to untagged hashing, keeping their keys
+        for old in [&unversioned, &v1] {
+            assert_eq!(old.derivation_config.domain_tag, DomainTagConfig::None);
+            assert_eq!(old.hash_input().unwrap(), old.canonical_entity().unwrap());
+        }
+        assert_eq!(current.derivation_config.domain_tag, DomainTagConfig::V1);
+        let mut tagged = b"BIP-KEYCHAIN/v1".to_vec();
+        tagged.extend_from_slice(&current.canonical_entity().unwrap());
+        assert_eq!(current.hash_input().unwrap(), tagged);
+        let untagged = KeyDerivation::from_json(&with_version(r#""version": 2,"#).replace(
+            r#""hardened": true"#,
+            r#""hardened": true, "domain_tag": "none""#,
+        ))
+        .unwrap();
+        assert_eq!(untagged.hash_input().unwrap(), v1.hash_input().unwrap());
+
+        // A migrated file serializes as the current version and reads back the same
+        let rewritten = serde_json::to_string(&v1).unwrap();
+        assert!(rewritten.contains(r#""domain_tag":"none""#));
+        assert_eq!(
+            KeyDerivation::from_json(&rewritten)
+                .unwrap()
+                .hash_input()
+                .unwrap(),
+            v1.hash_input().unwrap()
+        );
+
+        assert!(KeyDerivation::from_json(&with_version(r#""version": 0,"#)).is_err());
+        let error = KeyDerivation::from_json(&with_version(r#""version": 3,"#)).unwrap_err();
+        assert!(error.to_string().contains("newer than this build supports"));
+
+        // Entities with a version encode as crypto-entity version 2, which
+        // adds the key for the entity version
+        let ur = current.to_ur_string(false).unwrap();
+        let (_, cbor) = ur::decode(&ur).unwrap();
+        assert_eq!(&cbor[..3], &[0xa5, 0x00, 0x02]);
+        assert_eq!(
+            KeyDerivation::from_ur_string(&ur).unwrap().version,
+            Some(ENTITY_VERSION)
+        );
+        let migrated_ur = v1.to_ur_string(false).unwrap();
+        assert_eq!(
+            KeyDerivation::from_ur_string(&migrated_ur)
+                .unwrap()
+                .hash_input()
+                .unwrap(),
+            v1.hash_input().unwrap()
+        );
+        // Key 12 is not part of version 1
+        let mut downgraded = cbor.clone();
+        downgraded[2] = 0x01;
+        assert!(KeyDerivation::from_cbor(&downgraded).is_err());
+    }
+
+    #[test]
+    fn test_entity_ur_rejects_source_text_entities() {
+        let json = r#"{
+  "schema_type": "schema_org",
+  "entity": {"@type": "Person",   "name": "Alice"},
+  "derivation_config": {"hash_function": "hmac_sha512", "hardened": true, "canonicalization": "none"}
+}"#;
+        let key_derivation = KeyDerivation::from_json(json).unwrap();
+        assert!(key_derivation.to_ur_string(false).is_err());
     }
 }
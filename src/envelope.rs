@@ -0,0 +1,483 @@
+//! Gordian Envelope wrapping and signing of entities
+//!
+//! Builds the subset of Blockchain Commons Gordian Envelope needed to
+//! publish an entity as a signed, digest-addressable document:
+//! - a leaf subject holding the entity as dCBOR
+//! - an optional `'signed': Signature` assertion made with the entity's
+//!   derived Ed25519 key over the subject digest
+//!
+//! Envelopes are emitted as CBOR (`#6.200`) or as a single-part
+//! `ur:envelope/...` string using minimal Bytewords.
+//!
+//! Existing envelopes can be parsed from either form, which is how
+//! `gordian_envelope` entities are ingested. Parsing checks that the
+//! encoding is canonical dCBOR and that assertions are in digest order, so
+//! a parsed envelope re-encodes to exactly the input bytes. Elided parts
+//! are kept as their digests; encrypted, compressed, and wrapped envelopes
+//! are rejected.
+
+use crate::{
+    dcbor::{self, MAJOR_ARRAY, MAJOR_BYTES, MAJOR_MAP, MAJOR_TAG, MAJOR_UNSIGNED},
+    entity::KeyDerivation,
+    error::{BipKeychainError, Result},
+    output::Ed25519Keypair,
+    ur,
+};
+use ed25519_dalek::{Signature, Signer, Verifier, VerifyingKey};
+use sha2::{Digest, Sha256};
+
+// CBOR tags from the Blockchain Commons tag registry
+const TAG_ENVELOPE: u64 = 200;
+const TAG_LEAF: u64 = 201;
+const TAG_KNOWN_VALUE: u64 = 40000;
+const TAG_SIGNATURE: u64 = 40020;
+
+/// Byte length of an elided envelope's digest
+const DIGEST_LEN: usize = 32;
+
+/// Known value `'signed'`
+const KNOWN_VALUE_SIGNED: u64 = 3;
+
+/// Signature scheme discriminator for Ed25519 in `#6.40020([2, bytes])`
+const SIGNATURE_SCHEME_ED25519: u64 = 2;
+
+/// A Gordian Envelope
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Envelope {
+    /// A CBOR value (stored as its encoded bytes, without the leaf tag)
+    Leaf(Vec<u8>),
+    /// A registered known value such as `'signed'`
+    KnownValue(u64),
+    /// A predicate/object pair attached to a subject
+    Assertion {
+        predicate: Box<Envelope>,
+        object: Box<Envelope>,
+    },
+    /// A subject with one or more assertions, kept sorted by digest
+    Node {
+        subject: Box<Envelope>,
+        assertions: Vec<Envelope>,
+    },
+    /// An elided envelope, represented only by its digest
+    Elided([u8; DIGEST_LEN]),
+}
+
+impl Envelope {
+    /// Wrap an entity's JSON as a dCBOR leaf envelope
+    pub fn from_entity(key_derivation: &KeyDerivation) -> Self {
+        Envelope::Leaf(dcbor::encode_json(&key_derivation.entity.to_value()))
+    }
+
+    /// Parse tagged envelope CBOR (`#6.200(envelope-content)`)
+    pub fn from_cbor(cbor: &[u8]) -> Result<Self> {
+        let mut decoder = dcbor::Decoder::new(cbor);
+        match decoder
+            .read_head()
+            .map_err(BipKeychainError::EnvelopeError)?
+        {
+            (MAJOR_TAG, TAG_ENVELOPE) => {}
+            _ => {
+                return Err(BipKeychainError::EnvelopeError(
+                    "expected envelope tag #6.200".to_string(),
+                ))
+            }
+        }
+        Self::parse_all(decoder)
+    }
+
+    /// Parse a single-part `ur:envelope/...` string
+    pub fn from_ur_string(ur: &str) -> Result<Self> {
+        let (ur_type, content) = ur::decode(ur)?;
+        if ur_type != "envelope" {
+            return Err(BipKeychainError::UrError(format!(
+                "expected ur:envelope, got ur:{}",
+                ur_type
+            )));
+        }
+        Self::parse_all(dcbor::Decoder::new(&content))
+    }
+
+    fn parse_all(mut decoder: dcbor::Decoder) -> Result<Self> {
+        let envelope = Self::parse_content(&mut decoder, 0)?;
+        if !decoder.is_empty() {
+            return Err(BipKeychainError::EnvelopeError(
+                "trailing bytes after envelope".to_string(),
+            ));
+        }
+        Ok(envelope)
+    }
+
+    fn parse_content(decoder: &mut dcbor::Decoder, depth: usize) -> Result<Self> {
+        let error = |message: &str| BipKeychainError::EnvelopeError(message.to_string());
+        if depth > dcbor::MAX_DEPTH {
+            return Err(error("envelope nesting is too deep"));
+        }
+
+        match decoder
+            .read_head()
+            .map_err(BipKeychainError::EnvelopeError)?
+        {
+            (MAJOR_TAG, TAG_LEAF) => {
+                let cbor = decoder
+                    .read_item()
+                    .map_err(BipKeychainError::EnvelopeError)?;
+                Ok(Envelope::Leaf(cbor.to_vec()))
+            }
+            (MAJOR_TAG, TAG_ENVELOPE) => Err(error("wrapped envelopes are not supported")),
+            (MAJOR_TAG, tag) => Err(BipKeychainError::EnvelopeError(format!(
+                "unsupported envelope case #6.{} (encrypted or compressed?)",
+                tag
+            ))),
+            (MAJOR_UNSIGNED, value) => Ok(Envelope::KnownValue(value)),
+            (MAJOR_BYTES, len) if len == DIGEST_LEN as u64 => {
+                let digest = decoder
+                    .read_bytes(len)
+                    .map_err(BipKeychainError::EnvelopeError)?;
+                Ok(Envelope::Elided(digest.try_into().expect("length checked")))
+            }
+            (MAJOR_MAP, 1) => Ok(Envelope::Assertion {
+                predicate: Box::new(Self::parse_content(decoder, depth + 1)?),
+                object: Box::new(Self::parse_content(decoder, depth + 1)?),
+            }),
+            (MAJOR_ARRAY, len) if len >= 2 => {
+                let subject = Box::new(Self::parse_content(decoder, depth + 1)?);
+                let mut assertions: Vec<Envelope> = Vec::new();
+                for _ in 1..len {
+                    let assertion = Self::parse_content(decoder, depth + 1)?;
+                    if !matches!(assertion, Envelope::Assertion { .. } | Envelope::Elided(_)) {
+                        return Err(error("node members after the subject must be assertions"));
+                    }
+                    if assertions
+                        .last()
+                        .is_some_and(|last| last.digest() >= assertion.digest())
+                    {
+                        return Err(error("assertions are not sorted and unique by digest"));
+                    }
+                    assertions.push(assertion);
+                }
+                Ok(Envelope::Node {
+                    subject,
+                    assertions,
+                })
+            }
+            _ => Err(error("unrecognized envelope structure")),
+        }
+    }
+
+    /// Leaf envelope holding a text string
+    pub fn from_text(text: &str) -> Self {
+        let mut cbor = Vec::new();
+        dcbor::encode_text(&mut cbor, text);
+        Envelope::Leaf(cbor)
+    }
+
+    /// Replace this envelope by its digest, hiding its content
+    pub fn elide(&self) -> Self {
+        Envelope::Elided(self.digest())
+    }
+
+    /// The subject of this envelope (itself, unless it has assertions)
+    pub fn subject(&self) -> &Envelope {
+        match self {
+            Envelope::Node { subject, .. } => subject,
+            other => other,
+        }
+    }
+
+    /// Assertions on this envelope, in digest order
+    pub fn assertions(&self) -> &[Envelope] {
+        match self {
+            Envelope::Node { assertions, .. } => assertions,
+            _ => &[],
+        }
+    }
+
+    /// Add an assertion, keeping assertions sorted and de-duplicated by digest
+    pub fn add_assertion(self, predicate: Envelope, object: Envelope) -> Self {
+        let assertion = Envelope::Assertion {
+            predicate: Box::new(predicate),
+            object: Box::new(object),
+        };
+
+        let (subject, mut assertions) = match self {
+            Envelope::Node {
+                subject,
+                assertions,
+            } => (subject, assertions),
+            other => (Box::new(other), Vec::new()),
+        };
+
+        if !assertions.iter().any(|a| a.digest() == assertion.digest()) {
+            assertions.push(assertion);
+            assertions.sort_by_key(Envelope::digest);
+        }
+
+        Envelope::Node {
+            subject,
+            assertions,
+        }
+    }
+
+    /// Sign the subject digest and attach a `'signed': Signature` assertion
+    pub fn add_signature(self, keypair: &Ed25519Keypair) -> Self {
+        let signature = keypair.signing_key().sign(&self.subject().digest());
+
+        let mut cbor = Vec::new();
+        dcbor::encode_tag(&mut cbor, TAG_SIGNATURE);
+        dcbor::encode_array_header(&mut cbor, 2);
+        dcbor::encode_unsigned(&mut cbor, SIGNATURE_SCHEME_ED25519);
+        dcbor::encode_bytes(&mut cbor, &signature.to_bytes());
+
+        self.add_assertion(
+            Envelope::KnownValue(KNOWN_VALUE_SIGNED),
+            Envelope::Leaf(cbor),
+        )
+    }
+
+    /// Check that the subject carries a valid signature from `public_key`
+    pub fn verify_signature(&self, public_key: &[u8; 32]) -> Result<()> {
+        let verifying_key = VerifyingKey::from_bytes(public_key).map_err(|_| {
+            BipKeychainError::SignatureError("Invalid Ed25519 public key".to_string())
+        })?;
+        let subject_digest = self.subject().digest();
+
+        let verified = self
+            .assertions()
+            .iter()
+            .filter_map(signed_assertion_signature)
+            .any(|signature| verifying_key.verify(&subject_digest, &signature).is_ok());
+
+        if verified {
+            Ok(())
+        } else {
+            Err(BipKeychainError::SignatureError(
+                "Envelope has no valid signature from this key".to_string(),
+            ))
+        }
+    }
+
+    /// SHA-256 digest of this envelope's digest tree
+    pub fn digest(&self) -> [u8; 32] {
+        match self {
+            Envelope::Leaf(cbor) => {
+                let mut image = Vec::new();
+                dcbor::encode_tag(&mut image, TAG_LEAF);
+                image.extend_from_slice(cbor);
+                Sha256::digest(image).into()
+            }
+            Envelope::KnownValue(value) => {
+                let mut image = Vec::new();
+                dcbor::encode_tag(&mut image, TAG_KNOWN_VALUE);
+                dcbor::encode_unsigned(&mut image, *value);
+                Sha256::digest(image).into()
+            }
+            Envelope::Assertion { predicate, object } => {
+                let mut hasher = Sha256::new();
+                hasher.update(predicate.digest());
+                hasher.update(object.digest());
+                hasher.finalize().into()
+            }
+            Envelope::Node {
+                subject,
+                assertions,
+            } => {
+                let mut hasher = Sha256::new();
+                hasher.update(subject.digest());
+                for assertion in assertions {
+                    hasher.update(assertion.digest());
+                }
+                hasher.finalize().into()
+            }
+            Envelope::Elided(digest) => *digest,
+        }
+    }
+
+    /// Tagged CBOR encoding (`#6.200(envelope-content)`)
+    pub fn to_cbor(&self) -> Vec<u8> {
+        let mut cbor = Vec::new();
+        dcbor::encode_tag(&mut cbor, TAG_ENVELOPE);
+        self.append_content(&mut cbor);
+        cbor
+    }
+
+    /// Single-part UR string (`ur:envelope/...`)
+    pub fn to_ur_string(&self) -> String {
+        // The UR type implies the envelope tag, so only the content is encoded
+        let mut content = Vec::new();
+        self.append_content(&mut content);
+        ur::encode("envelope", &content)
+    }
+
+    fn append_content(&self, buf: &mut Vec<u8>) {
+        match self {
+            Envelope::Leaf(cbor) => {
+                dcbor::encode_tag(buf, TAG_LEAF);
+                buf.extend_from_slice(cbor);
+            }
+            Envelope::KnownValue(value) => dcbor::encode_unsigned(buf, *value),
+            Envelope::Assertion { predicate, object } => {
+                dcbor::encode_map_header(buf, 1);
+                predicate.append_content(buf);
+                object.append_content(buf);
+            }
+            Envelope::Node {
+                subject,
+                assertions,
+            } => {
+                dcbor::encode_array_header(buf, 1 + assertions.len());
+                subject.append_content(buf);
+                for assertion in assertions {
+                    assertion.append_content(buf);
+                }
+            }
+            Envelope::Elided(digest) => dcbor::encode_bytes(buf, digest),
+        }
+    }
+}
+
+/// Extract the Ed25519 signature from a `'signed': Signature` assertion
+fn signed_assertion_signature(assertion: &Envelope) -> Option<Signature> {
+    let Envelope::Assertion { predicate, object } = assertion else {
+        return None;
+    };
+    if **predicate != Envelope::KnownValue(KNOWN_VALUE_SIGNED) {
+        return None;
+    }
+    let Envelope::Leaf(cbor) = object.as_ref() else {
+        return None;
+    };
+
+    let mut prefix = Vec::new();
+    dcbor::encode_tag(&mut prefix, TAG_SIGNATURE);
+    dcbor::encode_array_header(&mut prefix, 2);
+    dcbor::encode_unsigned(&mut prefix, SIGNATURE_SCHEME_ED25519);
+    dcbor::encode_head(&mut prefix, 2, 64);
+
+    let bytes = cbor.strip_prefix(prefix.as_slice())?;
+    Signature::from_slice(bytes).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_leaf_ur_matches_reference() {
+        // Reference vector from the Blockchain Commons envelope documentation
+        let envelope = Envelope::from_text("Hello.");
+        assert_eq!(
+            envelope.to_ur_string(),
+            "ur:envelope/tpsoiyfdihjzjzjldmksbaoede"
+        );
+        assert_eq!(hex::encode(envelope.to_cbor()), "d8c8d8c96648656c6c6f2e");
+    }
+
+    #[test]
+    fn test_signed_envelope_verifies() {
+        let key_derivation = KeyDerivation::from_json(
+            r#"{
+                "schema_type": "custom",
+                "entity": {"name": "Alice", "role": "maintainer"},
+                "derivation_config": {"hash_function": "blake2b", "hardened": true}
+            }"#,
+        )
+        .unwrap();
+        let keypair = Ed25519Keypair::from_seed([3u8; 32]);
+        let other = Ed25519Keypair::from_seed([4u8; 32]);
+
+        let unsigned = Envelope::from_entity(&key_derivation);
+        let signed = unsigned.clone().add_signature(&keypair);
+
+        // Signing adds an assertion but leaves the subject untouched
+        assert_eq!(signed.subject(), &unsigned);
+        assert_eq!(signed.assertions().len(), 1);
+        assert_ne!(signed.digest(), unsigned.digest());
+
+        signed
+            .verify_signature(&keypair.public_key_bytes())
+            .unwrap();
+        assert!(signed.verify_signature(&other.public_key_bytes()).is_err());
+        assert!(unsigned
+            .verify_signature(&keypair.public_key_bytes())
+            .is_err());
+    }
+
+    #[test]
+    fn test_parse_round_trips() {
+        let keypair = Ed25519Keypair::from_seed([3u8; 32]);
+        let envelope = Envelope::from_text("Alice")
+            .add_assertion(Envelope::from_text("knows"), Envelope::from_text("Bob"))
+            .add_signature(&keypair);
+
+        let parsed = Envelope::from_ur_string(&envelope.to_ur_string()).unwrap();
+        assert_eq!(parsed, envelope);
+        assert_eq!(Envelope::from_cbor(&envelope.to_cbor()).unwrap(), envelope);
+        parsed
+            .verify_signature(&keypair.public_key_bytes())
+            .unwrap();
+
+        // Eliding an assertion keeps the digest and still parses
+        let Envelope::Node {
+            subject,
+            assertions,
+        } = envelope.clone()
+        else {
+            panic!("expected a node");
+        };
+        let elided = Envelope::Node {
+            subject,
+            assertions: vec![assertions[0].elide(), assertions[1].clone()],
+        };
+        assert_eq!(elided.digest(), envelope.digest());
+        assert_eq!(
+            Envelope::from_ur_string(&elided.to_ur_string()).unwrap(),
+            elided
+        );
+    }
+
+    #[test]
+    fn test_parse_rejects_malformed_envelopes() {
+        let leaf = Envelope::from_text("Hello.");
+
+        // Wrong UR type
+        assert!(Envelope::from_ur_string(&ur::encode("crypto-seed", &[0x01])).is_err());
+        // Missing #6.200 tag on raw CBOR
+        assert!(Envelope::from_cbor(&[0x01]).is_err());
+        // Trailing bytes
+        let mut cbor = leaf.to_cbor();
+        cbor.push(0x00);
+        assert!(Envelope::from_cbor(&cbor).is_err());
+        // Encrypted envelope case (#6.40002)
+        assert!(Envelope::from_cbor(&[0xd8, 0xc8, 0xd9, 0x9c, 0x42, 0x40]).is_err());
+
+        // Assertions out of digest order
+        let a = Envelope::from_text("x").add_assertion(leaf.clone(), leaf.clone());
+        let b = Envelope::from_text("x").add_assertion(Envelope::KnownValue(1), leaf.clone());
+        let mut assertions = vec![a.assertions()[0].clone(), b.assertions()[0].clone()];
+        assertions.sort_by_key(|assertion| std::cmp::Reverse(assertion.digest()));
+        let unsorted = Envelope::Node {
+            subject: Box::new(leaf),
+            assertions,
+        };
+        assert!(Envelope::from_cbor(&unsorted.to_cbor()).is_err());
+    }
+
+    #[test]
+    fn test_entity_key_order_does_not_change_envelope() {
+        let a = KeyDerivation::from_json(
+            r#"{"schema_type": "custom", "entity": {"a": 1, "b": 2},
+                "derivation_config": {"hash_function": "blake2b", "hardened": true}}"#,
+        )
+        .unwrap();
+        let b = KeyDerivation::from_json(
+            r#"{"schema_type": "custom", "entity": {"b": 2, "a": 1},
+                "derivation_config": {"hash_function": "blake2b", "hardened": true}}"#,
+        )
+        .unwrap();
+
+        assert_eq!(
+            Envelope::from_entity(&a).to_ur_string(),
+            Envelope::from_entity(&b).to_ur_string()
+        );
+    }
+}
@@ -42,9 +42,78 @@ pub enum BipKeychainError {
     /// Key output formatting error
     ///
     /// This indicates a problem converting the derived key to the requested format.
-    #[error("Output format error: {0}\n\nHelp: Supported formats:\n  - seed (raw 32-byte seed as hex)\n  - public-key (Ed25519 public key as hex)\n  - private-key (Ed25519 private key as hex)\n  - ssh (OpenSSH public key format)\n  - json (complete JSON with all keys)")]
+    #[error("Output format error: {0}\n\nHelp: Supported formats:\n  - seed (raw 32-byte seed as hex)\n  - public-key (public key as hex: Ed25519, or the entity's key_type)\n  - private-key (private key as hex)\n  - public-key-pem / private-key-pem (keys as PEM, for OpenSSL)\n  - ssh (OpenSSH public key format)\n  - sshfp (SSHFP DNS record for a host key)\n  - tlsa (TLSA record pinning the key for DANE)\n  - csr (PKCS#10 certificate signing request)\n  - jwk / jwk-private / jwk-thumbprint (JSON Web Key, e.g. an ACME account key)\n  - tuf-key / tuf-key-private / tuf-keyid (TUF and in-toto key metadata)\n  - omemo / omemo-private (XMPP OMEMO identity key)\n  - tor-hidden-service (Tor v3 onion address)\n  - peer-id / libp2p-private (libp2p / IPFS node identity)\n  - dnskey / ds / dnssec-private (DNSSEC key for the entity's zone)\n  - json (complete JSON with all keys)\n  - password (deterministic password from the key)\n  - otpauth (TOTP enrollment URI from the key)")]
     OutputError(String),
 
+    /// Seed could not be loaded from or saved to its source
+    ///
+    /// Raised by seed backends other than BIP_KEYCHAIN_SEED, such as the OS
+    /// credential store.
+    #[error("Seed source error: {0}\n\nHelp: Check that the seed source is available and holds a seed.\nYou can always fall back to: export BIP_KEYCHAIN_SEED=\"your seed phrase...\"")]
+    SeedSourceError(String),
+
+    /// Malformed or inconsistent SSKR share
+    ///
+    /// Raised when a share header cannot be parsed, or when a set of shares
+    /// does not belong to the same split.
+    #[error("Invalid SSKR share: {0}\n\nHelp: Shares must be hex-encoded SSKR shares from the same split.\nCheck for typos and make sure you are not mixing shares from different backups.")]
+    InvalidShare(String),
+
+    /// Malformed manifest or unreadable manifest entry
+    #[error("Manifest error: {0}\n\nHelp: A manifest is JSON of the form:\n  {{\"entities\": [{{\"file\": \"entity.json\", \"comment\": \"optional\", \"format\": \"ssh\", \"output\": \"optional.pub\"}}]}}\nEntity paths are relative to the manifest file.")]
+    ManifestError(String),
+
+    /// SSH agent protocol error
+    ///
+    /// Raised when talking to an SSH agent fails or the agent refuses a
+    /// request.
+    #[error("SSH agent error: {0}\n\nHelp: Check that SSH_AUTH_SOCK points to a running agent (try: ssh-add -l).")]
+    AgentError(String),
+
+    /// Signature creation or verification failed
+    #[error("Signature error: {0}\n\nHelp: Check that the signature, message, and namespace all match what was signed.")]
+    SignatureError(String),
+
+    /// Malformed Uniform Resource string
+    #[error("Invalid UR: {0}\n\nHelp: Expected a single-part UR such as ur:envelope/... with a valid Bytewords checksum.")]
+    UrError(String),
+
+    /// Malformed or unsupported Gordian Envelope
+    #[error("Invalid envelope: {0}\n\nHelp: Expected a dCBOR-encoded envelope of leaves, known values, assertions, and elided digests.\nEncrypted and compressed envelopes are not supported.")]
+    EnvelopeError(String),
+
+    /// Malformed revocation list or revoked key
+    #[error("Revocation error: {0}\n\nHelp: A revocation list is JSON of the form:\n  {{\"revocations\": [{{\"public_key\": \"<64 hex chars>\", \"reason\": \"...\", \"revoked_at\": <unix seconds>}}]}}\nTo derive a revoked key anyway, pass --allow-revoked.")]
+    RevocationError(String),
+
+    /// Malformed or tampered audit log
+    #[error("Audit log error: {0}\n\nHelp: The audit log is append-only; each line must be an unmodified record in its original order.\nIf verification fails, the log was edited after it was written.")]
+    AuditError(String),
+
+    /// Malformed CLI configuration file
+    #[error("Config error: {0}\n\nHelp: config.toml keys are long option names, e.g.\n  seed-source = \"env\"\n  format = [\"ssh\", \"public-key\"]\n  parent-entropy = \"default\"  # or \"require\", or hex\n  output-dir = \"~/keys\"")]
+    ConfigError(String),
+
+    /// Encryption or decryption to a derived key failed
+    #[error("Encryption error: {0}\n\nHelp: Decrypt with the same entity (and parent entropy) the file was encrypted to.")]
+    EncryptionError(String),
+
+    /// Entity is past its `metadata.not_after`
+    #[error("Entity expired: {0}\n\nHelp: The entity's metadata.not_after date has passed, so its key is due for rotation.\nRotate to a new entity (e.g. bump key_index) and update not_after, or pass --allow-expired to derive it anyway.")]
+    ExpiredEntity(String),
+
+    /// Two entities share a derivation path, or the index registry is malformed
+    #[error("Index registry error: {0}\n\nHelp: Two distinct entities whose hashes give the same index derive the same key.\nChange one of them (e.g. add a salt, or use index_levels: 2) so it moves to another path.")]
+    IndexRegistryError(String),
+
+    /// A transparency log rejected an entry, or its inclusion proof is invalid
+    #[error("Transparency log error: {0}\n\nHelp: Check the log URL and that the record was saved for this exact document.\nAn inclusion proof that does not verify means the log entry does not match the record; do not trust it.")]
+    TransparencyLogError(String),
+
+    /// A key backend (hardware wallet, HSM) is unavailable or refused a request
+    #[error("Key backend error: {0}\n\nHelp: Check that the device or token is connected and unlocked, and that the right app or slot is selected.\nConfirm the request on the device if it asks.")]
+    BackendError(String),
+
     /// General I/O error
     ///
     /// File system operations failed (reading entity JSON, etc.)
@@ -52,4 +121,196 @@ pub enum BipKeychainError {
     IoError(#[from] std::io::Error),
 }
 
+/// Stable, machine-readable category of a [`BipKeychainError`]
+///
+/// Codes and exit codes never change once released; new kinds get new
+/// values. The CLI exits with [`ErrorKind::exit_code`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum ErrorKind {
+    /// [`BipKeychainError::InvalidEntity`]
+    InvalidEntity,
+    /// [`BipKeychainError::HashError`]
+    Hash,
+    /// [`BipKeychainError::Bip32Error`]
+    Bip32,
+    /// [`BipKeychainError::InvalidSeedPhrase`]
+    InvalidSeedPhrase,
+    /// [`BipKeychainError::OutputError`]
+    Output,
+    /// [`BipKeychainError::SeedSourceError`]
+    SeedSource,
+    /// [`BipKeychainError::InvalidShare`]
+    InvalidShare,
+    /// [`BipKeychainError::ManifestError`]
+    Manifest,
+    /// [`BipKeychainError::AgentError`]
+    Agent,
+    /// [`BipKeychainError::SignatureError`]
+    Signature,
+    /// [`BipKeychainError::UrError`]
+    Ur,
+    /// [`BipKeychainError::EnvelopeError`]
+    Envelope,
+    /// [`BipKeychainError::RevocationError`]
+    Revocation,
+    /// [`BipKeychainError::AuditError`]
+    Audit,
+    /// [`BipKeychainError::ConfigError`]
+    Config,
+    /// [`BipKeychainError::IoError`]
+    Io,
+    /// [`BipKeychainError::EncryptionError`]
+    Encryption,
+    /// [`BipKeychainError::ExpiredEntity`]
+    ExpiredEntity,
+    /// [`BipKeychainError::IndexRegistryError`]
+    IndexRegistry,
+    /// [`BipKeychainError::TransparencyLogError`]
+    TransparencyLog,
+    /// [`BipKeychainError::BackendError`]
+    Backend,
+}
+
+impl ErrorKind {
+    /// Every kind, in exit code order
+    pub const ALL: &'static [ErrorKind] = &[
+        ErrorKind::InvalidEntity,
+        ErrorKind::Hash,
+        ErrorKind::Bip32,
+        ErrorKind::InvalidSeedPhrase,
+        ErrorKind::Output,
+        ErrorKind::SeedSource,
+        ErrorKind::InvalidShare,
+        ErrorKind::Manifest,
+        ErrorKind::Agent,
+        ErrorKind::Signature,
+        ErrorKind::Ur,
+        ErrorKind::Envelope,
+        ErrorKind::Revocation,
+        ErrorKind::Audit,
+        ErrorKind::Config,
+        ErrorKind::Io,
+        ErrorKind::Encryption,
+        ErrorKind::ExpiredEntity,
+        ErrorKind::IndexRegistry,
+        ErrorKind::TransparencyLog,
+        ErrorKind::Backend,
+    ];
+
+    /// Stable snake_case identifier, e.g. `invalid_entity`
+    pub fn code(self) -> &'static str {
+        match self {
+            ErrorKind::InvalidEntity => "invalid_entity",
+            ErrorKind::Hash => "hash",
+            ErrorKind::Bip32 => "bip32",
+            ErrorKind::InvalidSeedPhrase => "invalid_seed_phrase",
+            ErrorKind::Output => "output",
+            ErrorKind::SeedSource => "seed_source",
+            ErrorKind::InvalidShare => "invalid_share",
+            ErrorKind::Manifest => "manifest",
+            ErrorKind::Agent => "agent",
+            ErrorKind::Signature => "signature",
+            ErrorKind::Ur => "ur",
+            ErrorKind::Envelope => "envelope",
+            ErrorKind::Revocation => "revocation",
+            ErrorKind::Audit => "audit",
+            ErrorKind::Config => "config",
+            ErrorKind::Io => "io",
+            ErrorKind::Encryption => "encryption",
+            ErrorKind::ExpiredEntity => "expired_entity",
+            ErrorKind::IndexRegistry => "index_registry",
+            ErrorKind::TransparencyLog => "transparency_log",
+            ErrorKind::Backend => "backend",
+        }
+    }
+
+    /// Process exit code for the CLI (10-30; 1 is any other failure and
+    /// 2 a command-line usage error)
+    pub fn exit_code(self) -> u8 {
+        match self {
+            ErrorKind::InvalidEntity => 10,
+            ErrorKind::Hash => 11,
+            ErrorKind::Bip32 => 12,
+            ErrorKind::InvalidSeedPhrase => 13,
+            ErrorKind::Output => 14,
+            ErrorKind::SeedSource => 15,
+            ErrorKind::InvalidShare => 16,
+            ErrorKind::Manifest => 17,
+            ErrorKind::Agent => 18,
+            ErrorKind::Signature => 19,
+            ErrorKind::Ur => 20,
+            ErrorKind::Envelope => 21,
+            ErrorKind::Revocation => 22,
+            ErrorKind::Audit => 23,
+            ErrorKind::Config => 24,
+            ErrorKind::Io => 25,
+            ErrorKind::Encryption => 26,
+            ErrorKind::ExpiredEntity => 27,
+            ErrorKind::IndexRegistry => 28,
+            ErrorKind::TransparencyLog => 29,
+            ErrorKind::Backend => 30,
+        }
+    }
+}
+
+impl std::fmt::Display for ErrorKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.code())
+    }
+}
+
+impl BipKeychainError {
+    /// Machine-readable category of this error
+    pub fn kind(&self) -> ErrorKind {
+        match self {
+            BipKeychainError::InvalidEntity(_) => ErrorKind::InvalidEntity,
+            BipKeychainError::HashError(_) => ErrorKind::Hash,
+            BipKeychainError::Bip32Error(_) => ErrorKind::Bip32,
+            BipKeychainError::InvalidSeedPhrase(_) => ErrorKind::InvalidSeedPhrase,
+            BipKeychainError::OutputError(_) => ErrorKind::Output,
+            BipKeychainError::SeedSourceError(_) => ErrorKind::SeedSource,
+            BipKeychainError::InvalidShare(_) => ErrorKind::InvalidShare,
+            BipKeychainError::ManifestError(_) => ErrorKind::Manifest,
+            BipKeychainError::AgentError(_) => ErrorKind::Agent,
+            BipKeychainError::SignatureError(_) => ErrorKind::Signature,
+            BipKeychainError::UrError(_) => ErrorKind::Ur,
+            BipKeychainError::EnvelopeError(_) => ErrorKind::Envelope,
+            BipKeychainError::RevocationError(_) => ErrorKind::Revocation,
+            BipKeychainError::AuditError(_) => ErrorKind::Audit,
+            BipKeychainError::ConfigError(_) => ErrorKind::Config,
+            BipKeychainError::IoError(_) => ErrorKind::Io,
+            BipKeychainError::EncryptionError(_) => ErrorKind::Encryption,
+            BipKeychainError::ExpiredEntity(_) => ErrorKind::ExpiredEntity,
+            BipKeychainError::IndexRegistryError(_) => ErrorKind::IndexRegistry,
+            BipKeychainError::TransparencyLogError(_) => ErrorKind::TransparencyLog,
+            BipKeychainError::BackendError(_) => ErrorKind::Backend,
+        }
+    }
+}
+
 pub type Result<T> = std::result::Result<T, BipKeychainError>;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_error_codes_are_unique() {
+        let mut codes = std::collections::HashSet::new();
+        let mut exit_codes = std::collections::HashSet::new();
+        for kind in ErrorKind::ALL {
+            assert!(codes.insert(kind.code()), "duplicate code {}", kind);
+            assert!(
+                exit_codes.insert(kind.exit_code()),
+                "duplicate exit code {}",
+                kind
+            );
+            assert!(kind.exit_code() >= 10);
+        }
+
+        let error = BipKeychainError::UrError("bad checksum".to_string());
+        assert_eq!(error.kind(), ErrorKind::Ur);
+        assert_eq!(error.kind().to_string(), "ur");
+    }
+}
@@ -0,0 +1,130 @@
+//! Ethereum message signing (EIP-191)
+//!
+//! Signs with the secp256k1 key of the entity's BIP-32 node, the same key
+//! whose public half [`DerivedKey::secp256k1_public_key`] returns, so
+//! control of an entity-derived Ethereum address can be proven with
+//! `personal_sign` signatures:
+//!
+//! ```text
+//! keccak256("\x19Ethereum Signed Message:\n" || len(message) || message)
+//! ```
+//!
+//! Signatures are 65 bytes, `r || s || v`, with low `s` and `v` = 27 + the
+//! recovery ID, as returned by wallets.
+
+use crate::bip32_wrapper::DerivedKey;
+use crate::error::{BipKeychainError, Result};
+use k256::ecdsa::{RecoveryId, Signature, VerifyingKey};
+use sha3::{Digest, Keccak256};
+
+fn eth_error(message: String) -> BipKeychainError {
+    BipKeychainError::OutputError(message)
+}
+
+/// EIP-191 version 0x45 hash of a message, as signed by `personal_sign`
+pub fn personal_message_hash(message: &[u8]) -> [u8; 32] {
+    let mut data = format!("\x19Ethereum Signed Message:\n{}", message.len()).into_bytes();
+    data.extend_from_slice(message);
+    Keccak256::digest(&data).into()
+}
+
+/// EIP-55 checksummed address of a secp256k1 public key
+fn address(key: &VerifyingKey) -> String {
+    let point = key.to_encoded_point(false);
+    let hash = Keccak256::digest(&point.as_bytes()[1..]);
+    let lower = hex::encode(&hash[12..]);
+    let checksum = hex::encode(Keccak256::digest(lower.as_bytes()));
+    let mixed: String = lower
+        .chars()
+        .zip(checksum.chars())
+        .map(|(c, h)| if h >= '8' { c.to_ascii_uppercase() } else { c })
+        .collect();
+    format!("0x{}", mixed)
+}
+
+/// Checksummed Ethereum address of the derived key
+pub fn eth_address(derived: &DerivedKey) -> String {
+    address(derived.xprv().private_key().verifying_key())
+}
+
+/// Sign a message with `personal_sign` semantics, returning `r || s || v`
+pub fn personal_sign(derived: &DerivedKey, message: &[u8]) -> Result<[u8; 65]> {
+    let (signature, recovery_id) = derived
+        .xprv()
+        .private_key()
+        .sign_prehash_recoverable(&personal_message_hash(message))
+        .map_err(|e| eth_error(format!("Failed to sign message: {}", e)))?;
+
+    let mut encoded = [0u8; 65];
+    encoded[..64].copy_from_slice(&signature.to_bytes());
+    encoded[64] = 27 + recovery_id.to_byte();
+    Ok(encoded)
+}
+
+/// Recover the checksummed address that produced a `personal_sign` signature
+pub fn recover_personal_signer(message: &[u8], signature: &[u8; 65]) -> Result<String> {
+    let invalid = || eth_error("Invalid Ethereum signature".to_string());
+    let signature_rs = Signature::from_slice(&signature[..64]).map_err(|_| invalid())?;
+    // Wallets use 27/28; some libraries emit the bare recovery ID
+    let v = signature[64];
+    let recovery_id = RecoveryId::from_byte(v.checked_sub(27).unwrap_or(v)).ok_or_else(invalid)?;
+    let key = VerifyingKey::recover_from_prehash(
+        &personal_message_hash(message),
+        &signature_rs,
+        recovery_id,
+    )
+    .map_err(|_| invalid())?;
+    Ok(address(&key))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use k256::ecdsa::SigningKey;
+
+    fn key_address(private_key: &str) -> String {
+        let key = SigningKey::from_slice(&hex::decode(private_key).unwrap()).unwrap();
+        address(key.verifying_key())
+    }
+
+    #[test]
+    fn test_keccak256() {
+        assert_eq!(
+            hex::encode(Keccak256::digest(b"")),
+            "c5d2460186f7233c927e7db2dcc703c0e500b653ca82273b7bfad8045d85a470"
+        );
+        assert_eq!(
+            hex::encode(Keccak256::digest(b"abc")),
+            "4e03657aea45a94fc7d47ba826c8d667c0d1e6e33a64a036ec44f58fa12d6c45"
+        );
+    }
+
+    #[test]
+    fn test_addresses() {
+        assert_eq!(
+            key_address("0000000000000000000000000000000000000000000000000000000000000001"),
+            "0x7E5F4552091A69125d5DfCb7b8C2659029395Bdf"
+        );
+        assert_eq!(
+            key_address("4c0883a69102937d6231471b5dbb6204fe5129617082792ae468d01a3f362318"),
+            "0x2c7536E3605D9C16a7a3D7b1898e529396a65c23"
+        );
+    }
+
+    #[test]
+    fn test_personal_sign_round_trip() {
+        let keychain = crate::Keychain::from_seed_bytes(&[7u8; 64]).unwrap();
+        let derived = keychain.derive_bip_keychain_path(0).unwrap();
+        let signature = personal_sign(&derived, b"hello").unwrap();
+
+        assert!(signature[64] == 27 || signature[64] == 28);
+        assert_eq!(
+            recover_personal_signer(b"hello", &signature).unwrap(),
+            eth_address(&derived)
+        );
+        assert_ne!(
+            recover_personal_signer(b"hellO", &signature).unwrap(),
+            eth_address(&derived)
+        );
+    }
+}
@@ -0,0 +1,382 @@
+//! C FFI (`ffi` feature)
+//!
+//! A small C ABI for linking BIP-Keychain into C, C++, and Swift programs.
+//! The header is generated by cbindgen into `include/bip_keychain.h`.
+//!
+//! Every fallible function returns a [`BipkcStatus`]; on failure,
+//! [`bipkc_last_error`] describes what went wrong. Panics never cross the
+//! FFI boundary.
+//!
+//! ```c
+//! BipkcKey *key = NULL;
+//! char *ssh = NULL;
+//! if (bipkc_derive(mnemonic, entity_json, NULL, 0, &key) == BIPKC_STATUS_OK &&
+//!     bipkc_format(key, "ssh", &ssh) == BIPKC_STATUS_OK) {
+//!     puts(ssh);
+//! } else {
+//!     fprintf(stderr, "%s\n", bipkc_last_error());
+//! }
+//! bipkc_string_free(ssh);
+//! bipkc_key_free(key);
+//! ```
+
+use crate::{
+    bip32_wrapper::{DerivedKey, Keychain},
+    derivation::{derive_key_from_entity, DEFAULT_PARENT_ENTROPY},
+    entity::KeyDerivation,
+    error::BipKeychainError,
+    output::{format_key, OutputFormat},
+};
+use std::{
+    cell::RefCell,
+    ffi::{c_char, CStr, CString},
+    panic::{self, AssertUnwindSafe},
+    ptr,
+};
+use zeroize::Zeroize;
+
+/// Result code returned by every fallible FFI function
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BipkcStatus {
+    /// Success
+    Ok = 0,
+    /// A required pointer argument was NULL
+    NullPointer = 1,
+    /// A string argument was not valid UTF-8
+    InvalidUtf8 = 2,
+    /// The seed phrase could not be parsed
+    InvalidSeed = 3,
+    /// The entity JSON could not be parsed
+    InvalidEntity = 4,
+    /// Hashing or BIP-32 derivation failed
+    Derivation = 5,
+    /// Unknown output format, or formatting failed
+    Format = 6,
+    /// Any other library error
+    Internal = 98,
+    /// A Rust panic was caught at the FFI boundary
+    Panic = 99,
+}
+
+/// A derived key together with the entity it was derived from
+///
+/// Opaque to C. Free with [`bipkc_key_free`].
+pub struct BipkcKey {
+    derived: DerivedKey,
+    entity: KeyDerivation,
+}
+
+struct FfiError {
+    status: BipkcStatus,
+    message: String,
+}
+
+impl FfiError {
+    fn new(status: BipkcStatus, message: impl Into<String>) -> Self {
+        Self {
+            status,
+            message: message.into(),
+        }
+    }
+}
+
+impl From<BipKeychainError> for FfiError {
+    fn from(error: BipKeychainError) -> Self {
+        let status = match error {
+            BipKeychainError::InvalidSeedPhrase(_) | BipKeychainError::SeedSourceError(_) => {
+                BipkcStatus::InvalidSeed
+            }
+            BipKeychainError::InvalidEntity(_) => BipkcStatus::InvalidEntity,
+            BipKeychainError::HashError(_) | BipKeychainError::Bip32Error(_) => {
+                BipkcStatus::Derivation
+            }
+            BipKeychainError::OutputError(_) => BipkcStatus::Format,
+            _ => BipkcStatus::Internal,
+        };
+        Self::new(status, error.to_string())
+    }
+}
+
+thread_local! {
+    static LAST_ERROR: RefCell<Option<CString>> = const { RefCell::new(None) };
+}
+
+/// Run an FFI body, catching panics and recording the error message
+fn ffi_call(body: impl FnOnce() -> Result<(), FfiError>) -> BipkcStatus {
+    let result = panic::catch_unwind(AssertUnwindSafe(body)).unwrap_or_else(|_| {
+        Err(FfiError::new(
+            BipkcStatus::Panic,
+            "internal error: panic caught at FFI boundary",
+        ))
+    });
+
+    let (status, message) = match result {
+        Ok(()) => (BipkcStatus::Ok, None),
+        // Messages never contain NUL; fall back to an empty string if one does
+        Err(e) => (e.status, Some(CString::new(e.message).unwrap_or_default())),
+    };
+    LAST_ERROR.with(|last| *last.borrow_mut() = message);
+    status
+}
+
+/// Borrow a NUL-terminated UTF-8 string argument
+///
+/// # Safety
+///
+/// `ptr` must be NULL or point to a NUL-terminated string that outlives `'a`.
+unsafe fn str_arg<'a>(ptr: *const c_char, name: &str) -> Result<&'a str, FfiError> {
+    if ptr.is_null() {
+        return Err(FfiError::new(
+            BipkcStatus::NullPointer,
+            format!("{} must not be NULL", name),
+        ));
+    }
+    CStr::from_ptr(ptr).to_str().map_err(|_| {
+        FfiError::new(
+            BipkcStatus::InvalidUtf8,
+            format!("{} is not valid UTF-8", name),
+        )
+    })
+}
+
+/// Output format names, as accepted by the CLI's `--format`
+fn parse_output_format(name: &str) -> Result<OutputFormat, FfiError> {
+    match name {
+        "seed" => Ok(OutputFormat::HexSeed),
+        "public-key" => Ok(OutputFormat::Ed25519PublicHex),
+        "private-key" => Ok(OutputFormat::Ed25519PrivateHex),
+        "public-key-pem" => Ok(OutputFormat::Ed25519PublicPem),
+        "private-key-pem" => Ok(OutputFormat::Ed25519PrivatePem),
+        "ssh" => Ok(OutputFormat::SshPublicKey),
+        "sshfp" => Ok(OutputFormat::SshfpRecord),
+        "tlsa" => Ok(OutputFormat::TlsaRecord),
+        "csr" => Ok(OutputFormat::Csr),
+        "jwk" => Ok(OutputFormat::Jwk),
+        "jwk-private" => Ok(OutputFormat::JwkPrivate),
+        "jwk-thumbprint" => Ok(OutputFormat::JwkThumbprint),
+        "tuf-key" => Ok(OutputFormat::TufKey),
+        "tuf-key-private" => Ok(OutputFormat::TufKeyPrivate),
+        "tuf-keyid" => Ok(OutputFormat::TufKeyid),
+        "omemo" => Ok(OutputFormat::OmemoIdentity),
+        "omemo-private" => Ok(OutputFormat::OmemoPrivate),
+        "tor-hidden-service" => Ok(OutputFormat::TorHiddenService),
+        "peer-id" => Ok(OutputFormat::Libp2pPeerId),
+        "libp2p-private" => Ok(OutputFormat::Libp2pPrivate),
+        "dnskey" => Ok(OutputFormat::DnssecDnskey),
+        "ds" => Ok(OutputFormat::DnssecDs),
+        "dnssec-private" => Ok(OutputFormat::DnssecPrivate),
+        "gpg" => Ok(OutputFormat::GpgPublicKey),
+        "json" => Ok(OutputFormat::Json),
+        "password" => Ok(OutputFormat::Password),
+        "otpauth" => Ok(OutputFormat::Otpauth),
+        other => Err(FfiError::new(
+            BipkcStatus::Format,
+            format!(
+                "Unknown output format '{}': expected seed, public-key, private-key, public-key-pem, private-key-pem, ssh, sshfp, tlsa, csr, jwk, jwk-private, jwk-thumbprint, tuf-key, tuf-key-private, tuf-keyid, omemo, omemo-private, tor-hidden-service, peer-id, libp2p-private, dnskey, ds, dnssec-private, gpg, json, password, or otpauth",
+                other
+            ),
+        )),
+    }
+}
+
+/// Derive the key for an entity from a BIP-39 mnemonic
+///
+/// `parent_entropy` may be NULL to use the CLI's default parent entropy.
+/// On success `*out_key` receives a key to release with [`bipkc_key_free`].
+///
+/// # Safety
+///
+/// `mnemonic` and `entity_json` must be NULL or NUL-terminated strings.
+/// `parent_entropy` must be NULL or point to `parent_entropy_len` readable
+/// bytes. `out_key` must be NULL or valid for writes.
+#[no_mangle]
+pub unsafe extern "C" fn bipkc_derive(
+    mnemonic: *const c_char,
+    entity_json: *const c_char,
+    parent_entropy: *const u8,
+    parent_entropy_len: usize,
+    out_key: *mut *mut BipkcKey,
+) -> BipkcStatus {
+    ffi_call(|| {
+        if out_key.is_null() {
+            return Err(FfiError::new(
+                BipkcStatus::NullPointer,
+                "out_key must not be NULL",
+            ));
+        }
+        *out_key = ptr::null_mut();
+
+        let mnemonic = str_arg(mnemonic, "mnemonic")?;
+        let entity_json = str_arg(entity_json, "entity_json")?;
+        let parent_entropy = if parent_entropy.is_null() {
+            DEFAULT_PARENT_ENTROPY
+        } else {
+            std::slice::from_raw_parts(parent_entropy, parent_entropy_len)
+        };
+
+        let keychain = Keychain::from_mnemonic(mnemonic)?;
+        let entity = KeyDerivation::from_json(entity_json)?;
+        let derived = derive_key_from_entity(&keychain, &entity, parent_entropy)?;
+
+        *out_key = Box::into_raw(Box::new(BipkcKey { derived, entity }));
+        Ok(())
+    })
+}
+
+/// Format a derived key (`seed`, `public-key`, `private-key`, `ssh`, `gpg`, or `json`)
+///
+/// On success `*out_string` receives a NUL-terminated string to release
+/// with [`bipkc_string_free`].
+///
+/// # Safety
+///
+/// `key` must be NULL or a live key from [`bipkc_derive`]. `format` must be
+/// NULL or a NUL-terminated string. `out_string` must be NULL or valid for
+/// writes.
+#[no_mangle]
+pub unsafe extern "C" fn bipkc_format(
+    key: *const BipkcKey,
+    format: *const c_char,
+    out_string: *mut *mut c_char,
+) -> BipkcStatus {
+    ffi_call(|| {
+        if out_string.is_null() {
+            return Err(FfiError::new(
+                BipkcStatus::NullPointer,
+                "out_string must not be NULL",
+            ));
+        }
+        *out_string = ptr::null_mut();
+
+        let key = key
+            .as_ref()
+            .ok_or_else(|| FfiError::new(BipkcStatus::NullPointer, "key must not be NULL"))?;
+        let format = parse_output_format(str_arg(format, "format")?)?;
+
+        let output = format_key(&key.derived, &key.entity, format)?;
+        let output = CString::new(output)
+            .map_err(|_| FfiError::new(BipkcStatus::Format, "formatted key contains NUL"))?;
+
+        *out_string = output.into_raw();
+        Ok(())
+    })
+}
+
+/// Free a key returned by [`bipkc_derive`]; NULL is ignored
+///
+/// # Safety
+///
+/// `key` must be NULL or a key from [`bipkc_derive`] not already freed.
+#[no_mangle]
+pub unsafe extern "C" fn bipkc_key_free(key: *mut BipkcKey) {
+    if !key.is_null() {
+        drop(Box::from_raw(key));
+    }
+}
+
+/// Wipe and free a string returned by [`bipkc_format`]; NULL is ignored
+///
+/// # Safety
+///
+/// `string` must be NULL or a string from [`bipkc_format`] not already freed.
+#[no_mangle]
+pub unsafe extern "C" fn bipkc_string_free(string: *mut c_char) {
+    if !string.is_null() {
+        // The string may hold private key material
+        CString::from_raw(string).into_bytes().zeroize();
+    }
+}
+
+/// Message for the last failed call on this thread, or NULL
+///
+/// The pointer is valid until the next `bipkc_*` call on the same thread.
+#[no_mangle]
+pub extern "C" fn bipkc_last_error() -> *const c_char {
+    LAST_ERROR.with(|last| {
+        last.borrow()
+            .as_ref()
+            .map_or(ptr::null(), |message| message.as_ptr())
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TEST_MNEMONIC: &str = "abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon about";
+    const ENTITY_JSON: &str = r#"{
+        "schema_type": "schema_org",
+        "entity": {"@type": "SoftwareSourceCode", "name": "bip-keychain"},
+        "derivation_config": {"hash_function": "hmac_sha512", "hardened": true}
+    }"#;
+
+    fn derive(mnemonic: &str, entity_json: &str) -> (BipkcStatus, *mut BipkcKey) {
+        let mnemonic = CString::new(mnemonic).unwrap();
+        let entity_json = CString::new(entity_json).unwrap();
+        let mut key = ptr::null_mut();
+        let status = unsafe {
+            bipkc_derive(
+                mnemonic.as_ptr(),
+                entity_json.as_ptr(),
+                ptr::null(),
+                0,
+                &mut key,
+            )
+        };
+        (status, key)
+    }
+
+    #[test]
+    fn test_derive_and_format_match_library() {
+        let (status, key) = derive(TEST_MNEMONIC, ENTITY_JSON);
+        assert_eq!(status, BipkcStatus::Ok);
+        assert!(bipkc_last_error().is_null());
+
+        let format = CString::new("ssh").unwrap();
+        let mut output = ptr::null_mut();
+        let status = unsafe { bipkc_format(key, format.as_ptr(), &mut output) };
+        assert_eq!(status, BipkcStatus::Ok);
+
+        let entity = KeyDerivation::from_json(ENTITY_JSON).unwrap();
+        let expected = derive_key_from_entity(
+            &Keychain::from_mnemonic(TEST_MNEMONIC).unwrap(),
+            &entity,
+            DEFAULT_PARENT_ENTROPY,
+        )
+        .unwrap();
+        let expected = format_key(&expected, &entity, OutputFormat::SshPublicKey).unwrap();
+        assert_eq!(
+            unsafe { CStr::from_ptr(output) }.to_str().unwrap(),
+            expected
+        );
+
+        unsafe {
+            bipkc_string_free(output);
+            bipkc_key_free(key);
+        }
+    }
+
+    #[test]
+    fn test_errors_set_status_and_message() {
+        let (status, key) = derive("not a mnemonic", ENTITY_JSON);
+        assert_eq!(status, BipkcStatus::InvalidSeed);
+        assert!(key.is_null());
+        assert!(!bipkc_last_error().is_null());
+
+        let (status, _) = derive(TEST_MNEMONIC, "{");
+        assert_eq!(status, BipkcStatus::InvalidEntity);
+
+        let status =
+            unsafe { bipkc_derive(ptr::null(), ptr::null(), ptr::null(), 0, ptr::null_mut()) };
+        assert_eq!(status, BipkcStatus::NullPointer);
+
+        let (_, key) = derive(TEST_MNEMONIC, ENTITY_JSON);
+        let format = CString::new("qr").unwrap();
+        let mut output = ptr::null_mut();
+        let status = unsafe { bipkc_format(key, format.as_ptr(), &mut output) };
+        assert_eq!(status, BipkcStatus::Format);
+        assert!(output.is_null());
+        unsafe { bipkc_key_free(key) };
+    }
+}
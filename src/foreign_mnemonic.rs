@@ -0,0 +1,537 @@
+//! Mnemonics from other wallets
+//!
+//! Recovers the secret behind seeds that are not BIP-39 mnemonics, so a
+//! wallet's existing seed can root a [`Keychain`]:
+//!
+//! - Electrum (2.0+) seeds: BIP-39 English words whose HMAC-SHA512 with
+//!   `"Seed version"` starts with a version prefix. The BIP-32 seed is
+//!   PBKDF2-HMAC-SHA512 of the phrase with salt `"electrum"` + passphrase,
+//!   as in Electrum, so the master key matches the wallet's.
+//! - LND aezeed: 24 BIP-39 English words holding a version, a CRC-32C
+//!   checksum, and a salt around an AEZ-enciphered birthday and 16 bytes of
+//!   entropy. The key is scrypt of the passphrase (`"aezeed"` when empty);
+//!   LND uses the entropy itself as the BIP-32 seed.
+//! - Monero 25-word seeds: 24 words of a 1626-word list encoding the
+//!   32-byte private spend key, plus a checksum word. Monero's English
+//!   wordlist is bundled ([`MoneroWordlist::english`], BSD-3-Clause, see
+//!   `src/wordlists/LICENSE-monero`); other languages' lists can be
+//!   loaded with [`MoneroWordlist::from_text`]. Monero does not
+//!   use BIP-32, so the spend key is used as the BIP-32 seed: keys derived
+//!   from it are new keys, unrelated to the Monero wallet's own.
+//!
+//! Electrum 1.x seeds (from a separate 1626-word list) are not supported.
+
+use crate::{
+    aez,
+    bip32_wrapper::{Keychain, Language},
+    error::{BipKeychainError, Result},
+};
+use hmac::{Hmac, Mac};
+use sha2::Sha512;
+use unicode_normalization::{char::canonical_combining_class, UnicodeNormalization};
+use zeroize::Zeroizing;
+
+/// Electrum seed type, from the seed's version prefix
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ElectrumSeedType {
+    /// Legacy P2PKH wallet (prefix `01`)
+    Standard,
+    /// Native SegWit wallet (prefix `100`)
+    Segwit,
+    /// Two-factor wallet (prefix `101`)
+    TwoFactor,
+    /// Two-factor SegWit wallet (prefix `102`)
+    TwoFactorSegwit,
+}
+
+impl ElectrumSeedType {
+    /// Electrum's name for the seed type
+    pub fn name(self) -> &'static str {
+        match self {
+            ElectrumSeedType::Standard => "standard",
+            ElectrumSeedType::Segwit => "segwit",
+            ElectrumSeedType::TwoFactor => "2fa",
+            ElectrumSeedType::TwoFactorSegwit => "2fa_segwit",
+        }
+    }
+}
+
+/// Hex prefixes of an Electrum seed's version hash
+const ELECTRUM_PREFIXES: [(&str, ElectrumSeedType); 4] = [
+    ("01", ElectrumSeedType::Standard),
+    ("100", ElectrumSeedType::Segwit),
+    ("101", ElectrumSeedType::TwoFactor),
+    ("102", ElectrumSeedType::TwoFactorSegwit),
+];
+
+/// Which wallet a seed came from, and what it records about the seed
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ForeignSeedKind {
+    /// Electrum seed of the given type
+    Electrum(ElectrumSeedType),
+    /// LND aezeed
+    Aezeed {
+        /// Cipher seed version
+        version: u8,
+        /// Days from the Bitcoin genesis block to the wallet's creation
+        birthday: u16,
+    },
+    /// Monero 25-word seed
+    Monero,
+}
+
+/// A seed recovered from another wallet's mnemonic
+pub struct ForeignSeed {
+    /// Where the seed came from
+    pub kind: ForeignSeedKind,
+    /// The entropy the mnemonic encodes (for Monero, the spend key)
+    pub entropy: Zeroizing<Vec<u8>>,
+    /// The BIP-32 seed the keychain is built from
+    pub bip32_seed: Zeroizing<Vec<u8>>,
+}
+
+impl ForeignSeed {
+    /// A keychain rooted at the seed's BIP-32 master key
+    pub fn keychain(&self) -> Result<Keychain> {
+        Keychain::from_seed_bytes(&self.bip32_seed)
+    }
+
+    /// An aezeed's creation date (UTC, `YYYY-MM-DD`), where a wallet rescan
+    /// can start
+    pub fn birthday_date(&self) -> Option<String> {
+        let ForeignSeedKind::Aezeed { birthday, .. } = self.kind else {
+            return None;
+        };
+        // Days since 1970-01-01 to a civil date
+        let days = (BITCOIN_GENESIS_TIME / 86_400 + u64::from(birthday)) as i64 + 719_468;
+        let era = days.div_euclid(146_097);
+        let day_of_era = days - era * 146_097;
+        let year_of_era =
+            (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+        let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+        let month_index = (5 * day_of_year + 2) / 153;
+        let day = day_of_year - (153 * month_index + 2) / 5 + 1;
+        let month = if month_index < 10 {
+            month_index + 3
+        } else {
+            month_index - 9
+        };
+        let year = year_of_era + era * 400 + i64::from(month <= 2);
+        Some(format!("{:04}-{:02}-{:02}", year, month, day))
+    }
+}
+
+/// Unix time of the Bitcoin genesis block, the epoch of aezeed birthdays
+const BITCOIN_GENESIS_TIME: u64 = 1_231_006_505;
+
+const AEZEED_VERSION: u8 = 0;
+const AEZEED_DEFAULT_PASSPHRASE: &str = "aezeed";
+const AEZEED_SALT: std::ops::Range<usize> = 24..29;
+/// scrypt cost of aezeed version 0
+const AEZEED_SCRYPT_N: usize = 32768;
+
+fn seed_error(message: impl Into<String>) -> BipKeychainError {
+    BipKeychainError::InvalidSeedPhrase(message.into())
+}
+
+/// Electrum's text normalization: NFKD, lowercase, accents removed, single
+/// spaces
+fn electrum_normalize(text: &str) -> Zeroizing<String> {
+    let stripped: Zeroizing<String> = Zeroizing::new(
+        text.nfkd()
+            .flat_map(char::to_lowercase)
+            .filter(|&c| canonical_combining_class(c) == 0)
+            .collect(),
+    );
+    Zeroizing::new(stripped.split_whitespace().collect::<Vec<_>>().join(" "))
+}
+
+/// Indices of `phrase`'s words in the BIP-39 English wordlist
+fn english_indices(phrase: &str) -> Result<Vec<u16>> {
+    phrase
+        .split_whitespace()
+        .map(|word| {
+            Language::English.find_word(word).ok_or_else(|| {
+                seed_error(format!("'{}' is not in the BIP-39 English wordlist", word))
+            })
+        })
+        .collect()
+}
+
+/// Recover an Electrum 2.0+ seed; `passphrase` is the optional seed
+/// extension
+pub fn decode_electrum(phrase: &str, passphrase: &str) -> Result<ForeignSeed> {
+    let normalized = electrum_normalize(phrase);
+    let mut mac = Hmac::<Sha512>::new_from_slice(b"Seed version").expect("HMAC takes any key");
+    mac.update(normalized.as_bytes());
+    let version = hex::encode(mac.finalize().into_bytes());
+    let seed_type = ELECTRUM_PREFIXES
+        .iter()
+        .find(|(prefix, _)| version.starts_with(prefix))
+        .map(|&(_, seed_type)| seed_type)
+        .ok_or_else(|| {
+            seed_error(
+                "Not an Electrum seed: no known version prefix \
+                 (Electrum 1.x seeds are not supported)",
+            )
+        })?;
+
+    // The words are the digits of a base-2048 number, least significant first
+    let indices = english_indices(&normalized)?;
+    let mut entropy = Zeroizing::new(vec![0u8; (indices.len() * 11).div_ceil(8)]);
+    for &index in indices.iter().rev() {
+        let mut carry = u32::from(index);
+        for byte in entropy.iter_mut().rev() {
+            let value = u32::from(*byte) * 2048 + carry;
+            *byte = value as u8;
+            carry = value >> 8;
+        }
+    }
+
+    let mut salt = Zeroizing::new(b"electrum".to_vec());
+    salt.extend_from_slice(electrum_normalize(passphrase).as_bytes());
+    let mut bip32_seed = Zeroizing::new(vec![0u8; 64]);
+    pbkdf2::pbkdf2::<Hmac<Sha512>>(normalized.as_bytes(), &salt, 2048, &mut bip32_seed)
+        .expect("HMAC takes any key");
+
+    Ok(ForeignSeed {
+        kind: ForeignSeedKind::Electrum(seed_type),
+        entropy,
+        bip32_seed,
+    })
+}
+
+/// CRC-32C (Castagnoli), the aezeed checksum
+fn crc32c(data: &[u8]) -> u32 {
+    !data.iter().fold(!0u32, |crc, &byte| {
+        (0..8).fold(crc ^ u32::from(byte), |crc, _| {
+            (crc >> 1) ^ (0x82F6_3B78 & (crc & 1).wrapping_neg())
+        })
+    })
+}
+
+/// Recover an LND aezeed; `passphrase` is the cipher seed passphrase
+/// (empty for none)
+pub fn decode_aezeed(phrase: &str, passphrase: &str) -> Result<ForeignSeed> {
+    decode_aezeed_with_cost(phrase, passphrase, AEZEED_SCRYPT_N)
+}
+
+fn decode_aezeed_with_cost(phrase: &str, passphrase: &str, scrypt_n: usize) -> Result<ForeignSeed> {
+    let indices = english_indices(&phrase.to_lowercase())?;
+    if indices.len() != 24 {
+        return Err(seed_error(format!(
+            "An aezeed has 24 words, not {}",
+            indices.len()
+        )));
+    }
+    let mut bytes = Zeroizing::new([0u8; 33]);
+    for (word, &index) in indices.iter().enumerate() {
+        for bit in 0..11 {
+            if index >> (10 - bit) & 1 != 0 {
+                let position = word * 11 + bit;
+                bytes[position / 8] |= 0x80 >> (position % 8);
+            }
+        }
+    }
+
+    if bytes[0] != AEZEED_VERSION {
+        return Err(seed_error(format!(
+            "Unsupported aezeed version {}",
+            bytes[0]
+        )));
+    }
+    if crc32c(&bytes[..29]) != u32::from_be_bytes(bytes[29..].try_into().unwrap()) {
+        return Err(seed_error(
+            "aezeed checksum mismatch; a word is probably wrong",
+        ));
+    }
+
+    let salt = &bytes[AEZEED_SALT];
+    let passphrase = if passphrase.is_empty() {
+        AEZEED_DEFAULT_PASSPHRASE
+    } else {
+        passphrase
+    };
+    let params = scrypt::Params::new(scrypt_n.trailing_zeros() as u8, 8, 1, 32)
+        .expect("aezeed's scrypt parameters are valid");
+    let mut key = Zeroizing::new([0u8; 32]);
+    scrypt::scrypt(passphrase.as_bytes(), salt, &params, &mut key[..])
+        .expect("32 bytes is a valid scrypt output length");
+    let mut ad = vec![bytes[0]];
+    ad.extend_from_slice(salt);
+    let plaintext = aez::decrypt(&key[..], &[], &[&ad], 4, &bytes[1..24])
+        .map_err(|_| seed_error("Wrong aezeed passphrase"))?;
+
+    Ok(ForeignSeed {
+        kind: ForeignSeedKind::Aezeed {
+            version: plaintext[0],
+            birthday: u16::from_be_bytes([plaintext[1], plaintext[2]]),
+        },
+        entropy: Zeroizing::new(plaintext[3..].to_vec()),
+        bip32_seed: Zeroizing::new(plaintext[3..].to_vec()),
+    })
+}
+
+/// A Monero mnemonic wordlist (1626 words)
+///
+/// Monero matches words on their first `prefix_length` characters (3 for
+/// English), so seeds may be written with just the prefixes.
+pub struct MoneroWordlist {
+    words: Vec<String>,
+    prefix_length: usize,
+}
+
+impl MoneroWordlist {
+    /// Number of words in a Monero wordlist
+    pub const SIZE: usize = 1626;
+
+    /// Monero's English wordlist
+    pub fn english() -> Self {
+        Self::from_text(include_str!("wordlists/monero-english.txt"), 3)
+            .expect("bundled Monero wordlist has 1626 words")
+    }
+
+    /// Parse a wordlist with one word per line, in Monero's order
+    pub fn from_text(text: &str, prefix_length: usize) -> Result<Self> {
+        let words: Vec<String> = text
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty())
+            .map(|line| line.nfc().collect())
+            .collect();
+        if words.len() != Self::SIZE {
+            return Err(seed_error(format!(
+                "A Monero wordlist has {} words, not {}",
+                Self::SIZE,
+                words.len()
+            )));
+        }
+        Ok(Self {
+            words,
+            prefix_length,
+        })
+    }
+
+    fn prefix<'a>(&self, word: &'a str) -> &'a str {
+        match word.char_indices().nth(self.prefix_length) {
+            Some((end, _)) => &word[..end],
+            None => word,
+        }
+    }
+
+    fn index(&self, word: &str) -> Option<usize> {
+        let prefix = self.prefix(word);
+        self.words
+            .iter()
+            .position(|candidate| self.prefix(candidate) == prefix)
+    }
+}
+
+/// Recover the private spend key from a Monero 25-word seed
+pub fn decode_monero(phrase: &str, wordlist: &MoneroWordlist) -> Result<ForeignSeed> {
+    let phrase: Zeroizing<String> = Zeroizing::new(phrase.nfc().collect::<String>().to_lowercase());
+    let words: Vec<&str> = phrase.split_whitespace().collect();
+    if words.len() != 25 {
+        return Err(seed_error(format!(
+            "A Monero seed has 25 words, not {}",
+            words.len()
+        )));
+    }
+
+    let prefixes: Zeroizing<String> = Zeroizing::new(
+        words[..24]
+            .iter()
+            .map(|word| wordlist.prefix(word))
+            .collect(),
+    );
+    let expected = words[crc32fast::hash(prefixes.as_bytes()) as usize % 24];
+    if wordlist.prefix(words[24]) != wordlist.prefix(expected) {
+        return Err(seed_error(
+            "Monero checksum word does not match; a word is probably wrong",
+        ));
+    }
+
+    let n = MoneroWordlist::SIZE as u64;
+    let mut spend_key = Zeroizing::new(Vec::with_capacity(32));
+    for group in words[..24].chunks(3) {
+        let mut indices = [0u64; 3];
+        for (index, word) in indices.iter_mut().zip(group) {
+            *index = wordlist
+                .index(word)
+                .ok_or_else(|| seed_error(format!("'{}' is not in the Monero wordlist", word)))?
+                as u64;
+        }
+        let [w1, w2, w3] = indices;
+        let value = w1 + n * ((n - w1 + w2) % n) + n * n * ((n - w2 + w3) % n);
+        if value % n != w1 || value > u64::from(u32::MAX) {
+            return Err(seed_error("Invalid Monero seed"));
+        }
+        spend_key.extend_from_slice(&(value as u32).to_le_bytes());
+    }
+
+    Ok(ForeignSeed {
+        kind: ForeignSeedKind::Monero,
+        bip32_seed: spend_key.clone(),
+        entropy: spend_key,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use sha3::{Digest, Keccak256};
+
+    #[test]
+    fn test_electrum_seed() {
+        // Electrum's test_mnemonic vectors
+        let phrase =
+            "wild father tree among universe such mobile favorite target dynamic credit identify";
+        let seed = decode_electrum(phrase, "").unwrap();
+        assert_eq!(
+            seed.kind,
+            ForeignSeedKind::Electrum(ElectrumSeedType::Segwit)
+        );
+        assert_eq!(
+            hex::encode(&*seed.bip32_seed),
+            "aac2a6302e48577ab4b46f23dbae0774e2e62c796f797d0a1b5faeb528301e30\
+             64342dafb79069e7c4c6b8c38ae11d7a973bec0d4f70626f8cc5184a8d0b0756"
+        );
+        let extended = decode_electrum(
+            &phrase.to_uppercase(),
+            "Did you ever hear the tragedy of Darth Plagueis the Wise?",
+        )
+        .unwrap();
+        assert_eq!(
+            hex::encode(&*extended.bip32_seed),
+            "4aa29f2aeb0127efb55138ab9e7be83b36750358751906f86c662b21a1ea1370\
+             f949e6d1a12fa56d3d93cadda93038c76ac8118597364e46f5156fde6183c82f"
+        );
+        assert_eq!(extended.entropy, seed.entropy);
+        assert!(seed.keychain().is_ok());
+
+        // A BIP-39 phrase is not an Electrum seed
+        let bip39 = "abandon abandon abandon abandon abandon abandon \
+                     abandon abandon abandon abandon abandon about";
+        assert!(decode_electrum(bip39, "").is_err());
+    }
+
+    #[test]
+    fn test_electrum_entropy() {
+        // The first word is the least significant base-2048 digit
+        let phrase =
+            "wild father tree among universe such mobile favorite target dynamic credit identify";
+        let seed = decode_electrum(phrase, "").unwrap();
+        assert_eq!(seed.entropy.len(), 17);
+        let low = u16::from_be_bytes(seed.entropy[15..].try_into().unwrap()) & 0x7ff;
+        assert_eq!(Some(low), Language::English.find_word("wild"));
+    }
+
+    #[test]
+    fn test_aezeed() {
+        // LND aezeed test vectors, which LND generates with the scrypt
+        // cost lowered to 16
+        let seed = decode_aezeed_with_cost(
+            "ability liquid travel stem barely drastic pact cupboard apple thrive morning oak \
+             feature tissue couch old math inform success suggest drink motion know royal",
+            "",
+            16,
+        )
+        .unwrap();
+        assert_eq!(
+            seed.kind,
+            ForeignSeedKind::Aezeed {
+                version: 0,
+                birthday: 0
+            }
+        );
+        assert_eq!(
+            hex::encode(&*seed.entropy),
+            "81b637d86359e6960de795e41e0b4cfd"
+        );
+        assert_eq!(seed.bip32_seed, seed.entropy);
+        assert_eq!(seed.birthday_date().as_deref(), Some("2009-01-03"));
+
+        let with_passphrase = "able tree stool crush transfer cloud cross three profit outside \
+             hen citizen plate ride require leg siren drum success suggest drink require fiscal \
+             upgrade";
+        let seed =
+            decode_aezeed_with_cost(with_passphrase, "!very_safe_55345_password*", 16).unwrap();
+        assert_eq!(
+            seed.kind,
+            ForeignSeedKind::Aezeed {
+                version: 0,
+                birthday: 3365
+            }
+        );
+        assert_eq!(seed.birthday_date().as_deref(), Some("2018-03-22"));
+        assert_eq!(
+            hex::encode(&*seed.entropy),
+            "81b637d86359e6960de795e41e0b4cfd"
+        );
+        assert!(decode_aezeed_with_cost(with_passphrase, "", 16).is_err());
+        assert!(decode_aezeed(&with_passphrase.replace("fiscal", "fish"), "").is_err());
+    }
+
+    /// Monero's bytes-to-words encoding
+    fn encode_monero(key: &[u8; 32], wordlist: &MoneroWordlist) -> Vec<String> {
+        let n = MoneroWordlist::SIZE as u64;
+        let mut words = Vec::new();
+        for chunk in key.chunks(4) {
+            let value = u64::from(u32::from_le_bytes(chunk.try_into().unwrap()));
+            let w1 = value % n;
+            let w2 = (value / n + w1) % n;
+            let w3 = (value / n / n + w2) % n;
+            for index in [w1, w2, w3] {
+                words.push(wordlist.words[index as usize].clone());
+            }
+        }
+        let prefixes: String = words.iter().map(|word| wordlist.prefix(word)).collect();
+        words.push(words[crc32fast::hash(prefixes.as_bytes()) as usize % 24].clone());
+        words
+    }
+
+    #[test]
+    fn test_monero_seed() {
+        // monero-serai's classic seed test vector (English)
+        let phrase = "washing thirsty occur lectures tuesday fainted toxic adapt \
+                      abnormal memoir nylon mostly building shrugged online ember northern \
+                      ruby woes dauntless boil family illness inroads northern";
+        let spend_key = "c0af65c0dd837e666b9d0dfed62745f4df35aed7ea619b2798a709f0fe545403";
+        let wordlist = MoneroWordlist::english();
+
+        let seed = decode_monero(phrase, &wordlist).unwrap();
+        assert_eq!(hex::encode(&*seed.entropy), spend_key);
+        assert_eq!(seed.kind, ForeignSeedKind::Monero);
+        assert!(seed.keychain().is_ok());
+
+        // Monero's view key is Keccak-256 of the spend key, reduced mod l
+        let view_key = curve25519_dalek::Scalar::from_bytes_mod_order(
+            Keccak256::digest(&*seed.entropy).into(),
+        );
+        assert_eq!(
+            hex::encode(view_key.as_bytes()),
+            "513ba91c538a5a9069e0094de90e927c0cd147fa10428ce3ac1afd49f63e3b01"
+        );
+
+        let words: Vec<&str> = phrase.split_whitespace().collect();
+        let key: [u8; 32] = seed.entropy[..].try_into().unwrap();
+        assert_eq!(encode_monero(&key, &wordlist), words);
+
+        // Prefixes are enough
+        let prefixes: Vec<&str> = words.iter().map(|word| &word[..3]).collect();
+        assert_eq!(
+            hex::encode(
+                &*decode_monero(&prefixes.join(" "), &wordlist)
+                    .unwrap()
+                    .entropy
+            ),
+            spend_key
+        );
+
+        let mut swapped = words.clone();
+        swapped.swap(0, 1);
+        assert!(decode_monero(&swapped.join(" "), &wordlist).is_err());
+        assert!(decode_monero(&words[..24].join(" "), &wordlist).is_err());
+        assert!(MoneroWordlist::from_text("abbey\nabducts", 3).is_err());
+    }
+}
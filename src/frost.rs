@@ -0,0 +1,831 @@
+//! FROST threshold Ed25519 signatures
+//!
+//! Splits an entity's Ed25519 key into FROST signing shares (RFC 9591,
+//! `FROST(Ed25519, SHA-512)`), so that any `threshold` of `max_signers`
+//! participants can sign together while fewer learn nothing about the key.
+//! The group secret is the entity's Ed25519 signing scalar: the group
+//! public key is the entity's ordinary public key, and aggregated
+//! signatures verify as plain Ed25519 signatures. The other polynomial
+//! coefficients are derived from the entity's key, so any participant's
+//! share can be re-issued from the seed (a deterministic trusted dealer,
+//! RFC 9591 Appendix C).
+//!
+//! Signing takes two rounds:
+//! 1. each signer calls [`KeyPackage::commit`] and publishes the
+//!    [`SigningCommitment`], keeping the [`SigningNonces`] secret
+//! 2. with the message and the commitments of every signer, each signer
+//!    calls [`KeyPackage::sign`]; [`aggregate`] combines the
+//!    [`SignatureShare`]s into the signature
+//!
+//! Nonces must never be used for more than one signature.
+
+use crate::error::{BipKeychainError, Result};
+use crate::output::Ed25519Keypair;
+use curve25519_dalek::constants::ED25519_BASEPOINT_POINT;
+use curve25519_dalek::edwards::{CompressedEdwardsY, EdwardsPoint};
+use curve25519_dalek::scalar::Scalar;
+use curve25519_dalek::traits::IsIdentity;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha512};
+use zeroize::Zeroizing;
+
+/// RFC 9591 ciphersuite context string
+const CONTEXT_STRING: &[u8] = b"FROST-ED25519-SHA512-v1";
+
+/// Context string for the BLAKE3 coefficient derivation
+const COEFFICIENT_CONTEXT: &str = "bip-keychain 2025 frost coefficient";
+
+fn frost_error(message: String) -> BipKeychainError {
+    BipKeychainError::SignatureError(message)
+}
+
+/// Threshold and group size for FROST signing
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct FrostConfig {
+    /// Signers needed to produce a signature
+    pub threshold: u16,
+    /// Participants holding a share
+    pub max_signers: u16,
+}
+
+impl FrostConfig {
+    fn validate(&self) -> Result<()> {
+        if self.threshold < 2 || self.threshold > self.max_signers {
+            return Err(frost_error(format!(
+                "threshold must be between 2 and max_signers ({}), got {}",
+                self.max_signers, self.threshold
+            )));
+        }
+        Ok(())
+    }
+}
+
+/// Hex encoding for 32-byte scalars and points in JSON
+mod hex32 {
+    use serde::{Deserialize, Deserializer, Serializer};
+    use zeroize::Zeroizing;
+
+    pub fn serialize<S: Serializer, T: AsRef<[u8]>>(
+        bytes: &T,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&Zeroizing::new(hex::encode(bytes.as_ref())))
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>, T: From<[u8; 32]>>(
+        deserializer: D,
+    ) -> Result<T, D::Error> {
+        let text = Zeroizing::new(String::deserialize(deserializer)?);
+        let mut bytes = [0u8; 32];
+        hex::decode_to_slice(text.as_str(), &mut bytes)
+            .map_err(|_| serde::de::Error::custom("expected 64 hex characters"))?;
+        Ok(T::from(bytes))
+    }
+}
+
+/// SHA-512 of the context string, a domain label, and `parts`
+fn hash(label: &[u8], parts: &[&[u8]]) -> [u8; 64] {
+    let mut hasher = Sha512::new();
+    hasher.update(CONTEXT_STRING);
+    hasher.update(label);
+    for part in parts {
+        hasher.update(part);
+    }
+    hasher.finalize().into()
+}
+
+/// H1 (`rho`) and H3 (`nonce`): hash to a scalar
+fn hash_to_scalar(label: &[u8], parts: &[&[u8]]) -> Scalar {
+    Scalar::from_bytes_mod_order_wide(&hash(label, parts))
+}
+
+fn identifier_scalar(identifier: u16) -> Scalar {
+    Scalar::from(u64::from(identifier))
+}
+
+fn parse_scalar(bytes: &[u8; 32], what: &str) -> Result<Scalar> {
+    Option::from(Scalar::from_canonical_bytes(*bytes))
+        .ok_or_else(|| frost_error(format!("{} is not a canonical scalar", what)))
+}
+
+/// Decode a group element, rejecting the identity and points outside the
+/// prime-order subgroup
+fn parse_point(bytes: &[u8; 32], what: &str) -> Result<EdwardsPoint> {
+    CompressedEdwardsY(*bytes)
+        .decompress()
+        .filter(|point| !point.is_identity() && point.is_torsion_free())
+        .ok_or_else(|| frost_error(format!("{} is not a valid group element", what)))
+}
+
+/// Lagrange coefficient of `identifier` for interpolation at zero
+fn interpolating_value(identifiers: &[u16], identifier: u16) -> Scalar {
+    let x_i = identifier_scalar(identifier);
+    let mut numerator = Scalar::ONE;
+    let mut denominator = Scalar::ONE;
+    for &other in identifiers.iter().filter(|&&other| other != identifier) {
+        let x_j = identifier_scalar(other);
+        numerator *= x_j;
+        denominator *= x_j - x_i;
+    }
+    numerator * denominator.invert()
+}
+
+/// The shares of one group, computed from the entity's key
+pub struct FrostGroup {
+    config: FrostConfig,
+    coefficients: Zeroizing<Vec<Scalar>>,
+    public_key: [u8; 32],
+}
+
+impl FrostGroup {
+    /// Split `keypair` for `config`
+    pub fn derive(keypair: &Ed25519Keypair, config: FrostConfig) -> Result<Self> {
+        config.validate()?;
+
+        let mut coefficients = Zeroizing::new(vec![keypair.signing_key().to_scalar()]);
+        for index in 1..config.threshold {
+            let mut hasher = blake3::Hasher::new_derive_key(COEFFICIENT_CONTEXT);
+            hasher.update(&*keypair.private_key_bytes());
+            hasher.update(&index.to_be_bytes());
+            let mut wide = Zeroizing::new([0u8; 64]);
+            hasher.finalize_xof().fill(&mut *wide);
+            coefficients.push(Scalar::from_bytes_mod_order_wide(&wide));
+        }
+
+        Ok(Self {
+            config,
+            coefficients,
+            public_key: keypair.public_key_bytes(),
+        })
+    }
+
+    /// Group with the given polynomial coefficients, the first being the
+    /// group secret (for RFC 9591 test vectors)
+    #[cfg(test)]
+    fn from_coefficients(config: FrostConfig, coefficients: Vec<Scalar>) -> Self {
+        let public_key = (ED25519_BASEPOINT_POINT * coefficients[0])
+            .compress()
+            .to_bytes();
+        Self {
+            config,
+            coefficients: Zeroizing::new(coefficients),
+            public_key,
+        }
+    }
+
+    /// Group public key: the entity's Ed25519 public key
+    pub fn public_key(&self) -> [u8; 32] {
+        self.public_key
+    }
+
+    /// Signing share of participant `identifier` (1 to `max_signers`)
+    pub fn key_package(&self, identifier: u16) -> Result<KeyPackage> {
+        if identifier == 0 || identifier > self.config.max_signers {
+            return Err(frost_error(format!(
+                "participant must be between 1 and {}, got {}",
+                self.config.max_signers, identifier
+            )));
+        }
+
+        // Horner evaluation of the polynomial at the identifier
+        let x = identifier_scalar(identifier);
+        let mut share = Zeroizing::new(Scalar::ZERO);
+        for coefficient in self.coefficients.iter().rev() {
+            *share = *share * x + coefficient;
+        }
+
+        Ok(KeyPackage {
+            identifier,
+            signing_share: Zeroizing::new(share.to_bytes()),
+            verifying_share: (ED25519_BASEPOINT_POINT * *share).compress().to_bytes(),
+            group_public_key: self.public_key,
+            threshold: self.config.threshold,
+            max_signers: self.config.max_signers,
+        })
+    }
+}
+
+/// A participant's signing share and the group parameters
+#[derive(Clone, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct KeyPackage {
+    identifier: u16,
+    #[serde(with = "hex32")]
+    signing_share: Zeroizing<[u8; 32]>,
+    #[serde(with = "hex32")]
+    verifying_share: [u8; 32],
+    #[serde(with = "hex32")]
+    group_public_key: [u8; 32],
+    threshold: u16,
+    max_signers: u16,
+}
+
+impl KeyPackage {
+    /// Parse and check a key package from JSON
+    pub fn from_json(json: &str) -> Result<Self> {
+        let package: Self = serde_json::from_str(json)
+            .map_err(|e| frost_error(format!("Invalid FROST key package: {}", e)))?;
+        FrostConfig {
+            threshold: package.threshold,
+            max_signers: package.max_signers,
+        }
+        .validate()?;
+        let share = Zeroizing::new(package.signing_share()?);
+        if (ED25519_BASEPOINT_POINT * *share).compress().to_bytes() != package.verifying_share {
+            return Err(frost_error(
+                "signing share does not match verifying share".to_string(),
+            ));
+        }
+        parse_point(&package.group_public_key, "group public key")?;
+        Ok(package)
+    }
+
+    /// Key package as JSON (contains the signing share)
+    pub fn to_json(&self) -> Zeroizing<String> {
+        Zeroizing::new(serde_json::to_string_pretty(self).expect("key package serializes"))
+    }
+
+    /// Participant identifier
+    pub fn identifier(&self) -> u16 {
+        self.identifier
+    }
+
+    /// Group public key (an Ed25519 public key)
+    pub fn group_public_key(&self) -> [u8; 32] {
+        self.group_public_key
+    }
+
+    fn signing_share(&self) -> Result<Scalar> {
+        parse_scalar(&self.signing_share, "signing share")
+    }
+
+    /// RFC 9591 `nonce_generate`: `random` hashed with the share
+    fn nonce_generate(random: &[u8; 32], share: &Scalar) -> Zeroizing<Scalar> {
+        Zeroizing::new(hash_to_scalar(b"nonce", &[random, &share.to_bytes()]))
+    }
+
+    /// A nonce from fresh randomness
+    fn generate_nonce(share: &Scalar) -> Result<Zeroizing<Scalar>> {
+        let mut random = Zeroizing::new([0u8; 32]);
+        getrandom::getrandom(&mut *random)
+            .map_err(|e| BipKeychainError::IoError(std::io::Error::other(e.to_string())))?;
+        Ok(Self::nonce_generate(&random, share))
+    }
+
+    /// Round one: fresh nonces, and the commitment to publish
+    pub fn commit(&self) -> Result<(SigningNonces, SigningCommitment)> {
+        let share = Zeroizing::new(self.signing_share()?);
+        let hiding = Self::generate_nonce(&share)?;
+        let binding = Self::generate_nonce(&share)?;
+        Ok(self.commit_to(&hiding, &binding))
+    }
+
+    /// Round one with the nonce randomness given (for RFC 9591 test vectors)
+    #[cfg(test)]
+    fn commit_with_randomness(
+        &self,
+        hiding_random: &[u8; 32],
+        binding_random: &[u8; 32],
+    ) -> Result<(SigningNonces, SigningCommitment)> {
+        let share = Zeroizing::new(self.signing_share()?);
+        let hiding = Self::nonce_generate(hiding_random, &share);
+        let binding = Self::nonce_generate(binding_random, &share);
+        Ok(self.commit_to(&hiding, &binding))
+    }
+
+    fn commit_to(&self, hiding: &Scalar, binding: &Scalar) -> (SigningNonces, SigningCommitment) {
+        let commitment = SigningCommitment {
+            identifier: self.identifier,
+            hiding: (ED25519_BASEPOINT_POINT * hiding).compress().to_bytes(),
+            binding: (ED25519_BASEPOINT_POINT * binding).compress().to_bytes(),
+        };
+        let nonces = SigningNonces {
+            identifier: self.identifier,
+            hiding: Zeroizing::new(hiding.to_bytes()),
+            binding: Zeroizing::new(binding.to_bytes()),
+            commitment: commitment.clone(),
+        };
+        (nonces, commitment)
+    }
+
+    /// Round two: this participant's share of the signature on `message`
+    ///
+    /// `commitments` holds the commitment of every participant in this
+    /// signing session, including this one.
+    pub fn sign(
+        &self,
+        nonces: &SigningNonces,
+        commitments: &[SigningCommitment],
+        message: &[u8],
+    ) -> Result<SignatureShare> {
+        if nonces.identifier != self.identifier {
+            return Err(frost_error(format!(
+                "nonces belong to participant {}, not {}",
+                nonces.identifier, self.identifier
+            )));
+        }
+        let session = SigningSession::new(&self.group_public_key, commitments, message)?;
+        if session.commitments.len() < usize::from(self.threshold) {
+            return Err(frost_error(format!(
+                "need commitments from at least {} signers, got {}",
+                self.threshold,
+                session.commitments.len()
+            )));
+        }
+        let position = session
+            .commitments
+            .iter()
+            .position(|commitment| commitment.identifier == self.identifier)
+            .ok_or_else(|| {
+                frost_error(format!(
+                    "commitments do not include participant {}",
+                    self.identifier
+                ))
+            })?;
+        if session.commitments[position] != nonces.commitment {
+            return Err(frost_error(
+                "commitment for this participant does not match its nonces".to_string(),
+            ));
+        }
+
+        let share = Zeroizing::new(self.signing_share()?);
+        let hiding = Zeroizing::new(parse_scalar(&nonces.hiding, "hiding nonce")?);
+        let binding = Zeroizing::new(parse_scalar(&nonces.binding, "binding nonce")?);
+        let lambda = interpolating_value(&session.identifiers(), self.identifier);
+        let z = *hiding
+            + *binding * session.binding_factors[position]
+            + lambda * *share * session.challenge;
+
+        Ok(SignatureShare {
+            identifier: self.identifier,
+            share: z.to_bytes(),
+        })
+    }
+}
+
+/// A signer's secret round-one nonces; use once, then discard
+#[derive(Clone, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct SigningNonces {
+    identifier: u16,
+    #[serde(with = "hex32")]
+    hiding: Zeroizing<[u8; 32]>,
+    #[serde(with = "hex32")]
+    binding: Zeroizing<[u8; 32]>,
+    commitment: SigningCommitment,
+}
+
+impl SigningNonces {
+    /// Parse nonces from JSON
+    pub fn from_json(json: &str) -> Result<Self> {
+        serde_json::from_str(json).map_err(|e| frost_error(format!("Invalid FROST nonces: {}", e)))
+    }
+
+    /// Nonces as JSON (secret)
+    pub fn to_json(&self) -> Zeroizing<String> {
+        Zeroizing::new(serde_json::to_string_pretty(self).expect("nonces serialize"))
+    }
+}
+
+/// A signer's public round-one commitment
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct SigningCommitment {
+    /// Participant identifier
+    pub identifier: u16,
+    /// Hiding nonce commitment (compressed Edwards point)
+    #[serde(with = "hex32")]
+    pub hiding: [u8; 32],
+    /// Binding nonce commitment (compressed Edwards point)
+    #[serde(with = "hex32")]
+    pub binding: [u8; 32],
+}
+
+impl SigningCommitment {
+    /// Parse a commitment from JSON
+    pub fn from_json(json: &str) -> Result<Self> {
+        serde_json::from_str(json)
+            .map_err(|e| frost_error(format!("Invalid FROST commitment: {}", e)))
+    }
+
+    /// Commitment as JSON
+    pub fn to_json(&self) -> String {
+        serde_json::to_string_pretty(self).expect("commitment serializes")
+    }
+}
+
+/// A signer's round-two signature share
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct SignatureShare {
+    /// Participant identifier
+    pub identifier: u16,
+    /// Share of the signature scalar
+    #[serde(with = "hex32")]
+    pub share: [u8; 32],
+}
+
+impl SignatureShare {
+    /// Parse a signature share from JSON
+    pub fn from_json(json: &str) -> Result<Self> {
+        serde_json::from_str(json)
+            .map_err(|e| frost_error(format!("Invalid FROST signature share: {}", e)))
+    }
+
+    /// Signature share as JSON
+    pub fn to_json(&self) -> String {
+        serde_json::to_string_pretty(self).expect("signature share serializes")
+    }
+}
+
+/// Values shared by every signer in a session, and by the aggregator
+struct SigningSession {
+    commitments: Vec<SigningCommitment>,
+    binding_factors: Vec<Scalar>,
+    group_commitment: EdwardsPoint,
+    challenge: Scalar,
+}
+
+impl SigningSession {
+    fn new(
+        group_public_key: &[u8; 32],
+        commitments: &[SigningCommitment],
+        message: &[u8],
+    ) -> Result<Self> {
+        let mut commitments = commitments.to_vec();
+        commitments.sort_by_key(|commitment| commitment.identifier);
+        if commitments
+            .windows(2)
+            .any(|pair| pair[0].identifier == pair[1].identifier)
+        {
+            return Err(frost_error(
+                "commitments list a participant twice".to_string(),
+            ));
+        }
+        if commitments
+            .iter()
+            .any(|commitment| commitment.identifier == 0)
+        {
+            return Err(frost_error(
+                "participant identifiers start at 1".to_string(),
+            ));
+        }
+
+        // RFC 9591 section 4.4: binding factors
+        let mut encoded_commitments = Vec::with_capacity(commitments.len() * 96);
+        for commitment in &commitments {
+            encoded_commitments
+                .extend_from_slice(&identifier_scalar(commitment.identifier).to_bytes());
+            encoded_commitments.extend_from_slice(&commitment.hiding);
+            encoded_commitments.extend_from_slice(&commitment.binding);
+        }
+        let message_hash = hash(b"msg", &[message]);
+        let commitment_hash = hash(b"com", &[&encoded_commitments]);
+        let binding_factors: Vec<Scalar> = commitments
+            .iter()
+            .map(|commitment| {
+                hash_to_scalar(
+                    b"rho",
+                    &[
+                        group_public_key,
+                        &message_hash,
+                        &commitment_hash,
+                        &identifier_scalar(commitment.identifier).to_bytes(),
+                    ],
+                )
+            })
+            .collect();
+
+        // Section 4.5: group commitment
+        let mut group_commitment = EdwardsPoint::default();
+        for (commitment, binding_factor) in commitments.iter().zip(&binding_factors) {
+            let hiding = parse_point(&commitment.hiding, "hiding commitment")?;
+            let binding = parse_point(&commitment.binding, "binding commitment")?;
+            group_commitment += hiding + binding * binding_factor;
+        }
+
+        // Section 4.6: challenge, the Ed25519 H(R || A || M)
+        let mut hasher = Sha512::new();
+        hasher.update(group_commitment.compress().as_bytes());
+        hasher.update(group_public_key);
+        hasher.update(message);
+        let challenge = Scalar::from_bytes_mod_order_wide(&hasher.finalize().into());
+
+        Ok(Self {
+            commitments,
+            binding_factors,
+            group_commitment,
+            challenge,
+        })
+    }
+
+    fn identifiers(&self) -> Vec<u16> {
+        self.commitments
+            .iter()
+            .map(|commitment| commitment.identifier)
+            .collect()
+    }
+}
+
+/// Combine the signature shares of every signer into an Ed25519 signature
+///
+/// The result is checked against `group_public_key`, so an invalid share
+/// is reported here rather than producing a bad signature.
+pub fn aggregate(
+    group_public_key: &[u8; 32],
+    commitments: &[SigningCommitment],
+    shares: &[SignatureShare],
+    message: &[u8],
+) -> Result<[u8; 64]> {
+    let session = SigningSession::new(group_public_key, commitments, message)?;
+    let mut shares = shares.to_vec();
+    shares.sort_by_key(|share| share.identifier);
+    let share_identifiers: Vec<u16> = shares.iter().map(|share| share.identifier).collect();
+    if share_identifiers != session.identifiers() {
+        return Err(frost_error(format!(
+            "signature shares are from participants {:?}, commitments from {:?}",
+            share_identifiers,
+            session.identifiers()
+        )));
+    }
+
+    let mut z = Scalar::ZERO;
+    for share in &shares {
+        z += parse_scalar(&share.share, "signature share")?;
+    }
+
+    let mut signature = [0u8; 64];
+    signature[..32].copy_from_slice(session.group_commitment.compress().as_bytes());
+    signature[32..].copy_from_slice(&z.to_bytes());
+
+    let verifying_key = ed25519_dalek::VerifyingKey::from_bytes(group_public_key)
+        .map_err(|_| frost_error("group public key is not a valid Ed25519 key".to_string()))?;
+    verifying_key
+        .verify_strict(message, &ed25519_dalek::Signature::from_bytes(&signature))
+        .map_err(|_| {
+            frost_error(
+                "aggregated signature does not verify; a signature share is invalid".to_string(),
+            )
+        })?;
+    Ok(signature)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn test_group() -> FrostGroup {
+        let keypair = Ed25519Keypair::from_seed([1u8; 32]);
+        FrostGroup::derive(
+            &keypair,
+            FrostConfig {
+                threshold: 2,
+                max_signers: 3,
+            },
+        )
+        .unwrap()
+    }
+
+    fn sign_with(group: &FrostGroup, signers: &[u16], message: &[u8]) -> Result<[u8; 64]> {
+        let packages: Vec<KeyPackage> = signers
+            .iter()
+            .map(|&identifier| group.key_package(identifier).unwrap())
+            .collect();
+        let (nonces, commitments): (Vec<_>, Vec<_>) = packages
+            .iter()
+            .map(|package| package.commit().unwrap())
+            .unzip();
+        let shares: Vec<SignatureShare> = packages
+            .iter()
+            .zip(&nonces)
+            .map(|(package, nonces)| package.sign(nonces, &commitments, message).unwrap())
+            .collect();
+        aggregate(&group.public_key(), &commitments, &shares, message)
+    }
+
+    #[test]
+    fn test_any_threshold_subset_signs() {
+        let group = test_group();
+        let keypair = Ed25519Keypair::from_seed([1u8; 32]);
+        assert_eq!(group.public_key(), keypair.public_key_bytes());
+
+        for signers in [&[1, 2][..], &[1, 3], &[2, 3], &[1, 2, 3]] {
+            let signature = sign_with(&group, signers, b"release 1.0").unwrap();
+            keypair
+                .verifying_key()
+                .verify_strict(
+                    b"release 1.0",
+                    &ed25519_dalek::Signature::from_bytes(&signature),
+                )
+                .unwrap();
+        }
+    }
+
+    fn hex_array(text: &str) -> [u8; 32] {
+        hex::decode(text).unwrap().try_into().unwrap()
+    }
+
+    /// RFC 9591 Appendix E.1, FROST(Ed25519, SHA-512)
+    #[test]
+    fn test_rfc9591_vectors() {
+        let scalar = |text| parse_scalar(&hex_array(text), "vector").unwrap();
+        let group = FrostGroup::from_coefficients(
+            FrostConfig {
+                threshold: 2,
+                max_signers: 3,
+            },
+            vec![
+                scalar("7b1c33d3f5291d85de664833beb1ad469f7fb6025a0ec78b3a790c6e13a98304"),
+                scalar("178199860edd8c62f5212ee91eff1295d0d670ab4ed4506866bae57e7030b204"),
+            ],
+        );
+        assert_eq!(
+            hex::encode(group.public_key()),
+            "15d21ccd7ee42959562fc8aa63224c8851fb3ec85a3faf66040d380fb9738673"
+        );
+        for (identifier, share) in [
+            (
+                1,
+                "929dcc590407aae7d388761cddb0c0db6f5627aea8e217f4a033f2ec83d93509",
+            ),
+            (
+                2,
+                "a91e66e012e4364ac9aaa405fcafd370402d9859f7b6685c07eed76bf409e80d",
+            ),
+            (
+                3,
+                "d3cb090a075eb154e82fdb4b3cb507f110040905468bb9c46da8bdea643a9a02",
+            ),
+        ] {
+            assert_eq!(
+                hex::encode(*group.key_package(identifier).unwrap().signing_share),
+                share
+            );
+        }
+
+        let message = b"test";
+        // (identifier, hiding and binding nonce randomness, hiding nonce,
+        // binding nonce, hiding and binding commitments, binding factor)
+        let round_one = [
+            (
+                1,
+                "06894e04ee4aceec8619d5f6a0a180e2f47194d2ac306cba586b555e7c48d765",
+                "40d6f879ff22e22409f7d808fed81f37118e7d3e4af71c0f44c60207553bcbce",
+                "ef6599dea4010581a72b3018c37c29a4341d7cab0773e8687ca74dcf14009701",
+                "2baadfa0c69aa60d517ad4751de372a73f9d89cfc39026601f18458cdec12605",
+                "9b116f12589591a7e23fe8048059ab10ab48e67739e7a2fb3890f61a7999478c",
+                "c39b66b7dfccb122da24f13587f9a08c4347cae70046ca15169adf90ba65854d",
+                "ff960a65374e216a0918729b153466016664fa980d409bc3f308daa7acb30d0d",
+            ),
+            (
+                3,
+                "fa5d950626782aade9e33fa781376d4888c2d1de7c37518bc248eb818ed0cdde",
+                "7459a4d14ded0e365b085271be8dc6600d5b88f3978a2174297ffa32001a5afc",
+                "80f8d9a4b8f9366e1a0b618107c907cd3ee29fa9bb40b4691cc1bde696240005",
+                "2610b664a5a187b4855e87d2ff485bebdf043dc2f161fcd4854cd01dc0276404",
+                "e679a2a971748ccfaabead4dbe8ac1def61275c186c79d471e1e45091ad1e687",
+                "b2a942478453fabb6bd3181c56ba657413447b4136e1daea2484d396d1a516b3",
+                "279d48ec56f16d234c09ea62f3d02ab776ee38e03f66b20f939f1316e13df10f",
+            ),
+        ];
+        let mut packages = Vec::new();
+        let mut nonces = Vec::new();
+        let mut commitments = Vec::new();
+        for (
+            identifier,
+            hiding_random,
+            binding_random,
+            hiding,
+            binding,
+            hiding_commitment,
+            binding_commitment,
+            _,
+        ) in round_one
+        {
+            let package = group.key_package(identifier).unwrap();
+            let (signer_nonces, commitment) = package
+                .commit_with_randomness(&hex_array(hiding_random), &hex_array(binding_random))
+                .unwrap();
+            assert_eq!(hex::encode(*signer_nonces.hiding), hiding);
+            assert_eq!(hex::encode(*signer_nonces.binding), binding);
+            assert_eq!(hex::encode(commitment.hiding), hiding_commitment);
+            assert_eq!(hex::encode(commitment.binding), binding_commitment);
+            packages.push(package);
+            nonces.push(signer_nonces);
+            commitments.push(commitment);
+        }
+
+        let session = SigningSession::new(&group.public_key(), &commitments, message).unwrap();
+        for (binding_factor, vector) in session.binding_factors.iter().zip(&round_one) {
+            assert_eq!(hex::encode(binding_factor.to_bytes()), vector.7);
+        }
+
+        let shares: Vec<SignatureShare> = packages
+            .iter()
+            .zip(&nonces)
+            .map(|(package, nonces)| package.sign(nonces, &commitments, message).unwrap())
+            .collect();
+        assert_eq!(
+            hex::encode(shares[0].share),
+            "60997f0142e43e8005027fe5ab7447dac00d22c2d7ddd9571a02613ba7d81c08"
+        );
+        assert_eq!(
+            hex::encode(shares[1].share),
+            "79390e78bc59699c7af831f8f5fb478ec871a85f561a8641b5670ac4443f720f"
+        );
+
+        let signature = aggregate(&group.public_key(), &commitments, &shares, message).unwrap();
+        // The group commitment is the signature's R
+        assert_eq!(
+            session.group_commitment.compress().to_bytes(),
+            signature[..32]
+        );
+        assert_eq!(
+            hex::encode(signature),
+            "154fb694ee7fcb37bf2381d94488c2a84b03b3352ad085feca81ad26d45852b7\
+             ecfe971ce4da95c4a95db93ac376b053897fca212ef85f99cf696bffeb178f07"
+        );
+    }
+
+    #[test]
+    fn test_shares_interpolate_to_group_key() {
+        let group = test_group();
+        let share = |identifier| {
+            parse_scalar(
+                &group.key_package(identifier).unwrap().signing_share,
+                "share",
+            )
+            .unwrap()
+        };
+        let secret =
+            share(1) * interpolating_value(&[1, 3], 1) + share(3) * interpolating_value(&[1, 3], 3);
+        assert_eq!(
+            (ED25519_BASEPOINT_POINT * secret).compress().to_bytes(),
+            group.public_key()
+        );
+        // Shares are deterministic
+        assert_eq!(
+            *group.key_package(2).unwrap().signing_share,
+            *test_group().key_package(2).unwrap().signing_share
+        );
+    }
+
+    #[test]
+    fn test_rejects_bad_sessions() {
+        let group = test_group();
+        assert!(group.key_package(0).is_err());
+        assert!(group.key_package(4).is_err());
+        assert!(FrostGroup::derive(
+            &Ed25519Keypair::from_seed([1u8; 32]),
+            FrostConfig {
+                threshold: 4,
+                max_signers: 3
+            }
+        )
+        .is_err());
+
+        let first = group.key_package(1).unwrap();
+        let second = group.key_package(2).unwrap();
+        let (nonces, commitment) = first.commit().unwrap();
+        // Below threshold
+        assert!(first
+            .sign(&nonces, std::slice::from_ref(&commitment), b"m")
+            .is_err());
+        // Someone else's nonces
+        let (_, other) = second.commit().unwrap();
+        assert!(second
+            .sign(&nonces, &[commitment.clone(), other.clone()], b"m")
+            .is_err());
+
+        // A share for a different message fails aggregation
+        let (second_nonces, second_commitment) = second.commit().unwrap();
+        let commitments = [commitment, second_commitment];
+        let shares = [
+            first.sign(&nonces, &commitments, b"m").unwrap(),
+            second.sign(&second_nonces, &commitments, b"other").unwrap(),
+        ];
+        assert!(aggregate(&group.public_key(), &commitments, &shares, b"m").is_err());
+    }
+
+    #[test]
+    fn test_json_round_trip() {
+        let package = test_group().key_package(3).unwrap();
+        let parsed = KeyPackage::from_json(&package.to_json()).unwrap();
+        assert_eq!(parsed.identifier(), 3);
+        assert_eq!(*parsed.signing_share, *package.signing_share);
+
+        let (nonces, commitment) = package.commit().unwrap();
+        let nonces = SigningNonces::from_json(&nonces.to_json()).unwrap();
+        assert_eq!(nonces.commitment, commitment);
+        assert_eq!(
+            SigningCommitment::from_json(&commitment.to_json()).unwrap(),
+            commitment
+        );
+
+        // A tampered share no longer matches its verifying share
+        let mut tampered: serde_json::Value = serde_json::from_str(&package.to_json()).unwrap();
+        tampered["signing_share"] = hex::encode([1u8; 32]).into();
+        assert!(KeyPackage::from_json(&tampered.to_string()).is_err());
+    }
+}
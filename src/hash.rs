@@ -4,9 +4,26 @@
 //! - HMAC-SHA-512 (BIP-85 standard)
 //! - BLAKE2b (Blockchain Commons compatibility)
 //! - SHA-256
+//! - BLAKE3 (keyed mode, 64-byte XOF output)
+//! - Argon2id (memory-hard, for guessable entities)
 
-use crate::error::{BipKeychainError, Result};
+use crate::{
+    dcbor,
+    error::{BipKeychainError, Result},
+    jcs,
+};
 use serde_json::Value;
+use std::{borrow::Cow, io};
+
+/// Argon2id memory cost in KiB (19 MiB, the OWASP minimum recommendation)
+pub const ARGON2ID_MEMORY_KIB: u32 = 19 * 1024;
+/// Argon2id time cost (passes over memory)
+pub const ARGON2ID_ITERATIONS: u32 = 2;
+/// Argon2id lanes
+pub const ARGON2ID_PARALLELISM: u32 = 1;
+
+/// Context string for compressing non-32-byte parent entropy into a BLAKE3 key
+pub const BLAKE3_KEY_CONTEXT: &str = "bip-keychain 2024-01-01 BLAKE3 parent entropy key";
 
 /// Hash function selection for entity derivation
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -17,39 +34,87 @@ pub enum HashFunction {
     Blake2b,
     /// SHA-256
     Sha256,
+    /// BLAKE3 keyed hash
+    Blake3,
+    /// Argon2id memory-hard hash
+    Argon2id,
+}
+
+/// Canonical form of entity JSON before hashing
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Canonicalization {
+    /// Compact JSON with sorted keys (serde_json)
+    Serde,
+    /// JSON Canonicalization Scheme (RFC 8785)
+    Jcs,
+    /// Deterministic CBOR (Blockchain Commons dCBOR)
+    Dcbor,
+    /// The input bytes exactly as given
+    None,
 }
 
 /// Hash an entity JSON string with parent entropy
 ///
 /// Returns a 64-byte digest for all hash functions (padded if needed).
+///
+/// Input that is not valid JSON (e.g. plain-text test vectors) is hashed
+/// as-is. Use [`canonicalize`] in strict mode with [`hash_bytes`] to reject
+/// it instead.
 pub fn hash_entity(
     entity_json: &str,
     parent_entropy: &[u8],
     hash_fn: HashFunction,
 ) -> Result<[u8; 64]> {
+    // Canonicalize JSON for deterministic hashing
+    let canonical = canonicalize_borrowed(entity_json, Canonicalization::Serde, false)?;
+
+    hash_bytes(&canonical, parent_entropy, hash_fn)
+}
+
+/// Hash already-canonical entity bytes with parent entropy
+///
+/// Used for canonical forms that are not JSON text, such as dCBOR.
+/// Returns a 64-byte digest for all hash functions (padded if needed).
+pub fn hash_bytes(data: &[u8], parent_entropy: &[u8], hash_fn: HashFunction) -> Result<[u8; 64]> {
     match hash_fn {
-        HashFunction::HmacSha512 => hmac_sha512(entity_json, parent_entropy),
-        HashFunction::Blake2b => blake2b_hash(entity_json),
-        HashFunction::Sha256 => sha256_padded(entity_json, parent_entropy),
+        HashFunction::HmacSha512 => hmac_sha512(data, parent_entropy),
+        HashFunction::Blake2b => blake2b_hash(data),
+        HashFunction::Sha256 => sha256_padded(data, parent_entropy),
+        HashFunction::Blake3 => blake3_keyed(data, parent_entropy),
+        HashFunction::Argon2id => argon2id_hash(data, parent_entropy),
     }
 }
 
+/// Expand a 64-byte entity hash with HKDF-SHA512
+///
+/// The hash is used as input keying material (no salt) and expanded to 64
+/// bytes under `info`. Different `info` strings give independent outputs for
+/// the same entity, separating derivation domains.
+pub fn hkdf_sha512_expand(hash: &[u8; 64], info: &[u8]) -> Result<[u8; 64]> {
+    use hkdf::Hkdf;
+    use sha2::Sha512;
+
+    let mut output = [0u8; 64];
+    Hkdf::<Sha512>::new(None, hash)
+        .expand(info, &mut output)
+        .map_err(|e| BipKeychainError::HashError(format!("HKDF expansion failed: {}", e)))?;
+
+    Ok(output)
+}
+
 /// HMAC-SHA-512 implementation (BIP-85 standard)
-fn hmac_sha512(entity_json: &str, parent_entropy: &[u8]) -> Result<[u8; 64]> {
+fn hmac_sha512(data: &[u8], parent_entropy: &[u8]) -> Result<[u8; 64]> {
     use hmac::{Hmac, Mac};
     use sha2::Sha512;
 
     type HmacSha512 = Hmac<Sha512>;
 
-    // Canonicalize JSON for deterministic hashing
-    let canonical = canonicalize_json(entity_json)?;
-
     // Create HMAC instance with parent entropy as key
     let mut mac = HmacSha512::new_from_slice(parent_entropy)
         .map_err(|e| BipKeychainError::HashError(format!("HMAC key error: {}", e)))?;
 
-    // Hash the canonical JSON string
-    mac.update(canonical.as_bytes());
+    // Hash the canonical entity bytes
+    mac.update(data);
 
     // Finalize and get the result
     let result = mac.finalize();
@@ -70,23 +135,36 @@ fn hmac_sha512(entity_json: &str, parent_entropy: &[u8]) -> Result<[u8; 64]> {
 ///
 /// Note: This implementation does NOT use parent entropy as BLAKE2b is used
 /// as a pure hash function (not keyed hash like HMAC-SHA-512).
-fn blake2b_hash(entity_json: &str) -> Result<[u8; 64]> {
-    use alkali::hash::generic;
+fn blake2b_hash(data: &[u8]) -> Result<[u8; 64]> {
+    let mut output = [0u8; 64];
+    blake2b_into(data, &mut output)?;
+    Ok(output)
+}
 
-    // Canonicalize JSON for deterministic hashing
-    // For large entities, this allocates a new string. For pre-canonicalized
-    // inputs, this is a small overhead but ensures correctness.
-    let canonical = canonicalize_json(entity_json)?;
+/// Unkeyed BLAKE2b with a digest the length of `output` (16 to 64 bytes)
+#[cfg(not(target_arch = "wasm32"))]
+pub(crate) fn blake2b_into(data: &[u8], output: &mut [u8]) -> Result<()> {
+    use alkali::hash::generic;
 
-    // BLAKE2b-512 hash (64 bytes) using libsodium via alkali
     // Blockchain Commons uses libsodium's implementation for consistency
     // across their ecosystem (Gordian Envelope, etc.)
-    // Use hash_custom to specify 64-byte output (default is 32 bytes)
-    let mut output = [0u8; 64];
-    generic::hash_custom(canonical.as_bytes(), None, &mut output)
-        .map_err(|e| BipKeychainError::HashError(format!("BLAKE2b hashing failed: {:?}", e)))?;
+    generic::hash_custom(data, None, output)
+        .map_err(|e| BipKeychainError::HashError(format!("BLAKE2b hashing failed: {:?}", e)))
+}
 
-    Ok(output)
+/// BLAKE2b for wasm32, where libsodium is unavailable
+///
+/// Produces the same digest as the libsodium implementation.
+#[cfg(target_arch = "wasm32")]
+pub(crate) fn blake2b_into(data: &[u8], output: &mut [u8]) -> Result<()> {
+    use blake2::digest::{Update, VariableOutput};
+
+    let mut hasher = blake2::Blake2bVar::new(output.len())
+        .map_err(|e| BipKeychainError::HashError(format!("BLAKE2b hashing failed: {}", e)))?;
+    hasher.update(data);
+    hasher
+        .finalize_variable(output)
+        .map_err(|e| BipKeychainError::HashError(format!("BLAKE2b hashing failed: {}", e)))
 }
 
 /// SHA-256 implementation (padded to 64 bytes)
@@ -97,15 +175,12 @@ fn blake2b_hash(entity_json: &str) -> Result<[u8; 64]> {
 ///
 /// Note: For security-critical applications, prefer HMAC-SHA-512 or BLAKE2b
 /// which natively produce 512-bit (64-byte) outputs.
-fn sha256_padded(entity_json: &str, _parent_entropy: &[u8]) -> Result<[u8; 64]> {
+fn sha256_padded(data: &[u8], _parent_entropy: &[u8]) -> Result<[u8; 64]> {
     use sha2::{Digest, Sha256};
 
-    // Canonicalize JSON for deterministic hashing
-    let canonical = canonicalize_json(entity_json)?;
-
     // SHA-256 hash (32 bytes)
     let mut hasher = Sha256::new();
-    hasher.update(canonical.as_bytes());
+    hasher.update(data);
     let hash_32 = hasher.finalize();
 
     // Pad to 64 bytes with zeros
@@ -116,28 +191,146 @@ fn sha256_padded(entity_json: &str, _parent_entropy: &[u8]) -> Result<[u8; 64]>
     Ok(output)
 }
 
-/// Canonicalize JSON string for deterministic hashing
+/// BLAKE3 keyed hash with 64-byte XOF output
+///
+/// BLAKE3's keyed mode takes a 32-byte key. A 32-byte parent entropy is used
+/// as the key directly; any other length is first compressed to 32 bytes with
+/// BLAKE3's key derivation mode under [`BLAKE3_KEY_CONTEXT`]. The extendable
+/// output is read to 64 bytes, so the first 32 bytes equal the plain keyed
+/// hash.
+fn blake3_keyed(data: &[u8], parent_entropy: &[u8]) -> Result<[u8; 64]> {
+    if parent_entropy.is_empty() {
+        return Err(BipKeychainError::HashError(
+            "BLAKE3 keyed hash requires non-empty parent entropy".to_string(),
+        ));
+    }
+
+    let key: [u8; 32] = match parent_entropy.try_into() {
+        Ok(key) => key,
+        Err(_) => blake3::derive_key(BLAKE3_KEY_CONTEXT, parent_entropy),
+    };
+
+    let mut hasher = blake3::Hasher::new_keyed(&key);
+    hasher.update(data);
+
+    let mut output = [0u8; 64];
+    hasher.finalize_xof().fill(&mut output);
+
+    Ok(output)
+}
+
+/// Argon2id memory-hard hash
+///
+/// Entities such as hostnames or email addresses are easy to guess. Anyone
+/// holding an xpub and the parent entropy can test guesses by recomputing
+/// indices; Argon2id makes each guess cost [`ARGON2ID_MEMORY_KIB`] of memory
+/// and [`ARGON2ID_ITERATIONS`] passes instead of one HMAC.
+///
+/// The parameters are fixed: changing them would change every derived key.
+/// The parent entropy is the salt and must be at least 8 bytes.
+fn argon2id_hash(data: &[u8], parent_entropy: &[u8]) -> Result<[u8; 64]> {
+    use argon2::{Algorithm, Argon2, Params, Version};
+
+    let params = Params::new(
+        ARGON2ID_MEMORY_KIB,
+        ARGON2ID_ITERATIONS,
+        ARGON2ID_PARALLELISM,
+        Some(64),
+    )
+    .map_err(|e| BipKeychainError::HashError(format!("Argon2id parameters: {}", e)))?;
+
+    let mut output = [0u8; 64];
+    Argon2::new(Algorithm::Argon2id, Version::V0x13, params)
+        .hash_password_into(data, parent_entropy, &mut output)
+        .map_err(|e| BipKeychainError::HashError(format!("Argon2id hashing failed: {}", e)))?;
+
+    Ok(output)
+}
+
+/// Canonicalize entity JSON for deterministic hashing
+///
+/// If the input is valid JSON, re-serialize it in the requested form
+/// ([`Canonicalization::None`] keeps the input bytes unchanged).
 ///
-/// If the input is valid JSON, re-serialize it in canonical form:
-/// - Keys sorted alphabetically
-/// - No whitespace
-/// - UTF-8 encoding
+/// If the input is not JSON, strict mode returns
+/// [`BipKeychainError::InvalidEntity`]; otherwise the input is returned
+/// as-is (for plain-text test vectors).
+pub fn canonicalize(input: &str, mode: Canonicalization, strict: bool) -> Result<Vec<u8>> {
+    canonicalize_borrowed(input, mode, strict).map(Cow::into_owned)
+}
+
+/// Canonicalize an already-parsed JSON value
 ///
-/// If the input is not JSON (e.g., plain text test vectors), return as-is.
-fn canonicalize_json(input: &str) -> Result<String> {
+/// Avoids the string round-trip when the entity is held as a [`Value`].
+/// [`Canonicalization::None`] has no source text to preserve here, so it
+/// produces the same bytes as [`Canonicalization::Serde`].
+pub fn canonicalize_value(value: &Value, mode: Canonicalization) -> Result<Vec<u8>> {
+    match mode {
+        // serde_json sorts keys by default
+        Canonicalization::Serde | Canonicalization::None => serde_json::to_vec(value)
+            .map_err(|e| BipKeychainError::HashError(format!("JSON serialization error: {}", e))),
+        Canonicalization::Jcs => Ok(jcs::canonicalize(value).into_bytes()),
+        Canonicalization::Dcbor => Ok(dcbor::encode_json(value)),
+    }
+}
+
+/// Like [`canonicalize`], but borrows the input when it is already in
+/// canonical form instead of copying it
+fn canonicalize_borrowed(
+    input: &str,
+    mode: Canonicalization,
+    strict: bool,
+) -> Result<Cow<'_, [u8]>> {
     // Try to parse as JSON
-    match serde_json::from_str::<Value>(input) {
-        Ok(value) => {
-            // Re-serialize in canonical form (serde_json sorts keys by default)
-            serde_json::to_string(&value).map_err(|e| {
-                BipKeychainError::HashError(format!("JSON serialization error: {}", e))
-            })
+    let value = match serde_json::from_str::<Value>(input) {
+        Ok(value) => value,
+        Err(e) if strict => return Err(BipKeychainError::InvalidEntity(e)),
+        // Not JSON, use input as-is (for test vectors)
+        Err(_) => return Ok(Cow::Borrowed(input.as_bytes())),
+    };
+
+    match mode {
+        Canonicalization::None => Ok(Cow::Borrowed(input.as_bytes())),
+        Canonicalization::Serde if is_serde_canonical(input, &value) => {
+            Ok(Cow::Borrowed(input.as_bytes()))
+        }
+        _ => canonicalize_value(&value, mode).map(Cow::Owned),
+    }
+}
+
+/// Check whether `input` is byte-for-byte the serde canonical form of
+/// `value`, without allocating the serialized output
+fn is_serde_canonical(input: &str, value: &Value) -> bool {
+    // Serde output is a single line; reject pretty-printed input up front
+    if input.contains('\n') {
+        return false;
+    }
+
+    /// Writer that fails as soon as the output diverges from `expected`
+    struct MatchWriter<'a> {
+        expected: &'a [u8],
+    }
+
+    impl io::Write for MatchWriter<'_> {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            match self.expected.strip_prefix(buf) {
+                Some(rest) => {
+                    self.expected = rest;
+                    Ok(buf.len())
+                }
+                None => Err(io::ErrorKind::InvalidData.into()),
+            }
         }
-        Err(_) => {
-            // Not JSON, use input as-is (for test vectors)
-            Ok(input.to_string())
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
         }
     }
+
+    let mut writer = MatchWriter {
+        expected: input.as_bytes(),
+    };
+    serde_json::to_writer(&mut writer, value).is_ok() && writer.expected.is_empty()
 }
 
 #[cfg(test)]
@@ -152,12 +345,67 @@ mod tests {
   "age": 30,
   "city": "NYC"
 }"#;
-        let canonical = canonicalize_json(pretty).unwrap();
-        assert_eq!(canonical, r#"{"age":30,"city":"NYC","name":"test"}"#);
+        let canonical = canonicalize(pretty, Canonicalization::Serde, false).unwrap();
+        assert_eq!(canonical, br#"{"age":30,"city":"NYC","name":"test"}"#);
 
         // Test with plain text (non-JSON)
         let plain = "Hi There";
-        let result = canonicalize_json(plain).unwrap();
-        assert_eq!(result, plain);
+        let result = canonicalize(plain, Canonicalization::Serde, false).unwrap();
+        assert_eq!(result, plain.as_bytes());
+    }
+
+    #[test]
+    fn test_canonicalize_borrows_canonical_input() {
+        let canonical = r#"{"age":30,"city":"NYC","name":"test"}"#;
+        let result = canonicalize_borrowed(canonical, Canonicalization::Serde, false).unwrap();
+        assert!(matches!(result, Cow::Borrowed(_)));
+
+        // A canonical prefix is not enough
+        let padded = r#"{"age":30,"city":"NYC","name":"test"} "#;
+        let result = canonicalize_borrowed(padded, Canonicalization::Serde, false).unwrap();
+        assert!(matches!(result, Cow::Owned(_)));
+        assert_eq!(result.as_ref(), canonical.as_bytes());
+
+        let unsorted = r#"{"name":"test","age":30}"#;
+        let result = canonicalize_borrowed(unsorted, Canonicalization::Serde, false).unwrap();
+        assert_eq!(result.as_ref(), br#"{"age":30,"name":"test"}"#);
+    }
+
+    #[test]
+    fn test_canonicalize_value_matches_canonicalize() {
+        let input = r#"{"b": [1, 2.5, "x"], "a": {"d": null, "c": true}}"#;
+        let value: Value = serde_json::from_str(input).unwrap();
+
+        for mode in [
+            Canonicalization::Serde,
+            Canonicalization::Jcs,
+            Canonicalization::Dcbor,
+        ] {
+            assert_eq!(
+                canonicalize_value(&value, mode).unwrap(),
+                canonicalize(input, mode, true).unwrap()
+            );
+        }
+    }
+
+    #[test]
+    fn test_canonicalize_strict_rejects_non_json() {
+        for mode in [
+            Canonicalization::Serde,
+            Canonicalization::Jcs,
+            Canonicalization::Dcbor,
+            Canonicalization::None,
+        ] {
+            assert!(canonicalize("Hi There", mode, true).is_err());
+            assert!(canonicalize(r#"{"name": "test""#, mode, true).is_err());
+            assert!(canonicalize(r#"{"name": "test"}"#, mode, true).is_ok());
+        }
+    }
+
+    #[test]
+    fn test_canonicalize_none_keeps_input() {
+        let input = r#"{ "b": 1, "a": 2 }"#;
+        let result = canonicalize(input, Canonicalization::None, true).unwrap();
+        assert_eq!(result, input.as_bytes());
     }
 }
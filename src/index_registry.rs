@@ -0,0 +1,255 @@
+//! Index collision registry
+//!
+//! Each entity's hash is cut down to 31-bit path indices, so two distinct
+//! entities can land on the same derivation path and silently derive the
+//! same key. The registry remembers which entity hash was first derived at
+//! each path, so a later, different entity at that path is caught:
+//!
+//! ```json
+//! {
+//!   "entries": [
+//!     {
+//!       "id": "4e0a9c1f7d2b3856",
+//!       "path": "m/83696968'/67797668'/1234567'",
+//!       "entity_hash": "9f1c...",
+//!       "source": "examples/server-prod.json",
+//!       "first_seen": 1760572800
+//!     }
+//!   ]
+//! }
+//! ```
+//!
+//! Like the audit log, entries hold the entity hash rather than the entity,
+//! so the registry reveals no entity contents. Entries are kept sorted by
+//! entity hash (then path) and carry an [`entry_id`], so two registries
+//! over the same entities are byte-identical however they were built.
+
+use crate::{
+    audit::entry_id,
+    error::{BipKeychainError, Result},
+};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::Path;
+
+/// The entity first derived at a path
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+pub struct IndexEntry {
+    /// Stable ID from the entity hash and path (see [`entry_id`])
+    #[serde(default)]
+    pub id: String,
+
+    /// BIP-32 derivation path
+    pub path: String,
+
+    /// Entity hash as 128 hex characters
+    pub entity_hash: String,
+
+    /// Optional entity file the hash came from, for messages
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub source: Option<String>,
+
+    /// When the path was first derived, in seconds since the Unix epoch
+    pub first_seen: u64,
+}
+
+/// Derivation paths in use (`index-registry.json`)
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize, Serialize)]
+pub struct IndexRegistry {
+    /// Entries sorted by entity hash, then path
+    pub entries: Vec<IndexEntry>,
+}
+
+fn registry_error(message: String) -> BipKeychainError {
+    BipKeychainError::IndexRegistryError(message)
+}
+
+impl IndexRegistry {
+    /// Parse a registry from JSON
+    ///
+    /// Entries are re-sorted and their IDs recomputed, so registries written
+    /// before entries were ordered load the same as current ones.
+    pub fn from_json(json: &str) -> Result<Self> {
+        let mut registry: Self = serde_json::from_str(json)
+            .map_err(|e| registry_error(format!("Invalid index registry: {}", e)))?;
+        for entry in &mut registry.entries {
+            let valid = hex::decode(&entry.entity_hash).is_ok_and(|hash| hash.len() == 64);
+            if !valid {
+                return Err(registry_error(format!(
+                    "Entity hash '{}' for {} is not 128 hex characters",
+                    entry.entity_hash, entry.path
+                )));
+            }
+            entry.entity_hash.make_ascii_lowercase();
+            entry.id = entry_id(&entry.entity_hash, &entry.path);
+        }
+        registry.entries.sort_by(Self::order);
+        Ok(registry)
+    }
+
+    /// Listing order: entity hash, then path
+    fn order(a: &IndexEntry, b: &IndexEntry) -> std::cmp::Ordering {
+        (&a.entity_hash, &a.path).cmp(&(&b.entity_hash, &b.path))
+    }
+
+    /// Read a registry; a missing file is an empty registry
+    pub fn load(path: &Path) -> Result<Self> {
+        match fs::read_to_string(path) {
+            Ok(json) => Self::from_json(&json),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(Self::default()),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    /// Write the registry as pretty-printed JSON
+    pub fn save(&self, path: &Path) -> Result<()> {
+        let json = serde_json::to_string_pretty(self)
+            .map_err(|e| registry_error(format!("Failed to serialize: {}", e)))?;
+        fs::write(path, json + "\n")?;
+        Ok(())
+    }
+
+    /// The entry for `path`, if it has been derived
+    pub fn find(&self, path: &str) -> Option<&IndexEntry> {
+        self.entries.iter().find(|entry| entry.path == path)
+    }
+
+    /// Record that `entity_hash` derives at `path`
+    ///
+    /// Returns `Ok(true)` for a new entry and `Ok(false)` if the same entity
+    /// was already recorded there. A different entity already at `path` is
+    /// returned as the error, and the registry is left unchanged.
+    pub fn register(
+        &mut self,
+        path: &str,
+        entity_hash: &[u8; 64],
+        source: Option<String>,
+        first_seen: u64,
+    ) -> std::result::Result<bool, &IndexEntry> {
+        let entity_hash = hex::encode(entity_hash);
+        match self.entries.iter().position(|entry| entry.path == path) {
+            Some(i)
+                if self.entries[i]
+                    .entity_hash
+                    .eq_ignore_ascii_case(&entity_hash) =>
+            {
+                Ok(false)
+            }
+            Some(i) => Err(&self.entries[i]),
+            None => {
+                let entry = IndexEntry {
+                    id: entry_id(&entity_hash, path),
+                    path: path.to_string(),
+                    entity_hash,
+                    source,
+                    first_seen,
+                };
+                let at = self
+                    .entries
+                    .partition_point(|existing| Self::order(existing, &entry).is_lt());
+                self.entries.insert(at, entry);
+                Ok(true)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PATH: &str = "m/83696968'/67797668'/1234567'";
+
+    #[test]
+    fn test_register_detects_collisions() {
+        let mut registry = IndexRegistry::default();
+        assert_eq!(
+            registry.register(PATH, &[1u8; 64], Some("a.json".to_string()), 10),
+            Ok(true)
+        );
+        // The same entity again is not a collision
+        assert_eq!(registry.register(PATH, &[1u8; 64], None, 20), Ok(false));
+
+        let existing = registry
+            .register(PATH, &[2u8; 64], Some("b.json".to_string()), 30)
+            .unwrap_err();
+        assert_eq!(existing.source.as_deref(), Some("a.json"));
+        assert_eq!(existing.first_seen, 10);
+        assert_eq!(registry.entries.len(), 1);
+
+        // Other paths are independent
+        assert_eq!(
+            registry.register("m/83696968'/67797668'/7'", &[2u8; 64], None, 30),
+            Ok(true)
+        );
+        assert_eq!(
+            registry.find(PATH).unwrap().entity_hash,
+            hex::encode([1u8; 64])
+        );
+    }
+
+    #[test]
+    fn test_registry_round_trip() {
+        let mut registry = IndexRegistry::default();
+        registry.register(PATH, &[1u8; 64], None, 10).unwrap();
+        let path = std::env::temp_dir().join(format!(
+            "bip-keychain-index-registry-{}.json",
+            std::process::id()
+        ));
+        registry.save(&path).unwrap();
+        assert_eq!(IndexRegistry::load(&path).unwrap(), registry);
+        fs::remove_file(&path).unwrap();
+        assert_eq!(
+            IndexRegistry::load(&path).unwrap(),
+            IndexRegistry::default()
+        );
+
+        assert!(IndexRegistry::from_json(
+            r#"{"entries": [{"path": "m/0'", "entity_hash": "abcd", "first_seen": 0}]}"#
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn test_registry_order_is_independent_of_derivation_order() {
+        let paths = ["m/83696968'/67797668'/5'", PATH, "m/83696968'/67797668'/9'"];
+        let hashes = [[3u8; 64], [1u8; 64], [2u8; 64]];
+
+        let mut forward = IndexRegistry::default();
+        for (path, hash) in paths.iter().zip(&hashes) {
+            forward.register(path, hash, None, 10).unwrap();
+        }
+        let mut shuffled = IndexRegistry::default();
+        for i in [2, 0, 1] {
+            shuffled.register(paths[i], &hashes[i], None, 10).unwrap();
+        }
+
+        let json = serde_json::to_string_pretty(&forward).unwrap();
+        assert_eq!(json, serde_json::to_string_pretty(&shuffled).unwrap());
+        let listed: Vec<_> = forward.entries.iter().map(|e| &e.entity_hash).collect();
+        assert_eq!(
+            listed,
+            [
+                hex::encode([1u8; 64]),
+                hex::encode([2u8; 64]),
+                hex::encode([3u8; 64])
+            ]
+            .iter()
+            .collect::<Vec<_>>()
+        );
+        assert_eq!(
+            forward.entries[0].id,
+            entry_id(&hex::encode([1u8; 64]), PATH)
+        );
+
+        // A registry saved in another order loads sorted, with IDs
+        let mut unordered = forward.clone();
+        unordered.entries.reverse();
+        for entry in &mut unordered.entries {
+            entry.id.clear();
+        }
+        let reloaded =
+            IndexRegistry::from_json(&serde_json::to_string(&unordered).unwrap()).unwrap();
+        assert_eq!(reloaded, forward);
+    }
+}
@@ -0,0 +1,184 @@
+//! JSON Canonicalization Scheme (JCS, RFC 8785)
+//!
+//! Serializes JSON so that equal values always produce identical bytes:
+//! - no insignificant whitespace
+//! - object members sorted by the UTF-16 code units of their names
+//! - numbers formatted as ECMAScript doubles (`1e+21`, `0.000001`)
+//! - strings escaped as `JSON.stringify` does
+//!
+//! Unlike the default serde output, JCS has implementations in many
+//! languages, so other tools can reproduce BIP-Keychain entity hashes.
+
+use serde_json::Value;
+
+/// Serialize a JSON value in JCS canonical form
+pub fn canonicalize(value: &Value) -> String {
+    let mut out = String::new();
+    append_value(&mut out, value);
+    out
+}
+
+fn append_value(out: &mut String, value: &Value) {
+    match value {
+        Value::Null => out.push_str("null"),
+        Value::Bool(b) => out.push_str(if *b { "true" } else { "false" }),
+        // JCS numbers are IEEE 754 doubles; integers beyond 2^53 lose precision
+        Value::Number(n) => out.push_str(&format_number(n.as_f64().unwrap_or(0.0))),
+        Value::String(s) => append_string(out, s),
+        Value::Array(items) => {
+            out.push('[');
+            for (i, item) in items.iter().enumerate() {
+                if i > 0 {
+                    out.push(',');
+                }
+                append_value(out, item);
+            }
+            out.push(']');
+        }
+        Value::Object(map) => {
+            let mut entries: Vec<(&String, &Value)> = map.iter().collect();
+            entries.sort_by(|a, b| a.0.encode_utf16().cmp(b.0.encode_utf16()));
+
+            out.push('{');
+            for (i, (key, value)) in entries.into_iter().enumerate() {
+                if i > 0 {
+                    out.push(',');
+                }
+                append_string(out, key);
+                out.push(':');
+                append_value(out, value);
+            }
+            out.push('}');
+        }
+    }
+}
+
+fn append_string(out: &mut String, s: &str) {
+    // serde_json escapes exactly the characters JSON.stringify does
+    // (quote, backslash, control characters) with lowercase \u00XX hex
+    out.push_str(&serde_json::to_string(s).expect("strings always serialize"));
+}
+
+/// Format a double as ECMAScript Number.prototype.toString does
+fn format_number(value: f64) -> String {
+    if value == 0.0 {
+        // Covers -0 as well
+        return "0".to_string();
+    }
+
+    // Shortest round-trip digits in scientific form, e.g. "1.2345e-7"
+    let scientific = format!("{:e}", value.abs());
+    let (mantissa, exponent) = scientific
+        .split_once('e')
+        .expect("LowerExp output contains an exponent");
+    let digits: String = mantissa.chars().filter(|c| *c != '.').collect();
+    let exponent: i32 = exponent.parse().expect("LowerExp exponent is an integer");
+
+    // ECMAScript's k (digit count) and n (decimal point position)
+    let k = digits.len() as i32;
+    let n = exponent + 1;
+
+    let body = if k <= n && n <= 21 {
+        format!("{}{}", digits, "0".repeat((n - k) as usize))
+    } else if 0 < n && n <= 21 {
+        let (integer, fraction) = digits.split_at(n as usize);
+        format!("{}.{}", integer, fraction)
+    } else if -6 < n && n <= 0 {
+        format!("0.{}{}", "0".repeat(-n as usize), digits)
+    } else {
+        let (first, rest) = digits.split_at(1);
+        let fraction = if rest.is_empty() {
+            String::new()
+        } else {
+            format!(".{}", rest)
+        };
+        let sign = if n - 1 < 0 { '-' } else { '+' };
+        format!("{}{}e{}{}", first, fraction, sign, (n - 1).abs())
+    };
+
+    if value < 0.0 {
+        format!("-{}", body)
+    } else {
+        body
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_number_formatting_rfc8785_vectors() {
+        // RFC 8785 Appendix B
+        let vectors: &[(u64, &str)] = &[
+            (0x0000000000000000, "0"),
+            (0x8000000000000000, "0"),
+            (0x0000000000000001, "5e-324"),
+            (0x7fefffffffffffff, "1.7976931348623157e+308"),
+            (0x4340000000000000, "9007199254740992"),
+            (0xc340000000000000, "-9007199254740992"),
+            (0x4430000000000000, "295147905179352830000"),
+            (0x44b52d02c7e14af5, "9.999999999999997e+22"),
+            (0x44b52d02c7e14af6, "1e+23"),
+            (0x444b1ae4d6e2ef50, "1e+21"),
+            (0x444b1ae4d6e2ef4f, "999999999999999900000"),
+            (0x3eb0c6f7a0b5ed8d, "0.000001"),
+            (0x3eb0c6f7a0b5ed8c, "9.999999999999997e-7"),
+        ];
+
+        for (bits, expected) in vectors {
+            assert_eq!(format_number(f64::from_bits(*bits)), *expected);
+        }
+    }
+
+    #[test]
+    #[allow(clippy::excessive_precision)]
+    fn test_rfc8785_example() {
+        // RFC 8785 Section 3.2.3
+        let value = json!({
+            "numbers": [333333333.33333329, 1E30, 4.50, 2e-3, 0.000000000000000000000000001],
+            "string": "\u{20ac}$\u{000F}\u{000a}A'\u{0042}\u{0022}\u{005c}\\\"/",
+            "literals": [null, true, false]
+        });
+
+        assert_eq!(
+            canonicalize(&value),
+            r#"{"literals":[null,true,false],"numbers":[333333333.3333333,1e+30,4.5,0.002,1e-27],"string":"€$\u000f\nA'B\"\\\\\"/"}"#
+        );
+    }
+
+    #[test]
+    fn test_keys_sorted_by_utf16() {
+        // RFC 8785 Section 3.2.3 sorting example: U+1F600 (surrogate pair
+        // D83D DE00) sorts before U+FB33 in UTF-16 order
+        let value = json!({
+            "\u{20ac}": "Euro Sign",
+            "\r": "Carriage Return",
+            "\u{fb33}": "Hebrew Letter Dalet With Dagesh",
+            "1": "One",
+            "\u{1f600}": "Emoji: Grinning Face",
+            "\u{0080}": "Control",
+            "\u{00f6}": "Latin Small Letter O With Diaeresis"
+        });
+
+        let canonical = canonicalize(&value);
+        let values: Vec<&str> = canonical
+            .split("\":\"")
+            .skip(1)
+            .map(|part| part.split('"').next().unwrap())
+            .collect();
+        assert_eq!(
+            values,
+            [
+                "Carriage Return",
+                "One",
+                "Control",
+                "Latin Small Letter O With Diaeresis",
+                "Euro Sign",
+                "Emoji: Grinning Face",
+                "Hebrew Letter Dalet With Dagesh"
+            ]
+        );
+    }
+}
@@ -0,0 +1,323 @@
+//! Published key manifests
+//!
+//! A key manifest lists the public keys derived for a set of entities, so
+//! third parties can check which key belongs to which semantic identity:
+//!
+//! ```json
+//! {
+//!   "keys": [
+//!     {
+//!       "id": "c2d81f0e6a4b9735",
+//!       "entity_hash": "3b7f...",
+//!       "purpose": "Git commit signing key",
+//!       "public_key": "55c077e6...",
+//!       "fingerprint": "SHA256:..."
+//!     }
+//!   ],
+//!   "issued_at": 1760572800,
+//!   "signer": "ssh-ed25519 AAAA...",
+//!   "signature": "-----BEGIN SSH SIGNATURE-----\n..."
+//! }
+//! ```
+//!
+//! `entity_hash` is the SHA-256 of the entity's canonical form, as in
+//! derivation receipts (see [`crate::receipt`]), so anyone holding an
+//! entity file can find its key without the seed. Keys are Ed25519 with
+//! their OpenSSH fingerprint; entities with another `key_type` publish that
+//! key (compressed for secp256k1 and P-256) with its `key_type` and JWK
+//! thumbprint. The signature is SSHSIG with namespace
+//! [`KEY_MANIFEST_NAMESPACE`] over the JCS (RFC 8785) encoding of the
+//! document without `signer` and `signature`.
+//!
+//! Keys are listed sorted by entity hash (then public key), each with an
+//! [`entry_id`], so two exports over the same entities differ only where a
+//! key did.
+
+use crate::{
+    audit::entry_id,
+    bip32_wrapper::DerivedKey,
+    curve_key::CurveKey,
+    entity::{KeyDerivation, KeyTypeConfig},
+    error::{BipKeychainError, Result},
+    jcs,
+    output::Ed25519Keypair,
+    receipt::receipt_entity_hash,
+    sshsig,
+};
+use serde::{Deserialize, Serialize};
+
+/// SSHSIG namespace for signed key manifests
+pub const KEY_MANIFEST_NAMESPACE: &str = "bip-keychain-manifest";
+
+fn is_ed25519(value: &KeyTypeConfig) -> bool {
+    *value == KeyTypeConfig::Ed25519
+}
+
+/// One entity's published key
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+pub struct PublishedKey {
+    /// Stable ID from the entity hash and public key (see [`entry_id`];
+    /// absent in manifests exported before IDs were added)
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub id: String,
+
+    /// SHA-256 of the entity's canonical form, as 64 hex characters
+    pub entity_hash: String,
+
+    /// What the key is for
+    pub purpose: String,
+
+    /// Kind of key (absent for Ed25519)
+    #[serde(default, skip_serializing_if = "is_ed25519")]
+    pub key_type: KeyTypeConfig,
+
+    /// Public key as hex
+    pub public_key: String,
+
+    /// OpenSSH SHA256 fingerprint, or the JWK thumbprint for other key types
+    pub fingerprint: String,
+}
+
+impl PublishedKey {
+    /// Describe the key derived from `key_derivation`
+    pub fn new(
+        key_derivation: &KeyDerivation,
+        derived_key: &DerivedKey,
+        purpose: impl Into<String>,
+    ) -> Result<Self> {
+        let key_type = key_derivation.derivation_config.key_type;
+        let (public_key, fingerprint) = match key_type {
+            KeyTypeConfig::Ed25519 => {
+                let keypair = Ed25519Keypair::from_derived_key(derived_key);
+                (
+                    keypair.public_key_bytes().to_vec(),
+                    keypair.ssh_fingerprint(),
+                )
+            }
+            _ => {
+                let key = CurveKey::from_derived_key(derived_key, key_type)?;
+                (key.public_key_bytes(), key.thumbprint())
+            }
+        };
+        let entity_hash = hex::encode(receipt_entity_hash(key_derivation)?);
+        let public_key = hex::encode(public_key);
+        Ok(Self {
+            id: entry_id(&entity_hash, &public_key),
+            entity_hash,
+            purpose: purpose.into(),
+            key_type,
+            public_key,
+            fingerprint,
+        })
+    }
+}
+
+/// The keys of a set of entities, before signing
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize, Serialize)]
+pub struct KeyManifest {
+    /// Keys sorted by entity hash, then public key
+    pub keys: Vec<PublishedKey>,
+
+    /// When the manifest was issued, in seconds since the Unix epoch
+    pub issued_at: u64,
+}
+
+fn manifest_error(message: String) -> BipKeychainError {
+    BipKeychainError::SignatureError(message)
+}
+
+impl KeyManifest {
+    /// A manifest of `keys`, in listing order whatever order they came in
+    pub fn new(mut keys: Vec<PublishedKey>, issued_at: u64) -> Self {
+        keys.sort_by(|a, b| (&a.entity_hash, &a.public_key).cmp(&(&b.entity_hash, &b.public_key)));
+        Self { keys, issued_at }
+    }
+
+    /// Canonical bytes covered by the signature
+    fn signed_bytes(&self) -> Result<Vec<u8>> {
+        let value = serde_json::to_value(self)
+            .map_err(|e| manifest_error(format!("Failed to serialize: {}", e)))?;
+        Ok(jcs::canonicalize(&value).into_bytes())
+    }
+
+    /// Sign the manifest for publication
+    pub fn sign(&self, keypair: &Ed25519Keypair) -> Result<SignedKeyManifest> {
+        Ok(SignedKeyManifest {
+            keys: self.keys.clone(),
+            issued_at: self.issued_at,
+            signer: keypair.to_ssh_public_key(None),
+            signature: sshsig::sign(keypair, KEY_MANIFEST_NAMESPACE, &self.signed_bytes()?)?,
+        })
+    }
+
+    /// The published key for `key_derivation`, if the manifest lists it
+    pub fn find(&self, key_derivation: &KeyDerivation) -> Result<Option<&PublishedKey>> {
+        let entity_hash = hex::encode(receipt_entity_hash(key_derivation)?);
+        Ok(self
+            .keys
+            .iter()
+            .find(|key| key.entity_hash.eq_ignore_ascii_case(&entity_hash)))
+    }
+}
+
+/// A key manifest with an SSHSIG signature over its JCS encoding
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+pub struct SignedKeyManifest {
+    /// The published keys
+    pub keys: Vec<PublishedKey>,
+
+    /// When the manifest was issued, in seconds since the Unix epoch
+    pub issued_at: u64,
+
+    /// Signer's OpenSSH public key
+    pub signer: String,
+
+    /// Armored SSHSIG signature
+    pub signature: String,
+}
+
+impl SignedKeyManifest {
+    /// Parse a signed manifest from JSON
+    pub fn from_json(json: &str) -> Result<Self> {
+        serde_json::from_str(json)
+            .map_err(|e| manifest_error(format!("Invalid signed key manifest: {}", e)))
+    }
+
+    /// Check the signature and return the manifest
+    ///
+    /// The signature must be made by `signer` (the embedded signer key is
+    /// only a hint and is not trusted on its own).
+    pub fn verify(&self, signer: &[u8; 32]) -> Result<KeyManifest> {
+        let manifest = KeyManifest {
+            keys: self.keys.clone(),
+            issued_at: self.issued_at,
+        };
+        let actual = sshsig::verify(
+            &self.signature,
+            KEY_MANIFEST_NAMESPACE,
+            &manifest.signed_bytes()?,
+        )?;
+        if &actual != signer {
+            return Err(manifest_error(
+                "Key manifest was signed by a different key".to_string(),
+            ));
+        }
+        Ok(manifest)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{bip32_wrapper::Keychain, derivation::derive_key_from_entity};
+
+    fn published(key_type: &str) -> (KeyDerivation, PublishedKey) {
+        let key_derivation = KeyDerivation::from_json(&format!(
+            r#"{{
+  "schema_type": "schema_org",
+  "entity": {{"@type": "Organization", "name": "server"}},
+  "derivation_config": {{"hash_function": "hmac_sha512", "hardened": true, "key_type": "{}"}}
+}}"#,
+            key_type
+        ))
+        .unwrap();
+        let keychain = Keychain::from_mnemonic(
+            "abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon about",
+        )
+        .unwrap();
+        let derived =
+            derive_key_from_entity(&keychain, &key_derivation, crate::DEFAULT_PARENT_ENTROPY)
+                .unwrap();
+        let key = PublishedKey::new(&key_derivation, &derived, "server key").unwrap();
+        (key_derivation, key)
+    }
+
+    #[test]
+    fn test_published_keys() {
+        let (key_derivation, ed25519) = published("ed25519");
+        assert_eq!(ed25519.public_key.len(), 64);
+        assert!(ed25519.fingerprint.starts_with("SHA256:"));
+        assert!(!serde_json::to_string(&ed25519)
+            .unwrap()
+            .contains("key_type"));
+
+        let (_, p256) = published("p256");
+        assert_eq!(p256.key_type, KeyTypeConfig::P256);
+        assert_eq!(p256.public_key.len(), 66);
+        assert_eq!(p256.entity_hash, ed25519.entity_hash);
+
+        let manifest = KeyManifest::new(vec![ed25519.clone()], 1_760_000_000);
+        assert_eq!(manifest.find(&key_derivation).unwrap(), Some(&ed25519));
+        assert_eq!(
+            manifest.find(&published("ed25519").0).unwrap(),
+            Some(&ed25519)
+        );
+    }
+
+    #[test]
+    fn test_signed_manifest_verifies() {
+        let authority = Ed25519Keypair::from_seed([1u8; 32]);
+        let other = Ed25519Keypair::from_seed([2u8; 32]);
+        let manifest = KeyManifest::new(
+            vec![published("ed25519").1, published("secp256k1").1],
+            1_760_000_000,
+        );
+
+        let signed = manifest.sign(&authority).unwrap();
+        let round_tripped =
+            SignedKeyManifest::from_json(&serde_json::to_string(&signed).unwrap()).unwrap();
+        assert_eq!(
+            round_tripped.verify(&authority.public_key_bytes()).unwrap(),
+            manifest
+        );
+        assert!(round_tripped.verify(&other.public_key_bytes()).is_err());
+
+        // Swapping a key for another entity's breaks the signature
+        let mut tampered = round_tripped.clone();
+        tampered.keys[0].public_key = hex::encode(other.public_key_bytes());
+        assert!(tampered.verify(&authority.public_key_bytes()).is_err());
+
+        let mut backdated = round_tripped;
+        backdated.issued_at -= 1;
+        assert!(backdated.verify(&authority.public_key_bytes()).is_err());
+    }
+
+    #[test]
+    fn test_manifest_order_is_independent_of_input_order() {
+        let keys = vec![
+            published("ed25519").1,
+            published("secp256k1").1,
+            published("p256").1,
+        ];
+        let mut shuffled = keys.clone();
+        shuffled.rotate_left(1);
+        shuffled.swap(0, 1);
+
+        let manifest = KeyManifest::new(keys, 1_760_000_000);
+        let reordered = KeyManifest::new(shuffled, 1_760_000_000);
+        assert_eq!(
+            serde_json::to_string_pretty(&manifest).unwrap(),
+            serde_json::to_string_pretty(&reordered).unwrap()
+        );
+        assert!(manifest
+            .keys
+            .windows(2)
+            .all(|pair| pair[0].public_key < pair[1].public_key));
+        let key = &manifest.keys[0];
+        assert_eq!(key.id, entry_id(&key.entity_hash, &key.public_key));
+
+        // Manifests exported before IDs were added still verify
+        let authority = Ed25519Keypair::from_seed([1u8; 32]);
+        let mut legacy = manifest;
+        for key in &mut legacy.keys {
+            key.id.clear();
+        }
+        let signed = legacy.sign(&authority).unwrap();
+        let json = serde_json::to_string(&signed).unwrap();
+        assert!(!json.contains("\"id\""));
+        assert!(SignedKeyManifest::from_json(&json)
+            .unwrap()
+            .verify(&authority.public_key_bytes())
+            .is_ok());
+    }
+}
@@ -0,0 +1,350 @@
+//! Ledger hardware wallet backend
+//!
+//! [`LedgerBackend`] is a [`KeychainBackend`] whose derivation root is the
+//! seed on a Ledger device. The device runs the Ledger Bitcoin app (2.1 or
+//! later), which derives m/83696968'/67797668'/{index}' on-device and
+//! returns its extended public key; this library only hashes the entity and
+//! computes the index, so the seed never reaches the host.
+//!
+//! The path is not a standard Bitcoin path, so the app asks the user to
+//! confirm every request on the device. The device never releases private
+//! key bytes, so only secp256k1 public keys are available this way; the
+//! Ed25519 outputs, which are seeded from the private key, are not.
+//!
+//! The APDU protocol is always built, so other transports can implement
+//! [`ApduTransport`]; the USB HID transport, `LedgerHid`, needs the
+//! `hardware-wallet` feature. Trezor is not supported yet.
+
+use crate::{
+    backend::KeychainBackend,
+    error::{BipKeychainError, Result},
+};
+use bip32::XPub;
+
+/// Ledger's USB vendor ID
+pub const LEDGER_VENDOR_ID: u16 = 0x2c97;
+
+/// HID usage page of the Ledger APDU interface
+#[cfg(feature = "hardware-wallet")]
+const LEDGER_USAGE_PAGE: u16 = 0xffa0;
+
+/// Class byte of Ledger Bitcoin app commands
+const CLA_BITCOIN: u8 = 0xe1;
+/// Ledger Bitcoin app `GET_EXTENDED_PUBKEY`
+const INS_GET_EXTENDED_PUBKEY: u8 = 0x00;
+/// Ledger Bitcoin app `GET_MASTER_FINGERPRINT`
+const INS_GET_MASTER_FINGERPRINT: u8 = 0x05;
+
+/// HID report size
+#[cfg(any(feature = "hardware-wallet", test))]
+const PACKET_LEN: usize = 64;
+/// Channel ID used by Ledger host software
+#[cfg(any(feature = "hardware-wallet", test))]
+const CHANNEL: u16 = 0x0101;
+/// Tag of APDU packets
+#[cfg(any(feature = "hardware-wallet", test))]
+const TAG_APDU: u8 = 0x05;
+
+fn ledger_error(message: String) -> BipKeychainError {
+    BipKeychainError::BackendError(message)
+}
+
+/// Sends command APDUs to a device and returns its responses
+pub trait ApduTransport {
+    /// Exchange one APDU; the response ends with the two-byte status word
+    fn exchange(&self, apdu: &[u8]) -> Result<Vec<u8>>;
+}
+
+/// Split an APDU into HID packets: channel, tag, sequence number, then (in
+/// the first packet) the APDU length, with the last packet zero-padded
+#[cfg(any(feature = "hardware-wallet", test))]
+fn hid_packets(apdu: &[u8]) -> Vec<[u8; PACKET_LEN]> {
+    let mut payload = (apdu.len() as u16).to_be_bytes().to_vec();
+    payload.extend_from_slice(apdu);
+
+    payload
+        .chunks(PACKET_LEN - 5)
+        .enumerate()
+        .map(|(sequence, chunk)| {
+            let mut packet = [0u8; PACKET_LEN];
+            packet[..2].copy_from_slice(&CHANNEL.to_be_bytes());
+            packet[2] = TAG_APDU;
+            packet[3..5].copy_from_slice(&(sequence as u16).to_be_bytes());
+            packet[5..5 + chunk.len()].copy_from_slice(chunk);
+            packet
+        })
+        .collect()
+}
+
+/// Reassemble a response from HID packets read by `next_packet`
+#[cfg(any(feature = "hardware-wallet", test))]
+fn read_hid_response(mut next_packet: impl FnMut() -> Result<[u8; PACKET_LEN]>) -> Result<Vec<u8>> {
+    let mut payload = Vec::new();
+    let mut expected = None;
+    for sequence in 0u16.. {
+        let packet = next_packet()?;
+        if packet[..2] != CHANNEL.to_be_bytes()
+            || packet[2] != TAG_APDU
+            || packet[3..5] != sequence.to_be_bytes()
+        {
+            return Err(ledger_error(format!(
+                "Unexpected HID packet from device (sequence {})",
+                sequence
+            )));
+        }
+        payload.extend_from_slice(&packet[5..]);
+        let len = *expected
+            .get_or_insert_with(|| 2 + u16::from_be_bytes([payload[0], payload[1]]) as usize);
+        if payload.len() >= len {
+            payload.truncate(len);
+            break;
+        }
+    }
+    Ok(payload.split_off(2))
+}
+
+/// A Ledger device over USB HID (`hardware-wallet` feature)
+#[cfg(feature = "hardware-wallet")]
+pub struct LedgerHid {
+    device: hidapi::HidDevice,
+}
+
+#[cfg(feature = "hardware-wallet")]
+impl LedgerHid {
+    /// Open the first connected Ledger device
+    pub fn open() -> Result<Self> {
+        let api = hidapi::HidApi::new()
+            .map_err(|e| ledger_error(format!("USB HID unavailable: {}", e)))?;
+        let info = api
+            .device_list()
+            .find(|info| {
+                info.vendor_id() == LEDGER_VENDOR_ID
+                    && (info.usage_page() == LEDGER_USAGE_PAGE || info.interface_number() == 0)
+            })
+            .ok_or_else(|| ledger_error("No Ledger device found".to_string()))?;
+        let device = info
+            .open_device(&api)
+            .map_err(|e| ledger_error(format!("Failed to open Ledger device: {}", e)))?;
+        Ok(Self { device })
+    }
+}
+
+#[cfg(feature = "hardware-wallet")]
+impl ApduTransport for LedgerHid {
+    fn exchange(&self, apdu: &[u8]) -> Result<Vec<u8>> {
+        let hid_error = |e: hidapi::HidError| ledger_error(format!("Ledger HID error: {}", e));
+        for packet in hid_packets(apdu) {
+            // Report ID 0, then the packet
+            let mut report = [0u8; PACKET_LEN + 1];
+            report[1..].copy_from_slice(&packet);
+            self.device.write(&report).map_err(hid_error)?;
+        }
+        read_hid_response(|| {
+            let mut packet = [0u8; PACKET_LEN];
+            // Blocks while the user confirms on the device
+            let read = self.device.read(&mut packet).map_err(hid_error)?;
+            if read != PACKET_LEN {
+                return Err(ledger_error(format!("Short HID read ({} bytes)", read)));
+            }
+            Ok(packet)
+        })
+    }
+}
+
+/// Hardened BIP-Keychain path m/83696968'/67797668'/{entity_index}'
+fn entity_path(entity_index: u32) -> [u32; 3] {
+    const HARDENED: u32 = 1 << 31;
+    [
+        83696968 | HARDENED,
+        67797668 | HARDENED,
+        // Same child-number mapping as `Keychain::derive_bip_keychain_path`
+        entity_index.wrapping_add(HARDENED),
+    ]
+}
+
+/// [`KeychainBackend`] backed by the Ledger Bitcoin app
+pub struct LedgerBackend<T> {
+    transport: T,
+}
+
+#[cfg(feature = "hardware-wallet")]
+impl LedgerBackend<LedgerHid> {
+    /// Use the first connected Ledger device, with the Bitcoin app open
+    pub fn open() -> Result<Self> {
+        Ok(Self::new(LedgerHid::open()?))
+    }
+}
+
+impl<T: ApduTransport> LedgerBackend<T> {
+    /// Talk to a device over `transport`
+    pub fn new(transport: T) -> Self {
+        Self { transport }
+    }
+
+    /// Send a Bitcoin app command and return the response data
+    fn command(&self, ins: u8, data: &[u8]) -> Result<Vec<u8>> {
+        let mut apdu = vec![CLA_BITCOIN, ins, 0, 0, data.len() as u8];
+        apdu.extend_from_slice(data);
+        let mut response = self.transport.exchange(&apdu)?;
+        if response.len() < 2 {
+            return Err(ledger_error("Truncated response from device".to_string()));
+        }
+        let status = response.split_off(response.len() - 2);
+        match u16::from_be_bytes([status[0], status[1]]) {
+            0x9000 => Ok(response),
+            0x6985 => Err(ledger_error("Request rejected on the device".to_string())),
+            0x5515 => Err(ledger_error("Device is locked".to_string())),
+            0x6e00 | 0x6d00 | 0x6e01 | 0x6511 => Err(ledger_error(
+                "Open the Bitcoin app (2.1 or later) on the device".to_string(),
+            )),
+            status => Err(ledger_error(format!(
+                "Device returned status {:04x}",
+                status
+            ))),
+        }
+    }
+
+    /// Extended public key at `path`, confirmed on the device
+    pub fn extended_public_key(&self, path: &[u32]) -> Result<XPub> {
+        // Display flag (non-standard paths must be shown), then the path
+        let mut data = vec![1, path.len() as u8];
+        for child in path {
+            data.extend_from_slice(&child.to_be_bytes());
+        }
+        let response = self.command(INS_GET_EXTENDED_PUBKEY, &data)?;
+        std::str::from_utf8(&response)
+            .ok()
+            .and_then(|xpub| xpub.parse::<XPub>().ok())
+            .ok_or_else(|| {
+                ledger_error("Device returned an invalid extended public key".to_string())
+            })
+    }
+}
+
+impl<T: ApduTransport> KeychainBackend for LedgerBackend<T> {
+    fn name(&self) -> &str {
+        "ledger"
+    }
+
+    fn master_fingerprint(&self) -> Result<[u8; 4]> {
+        self.command(INS_GET_MASTER_FINGERPRINT, &[])?
+            .try_into()
+            .map_err(|_| ledger_error("Device returned an invalid fingerprint".to_string()))
+    }
+
+    fn public_key(&self, entity_index: u32) -> Result<[u8; 33]> {
+        Ok(self
+            .extended_public_key(&entity_path(entity_index))?
+            .to_bytes())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bip32_wrapper::Keychain;
+    use bip32::{ChildNumber, Prefix, XPrv};
+    use std::cell::Cell;
+
+    const TEST_MNEMONIC: &str = "abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon about";
+
+    /// The Bitcoin app, simulated over HID packets
+    struct SimulatedLedger {
+        master: XPrv,
+        approve: bool,
+        requests: Cell<usize>,
+    }
+
+    impl SimulatedLedger {
+        fn new(approve: bool) -> Self {
+            let seed = bip39::Mnemonic::parse(TEST_MNEMONIC).unwrap().to_seed("");
+            Self {
+                master: XPrv::new(seed).unwrap(),
+                approve,
+                requests: Cell::new(0),
+            }
+        }
+
+        fn respond(&self, apdu: &[u8]) -> Vec<u8> {
+            assert_eq!(apdu[0], CLA_BITCOIN);
+            assert_eq!(apdu[4] as usize, apdu.len() - 5);
+            let data = &apdu[5..];
+            let mut response = match apdu[1] {
+                INS_GET_MASTER_FINGERPRINT => self.master.public_key().fingerprint().to_vec(),
+                INS_GET_EXTENDED_PUBKEY if !self.approve => return vec![0x69, 0x85],
+                INS_GET_EXTENDED_PUBKEY => {
+                    assert_eq!(data[0], 1, "non-standard paths are displayed");
+                    let mut key = self.master.clone();
+                    for child in data[2..].chunks(4) {
+                        let child = u32::from_be_bytes(child.try_into().unwrap());
+                        key = key.derive_child(ChildNumber::from(child)).unwrap();
+                    }
+                    key.public_key().to_string(Prefix::XPUB).into_bytes()
+                }
+                _ => return vec![0x6d, 0x00],
+            };
+            response.extend_from_slice(&[0x90, 0x00]);
+            response
+        }
+    }
+
+    impl ApduTransport for SimulatedLedger {
+        fn exchange(&self, apdu: &[u8]) -> Result<Vec<u8>> {
+            self.requests.set(self.requests.get() + 1);
+            // Through the HID framing in both directions
+            let mut packets = hid_packets(apdu).into_iter();
+            let command = read_hid_response(|| Ok(packets.next().unwrap()))?;
+            assert_eq!(command, apdu);
+
+            let mut packets = hid_packets(&self.respond(apdu)).into_iter();
+            read_hid_response(|| Ok(packets.next().unwrap()))
+        }
+    }
+
+    #[test]
+    fn test_ledger_backend_matches_software_backend() {
+        let keychain = Keychain::from_mnemonic(TEST_MNEMONIC).unwrap();
+        let ledger = LedgerBackend::new(SimulatedLedger::new(true));
+
+        assert_eq!(ledger.name(), "ledger");
+        assert_eq!(
+            ledger.master_fingerprint().unwrap(),
+            keychain.master_fingerprint().unwrap()
+        );
+        for index in [0, 42, (1 << 31) - 1] {
+            assert_eq!(
+                ledger.public_key(index).unwrap(),
+                keychain.public_key(index).unwrap()
+            );
+        }
+        assert_eq!(ledger.transport.requests.get(), 4);
+    }
+
+    #[test]
+    fn test_ledger_rejection() {
+        let ledger = LedgerBackend::new(SimulatedLedger::new(false));
+        let error = ledger.public_key(42).unwrap_err();
+        assert!(matches!(error, BipKeychainError::BackendError(ref m) if m.contains("rejected")));
+    }
+
+    #[test]
+    fn test_hid_framing() {
+        // A 150-byte APDU spans three packets: 57, 59, and 34 bytes
+        let apdu: Vec<u8> = (0..150).map(|i| i as u8).collect();
+        let packets = hid_packets(&apdu);
+        assert_eq!(packets.len(), 3);
+        assert_eq!(&packets[0][..7], &[0x01, 0x01, 0x05, 0x00, 0x00, 0x00, 150]);
+        assert_eq!(&packets[2][..5], &[0x01, 0x01, 0x05, 0x00, 0x02]);
+        assert!(packets[2][5 + 34..].iter().all(|&b| b == 0));
+
+        let mut iter = packets.clone().into_iter();
+        assert_eq!(
+            read_hid_response(|| Ok(iter.next().unwrap())).unwrap(),
+            apdu
+        );
+
+        // Packets out of sequence are rejected
+        let mut iter = [packets[0], packets[2]].into_iter();
+        assert!(read_hid_response(|| Ok(iter.next().unwrap())).is_err());
+    }
+}
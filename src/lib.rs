@@ -14,20 +14,127 @@
 //! ```
 
 // Module declarations
+pub mod acme;
+mod aez;
+pub mod agent;
+pub mod audit;
+pub mod backend;
+pub mod backup_sheet;
 pub mod bip32_wrapper;
+pub mod bip85;
+pub mod builder;
+pub mod config;
+pub mod cosign;
+pub mod csr;
+pub mod curve_key;
+pub mod dcbor;
 pub mod derivation;
+pub mod did;
+pub mod dnssec;
 pub mod entity;
+pub mod envelope;
 pub mod error;
+pub mod eth;
+#[cfg(feature = "ffi")]
+pub mod ffi;
+pub mod foreign_mnemonic;
+pub mod frost;
 pub mod hash;
+pub mod index_registry;
+pub mod jcs;
+pub mod key_manifest;
+pub mod ledger;
+pub mod libp2p;
+pub mod manifest;
+pub mod mnemonic;
+#[cfg(feature = "uniffi")]
+pub mod mobile;
+pub mod musig2;
+pub mod omemo;
+#[cfg(feature = "os-keychain")]
+pub mod os_keychain;
 pub mod output;
+pub mod password;
+#[cfg(feature = "pkcs11")]
+pub mod pkcs11;
+pub mod qr;
+pub mod receipt;
+pub mod revocation;
+pub mod sealed_box;
+pub mod secret;
+pub mod seed_qr;
+pub mod seed_ur;
+pub mod signing;
+pub mod ssh_config;
+pub mod ssh_files;
+mod ssh_wire;
+pub mod sshsig;
+pub mod sskr;
+pub mod template;
+pub mod tlsa;
+pub mod tor;
+pub mod totp;
+pub mod transparency;
+pub mod tuf;
+pub mod ur;
+pub mod vectors;
+#[cfg(feature = "wasm")]
+pub mod wasm;
 
 // Re-exports for convenience
-pub use bip32_wrapper::{DerivedKey, Keychain};
-pub use derivation::derive_key_from_entity;
-pub use entity::{DerivationConfig, HashFunctionConfig, KeyDerivation};
-pub use error::BipKeychainError;
-pub use hash::{hash_entity, HashFunction};
-pub use output::{format_key, Ed25519Keypair, OutputFormat};
+pub use acme::AcmeAccountKey;
+pub use agent::Agent;
+pub use audit::{AuditLog, AuditRecord};
+pub use backend::KeychainBackend;
+pub use bip32_wrapper::{
+    generate_mnemonic, generate_mnemonic_in, parse_mnemonic, DerivedKey, Keychain, PublicKeychain,
+};
+pub use builder::{EntityBuilder, KeyDerivationBuilder};
+pub use csr::CsrSubject;
+pub use curve_key::CurveKey;
+pub use derivation::{
+    derive_child_entity, derive_key_from_entity, derive_public_key_from_entity,
+    derive_public_key_with_backend, entity_derivation_path, entity_hash, entity_index,
+    entity_indices, parent_reference, tenant_index, DEFAULT_PARENT_ENTROPY, TENANT_HMAC_KEY,
+};
+pub use did::Did;
+pub use dnssec::{DnssecConfig, DnssecKey, DnssecRole};
+pub use entity::{
+    CanonicalizationConfig, DerivationConfig, DidEntity, DomainTagConfig, Entity, EntityParent,
+    GordianEnvelopeEntity, HashFunctionConfig, IndexExpansionConfig, KeyDerivation,
+    KeyEntropyConfig, KeyTypeConfig, ParentReference, SchemaOrgEntity,
+};
+pub use error::{BipKeychainError, ErrorKind};
+pub use foreign_mnemonic::{ForeignSeed, ForeignSeedKind};
+pub use frost::{
+    FrostConfig, FrostGroup, KeyPackage, SignatureShare, SigningCommitment, SigningNonces,
+};
+pub use hash::{
+    canonicalize, canonicalize_value, hash_bytes, hash_entity, Canonicalization, HashFunction,
+};
+pub use index_registry::{IndexEntry, IndexRegistry};
+pub use key_manifest::{KeyManifest, PublishedKey, SignedKeyManifest};
+pub use libp2p::Libp2pIdentity;
+pub use manifest::{Manifest, ManifestEntry};
+pub use mnemonic::{check_mnemonic, check_mnemonic_in, MnemonicCheck};
+pub use omemo::OmemoIdentity;
+pub use output::{format_key, format_keys, Ed25519Keypair, OutputFormat};
+pub use password::{derive_password, PasswordCharset, PasswordConfig};
+pub use receipt::Receipt;
+pub use revocation::{Revocation, RevocationList, SignedRevocationList};
+pub use secret::SecretString;
+pub use ssh_config::SshHost;
+pub use ssh_files::SshKeyFile;
+pub use tlsa::{TlsaConfig, TlsaMatchingType, TlsaSelector, TlsaUsage};
+pub use tor::OnionService;
+pub use totp::{Totp, TotpAlgorithm, TotpConfig};
+pub use transparency::{InclusionProof, LogPublicKey, LogRecord};
+pub use tuf::TufKey;
+pub use vectors::{generate_vectors, ReferenceVectors, TestVector, TestVectors};
+
+// UniFFI's generated code expects its tag type at the crate root
+#[cfg(feature = "uniffi")]
+use mobile::UniFfiTag;
 
 /// Library version
 pub const VERSION: &str = env!("CARGO_PKG_VERSION");
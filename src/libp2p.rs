@@ -0,0 +1,134 @@
+//! libp2p peer identities
+//!
+//! A libp2p node (IPFS, Filecoin, ...) is identified by its key: the PeerId
+//! is a multihash of the protobuf-encoded public key. Deriving the key from
+//! an entity gives a node a stable PeerId that survives reinstalling from
+//! the seed.
+//!
+//! Keys use the `crypto.pb` encoding from the libp2p peer-id spec:
+//!
+//! ```text
+//! message PublicKey  { required KeyType Type = 1; required bytes Data = 2; }
+//! message PrivateKey { required KeyType Type = 1; required bytes Data = 2; }
+//! ```
+//!
+//! For Ed25519 (`KeyType` 1) the public `Data` is the 32-byte key and the
+//! private `Data` is the seed followed by the public key. Encoded public
+//! keys this short are embedded in the PeerId with the identity multihash.
+
+use crate::output::Ed25519Keypair;
+use crate::totp::base32;
+use serde_json::json;
+use zeroize::Zeroizing;
+
+/// `KeyType` of Ed25519 keys in `crypto.pb`
+const KEY_TYPE_ED25519: u8 = 1;
+
+/// Multihash code of the identity hash
+const MULTIHASH_IDENTITY: u8 = 0x00;
+
+/// CID version and multicodec `libp2p-key`
+const CID_PREFIX: [u8; 2] = [0x01, 0x72];
+
+fn base64(data: &[u8]) -> String {
+    base64::Engine::encode(&base64::engine::general_purpose::STANDARD, data)
+}
+
+/// Protobuf encoding of a `crypto.pb` key with a short `Data` field
+fn encode_key(data: &[u8]) -> Vec<u8> {
+    let mut encoded = vec![0x08, KEY_TYPE_ED25519, 0x12, data.len() as u8];
+    encoded.extend_from_slice(data);
+    encoded
+}
+
+/// A libp2p node identity
+pub struct Libp2pIdentity<'a> {
+    keypair: &'a Ed25519Keypair,
+}
+
+impl<'a> Libp2pIdentity<'a> {
+    /// Use `keypair` as the node's identity key
+    pub fn new(keypair: &'a Ed25519Keypair) -> Self {
+        Self { keypair }
+    }
+
+    /// Protobuf-encoded public key
+    pub fn public_key_protobuf(&self) -> Vec<u8> {
+        encode_key(&self.keypair.public_key_bytes())
+    }
+
+    /// Protobuf-encoded private key, as go-libp2p and rust-libp2p store it
+    pub fn private_key_protobuf(&self) -> Zeroizing<Vec<u8>> {
+        let mut data = Zeroizing::new([0u8; 64]);
+        data[..32].copy_from_slice(&*self.keypair.private_key_bytes());
+        data[32..].copy_from_slice(&self.keypair.public_key_bytes());
+        Zeroizing::new(encode_key(&*data))
+    }
+
+    /// PeerId multihash: the identity hash of the encoded public key
+    fn multihash(&self) -> Vec<u8> {
+        let public_key = self.public_key_protobuf();
+        let mut multihash = vec![MULTIHASH_IDENTITY, public_key.len() as u8];
+        multihash.extend_from_slice(&public_key);
+        multihash
+    }
+
+    /// PeerId as a CIDv1 (`libp2p-key` codec, base32 multibase), e.g. `bafzaa...`
+    pub fn peer_id(&self) -> String {
+        let mut cid = CID_PREFIX.to_vec();
+        cid.extend_from_slice(&self.multihash());
+        format!("b{}", base32(&cid).to_lowercase())
+    }
+
+    /// PeerId in the legacy base58btc form, e.g. `12D3KooW...`
+    pub fn peer_id_base58(&self) -> String {
+        bs58::encode(self.multihash()).into_string()
+    }
+
+    /// Kubo `Identity` config section: the base58 `PeerID` and the base64
+    /// protobuf `PrivKey`
+    pub fn to_private_json(&self) -> Zeroizing<String> {
+        let private_key = Zeroizing::new(base64(&self.private_key_protobuf()));
+        let mut value = json!({
+            "PeerID": self.peer_id_base58(),
+            "PrivKey": *private_key,
+        });
+        let json =
+            Zeroizing::new(serde_json::to_string_pretty(&value).expect("JSON values serialize"));
+        if let Some(serde_json::Value::String(secret)) = value.get_mut("PrivKey") {
+            zeroize::Zeroize::zeroize(secret);
+        }
+        json
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_identity() {
+        let keypair = Ed25519Keypair::from_seed([1u8; 32]);
+        let identity = Libp2pIdentity::new(&keypair);
+
+        assert_eq!(
+            base64(&identity.public_key_protobuf()),
+            "CAESIIqI4910CfGV/VLbLTy6XXLKZwm/HZQSG/N0iAG0D29c"
+        );
+        assert_eq!(
+            base64(&identity.private_key_protobuf()),
+            "CAESQAEBAQEBAQEBAQEBAQEBAQEBAQEBAQEBAQEBAQEBAQEBiojj3XQJ8ZX9UtstPLpdcspnCb8dlBIb83SIAbQPb1w="
+        );
+        assert_eq!(
+            identity.peer_id(),
+            "bafzaajaiaejcbcui4poxicprsx6vfwznhs5f24wkm4e36hmucin7g5eiag2a6324"
+        );
+        assert_eq!(
+            identity.peer_id_base58(),
+            "12D3KooWK99VoVxNE7XzyBwXEzW7xhK7Gpv85r9F3V3fyKSUKPH5"
+        );
+
+        let config: serde_json::Value = serde_json::from_str(&identity.to_private_json()).unwrap();
+        assert_eq!(config["PeerID"], identity.peer_id_base58());
+    }
+}
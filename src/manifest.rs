@@ -0,0 +1,288 @@
+//! Entity manifests
+//!
+//! A manifest lists entity JSON files that belong together (e.g. all the
+//! keys a user wants loaded into an SSH agent, or provisioned onto a
+//! machine with `derive-all`):
+//!
+//! ```json
+//! {
+//!   "entities": [
+//!     {"file": "github-repo.json", "comment": "github", "output": "keys/github.pub"},
+//!     {"file": "server-prod.json", "format": "public-key"},
+//!     {"file": "db1-access.json", "host": "db1 db1.example.com"}
+//!   ]
+//! }
+//! ```
+//!
+//! Entity and output paths are resolved relative to the manifest file.
+
+use crate::{
+    bip32_wrapper::Keychain,
+    derivation::derive_key_from_entity,
+    entity::KeyDerivation,
+    error::{BipKeychainError, Result},
+    output::{format_key, OutputFormat},
+};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// A list of entities to derive together
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct Manifest {
+    /// Entities in the manifest, in file order
+    pub entities: Vec<ManifestEntry>,
+}
+
+/// A single manifest entry
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct ManifestEntry {
+    /// Path to the entity JSON file
+    pub file: PathBuf,
+
+    /// Optional label for the key (defaults to the entity's purpose)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub comment: Option<String>,
+
+    /// Output format for `derive-all` (default: the entity's
+    /// `output_format`, else ssh)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub format: Option<OutputFormat>,
+
+    /// File `derive-all` writes the key to (default: stdout)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub output: Option<PathBuf>,
+
+    /// SSH `Host` patterns for `ssh config` (default: the entity's `fqdn`)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub host: Option<String>,
+}
+
+impl Manifest {
+    /// Parse a manifest from a JSON string
+    ///
+    /// Entry paths are kept as written; use [`Manifest::load`] to resolve
+    /// them against the manifest's directory.
+    pub fn from_json(json: &str) -> Result<Self> {
+        let manifest: Self = serde_json::from_str(json)
+            .map_err(|e| BipKeychainError::ManifestError(format!("Invalid manifest: {}", e)))?;
+
+        if manifest.entities.is_empty() {
+            return Err(BipKeychainError::ManifestError(
+                "Manifest lists no entities".to_string(),
+            ));
+        }
+
+        Ok(manifest)
+    }
+
+    /// Read a manifest file and resolve entry paths relative to it
+    pub fn load(path: &Path) -> Result<Self> {
+        let json = fs::read_to_string(path)?;
+        let mut manifest = Self::from_json(&json)?;
+
+        let base_dir = path.parent().unwrap_or_else(|| Path::new("."));
+        for entry in &mut manifest.entities {
+            if entry.file.is_relative() {
+                entry.file = base_dir.join(&entry.file);
+            }
+            if let Some(output) = entry.output.as_mut().filter(|output| output.is_relative()) {
+                *output = base_dir.join(&*output);
+            }
+        }
+
+        Ok(manifest)
+    }
+}
+
+impl ManifestEntry {
+    /// Read and parse this entry's entity file
+    pub fn load_derivation(&self) -> Result<KeyDerivation> {
+        let json = fs::read_to_string(&self.file).map_err(|e| {
+            BipKeychainError::ManifestError(format!(
+                "Failed to read entity file {}: {}",
+                self.file.display(),
+                e
+            ))
+        })?;
+
+        KeyDerivation::from_json(&json)
+    }
+
+    /// The entry's output format: its own `format`, else the entity's
+    /// preferred `output_format`, else the default for its `key_type` (an
+    /// SSH public key for Ed25519)
+    pub fn output_format(&self, key_derivation: &KeyDerivation) -> OutputFormat {
+        self.format
+            .or(key_derivation.output_format)
+            .unwrap_or_else(|| {
+                key_derivation
+                    .derivation_config
+                    .key_type
+                    .default_output_format()
+            })
+    }
+
+    /// Derive this entry's key and format it in [`Self::output_format`]
+    ///
+    /// The label (see [`Self::label`]) is used as the key comment.
+    pub fn render(&self, keychain: &Keychain, parent_entropy: &[u8]) -> Result<String> {
+        let mut key_derivation = self.load_derivation()?;
+        let derived = derive_key_from_entity(keychain, &key_derivation, parent_entropy)?;
+
+        key_derivation.purpose = Some(self.label(&key_derivation));
+        let format = self.output_format(&key_derivation);
+        format_key(&derived, &key_derivation, format)
+    }
+
+    /// SSH `Host` patterns for this entry: the explicit `host`, else the
+    /// entity's `fqdn` property (as in the `ssh-host` template)
+    pub fn ssh_host(&self, key_derivation: &KeyDerivation) -> Option<String> {
+        self.host.clone().or_else(|| {
+            key_derivation.entity.to_value()["fqdn"]
+                .as_str()
+                .map(str::to_string)
+        })
+    }
+
+    /// Label for this entry: the explicit comment, else the entity's
+    /// purpose, else the entity file name
+    pub fn label(&self, key_derivation: &KeyDerivation) -> String {
+        self.comment
+            .clone()
+            .or_else(|| key_derivation.purpose.clone())
+            .unwrap_or_else(|| {
+                self.file
+                    .file_stem()
+                    .map(|stem| stem.to_string_lossy().into_owned())
+                    .unwrap_or_default()
+            })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_manifest() {
+        let json = r#"{
+            "entities": [
+                {"file": "github-repo.json", "comment": "github"},
+                {"file": "server-prod.json"}
+            ]
+        }"#;
+
+        let manifest = Manifest::from_json(json).unwrap();
+        assert_eq!(manifest.entities.len(), 2);
+        assert_eq!(manifest.entities[0].comment.as_deref(), Some("github"));
+        assert_eq!(manifest.entities[1].comment, None);
+    }
+
+    #[test]
+    fn test_entry_ssh_host() {
+        let manifest = Manifest::from_json(
+            r#"{"entities": [{"file": "a.json"}, {"file": "b.json", "host": "db1 db1.example.com"}]}"#,
+        )
+        .unwrap();
+        let host_entity = KeyDerivation::from_json(
+            r#"{
+                "schema_type": "dns",
+                "entity": {"fqdn": "db1.example.com", "zone": "example.com"},
+                "derivation_config": {"hash_function": "hmac_sha512", "hardened": true}
+            }"#,
+        )
+        .unwrap();
+        let other = KeyDerivation::from_json(
+            r#"{
+                "schema_type": "custom",
+                "entity": {"name": "server"},
+                "derivation_config": {"hash_function": "hmac_sha512", "hardened": true}
+            }"#,
+        )
+        .unwrap();
+
+        let [plain, explicit] = &manifest.entities[..] else {
+            panic!("two entries")
+        };
+        assert_eq!(
+            plain.ssh_host(&host_entity).as_deref(),
+            Some("db1.example.com")
+        );
+        assert_eq!(plain.ssh_host(&other), None);
+        assert_eq!(
+            explicit.ssh_host(&other).as_deref(),
+            Some("db1 db1.example.com")
+        );
+    }
+
+    #[test]
+    fn test_entry_format_and_output() {
+        let manifest_path = Path::new(env!("CARGO_MANIFEST_DIR")).join("examples/manifest.json");
+        let mut manifest = Manifest::from_json(
+            r#"{
+                "entities": [
+                    {"file": "github-repo.json", "comment": "github", "output": "keys/github.pub"},
+                    {"file": "/abs/server.json", "format": "public-key", "output": "/etc/key"}
+                ]
+            }"#,
+        )
+        .unwrap();
+        let mut key_derivation = KeyDerivation::from_json(
+            r#"{
+                "schema_type": "custom",
+                "entity": {"name": "server"},
+                "derivation_config": {"hash_function": "hmac_sha512", "hardened": true}
+            }"#,
+        )
+        .unwrap();
+        assert_eq!(
+            manifest.entities[0].output_format(&key_derivation),
+            OutputFormat::SshPublicKey
+        );
+        assert_eq!(
+            manifest.entities[1].output_format(&key_derivation),
+            OutputFormat::Ed25519PublicHex
+        );
+
+        // The entity's preferred format applies unless the entry sets one
+        key_derivation.output_format = Some(OutputFormat::Jwk);
+        assert_eq!(
+            manifest.entities[0].output_format(&key_derivation),
+            OutputFormat::Jwk
+        );
+        assert_eq!(
+            manifest.entities[1].output_format(&key_derivation),
+            OutputFormat::Ed25519PublicHex
+        );
+        assert!(
+            Manifest::from_json(r#"{"entities": [{"file": "a.json", "format": "qr"}]}"#).is_err()
+        );
+
+        let keychain = Keychain::from_mnemonic(
+            "abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon about",
+        )
+        .unwrap();
+        manifest.entities[0].file = manifest_path.with_file_name("github-repo.json");
+        let rendered = manifest.entities[0]
+            .render(&keychain, crate::DEFAULT_PARENT_ENTROPY)
+            .unwrap();
+        assert!(rendered.starts_with("ssh-ed25519 ") && rendered.ends_with(" github"));
+    }
+
+    #[test]
+    fn test_empty_manifest_rejected() {
+        assert!(Manifest::from_json(r#"{"entities": []}"#).is_err());
+    }
+
+    #[test]
+    fn test_load_resolves_relative_paths() {
+        let manifest_path = Path::new(env!("CARGO_MANIFEST_DIR")).join("examples/manifest.json");
+        let manifest = Manifest::load(&manifest_path).unwrap();
+
+        for entry in &manifest.entities {
+            let key_derivation = entry.load_derivation().unwrap();
+            assert!(!entry.label(&key_derivation).is_empty());
+        }
+    }
+}
@@ -0,0 +1,377 @@
+//! BIP-39 mnemonic checking and typo repair
+//!
+//! Helps recover a seed phrase copied from a paper backup: every word is
+//! checked against the phrase's wordlist, unknown words get the closest
+//! valid words (by edit distance) as suggestions, and a phrase whose words
+//! are all valid but whose checksum fails gets the single-word fixes that
+//! would make it valid.
+//!
+//! Reports contain seed words, so treat them like the phrase itself.
+//!
+//! [`random_positions`] and [`word_matches`] support a backup quiz: the
+//! user is asked for a few words of their backup, which are checked
+//! against the seed without ever displaying it.
+
+use crate::error::{BipKeychainError, Result};
+use bip39::{Language, Mnemonic};
+use std::borrow::Cow;
+
+/// Mnemonic lengths allowed by BIP-39
+pub const VALID_WORD_COUNTS: [usize; 5] = [12, 15, 18, 21, 24];
+
+/// Largest edit distance considered a plausible typo
+const MAX_DISTANCE: usize = 2;
+
+/// A word that is not in the BIP-39 wordlist
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UnknownWord {
+    /// 1-based position in the phrase
+    pub position: usize,
+    /// The word as written
+    pub word: String,
+    /// Closest wordlist entries, best first
+    pub suggestions: Vec<&'static str>,
+}
+
+/// Replacing one word that makes the checksum valid
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ChecksumRepair {
+    /// 1-based position in the phrase
+    pub position: usize,
+    /// The word as written
+    pub word: String,
+    /// The word that fixes the checksum
+    pub replacement: &'static str,
+}
+
+/// Result of [`check_mnemonic`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MnemonicCheck {
+    /// Wordlist the phrase was checked against
+    pub language: Language,
+    /// Number of words in the phrase
+    pub word_count: usize,
+    /// Words missing from the wordlist
+    pub unknown_words: Vec<UnknownWord>,
+    /// Whether the checksum is valid (`None` when it can't be checked
+    /// because of unknown words or a bad word count)
+    pub checksum_valid: Option<bool>,
+    /// Single-word fixes for a failed checksum
+    pub repairs: Vec<ChecksumRepair>,
+}
+
+impl MnemonicCheck {
+    /// Whether the phrase is a valid BIP-39 mnemonic
+    pub fn is_valid(&self) -> bool {
+        self.checksum_valid == Some(true)
+    }
+
+    /// Whether the phrase has an allowed number of words
+    pub fn valid_word_count(&self) -> bool {
+        VALID_WORD_COUNTS.contains(&self.word_count)
+    }
+}
+
+/// Check a mnemonic's words and checksum, detecting its language
+///
+/// The language is the wordlist containing the most of the phrase's words
+/// (English on a tie).
+pub fn check_mnemonic(phrase: &str) -> MnemonicCheck {
+    let words = normalized_words(phrase);
+    let language = Language::ALL
+        .iter()
+        .copied()
+        .rev()
+        .max_by_key(|language| {
+            words
+                .iter()
+                .filter(|word| language.find_word(word).is_some())
+                .count()
+        })
+        .unwrap_or(Language::English);
+    check_words(words, language)
+}
+
+/// Check a mnemonic's words and checksum against `language`'s wordlist
+///
+/// Words are NFKD-normalized, compared case-insensitively, and may be
+/// separated by any whitespace.
+pub fn check_mnemonic_in(phrase: &str, language: Language) -> MnemonicCheck {
+    check_words(normalized_words(phrase), language)
+}
+
+/// Pick `count` distinct 1-based word positions at random, in order
+///
+/// `count` is capped at `word_count`.
+pub fn random_positions(word_count: usize, count: usize) -> Result<Vec<usize>> {
+    // Partial Fisher-Yates shuffle of 1..=word_count
+    let mut positions: Vec<usize> = (1..=word_count).collect();
+    let count = count.min(word_count);
+    for i in 0..count {
+        let j = i + random_below(word_count - i)?;
+        positions.swap(i, j);
+    }
+
+    positions.truncate(count);
+    positions.sort_unstable();
+    Ok(positions)
+}
+
+/// Uniform random number in `0..bound` (`bound` must be non-zero)
+fn random_below(bound: usize) -> Result<usize> {
+    let bound = bound as u64;
+    // Reject the top partial range so every value is equally likely
+    let limit = u64::MAX - u64::MAX % bound;
+    loop {
+        let mut bytes = [0u8; 8];
+        getrandom::getrandom(&mut bytes).map_err(|e| {
+            BipKeychainError::SeedSourceError(format!("Failed to generate randomness: {}", e))
+        })?;
+        let value = u64::from_le_bytes(bytes);
+        if value < limit {
+            return Ok((value % bound) as usize);
+        }
+    }
+}
+
+/// Whether `answer` is the word at 1-based `position` in `phrase`
+///
+/// Both are NFKD-normalized and compared case-insensitively.
+pub fn word_matches(phrase: &str, position: usize, answer: &str) -> bool {
+    let answer = normalized_words(answer);
+    match (
+        normalized_words(phrase).get(position.wrapping_sub(1)),
+        answer.as_slice(),
+    ) {
+        (Some(word), [answer]) => word == answer,
+        _ => false,
+    }
+}
+
+fn normalized_words(phrase: &str) -> Vec<String> {
+    let mut phrase = Cow::Borrowed(phrase);
+    Mnemonic::normalize_utf8_cow(&mut phrase);
+    phrase.split_whitespace().map(str::to_lowercase).collect()
+}
+
+fn check_words(words: Vec<String>, language: Language) -> MnemonicCheck {
+    let unknown_words: Vec<UnknownWord> = words
+        .iter()
+        .enumerate()
+        .filter(|(_, word)| language.find_word(word).is_none())
+        .map(|(i, word)| UnknownWord {
+            position: i + 1,
+            word: word.clone(),
+            suggestions: suggest_words(language, word),
+        })
+        .collect();
+
+    let mut check = MnemonicCheck {
+        language,
+        word_count: words.len(),
+        unknown_words,
+        checksum_valid: None,
+        repairs: Vec::new(),
+    };
+    if !check.unknown_words.is_empty() || !check.valid_word_count() {
+        return check;
+    }
+
+    let valid = checksum_valid(language, &words);
+    check.checksum_valid = Some(valid);
+    if !valid {
+        check.repairs = checksum_repairs(language, &words);
+    }
+    check
+}
+
+/// Wordlist entries closest to `word`, best first
+///
+/// English BIP-39 words are identified by their first four letters, so a
+/// word sharing those with exactly one entry (e.g. an abbreviated `aban`)
+/// is suggested alone. Otherwise entries within a small edit distance are
+/// returned, nearest first.
+pub fn suggest_words(language: Language, word: &str) -> Vec<&'static str> {
+    let word = word.to_lowercase();
+
+    let prefix: String = word.chars().take(4).collect();
+    if prefix.chars().count() == 4 {
+        if let [only] = language.words_by_prefix(&prefix) {
+            return vec![only];
+        }
+    }
+
+    let mut candidates: Vec<(usize, &'static str)> = language
+        .word_list()
+        .iter()
+        .map(|&candidate| (edit_distance(&word, candidate), candidate))
+        .filter(|&(distance, _)| distance <= MAX_DISTANCE)
+        .collect();
+    candidates.sort();
+
+    // Keep only the nearest matches
+    let best = candidates.first().map_or(0, |&(distance, _)| distance);
+    candidates
+        .into_iter()
+        .filter(|&(distance, _)| distance == best)
+        .map(|(_, candidate)| candidate)
+        .collect()
+}
+
+fn checksum_valid(language: Language, words: &[String]) -> bool {
+    Mnemonic::parse_in_normalized(language, &words.join(" ")).is_ok()
+}
+
+/// Replacements of one word, within [`MAX_DISTANCE`], that fix the checksum
+fn checksum_repairs(language: Language, words: &[String]) -> Vec<ChecksumRepair> {
+    let mut repairs = Vec::new();
+    let mut candidate = words.to_vec();
+
+    for (i, word) in words.iter().enumerate() {
+        for &replacement in language.word_list() {
+            let distance = edit_distance(word, replacement);
+            if distance == 0 || distance > MAX_DISTANCE {
+                continue;
+            }
+            candidate[i] = replacement.to_string();
+            if checksum_valid(language, &candidate) {
+                repairs.push(ChecksumRepair {
+                    position: i + 1,
+                    word: word.clone(),
+                    replacement,
+                });
+            }
+        }
+        candidate[i] = word.clone();
+    }
+
+    repairs
+}
+
+/// Levenshtein distance between two words
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+
+    for (i, a_char) in a.chars().enumerate() {
+        let mut current = vec![i + 1; b.len() + 1];
+        for (j, &b_char) in b.iter().enumerate() {
+            let substitution = previous[j] + usize::from(a_char != b_char);
+            current[j + 1] = substitution.min(previous[j + 1] + 1).min(current[j] + 1);
+        }
+        previous = current;
+    }
+
+    previous[b.len()]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const VALID: &str = "abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon about";
+
+    #[test]
+    fn test_valid_mnemonic() {
+        let check = check_mnemonic(&VALID.to_uppercase());
+
+        assert!(check.is_valid());
+        assert_eq!(check.language, Language::English);
+        assert_eq!(check.word_count, 12);
+        assert!(check.unknown_words.is_empty());
+        assert!(check.repairs.is_empty());
+    }
+
+    #[test]
+    fn test_suggests_words_for_typos() {
+        let check = check_mnemonic(&VALID.replacen("abandon", "abandn", 1));
+
+        assert!(!check.is_valid());
+        assert_eq!(check.checksum_valid, None);
+        assert_eq!(
+            check.unknown_words,
+            [UnknownWord {
+                position: 1,
+                word: "abandn".to_string(),
+                suggestions: vec!["abandon"],
+            }]
+        );
+
+        assert_eq!(suggest_words(Language::English, "aban"), ["abandon"]);
+        assert_eq!(suggest_words(Language::English, "zoo"), ["zoo"]);
+        assert!(suggest_words(Language::English, "qqqqqqqq").is_empty());
+    }
+
+    #[test]
+    fn test_checksum_repairs() {
+        // "about" miscopied as the valid word "above"
+        let check = check_mnemonic(&VALID.replace("about", "above"));
+
+        assert_eq!(check.checksum_valid, Some(false));
+        assert!(check.repairs.contains(&ChecksumRepair {
+            position: 12,
+            word: "above".to_string(),
+            replacement: "about",
+        }));
+    }
+
+    #[test]
+    fn test_detects_language() {
+        // Spanish, typed with precomposed accents and one typo
+        let spanish = ["\u{e1}baco"; 10].join(" ") + " abbaco abierto";
+        let check = check_mnemonic(&spanish);
+
+        assert_eq!(check.language, Language::Spanish);
+        assert_eq!(check.unknown_words.len(), 1);
+        assert_eq!(check.unknown_words[0].position, 11);
+        assert_eq!(
+            check.unknown_words[0].suggestions,
+            [Language::Spanish.word_list()[0]]
+        );
+
+        let fixed = spanish.replace("abbaco", "\u{e1}baco");
+        assert!(check_mnemonic(&fixed).is_valid());
+        assert!(!check_mnemonic_in(&fixed, Language::English).is_valid());
+    }
+
+    #[test]
+    fn test_random_positions() {
+        for _ in 0..20 {
+            let positions = random_positions(24, 3).unwrap();
+            assert_eq!(positions.len(), 3);
+            assert!(positions.windows(2).all(|pair| pair[0] < pair[1]));
+            assert!(positions.iter().all(|&p| (1..=24).contains(&p)));
+        }
+        assert_eq!(
+            random_positions(12, 20).unwrap(),
+            (1..=12).collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn test_word_matches() {
+        assert!(word_matches(VALID, 12, "about"));
+        assert!(word_matches(VALID, 1, " Abandon\n"));
+        assert!(!word_matches(VALID, 12, "abandon"));
+        assert!(!word_matches(VALID, 13, "about"));
+        assert!(!word_matches(VALID, 0, "abandon"));
+        assert!(!word_matches(VALID, 1, "abandon abandon"));
+    }
+
+    #[test]
+    fn test_word_count() {
+        let check = check_mnemonic("abandon abandon about");
+
+        assert!(!check.valid_word_count());
+        assert_eq!(check.checksum_valid, None);
+    }
+
+    #[test]
+    fn test_edit_distance() {
+        assert_eq!(edit_distance("abandon", "abandon"), 0);
+        assert_eq!(edit_distance("abandn", "abandon"), 1);
+        assert_eq!(edit_distance("abort", "about"), 1);
+        assert_eq!(edit_distance("above", "about"), 2);
+        assert_eq!(edit_distance("", "zoo"), 3);
+    }
+}
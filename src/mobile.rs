@@ -0,0 +1,251 @@
+//! UniFFI bindings for mobile apps (`uniffi` feature)
+//!
+//! Implements the interface in `src/bip_keychain.udl` so iOS and Android
+//! companion apps run exactly the same derivation as the CLI. Generate
+//! Swift or Kotlin sources from the built library with `uniffi-bindgen`:
+//!
+//! ```text
+//! uniffi-bindgen generate src/bip_keychain.udl --language swift --out-dir out/
+//! ```
+//!
+//! Parent entropy arguments are optional and default to
+//! [`DEFAULT_PARENT_ENTROPY`], matching the CLI.
+
+// The generated scaffolding trips this lint
+#![allow(clippy::empty_line_after_doc_comments)]
+
+use crate::{
+    bip32_wrapper,
+    derivation::{derive_key_from_entity, entity_derivation_path, DEFAULT_PARENT_ENTROPY},
+    entity,
+    error::BipKeychainError,
+    output::{self, OutputFormat},
+    ur,
+};
+use std::sync::Arc;
+
+uniffi::include_scaffolding!("bip_keychain");
+
+/// Errors surfaced to Swift/Kotlin, one case per failure category
+#[derive(Debug, thiserror::Error)]
+pub enum MobileError {
+    #[error("{message}")]
+    InvalidSeed { message: String },
+    #[error("{message}")]
+    InvalidEntity { message: String },
+    #[error("{message}")]
+    Derivation { message: String },
+    #[error("{message}")]
+    Format { message: String },
+    #[error("{message}")]
+    Ur { message: String },
+    #[error("{message}")]
+    Other { message: String },
+}
+
+impl From<BipKeychainError> for MobileError {
+    fn from(error: BipKeychainError) -> Self {
+        let message = error.to_string();
+        match error {
+            BipKeychainError::InvalidSeedPhrase(_) | BipKeychainError::SeedSourceError(_) => {
+                MobileError::InvalidSeed { message }
+            }
+            BipKeychainError::InvalidEntity(_) => MobileError::InvalidEntity { message },
+            BipKeychainError::HashError(_) | BipKeychainError::Bip32Error(_) => {
+                MobileError::Derivation { message }
+            }
+            BipKeychainError::OutputError(_) => MobileError::Format { message },
+            BipKeychainError::UrError(_) => MobileError::Ur { message },
+            _ => MobileError::Other { message },
+        }
+    }
+}
+
+type Result<T> = std::result::Result<T, MobileError>;
+
+/// Output formats, mirroring the CLI's `--format`
+pub enum KeyFormat {
+    Seed,
+    PublicKey,
+    PrivateKey,
+    PublicKeyPem,
+    PrivateKeyPem,
+    Ssh,
+    Sshfp,
+    Tlsa,
+    Csr,
+    Jwk,
+    JwkPrivate,
+    JwkThumbprint,
+    TufKey,
+    TufKeyPrivate,
+    TufKeyid,
+    Omemo,
+    OmemoPrivate,
+    TorHiddenService,
+    PeerId,
+    Libp2pPrivate,
+    Dnskey,
+    Ds,
+    DnssecPrivate,
+    Gpg,
+    Json,
+    Password,
+    Otpauth,
+}
+
+impl From<KeyFormat> for OutputFormat {
+    fn from(format: KeyFormat) -> Self {
+        match format {
+            KeyFormat::Seed => OutputFormat::HexSeed,
+            KeyFormat::PublicKey => OutputFormat::Ed25519PublicHex,
+            KeyFormat::PrivateKey => OutputFormat::Ed25519PrivateHex,
+            KeyFormat::PublicKeyPem => OutputFormat::Ed25519PublicPem,
+            KeyFormat::PrivateKeyPem => OutputFormat::Ed25519PrivatePem,
+            KeyFormat::Ssh => OutputFormat::SshPublicKey,
+            KeyFormat::Sshfp => OutputFormat::SshfpRecord,
+            KeyFormat::Tlsa => OutputFormat::TlsaRecord,
+            KeyFormat::Csr => OutputFormat::Csr,
+            KeyFormat::Jwk => OutputFormat::Jwk,
+            KeyFormat::JwkPrivate => OutputFormat::JwkPrivate,
+            KeyFormat::JwkThumbprint => OutputFormat::JwkThumbprint,
+            KeyFormat::TufKey => OutputFormat::TufKey,
+            KeyFormat::TufKeyPrivate => OutputFormat::TufKeyPrivate,
+            KeyFormat::TufKeyid => OutputFormat::TufKeyid,
+            KeyFormat::Omemo => OutputFormat::OmemoIdentity,
+            KeyFormat::OmemoPrivate => OutputFormat::OmemoPrivate,
+            KeyFormat::TorHiddenService => OutputFormat::TorHiddenService,
+            KeyFormat::PeerId => OutputFormat::Libp2pPeerId,
+            KeyFormat::Libp2pPrivate => OutputFormat::Libp2pPrivate,
+            KeyFormat::Dnskey => OutputFormat::DnssecDnskey,
+            KeyFormat::Ds => OutputFormat::DnssecDs,
+            KeyFormat::DnssecPrivate => OutputFormat::DnssecPrivate,
+            KeyFormat::Gpg => OutputFormat::GpgPublicKey,
+            KeyFormat::Json => OutputFormat::Json,
+            KeyFormat::Password => OutputFormat::Password,
+            KeyFormat::Otpauth => OutputFormat::Otpauth,
+        }
+    }
+}
+
+/// Type and CBOR payload of a decoded UR
+pub struct UrPayload {
+    pub ur_type: String,
+    pub cbor: Vec<u8>,
+}
+
+/// A BIP-32 keychain built from a mnemonic
+pub struct Keychain(bip32_wrapper::Keychain);
+
+impl Keychain {
+    fn from_mnemonic(phrase: &str) -> Result<Self> {
+        Ok(Self(bip32_wrapper::Keychain::from_mnemonic(phrase)?))
+    }
+
+    fn fingerprint(&self) -> String {
+        hex::encode(self.0.fingerprint())
+    }
+
+    fn derive(
+        &self,
+        entity: &KeyDerivation,
+        parent_entropy: Option<Vec<u8>>,
+    ) -> Result<Arc<DerivedKey>> {
+        let parent_entropy = parent_entropy.as_deref().unwrap_or(DEFAULT_PARENT_ENTROPY);
+        let derived = derive_key_from_entity(&self.0, &entity.0, parent_entropy)?;
+        Ok(Arc::new(DerivedKey(derived)))
+    }
+}
+
+/// An entity plus its derivation configuration
+pub struct KeyDerivation(entity::KeyDerivation);
+
+impl KeyDerivation {
+    fn from_json(json: &str) -> Result<Self> {
+        Ok(Self(entity::KeyDerivation::from_json(json)?))
+    }
+
+    fn purpose(&self) -> Option<String> {
+        self.0.purpose.clone()
+    }
+
+    fn derivation_path(&self, parent_entropy: Option<Vec<u8>>) -> Result<String> {
+        let parent_entropy = parent_entropy.as_deref().unwrap_or(DEFAULT_PARENT_ENTROPY);
+        Ok(entity_derivation_path(&self.0, parent_entropy)?.to_string())
+    }
+}
+
+/// A key derived from an entity
+pub struct DerivedKey(bip32_wrapper::DerivedKey);
+
+impl DerivedKey {
+    fn fingerprint(&self) -> String {
+        hex::encode(self.0.fingerprint())
+    }
+}
+
+fn generate_mnemonic(word_count: u8) -> Result<String> {
+    Ok(bip32_wrapper::generate_mnemonic(word_count as usize)?.to_string())
+}
+
+fn format_key(key: &DerivedKey, entity: &KeyDerivation, format: KeyFormat) -> Result<String> {
+    Ok(output::format_key(&key.0, &entity.0, format.into())?)
+}
+
+fn ur_encode(ur_type: &str, cbor: &[u8]) -> String {
+    ur::encode(ur_type, cbor)
+}
+
+fn ur_decode(ur: &str) -> Result<UrPayload> {
+    let (ur_type, cbor) = ur::decode(ur)?;
+    Ok(UrPayload { ur_type, cbor })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TEST_MNEMONIC: &str = "abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon about";
+
+    #[test]
+    fn test_mobile_derivation_matches_library() {
+        let entity_json = r#"{
+            "schema_type": "schema_org",
+            "entity": {"@type": "MobileApplication", "name": "companion"},
+            "derivation_config": {"hash_function": "blake2b", "hardened": true}
+        }"#;
+
+        let keychain = Keychain::from_mnemonic(TEST_MNEMONIC).unwrap();
+        let entity = KeyDerivation::from_json(entity_json).unwrap();
+        let key = keychain.derive(&entity, None).unwrap();
+
+        let core_entity = entity::KeyDerivation::from_json(entity_json).unwrap();
+        let expected = derive_key_from_entity(
+            &bip32_wrapper::Keychain::from_mnemonic(TEST_MNEMONIC).unwrap(),
+            &core_entity,
+            DEFAULT_PARENT_ENTROPY,
+        )
+        .unwrap();
+
+        assert_eq!(
+            format_key(&key, &entity, KeyFormat::Ssh).unwrap(),
+            output::format_key(&expected, &core_entity, OutputFormat::SshPublicKey).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_errors_map_to_categories() {
+        assert!(matches!(
+            Keychain::from_mnemonic("not a mnemonic"),
+            Err(MobileError::InvalidSeed { .. })
+        ));
+        assert!(matches!(
+            generate_mnemonic(13),
+            Err(MobileError::InvalidSeed { .. })
+        ));
+        assert!(matches!(
+            ur_decode("ur:envelope/zz"),
+            Err(MobileError::Ur { .. })
+        ));
+    }
+}
@@ -0,0 +1,730 @@
+//! MuSig2 multi-signatures (BIP-327)
+//!
+//! Lets several entity-derived secp256k1 keys (the keys of their BIP-32
+//! nodes, see [`DerivedKey::secp256k1_public_key`]) produce one BIP-340
+//! Schnorr signature for their aggregate key. Unlike FROST (see
+//! [`crate::frost`]), every signer must take part, and each keeps its own
+//! independently derived key.
+//!
+//! A session:
+//! 1. aggregate the signers' public keys with [`KeyAggContext::new`]
+//! 2. each signer calls [`nonce_gen`], keeps the [`SecNonce`], and shares
+//!    the public nonce; combine them with [`nonce_agg`]
+//! 3. each signer calls [`partial_sign`] (consuming its secret nonce);
+//!    [`partial_sig_agg`] combines the partial signatures
+//!
+//! Tweaking the aggregate key (e.g. for Taproot) is not supported.
+
+use crate::bip32_wrapper::DerivedKey;
+use crate::error::{BipKeychainError, Result};
+use k256::elliptic_curve::group::prime::PrimeCurveAffine;
+use k256::elliptic_curve::ops::Reduce;
+use k256::elliptic_curve::sec1::{FromEncodedPoint, ToEncodedPoint};
+use k256::elliptic_curve::PrimeField;
+use k256::{AffinePoint, EncodedPoint, ProjectivePoint, Scalar, U256};
+use sha2::{Digest, Sha256};
+use zeroize::Zeroizing;
+
+/// Compressed public nonce pair
+pub type PubNonce = [u8; 66];
+
+fn musig_error(message: String) -> BipKeychainError {
+    BipKeychainError::SignatureError(message)
+}
+
+/// BIP-340 tagged hash
+fn tagged_hash(tag: &str, parts: &[&[u8]]) -> [u8; 32] {
+    let tag_hash = Sha256::digest(tag.as_bytes());
+    let mut hasher = Sha256::new();
+    hasher.update(tag_hash);
+    hasher.update(tag_hash);
+    for part in parts {
+        hasher.update(part);
+    }
+    hasher.finalize().into()
+}
+
+fn scalar_from_hash(hash: &[u8; 32]) -> Scalar {
+    <Scalar as Reduce<U256>>::reduce_bytes(hash.into())
+}
+
+/// Parse a compressed point (`cpoint`)
+fn parse_point(bytes: &[u8], what: &str) -> Result<ProjectivePoint> {
+    EncodedPoint::from_bytes(bytes)
+        .ok()
+        .filter(|encoded| encoded.is_compressed())
+        .and_then(|encoded| Option::<AffinePoint>::from(AffinePoint::from_encoded_point(&encoded)))
+        .map(ProjectivePoint::from)
+        .ok_or_else(|| musig_error(format!("{} is not a valid compressed point", what)))
+}
+
+/// Parse a compressed point, with 33 zero bytes for infinity (`cpoint_ext`)
+fn parse_point_ext(bytes: &[u8], what: &str) -> Result<ProjectivePoint> {
+    if bytes.iter().all(|&byte| byte == 0) {
+        return Ok(ProjectivePoint::IDENTITY);
+    }
+    parse_point(bytes, what)
+}
+
+/// Compressed encoding, with 33 zero bytes for infinity (`cbytes_ext`)
+fn point_bytes_ext(point: &ProjectivePoint) -> [u8; 33] {
+    let affine = point.to_affine();
+    let mut bytes = [0u8; 33];
+    if !bool::from(affine.is_identity()) {
+        bytes.copy_from_slice(affine.to_encoded_point(true).as_bytes());
+    }
+    bytes
+}
+
+fn has_even_y(point: &ProjectivePoint) -> bool {
+    point.to_affine().to_encoded_point(true).as_bytes()[0] == 0x02
+}
+
+fn x_bytes(point: &ProjectivePoint) -> [u8; 32] {
+    point_bytes_ext(point)[1..]
+        .try_into()
+        .expect("32-byte x coordinate")
+}
+
+/// Aggregated public key of a set of signers (BIP-327 `KeyAgg`)
+pub struct KeyAggContext {
+    public_keys: Vec<[u8; 33]>,
+    list_hash: [u8; 32],
+    second_key: Option<[u8; 33]>,
+    q: ProjectivePoint,
+}
+
+impl KeyAggContext {
+    /// Aggregate compressed public keys, in signing order
+    ///
+    /// The order matters; sort the keys first (BIP-327 `KeySort`) when
+    /// signers do not agree on one.
+    pub fn new(public_keys: &[[u8; 33]]) -> Result<Self> {
+        if public_keys.is_empty() {
+            return Err(musig_error("no public keys to aggregate".to_string()));
+        }
+        let list_hash = tagged_hash(
+            "KeyAgg list",
+            &public_keys.iter().map(|key| &key[..]).collect::<Vec<_>>(),
+        );
+        let second_key = public_keys
+            .iter()
+            .find(|key| *key != &public_keys[0])
+            .copied();
+
+        let mut context = Self {
+            public_keys: public_keys.to_vec(),
+            list_hash,
+            second_key,
+            q: ProjectivePoint::IDENTITY,
+        };
+        for (index, key) in public_keys.iter().enumerate() {
+            let point = parse_point(key, &format!("public key {}", index))?;
+            context.q += point * context.coefficient(key);
+        }
+        if bool::from(context.q.to_affine().is_identity()) {
+            return Err(musig_error("aggregate public key is infinity".to_string()));
+        }
+        Ok(context)
+    }
+
+    /// `KeyAggCoeff`: 1 for the second distinct key, a hash otherwise
+    fn coefficient(&self, public_key: &[u8; 33]) -> Scalar {
+        if Some(public_key) == self.second_key.as_ref() {
+            return Scalar::ONE;
+        }
+        scalar_from_hash(&tagged_hash(
+            "KeyAgg coefficient",
+            &[&self.list_hash, public_key],
+        ))
+    }
+
+    /// X-only aggregate public key, as used by BIP-340 and Taproot
+    pub fn aggregate_public_key(&self) -> [u8; 32] {
+        x_bytes(&self.q)
+    }
+
+    /// Sign-adjusted key coefficient of a participant's key
+    fn signer_coefficient(&self, public_key: &[u8; 33]) -> Result<Scalar> {
+        if !self.public_keys.contains(public_key) {
+            return Err(musig_error(format!(
+                "{} is not one of the aggregated keys",
+                hex::encode(public_key)
+            )));
+        }
+        let g = if has_even_y(&self.q) {
+            Scalar::ONE
+        } else {
+            -Scalar::ONE
+        };
+        Ok(self.coefficient(public_key) * g)
+    }
+}
+
+/// Sort compressed public keys lexicographically (BIP-327 `KeySort`)
+pub fn key_sort(public_keys: &[[u8; 33]]) -> Vec<[u8; 33]> {
+    let mut sorted = public_keys.to_vec();
+    sorted.sort();
+    sorted
+}
+
+/// A signer's secret nonce; [`partial_sign`] consumes it so it cannot be reused
+pub struct SecNonce {
+    k1: Zeroizing<[u8; 32]>,
+    k2: Zeroizing<[u8; 32]>,
+    public_key: [u8; 33],
+}
+
+/// BIP-327 97-byte secret nonce encoding, k1 || k2 || pk (for test vectors)
+#[cfg(test)]
+impl SecNonce {
+    fn from_bytes(bytes: &[u8; 97]) -> Self {
+        Self {
+            k1: Zeroizing::new(bytes[..32].try_into().unwrap()),
+            k2: Zeroizing::new(bytes[32..64].try_into().unwrap()),
+            public_key: bytes[64..].try_into().unwrap(),
+        }
+    }
+
+    fn to_bytes(&self) -> Vec<u8> {
+        [&self.k1[..], &self.k2[..], &self.public_key[..]].concat()
+    }
+}
+
+/// Generate a signer's nonce pair (BIP-327 `NonceGen`)
+///
+/// Fresh randomness is mixed with the signer's key and, when known, the
+/// aggregate key and message, as hardening against weak randomness.
+pub fn nonce_gen(
+    derived: &DerivedKey,
+    aggregate_public_key: Option<&[u8; 32]>,
+    message: Option<&[u8]>,
+) -> Result<(SecNonce, PubNonce)> {
+    let mut rand = Zeroizing::new([0u8; 32]);
+    getrandom::getrandom(&mut *rand)
+        .map_err(|e| BipKeychainError::IoError(std::io::Error::other(e.to_string())))?;
+    nonce_gen_with(
+        &rand,
+        Some(&derived.to_bytes()),
+        derived.secp256k1_public_key(),
+        aggregate_public_key,
+        message,
+        &[],
+    )
+}
+
+/// `NonceGen` with the randomness `rand_` and every optional input given
+fn nonce_gen_with(
+    rand_: &[u8; 32],
+    secret_key: Option<&[u8]>,
+    public_key: [u8; 33],
+    aggregate_public_key: Option<&[u8; 32]>,
+    message: Option<&[u8]>,
+    extra_input: &[u8],
+) -> Result<(SecNonce, PubNonce)> {
+    let mut rand = Zeroizing::new(*rand_);
+    if let Some(secret_key) = secret_key {
+        let aux = tagged_hash("MuSig/aux", &[rand_]);
+        for (byte, (secret, mask)) in rand.iter_mut().zip(secret_key.iter().zip(aux)) {
+            *byte = secret ^ mask;
+        }
+    }
+
+    let aggregate_public_key: &[u8] = aggregate_public_key.map_or(&[], |key| &key[..]);
+    let mut message_prefixed = Vec::new();
+    match message {
+        None => message_prefixed.push(0),
+        Some(message) => {
+            message_prefixed.push(1);
+            message_prefixed.extend_from_slice(&(message.len() as u64).to_be_bytes());
+            message_prefixed.extend_from_slice(message);
+        }
+    }
+
+    let mut nonces = [Zeroizing::new([0u8; 32]), Zeroizing::new([0u8; 32])];
+    let mut pubnonce = [0u8; 66];
+    for (i, nonce) in nonces.iter_mut().enumerate() {
+        let k = Zeroizing::new(scalar_from_hash(&tagged_hash(
+            "MuSig/nonce",
+            &[
+                &*rand,
+                &[public_key.len() as u8],
+                &public_key,
+                &[aggregate_public_key.len() as u8],
+                aggregate_public_key,
+                &message_prefixed,
+                &(extra_input.len() as u32).to_be_bytes(),
+                extra_input,
+                &[i as u8],
+            ],
+        )));
+        if bool::from(k.is_zero()) {
+            return Err(musig_error("nonce is zero".to_string()));
+        }
+        nonce.copy_from_slice(&k.to_bytes());
+        pubnonce[33 * i..33 * (i + 1)]
+            .copy_from_slice(&point_bytes_ext(&(ProjectivePoint::GENERATOR * *k)));
+    }
+
+    let [k1, k2] = nonces;
+    Ok((SecNonce { k1, k2, public_key }, pubnonce))
+}
+
+/// Combine every signer's public nonce (BIP-327 `NonceAgg`)
+pub fn nonce_agg(pubnonces: &[PubNonce]) -> Result<PubNonce> {
+    let mut aggnonce = [0u8; 66];
+    for half in 0..2 {
+        let mut sum = ProjectivePoint::IDENTITY;
+        for (index, pubnonce) in pubnonces.iter().enumerate() {
+            sum += parse_point(
+                &pubnonce[33 * half..33 * (half + 1)],
+                &format!("public nonce {}", index),
+            )?;
+        }
+        aggnonce[33 * half..33 * (half + 1)].copy_from_slice(&point_bytes_ext(&sum));
+    }
+    Ok(aggnonce)
+}
+
+/// Values every signer and the aggregator compute for a session
+struct Session {
+    b: Scalar,
+    r: ProjectivePoint,
+    e: Scalar,
+}
+
+impl Session {
+    fn new(context: &KeyAggContext, aggnonce: &PubNonce, message: &[u8]) -> Result<Self> {
+        let q_bytes = context.aggregate_public_key();
+        let b = scalar_from_hash(&tagged_hash(
+            "MuSig/noncecoef",
+            &[aggnonce, &q_bytes, message],
+        ));
+        let r1 = parse_point_ext(&aggnonce[..33], "aggregate nonce")?;
+        let r2 = parse_point_ext(&aggnonce[33..], "aggregate nonce")?;
+        let mut r = r1 + r2 * b;
+        if bool::from(r.to_affine().is_identity()) {
+            r = ProjectivePoint::GENERATOR;
+        }
+        let e = scalar_from_hash(&tagged_hash(
+            "BIP0340/challenge",
+            &[&x_bytes(&r), &q_bytes, message],
+        ));
+        Ok(Self { b, r, e })
+    }
+}
+
+/// Produce a signer's partial signature (BIP-327 `Sign`)
+pub fn partial_sign(
+    secnonce: SecNonce,
+    derived: &DerivedKey,
+    context: &KeyAggContext,
+    aggnonce: &PubNonce,
+    message: &[u8],
+) -> Result<[u8; 32]> {
+    let d = Zeroizing::new(*derived.xprv().private_key().as_nonzero_scalar().as_ref());
+    sign_with_key(secnonce, &d, context, aggnonce, message)
+}
+
+/// `Sign` with the secret key `d` as a scalar
+fn sign_with_key(
+    secnonce: SecNonce,
+    d: &Scalar,
+    context: &KeyAggContext,
+    aggnonce: &PubNonce,
+    message: &[u8],
+) -> Result<[u8; 32]> {
+    let public_key = point_bytes_ext(&(ProjectivePoint::GENERATOR * d));
+    if secnonce.public_key != public_key {
+        return Err(musig_error(
+            "secret nonce was generated for a different key".to_string(),
+        ));
+    }
+    let session = Session::new(context, aggnonce, message)?;
+    let a = context.signer_coefficient(&public_key)?;
+
+    let nonce = |bytes: &[u8; 32]| Zeroizing::new(scalar_from_hash(bytes));
+    let (mut k1, mut k2) = (nonce(&secnonce.k1), nonce(&secnonce.k2));
+    if bool::from(k1.is_zero()) || bool::from(k2.is_zero()) {
+        return Err(musig_error("secret nonce is zero".to_string()));
+    }
+    if !has_even_y(&session.r) {
+        *k1 = -*k1;
+        *k2 = -*k2;
+    }
+
+    let s = *k1 + session.b * *k2 + session.e * a * d;
+    Ok(s.to_bytes().into())
+}
+
+/// Check one signer's partial signature (BIP-327 `PartialSigVerify`)
+pub fn partial_sig_verify(
+    partial_signature: &[u8; 32],
+    pubnonce: &PubNonce,
+    public_key: &[u8; 33],
+    context: &KeyAggContext,
+    aggnonce: &PubNonce,
+    message: &[u8],
+) -> Result<()> {
+    let session = Session::new(context, aggnonce, message)?;
+    let s = Option::<Scalar>::from(Scalar::from_repr((*partial_signature).into()))
+        .ok_or_else(|| musig_error("partial signature is out of range".to_string()))?;
+
+    let mut r = parse_point(&pubnonce[..33], "public nonce")?
+        + parse_point(&pubnonce[33..], "public nonce")? * session.b;
+    if !has_even_y(&session.r) {
+        r = -r;
+    }
+    let a = context.signer_coefficient(public_key)?;
+    let p = parse_point(public_key, "public key")?;
+    if ProjectivePoint::GENERATOR * s != r + p * (session.e * a) {
+        return Err(musig_error("partial signature is invalid".to_string()));
+    }
+    Ok(())
+}
+
+/// Combine partial signatures into a BIP-340 signature (BIP-327 `PartialSigAgg`)
+pub fn partial_sig_agg(
+    context: &KeyAggContext,
+    aggnonce: &PubNonce,
+    message: &[u8],
+    partial_signatures: &[[u8; 32]],
+) -> Result<[u8; 64]> {
+    let session = Session::new(context, aggnonce, message)?;
+    let mut s = Scalar::ZERO;
+    for (index, partial) in partial_signatures.iter().enumerate() {
+        s += Option::<Scalar>::from(Scalar::from_repr((*partial).into()))
+            .ok_or_else(|| musig_error(format!("partial signature {} is out of range", index)))?;
+    }
+
+    let mut signature = [0u8; 64];
+    signature[..32].copy_from_slice(&x_bytes(&session.r));
+    signature[32..].copy_from_slice(&s.to_bytes());
+    Ok(signature)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn key(hex_key: &str) -> [u8; 33] {
+        hex::decode(hex_key).unwrap().try_into().unwrap()
+    }
+
+    #[test]
+    fn test_key_agg_vectors() {
+        // BIP-327 key_agg_vectors.json
+        let keys = [
+            key("02F9308A019258C31049344F85F89D5229B531C845836F99B08601F113BCE036F9"),
+            key("03DFF1D77F2A671C5F36183726DB2341BE58FEAE1DA2DECED843240F7B502BA659"),
+            key("023590A94E768F8E1815C2F24B4D80A8E3149316C3518CE7B7AD338368D038CA66"),
+        ];
+        let cases: [(&[usize], &str); 4] = [
+            (
+                &[0, 1, 2],
+                "90539EEDE565F5D054F32CC0C220126889ED1E5D193BAF15AEF344FE59D4610C",
+            ),
+            (
+                &[2, 1, 0],
+                "6204DE8B083426DC6EAF9502D27024D53FC826BF7D2012148A0575435DF54B2B",
+            ),
+            (
+                &[0, 0, 0],
+                "B436E3BAD62B8CD409969A224731C193D051162D8C5AE8B109306127DA3AA935",
+            ),
+            (
+                &[0, 0, 1, 1],
+                "69BC22BFA5D106306E48A20679DE1D7389386124D07571D0D872686028C26A3E",
+            ),
+        ];
+        for (indices, expected) in cases {
+            let public_keys: Vec<[u8; 33]> = indices.iter().map(|&i| keys[i]).collect();
+            let context = KeyAggContext::new(&public_keys).unwrap();
+            assert_eq!(hex::encode_upper(context.aggregate_public_key()), expected);
+        }
+    }
+
+    fn bytes<const N: usize>(hex_bytes: &str) -> [u8; N] {
+        hex::decode(hex_bytes).unwrap().try_into().unwrap()
+    }
+
+    #[test]
+    fn test_nonce_gen_vectors() {
+        // BIP-327 nonce_gen_vectors.json
+        let rand = bytes("0F0F0F0F0F0F0F0F0F0F0F0F0F0F0F0F0F0F0F0F0F0F0F0F0F0F0F0F0F0F0F0F");
+        let (secnonce, pubnonce) = nonce_gen_with(
+            &rand,
+            Some(&bytes::<32>(
+                "0202020202020202020202020202020202020202020202020202020202020202",
+            )),
+            key("024D4B6CD1361032CA9BD2AEB9D900AA4D45D9EAD80AC9423374C451A7254D0766"),
+            Some(&bytes(
+                "0707070707070707070707070707070707070707070707070707070707070707",
+            )),
+            Some(&bytes::<32>(
+                "0101010101010101010101010101010101010101010101010101010101010101",
+            )),
+            &bytes::<32>("0808080808080808080808080808080808080808080808080808080808080808"),
+        )
+        .unwrap();
+        assert_eq!(
+            hex::encode_upper(secnonce.to_bytes()),
+            "B114E502BEAA4E301DD08A50264172C84E41650E6CB726B410C0694D59EFFB64\
+             95B5CAF28D045B973D63E3C99A44B807BDE375FD6CB39E46DC4A511708D0E9D2\
+             024D4B6CD1361032CA9BD2AEB9D900AA4D45D9EAD80AC9423374C451A7254D0766"
+        );
+        assert_eq!(
+            hex::encode_upper(pubnonce),
+            "02F7BE7089E8376EB355272368766B17E88E7DB72047D05E56AA881EA52B3B35DF\
+             02C29C8046FDD0DED4C7E55869137200FBDBFE2EB654267B6D7013602CAED3115A"
+        );
+
+        // No secret key, aggregate key, message, or extra input
+        let (secnonce, pubnonce) = nonce_gen_with(
+            &rand,
+            None,
+            key("02F9308A019258C31049344F85F89D5229B531C845836F99B08601F113BCE036F9"),
+            None,
+            None,
+            &[],
+        )
+        .unwrap();
+        assert_eq!(
+            hex::encode_upper(secnonce.to_bytes()),
+            "89BDD787D0284E5E4D5FC572E49E316BAB7E21E3B1830DE37DFE80156FA41A6D\
+             0B17AE8D024C53679699A6FD7944D9C4A366B514BAF43088E0708B1023DD2897\
+             02F9308A019258C31049344F85F89D5229B531C845836F99B08601F113BCE036F9"
+        );
+        assert_eq!(
+            hex::encode_upper(pubnonce),
+            "02C96E7CB1E8AA5DAC64D872947914198F607D90ECDE5200DE52978AD5DED63C00\
+             0299EC5117C2D29EDEE8A2092587C3909BE694D5CFF0667D6C02EA4059F7CD9786"
+        );
+    }
+
+    #[test]
+    fn test_sign_verify_vectors() {
+        // BIP-327 sign_verify_vectors.json
+        let secret_key = Option::<Scalar>::from(Scalar::from_repr(
+            bytes::<32>("7FB9E0E687ADA1EEBF7ECFE2F21E73EBDB51A7D450948DFE8D76D7F2D1007671").into(),
+        ))
+        .unwrap();
+        let pubkeys = [
+            key("03935F972DA013F80AE011890FA89B67A27B7BE6CCB24D3274D18B2D4067F261A9"),
+            key("02F9308A019258C31049344F85F89D5229B531C845836F99B08601F113BCE036F9"),
+            key("02DFF1D77F2A671C5F36183726DB2341BE58FEAE1DA2DECED843240F7B502BA661"),
+            // Not on the curve
+            key("020000000000000000000000000000000000000000000000000000000000000007"),
+        ];
+        let secnonce = || {
+            SecNonce::from_bytes(&bytes(
+                "508B81A611F100A6B2B6B29656590898AF488BCF2E1F55CF22E5CFB84421FE61\
+                 FA27FD49B1D50085B481285E1CA205D55C82CC1B31FF5CD54A489829355901F7\
+                 03935F972DA013F80AE011890FA89B67A27B7BE6CCB24D3274D18B2D4067F261A9",
+            ))
+        };
+        let pubnonces: [PubNonce; 5] = [
+            bytes(
+                "0337C87821AFD50A8644D820A8F3E02E499C931865C2360FB43D0A0D20DAFE07EA\
+                   0287BF891D2A6DEAEBADC909352AA9405D1428C15F4B75F04DAE642A95C2548480",
+            ),
+            bytes(
+                "0279BE667EF9DCBBAC55A06295CE870B07029BFCDB2DCE28D959F2815B16F81798\
+                   0279BE667EF9DCBBAC55A06295CE870B07029BFCDB2DCE28D959F2815B16F81798",
+            ),
+            bytes(
+                "032DE2662628C90B03F5E720284EB52FF7D71F4284F627B68A853D78C78E1FFE93\
+                   03E4C5524E83FFE1493B9077CF1CA6BEB2090C93D930321071AD40B2F44E599046",
+            ),
+            bytes(
+                "0237C87821AFD50A8644D820A8F3E02E499C931865C2360FB43D0A0D20DAFE07EA\
+                   0387BF891D2A6DEAEBADC909352AA9405D1428C15F4B75F04DAE642A95C2548480",
+            ),
+            // Invalid x coordinate in the first half
+            bytes(
+                "020000000000000000000000000000000000000000000000000000000000000009\
+                   0287BF891D2A6DEAEBADC909352AA9405D1428C15F4B75F04DAE642A95C2548480",
+            ),
+        ];
+        let aggnonces: [PubNonce; 5] = [
+            bytes(
+                "028465FCF0BBDBCF443AABCCE533D42B4B5A10966AC09A49655E8C42DAAB8FCD61\
+                   037496A3CC86926D452CAFCFD55D25972CA1675D549310DE296BFF42F72EEEA8C9",
+            ),
+            // Both halves at infinity
+            [0u8; 66],
+            // Invalid tag, x coordinate not on the curve, x exceeding the field
+            bytes(
+                "048465FCF0BBDBCF443AABCCE533D42B4B5A10966AC09A49655E8C42DAAB8FCD61\
+                   037496A3CC86926D452CAFCFD55D25972CA1675D549310DE296BFF42F72EEEA8C9",
+            ),
+            bytes(
+                "028465FCF0BBDBCF443AABCCE533D42B4B5A10966AC09A49655E8C42DAAB8FCD61\
+                   020000000000000000000000000000000000000000000000000000000000000009",
+            ),
+            bytes(
+                "028465FCF0BBDBCF443AABCCE533D42B4B5A10966AC09A49655E8C42DAAB8FCD61\
+                   02FFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFEFFFFFC30",
+            ),
+        ];
+        let message =
+            bytes::<32>("F95466D086770E689964664219266FE5ED215C92AE20BAB5C9D79ADDDDF3C0CF");
+        let context = |indices: &[usize]| {
+            KeyAggContext::new(&indices.iter().map(|&i| pubkeys[i]).collect::<Vec<_>>())
+        };
+
+        // (key indices, nonce indices, aggregate nonce index, expected
+        // partial signature); the signer holds key 0
+        let valid: [(&[usize], &[usize], usize, &str); 4] = [
+            (
+                &[0, 1, 2],
+                &[0, 1, 2],
+                0,
+                "012ABBCB52B3016AC03AD82395A1A415C48B93DEF78718E62A7A90052FE224FB",
+            ),
+            (
+                &[1, 0, 2],
+                &[1, 0, 2],
+                0,
+                "9FF2F7AAA856150CC8819254218D3ADEEB0535269051897724F9DB3789513A52",
+            ),
+            (
+                &[1, 2, 0],
+                &[1, 2, 0],
+                0,
+                "FA23C359F6FAC4E7796BB93BC9F0532A95468C539BA20FF86D7C76ED92227900",
+            ),
+            (
+                &[0, 1],
+                &[0, 3],
+                1,
+                "AE386064B26105404798F75DE2EB9AF5EDA5387B064B83D049CB7C5E08879531",
+            ),
+        ];
+        for (keys, nonces, aggnonce, expected) in valid {
+            let aggnonce = &aggnonces[aggnonce];
+            let signer_nonces: Vec<PubNonce> = nonces.iter().map(|&i| pubnonces[i]).collect();
+            let signer = keys.iter().position(|&i| i == 0).unwrap();
+            assert_eq!(&nonce_agg(&signer_nonces).unwrap(), aggnonce);
+
+            let context = context(keys).unwrap();
+            let partial =
+                sign_with_key(secnonce(), &secret_key, &context, aggnonce, &message).unwrap();
+            assert_eq!(hex::encode_upper(partial), expected);
+            partial_sig_verify(
+                &partial,
+                &signer_nonces[signer],
+                &pubkeys[0],
+                &context,
+                aggnonce,
+                &message,
+            )
+            .unwrap();
+        }
+
+        // Sign errors: signer's key not aggregated, invalid key, invalid
+        // aggregate nonces, zero secret nonce
+        let group = context(&[0, 1, 2]).unwrap();
+        let sign = |context: &KeyAggContext, aggnonce: &PubNonce, secnonce: SecNonce| {
+            sign_with_key(secnonce, &secret_key, context, aggnonce, &message)
+        };
+        assert!(sign(&context(&[1, 2]).unwrap(), &aggnonces[0], secnonce()).is_err());
+        assert!(context(&[1, 0, 3]).is_err());
+        for aggnonce in &aggnonces[2..] {
+            assert!(sign(&group, aggnonce, secnonce()).is_err());
+        }
+        let zero_secnonce = SecNonce::from_bytes(&bytes(
+            "0000000000000000000000000000000000000000000000000000000000000000\
+             0000000000000000000000000000000000000000000000000000000000000000\
+             03935F972DA013F80AE011890FA89B67A27B7BE6CCB24D3274D18B2D4067F261A9",
+        ));
+        assert!(sign(&group, &aggnonces[0], zero_secnonce).is_err());
+
+        // Verify failures: negated signature, wrong signer, signature
+        // exceeding the group order, invalid public nonce
+        let verify = |partial: &str, pubnonce: &PubNonce, public_key: &[u8; 33]| {
+            partial_sig_verify(
+                &bytes(partial),
+                pubnonce,
+                public_key,
+                &group,
+                &aggnonces[0],
+                &message,
+            )
+        };
+        let valid_partial = "012ABBCB52B3016AC03AD82395A1A415C48B93DEF78718E62A7A90052FE224FB";
+        assert!(verify(
+            "FED54434AD4CFE953FC527DC6A5E5BE8F6234907B7C187559557CE87A0541C46",
+            &pubnonces[0],
+            &pubkeys[0]
+        )
+        .is_err());
+        assert!(verify(valid_partial, &pubnonces[1], &pubkeys[1]).is_err());
+        assert!(verify(
+            "FFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFEBAAEDCE6AF48A03BBFD25E8CD0364141",
+            &pubnonces[0],
+            &pubkeys[0]
+        )
+        .is_err());
+        assert!(verify(valid_partial, &pubnonces[4], &pubkeys[0]).is_err());
+        verify(valid_partial, &pubnonces[0], &pubkeys[0]).unwrap();
+    }
+
+    #[test]
+    fn test_signing_session() {
+        let keychain = crate::Keychain::from_seed_bytes(&[7u8; 64]).unwrap();
+        let signers: Vec<DerivedKey> = (0..3)
+            .map(|index| keychain.derive_bip_keychain_path(index).unwrap())
+            .collect();
+        let public_keys = key_sort(
+            &signers
+                .iter()
+                .map(DerivedKey::secp256k1_public_key)
+                .collect::<Vec<_>>(),
+        );
+        let context = KeyAggContext::new(&public_keys).unwrap();
+        let aggregate_key = context.aggregate_public_key();
+        let message = b"entity-backed multisig";
+
+        let (secnonces, pubnonces): (Vec<_>, Vec<_>) = signers
+            .iter()
+            .map(|signer| nonce_gen(signer, Some(&aggregate_key), Some(message)).unwrap())
+            .unzip();
+        let aggnonce = nonce_agg(&pubnonces).unwrap();
+
+        let mut partials = Vec::new();
+        for ((signer, secnonce), pubnonce) in signers.iter().zip(secnonces).zip(&pubnonces) {
+            let partial = partial_sign(secnonce, signer, &context, &aggnonce, message).unwrap();
+            partial_sig_verify(
+                &partial,
+                pubnonce,
+                &signer.secp256k1_public_key(),
+                &context,
+                &aggnonce,
+                message,
+            )
+            .unwrap();
+            partials.push(partial);
+        }
+        assert!(partial_sig_verify(
+            &partials[0],
+            &pubnonces[1],
+            &signers[1].secp256k1_public_key(),
+            &context,
+            &aggnonce,
+            message
+        )
+        .is_err());
+
+        let signature = partial_sig_agg(&context, &aggnonce, message, &partials).unwrap();
+        let verifying_key = k256::schnorr::VerifyingKey::from_bytes(&aggregate_key).unwrap();
+        let signature = k256::schnorr::Signature::try_from(&signature[..]).unwrap();
+        verifying_key.verify_raw(message, &signature).unwrap();
+        verifying_key.verify_raw(b"other", &signature).unwrap_err();
+
+        // A key outside the group cannot sign
+        let outsider = keychain.derive_bip_keychain_path(9).unwrap();
+        let (secnonce, _) = nonce_gen(&outsider, None, None).unwrap();
+        assert!(partial_sign(secnonce, &outsider, &context, &aggnonce, message).is_err());
+    }
+}
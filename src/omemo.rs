@@ -0,0 +1,139 @@
+//! OMEMO identity keys for XMPP
+//!
+//! Uses a derived Ed25519 key as an OMEMO (XEP-0384) identity key, so a
+//! chat identity can be restored on a new device from the seed instead of
+//! being verified again by every contact. OMEMO 2 (`urn:xmpp:omemo:2`)
+//! publishes the Ed25519 key in the bundle's `<ik>` element; the legacy
+//! `eu.siacs.conversations.axolotl` namespace publishes its Curve25519 form
+//! with libsignal's `0x05` type prefix in `<identityKey>`. Clients show
+//! the Curve25519 form as the fingerprint contacts verify.
+
+use crate::output::Ed25519Keypair;
+use serde_json::json;
+use zeroize::Zeroizing;
+
+/// libsignal key type byte for Curve25519 public keys
+const DJB_KEY_TYPE: u8 = 0x05;
+
+fn base64(data: &[u8]) -> String {
+    base64::Engine::encode(&base64::engine::general_purpose::STANDARD, data)
+}
+
+/// An OMEMO identity key pair
+pub struct OmemoIdentity<'a> {
+    keypair: &'a Ed25519Keypair,
+}
+
+impl<'a> OmemoIdentity<'a> {
+    /// Use `keypair` as the identity key
+    pub fn new(keypair: &'a Ed25519Keypair) -> Self {
+        Self { keypair }
+    }
+
+    /// Curve25519 (Montgomery) form of the public key
+    pub fn curve25519_public_key(&self) -> [u8; 32] {
+        self.keypair.verifying_key().to_montgomery().to_bytes()
+    }
+
+    /// Curve25519 private key, clamped as libsignal stores it
+    pub fn curve25519_private_key(&self) -> Zeroizing<[u8; 32]> {
+        let mut scalar = Zeroizing::new(self.keypair.signing_key().to_scalar_bytes());
+        scalar[0] &= 248;
+        scalar[31] &= 127;
+        scalar[31] |= 64;
+        scalar
+    }
+
+    /// OMEMO 2 bundle `<ik>` content: the base64 Ed25519 public key
+    pub fn identity_key(&self) -> String {
+        base64(&self.keypair.public_key_bytes())
+    }
+
+    /// Legacy OMEMO bundle `<identityKey>` content: base64 of the type
+    /// byte and the Curve25519 public key
+    pub fn legacy_identity_key(&self) -> String {
+        let mut key = vec![DJB_KEY_TYPE];
+        key.extend_from_slice(&self.curve25519_public_key());
+        base64(&key)
+    }
+
+    /// Fingerprint as shown by clients: Curve25519 public key in hex,
+    /// in groups of eight
+    pub fn fingerprint(&self) -> String {
+        hex::encode(self.curve25519_public_key())
+            .as_bytes()
+            .chunks(8)
+            .map(|group| std::str::from_utf8(group).expect("hex is ASCII"))
+            .collect::<Vec<_>>()
+            .join(" ")
+    }
+
+    /// Public identity as JSON
+    pub fn to_json(&self) -> String {
+        serde_json::to_string_pretty(&json!({
+            "ik": self.identity_key(),
+            "identity_key": self.legacy_identity_key(),
+            "fingerprint": self.fingerprint(),
+        }))
+        .expect("JSON values serialize")
+    }
+
+    /// Identity as JSON with the private keys, for importing into a client:
+    /// `ed25519_seed` for OMEMO 2 libraries, `curve25519_private` for
+    /// libsignal-based ones
+    pub fn to_private_json(&self) -> Zeroizing<String> {
+        let seed = Zeroizing::new(base64(&*self.keypair.private_key_bytes()));
+        let private = Zeroizing::new(base64(&*self.curve25519_private_key()));
+        let mut value = json!({
+            "ik": self.identity_key(),
+            "identity_key": self.legacy_identity_key(),
+            "fingerprint": self.fingerprint(),
+            "ed25519_seed": *seed,
+            "curve25519_private": *private,
+        });
+        let json =
+            Zeroizing::new(serde_json::to_string_pretty(&value).expect("JSON values serialize"));
+        for field in ["ed25519_seed", "curve25519_private"] {
+            if let Some(serde_json::Value::String(secret)) = value.get_mut(field) {
+                zeroize::Zeroize::zeroize(secret);
+            }
+        }
+        json
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_curve25519_form() {
+        let keypair = Ed25519Keypair::from_seed([1u8; 32]);
+        let identity = OmemoIdentity::new(&keypair);
+
+        // X25519 public key computed by `openssl pkey -pubout` from the private key
+        assert_eq!(
+            hex::encode(*identity.curve25519_private_key()),
+            "58e86efb75fa4e2c410f46e16de9f6acae1a1703528651b69bc176c088bef36e"
+        );
+        assert_eq!(
+            hex::encode(identity.curve25519_public_key()),
+            "1b1b58dd50ea14b60da17b790cd02754d970c9bab864ebb3c0f3016fe51d3f57"
+        );
+
+        let legacy = base64::Engine::decode(
+            &base64::engine::general_purpose::STANDARD,
+            identity.legacy_identity_key(),
+        )
+        .unwrap();
+        assert_eq!(legacy[0], 0x05);
+        assert_eq!(legacy[1..], identity.curve25519_public_key());
+
+        let fingerprint = identity.fingerprint();
+        assert_eq!(fingerprint.len(), 64 + 7);
+        assert_eq!(
+            fingerprint.replace(' ', ""),
+            hex::encode(identity.curve25519_public_key())
+        );
+    }
+}
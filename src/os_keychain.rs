@@ -0,0 +1,58 @@
+//! OS credential store backend for the seed phrase
+//!
+//! Reads and writes the seed phrase in the platform credential store instead
+//! of the BIP_KEYCHAIN_SEED environment variable:
+//! - macOS: Keychain
+//! - Linux: Secret Service (GNOME Keyring, KWallet)
+//! - Windows: Credential Manager
+//!
+//! Only available with the `os-keychain` feature.
+
+use crate::{
+    bip32_wrapper::parse_mnemonic,
+    error::{BipKeychainError, Result},
+    secret::SecretString,
+};
+use keyring::Entry;
+
+/// Service name under which seeds are stored
+pub const SERVICE: &str = "bip-keychain";
+
+/// Account name used when none is given
+pub const DEFAULT_ACCOUNT: &str = "default";
+
+/// Load the seed phrase stored for `account`
+pub fn load_seed(account: &str) -> Result<SecretString> {
+    let phrase = entry(account)?.get_password().map_err(|e| match e {
+        keyring::Error::NoEntry => BipKeychainError::SeedSourceError(format!(
+            "No seed stored in the OS keychain for account '{}'",
+            account
+        )),
+        e => BipKeychainError::SeedSourceError(format!("OS keychain read failed: {}", e)),
+    })?;
+
+    Ok(SecretString::new(phrase))
+}
+
+/// Store a seed phrase for `account`, replacing any existing one
+///
+/// The phrase is validated as a BIP-39 mnemonic before it is stored.
+pub fn store_seed(account: &str, phrase: &SecretString) -> Result<()> {
+    parse_mnemonic(phrase.expose_secret(), None)?;
+
+    entry(account)?
+        .set_password(phrase.expose_secret())
+        .map_err(|e| BipKeychainError::SeedSourceError(format!("OS keychain write failed: {}", e)))
+}
+
+/// Remove the seed phrase stored for `account`
+pub fn delete_seed(account: &str) -> Result<()> {
+    entry(account)?
+        .delete_credential()
+        .map_err(|e| BipKeychainError::SeedSourceError(format!("OS keychain delete failed: {}", e)))
+}
+
+fn entry(account: &str) -> Result<Entry> {
+    Entry::new(SERVICE, account)
+        .map_err(|e| BipKeychainError::SeedSourceError(format!("OS keychain unavailable: {}", e)))
+}
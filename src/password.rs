@@ -0,0 +1,270 @@
+//! Deterministic passwords from derived keys
+//!
+//! Maps an entity's BIP-85 entropy to a password, so an entity such as
+//! `{"@type": "WebSite", "url": "https://example.com"}` always yields the
+//! same password for the same seed. The `base64` and `base85` charsets
+//! follow BIP-85's PWD BASE64 and PWD BASE85 applications (encode the 64
+//! bytes of entropy, keep the first `length` characters); other charsets
+//! draw characters without bias from a BLAKE3 stream keyed by the entropy.
+//!
+//! Entities choose their settings with an optional `password` object:
+//!
+//! ```json
+//! "password": {"length": 16, "charset": "alphanumeric"}
+//! ```
+
+use crate::error::{BipKeychainError, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeSet;
+use std::str::FromStr;
+use zeroize::Zeroizing;
+
+/// Context string for the BLAKE3 character stream
+const PASSWORD_CONTEXT: &str = "bip-keychain 2025 password characters";
+
+/// RFC 1924 alphabet used by BIP-85 PWD BASE85 (Python's `b85encode`)
+const BASE85_ALPHABET: &[u8; 85] =
+    b"0123456789ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz!#$%&()*+-;<=>?@^_`{|}~";
+
+/// Characters a password is drawn from
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PasswordCharset {
+    /// BIP-85 PWD BASE64 (20-86 characters)
+    #[default]
+    Base64,
+    /// BIP-85 PWD BASE85 (10-80 characters)
+    Base85,
+    /// `A-Z`, `a-z`, and `0-9`
+    Alphanumeric,
+    /// `0-9`, for PINs
+    Digits,
+    /// Any set of at least two distinct characters
+    Custom(String),
+}
+
+impl PasswordCharset {
+    /// Lengths this charset supports
+    pub fn length_range(&self) -> std::ops::RangeInclusive<usize> {
+        match self {
+            PasswordCharset::Base64 => 20..=86,
+            PasswordCharset::Base85 => 10..=80,
+            _ => 4..=128,
+        }
+    }
+}
+
+impl FromStr for PasswordCharset {
+    type Err = String;
+
+    /// Parse a charset name, or `custom:<characters>`
+    fn from_str(s: &str) -> std::result::Result<Self, String> {
+        match s {
+            "base64" => Ok(Self::Base64),
+            "base85" => Ok(Self::Base85),
+            "alphanumeric" => Ok(Self::Alphanumeric),
+            "digits" => Ok(Self::Digits),
+            _ => s
+                .strip_prefix("custom:")
+                .map(|chars| Self::Custom(chars.to_string()))
+                .ok_or_else(|| {
+                    format!(
+                        "unknown charset '{}' (expected base64, base85, alphanumeric, digits, or custom:<characters>)",
+                        s
+                    )
+                }),
+        }
+    }
+}
+
+/// Length and charset of a derived password
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct PasswordConfig {
+    /// Number of characters
+    pub length: usize,
+    /// Characters to draw from
+    pub charset: PasswordCharset,
+}
+
+impl Default for PasswordConfig {
+    fn default() -> Self {
+        Self {
+            length: 20,
+            charset: PasswordCharset::default(),
+        }
+    }
+}
+
+fn password_error(message: String) -> BipKeychainError {
+    BipKeychainError::OutputError(message)
+}
+
+/// Derive a password from 64 bytes of BIP-85 entropy
+pub fn derive_password(entropy: &[u8; 64], config: &PasswordConfig) -> Result<Zeroizing<String>> {
+    let range = config.charset.length_range();
+    if !range.contains(&config.length) {
+        return Err(password_error(format!(
+            "Password length for {:?} must be {} to {}, got {}",
+            config.charset,
+            range.start(),
+            range.end(),
+            config.length
+        )));
+    }
+
+    let encoded = match &config.charset {
+        PasswordCharset::Base64 => Zeroizing::new(base64::Engine::encode(
+            &base64::engine::general_purpose::STANDARD,
+            entropy,
+        )),
+        PasswordCharset::Base85 => base85(entropy),
+        PasswordCharset::Alphanumeric => sample(
+            entropy,
+            "ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789",
+            config.length,
+        )?,
+        PasswordCharset::Digits => sample(entropy, "0123456789", config.length)?,
+        PasswordCharset::Custom(chars) => sample(entropy, chars, config.length)?,
+    };
+
+    Ok(Zeroizing::new(
+        encoded.chars().take(config.length).collect(),
+    ))
+}
+
+/// Base85-encode with the RFC 1924 alphabet, 4 bytes to 5 characters
+fn base85(data: &[u8; 64]) -> Zeroizing<String> {
+    let mut encoded = Zeroizing::new(String::with_capacity(80));
+    for chunk in data.chunks_exact(4) {
+        let mut value = u32::from_be_bytes(chunk.try_into().expect("4-byte chunk"));
+        let mut digits = [0u8; 5];
+        for digit in digits.iter_mut().rev() {
+            *digit = BASE85_ALPHABET[(value % 85) as usize];
+            value /= 85;
+        }
+        encoded.extend(digits.iter().map(|&d| d as char));
+    }
+    encoded
+}
+
+/// Draw `length` characters uniformly from `chars` by rejection sampling
+fn sample(entropy: &[u8; 64], chars: &str, length: usize) -> Result<Zeroizing<String>> {
+    let alphabet: Vec<char> = chars.chars().collect();
+    let distinct = alphabet.iter().collect::<BTreeSet<_>>().len();
+    if distinct != alphabet.len() || !(2..=256).contains(&alphabet.len()) {
+        return Err(password_error(format!(
+            "Password charset must have 2 to 256 distinct characters, got '{}'",
+            chars
+        )));
+    }
+
+    let mut stream = blake3::Hasher::new_derive_key(PASSWORD_CONTEXT)
+        .update(entropy)
+        .finalize_xof();
+    // Bytes at or above `limit` would favour the first characters
+    let limit = 256 - 256 % alphabet.len();
+    let mut password = Zeroizing::new(String::with_capacity(length));
+    let mut byte = [0u8; 1];
+    while password.chars().count() < length {
+        stream.fill(&mut byte);
+        if (byte[0] as usize) < limit {
+            password.push(alphabet[byte[0] as usize % alphabet.len()]);
+        }
+    }
+    Ok(password)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entropy(hex_str: &str) -> [u8; 64] {
+        hex::decode(hex_str).unwrap().try_into().unwrap()
+    }
+
+    #[test]
+    fn test_bip85_pwd_vectors() {
+        // BIP-85 PWD BASE64 test vector (pwd_len 21)
+        let base64 = entropy(
+            "74a2e87a9ba0cdd549bdd2f9ea880d554c6c355b08ed25088cfa88f3f1c4f746\
+             32b652fd4a8f5fda43074c6f6964a3753b08bb5210c8f5e75c07a4c2a20bf6e9",
+        );
+        let config = PasswordConfig {
+            length: 21,
+            charset: PasswordCharset::Base64,
+        };
+        assert_eq!(
+            *derive_password(&base64, &config).unwrap(),
+            "dKLoepugzdVJvdL56ogNV"
+        );
+
+        // BIP-85 PWD BASE85 test vector (pwd_len 12)
+        let base85 = entropy(
+            "f7cfe56f63dca2490f65fcbf9ee63dcd85d18f751b6b5e1c1b8733af6459c904\
+             a75e82b4a22efff9b9e69de2144b293aa8714319a054b6cb55826a8e51425209",
+        );
+        let config = PasswordConfig {
+            length: 12,
+            charset: PasswordCharset::Base85,
+        };
+        assert_eq!(*derive_password(&base85, &config).unwrap(), "_s`{TW89)i4`");
+    }
+
+    #[test]
+    fn test_sampled_charsets() {
+        let entropy = [7u8; 64];
+        let pin = PasswordConfig {
+            length: 6,
+            charset: PasswordCharset::Digits,
+        };
+        let derived = derive_password(&entropy, &pin).unwrap();
+        assert_eq!(derived.len(), 6);
+        assert!(derived.chars().all(|c| c.is_ascii_digit()));
+        assert_eq!(derived, derive_password(&entropy, &pin).unwrap());
+
+        let custom = PasswordConfig {
+            length: 32,
+            charset: PasswordCharset::Custom("xyz".to_string()),
+        };
+        assert!(derive_password(&entropy, &custom)
+            .unwrap()
+            .chars()
+            .all(|c| "xyz".contains(c)));
+    }
+
+    #[test]
+    fn test_rejects_invalid_config() {
+        let entropy = [7u8; 64];
+        for config in [
+            PasswordConfig {
+                length: 87,
+                charset: PasswordCharset::Base64,
+            },
+            PasswordConfig {
+                length: 8,
+                charset: PasswordCharset::Custom("aa".to_string()),
+            },
+        ] {
+            assert!(derive_password(&entropy, &config).is_err());
+        }
+    }
+
+    #[test]
+    fn test_parse_charset() {
+        assert_eq!(
+            "alphanumeric".parse::<PasswordCharset>().unwrap(),
+            PasswordCharset::Alphanumeric
+        );
+        assert_eq!(
+            "custom:abc".parse::<PasswordCharset>().unwrap(),
+            PasswordCharset::Custom("abc".to_string())
+        );
+        assert!("hex".parse::<PasswordCharset>().is_err());
+
+        let config: PasswordConfig =
+            serde_json::from_str(r#"{"charset": {"custom": "01"}}"#).unwrap();
+        assert_eq!(config.length, 20);
+        assert_eq!(config.charset, PasswordCharset::Custom("01".to_string()));
+    }
+}
@@ -0,0 +1,368 @@
+//! PKCS#11 token and HSM backend
+//!
+//! Standard PKCS#11 has no BIP-32 child-derivation mechanism, so
+//! [`Pkcs11Backend`] keeps the derived keys themselves on the token rather
+//! than the master key. At provisioning time [`Pkcs11Backend::import_key`]
+//! derives m/83696968'/67797668'/{index}' from the seed and stores it as a
+//! non-extractable secp256k1 key pair, labelled with its path and identified
+//! by its entity index. After that the seed can go back into cold storage:
+//! [`KeychainBackend::public_key`] reads `CKA_EC_POINT` from the token, and
+//! entity hashing and index computation still happen in this library.
+//!
+//! The master fingerprint is stored next to the keys as a `CKO_DATA` object,
+//! so a token holds the keys of one master only.
+//!
+//! Only available with the `pkcs11` feature. The module (e.g. SoftHSM's
+//! `libsofthsm2.so`, or a vendor HSM library) is loaded at runtime.
+
+use crate::{
+    backend::KeychainBackend,
+    bip32_wrapper::Keychain,
+    error::{BipKeychainError, Result},
+    secret::SecretString,
+};
+use cryptoki::{
+    context::{CInitializeArgs, Pkcs11},
+    object::{Attribute, AttributeType, KeyType, ObjectClass, ObjectHandle},
+    session::{Session, UserType},
+    types::AuthPin,
+};
+use k256::elliptic_curve::sec1::ToEncodedPoint;
+use std::path::Path;
+use zeroize::Zeroize;
+
+/// DER-encoded OID of secp256k1 (1.3.132.0.10), the `CKA_EC_PARAMS` value
+const SECP256K1_PARAMS: [u8; 7] = [0x06, 0x05, 0x2b, 0x81, 0x04, 0x00, 0x0a];
+
+/// `CKA_APPLICATION` of the master fingerprint object
+const APPLICATION: &[u8] = b"bip-keychain";
+
+/// `CKA_LABEL` of the master fingerprint object
+const FINGERPRINT_LABEL: &[u8] = b"master fingerprint";
+
+fn pkcs11_error(message: String) -> BipKeychainError {
+    BipKeychainError::BackendError(message)
+}
+
+/// Path label of an entity key, m/83696968'/67797668'/{entity_index}'
+fn key_label(entity_index: u32) -> Vec<u8> {
+    format!("m/83696968'/67797668'/{}'", entity_index).into_bytes()
+}
+
+/// `CKA_EC_POINT` value: the uncompressed point in a DER OCTET STRING
+fn encode_ec_point(public_key: &[u8; 33]) -> Result<Vec<u8>> {
+    let point = k256::PublicKey::from_sec1_bytes(public_key)
+        .map_err(|_| pkcs11_error("Invalid secp256k1 public key".to_string()))?
+        .to_encoded_point(false);
+    let mut der = vec![0x04, point.len() as u8];
+    der.extend_from_slice(point.as_bytes());
+    Ok(der)
+}
+
+/// Compressed public key from `CKA_EC_POINT`
+///
+/// PKCS#11 specifies a DER OCTET STRING, but some tokens return the bare
+/// SEC1 point, so both are accepted.
+fn decode_ec_point(value: &[u8]) -> Result<[u8; 33]> {
+    let der = match value {
+        [0x04, len, point @ ..] if *len as usize == point.len() => Some(point),
+        _ => None,
+    };
+    let public_key = der
+        .and_then(|point| k256::PublicKey::from_sec1_bytes(point).ok())
+        .or_else(|| k256::PublicKey::from_sec1_bytes(value).ok())
+        .ok_or_else(|| pkcs11_error("Token returned an invalid secp256k1 EC point".to_string()))?;
+    let mut compressed = [0u8; 33];
+    compressed.copy_from_slice(public_key.to_encoded_point(true).as_bytes());
+    Ok(compressed)
+}
+
+/// [`KeychainBackend`] whose entity keys live on a PKCS#11 token
+pub struct Pkcs11Backend {
+    session: Session,
+}
+
+impl Pkcs11Backend {
+    /// Load `module`, open the token labelled `token_label`, and log in as
+    /// the user with `pin`
+    pub fn open(module: impl AsRef<Path>, token_label: &str, pin: &SecretString) -> Result<Self> {
+        let module = module.as_ref();
+        let pkcs11 = Pkcs11::new(module).map_err(|e| {
+            pkcs11_error(format!(
+                "Failed to load PKCS#11 module {}: {}",
+                module.display(),
+                e
+            ))
+        })?;
+        pkcs11
+            .initialize(CInitializeArgs::OsThreads)
+            .map_err(|e| pkcs11_error(format!("Failed to initialize PKCS#11 module: {}", e)))?;
+
+        let slots = pkcs11
+            .get_slots_with_initialized_token()
+            .map_err(|e| pkcs11_error(format!("Failed to list PKCS#11 slots: {}", e)))?;
+        let slot = slots
+            .into_iter()
+            .find(|slot| {
+                pkcs11
+                    .get_token_info(*slot)
+                    .is_ok_and(|info| info.label().trim_end() == token_label)
+            })
+            .ok_or_else(|| pkcs11_error(format!("No PKCS#11 token labelled '{}'", token_label)))?;
+
+        let session = pkcs11
+            .open_rw_session(slot)
+            .map_err(|e| pkcs11_error(format!("Failed to open PKCS#11 session: {}", e)))?;
+        session
+            .login(
+                UserType::User,
+                Some(&AuthPin::new(pin.expose_secret().to_string())),
+            )
+            .map_err(|e| pkcs11_error(format!("PKCS#11 login failed: {}", e)))?;
+
+        Ok(Self { session })
+    }
+
+    /// Derive the key at m/83696968'/67797668'/{entity_index}' and store it
+    /// on the token, returning its compressed public key
+    ///
+    /// The private key is stored sensitive and non-extractable. Importing an
+    /// index that is already on the token is an error, as is importing keys
+    /// of a different master than the token already holds.
+    pub fn import_key(&self, keychain: &Keychain, entity_index: u32) -> Result<[u8; 33]> {
+        let fingerprint = keychain.fingerprint();
+        match self.stored_fingerprint()? {
+            Some(stored) if stored != fingerprint => {
+                return Err(pkcs11_error(format!(
+                    "Token holds keys of master {}, not {}",
+                    hex::encode(stored),
+                    hex::encode(fingerprint)
+                )))
+            }
+            Some(_) => {}
+            None => {
+                self.create(&[
+                    Attribute::Class(ObjectClass::DATA),
+                    Attribute::Token(true),
+                    Attribute::Application(APPLICATION.to_vec()),
+                    Attribute::Label(FINGERPRINT_LABEL.to_vec()),
+                    Attribute::Value(fingerprint.to_vec()),
+                ])?;
+            }
+        }
+        if self.find_public_key(entity_index)?.is_some() {
+            return Err(pkcs11_error(format!(
+                "Token already holds a key for index {}",
+                entity_index
+            )));
+        }
+
+        let derived = keychain.derive_bip_keychain_path(entity_index)?;
+        let public_key = derived.secp256k1_public_key();
+        let common = [
+            Attribute::Token(true),
+            Attribute::KeyType(KeyType::EC),
+            Attribute::EcParams(SECP256K1_PARAMS.to_vec()),
+            Attribute::Label(key_label(entity_index)),
+            Attribute::Id(entity_index.to_be_bytes().to_vec()),
+        ];
+
+        let mut private = common.to_vec();
+        private.extend([
+            Attribute::Class(ObjectClass::PRIVATE_KEY),
+            Attribute::Private(true),
+            Attribute::Sensitive(true),
+            Attribute::Extractable(false),
+            Attribute::Sign(true),
+            Attribute::Value(derived.to_bytes().to_vec()),
+        ]);
+        let created = self.create(&private);
+        for attribute in &mut private {
+            if let Attribute::Value(value) = attribute {
+                value.zeroize();
+            }
+        }
+        created?;
+
+        let mut public = common.to_vec();
+        public.extend([
+            Attribute::Class(ObjectClass::PUBLIC_KEY),
+            Attribute::Verify(true),
+            Attribute::EcPoint(encode_ec_point(&public_key)?),
+        ]);
+        self.create(&public)?;
+
+        Ok(public_key)
+    }
+
+    fn create(&self, template: &[Attribute]) -> Result<ObjectHandle> {
+        self.session
+            .create_object(template)
+            .map_err(|e| pkcs11_error(format!("Failed to store object on token: {}", e)))
+    }
+
+    fn find(&self, template: &[Attribute]) -> Result<Option<ObjectHandle>> {
+        let objects = self
+            .session
+            .find_objects(template)
+            .map_err(|e| pkcs11_error(format!("PKCS#11 object search failed: {}", e)))?;
+        Ok(objects.into_iter().next())
+    }
+
+    fn find_public_key(&self, entity_index: u32) -> Result<Option<ObjectHandle>> {
+        self.find(&[
+            Attribute::Class(ObjectClass::PUBLIC_KEY),
+            Attribute::KeyType(KeyType::EC),
+            Attribute::Id(entity_index.to_be_bytes().to_vec()),
+        ])
+    }
+
+    /// Read one attribute of `object`
+    fn attribute(&self, object: ObjectHandle, kind: AttributeType) -> Result<Option<Attribute>> {
+        let attributes = self
+            .session
+            .get_attributes(object, &[kind])
+            .map_err(|e| pkcs11_error(format!("Failed to read token object: {}", e)))?;
+        Ok(attributes.into_iter().next())
+    }
+
+    fn stored_fingerprint(&self) -> Result<Option<[u8; 4]>> {
+        let Some(object) = self.find(&[
+            Attribute::Class(ObjectClass::DATA),
+            Attribute::Application(APPLICATION.to_vec()),
+            Attribute::Label(FINGERPRINT_LABEL.to_vec()),
+        ])?
+        else {
+            return Ok(None);
+        };
+        match self.attribute(object, AttributeType::Value)? {
+            Some(Attribute::Value(value)) => value
+                .try_into()
+                .map(Some)
+                .map_err(|_| pkcs11_error("Token holds an invalid master fingerprint".to_string())),
+            _ => Err(pkcs11_error(
+                "Token holds an invalid master fingerprint".to_string(),
+            )),
+        }
+    }
+}
+
+impl KeychainBackend for Pkcs11Backend {
+    fn name(&self) -> &str {
+        "pkcs11"
+    }
+
+    fn master_fingerprint(&self) -> Result<[u8; 4]> {
+        self.stored_fingerprint()?.ok_or_else(|| {
+            pkcs11_error("Token holds no BIP-Keychain keys; import them first".to_string())
+        })
+    }
+
+    fn public_key(&self, entity_index: u32) -> Result<[u8; 33]> {
+        let object = self.find_public_key(entity_index)?.ok_or_else(|| {
+            pkcs11_error(format!(
+                "Token holds no key for index {} (m/83696968'/67797668'/{}')",
+                entity_index, entity_index
+            ))
+        })?;
+        match self.attribute(object, AttributeType::EcPoint)? {
+            Some(Attribute::EcPoint(point)) => decode_ec_point(&point),
+            _ => Err(pkcs11_error(format!(
+                "Token key for index {} has no EC point",
+                entity_index
+            ))),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TEST_MNEMONIC: &str = "abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon about";
+
+    #[test]
+    fn test_ec_point_round_trip() {
+        let keychain = Keychain::from_mnemonic(TEST_MNEMONIC).unwrap();
+        let public_key = keychain
+            .derive_bip_keychain_path(42)
+            .unwrap()
+            .secp256k1_public_key();
+
+        let der = encode_ec_point(&public_key).unwrap();
+        assert_eq!(&der[..3], &[0x04, 65, 0x04]);
+        assert_eq!(decode_ec_point(&der).unwrap(), public_key);
+        // Bare SEC1 points, as some tokens return them
+        assert_eq!(decode_ec_point(&der[2..]).unwrap(), public_key);
+        assert_eq!(decode_ec_point(&public_key).unwrap(), public_key);
+
+        assert!(decode_ec_point(&[0x04, 0x02, 0x04, 0x00]).is_err());
+    }
+
+    /// Initialize a fresh SoftHSM token in a temporary directory
+    fn softhsm_token(module: &str, label: &str, pin: &str) {
+        let dir = std::env::temp_dir().join(format!("bip-keychain-softhsm-{}", std::process::id()));
+        let tokens = dir.join("tokens");
+        std::fs::create_dir_all(&tokens).unwrap();
+        let conf = dir.join("softhsm2.conf");
+        std::fs::write(
+            &conf,
+            format!("directories.tokendir = {}\n", tokens.display()),
+        )
+        .unwrap();
+        std::env::set_var("SOFTHSM2_CONF", &conf);
+
+        let pkcs11 = Pkcs11::new(module).unwrap();
+        pkcs11.initialize(CInitializeArgs::OsThreads).unwrap();
+        let slot = pkcs11.get_slots_with_token().unwrap().remove(0);
+        let so_pin = AuthPin::new("so-pin".into());
+        pkcs11.init_token(slot, &so_pin, label).unwrap();
+        let session = pkcs11.open_rw_session(slot).unwrap();
+        session.login(UserType::So, Some(&so_pin)).unwrap();
+        session.init_pin(&AuthPin::new(pin.into())).unwrap();
+    }
+
+    #[test]
+    #[ignore = "needs SoftHSM: set SOFTHSM2_MODULE to libsofthsm2.so and run with --ignored"]
+    fn test_softhsm_backend_matches_software_backend() {
+        let module = std::env::var("SOFTHSM2_MODULE").expect("SOFTHSM2_MODULE is not set");
+        softhsm_token(&module, "bip-keychain-test", "1234");
+
+        let keychain = Keychain::from_mnemonic(TEST_MNEMONIC).unwrap();
+        let pin = SecretString::new("1234".to_string());
+        let backend = Pkcs11Backend::open(&module, "bip-keychain-test", &pin).unwrap();
+        assert!(backend.master_fingerprint().is_err());
+        assert!(backend.public_key(42).is_err());
+
+        let imported = backend.import_key(&keychain, 42).unwrap();
+        assert_eq!(imported, keychain.public_key(42).unwrap());
+        assert_eq!(backend.name(), "pkcs11");
+        assert_eq!(
+            backend.master_fingerprint().unwrap(),
+            keychain.fingerprint()
+        );
+        assert_eq!(
+            backend.public_key(42).unwrap(),
+            keychain.public_key(42).unwrap()
+        );
+        assert!(backend.public_key(43).is_err());
+        assert!(backend.import_key(&keychain, 42).is_err());
+
+        // The private key stays on the token
+        let private = backend
+            .find(&[
+                Attribute::Class(ObjectClass::PRIVATE_KEY),
+                Attribute::Id(42u32.to_be_bytes().to_vec()),
+            ])
+            .unwrap()
+            .unwrap();
+        assert!(!matches!(
+            backend.attribute(private, AttributeType::Value),
+            Ok(Some(_))
+        ));
+
+        // Keys of another master are refused
+        let other = Keychain::from_seed_bytes(&[7u8; 64]).unwrap();
+        assert!(backend.import_key(&other, 1).is_err());
+    }
+}
@@ -0,0 +1,324 @@
+//! QR codes (ISO/IEC 18004)
+//!
+//! Encodes short byte strings, such as `otpauth://` URIs and backup URs, as
+//! QR codes for display in a terminal or on paper. Symbols are built by the
+//! `qrcode` crate; this module picks the mode and smallest version, and
+//! renders with the 4-module quiet zone the standard requires. Byte and
+//! numeric mode and error correction levels L and M are supported.
+
+use crate::error::{BipKeychainError, Result};
+use qrcode::{bits::Bits, Color, Version};
+
+/// Light modules around the symbol (ISO/IEC 18004 section 6.3.8)
+pub const QUIET_ZONE: usize = 4;
+
+/// Largest symbol version
+const MAX_VERSION: i16 = 40;
+
+/// Error correction level
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EcLevel {
+    /// Recovers about 7% of the symbol
+    L,
+    /// Recovers about 15% of the symbol
+    M,
+}
+
+impl From<EcLevel> for qrcode::EcLevel {
+    fn from(level: EcLevel) -> Self {
+        match level {
+            EcLevel::L => qrcode::EcLevel::L,
+            EcLevel::M => qrcode::EcLevel::M,
+        }
+    }
+}
+
+/// Encoding mode of the data segment
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Mode {
+    /// ASCII digits, three per 10 bits
+    Numeric,
+    /// Arbitrary bytes
+    Byte,
+}
+
+/// A QR code symbol
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct QrCode {
+    version: usize,
+    size: usize,
+    /// Row-major, `true` is dark
+    modules: Vec<bool>,
+}
+
+impl QrCode {
+    /// Encode `data` in byte mode at error correction level M
+    pub fn encode(data: &[u8]) -> Result<Self> {
+        Self::encode_bytes(data, EcLevel::M)
+    }
+
+    /// Encode `data` in byte mode at error correction level `level`
+    pub fn encode_bytes(data: &[u8], level: EcLevel) -> Result<Self> {
+        Self::encode_segment(Mode::Byte, data, level)
+    }
+
+    /// Encode a string of ASCII digits in numeric mode, which packs three
+    /// digits into 10 bits
+    pub fn encode_numeric(digits: &str, level: EcLevel) -> Result<Self> {
+        if !digits.bytes().all(|b| b.is_ascii_digit()) {
+            return Err(BipKeychainError::OutputError(
+                "Numeric QR data must be digits only".to_string(),
+            ));
+        }
+        Self::encode_segment(Mode::Numeric, digits.as_bytes(), level)
+    }
+
+    /// A single `mode` segment in the smallest version that holds it; the
+    /// mask is chosen with the standard penalty rules
+    fn encode_segment(mode: Mode, data: &[u8], level: EcLevel) -> Result<Self> {
+        for version in 1..=MAX_VERSION {
+            let mut bits = Bits::new(Version::Normal(version));
+            let pushed = match mode {
+                Mode::Numeric => bits.push_numeric_data(data),
+                Mode::Byte => bits.push_byte_data(data),
+            };
+            if pushed
+                .and_then(|()| bits.push_terminator(level.into()))
+                .is_err()
+            {
+                continue;
+            }
+            let code = qrcode::QrCode::with_bits(bits, level.into())
+                .map_err(|e| BipKeychainError::OutputError(format!("QR encoding failed: {}", e)))?;
+            return Ok(Self {
+                version: version as usize,
+                size: code.width(),
+                modules: code
+                    .to_colors()
+                    .into_iter()
+                    .map(|color| color == Color::Dark)
+                    .collect(),
+            });
+        }
+
+        let unit = match mode {
+            Mode::Numeric => "digits",
+            Mode::Byte => "bytes",
+        };
+        Err(BipKeychainError::OutputError(format!(
+            "{} {} is too long for a QR code",
+            data.len(),
+            unit
+        )))
+    }
+
+    /// Symbol version (1-40)
+    pub fn version(&self) -> usize {
+        self.version
+    }
+
+    /// Modules per side
+    pub fn size(&self) -> usize {
+        self.size
+    }
+
+    /// Whether the module at column `x`, row `y` is dark
+    pub fn is_dark(&self, x: usize, y: usize) -> bool {
+        self.modules[y * self.size + x]
+    }
+
+    /// Render as a standalone SVG image with the quiet zone, drawn `width`
+    /// wide and high (a CSS length such as `60mm`)
+    pub fn to_svg(&self, width: &str) -> String {
+        let span = self.size + 2 * QUIET_ZONE;
+        let mut path = String::new();
+        for y in 0..self.size {
+            for x in 0..self.size {
+                if self.is_dark(x, y) {
+                    path.push_str(&format!("M{} {}h1v1h-1z", x + QUIET_ZONE, y + QUIET_ZONE));
+                }
+            }
+        }
+        format!(
+            "<svg xmlns=\"http://www.w3.org/2000/svg\" viewBox=\"0 0 {span} {span}\" \
+             width=\"{width}\" height=\"{width}\" shape-rendering=\"crispEdges\">\
+             <rect width=\"{span}\" height=\"{span}\" fill=\"#fff\"/>\
+             <path d=\"{path}\" fill=\"#000\"/></svg>\n"
+        )
+    }
+
+    /// Render as an 8-bit greyscale PNG with the quiet zone, `scale`
+    /// pixels per module
+    pub fn to_png(&self, scale: usize) -> Result<Vec<u8>> {
+        let png_error = |e: png::EncodingError| {
+            BipKeychainError::OutputError(format!("PNG encoding failed: {}", e))
+        };
+        let side = (self.size + 2 * QUIET_ZONE) * scale;
+        let mut pixels = vec![0xFFu8; side * side];
+        for y in 0..self.size {
+            for x in 0..self.size {
+                if self.is_dark(x, y) {
+                    for row in (y + QUIET_ZONE) * scale..(y + QUIET_ZONE + 1) * scale {
+                        let start = row * side + (x + QUIET_ZONE) * scale;
+                        pixels[start..start + scale].fill(0);
+                    }
+                }
+            }
+        }
+
+        let mut out = Vec::new();
+        let mut encoder = png::Encoder::new(&mut out, side as u32, side as u32);
+        encoder.set_color(png::ColorType::Grayscale);
+        encoder.set_depth(png::BitDepth::Eight);
+        let mut writer = encoder.write_header().map_err(png_error)?;
+        writer.write_image_data(&pixels).map_err(png_error)?;
+        writer.finish().map_err(png_error)?;
+        Ok(out)
+    }
+
+    /// Render with Unicode half blocks, two rows per line, with the quiet
+    /// zone
+    ///
+    /// Light modules are drawn as blocks (like `qrencode -t UTF8`), so the
+    /// code reads correctly on a dark terminal background.
+    pub fn to_terminal_string(&self) -> String {
+        let span = self.size + 2 * QUIET_ZONE;
+        let light = |x: usize, y: usize| {
+            let inside = QUIET_ZONE..QUIET_ZONE + self.size;
+            !(inside.contains(&x)
+                && inside.contains(&y)
+                && self.is_dark(x - QUIET_ZONE, y - QUIET_ZONE))
+        };
+
+        let mut out = String::new();
+        for y in (0..span).step_by(2) {
+            for x in 0..span {
+                let top = light(x, y);
+                let bottom = y + 1 < span && light(x, y + 1);
+                out.push(match (top, bottom) {
+                    (true, true) => '█',
+                    (true, false) => '▀',
+                    (false, true) => '▄',
+                    (false, false) => ' ',
+                });
+            }
+            out.push('\n');
+        }
+        out
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Scan `qr` with an independent decoder
+    fn scan(qr: &QrCode) -> Vec<u8> {
+        let grid = rqrr::SimpleGrid::from_func(qr.size(), |x, y| qr.is_dark(x, y));
+        let mut data = Vec::new();
+        rqrr::Grid::new(grid)
+            .decode_to(&mut data)
+            .expect("symbol decodes");
+        data
+    }
+
+    #[test]
+    fn test_symbols_scan() {
+        let uri = "otpauth://totp/bip-keychain:example.com?secret=ABCDEFGHIJKLMNOPQRSTUVWXYZ234567&issuer=bip-keychain&algorithm=SHA1&digits=6&period=30";
+        let cases: [(&[u8], usize); 4] = [
+            (b"hi", 1),
+            (b"otpauth://totp/x", 2),
+            (uri.as_bytes(), 8),
+            (&[b'a'; 213], 10),
+        ];
+        for (data, version) in cases {
+            let qr = QrCode::encode(data).unwrap();
+            assert_eq!(qr.version(), version);
+            assert_eq!(qr.size(), 17 + 4 * version);
+            assert_eq!(scan(&qr), data);
+        }
+
+        // Past version 10, and binary data at level L
+        let ur = "UR:CRYPTO-SSKR/".repeat(20);
+        assert_eq!(scan(&QrCode::encode(ur.as_bytes()).unwrap()), ur.as_bytes());
+        let binary: Vec<u8> = (0..=255).collect();
+        let qr = QrCode::encode_bytes(&binary, EcLevel::L).unwrap();
+        assert_eq!(scan(&qr), binary);
+
+        assert!(QrCode::encode(&[b'a'; 2400]).is_err());
+    }
+
+    #[test]
+    fn test_encode_numeric() {
+        // Numeric mode and level L fit 48 digits in version 2
+        let digits = "0123456789".repeat(5)[..48].to_string();
+        let qr = QrCode::encode_numeric(&digits, EcLevel::L).unwrap();
+        assert_eq!(qr.version(), 2);
+        assert_eq!(scan(&qr), digits.as_bytes());
+        assert_eq!(
+            QrCode::encode_bytes(&[0u8; 16], EcLevel::L)
+                .unwrap()
+                .version(),
+            1
+        );
+        assert!(QrCode::encode_numeric("12a", EcLevel::L).is_err());
+    }
+
+    #[test]
+    fn test_terminal_rendering() {
+        let qr = QrCode::encode(b"hi").unwrap();
+        let rendered = qr.to_terminal_string();
+        let lines: Vec<&str> = rendered.lines().collect();
+
+        // 21 modules plus a 4-module quiet zone each side, two rows per line
+        assert_eq!(lines.len(), 15);
+        assert!(lines.iter().all(|line| line.chars().count() == 29));
+        assert!(lines[..2].iter().all(|line| line.chars().all(|c| c == '█')));
+        // The top-left finder starts after the quiet zone
+        assert_eq!(lines[2].chars().nth(3), Some('█'));
+        assert_eq!(lines[2].chars().nth(4), Some(' '));
+    }
+
+    /// Find and scan the QR code in a PNG image
+    fn scan_png(png: &[u8]) -> Vec<u8> {
+        let mut reader = png::Decoder::new(png).read_info().unwrap();
+        let mut pixels = vec![0; reader.output_buffer_size()];
+        let info = reader.next_frame(&mut pixels).unwrap();
+        assert_eq!(info.color_type, png::ColorType::Grayscale);
+        let (width, height) = (info.width as usize, info.height as usize);
+
+        let mut image = rqrr::PreparedImage::prepare_from_greyscale(width, height, |x, y| {
+            pixels[y * width + x]
+        });
+        let grids = image.detect_grids();
+        assert_eq!(grids.len(), 1);
+        let mut data = Vec::new();
+        grids[0].decode_to(&mut data).expect("symbol decodes");
+        data
+    }
+
+    #[test]
+    fn test_png_rendering() {
+        let qr = QrCode::encode(b"hi").unwrap();
+        let png = qr.to_png(4).unwrap();
+        assert!(png.starts_with(b"\x89PNG\r\n\x1a\n"));
+        assert_eq!(scan_png(&png), b"hi");
+
+        // An SSKR share UR, as `backup-seed --qr png` writes it
+        let group = crate::sskr::GroupSpec::parse("2of3").unwrap();
+        let shares = crate::sskr::generate_shares(&[7u8; 16], 1, &[group]).unwrap();
+        let ur = crate::seed_ur::encode_share(&shares[0][1]).to_ascii_uppercase();
+        let png = QrCode::encode(ur.as_bytes()).unwrap().to_png(8).unwrap();
+        assert_eq!(scan_png(&png), ur.as_bytes());
+    }
+
+    #[test]
+    fn test_svg_rendering() {
+        let qr = QrCode::encode(b"hi").unwrap();
+        let svg = qr.to_svg("40mm");
+        assert!(svg.starts_with("<svg xmlns=\"http://www.w3.org/2000/svg\" viewBox=\"0 0 29 29\""));
+        assert!(svg.contains("width=\"40mm\""));
+        // The top-left finder's corner module, offset by the quiet zone
+        assert!(svg.contains("<path d=\"M4 4h1v1h-1z"));
+    }
+}
@@ -0,0 +1,194 @@
+//! Verifiable derivation receipts
+//!
+//! A receipt is a statement, signed by a derived key, that the key was
+//! derived from a given entity:
+//!
+//! ```json
+//! {
+//!   "entity_hash": "3b7f...",
+//!   "path": "m/83696968'/67797668'/1234567'",
+//!   "public_key": "55c077e6...",
+//!   "issued_at": 1760572800,
+//!   "signature": "-----BEGIN SSH SIGNATURE-----\n..."
+//! }
+//! ```
+//!
+//! `entity_hash` is the SHA-256 of the entity's canonical form (see
+//! [`KeyDerivation::canonical_entity`]), which anyone holding the entity
+//! file can recompute without the seed. The signature is SSHSIG with
+//! namespace [`RECEIPT_NAMESPACE`] over the JCS (RFC 8785) encoding of the
+//! receipt without its `signature` field, made by `public_key` itself, so a
+//! valid receipt shows the holder of the published key vouched for the
+//! entity and path.
+
+use crate::{
+    entity::KeyDerivation,
+    error::{BipKeychainError, Result},
+    jcs,
+    output::Ed25519Keypair,
+    sshsig,
+};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+/// SSHSIG namespace for derivation receipts
+pub const RECEIPT_NAMESPACE: &str = "bip-keychain-receipt";
+
+/// A signed statement binding an entity to its derived public key
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+pub struct Receipt {
+    /// SHA-256 of the entity's canonical form, as 64 hex characters
+    pub entity_hash: String,
+
+    /// BIP-32 derivation path (absent for entities derived below a parent)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub path: Option<String>,
+
+    /// Ed25519 public key as 64 hex characters
+    pub public_key: String,
+
+    /// When the receipt was issued, in seconds since the Unix epoch
+    pub issued_at: u64,
+
+    /// Armored SSHSIG signature by `public_key`
+    pub signature: String,
+}
+
+fn receipt_error(message: String) -> BipKeychainError {
+    BipKeychainError::SignatureError(message)
+}
+
+/// SHA-256 of an entity's canonical form, as recorded in receipts
+pub fn receipt_entity_hash(key_derivation: &KeyDerivation) -> Result<[u8; 32]> {
+    Ok(Sha256::digest(key_derivation.canonical_entity()?).into())
+}
+
+impl Receipt {
+    /// Issue a receipt for `keypair`, the key derived from `key_derivation`
+    pub fn issue(
+        key_derivation: &KeyDerivation,
+        path: Option<String>,
+        keypair: &Ed25519Keypair,
+        issued_at: u64,
+    ) -> Result<Self> {
+        let mut receipt = Self {
+            entity_hash: hex::encode(receipt_entity_hash(key_derivation)?),
+            path,
+            public_key: hex::encode(keypair.public_key_bytes()),
+            issued_at,
+            signature: String::new(),
+        };
+        receipt.signature = sshsig::sign(keypair, RECEIPT_NAMESPACE, &receipt.signed_bytes()?)?;
+        Ok(receipt)
+    }
+
+    /// Parse a receipt from JSON
+    pub fn from_json(json: &str) -> Result<Self> {
+        serde_json::from_str(json).map_err(|e| receipt_error(format!("Invalid receipt: {}", e)))
+    }
+
+    /// Canonical bytes covered by the signature
+    fn signed_bytes(&self) -> Result<Vec<u8>> {
+        let mut value = serde_json::to_value(self)
+            .map_err(|e| receipt_error(format!("Failed to serialize: {}", e)))?;
+        if let Some(object) = value.as_object_mut() {
+            object.remove("signature");
+        }
+        Ok(jcs::canonicalize(&value).into_bytes())
+    }
+
+    /// The receipt's public key
+    pub fn public_key_bytes(&self) -> Result<[u8; 32]> {
+        hex::decode(&self.public_key)
+            .ok()
+            .and_then(|bytes| bytes.try_into().ok())
+            .ok_or_else(|| {
+                receipt_error(format!(
+                    "Receipt public key '{}' is not 64 hex characters",
+                    self.public_key
+                ))
+            })
+    }
+
+    /// Check that the receipt is signed by its own public key
+    pub fn verify(&self) -> Result<()> {
+        let signer = sshsig::verify(&self.signature, RECEIPT_NAMESPACE, &self.signed_bytes()?)?;
+        if signer != self.public_key_bytes()? {
+            return Err(receipt_error(
+                "Receipt was signed by a different key than it names".to_string(),
+            ));
+        }
+        Ok(())
+    }
+
+    /// Check that the receipt was issued for `key_derivation`
+    pub fn verify_entity(&self, key_derivation: &KeyDerivation) -> Result<()> {
+        let expected = hex::encode(receipt_entity_hash(key_derivation)?);
+        if !self.entity_hash.eq_ignore_ascii_case(&expected) {
+            return Err(receipt_error(format!(
+                "Receipt is for entity hash {}, not {}",
+                self.entity_hash, expected
+            )));
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entity(name: &str) -> KeyDerivation {
+        KeyDerivation::from_json(&format!(
+            r#"{{
+  "schema_type": "schema_org",
+  "entity": {{"@type": "Organization", "name": "{}"}},
+  "derivation_config": {{"hash_function": "hmac_sha512", "hardened": true}}
+}}"#,
+            name
+        ))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_receipt_verifies() {
+        let keypair = Ed25519Keypair::from_seed([1u8; 32]);
+        let key_derivation = entity("server");
+        let receipt = Receipt::issue(
+            &key_derivation,
+            Some("m/83696968'/67797668'/1'".to_string()),
+            &keypair,
+            1_760_000_000,
+        )
+        .unwrap();
+
+        let round_tripped = Receipt::from_json(&serde_json::to_string(&receipt).unwrap()).unwrap();
+        round_tripped.verify().unwrap();
+        round_tripped.verify_entity(&key_derivation).unwrap();
+        assert!(round_tripped.verify_entity(&entity("staging")).is_err());
+        assert_eq!(
+            round_tripped.public_key_bytes().unwrap(),
+            keypair.public_key_bytes()
+        );
+    }
+
+    #[test]
+    fn test_tampered_receipt_fails() {
+        let keypair = Ed25519Keypair::from_seed([1u8; 32]);
+        let other = Ed25519Keypair::from_seed([2u8; 32]);
+        let receipt = Receipt::issue(&entity("server"), None, &keypair, 1_760_000_000).unwrap();
+
+        let mut tampered = receipt.clone();
+        tampered.issued_at += 1;
+        assert!(tampered.verify().is_err());
+
+        // A valid signature by a key other than the one named
+        let mut swapped = receipt.clone();
+        swapped.public_key = hex::encode(other.public_key_bytes());
+        assert!(swapped.verify().is_err());
+
+        let mut path_added = receipt;
+        path_added.path = Some("m/0'".to_string());
+        assert!(path_added.verify().is_err());
+    }
+}
@@ -0,0 +1,233 @@
+//! Key revocation lists
+//!
+//! A revocation list records derived keys that must no longer be used,
+//! identified by their Ed25519 public key:
+//!
+//! ```json
+//! {
+//!   "revocations": [
+//!     {
+//!       "public_key": "55c077e6...",
+//!       "label": "prod server",
+//!       "reason": "host decommissioned",
+//!       "revoked_at": 1760572800
+//!     }
+//!   ]
+//! }
+//! ```
+//!
+//! Lists are published as a [`SignedRevocationList`]: the JCS (RFC 8785)
+//! encoding of the list, signed as SSHSIG with namespace
+//! [`REVOCATION_NAMESPACE`], so anyone can check it with the signer's
+//! public key (or `ssh-keygen -Y verify` over the JCS bytes).
+
+use crate::{
+    error::{BipKeychainError, Result},
+    jcs,
+    output::Ed25519Keypair,
+    sshsig,
+};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::Path;
+
+/// SSHSIG namespace for signed revocation lists
+pub const REVOCATION_NAMESPACE: &str = "bip-keychain-revocations";
+
+/// A revoked key
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+pub struct Revocation {
+    /// Ed25519 public key as 64 hex characters
+    pub public_key: String,
+
+    /// Optional human-readable name for the key
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub label: Option<String>,
+
+    /// Why the key was revoked
+    pub reason: String,
+
+    /// When the key was revoked, in seconds since the Unix epoch
+    pub revoked_at: u64,
+}
+
+/// A set of revoked keys (`revocations.json`)
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize, Serialize)]
+pub struct RevocationList {
+    /// Revocations in the order they were made
+    pub revocations: Vec<Revocation>,
+}
+
+fn store_error(message: String) -> BipKeychainError {
+    BipKeychainError::RevocationError(message)
+}
+
+impl RevocationList {
+    /// Parse a revocation list from JSON
+    pub fn from_json(json: &str) -> Result<Self> {
+        let list: Self = serde_json::from_str(json)
+            .map_err(|e| store_error(format!("Invalid revocation list: {}", e)))?;
+        for revocation in &list.revocations {
+            parse_public_key_hex(&revocation.public_key)?;
+        }
+        Ok(list)
+    }
+
+    /// Read a revocation list; a missing file is an empty list
+    pub fn load(path: &Path) -> Result<Self> {
+        match fs::read_to_string(path) {
+            Ok(json) => Self::from_json(&json),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(Self::default()),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    /// Write the list as pretty-printed JSON
+    pub fn save(&self, path: &Path) -> Result<()> {
+        let json = serde_json::to_string_pretty(self)
+            .map_err(|e| store_error(format!("Failed to serialize: {}", e)))?;
+        fs::write(path, json + "\n")?;
+        Ok(())
+    }
+
+    /// Revoke a key; returns false if it was already revoked
+    pub fn revoke(
+        &mut self,
+        public_key: &[u8; 32],
+        label: Option<String>,
+        reason: impl Into<String>,
+        revoked_at: u64,
+    ) -> bool {
+        if self.find(public_key).is_some() {
+            return false;
+        }
+        self.revocations.push(Revocation {
+            public_key: hex::encode(public_key),
+            label,
+            reason: reason.into(),
+            revoked_at,
+        });
+        true
+    }
+
+    /// The revocation for `public_key`, if it has been revoked
+    pub fn find(&self, public_key: &[u8; 32]) -> Option<&Revocation> {
+        let public_key = hex::encode(public_key);
+        self.revocations
+            .iter()
+            .find(|revocation| revocation.public_key.eq_ignore_ascii_case(&public_key))
+    }
+
+    /// Canonical bytes covered by the signature
+    fn signed_bytes(&self) -> Result<Vec<u8>> {
+        let value = serde_json::to_value(self)
+            .map_err(|e| store_error(format!("Failed to serialize: {}", e)))?;
+        Ok(jcs::canonicalize(&value).into_bytes())
+    }
+
+    /// Sign the list for publication
+    pub fn sign(&self, keypair: &Ed25519Keypair) -> Result<SignedRevocationList> {
+        Ok(SignedRevocationList {
+            revocations: self.revocations.clone(),
+            signer: keypair.to_ssh_public_key(None),
+            signature: sshsig::sign(keypair, REVOCATION_NAMESPACE, &self.signed_bytes()?)?,
+        })
+    }
+}
+
+/// A revocation list with an SSHSIG signature over its JCS encoding
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+pub struct SignedRevocationList {
+    /// The revoked keys
+    pub revocations: Vec<Revocation>,
+
+    /// Signer's OpenSSH public key
+    pub signer: String,
+
+    /// Armored SSHSIG signature
+    pub signature: String,
+}
+
+impl SignedRevocationList {
+    /// Parse a signed list from JSON
+    pub fn from_json(json: &str) -> Result<Self> {
+        serde_json::from_str(json)
+            .map_err(|e| store_error(format!("Invalid signed revocation list: {}", e)))
+    }
+
+    /// Check the signature and return the list
+    ///
+    /// The signature must be made by `signer` (the embedded signer key is
+    /// only a hint and is not trusted on its own).
+    pub fn verify(&self, signer: &[u8; 32]) -> Result<RevocationList> {
+        let list = RevocationList {
+            revocations: self.revocations.clone(),
+        };
+        let actual = sshsig::verify(&self.signature, REVOCATION_NAMESPACE, &list.signed_bytes()?)?;
+        if &actual != signer {
+            return Err(BipKeychainError::SignatureError(
+                "Revocation list was signed by a different key".to_string(),
+            ));
+        }
+        Ok(list)
+    }
+}
+
+fn parse_public_key_hex(public_key: &str) -> Result<[u8; 32]> {
+    hex::decode(public_key)
+        .ok()
+        .and_then(|bytes| bytes.try_into().ok())
+        .ok_or_else(|| {
+            store_error(format!(
+                "Revoked public key '{}' is not 64 hex characters",
+                public_key
+            ))
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_revoke_and_find() {
+        let mut list = RevocationList::default();
+        let key = [7u8; 32];
+
+        assert!(list.revoke(&key, Some("laptop".into()), "lost", 1_760_000_000));
+        assert!(!list.revoke(&key, None, "again", 1_760_000_001));
+        assert_eq!(list.revocations.len(), 1);
+        assert_eq!(list.find(&key).unwrap().reason, "lost");
+        assert!(list.find(&[8u8; 32]).is_none());
+
+        let json = serde_json::to_string(&list).unwrap();
+        assert_eq!(RevocationList::from_json(&json).unwrap(), list);
+        assert!(RevocationList::from_json(
+            r#"{"revocations": [{"public_key": "zz", "reason": "x", "revoked_at": 0}]}"#
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn test_signed_list_verifies() {
+        let authority = Ed25519Keypair::from_seed([1u8; 32]);
+        let other = Ed25519Keypair::from_seed([2u8; 32]);
+
+        let mut list = RevocationList::default();
+        list.revoke(&[7u8; 32], None, "compromised", 1_760_000_000);
+
+        let signed = list.sign(&authority).unwrap();
+        let round_tripped =
+            SignedRevocationList::from_json(&serde_json::to_string(&signed).unwrap()).unwrap();
+        assert_eq!(
+            round_tripped.verify(&authority.public_key_bytes()).unwrap(),
+            list
+        );
+        assert!(round_tripped.verify(&other.public_key_bytes()).is_err());
+
+        // Tampering with an entry breaks the signature
+        let mut tampered = round_tripped;
+        tampered.revocations[0].reason = "routine rotation".to_string();
+        assert!(tampered.verify(&authority.public_key_bytes()).is_err());
+    }
+}
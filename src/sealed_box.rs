@@ -0,0 +1,168 @@
+//! Anonymous public-key encryption to derived keys
+//!
+//! Encrypts data so that only the holder of an entity's derived key can
+//! read it, using the X25519 (Curve25519) form of the entity's Ed25519 key.
+//! The format is libsodium's `crypto_box_seal`, readable by any NaCl
+//! binding (`crypto_box_seal_open`, PyNaCl's `SealedBox`, ...):
+//!
+//! ```text
+//! ephemeral public key (32) || crypto_box(message, nonce, recipient, ephemeral secret)
+//! nonce = BLAKE2b-192(ephemeral public key || recipient public key)
+//! ```
+//!
+//! `crypto_box` is XSalsa20-Poly1305 keyed with HSalsa20 of the X25519
+//! shared secret, so a sealed message is 48 bytes longer than the input.
+//! The sender is anonymous: the ephemeral key is discarded after sealing.
+
+use crate::error::{BipKeychainError, Result};
+use crate::output::Ed25519Keypair;
+use crypto_box::aead::rand_core::{CryptoRng, RngCore};
+use crypto_box::aead::OsRng;
+use crypto_box::{PublicKey, SecretKey};
+use curve25519_dalek::edwards::CompressedEdwardsY;
+use zeroize::Zeroizing;
+
+/// Bytes a sealed box adds to the message: ephemeral key and Poly1305 tag
+pub const SEAL_OVERHEAD: usize = crypto_box::SEALBYTES;
+
+fn seal_error(message: String) -> BipKeychainError {
+    BipKeychainError::EncryptionError(message)
+}
+
+/// X25519 public key of a derived keypair
+pub fn x25519_public_key(keypair: &Ed25519Keypair) -> [u8; 32] {
+    keypair.verifying_key().to_montgomery().to_bytes()
+}
+
+/// X25519 form of an Ed25519 public key, e.g. one read from an SSH key
+pub fn x25519_from_ed25519(public_key: &[u8; 32]) -> Result<[u8; 32]> {
+    CompressedEdwardsY(*public_key)
+        .decompress()
+        .map(|point| point.to_montgomery().to_bytes())
+        .ok_or_else(|| seal_error("Invalid Ed25519 public key".to_string()))
+}
+
+/// X25519 secret key of a derived keypair
+fn x25519_secret_key(keypair: &Ed25519Keypair) -> SecretKey {
+    SecretKey::from_bytes(*Zeroizing::new(keypair.signing_key().to_scalar_bytes()))
+}
+
+/// Encrypt `message` to an X25519 public key
+pub fn seal(recipient: &[u8; 32], message: &[u8]) -> Result<Vec<u8>> {
+    seal_with(recipient, message, &mut OsRng)
+}
+
+fn seal_with(
+    recipient: &[u8; 32],
+    message: &[u8],
+    rng: &mut (impl RngCore + CryptoRng),
+) -> Result<Vec<u8>> {
+    PublicKey::from(*recipient)
+        .seal(rng, message)
+        .map_err(|_| seal_error("Failed to encrypt".to_string()))
+}
+
+/// Decrypt a sealed box with the recipient's derived keypair
+pub fn open(keypair: &Ed25519Keypair, sealed: &[u8]) -> Result<Zeroizing<Vec<u8>>> {
+    if sealed.len() < SEAL_OVERHEAD {
+        return Err(seal_error("Sealed box is too short".to_string()));
+    }
+    x25519_secret_key(keypair)
+        .unseal(sealed)
+        .map(Zeroizing::new)
+        .map_err(|_| {
+            seal_error("Decryption failed: wrong key, or the data was modified".to_string())
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Test RNG that always returns the same byte
+    struct FixedRng(u8);
+
+    impl RngCore for FixedRng {
+        fn next_u32(&mut self) -> u32 {
+            u32::from_le_bytes([self.0; 4])
+        }
+
+        fn next_u64(&mut self) -> u64 {
+            u64::from_le_bytes([self.0; 8])
+        }
+
+        fn fill_bytes(&mut self, dest: &mut [u8]) {
+            dest.fill(self.0)
+        }
+
+        fn try_fill_bytes(
+            &mut self,
+            dest: &mut [u8],
+        ) -> std::result::Result<(), crypto_box::aead::rand_core::Error> {
+            dest.fill(self.0);
+            Ok(())
+        }
+    }
+
+    impl CryptoRng for FixedRng {}
+
+    #[test]
+    fn test_libsodium_vector() {
+        // Sealed by libsodium 1.0.22 `crypto_box_seal` to the recipient's
+        // X25519 key
+        let recipient = Ed25519Keypair::from_seed([1u8; 32]);
+        let sealed = hex::decode(
+            "f27d4303b286f8e343e70fac7314ebe7bc8260007a64a4c25746939ed5ba7324\
+             fd99437d2b486456f4d9eb88b7b19adfb05f8154e9278d1769f1f397315ec82b\
+             b383ff3070c0e6dec801995e3efd7dd1350ba6f54b",
+        )
+        .unwrap();
+        assert_eq!(
+            &*open(&recipient, &sealed).unwrap(),
+            b"sealed with libsodium crypto_box_seal"
+        );
+        assert!(open(&Ed25519Keypair::from_seed([2u8; 32]), &sealed).is_err());
+    }
+
+    #[test]
+    fn test_sealed_box_vector() {
+        // Computed independently with PyCA cryptography (X25519, Poly1305),
+        // hashlib BLAKE2b, and a Python XSalsa20
+        let recipient = Ed25519Keypair::from_seed([1u8; 32]);
+        assert_eq!(
+            hex::encode(x25519_public_key(&recipient)),
+            "1b1b58dd50ea14b60da17b790cd02754d970c9bab864ebb3c0f3016fe51d3f57"
+        );
+        let sealed = seal_with(
+            &x25519_public_key(&recipient),
+            b"attack at dawn",
+            &mut FixedRng(9),
+        )
+        .unwrap();
+        assert_eq!(
+            hex::encode(&sealed),
+            "57db4b359f23ae5e146e4e2512056704722506348c150c14753d0c933d04d421\
+             7740890a95bc3d188c0561b5c8f04ab0b43cbe10c4e5e6867f1796407727"
+        );
+    }
+
+    #[test]
+    fn test_seal_and_open() {
+        let recipient = Ed25519Keypair::from_seed([1u8; 32]);
+        let other = Ed25519Keypair::from_seed([2u8; 32]);
+
+        let sealed = seal(&x25519_public_key(&recipient), b"attack at dawn").unwrap();
+        assert_eq!(sealed.len(), 14 + SEAL_OVERHEAD);
+        assert_eq!(&*open(&recipient, &sealed).unwrap(), b"attack at dawn");
+        assert!(open(&other, &sealed).is_err());
+
+        let mut tampered = sealed.clone();
+        tampered[40] ^= 1;
+        assert!(open(&recipient, &tampered).is_err());
+        assert!(open(&recipient, &sealed[..SEAL_OVERHEAD - 1]).is_err());
+
+        // Sealing to the SSH (Ed25519) form of the key reaches the same recipient
+        let converted = x25519_from_ed25519(&recipient.public_key_bytes()).unwrap();
+        assert_eq!(converted, x25519_public_key(&recipient));
+    }
+}
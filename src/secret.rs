@@ -0,0 +1,53 @@
+//! Wrapper types for secret values
+//!
+//! Secrets such as the seed phrase are kept in wrappers that zeroize on drop
+//! and never print their contents through `Debug`.
+
+use std::fmt;
+use zeroize::Zeroizing;
+
+/// A string holding secret material (e.g. a BIP-39 seed phrase)
+///
+/// `Debug` output shows only the length, and the buffer is zeroized on drop.
+/// Use [`SecretString::expose_secret`] at the point the value is needed.
+#[derive(Clone)]
+pub struct SecretString(Zeroizing<String>);
+
+impl SecretString {
+    /// Wrap a secret string
+    pub fn new(secret: String) -> Self {
+        Self(Zeroizing::new(secret))
+    }
+
+    /// Borrow the secret value
+    pub fn expose_secret(&self) -> &str {
+        &self.0
+    }
+}
+
+impl From<String> for SecretString {
+    fn from(secret: String) -> Self {
+        Self::new(secret)
+    }
+}
+
+impl fmt::Debug for SecretString {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "SecretString([REDACTED; {} bytes])", self.0.len())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_debug_redacts() {
+        let secret = SecretString::new("abandon abandon about".to_string());
+        let debug = format!("{:?}", secret);
+
+        assert!(!debug.contains("abandon"));
+        assert_eq!(debug, "SecretString([REDACTED; 21 bytes])");
+        assert_eq!(secret.expose_secret(), "abandon abandon about");
+    }
+}
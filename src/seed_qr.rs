@@ -0,0 +1,161 @@
+//! SeedQR encoding of BIP-39 seeds
+//!
+//! SeedQR (from the SeedSigner project) moves a seed to and from hardware
+//! wallets as a QR code:
+//!
+//! - Standard SeedQR: each word's wordlist index as four decimal digits,
+//!   concatenated and encoded in numeric mode (48 digits for 12 words).
+//! - Compact SeedQR: the raw entropy bytes in byte mode, without the
+//!   checksum (16 bytes for 12 words).
+//!
+//! Both use error correction level L, giving symbols of 25x25 (standard)
+//! or 21x21 (compact) modules for 12 words and 29x29 or 25x25 for 24 words,
+//! the sizes of the hand-transcription templates. Only 12- and 24-word
+//! seeds are defined.
+
+use crate::{
+    bip32_wrapper::Language,
+    error::{BipKeychainError, Result},
+    qr::{EcLevel, QrCode},
+};
+use zeroize::Zeroizing;
+
+/// Which SeedQR encoding to use
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SeedQrFormat {
+    /// Four-digit word indices in numeric mode
+    Standard,
+    /// Raw entropy in byte mode
+    Compact,
+}
+
+fn seed_qr_error(message: impl Into<String>) -> BipKeychainError {
+    BipKeychainError::InvalidSeedPhrase(message.into())
+}
+
+fn check_word_count(count: usize) -> Result<()> {
+    match count {
+        12 | 24 => Ok(()),
+        _ => Err(seed_qr_error(format!(
+            "SeedQR holds 12 or 24 words, not {}",
+            count
+        ))),
+    }
+}
+
+/// The SeedQR payload of `mnemonic`: ASCII digits for standard, entropy for
+/// compact
+pub fn encode(mnemonic: &bip39::Mnemonic, format: SeedQrFormat) -> Result<Zeroizing<Vec<u8>>> {
+    check_word_count(mnemonic.word_count())?;
+    Ok(Zeroizing::new(match format {
+        SeedQrFormat::Standard => mnemonic
+            .word_indices()
+            .flat_map(|index| format!("{:04}", index).into_bytes())
+            .collect(),
+        SeedQrFormat::Compact => mnemonic.to_entropy(),
+    }))
+}
+
+/// `mnemonic` as a SeedQR code
+pub fn to_qr(mnemonic: &bip39::Mnemonic, format: SeedQrFormat) -> Result<QrCode> {
+    let payload = encode(mnemonic, format)?;
+    match format {
+        SeedQrFormat::Standard => QrCode::encode_numeric(
+            std::str::from_utf8(&payload).expect("digits are ASCII"),
+            EcLevel::L,
+        ),
+        SeedQrFormat::Compact => QrCode::encode_bytes(&payload, EcLevel::L),
+    }
+}
+
+/// Decode a scanned SeedQR payload into a mnemonic in `language`
+///
+/// 48 or 96 ASCII digits (surrounding whitespace ignored) are read as
+/// standard SeedQR; 16 or 32 bytes as compact SeedQR.
+pub fn decode(payload: &[u8], language: Language) -> Result<(bip39::Mnemonic, SeedQrFormat)> {
+    let trimmed = payload.trim_ascii();
+    if matches!(trimmed.len(), 48 | 96) && trimmed.iter().all(u8::is_ascii_digit) {
+        let words = Zeroizing::new(
+            trimmed
+                .chunks(4)
+                .map(|digits| {
+                    let index: usize = std::str::from_utf8(digits)
+                        .expect("digits are ASCII")
+                        .parse()
+                        .expect("four digits");
+                    language.word_list().get(index).copied().ok_or_else(|| {
+                        seed_qr_error(format!("SeedQR word index {} is out of range", index))
+                    })
+                })
+                .collect::<Result<Vec<_>>>()?
+                .join(" "),
+        );
+        let mnemonic = bip39::Mnemonic::parse_in(language, words.as_str())
+            .map_err(|e| seed_qr_error(format!("Invalid SeedQR: {}", e)))?;
+        return Ok((mnemonic, SeedQrFormat::Standard));
+    }
+
+    match payload.len() {
+        16 | 32 => {
+            let mnemonic = bip39::Mnemonic::from_entropy_in(language, payload)
+                .map_err(|e| seed_qr_error(format!("Invalid compact SeedQR: {}", e)))?;
+            Ok((mnemonic, SeedQrFormat::Compact))
+        }
+        len => Err(seed_qr_error(format!(
+            "Not a SeedQR: expected 48 or 96 digits, or 16 or 32 bytes, got {} bytes",
+            len
+        ))),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const WORDS_24: &str = "attack pizza motion avocado network gather crop fresh patrol unusual wild holiday candy pony ranch winter theme error hybrid van cereal salon goddess expire";
+
+    #[test]
+    fn test_standard_seed_qr() {
+        // SeedQR specification example
+        let mnemonic = bip39::Mnemonic::parse(WORDS_24).unwrap();
+        let digits = encode(&mnemonic, SeedQrFormat::Standard).unwrap();
+        assert_eq!(
+            std::str::from_utf8(&digits).unwrap(),
+            "011513251154012711900771041507421289190620080870026613431420201617920614089619290300152408010643"
+        );
+        let (decoded, format) = decode(&digits, Language::English).unwrap();
+        assert_eq!(
+            (decoded, format),
+            (mnemonic.clone(), SeedQrFormat::Standard)
+        );
+        assert_eq!(to_qr(&mnemonic, SeedQrFormat::Standard).unwrap().size(), 29);
+
+        // A broken checksum word is rejected
+        let mut broken = digits.to_vec();
+        broken[92..].copy_from_slice(b"0000");
+        assert!(decode(&broken, Language::English).is_err());
+        let mut out_of_range = digits.to_vec();
+        out_of_range[..4].copy_from_slice(b"2048");
+        assert!(decode(&out_of_range, Language::English).is_err());
+    }
+
+    #[test]
+    fn test_compact_seed_qr() {
+        let mnemonic = bip39::Mnemonic::from_entropy(&[0u8; 16]).unwrap();
+        let entropy = encode(&mnemonic, SeedQrFormat::Compact).unwrap();
+        assert_eq!(*entropy, [0u8; 16]);
+        assert_eq!(to_qr(&mnemonic, SeedQrFormat::Compact).unwrap().size(), 21);
+        assert_eq!(to_qr(&mnemonic, SeedQrFormat::Standard).unwrap().size(), 25);
+
+        let (decoded, format) = decode(&entropy, Language::English).unwrap();
+        assert_eq!((decoded, format), (mnemonic, SeedQrFormat::Compact));
+
+        let long = bip39::Mnemonic::from_entropy(&[7u8; 32]).unwrap();
+        assert_eq!(to_qr(&long, SeedQrFormat::Compact).unwrap().size(), 25);
+
+        // 15-word seeds have no SeedQR form
+        let odd = bip39::Mnemonic::from_entropy(&[0u8; 20]).unwrap();
+        assert!(encode(&odd, SeedQrFormat::Compact).is_err());
+        assert!(decode(&[0u8; 20], Language::English).is_err());
+    }
+}
@@ -0,0 +1,208 @@
+//! Seed and SSKR share URs (BCR-2020-006, BCR-2020-011)
+//!
+//! `ur:crypto-seed` carries the seed's BIP-39 entropy as a CBOR map
+//! (`1`: payload, `3`: optional name); `ur:crypto-sskr` carries one SSKR
+//! share as a CBOR byte string. Both fit in a single QR code, so a seed and
+//! its shares can move between airgapped devices with a camera.
+//!
+//! The creation date (`2`) and note (`4`) of a crypto-seed are skipped on
+//! decoding. Either payload can also be written down as standard Bytewords
+//! (see [`crate::backup_sheet`]) and turned back into its UR with
+//! [`from_bytewords`].
+
+use crate::{
+    dcbor::{self, MAJOR_BYTES, MAJOR_MAP, MAJOR_TEXT, MAJOR_UNSIGNED},
+    error::{BipKeychainError, Result},
+    ur,
+};
+use zeroize::Zeroizing;
+
+/// UR type of a seed
+pub const SEED_UR_TYPE: &str = "crypto-seed";
+
+/// UR type of an SSKR share
+pub const SSKR_UR_TYPE: &str = "crypto-sskr";
+
+const SEED_PAYLOAD: u64 = 1;
+const SEED_NAME: u64 = 3;
+
+fn ur_error(message: String) -> BipKeychainError {
+    BipKeychainError::UrError(message)
+}
+
+/// Decode a UR, checking its type
+fn decode_typed(ur_string: &str, expected: &str) -> Result<Vec<u8>> {
+    let (ur_type, cbor) = ur::decode(ur_string)?;
+    if ur_type != expected {
+        return Err(ur_error(format!(
+            "expected ur:{}, got ur:{}",
+            expected, ur_type
+        )));
+    }
+    Ok(cbor)
+}
+
+/// Encode seed entropy (e.g. [`bip39::Mnemonic::to_entropy`]) as `ur:crypto-seed`
+pub fn encode_seed(entropy: &[u8], name: Option<&str>) -> Zeroizing<String> {
+    let mut cbor = Zeroizing::new(Vec::new());
+    dcbor::encode_map_header(&mut cbor, 1 + name.is_some() as usize);
+    dcbor::encode_unsigned(&mut cbor, SEED_PAYLOAD);
+    dcbor::encode_bytes(&mut cbor, entropy);
+    if let Some(name) = name {
+        dcbor::encode_unsigned(&mut cbor, SEED_NAME);
+        dcbor::encode_text(&mut cbor, name);
+    }
+    Zeroizing::new(ur::encode(SEED_UR_TYPE, &cbor))
+}
+
+/// Decode `ur:crypto-seed` into the seed entropy and its name, if any
+pub fn decode_seed(ur_string: &str) -> Result<(Zeroizing<Vec<u8>>, Option<String>)> {
+    let cbor = Zeroizing::new(decode_typed(ur_string, SEED_UR_TYPE)?);
+    let mut decoder = dcbor::Decoder::new(&cbor);
+    let invalid = |e: String| ur_error(format!("invalid crypto-seed: {}", e));
+
+    let (major, entries) = decoder.read_head().map_err(invalid)?;
+    if major != MAJOR_MAP {
+        return Err(invalid("not a CBOR map".to_string()));
+    }
+    let mut payload = None;
+    let mut name = None;
+    for _ in 0..entries {
+        let (major, key) = decoder.read_head().map_err(invalid)?;
+        if major != MAJOR_UNSIGNED {
+            return Err(invalid("map key is not an integer".to_string()));
+        }
+        match key {
+            SEED_PAYLOAD | SEED_NAME => {
+                let (major, len) = decoder.read_head().map_err(invalid)?;
+                let bytes = decoder.read_bytes(len).map_err(invalid)?;
+                match (key, major) {
+                    (SEED_PAYLOAD, MAJOR_BYTES) => payload = Some(Zeroizing::new(bytes.to_vec())),
+                    (SEED_NAME, MAJOR_TEXT) => {
+                        name = Some(
+                            String::from_utf8(bytes.to_vec())
+                                .map_err(|_| invalid("name is not UTF-8".to_string()))?,
+                        )
+                    }
+                    _ => return Err(invalid(format!("field {} has the wrong type", key))),
+                }
+            }
+            _ => {
+                decoder.read_item().map_err(invalid)?;
+            }
+        }
+    }
+    if !decoder.is_empty() {
+        return Err(invalid("trailing data".to_string()));
+    }
+
+    let payload = payload.ok_or_else(|| invalid("missing payload".to_string()))?;
+    Ok((payload, name))
+}
+
+/// Encode an SSKR share as `ur:crypto-sskr`
+pub fn encode_share(share: &[u8]) -> String {
+    let mut cbor = Vec::new();
+    dcbor::encode_bytes(&mut cbor, share);
+    ur::encode(SSKR_UR_TYPE, &cbor)
+}
+
+/// Decode `ur:crypto-sskr` into the share bytes
+pub fn decode_share(ur_string: &str) -> Result<Vec<u8>> {
+    let cbor = decode_typed(ur_string, SSKR_UR_TYPE)?;
+    let mut decoder = dcbor::Decoder::new(&cbor);
+    let invalid = |e: String| ur_error(format!("invalid crypto-sskr: {}", e));
+
+    let (major, len) = decoder.read_head().map_err(invalid)?;
+    if major != MAJOR_BYTES {
+        return Err(invalid("not a CBOR byte string".to_string()));
+    }
+    let share = decoder.read_bytes(len).map_err(invalid)?.to_vec();
+    if !decoder.is_empty() {
+        return Err(invalid("trailing data".to_string()));
+    }
+    Ok(share)
+}
+
+/// Turn the standard Bytewords of a crypto-seed or crypto-sskr payload back
+/// into its UR
+///
+/// The type is told apart by the payload: a CBOR map is a seed, a byte
+/// string is a share.
+pub fn from_bytewords(text: &str) -> Result<Zeroizing<String>> {
+    let cbor = Zeroizing::new(ur::bytewords_decode(text)?);
+    let ur_type = match cbor.first().map(|byte| byte >> 5) {
+        Some(MAJOR_MAP) => SEED_UR_TYPE,
+        Some(MAJOR_BYTES) => SSKR_UR_TYPE,
+        _ => {
+            return Err(ur_error(
+                "Bytewords are not a crypto-seed or crypto-sskr payload".to_string(),
+            ))
+        }
+    };
+    Ok(Zeroizing::new(ur::encode(ur_type, &cbor)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sskr::{self, GroupSpec};
+
+    #[test]
+    fn test_seed_ur_round_trip() {
+        let entropy = [0x42u8; 32];
+        let ur_string = encode_seed(&entropy, Some("Work"));
+        assert!(ur_string.starts_with("ur:crypto-seed/"));
+        let (decoded, name) = decode_seed(&ur_string.to_uppercase()).unwrap();
+        assert_eq!(*decoded, entropy);
+        assert_eq!(name.as_deref(), Some("Work"));
+
+        let (decoded, name) = decode_seed(&encode_seed(&entropy[..16], None)).unwrap();
+        assert_eq!(*decoded, entropy[..16]);
+        assert_eq!(name, None);
+
+        // Creation date (tag 1, epoch seconds) and note are skipped
+        let mut cbor = vec![0xa3, 0x01, 0x50];
+        cbor.extend_from_slice(&[7u8; 16]);
+        cbor.extend_from_slice(&[0x02, 0xc1, 0x1a, 0x65, 0x00, 0x00, 0x00]);
+        cbor.extend_from_slice(&[0x04, 0x62, b'h', b'i']);
+        let (decoded, _) = decode_seed(&ur::encode(SEED_UR_TYPE, &cbor)).unwrap();
+        assert_eq!(*decoded, [7u8; 16]);
+
+        assert!(decode_seed(&encode_share(&entropy)).is_err());
+        assert!(decode_seed(&ur::encode(SEED_UR_TYPE, &[0xa0])).is_err());
+    }
+
+    #[test]
+    fn test_share_urs_recover_the_seed() {
+        let entropy = [9u8; 16];
+        let group = GroupSpec::parse("2of3").unwrap();
+        let shares = sskr::generate_shares(&entropy, 1, &[group]).unwrap();
+        let urs: Vec<String> = shares[0].iter().map(|share| encode_share(share)).collect();
+        assert!(urs.iter().all(|ur| ur.starts_with("ur:crypto-sskr/")));
+
+        let decoded: Vec<Vec<u8>> = urs[1..]
+            .iter()
+            .map(|ur| decode_share(ur).unwrap())
+            .collect();
+        assert_eq!(sskr::combine_shares(&decoded).unwrap(), entropy);
+        assert!(decode_share(&encode_seed(&entropy, None)).is_err());
+    }
+
+    #[test]
+    fn test_from_bytewords() {
+        let seed = encode_seed(&[3u8; 16], Some("Work"));
+        let share = encode_share(&[4u8; 21]);
+        for ur_string in [seed.as_str(), share.as_str()] {
+            let (_, cbor) = ur::decode(ur_string).unwrap();
+            let words = ur::bytewords(&cbor).to_uppercase();
+            assert_eq!(*from_bytewords(&words).unwrap(), ur_string);
+        }
+
+        let (_, cbor) = ur::decode(&share).unwrap();
+        let mut words = ur::bytewords(&cbor);
+        words.replace_range(..4, "zoom");
+        assert!(from_bytewords(&words).is_err());
+        assert!(from_bytewords(&ur::bytewords(&[0x01])).is_err());
+    }
+}
@@ -0,0 +1,194 @@
+//! Detached Ed25519 signatures over arbitrary data
+//!
+//! Signs files with an entity-derived key in one of three encodings:
+//! - raw 64-byte signature
+//! - hex-encoded signature
+//! - armored SSHSIG (compatible with `ssh-keygen -Y verify`)
+//!
+//! Verification detects the encoding automatically.
+
+use crate::{
+    error::{BipKeychainError, Result},
+    output::Ed25519Keypair,
+    ssh_wire::parse_ed25519_key_blob,
+    sshsig,
+};
+use base64::Engine;
+use ed25519_dalek::{Signature, Signer, Verifier, VerifyingKey};
+
+/// Namespace used for SSHSIG file signatures (matches `ssh-keygen -Y sign -n file`)
+pub const DEFAULT_NAMESPACE: &str = "file";
+
+/// Encoding for detached signatures
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SignatureFormat {
+    /// Raw 64-byte Ed25519 signature
+    Raw,
+    /// Ed25519 signature as 128 hex characters
+    Hex,
+    /// Armored OpenSSH signature
+    SshSig,
+}
+
+/// Sign a message and encode the signature
+///
+/// `namespace` only applies to [`SignatureFormat::SshSig`]; raw and hex
+/// signatures cover the message bytes directly.
+pub fn sign_message(
+    keypair: &Ed25519Keypair,
+    message: &[u8],
+    format: SignatureFormat,
+    namespace: &str,
+) -> Result<Vec<u8>> {
+    match format {
+        SignatureFormat::Raw => Ok(keypair.signing_key().sign(message).to_bytes().to_vec()),
+        SignatureFormat::Hex => {
+            let signature = keypair.signing_key().sign(message);
+            Ok(hex::encode(signature.to_bytes()).into_bytes())
+        }
+        SignatureFormat::SshSig => Ok(sshsig::sign(keypair, namespace, message)?.into_bytes()),
+    }
+}
+
+/// Verify a detached signature in any supported encoding
+///
+/// For SSHSIG signatures, the embedded signer key must equal `public_key`.
+pub fn verify_message(
+    public_key: &[u8; 32],
+    message: &[u8],
+    signature: &[u8],
+    namespace: &str,
+) -> Result<()> {
+    let format = detect_format(signature)?;
+
+    if format == SignatureFormat::SshSig {
+        let armored = std::str::from_utf8(signature).map_err(|_| {
+            BipKeychainError::SignatureError("SSH signature is not valid UTF-8".to_string())
+        })?;
+        let signer = sshsig::verify(armored, namespace, message)?;
+        if &signer != public_key {
+            return Err(BipKeychainError::SignatureError(
+                "Signature was made by a different key".to_string(),
+            ));
+        }
+        return Ok(());
+    }
+
+    let signature_bytes = match format {
+        SignatureFormat::Hex => {
+            let text = std::str::from_utf8(signature).unwrap_or_default().trim();
+            hex::decode(text).map_err(|e| {
+                BipKeychainError::SignatureError(format!("Invalid hex signature: {}", e))
+            })?
+        }
+        _ => signature.to_vec(),
+    };
+
+    let signature = Signature::from_slice(&signature_bytes).map_err(|_| {
+        BipKeychainError::SignatureError("Ed25519 signatures must be 64 bytes".to_string())
+    })?;
+    let verifying_key = VerifyingKey::from_bytes(public_key)
+        .map_err(|_| BipKeychainError::SignatureError("Invalid Ed25519 public key".to_string()))?;
+
+    verifying_key
+        .verify(message, &signature)
+        .map_err(|_| BipKeychainError::SignatureError("Signature does not match".to_string()))
+}
+
+/// Work out which encoding a signature uses
+pub fn detect_format(signature: &[u8]) -> Result<SignatureFormat> {
+    let text = std::str::from_utf8(signature).map(str::trim);
+
+    match text {
+        Ok(t) if t.starts_with("-----BEGIN SSH SIGNATURE-----") => Ok(SignatureFormat::SshSig),
+        Ok(t) if t.len() == 128 && t.bytes().all(|b| b.is_ascii_hexdigit()) => {
+            Ok(SignatureFormat::Hex)
+        }
+        _ if signature.len() == 64 => Ok(SignatureFormat::Raw),
+        _ => Err(BipKeychainError::SignatureError(
+            "Unrecognized signature: expected 64 raw bytes, 128 hex characters, or an SSH signature"
+                .to_string(),
+        )),
+    }
+}
+
+/// Parse an Ed25519 public key from an OpenSSH line or hex
+///
+/// Accepts `ssh-ed25519 AAAA... [comment]` (as printed by `derive`) or the
+/// 32-byte key as 64 hex characters.
+pub fn parse_public_key(input: &str) -> Result<[u8; 32]> {
+    let input = input.trim();
+    let invalid = || {
+        BipKeychainError::SignatureError(
+            "Public key must be an ssh-ed25519 line or 64 hex characters".to_string(),
+        )
+    };
+
+    if let Some(rest) = input.strip_prefix("ssh-ed25519 ") {
+        let encoded = rest.split_whitespace().next().ok_or_else(invalid)?;
+        let blob = base64::engine::general_purpose::STANDARD
+            .decode(encoded)
+            .map_err(|_| invalid())?;
+        return parse_ed25519_key_blob(&blob).ok_or_else(invalid);
+    }
+
+    hex::decode(input)
+        .ok()
+        .and_then(|bytes| bytes.try_into().ok())
+        .ok_or_else(invalid)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MESSAGE: &[u8] = b"release-v1.0.tar.gz contents";
+
+    #[test]
+    fn test_sign_verify_all_formats() {
+        let keypair = Ed25519Keypair::from_seed([9u8; 32]);
+        let public_key = keypair.public_key_bytes();
+
+        for format in [
+            SignatureFormat::Raw,
+            SignatureFormat::Hex,
+            SignatureFormat::SshSig,
+        ] {
+            let signature = sign_message(&keypair, MESSAGE, format, DEFAULT_NAMESPACE).unwrap();
+            assert_eq!(detect_format(&signature).unwrap(), format);
+            verify_message(&public_key, MESSAGE, &signature, DEFAULT_NAMESPACE).unwrap();
+            assert!(
+                verify_message(&public_key, b"tampered", &signature, DEFAULT_NAMESPACE).is_err()
+            );
+        }
+    }
+
+    #[test]
+    fn test_verify_rejects_other_key() {
+        let keypair = Ed25519Keypair::from_seed([9u8; 32]);
+        let other = Ed25519Keypair::from_seed([10u8; 32]).public_key_bytes();
+
+        let signature = sign_message(
+            &keypair,
+            MESSAGE,
+            SignatureFormat::SshSig,
+            DEFAULT_NAMESPACE,
+        )
+        .unwrap();
+        assert!(verify_message(&other, MESSAGE, &signature, DEFAULT_NAMESPACE).is_err());
+    }
+
+    #[test]
+    fn test_parse_public_key_formats() {
+        let keypair = Ed25519Keypair::from_seed([9u8; 32]);
+        let public_key = keypair.public_key_bytes();
+
+        let ssh_line = keypair.to_ssh_public_key(Some("comment with spaces"));
+        assert_eq!(parse_public_key(&ssh_line).unwrap(), public_key);
+        assert_eq!(
+            parse_public_key(&hex::encode(public_key)).unwrap(),
+            public_key
+        );
+        assert!(parse_public_key("ssh-rsa AAAA").is_err());
+    }
+}
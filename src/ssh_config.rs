@@ -0,0 +1,215 @@
+//! OpenSSH client configuration
+//!
+//! Renders `Host` blocks (ssh_config(5)) that point ssh(1) at entity-derived
+//! keys, and keeps them in a marked section of `~/.ssh/config`:
+//!
+//! ```text
+//! # BEGIN bip-keychain (generated by `bip-keychain ssh config`)
+//! # SSH access key for db1.example.com (SHA256:...)
+//! Host db1.example.com
+//!     IdentityFile ~/.config/bip-keychain/db1.pub
+//!     IdentitiesOnly yes
+//! # END bip-keychain
+//! ```
+//!
+//! `IdentityFile` names the public key: with the private key loaded in an
+//! agent, ssh offers exactly that key, and `IdentitiesOnly` stops it trying
+//! every other agent key first. Regenerating replaces the section and
+//! leaves every other line of the file alone.
+
+use crate::error::{BipKeychainError, Result};
+
+/// First line of the generated section
+pub const SECTION_BEGIN: &str = "# BEGIN bip-keychain";
+
+/// Last line of the generated section
+pub const SECTION_END: &str = "# END bip-keychain";
+
+/// One generated `Host` block
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SshHost {
+    /// `Host` patterns, separated by spaces (e.g. `db1 db1.example.com`)
+    pub host: String,
+
+    /// Public key file for `IdentityFile`
+    pub identity_file: String,
+
+    /// Comment line above the block, e.g. the key's purpose and fingerprint
+    pub comment: String,
+}
+
+fn config_error(message: String) -> BipKeychainError {
+    BipKeychainError::OutputError(message)
+}
+
+/// A path as an ssh_config argument, quoted if it contains whitespace
+fn quote_argument(value: &str) -> Result<String> {
+    if value.chars().any(|c| c.is_control() || c == '"') {
+        return Err(config_error(format!(
+            "'{}' cannot be written to an SSH config",
+            value.escape_debug()
+        )));
+    }
+    Ok(if value.chars().any(char::is_whitespace) {
+        format!("\"{}\"", value)
+    } else {
+        value.to_string()
+    })
+}
+
+/// The generated section, markers included
+///
+/// With `identity_agent`, every block also sets `IdentityAgent` so ssh asks
+/// that agent (e.g. `bip-keychain agent --socket`) for the keys.
+pub fn render_section(hosts: &[SshHost], identity_agent: Option<&str>) -> Result<String> {
+    let mut section = format!(
+        "{} (generated by `bip-keychain ssh config`)\n",
+        SECTION_BEGIN
+    );
+    for host in hosts {
+        let patterns: Vec<&str> = host.host.split_whitespace().collect();
+        let valid = !patterns.is_empty()
+            && !host
+                .host
+                .chars()
+                .any(|c| c.is_control() || c == '#' || c == '"');
+        if !valid {
+            return Err(config_error(format!(
+                "Invalid SSH Host pattern '{}'",
+                host.host.escape_debug()
+            )));
+        }
+
+        // A comment must stay on its own line
+        let comment: String = host
+            .comment
+            .chars()
+            .map(|c| if c.is_control() { ' ' } else { c })
+            .collect();
+        section.push_str(&format!("# {}\n", comment.trim()));
+        section.push_str(&format!("Host {}\n", patterns.join(" ")));
+        section.push_str(&format!(
+            "    IdentityFile {}\n",
+            quote_argument(&host.identity_file)?
+        ));
+        section.push_str("    IdentitiesOnly yes\n");
+        if let Some(agent) = identity_agent {
+            section.push_str(&format!("    IdentityAgent {}\n", quote_argument(agent)?));
+        }
+    }
+    section.push_str(SECTION_END);
+    section.push('\n');
+    Ok(section)
+}
+
+/// `contents` with its generated section replaced by `section`
+///
+/// A file without a section gets it appended: a `Host` block at the end
+/// cannot capture the options of blocks that follow it. Returns `None` if
+/// the file already holds exactly this section.
+pub fn update_config(contents: &str, section: &str) -> Result<Option<String>> {
+    let lines: Vec<&str> = contents.lines().collect();
+    let begin = lines
+        .iter()
+        .position(|line| line.starts_with(SECTION_BEGIN));
+    let updated = match begin {
+        Some(begin) => {
+            let end = lines[begin..]
+                .iter()
+                .position(|line| line.trim_end() == SECTION_END)
+                .map(|offset| begin + offset)
+                .ok_or_else(|| {
+                    config_error(format!(
+                        "SSH config has '{}' without a matching '{}'",
+                        SECTION_BEGIN, SECTION_END
+                    ))
+                })?;
+            let mut updated = String::new();
+            for line in &lines[..begin] {
+                updated.push_str(line);
+                updated.push('\n');
+            }
+            updated.push_str(section);
+            for line in &lines[end + 1..] {
+                updated.push_str(line);
+                updated.push('\n');
+            }
+            updated
+        }
+        None => {
+            let mut updated = contents.to_string();
+            if !updated.is_empty() {
+                if !updated.ends_with('\n') {
+                    updated.push('\n');
+                }
+                updated.push('\n');
+            }
+            updated.push_str(section);
+            updated
+        }
+    };
+    Ok((updated != contents).then_some(updated))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn host(name: &str) -> SshHost {
+        SshHost {
+            host: name.to_string(),
+            identity_file: format!("/home/me/.config/bip-keychain/{}.pub", name),
+            comment: format!("Access key for {}", name),
+        }
+    }
+
+    #[test]
+    fn test_render_section() {
+        let section = render_section(&[host("db1")], Some("/run/user/1000/bk.sock")).unwrap();
+        assert_eq!(
+            section,
+            "# BEGIN bip-keychain (generated by `bip-keychain ssh config`)\n\
+             # Access key for db1\n\
+             Host db1\n    \
+             IdentityFile /home/me/.config/bip-keychain/db1.pub\n    \
+             IdentitiesOnly yes\n    \
+             IdentityAgent /run/user/1000/bk.sock\n\
+             # END bip-keychain\n"
+        );
+
+        let mut spaced = host("db1 db1.example.com");
+        spaced.identity_file = "/home/me/My Keys/db1.pub".to_string();
+        spaced.comment = "line one\nHost evil".to_string();
+        let section = render_section(&[spaced], None).unwrap();
+        assert!(section.contains("Host db1 db1.example.com\n"));
+        assert!(section.contains("IdentityFile \"/home/me/My Keys/db1.pub\"\n"));
+        assert!(section.contains("# line one Host evil\n"));
+        assert!(!section.contains("IdentityAgent"));
+
+        for bad in ["", "db1\nHost *", "db1#x"] {
+            assert!(render_section(&[host(bad)], None).is_err(), "{:?}", bad);
+        }
+    }
+
+    #[test]
+    fn test_update_config() {
+        let first = render_section(&[host("db1")], None).unwrap();
+        let second = render_section(&[host("db1"), host("db2")], None).unwrap();
+
+        let existing = "Host *\n    ServerAliveInterval 60\n";
+        let added = update_config(existing, &first).unwrap().unwrap();
+        assert_eq!(added, format!("{}\n{}", existing, first));
+        assert_eq!(update_config(&added, &first).unwrap(), None);
+        assert_eq!(update_config("", &first).unwrap().unwrap(), first);
+
+        // The section is replaced in place; lines around it are kept
+        let surrounded = format!("# mine\n{}Host work\n    User me\n", first);
+        assert_eq!(
+            update_config(&surrounded, &second).unwrap().unwrap(),
+            format!("# mine\n{}Host work\n    User me\n", second)
+        );
+
+        let unterminated = format!("{}\nHost db1\n", SECTION_BEGIN);
+        assert!(update_config(&unterminated, &first).is_err());
+    }
+}
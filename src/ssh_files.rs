@@ -0,0 +1,212 @@
+//! authorized_keys and known_hosts editing
+//!
+//! Adds and removes derived Ed25519 public keys in OpenSSH's
+//! `authorized_keys` (sshd(8) AUTHORIZED_KEYS FILE FORMAT) and
+//! `known_hosts` (SSH_KNOWN_HOSTS FILE FORMAT) files. Entries are matched
+//! by key, not by comment, so installing twice leaves the file unchanged
+//! and removal finds entries whatever their comment or options. Every
+//! other line, including comments and blank lines, is kept as is.
+
+use crate::ssh_wire::{ed25519_public_key_line, parse_ed25519_key_blob, ED25519_KEY_TYPE};
+
+/// An OpenSSH file listing public keys
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SshKeyFile {
+    /// `authorized_keys`: keys allowed to log in
+    AuthorizedKeys,
+    /// `known_hosts`: host keys trusted for these host names
+    KnownHosts {
+        /// Host names or `[host]:port` patterns
+        hosts: Vec<String>,
+    },
+}
+
+/// One line of the file, split into host names (known_hosts only) and key
+struct Entry<'a> {
+    hosts: Vec<&'a str>,
+    key: [u8; 32],
+}
+
+impl SshKeyFile {
+    /// Ed25519 entry in `line`, if any
+    ///
+    /// Marker lines (`@cert-authority`, `@revoked`) and hashed host names
+    /// are never matched.
+    fn parse<'a>(&self, line: &'a str) -> Option<Entry<'a>> {
+        let fields: Vec<&str> = line.split_whitespace().collect();
+        if fields.first()?.starts_with('#') {
+            return None;
+        }
+        let (hosts, key_fields) = match self {
+            // Options may precede the key type; find the key type field
+            SshKeyFile::AuthorizedKeys => {
+                let at = fields
+                    .iter()
+                    .position(|field| field.as_bytes() == ED25519_KEY_TYPE)?;
+                (Vec::new(), &fields[at..])
+            }
+            SshKeyFile::KnownHosts { .. } => {
+                if fields[0].starts_with('@') || fields[0].starts_with('|') {
+                    return None;
+                }
+                (fields[0].split(',').collect(), &fields[1..])
+            }
+        };
+
+        if key_fields.first()?.as_bytes() != ED25519_KEY_TYPE {
+            return None;
+        }
+        let blob = base64::Engine::decode(
+            &base64::engine::general_purpose::STANDARD,
+            key_fields.get(1)?,
+        )
+        .ok()?;
+        Some(Entry {
+            hosts,
+            key: parse_ed25519_key_blob(&blob)?,
+        })
+    }
+
+    /// Line for `public_key`
+    fn line(&self, public_key: &[u8; 32], comment: &str) -> String {
+        let key = ed25519_public_key_line(public_key, comment);
+        match self {
+            SshKeyFile::AuthorizedKeys => key,
+            SshKeyFile::KnownHosts { hosts } => format!("{} {}", hosts.join(","), key),
+        }
+    }
+
+    /// Add `public_key` to `contents`
+    ///
+    /// Returns the new file contents, or `None` if the key is already
+    /// listed (for known_hosts: for every host).
+    pub fn install(&self, contents: &str, public_key: &[u8; 32], comment: &str) -> Option<String> {
+        let entries: Vec<Entry> = contents
+            .lines()
+            .filter_map(|line| self.parse(line))
+            .filter(|entry| entry.key == *public_key)
+            .collect();
+        let listed = match self {
+            SshKeyFile::AuthorizedKeys => !entries.is_empty(),
+            SshKeyFile::KnownHosts { hosts } => hosts.iter().all(|host| {
+                entries
+                    .iter()
+                    .any(|entry| entry.hosts.contains(&host.as_str()))
+            }),
+        };
+        if listed {
+            return None;
+        }
+
+        let mut updated = contents.to_string();
+        if !updated.is_empty() && !updated.ends_with('\n') {
+            updated.push('\n');
+        }
+        updated.push_str(&self.line(public_key, comment));
+        updated.push('\n');
+        Some(updated)
+    }
+
+    /// Remove `public_key` from `contents`
+    ///
+    /// For known_hosts, only the given hosts are removed from matching
+    /// lines; a line left with no hosts is dropped. Returns the new file
+    /// contents, or `None` if nothing matched.
+    pub fn remove(&self, contents: &str, public_key: &[u8; 32]) -> Option<String> {
+        let mut updated = String::with_capacity(contents.len());
+        let mut changed = false;
+        for line in contents.lines() {
+            let entry = self.parse(line).filter(|entry| entry.key == *public_key);
+            match (self, entry) {
+                (_, None) => {
+                    updated.push_str(line);
+                    updated.push('\n');
+                }
+                (SshKeyFile::AuthorizedKeys, Some(_)) => changed = true,
+                (SshKeyFile::KnownHosts { hosts }, Some(entry)) => {
+                    let kept: Vec<&str> = entry
+                        .hosts
+                        .iter()
+                        .copied()
+                        .filter(|host| !hosts.iter().any(|removed| removed == host))
+                        .collect();
+                    if kept.len() == entry.hosts.len() {
+                        updated.push_str(line);
+                        updated.push('\n');
+                        continue;
+                    }
+                    changed = true;
+                    if !kept.is_empty() {
+                        let rest = line
+                            .trim_start()
+                            .split_once(char::is_whitespace)
+                            .map_or("", |(_, rest)| rest.trim_start());
+                        updated.push_str(&format!("{} {}\n", kept.join(","), rest));
+                    }
+                }
+            }
+        }
+        changed.then_some(updated)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Ed25519Keypair;
+
+    fn key(seed: u8) -> [u8; 32] {
+        Ed25519Keypair::from_seed([seed; 32]).public_key_bytes()
+    }
+
+    fn key_line(seed: u8, comment: &str) -> String {
+        Ed25519Keypair::from_seed([seed; 32]).to_ssh_public_key(Some(comment))
+    }
+
+    #[test]
+    fn test_authorized_keys() {
+        let file = SshKeyFile::AuthorizedKeys;
+        let existing = "# managed by hand\nssh-rsa AAAAB3Nza other\n";
+
+        let installed = file.install(existing, &key(1), "deploy key").unwrap();
+        assert!(installed.starts_with(existing));
+        assert!(installed.ends_with(" deploy key\n"));
+        assert_eq!(file.install(&installed, &key(1), "renamed"), None);
+
+        // Matched by key even with options and another comment
+        let with_options = format!(
+            "restrict,command=\"echo hi\" {}\n",
+            key_line(1, "other comment")
+        );
+        assert_eq!(file.install(&with_options, &key(1), "deploy key"), None);
+        assert_eq!(file.remove(&with_options, &key(1)).unwrap(), "");
+
+        assert_eq!(file.remove(&installed, &key(1)).unwrap(), existing);
+        assert_eq!(file.remove(&installed, &key(2)), None);
+    }
+
+    #[test]
+    fn test_known_hosts() {
+        let file = SshKeyFile::KnownHosts {
+            hosts: vec!["db1.example.com".to_string()],
+        };
+        let both = SshKeyFile::KnownHosts {
+            hosts: vec!["db1.example.com".to_string(), "10.0.0.5".to_string()],
+        };
+
+        let installed = both.install("", &key(1), "db1 host key").unwrap();
+        assert!(installed.starts_with("db1.example.com,10.0.0.5 ssh-ed25519 "));
+        assert_eq!(file.install(&installed, &key(1), "db1 host key"), None);
+        assert!(file.install(&installed, &key(2), "db1 host key").is_some());
+
+        // Removing one host keeps the line for the other
+        let removed = file.remove(&installed, &key(1)).unwrap();
+        assert!(removed.starts_with("10.0.0.5 ssh-ed25519 "));
+        assert!(removed.ends_with(" db1 host key\n"));
+        assert_eq!(both.remove(&installed, &key(1)).unwrap(), "");
+
+        // Marker lines are left alone
+        let hashed = format!("@revoked db1.example.com {}\n", key_line(1, "x"));
+        assert_eq!(file.remove(&hashed, &key(1)), None);
+    }
+}
@@ -0,0 +1,62 @@
+//! SSH wire-format encoding helpers (RFC 4251, section 5)
+//!
+//! Shared by the SSH agent and SSHSIG code.
+
+/// Key type name for Ed25519 keys and signatures
+pub(crate) const ED25519_KEY_TYPE: &[u8] = b"ssh-ed25519";
+
+/// Append an SSH `string` (u32 big-endian length followed by the bytes)
+pub(crate) fn put_string(buf: &mut Vec<u8>, data: &[u8]) {
+    buf.extend_from_slice(&(data.len() as u32).to_be_bytes());
+    buf.extend_from_slice(data);
+}
+
+/// SSH public key blob: string("ssh-ed25519") || string(pubkey)
+pub(crate) fn ed25519_key_blob(public_key: &[u8; 32]) -> Vec<u8> {
+    let mut blob = Vec::new();
+    put_string(&mut blob, ED25519_KEY_TYPE);
+    put_string(&mut blob, public_key);
+    blob
+}
+
+/// OpenSSH public key line: `ssh-ed25519 <base64 blob> <comment>`
+pub(crate) fn ed25519_public_key_line(public_key: &[u8; 32], comment: &str) -> String {
+    let encoded = base64::Engine::encode(
+        &base64::engine::general_purpose::STANDARD,
+        ed25519_key_blob(public_key),
+    );
+    format!("ssh-ed25519 {} {}", encoded, comment)
+}
+
+/// Parse an Ed25519 SSH public key blob back into the raw key
+pub(crate) fn parse_ed25519_key_blob(blob: &[u8]) -> Option<[u8; 32]> {
+    let mut reader = WireReader::new(blob);
+    if reader.string()? != ED25519_KEY_TYPE {
+        return None;
+    }
+    reader.string()?.try_into().ok()
+}
+
+/// Minimal reader for SSH wire-format fields
+pub(crate) struct WireReader<'a> {
+    pub(crate) data: &'a [u8],
+}
+
+impl<'a> WireReader<'a> {
+    pub(crate) fn new(data: &'a [u8]) -> Self {
+        Self { data }
+    }
+
+    pub(crate) fn u32(&mut self) -> Option<u32> {
+        let (head, rest) = self.data.split_at_checked(4)?;
+        self.data = rest;
+        Some(u32::from_be_bytes(head.try_into().ok()?))
+    }
+
+    pub(crate) fn string(&mut self) -> Option<&'a [u8]> {
+        let len = self.u32()? as usize;
+        let (head, rest) = self.data.split_at_checked(len)?;
+        self.data = rest;
+        Some(head)
+    }
+}
@@ -0,0 +1,222 @@
+//! OpenSSH signature format (SSHSIG)
+//!
+//! Produces and checks the armored detached signatures written by
+//! `ssh-keygen -Y sign`, as specified in OpenSSH's PROTOCOL.sshsig. This is
+//! the format Git uses for SSH commit signing (`gpg.format=ssh`).
+//!
+//! Only Ed25519 keys are supported, matching the keys BIP-Keychain derives.
+
+use crate::{
+    error::{BipKeychainError, Result},
+    output::Ed25519Keypair,
+    ssh_wire::{
+        ed25519_key_blob, parse_ed25519_key_blob, put_string, WireReader, ED25519_KEY_TYPE,
+    },
+};
+use base64::Engine;
+use ed25519_dalek::{Signature, Signer, Verifier, VerifyingKey};
+use sha2::{Digest, Sha256, Sha512};
+
+const MAGIC_PREAMBLE: &[u8] = b"SSHSIG";
+const SIG_VERSION: u32 = 1;
+const BEGIN_ARMOR: &str = "-----BEGIN SSH SIGNATURE-----";
+const END_ARMOR: &str = "-----END SSH SIGNATURE-----";
+/// Base64 line width used by ssh-keygen
+const ARMOR_LINE_WIDTH: usize = 70;
+
+/// Namespace Git uses for commit and tag signatures
+pub const GIT_NAMESPACE: &str = "git";
+
+/// Sign a message and return an armored SSHSIG signature
+///
+/// The message is hashed with SHA-512 before signing, as ssh-keygen does.
+///
+/// # Example
+///
+/// ```ignore
+/// let armored = sshsig::sign(&keypair, sshsig::GIT_NAMESPACE, commit_bytes)?;
+/// ```
+pub fn sign(keypair: &Ed25519Keypair, namespace: &str, message: &[u8]) -> Result<String> {
+    if namespace.is_empty() {
+        return Err(BipKeychainError::SignatureError(
+            "SSHSIG namespace must not be empty".to_string(),
+        ));
+    }
+
+    let message_hash = Sha512::digest(message);
+    let signed_data = signed_data(namespace, "sha512", &message_hash);
+    let signature = keypair.signing_key().sign(&signed_data);
+
+    let mut signature_blob = Vec::new();
+    put_string(&mut signature_blob, ED25519_KEY_TYPE);
+    put_string(&mut signature_blob, &signature.to_bytes());
+
+    let mut blob = MAGIC_PREAMBLE.to_vec();
+    blob.extend_from_slice(&SIG_VERSION.to_be_bytes());
+    put_string(&mut blob, &ed25519_key_blob(&keypair.public_key_bytes()));
+    put_string(&mut blob, namespace.as_bytes());
+    put_string(&mut blob, b"");
+    put_string(&mut blob, b"sha512");
+    put_string(&mut blob, &signature_blob);
+
+    Ok(armor(&blob))
+}
+
+/// Verify an armored SSHSIG signature over a message
+///
+/// Returns the signer's Ed25519 public key on success. The caller must still
+/// check that this key is one it trusts.
+pub fn verify(armored: &str, namespace: &str, message: &[u8]) -> Result<[u8; 32]> {
+    let blob = dearmor(armored)?;
+    let invalid =
+        |what: &str| BipKeychainError::SignatureError(format!("Malformed SSHSIG: {}", what));
+
+    let rest = blob
+        .strip_prefix(MAGIC_PREAMBLE)
+        .ok_or_else(|| invalid("missing SSHSIG preamble"))?;
+    let mut reader = WireReader::new(rest);
+
+    if reader.u32() != Some(SIG_VERSION) {
+        return Err(invalid("unsupported version"));
+    }
+
+    let public_key_blob = reader.string().ok_or_else(|| invalid("public key"))?;
+    let sig_namespace = reader.string().ok_or_else(|| invalid("namespace"))?;
+    let _reserved = reader.string().ok_or_else(|| invalid("reserved field"))?;
+    let hash_algorithm = reader.string().ok_or_else(|| invalid("hash algorithm"))?;
+    let signature_blob = reader.string().ok_or_else(|| invalid("signature"))?;
+
+    if sig_namespace != namespace.as_bytes() {
+        return Err(BipKeychainError::SignatureError(format!(
+            "Signature namespace \"{}\" does not match expected \"{}\"",
+            String::from_utf8_lossy(sig_namespace),
+            namespace
+        )));
+    }
+
+    let public_key =
+        parse_ed25519_key_blob(public_key_blob).ok_or_else(|| invalid("public key"))?;
+
+    let mut sig_reader = WireReader::new(signature_blob);
+    if sig_reader.string() != Some(ED25519_KEY_TYPE) {
+        return Err(invalid("only ssh-ed25519 signatures are supported"));
+    }
+    let signature = sig_reader
+        .string()
+        .and_then(|bytes| Signature::from_slice(bytes).ok())
+        .ok_or_else(|| invalid("signature bytes"))?;
+
+    let signed_data = match hash_algorithm {
+        b"sha512" => signed_data(namespace, "sha512", &Sha512::digest(message)),
+        b"sha256" => signed_data(namespace, "sha256", &Sha256::digest(message)),
+        other => {
+            return Err(invalid(&format!(
+                "unsupported hash algorithm {}",
+                String::from_utf8_lossy(other)
+            )))
+        }
+    };
+
+    let verifying_key = VerifyingKey::from_bytes(&public_key).map_err(|_| invalid("public key"))?;
+    verifying_key
+        .verify(&signed_data, &signature)
+        .map_err(|_| BipKeychainError::SignatureError("Signature does not match".to_string()))?;
+
+    Ok(public_key)
+}
+
+/// Format an `allowed_signers` line for Git/ssh-keygen verification
+///
+/// Produces `<principal> namespaces="git" ssh-ed25519 <base64> [comment]`.
+pub fn allowed_signers_line(principal: &str, ssh_public_key: &str) -> String {
+    format!(
+        "{} namespaces=\"{}\" {}",
+        principal,
+        GIT_NAMESPACE,
+        ssh_public_key.trim()
+    )
+}
+
+/// Data covered by the signature (PROTOCOL.sshsig, "Signed Data")
+fn signed_data(namespace: &str, hash_algorithm: &str, message_hash: &[u8]) -> Vec<u8> {
+    let mut data = MAGIC_PREAMBLE.to_vec();
+    put_string(&mut data, namespace.as_bytes());
+    put_string(&mut data, b"");
+    put_string(&mut data, hash_algorithm.as_bytes());
+    put_string(&mut data, message_hash);
+    data
+}
+
+fn armor(blob: &[u8]) -> String {
+    let encoded = base64::engine::general_purpose::STANDARD.encode(blob);
+
+    let mut armored = String::from(BEGIN_ARMOR);
+    armored.push('\n');
+    for line in encoded.as_bytes().chunks(ARMOR_LINE_WIDTH) {
+        armored.push_str(std::str::from_utf8(line).expect("base64 is ASCII"));
+        armored.push('\n');
+    }
+    armored.push_str(END_ARMOR);
+    armored.push('\n');
+    armored
+}
+
+fn dearmor(armored: &str) -> Result<Vec<u8>> {
+    let body = armored
+        .trim()
+        .strip_prefix(BEGIN_ARMOR)
+        .and_then(|rest| rest.strip_suffix(END_ARMOR))
+        .ok_or_else(|| {
+            BipKeychainError::SignatureError(
+                "Not an SSH signature (missing BEGIN/END SSH SIGNATURE lines)".to_string(),
+            )
+        })?;
+
+    let encoded: String = body.split_whitespace().collect();
+    base64::engine::general_purpose::STANDARD
+        .decode(encoded)
+        .map_err(|e| {
+            BipKeychainError::SignatureError(format!("Invalid base64 in signature: {}", e))
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn test_keypair() -> Ed25519Keypair {
+        Ed25519Keypair::from_seed([42u8; 32])
+    }
+
+    #[test]
+    fn test_sign_verify_roundtrip() {
+        let keypair = test_keypair();
+        let armored = sign(&keypair, GIT_NAMESPACE, b"tree 1234\n").unwrap();
+
+        assert!(armored.starts_with(BEGIN_ARMOR));
+        assert!(armored.trim_end().ends_with(END_ARMOR));
+        assert!(armored.lines().all(|line| line.len() <= ARMOR_LINE_WIDTH));
+
+        let signer = verify(&armored, GIT_NAMESPACE, b"tree 1234\n").unwrap();
+        assert_eq!(signer, keypair.public_key_bytes());
+    }
+
+    #[test]
+    fn test_verify_rejects_tampering_and_wrong_namespace() {
+        let keypair = test_keypair();
+        let armored = sign(&keypair, GIT_NAMESPACE, b"message").unwrap();
+
+        assert!(verify(&armored, GIT_NAMESPACE, b"messagf").is_err());
+        assert!(verify(&armored, "file", b"message").is_err());
+        assert!(verify("not a signature", GIT_NAMESPACE, b"message").is_err());
+    }
+
+    #[test]
+    fn test_allowed_signers_line() {
+        let line = allowed_signers_line("dev@example.com", "ssh-ed25519 AAAA test\n");
+        assert_eq!(
+            line,
+            "dev@example.com namespaces=\"git\" ssh-ed25519 AAAA test"
+        );
+    }
+}
@@ -0,0 +1,710 @@
+//! SSKR (Sharded Secret Key Reconstruction) share handling
+//!
+//! Parses share metadata in the Blockchain Commons SSKR binary format so that
+//! users can check their backups without reconstructing the seed.
+//!
+//! Share layout (5-byte header followed by the share value):
+//! - bytes 0-1: split identifier (big-endian u16)
+//! - byte 2: `group_threshold - 1` (high nibble), `group_count - 1` (low nibble)
+//! - byte 3: `group_index` (high nibble), `member_threshold - 1` (low nibble)
+//! - byte 4: reserved, must be zero (high nibble), `member_index` (low nibble)
+//!
+//! Splitting uses Shamir's Secret Sharing over GF(256) (the AES field) with a
+//! digest share, as specified by Blockchain Commons, so a wrong combination of
+//! shares is detected instead of silently producing a different secret.
+
+use crate::error::{BipKeychainError, Result};
+use std::collections::{BTreeMap, BTreeSet};
+
+/// Maximum number of groups, and of members per group
+pub const MAX_SHARE_COUNT: usize = 16;
+
+/// Shamir x-coordinate holding the checksum digest
+const DIGEST_INDEX: u8 = 254;
+
+/// Shamir x-coordinate holding the secret itself
+const SECRET_INDEX: u8 = 255;
+
+/// Length of the checksum prefix in the digest share
+const DIGEST_LEN: usize = 4;
+
+/// A Shamir point: x-coordinate and the share bytes at that coordinate
+type Point<'a> = (u8, &'a [u8]);
+
+/// Size of the SSKR share header in bytes
+pub const METADATA_SIZE_BYTES: usize = 5;
+
+/// Minimum share value length (128-bit secrets)
+pub const MIN_SECRET_LEN: usize = 16;
+
+/// Maximum share value length (256-bit secrets)
+pub const MAX_SECRET_LEN: usize = 32;
+
+/// Metadata carried by a single SSKR share
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ShareInfo {
+    /// Random identifier shared by every share of one split
+    pub identifier: u16,
+    /// Number of groups required to recover the secret
+    pub group_threshold: u8,
+    /// Total number of groups in the split
+    pub group_count: u8,
+    /// Zero-based index of the group this share belongs to
+    pub group_index: u8,
+    /// Number of members of this group required to recover the group secret
+    pub member_threshold: u8,
+    /// Zero-based index of this share within its group
+    pub member_index: u8,
+    /// Length of the share value (equals the secret length)
+    pub value_len: usize,
+}
+
+/// Result of checking a set of shares against each other
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ShareSetReport {
+    /// Split identifier common to all shares
+    pub identifier: u16,
+    /// Number of groups required to recover the secret
+    pub group_threshold: u8,
+    /// Total number of groups in the split
+    pub group_count: u8,
+    /// Groups whose member threshold is met by the supplied shares
+    pub complete_groups: Vec<u8>,
+    /// Whether enough groups are complete to meet the group threshold
+    pub sufficient: bool,
+}
+
+/// Parse the metadata of a single SSKR share
+///
+/// Does not touch the share value beyond checking its length.
+pub fn inspect_share(share: &[u8]) -> Result<ShareInfo> {
+    if share.len() < METADATA_SIZE_BYTES {
+        return Err(BipKeychainError::InvalidShare(format!(
+            "share is {} bytes, shorter than the {}-byte header",
+            share.len(),
+            METADATA_SIZE_BYTES
+        )));
+    }
+
+    let value_len = share.len() - METADATA_SIZE_BYTES;
+    if !(MIN_SECRET_LEN..=MAX_SECRET_LEN).contains(&value_len) || !value_len.is_multiple_of(2) {
+        return Err(BipKeychainError::InvalidShare(format!(
+            "share value is {} bytes; expected an even length between {} and {}",
+            value_len, MIN_SECRET_LEN, MAX_SECRET_LEN
+        )));
+    }
+
+    let identifier = u16::from_be_bytes([share[0], share[1]]);
+    let group_threshold = (share[2] >> 4) + 1;
+    let group_count = (share[2] & 0x0f) + 1;
+    let group_index = share[3] >> 4;
+    let member_threshold = (share[3] & 0x0f) + 1;
+    let reserved = share[4] >> 4;
+    let member_index = share[4] & 0x0f;
+
+    if reserved != 0 {
+        return Err(BipKeychainError::InvalidShare(
+            "reserved header bits are not zero".to_string(),
+        ));
+    }
+    if group_threshold > group_count {
+        return Err(BipKeychainError::InvalidShare(format!(
+            "group threshold {} exceeds group count {}",
+            group_threshold, group_count
+        )));
+    }
+    if group_index >= group_count {
+        return Err(BipKeychainError::InvalidShare(format!(
+            "group index {} is out of range for {} groups",
+            group_index, group_count
+        )));
+    }
+
+    Ok(ShareInfo {
+        identifier,
+        group_threshold,
+        group_count,
+        group_index,
+        member_threshold,
+        member_index,
+        value_len,
+    })
+}
+
+/// Check that a set of shares belongs to the same split
+///
+/// Verifies that identifiers, group parameters, and value lengths agree, that
+/// each group reports a single member threshold, and that no share appears
+/// twice. The secret is never reconstructed.
+pub fn verify_shares(shares: &[Vec<u8>]) -> Result<ShareSetReport> {
+    let infos = shares
+        .iter()
+        .map(|s| inspect_share(s))
+        .collect::<Result<Vec<_>>>()?;
+
+    let first = infos
+        .first()
+        .ok_or_else(|| BipKeychainError::InvalidShare("no shares provided".to_string()))?;
+
+    let mut groups: BTreeMap<u8, (u8, BTreeSet<u8>)> = BTreeMap::new();
+
+    for (i, info) in infos.iter().enumerate() {
+        if info.identifier != first.identifier {
+            return Err(BipKeychainError::InvalidShare(format!(
+                "share {} has identifier {:04x}, expected {:04x} (shares come from different splits)",
+                i + 1,
+                info.identifier,
+                first.identifier
+            )));
+        }
+        if info.group_threshold != first.group_threshold || info.group_count != first.group_count {
+            return Err(BipKeychainError::InvalidShare(format!(
+                "share {} disagrees on group policy ({} of {} vs {} of {})",
+                i + 1,
+                info.group_threshold,
+                info.group_count,
+                first.group_threshold,
+                first.group_count
+            )));
+        }
+        if info.value_len != first.value_len {
+            return Err(BipKeychainError::InvalidShare(format!(
+                "share {} has a {}-byte value, expected {}",
+                i + 1,
+                info.value_len,
+                first.value_len
+            )));
+        }
+
+        let (threshold, members) = groups
+            .entry(info.group_index)
+            .or_insert_with(|| (info.member_threshold, BTreeSet::new()));
+        if *threshold != info.member_threshold {
+            return Err(BipKeychainError::InvalidShare(format!(
+                "share {} disagrees on the member threshold of group {}",
+                i + 1,
+                info.group_index + 1
+            )));
+        }
+        if !members.insert(info.member_index) {
+            return Err(BipKeychainError::InvalidShare(format!(
+                "share {} duplicates member {} of group {}",
+                i + 1,
+                info.member_index + 1,
+                info.group_index + 1
+            )));
+        }
+    }
+
+    let complete_groups: Vec<u8> = groups
+        .iter()
+        .filter(|(_, (threshold, members))| members.len() >= *threshold as usize)
+        .map(|(index, _)| *index)
+        .collect();
+    let sufficient = complete_groups.len() >= first.group_threshold as usize;
+
+    Ok(ShareSetReport {
+        identifier: first.identifier,
+        group_threshold: first.group_threshold,
+        group_count: first.group_count,
+        complete_groups,
+        sufficient,
+    })
+}
+
+/// Member policy for one SSKR group
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct GroupSpec {
+    /// Number of members required to recover the group secret
+    pub member_threshold: u8,
+    /// Total number of member shares in the group
+    pub member_count: u8,
+}
+
+impl GroupSpec {
+    /// Parse an `MofN` policy string such as `2of3`
+    pub fn parse(spec: &str) -> Result<Self> {
+        let (threshold, count) = spec
+            .trim()
+            .to_ascii_lowercase()
+            .split_once("of")
+            .and_then(|(m, n)| Some((m.parse::<u8>().ok()?, n.parse::<u8>().ok()?)))
+            .ok_or_else(|| {
+                BipKeychainError::InvalidShare(format!(
+                    "invalid policy '{}', expected MofN (e.g. 2of3)",
+                    spec
+                ))
+            })?;
+
+        let group = Self {
+            member_threshold: threshold,
+            member_count: count,
+        };
+        group.validate()?;
+        Ok(group)
+    }
+
+    fn validate(&self) -> Result<()> {
+        if self.member_count == 0 || self.member_count as usize > MAX_SHARE_COUNT {
+            return Err(BipKeychainError::InvalidShare(format!(
+                "member count must be between 1 and {}",
+                MAX_SHARE_COUNT
+            )));
+        }
+        if self.member_threshold == 0 || self.member_threshold > self.member_count {
+            return Err(BipKeychainError::InvalidShare(format!(
+                "member threshold {} must be between 1 and {}",
+                self.member_threshold, self.member_count
+            )));
+        }
+        Ok(())
+    }
+}
+
+/// Split a secret into SSKR shares
+///
+/// Returns one `Vec` of encoded shares per group, in group order. The secret
+/// must be 16-32 bytes with an even length (e.g. BIP-39 entropy).
+pub fn generate_shares(
+    secret: &[u8],
+    group_threshold: u8,
+    groups: &[GroupSpec],
+) -> Result<Vec<Vec<Vec<u8>>>> {
+    generate_shares_with(secret, group_threshold, groups, &mut random_bytes)
+}
+
+/// [`generate_shares`] drawing its identifier and polynomial coefficients from `random`
+fn generate_shares_with(
+    secret: &[u8],
+    group_threshold: u8,
+    groups: &[GroupSpec],
+    random: &mut dyn FnMut(usize) -> Result<Vec<u8>>,
+) -> Result<Vec<Vec<Vec<u8>>>> {
+    if !(MIN_SECRET_LEN..=MAX_SECRET_LEN).contains(&secret.len()) || !secret.len().is_multiple_of(2)
+    {
+        return Err(BipKeychainError::InvalidShare(format!(
+            "secret is {} bytes; expected an even length between {} and {}",
+            secret.len(),
+            MIN_SECRET_LEN,
+            MAX_SECRET_LEN
+        )));
+    }
+    if groups.is_empty() || groups.len() > MAX_SHARE_COUNT {
+        return Err(BipKeychainError::InvalidShare(format!(
+            "group count must be between 1 and {}",
+            MAX_SHARE_COUNT
+        )));
+    }
+    if group_threshold == 0 || group_threshold as usize > groups.len() {
+        return Err(BipKeychainError::InvalidShare(format!(
+            "group threshold {} must be between 1 and {}",
+            group_threshold,
+            groups.len()
+        )));
+    }
+    for group in groups {
+        group.validate()?;
+    }
+
+    let id_bytes = random(2)?;
+    let identifier = u16::from_be_bytes([id_bytes[0], id_bytes[1]]);
+
+    let group_secrets = split_secret(group_threshold, groups.len() as u8, secret, random)?;
+
+    let mut result = Vec::with_capacity(groups.len());
+    for (group_index, (group, group_secret)) in groups.iter().zip(&group_secrets).enumerate() {
+        let member_secrets = split_secret(
+            group.member_threshold,
+            group.member_count,
+            group_secret,
+            random,
+        )?;
+
+        let shares = member_secrets
+            .into_iter()
+            .enumerate()
+            .map(|(member_index, value)| {
+                let mut share = Vec::with_capacity(METADATA_SIZE_BYTES + value.len());
+                share.extend_from_slice(&identifier.to_be_bytes());
+                share.push(((group_threshold - 1) << 4) | (groups.len() as u8 - 1));
+                share.push(((group_index as u8) << 4) | (group.member_threshold - 1));
+                share.push(member_index as u8);
+                share.extend_from_slice(&value);
+                share
+            })
+            .collect();
+        result.push(shares);
+    }
+
+    Ok(result)
+}
+
+/// Recover the secret from a sufficient set of SSKR shares
+///
+/// The shares are checked with [`verify_shares`] first, and the recovered
+/// secret is validated against the embedded digest.
+pub fn combine_shares(shares: &[Vec<u8>]) -> Result<Vec<u8>> {
+    let report = verify_shares(shares)?;
+    if !report.sufficient {
+        return Err(BipKeychainError::InvalidShare(format!(
+            "not enough shares: {} of {} required groups complete",
+            report.complete_groups.len(),
+            report.group_threshold
+        )));
+    }
+
+    let mut by_group: BTreeMap<u8, (u8, Vec<Point>)> = BTreeMap::new();
+    for share in shares {
+        let info = inspect_share(share)?;
+        by_group
+            .entry(info.group_index)
+            .or_insert_with(|| (info.member_threshold, Vec::new()))
+            .1
+            .push((info.member_index, &share[METADATA_SIZE_BYTES..]));
+    }
+
+    let mut group_secrets = Vec::new();
+    for (group_index, (member_threshold, members)) in by_group {
+        if members.len() < member_threshold as usize {
+            continue;
+        }
+        let secret = recover_secret(member_threshold, &members[..member_threshold as usize])?;
+        group_secrets.push((group_index, secret));
+        if group_secrets.len() == report.group_threshold as usize {
+            break;
+        }
+    }
+
+    let points: Vec<Point> = group_secrets
+        .iter()
+        .map(|(x, y)| (*x, y.as_slice()))
+        .collect();
+    recover_secret(report.group_threshold, &points)
+}
+
+/// Shamir split of `secret` into `count` shares, any `threshold` of which recover it
+fn split_secret(
+    threshold: u8,
+    count: u8,
+    secret: &[u8],
+    random: &mut dyn FnMut(usize) -> Result<Vec<u8>>,
+) -> Result<Vec<Vec<u8>>> {
+    if threshold == 1 {
+        return Ok(vec![secret.to_vec(); count as usize]);
+    }
+
+    let random_count = threshold - 2;
+    let mut points: Vec<(u8, Vec<u8>)> = Vec::with_capacity(threshold as usize);
+    let mut shares = Vec::with_capacity(count as usize);
+
+    for x in 0..random_count {
+        let y = random(secret.len())?;
+        points.push((x, y.clone()));
+        shares.push(y);
+    }
+
+    let random_part = random(secret.len() - DIGEST_LEN)?;
+    let mut digest_share = create_digest(&random_part, secret).to_vec();
+    digest_share.extend_from_slice(&random_part);
+    points.push((DIGEST_INDEX, digest_share));
+    points.push((SECRET_INDEX, secret.to_vec()));
+
+    let points: Vec<Point> = points.iter().map(|(x, y)| (*x, y.as_slice())).collect();
+    for x in random_count..count {
+        shares.push(interpolate(&points, x));
+    }
+
+    Ok(shares)
+}
+
+/// Inverse of [`split_secret`], checking the digest share
+fn recover_secret(threshold: u8, points: &[Point]) -> Result<Vec<u8>> {
+    if points.len() < threshold as usize || points.is_empty() {
+        return Err(BipKeychainError::InvalidShare(
+            "not enough shares to recover secret".to_string(),
+        ));
+    }
+    if threshold == 1 {
+        return Ok(points[0].1.to_vec());
+    }
+
+    let secret = interpolate(points, SECRET_INDEX);
+    let digest_share = interpolate(points, DIGEST_INDEX);
+    let expected = create_digest(&digest_share[DIGEST_LEN..], &secret);
+
+    if digest_share[..DIGEST_LEN] != expected {
+        return Err(BipKeychainError::InvalidShare(
+            "share checksum mismatch; shares are corrupted or do not belong together".to_string(),
+        ));
+    }
+
+    Ok(secret)
+}
+
+/// First 4 bytes of HMAC-SHA-256(key = random data, message = secret)
+fn create_digest(random_data: &[u8], secret: &[u8]) -> [u8; DIGEST_LEN] {
+    use hmac::{Hmac, Mac};
+    use sha2::Sha256;
+
+    let mut mac =
+        Hmac::<Sha256>::new_from_slice(random_data).expect("HMAC accepts keys of any length");
+    mac.update(secret);
+    let bytes = mac.finalize().into_bytes();
+
+    let mut digest = [0u8; DIGEST_LEN];
+    digest.copy_from_slice(&bytes[..DIGEST_LEN]);
+    digest
+}
+
+/// Lagrange interpolation of the points at `x`, bytewise over GF(256)
+fn interpolate(points: &[Point], x: u8) -> Vec<u8> {
+    let len = points[0].1.len();
+    let mut result = vec![0u8; len];
+
+    for (i, (xi, yi)) in points.iter().enumerate() {
+        // Lagrange basis polynomial l_i(x); subtraction is XOR in GF(2^8)
+        let mut basis = 1u8;
+        for (j, (xj, _)) in points.iter().enumerate() {
+            if i != j {
+                basis = gf_mul(basis, gf_mul(x ^ xj, gf_inv(xi ^ xj)));
+            }
+        }
+        for (out, y) in result.iter_mut().zip(yi.iter()) {
+            *out ^= gf_mul(*y, basis);
+        }
+    }
+
+    result
+}
+
+/// Multiplication in GF(2^8) modulo the AES polynomial x^8 + x^4 + x^3 + x + 1
+fn gf_mul(mut a: u8, mut b: u8) -> u8 {
+    let mut product = 0u8;
+    while b != 0 {
+        if b & 1 != 0 {
+            product ^= a;
+        }
+        let carry = a & 0x80;
+        a <<= 1;
+        if carry != 0 {
+            a ^= 0x1b;
+        }
+        b >>= 1;
+    }
+    product
+}
+
+/// Multiplicative inverse in GF(2^8) (a^254)
+fn gf_inv(a: u8) -> u8 {
+    let mut result = 1u8;
+    let mut base = a;
+    let mut exp = 254u8;
+    while exp != 0 {
+        if exp & 1 != 0 {
+            result = gf_mul(result, base);
+        }
+        base = gf_mul(base, base);
+        exp >>= 1;
+    }
+    result
+}
+
+fn random_bytes(len: usize) -> Result<Vec<u8>> {
+    let mut bytes = vec![0u8; len];
+    getrandom::getrandom(&mut bytes)
+        .map_err(|e| BipKeychainError::IoError(std::io::Error::other(e.to_string())))?;
+    Ok(bytes)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn share(id: u16, gt: u8, gc: u8, gi: u8, mt: u8, mi: u8) -> Vec<u8> {
+        let mut s = id.to_be_bytes().to_vec();
+        s.push(((gt - 1) << 4) | (gc - 1));
+        s.push((gi << 4) | (mt - 1));
+        s.push(mi);
+        s.extend_from_slice(&[0xab; 16]);
+        s
+    }
+
+    #[test]
+    fn test_inspect_share() {
+        let info = inspect_share(&share(0x1234, 1, 1, 0, 2, 1)).unwrap();
+        assert_eq!(info.identifier, 0x1234);
+        assert_eq!(info.group_threshold, 1);
+        assert_eq!(info.group_count, 1);
+        assert_eq!(info.group_index, 0);
+        assert_eq!(info.member_threshold, 2);
+        assert_eq!(info.member_index, 1);
+        assert_eq!(info.value_len, 16);
+    }
+
+    #[test]
+    fn test_inspect_rejects_bad_length() {
+        assert!(inspect_share(&[0u8; 4]).is_err());
+        assert!(inspect_share(&[0u8; 5 + 17]).is_err());
+    }
+
+    #[test]
+    fn test_verify_sufficient_set() {
+        let shares = vec![share(7, 1, 1, 0, 2, 0), share(7, 1, 1, 0, 2, 2)];
+        let report = verify_shares(&shares).unwrap();
+        assert_eq!(report.complete_groups, vec![0]);
+        assert!(report.sufficient);
+    }
+
+    #[test]
+    fn test_verify_insufficient_set() {
+        let shares = vec![share(7, 1, 1, 0, 2, 0)];
+        let report = verify_shares(&shares).unwrap();
+        assert!(report.complete_groups.is_empty());
+        assert!(!report.sufficient);
+    }
+
+    #[test]
+    fn test_verify_rejects_mixed_splits() {
+        let shares = vec![share(7, 1, 1, 0, 2, 0), share(8, 1, 1, 0, 2, 1)];
+        assert!(verify_shares(&shares).is_err());
+    }
+
+    #[test]
+    fn test_verify_rejects_duplicates() {
+        let shares = vec![share(7, 1, 1, 0, 2, 1), share(7, 1, 1, 0, 2, 1)];
+        assert!(verify_shares(&shares).is_err());
+    }
+
+    #[test]
+    fn test_gf_inverse() {
+        for a in 1..=255u8 {
+            assert_eq!(gf_mul(a, gf_inv(a)), 1);
+        }
+    }
+
+    #[test]
+    fn test_parse_group_spec() {
+        let spec = GroupSpec::parse("2of3").unwrap();
+        assert_eq!(spec.member_threshold, 2);
+        assert_eq!(spec.member_count, 3);
+
+        assert!(GroupSpec::parse("3of2").is_err());
+        assert!(GroupSpec::parse("two of three").is_err());
+    }
+
+    #[test]
+    fn test_split_and_combine_single_group() {
+        let secret = [0x42u8; 16];
+        let groups = generate_shares(&secret, 1, &[GroupSpec::parse("2of3").unwrap()]).unwrap();
+        let shares = &groups[0];
+        assert_eq!(shares.len(), 3);
+
+        for pair in [[0, 1], [0, 2], [1, 2]] {
+            let subset = vec![shares[pair[0]].clone(), shares[pair[1]].clone()];
+            assert_eq!(combine_shares(&subset).unwrap(), secret);
+        }
+
+        assert!(combine_shares(&shares[..1]).is_err());
+    }
+
+    #[test]
+    fn test_split_and_combine_multiple_groups() {
+        let secret: Vec<u8> = (0..32).collect();
+        let groups = [
+            GroupSpec::parse("2of3").unwrap(),
+            GroupSpec::parse("1of1").unwrap(),
+            GroupSpec::parse("3of5").unwrap(),
+        ];
+        let shares = generate_shares(&secret, 2, &groups).unwrap();
+
+        let subset = vec![
+            shares[2][4].clone(),
+            shares[0][1].clone(),
+            shares[2][0].clone(),
+            shares[0][2].clone(),
+            shares[2][3].clone(),
+        ];
+        assert_eq!(combine_shares(&subset).unwrap(), secret);
+    }
+
+    /// The byte pattern `FakeRandomNumberGenerator` in the Blockchain Commons
+    /// `sskr` crate's tests returns for every request: 0x00, 0x11, 0x22, ...
+    fn reference_random(len: usize) -> Result<Vec<u8>> {
+        Ok((0..len).map(|i| (i as u8).wrapping_mul(17)).collect())
+    }
+
+    fn decode(shares: &[&str]) -> Vec<Vec<u8>> {
+        shares.iter().map(|s| hex::decode(s).unwrap()).collect()
+    }
+
+    // Expected shares are the output of `sskr_generate_using` from the
+    // Blockchain Commons `sskr` crate 0.8.0 with that generator.
+    #[test]
+    fn test_reference_vector_single_group() {
+        let secret = hex::decode("0ff784df000c4380a5ed683f7e6e3dcf").unwrap();
+        let expected = decode(&[
+            "001100020000112233445566778899aabbccddeeff",
+            "0011000201d43099fe444807c46921a4f33a2a798b",
+            "0011000202d9ad4e3bec2e1a7485698823abf05d36",
+            "00110002030d8cf5f6ec337bc764d1866b5d07ca42",
+            "00110002041aa7fe3199bc5092ef3816b074cabdf2",
+        ]);
+
+        let groups = [GroupSpec::parse("3of5").unwrap()];
+        let shares = generate_shares_with(&secret, 1, &groups, &mut reference_random).unwrap();
+        assert_eq!(shares, vec![expected.clone()]);
+
+        assert_eq!(combine_shares(&expected).unwrap(), secret);
+        let subset = vec![
+            expected[4].clone(),
+            expected[1].clone(),
+            expected[2].clone(),
+        ];
+        assert_eq!(combine_shares(&subset).unwrap(), secret);
+    }
+
+    #[test]
+    fn test_reference_vector_two_groups() {
+        let secret =
+            hex::decode("204188bfa6b440a1bdfd6753ff55a8241e07af5c5be943db917e3efabc184b1a")
+                .unwrap();
+        let expected = [
+            decode(&[
+                "0011110100ce5cce1ad9fe9cefa4707449576e8eadfc7d107c5a9e812b21f80aeca635cacd",
+                "001111010184190ee2fcc276947ad68a6eef10694c784811720d350b061029440281a5a550",
+                "00111101025ad655f193865319032793073c925b74ef171260f4d38e714341962be80e14ec",
+            ]),
+            decode(&[
+                "00111111004d741d38fcc276947ad68a6eef10694c784811720d350b061029440281a5a550",
+                "0011111101b39fde657f5bfe7d5dd8756d20a28c322ea751df1156f0b3e4e3429182843b1c",
+                "0011111102aab98082e1eb7d5d34ca6f686a6fb8b0d48d913335f3e677e3a6483f87e782c8",
+            ]),
+        ];
+
+        let groups = [
+            GroupSpec::parse("2of3").unwrap(),
+            GroupSpec::parse("2of3").unwrap(),
+        ];
+        let shares = generate_shares_with(&secret, 2, &groups, &mut reference_random).unwrap();
+        assert_eq!(shares, expected.to_vec());
+
+        let subset = vec![
+            expected[1][2].clone(),
+            expected[0][0].clone(),
+            expected[1][0].clone(),
+            expected[0][2].clone(),
+        ];
+        assert_eq!(combine_shares(&subset).unwrap(), secret);
+        assert!(combine_shares(&expected[0]).is_err());
+    }
+
+    #[test]
+    fn test_combine_detects_tampering() {
+        let secret = [7u8; 16];
+        let groups = generate_shares(&secret, 1, &[GroupSpec::parse("2of2").unwrap()]).unwrap();
+        let mut shares = groups[0].clone();
+        shares[1][METADATA_SIZE_BYTES] ^= 0x01;
+
+        assert!(combine_shares(&shares).is_err());
+    }
+}
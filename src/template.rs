@@ -0,0 +1,131 @@
+//! Built-in entity templates
+//!
+//! Starting points for common keys, so users don't have to write entity
+//! JSON by hand. Each template fills in placeholder values that are meant
+//! to be replaced, either by editing the output or with
+//! [`Template::key_derivation`] overrides (`bip-keychain template new
+//! <kind> --set key=value`).
+
+use crate::{builder::KeyDerivationBuilder, entity::KeyDerivation, error::Result};
+use serde_json::{json, Value};
+
+/// A named entity template
+pub struct Template {
+    /// Name used on the command line, e.g. `git-signing`
+    pub kind: &'static str,
+    /// One-line description for `template list`
+    pub description: &'static str,
+    builder: fn() -> KeyDerivationBuilder,
+}
+
+/// All built-in templates
+pub const TEMPLATES: &[Template] = &[
+    Template {
+        kind: "git-signing",
+        description: "Git commit signing key for a repository",
+        builder: git_signing,
+    },
+    Template {
+        kind: "ssh-host",
+        description: "SSH key for a server, identified by hostname",
+        builder: ssh_host,
+    },
+    Template {
+        kind: "website",
+        description: "Login credential for a website",
+        builder: website,
+    },
+    Template {
+        kind: "service-account",
+        description: "Key for an automated service account",
+        builder: service_account,
+    },
+];
+
+fn git_signing() -> KeyDerivationBuilder {
+    KeyDerivationBuilder::new()
+        .schema_org()
+        .type_("SoftwareSourceCode")
+        .name("REPO")
+        .property("codeRepository", "https://github.com/OWNER/REPO")
+        .purpose("Git commit signing key")
+}
+
+fn ssh_host() -> KeyDerivationBuilder {
+    KeyDerivationBuilder::new()
+        .custom("dns")
+        .property("fqdn", "host.example.com")
+        .property("zone", "example.com")
+        .purpose("SSH access key for host.example.com")
+}
+
+fn website() -> KeyDerivationBuilder {
+    KeyDerivationBuilder::new()
+        .schema_org()
+        .type_("WebSite")
+        .name("Example")
+        .property("url", "https://example.com")
+        .purpose("Login credential for example.com")
+}
+
+fn service_account() -> KeyDerivationBuilder {
+    KeyDerivationBuilder::new()
+        .schema_org()
+        .type_("Service")
+        .name("SERVICE")
+        .property(
+            "provider",
+            json!({"@type": "Organization", "name": "Example Org"}),
+        )
+        .purpose("Service account key for SERVICE")
+}
+
+/// Look up a template by kind
+pub fn find(kind: &str) -> Option<&'static Template> {
+    TEMPLATES.iter().find(|template| template.kind == kind)
+}
+
+impl Template {
+    /// Build the template's entity, with `overrides` replacing entity properties
+    pub fn key_derivation(&self, overrides: &[(String, Value)]) -> Result<KeyDerivation> {
+        overrides
+            .iter()
+            .fold((self.builder)(), |builder, (key, value)| {
+                builder.property(key.as_str(), value.clone())
+            })
+            .build()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_templates_produce_valid_entities() {
+        for template in TEMPLATES {
+            let key_derivation = template.key_derivation(&[]).unwrap();
+            let json = serde_json::to_string_pretty(&key_derivation).unwrap();
+
+            // The emitted JSON parses back to the same entity
+            let parsed = KeyDerivation::from_json(&json).unwrap();
+            assert_eq!(parsed.entity, key_derivation.entity, "{}", template.kind);
+        }
+    }
+
+    #[test]
+    fn test_overrides_replace_properties() {
+        let key_derivation = find("ssh-host")
+            .unwrap()
+            .key_derivation(&[("fqdn".to_string(), json!("db1.internal"))])
+            .unwrap();
+        assert_eq!(key_derivation.entity.to_value()["fqdn"], "db1.internal");
+
+        // Overrides are validated like any other entity
+        let invalid = find("website")
+            .unwrap()
+            .key_derivation(&[("@type".to_string(), json!(42))]);
+        assert!(invalid.is_err());
+        assert!(find("pgp").is_none());
+    }
+}
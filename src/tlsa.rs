@@ -0,0 +1,212 @@
+//! TLSA records for DANE (RFC 6698, RFC 7671)
+//!
+//! Pins a derived key in DNS so TLS clients that validate with DANE accept
+//! only that key for a service. bip-keychain does not issue X.509
+//! certificates, so records pin the key's SubjectPublicKeyInfo (selector
+//! `spki`); this matches any certificate later issued for the key.
+//!
+//! Entities choose their settings with an optional `tlsa` object:
+//!
+//! ```json
+//! "tlsa": {"port": 443, "protocol": "tcp", "usage": "dane-ee", "selector": "spki", "matching_type": "sha256"}
+//! ```
+
+use crate::error::{BipKeychainError, Result};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256, Sha512};
+use std::str::FromStr;
+
+/// Certificate usage field: which certificate in the chain is matched
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum TlsaUsage {
+    /// 0: CA constraint, with PKIX validation
+    PkixTa,
+    /// 1: service certificate constraint, with PKIX validation
+    PkixEe,
+    /// 2: trust anchor assertion
+    DaneTa,
+    /// 3: domain-issued certificate (the usual choice for pinning a key)
+    #[default]
+    DaneEe,
+}
+
+/// Selector field: which part of the certificate is matched
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TlsaSelector {
+    /// 0: the full certificate (not supported; no certificate is derived)
+    Cert,
+    /// 1: the SubjectPublicKeyInfo
+    #[default]
+    Spki,
+}
+
+/// Matching type field: how the selected data is compared
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TlsaMatchingType {
+    /// 0: exact match on the selected data
+    Full,
+    /// 1: SHA-256 hash of the selected data
+    #[default]
+    Sha256,
+    /// 2: SHA-512 hash of the selected data
+    Sha512,
+}
+
+impl FromStr for TlsaSelector {
+    type Err = String;
+
+    /// Parse `cert`/`spki` or the field value `0`/`1`
+    fn from_str(s: &str) -> std::result::Result<Self, String> {
+        match s {
+            "cert" | "0" => Ok(Self::Cert),
+            "spki" | "1" => Ok(Self::Spki),
+            _ => Err(format!(
+                "unknown TLSA selector '{}' (expected spki/1 or cert/0)",
+                s
+            )),
+        }
+    }
+}
+
+impl FromStr for TlsaMatchingType {
+    type Err = String;
+
+    /// Parse `full`/`sha256`/`sha512` or the field value `0`/`1`/`2`
+    fn from_str(s: &str) -> std::result::Result<Self, String> {
+        match s {
+            "full" | "0" => Ok(Self::Full),
+            "sha256" | "1" => Ok(Self::Sha256),
+            "sha512" | "2" => Ok(Self::Sha512),
+            _ => Err(format!(
+                "unknown TLSA matching type '{}' (expected full/0, sha256/1, or sha512/2)",
+                s
+            )),
+        }
+    }
+}
+
+/// Service and record fields for a TLSA record
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct TlsaConfig {
+    /// Service host name (default: the entity's host name)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub host: Option<String>,
+    /// Service port
+    pub port: u16,
+    /// Transport protocol: `tcp`, `udp`, or `sctp`
+    pub protocol: String,
+    /// Certificate usage
+    pub usage: TlsaUsage,
+    /// Part of the certificate to match
+    pub selector: TlsaSelector,
+    /// How to compare it
+    pub matching_type: TlsaMatchingType,
+}
+
+impl Default for TlsaConfig {
+    fn default() -> Self {
+        Self {
+            host: None,
+            port: 443,
+            protocol: "tcp".to_string(),
+            usage: TlsaUsage::default(),
+            selector: TlsaSelector::default(),
+            matching_type: TlsaMatchingType::default(),
+        }
+    }
+}
+
+fn tlsa_error(message: String) -> BipKeychainError {
+    BipKeychainError::OutputError(message)
+}
+
+/// Format a TLSA record pinning a DER-encoded SubjectPublicKeyInfo
+///
+/// `host` is an absolute name with trailing dot; without one the owner is
+/// relative to the zone origin (`_443._tcp`).
+pub fn tlsa_record(host: Option<&str>, spki_der: &[u8], config: &TlsaConfig) -> Result<String> {
+    if config.selector == TlsaSelector::Cert {
+        return Err(tlsa_error(
+            "TLSA selector cert needs an X.509 certificate, which bip-keychain does not issue; \
+             use selector spki to pin the key"
+                .to_string(),
+        ));
+    }
+    if !matches!(config.protocol.as_str(), "tcp" | "udp" | "sctp") {
+        return Err(tlsa_error(format!(
+            "TLSA protocol must be tcp, udp, or sctp, got '{}'",
+            config.protocol
+        )));
+    }
+
+    let data = match config.matching_type {
+        TlsaMatchingType::Full => hex::encode(spki_der),
+        TlsaMatchingType::Sha256 => hex::encode(Sha256::digest(spki_der)),
+        TlsaMatchingType::Sha512 => hex::encode(Sha512::digest(spki_der)),
+    };
+    let mut owner = format!("_{}._{}", config.port, config.protocol);
+    if let Some(host) = host {
+        owner = format!("{}.{}", owner, host);
+    }
+    Ok(format!(
+        "{} IN TLSA {} {} {} {}",
+        owner, config.usage as u8, config.selector as u8, config.matching_type as u8, data
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // SubjectPublicKeyInfo of the Ed25519 key in tests/fixtures/openssl-ed25519.pub.pem
+    const SPKI: &str =
+        "302a300506032b65700321000d88e460a7599eeb7303881023987d1f3f23f054f0cdb32592170e368dc26037";
+
+    #[test]
+    fn test_tlsa_record() {
+        let spki = hex::decode(SPKI).unwrap();
+        let config = TlsaConfig::default();
+
+        // openssl pkey -pubin -in openssl-ed25519.pub.pem -outform DER | sha256sum
+        assert_eq!(
+            tlsa_record(Some("www.example.com."), &spki, &config).unwrap(),
+            "_443._tcp.www.example.com. IN TLSA 3 1 1 \
+             84d5e19ebbdbf2ca1ccb39bb15ec1bc25680012f042cfee2c48e94651f8315d3"
+        );
+
+        let full = TlsaConfig {
+            port: 25,
+            usage: TlsaUsage::PkixEe,
+            matching_type: TlsaMatchingType::Full,
+            ..TlsaConfig::default()
+        };
+        assert_eq!(
+            tlsa_record(None, &spki, &full).unwrap(),
+            format!("_25._tcp IN TLSA 1 1 0 {}", SPKI)
+        );
+    }
+
+    #[test]
+    fn test_rejects_unsupported_fields() {
+        let spki = hex::decode(SPKI).unwrap();
+        let cert = TlsaConfig {
+            selector: TlsaSelector::Cert,
+            ..TlsaConfig::default()
+        };
+        assert!(tlsa_record(None, &spki, &cert).is_err());
+
+        let config: TlsaConfig =
+            serde_json::from_str(r#"{"protocol": "quic", "matching_type": "sha512"}"#).unwrap();
+        assert!(tlsa_record(None, &spki, &config).is_err());
+
+        assert_eq!(
+            "2".parse::<TlsaMatchingType>(),
+            Ok(TlsaMatchingType::Sha512)
+        );
+        assert!("sha1".parse::<TlsaMatchingType>().is_err());
+    }
+}
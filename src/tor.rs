@@ -0,0 +1,103 @@
+//! Tor onion service keys
+//!
+//! A v3 onion service's address is its Ed25519 public key, so deriving the
+//! key from an entity lets the service keep its `.onion` address after
+//! restoring from the seed. Tor reads the key from the files it keeps in the
+//! `HiddenServiceDir`:
+//!
+//! - `hs_ed25519_secret_key`: a 32-byte header followed by the 64-byte
+//!   expanded secret key (clamped scalar and hash prefix)
+//! - `hs_ed25519_public_key`: a 32-byte header followed by the public key
+//! - `hostname`: the onion address (rend-spec-v3, section 6)
+
+use crate::output::Ed25519Keypair;
+use crate::totp::base32;
+use sha2::{Digest, Sha512};
+use sha3::Sha3_256;
+use zeroize::Zeroizing;
+
+/// Header of `hs_ed25519_secret_key`
+const SECRET_KEY_HEADER: &[u8; 32] = b"== ed25519v1-secret: type0 ==\0\0\0";
+
+/// Header of `hs_ed25519_public_key`
+const PUBLIC_KEY_HEADER: &[u8; 32] = b"== ed25519v1-public: type0 ==\0\0\0";
+
+/// Onion address version
+const VERSION: u8 = 0x03;
+
+/// A v3 onion service identity
+pub struct OnionService<'a> {
+    keypair: &'a Ed25519Keypair,
+}
+
+impl<'a> OnionService<'a> {
+    /// Use `keypair` as the service's identity key
+    pub fn new(keypair: &'a Ed25519Keypair) -> Self {
+        Self { keypair }
+    }
+
+    /// Onion address, e.g. `<56 characters>.onion`
+    pub fn hostname(&self) -> String {
+        let public_key = self.keypair.public_key_bytes();
+
+        let mut checksum_input = b".onion checksum".to_vec();
+        checksum_input.extend_from_slice(&public_key);
+        checksum_input.push(VERSION);
+        let checksum = Sha3_256::digest(&checksum_input);
+
+        let mut address = public_key.to_vec();
+        address.extend_from_slice(&checksum[..2]);
+        address.push(VERSION);
+        format!("{}.onion", base32(&address).to_lowercase())
+    }
+
+    /// Contents of `hs_ed25519_secret_key`
+    pub fn secret_key_file(&self) -> Zeroizing<Vec<u8>> {
+        let mut expanded = Zeroizing::new([0u8; 64]);
+        expanded.copy_from_slice(&Sha512::digest(*self.keypair.private_key_bytes()));
+        expanded[0] &= 248;
+        expanded[31] &= 127;
+        expanded[31] |= 64;
+
+        let mut file = Zeroizing::new(Vec::with_capacity(96));
+        file.extend_from_slice(SECRET_KEY_HEADER);
+        file.extend_from_slice(&*expanded);
+        file
+    }
+
+    /// Contents of `hs_ed25519_public_key`
+    pub fn public_key_file(&self) -> Vec<u8> {
+        let mut file = PUBLIC_KEY_HEADER.to_vec();
+        file.extend_from_slice(&self.keypair.public_key_bytes());
+        file
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_onion_service() {
+        let keypair = Ed25519Keypair::from_seed([1u8; 32]);
+        let service = OnionService::new(&keypair);
+
+        assert_eq!(
+            service.hostname(),
+            "rkeohxlubhyzl7ks3mwtzos5olfgocn7dwkbeg7toseadnapn5optfad.onion"
+        );
+
+        let secret = service.secret_key_file();
+        assert_eq!(secret.len(), 96);
+        assert_eq!(&secret[..32], SECRET_KEY_HEADER);
+        // The clamped scalar is the X25519 private key of the same seed
+        assert_eq!(
+            hex::encode(&secret[32..64]),
+            "58e86efb75fa4e2c410f46e16de9f6acae1a1703528651b69bc176c088bef36e"
+        );
+
+        let public = service.public_key_file();
+        assert_eq!(&public[..32], PUBLIC_KEY_HEADER);
+        assert_eq!(public[32..], keypair.public_key_bytes());
+    }
+}
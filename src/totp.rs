@@ -0,0 +1,311 @@
+//! Deterministic TOTP secrets from derived keys
+//!
+//! Maps an entity's BIP-85 entropy to an RFC 6238 shared secret, so a 2FA
+//! enrollment can be recreated from the master seed instead of being
+//! backed up separately. The secret is exported as an `otpauth://` URI
+//! (the Key Uri Format understood by authenticator apps) and codes can be
+//! computed locally.
+//!
+//! Entities choose their settings with an optional `totp` object:
+//!
+//! ```json
+//! "totp": {"issuer": "GitHub", "account": "alice", "digits": 6, "period": 30}
+//! ```
+
+use crate::error::{BipKeychainError, Result};
+use hmac::{Hmac, Mac};
+use serde::{Deserialize, Serialize};
+use zeroize::Zeroizing;
+
+/// Context string for the BLAKE3 secret derivation
+const TOTP_CONTEXT: &str = "bip-keychain 2025 totp secret";
+
+/// RFC 4648 base32 alphabet
+const BASE32_ALPHABET: &[u8; 32] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZ234567";
+
+/// HMAC algorithm for code generation
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TotpAlgorithm {
+    /// HMAC-SHA1, the only algorithm every authenticator app supports
+    #[default]
+    Sha1,
+    /// HMAC-SHA256
+    Sha256,
+    /// HMAC-SHA512
+    Sha512,
+}
+
+impl TotpAlgorithm {
+    /// Name used in `otpauth://` URIs
+    pub fn uri_name(self) -> &'static str {
+        match self {
+            TotpAlgorithm::Sha1 => "SHA1",
+            TotpAlgorithm::Sha256 => "SHA256",
+            TotpAlgorithm::Sha512 => "SHA512",
+        }
+    }
+
+    /// Secret length RFC 6238 recommends (the HMAC output size)
+    pub fn secret_len(self) -> usize {
+        match self {
+            TotpAlgorithm::Sha1 => 20,
+            TotpAlgorithm::Sha256 => 32,
+            TotpAlgorithm::Sha512 => 64,
+        }
+    }
+}
+
+/// Code parameters and URI labels for a TOTP enrollment
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct TotpConfig {
+    /// Service name shown by authenticator apps (default `bip-keychain`)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub issuer: Option<String>,
+    /// Account name (default: the entity's purpose)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub account: Option<String>,
+    /// Code length, 6 to 8
+    pub digits: u32,
+    /// Time step in seconds
+    pub period: u64,
+    /// HMAC algorithm
+    pub algorithm: TotpAlgorithm,
+}
+
+impl Default for TotpConfig {
+    fn default() -> Self {
+        Self {
+            issuer: None,
+            account: None,
+            digits: 6,
+            period: 30,
+            algorithm: TotpAlgorithm::default(),
+        }
+    }
+}
+
+impl TotpConfig {
+    fn validate(&self) -> Result<()> {
+        if !(6..=8).contains(&self.digits) {
+            return Err(totp_error(format!(
+                "TOTP digits must be 6 to 8, got {}",
+                self.digits
+            )));
+        }
+        if self.period == 0 {
+            return Err(totp_error(
+                "TOTP period must be at least 1 second".to_string(),
+            ));
+        }
+        Ok(())
+    }
+}
+
+fn totp_error(message: String) -> BipKeychainError {
+    BipKeychainError::OutputError(message)
+}
+
+/// A TOTP shared secret with its parameters
+pub struct Totp {
+    secret: Zeroizing<Vec<u8>>,
+    config: TotpConfig,
+}
+
+impl Totp {
+    /// Derive the shared secret from 64 bytes of BIP-85 entropy
+    pub fn derive(entropy: &[u8; 64], config: &TotpConfig) -> Result<Self> {
+        config.validate()?;
+        let mut secret = Zeroizing::new(vec![0u8; config.algorithm.secret_len()]);
+        blake3::Hasher::new_derive_key(TOTP_CONTEXT)
+            .update(entropy)
+            .finalize_xof()
+            .fill(&mut secret);
+        Ok(Self {
+            secret,
+            config: config.clone(),
+        })
+    }
+
+    /// Use an existing shared secret
+    pub fn from_secret(secret: &[u8], config: &TotpConfig) -> Result<Self> {
+        config.validate()?;
+        Ok(Self {
+            secret: Zeroizing::new(secret.to_vec()),
+            config: config.clone(),
+        })
+    }
+
+    /// Code parameters
+    pub fn config(&self) -> &TotpConfig {
+        &self.config
+    }
+
+    /// Shared secret as unpadded base32, as typed into authenticator apps
+    pub fn secret_base32(&self) -> Zeroizing<String> {
+        base32(&self.secret)
+    }
+
+    /// Code for the time step containing `unix_time`
+    pub fn code_at(&self, unix_time: u64) -> String {
+        let counter = unix_time / self.config.period;
+        let value = match self.config.algorithm {
+            TotpAlgorithm::Sha1 => hotp::<Hmac<sha1::Sha1>>(&self.secret, counter),
+            TotpAlgorithm::Sha256 => hotp::<Hmac<sha2::Sha256>>(&self.secret, counter),
+            TotpAlgorithm::Sha512 => hotp::<Hmac<sha2::Sha512>>(&self.secret, counter),
+        };
+        let digits = self.config.digits as usize;
+        format!(
+            "{:0width$}",
+            value % 10u32.pow(self.config.digits),
+            width = digits
+        )
+    }
+
+    /// Seconds until the code for `unix_time` expires
+    pub fn seconds_remaining(&self, unix_time: u64) -> u64 {
+        self.config.period - unix_time % self.config.period
+    }
+
+    /// `otpauth://totp/...` URI for enrolling an authenticator app
+    ///
+    /// `account` is used when the config does not name one.
+    pub fn to_uri(&self, account: &str) -> Zeroizing<String> {
+        let issuer = self.config.issuer.as_deref().unwrap_or("bip-keychain");
+        let account = self.config.account.as_deref().unwrap_or(account);
+        Zeroizing::new(format!(
+            "otpauth://totp/{}:{}?secret={}&issuer={}&algorithm={}&digits={}&period={}",
+            percent_encode(issuer),
+            percent_encode(account),
+            *self.secret_base32(),
+            percent_encode(issuer),
+            self.config.algorithm.uri_name(),
+            self.config.digits,
+            self.config.period
+        ))
+    }
+}
+
+/// RFC 4226 HOTP value before reduction to digits
+fn hotp<M: Mac + hmac::digest::KeyInit>(secret: &[u8], counter: u64) -> u32 {
+    let mut mac = <M as Mac>::new_from_slice(secret).expect("HMAC accepts any key length");
+    mac.update(&counter.to_be_bytes());
+    let digest = mac.finalize().into_bytes();
+    let offset = (digest[digest.len() - 1] & 0x0f) as usize;
+    u32::from_be_bytes(digest[offset..offset + 4].try_into().expect("4 bytes")) & 0x7fff_ffff
+}
+
+/// Unpadded RFC 4648 base32
+pub(crate) fn base32(data: &[u8]) -> Zeroizing<String> {
+    let mut encoded = Zeroizing::new(String::with_capacity(data.len().div_ceil(5) * 8));
+    for chunk in data.chunks(5) {
+        let mut buffer = [0u8; 5];
+        buffer[..chunk.len()].copy_from_slice(chunk);
+        let bits = u64::from_be_bytes([
+            0, 0, 0, buffer[0], buffer[1], buffer[2], buffer[3], buffer[4],
+        ]);
+        let chars = (chunk.len() * 8).div_ceil(5);
+        for i in 0..chars {
+            encoded.push(BASE32_ALPHABET[(bits >> (35 - 5 * i)) as usize & 31] as char);
+        }
+    }
+    encoded
+}
+
+/// Percent-encode everything but RFC 3986 unreserved characters
+fn percent_encode(text: &str) -> String {
+    let mut encoded = String::with_capacity(text.len());
+    for byte in text.bytes() {
+        if byte.is_ascii_alphanumeric() || b"-._~".contains(&byte) {
+            encoded.push(byte as char);
+        } else {
+            encoded.push_str(&format!("%{:02X}", byte));
+        }
+    }
+    encoded
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rfc6238(algorithm: TotpAlgorithm, secret: &[u8]) -> Totp {
+        let config = TotpConfig {
+            digits: 8,
+            algorithm,
+            ..TotpConfig::default()
+        };
+        Totp::from_secret(secret, &config).unwrap()
+    }
+
+    #[test]
+    fn test_rfc6238_vectors() {
+        let sha1 = rfc6238(TotpAlgorithm::Sha1, b"12345678901234567890");
+        let sha256 = rfc6238(TotpAlgorithm::Sha256, b"12345678901234567890123456789012");
+        let sha512 = rfc6238(
+            TotpAlgorithm::Sha512,
+            b"1234567890123456789012345678901234567890123456789012345678901234",
+        );
+
+        // RFC 6238 Appendix B
+        for (time, codes) in [
+            (59, ["94287082", "46119246", "90693936"]),
+            (1111111109, ["07081804", "68084774", "25091201"]),
+            (20000000000, ["65353130", "77737706", "47863826"]),
+        ] {
+            assert_eq!(sha1.code_at(time), codes[0]);
+            assert_eq!(sha256.code_at(time), codes[1]);
+            assert_eq!(sha512.code_at(time), codes[2]);
+        }
+    }
+
+    #[test]
+    fn test_base32() {
+        // RFC 4648 section 10, without padding
+        assert_eq!(*base32(b"f"), "MY");
+        assert_eq!(*base32(b"foob"), "MZXW6YQ");
+        assert_eq!(*base32(b"foobar"), "MZXW6YTBOI");
+    }
+
+    #[test]
+    fn test_derived_uri() {
+        let config = TotpConfig {
+            issuer: Some("Example Co".to_string()),
+            ..TotpConfig::default()
+        };
+        let totp = Totp::derive(&[7u8; 64], &config).unwrap();
+        assert_eq!(totp.secret_base32().len(), 32);
+        assert_eq!(totp.code_at(0).len(), 6);
+        assert_eq!(totp.seconds_remaining(59), 1);
+
+        let uri = totp.to_uri("alice@example.com");
+        assert!(uri.starts_with("otpauth://totp/Example%20Co:alice%40example.com?secret="));
+        assert!(uri.ends_with("&issuer=Example%20Co&algorithm=SHA1&digits=6&period=30"));
+        assert!(uri.contains(&*totp.secret_base32()));
+
+        // Same entropy, same secret
+        let again = Totp::derive(&[7u8; 64], &config).unwrap();
+        assert_eq!(totp.secret_base32(), again.secret_base32());
+    }
+
+    #[test]
+    fn test_rejects_invalid_config() {
+        for config in [
+            TotpConfig {
+                digits: 4,
+                ..TotpConfig::default()
+            },
+            TotpConfig {
+                period: 0,
+                ..TotpConfig::default()
+            },
+        ] {
+            assert!(Totp::derive(&[7u8; 64], &config).is_err());
+        }
+
+        let config: TotpConfig = serde_json::from_str(r#"{"algorithm": "sha256"}"#).unwrap();
+        assert_eq!(config.digits, 6);
+        assert_eq!(config.algorithm.secret_len(), 32);
+    }
+}
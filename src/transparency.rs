@@ -0,0 +1,714 @@
+//! Transparency log records
+//!
+//! Receipts and key manifests can be submitted to a Rekor transparency log,
+//! so anyone can later check that a key was issued, and when, from an
+//! append-only public record. Documents are logged as Rekor `rekord` entries
+//! of format `ssh`: the document's SHA-256, an SSHSIG over the document, and
+//! the signer's OpenSSH public key.
+//!
+//! The log's answer is kept as a [`LogRecord`] next to the document:
+//!
+//! ```json
+//! {
+//!   "log_url": "https://rekor.sigstore.dev",
+//!   "uuid": "24296fb24b8ad77a...",
+//!   "log_index": 123456789,
+//!   "integrated_time": 1760572800,
+//!   "body": "eyJhcGlWZXJzaW9uIjoiMC4wLjEiLCJraW5kIjoicmVrb3JkIi...",
+//!   "inclusion_proof": {
+//!     "log_index": 1234567,
+//!     "tree_size": 1234570,
+//!     "root_hash": "5be2...",
+//!     "hashes": ["a1c0...", "..."]
+//!   }
+//! }
+//! ```
+//!
+//! [`LogRecord::verify`] checks the record offline against the log's public
+//! key: that the logged entry is a valid signature over the document, that
+//! the entry is in the log's Merkle tree (RFC 6962 inclusion proof against
+//! `root_hash`), and that the log signed that root: the record must carry a
+//! checkpoint signed by the log whose root and tree size match the proof.
+//! A signed entry timestamp (SET) only covers the entry's index and time,
+//! not the tree, so it is checked when present but is not enough on its
+//! own. A valid checkpoint still only shows what the log signed: compare
+//! it with one from a witness to detect a log presenting different views.
+//!
+//! Submitting needs the `transparency-log` feature (see [`submit`]).
+
+use crate::{
+    error::{BipKeychainError, Result},
+    jcs,
+    output::Ed25519Keypair,
+    signing, sshsig,
+};
+use base64::Engine;
+use p256::ecdsa::{signature::Verifier, DerSignature, VerifyingKey};
+use p256::pkcs8::{DecodePublicKey, EncodePublicKey};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+/// Public Sigstore Rekor instance
+pub const DEFAULT_LOG_URL: &str = "https://rekor.sigstore.dev";
+
+/// Public key of [`DEFAULT_LOG_URL`] (log ID `c0d23d6a...`)
+pub const DEFAULT_LOG_PUBLIC_KEY: &str = "-----BEGIN PUBLIC KEY-----
+MFkwEwYHKoZIzj0CAQYIKoZIzj0DAQcDQgAE2G2Y+2tabdTV5BcGiBIx0a9fAFwr
+kBbmLSGtks4L3qX6yYY0zufBnhC8Ur/iy55GhWP/9A/bY2LhC30M9+RYtw==
+-----END PUBLIC KEY-----
+";
+
+/// SSHSIG namespace of logged signatures; Rekor verifies `ssh` signatures
+/// in the `file` namespace, as made by `ssh-keygen -Y sign -n file`
+pub const LOG_NAMESPACE: &str = "file";
+
+/// Proof that an entry is in the log's Merkle tree
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+pub struct InclusionProof {
+    /// Leaf index of the entry in the tree
+    pub log_index: u64,
+
+    /// Number of leaves in the tree the proof is for
+    pub tree_size: u64,
+
+    /// Merkle tree root as hex
+    pub root_hash: String,
+
+    /// Audit path from the leaf to the root, as hex
+    pub hashes: Vec<String>,
+
+    /// The log's signed checkpoint for `root_hash`, if it sent one
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub checkpoint: Option<String>,
+}
+
+/// A document's entry in a transparency log
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+pub struct LogRecord {
+    /// Log the entry was submitted to
+    pub log_url: String,
+
+    /// Entry UUID (ends with the leaf hash as hex)
+    pub uuid: String,
+
+    /// Position of the entry across the whole log
+    pub log_index: u64,
+
+    /// When the log integrated the entry, in seconds since the Unix epoch
+    pub integrated_time: u64,
+
+    /// The logged entry, base64 encoded as returned by the log
+    pub body: String,
+
+    /// Proof that the entry is in the log
+    pub inclusion_proof: InclusionProof,
+
+    /// The log's signed promise to include the entry, base64 encoded
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub signed_entry_timestamp: Option<String>,
+}
+
+/// Rekor's JSON for a log entry
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct RekorEntry {
+    body: String,
+    integrated_time: u64,
+    log_index: u64,
+    verification: RekorVerification,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct RekorVerification {
+    inclusion_proof: RekorInclusionProof,
+    #[serde(default)]
+    signed_entry_timestamp: Option<String>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct RekorInclusionProof {
+    log_index: u64,
+    tree_size: u64,
+    root_hash: String,
+    hashes: Vec<String>,
+    #[serde(default)]
+    checkpoint: Option<String>,
+}
+
+fn log_error(message: String) -> BipKeychainError {
+    BipKeychainError::TransparencyLogError(message)
+}
+
+fn base64_encode(data: &[u8]) -> String {
+    base64::engine::general_purpose::STANDARD.encode(data)
+}
+
+fn base64_decode(field: &str, data: &str) -> Result<Vec<u8>> {
+    base64::engine::general_purpose::STANDARD
+        .decode(data.trim())
+        .map_err(|e| log_error(format!("{} is not valid base64: {}", field, e)))
+}
+
+fn hash_from_hex(field: &str, value: &str) -> Result<[u8; 32]> {
+    hex::decode(value)
+        .ok()
+        .and_then(|hash| hash.try_into().ok())
+        .ok_or_else(|| log_error(format!("{} is not a 32-byte hex hash", field)))
+}
+
+/// A transparency log's ECDSA P-256 signing key
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LogPublicKey {
+    key: VerifyingKey,
+    log_id: [u8; 32],
+}
+
+impl LogPublicKey {
+    /// Parse a PEM public key, as served at `/api/v1/log/publicKey`
+    pub fn from_pem(pem: &str) -> Result<Self> {
+        let key = VerifyingKey::from_public_key_pem(pem.trim())
+            .map_err(|e| log_error(format!("Invalid log public key: {}", e)))?;
+        let der = key
+            .to_public_key_der()
+            .map_err(|e| log_error(format!("Invalid log public key: {}", e)))?;
+        Ok(Self {
+            key,
+            log_id: Sha256::digest(der.as_bytes()).into(),
+        })
+    }
+
+    /// Key of the public Sigstore instance, [`DEFAULT_LOG_URL`]
+    pub fn sigstore() -> Self {
+        Self::from_pem(DEFAULT_LOG_PUBLIC_KEY).expect("bundled log key parses")
+    }
+
+    /// Log ID: SHA-256 of the DER public key, as hex
+    pub fn log_id(&self) -> String {
+        hex::encode(self.log_id)
+    }
+
+    fn verifies(&self, message: &[u8], signature: &[u8]) -> bool {
+        DerSignature::try_from(signature)
+            .is_ok_and(|signature| self.key.verify(message, &signature).is_ok())
+    }
+}
+
+/// Check a checkpoint (a signed note) and return its tree size and root
+///
+/// The note is an origin line, the tree size, the base64 root hash, and
+/// optional extension lines, then a blank line and signature lines of the
+/// form `— <name> <base64 of key hint || DER signature>`. The key hint is
+/// the first 4 bytes of the log ID.
+pub fn verify_checkpoint(checkpoint: &str, log_key: &LogPublicKey) -> Result<(u64, [u8; 32])> {
+    let (text, signatures) = checkpoint
+        .split_once("\n\n")
+        .ok_or_else(|| log_error("Checkpoint has no signatures".to_string()))?;
+    let text = format!("{}\n", text);
+    let mut lines = text.lines().skip(1);
+    let tree_size = lines
+        .next()
+        .and_then(|line| line.parse().ok())
+        .ok_or_else(|| log_error("Checkpoint has no tree size".to_string()))?;
+    let root_hash = lines
+        .next()
+        .and_then(|line| base64_decode("checkpoint root hash", line).ok())
+        .and_then(|root| root.try_into().ok())
+        .ok_or_else(|| log_error("Checkpoint has no 32-byte root hash".to_string()))?;
+
+    let signed = signatures
+        .lines()
+        .filter_map(|line| line.strip_prefix("\u{2014} "))
+        .filter_map(|line| line.rsplit_once(' '))
+        .filter_map(|(_, signature)| base64_decode("checkpoint signature", signature).ok())
+        .any(|signature| {
+            signature.len() > 4
+                && signature[..4] == log_key.log_id[..4]
+                && log_key.verifies(text.as_bytes(), &signature[4..])
+        });
+    if !signed {
+        return Err(log_error(format!(
+            "Checkpoint is not signed by log {}",
+            log_key.log_id()
+        )));
+    }
+    Ok((tree_size, root_hash))
+}
+
+/// The Rekor `rekord` entry to submit for `document`, signed by `keypair`
+pub fn rekord_entry(document: &[u8], keypair: &Ed25519Keypair) -> Result<serde_json::Value> {
+    let signature = sshsig::sign(keypair, LOG_NAMESPACE, document)?;
+    Ok(serde_json::json!({
+        "apiVersion": "0.0.1",
+        "kind": "rekord",
+        "spec": {
+            "signature": {
+                "format": "ssh",
+                "content": base64_encode(signature.as_bytes()),
+                "publicKey": {
+                    "content": base64_encode(keypair.to_ssh_public_key(None).as_bytes()),
+                },
+            },
+            "data": {
+                "content": base64_encode(document),
+            },
+        },
+    }))
+}
+
+/// RFC 6962 leaf hash of a log entry
+pub fn leaf_hash(entry: &[u8]) -> [u8; 32] {
+    let mut hasher = Sha256::new();
+    hasher.update([0u8]);
+    hasher.update(entry);
+    hasher.finalize().into()
+}
+
+fn node_hash(left: &[u8; 32], right: &[u8; 32]) -> [u8; 32] {
+    let mut hasher = Sha256::new();
+    hasher.update([1u8]);
+    hasher.update(left);
+    hasher.update(right);
+    hasher.finalize().into()
+}
+
+/// Check an RFC 6962 inclusion proof (RFC 9162 section 2.1.3.2)
+pub fn verify_inclusion(
+    leaf_hash: &[u8; 32],
+    index: u64,
+    tree_size: u64,
+    proof: &[[u8; 32]],
+    root_hash: &[u8; 32],
+) -> bool {
+    if index >= tree_size {
+        return false;
+    }
+    let (mut fn_, mut sn) = (index, tree_size - 1);
+    let mut hash = *leaf_hash;
+    for sibling in proof {
+        if sn == 0 {
+            return false;
+        }
+        if fn_ & 1 == 1 || fn_ == sn {
+            hash = node_hash(sibling, &hash);
+            while fn_ & 1 == 0 && fn_ != 0 {
+                fn_ >>= 1;
+                sn >>= 1;
+            }
+        } else {
+            hash = node_hash(&hash, sibling);
+        }
+        fn_ >>= 1;
+        sn >>= 1;
+    }
+    sn == 0 && hash == *root_hash
+}
+
+impl LogRecord {
+    /// Parse the log's response to a submission or lookup
+    /// (`{"<uuid>": {"body": ..., "verification": ...}}`)
+    pub fn from_response(log_url: &str, json: &str) -> Result<Self> {
+        let entries: std::collections::BTreeMap<String, RekorEntry> = serde_json::from_str(json)
+            .map_err(|e| log_error(format!("Unexpected transparency log response: {}", e)))?;
+        let mut entries = entries.into_iter();
+        let (uuid, entry) = match (entries.next(), entries.next()) {
+            (Some(entry), None) => entry,
+            _ => {
+                return Err(log_error(
+                    "Transparency log response must hold exactly one entry".to_string(),
+                ))
+            }
+        };
+        let proof = entry.verification.inclusion_proof;
+        Ok(Self {
+            log_url: log_url.trim_end_matches('/').to_string(),
+            uuid,
+            log_index: entry.log_index,
+            integrated_time: entry.integrated_time,
+            body: entry.body,
+            inclusion_proof: InclusionProof {
+                log_index: proof.log_index,
+                tree_size: proof.tree_size,
+                root_hash: proof.root_hash,
+                hashes: proof.hashes,
+                checkpoint: proof.checkpoint,
+            },
+            signed_entry_timestamp: entry.verification.signed_entry_timestamp,
+        })
+    }
+
+    /// Parse a record from JSON
+    pub fn from_json(json: &str) -> Result<Self> {
+        serde_json::from_str(json)
+            .map_err(|e| log_error(format!("Invalid transparency log record: {}", e)))
+    }
+
+    /// Check that the record logs `document`, that the log includes it, and
+    /// that the log with `log_key` signed for it
+    ///
+    /// Returns the Ed25519 key that signed the logged entry.
+    pub fn verify(&self, document: &[u8], log_key: &LogPublicKey) -> Result<[u8; 32]> {
+        let body = base64_decode("body", &self.body)?;
+        let leaf = leaf_hash(&body);
+        if !self.uuid.to_ascii_lowercase().ends_with(&hex::encode(leaf)) {
+            return Err(log_error(format!(
+                "Entry {} does not match the logged body",
+                self.uuid
+            )));
+        }
+
+        let proof = &self.inclusion_proof;
+        let hashes = proof
+            .hashes
+            .iter()
+            .map(|hash| hash_from_hex("inclusion_proof.hashes", hash))
+            .collect::<Result<Vec<_>>>()?;
+        let root_hash = hash_from_hex("inclusion_proof.root_hash", &proof.root_hash)?;
+        if !verify_inclusion(&leaf, proof.log_index, proof.tree_size, &hashes, &root_hash) {
+            return Err(log_error(format!(
+                "Inclusion proof for entry {} does not verify against root {}",
+                self.uuid, proof.root_hash
+            )));
+        }
+        self.verify_log_signatures(&root_hash, log_key)?;
+
+        let entry: serde_json::Value = serde_json::from_slice(&body)
+            .map_err(|e| log_error(format!("Logged body is not JSON: {}", e)))?;
+        let spec = &entry["spec"];
+        if entry["kind"] != "rekord" || spec["signature"]["format"] != "ssh" {
+            return Err(log_error(
+                "Logged entry is not an ssh-signed rekord".to_string(),
+            ));
+        }
+        let logged_hash = spec["data"]["hash"]["value"].as_str().unwrap_or_default();
+        if !logged_hash.eq_ignore_ascii_case(&hex::encode(Sha256::digest(document))) {
+            return Err(log_error(
+                "Logged entry is for a different document".to_string(),
+            ));
+        }
+
+        let field = |value: &serde_json::Value, name: &str| -> Result<String> {
+            let bytes = base64_decode(name, value.as_str().unwrap_or_default())?;
+            String::from_utf8(bytes).map_err(|_| log_error(format!("{} is not text", name)))
+        };
+        let signer = signing::parse_public_key(&field(
+            &spec["signature"]["publicKey"]["content"],
+            "publicKey",
+        )?)?;
+        let signed_by = sshsig::verify(
+            &field(&spec["signature"]["content"], "signature")?,
+            LOG_NAMESPACE,
+            document,
+        )?;
+        if signed_by != signer {
+            return Err(log_error(
+                "Logged signature was made by a different key".to_string(),
+            ));
+        }
+        Ok(signer)
+    }
+
+    /// Check that the log's signed checkpoint carries `root_hash`, and the
+    /// SET, if the record holds one
+    fn verify_log_signatures(&self, root_hash: &[u8; 32], log_key: &LogPublicKey) -> Result<()> {
+        let proof = &self.inclusion_proof;
+        // A SET does not sign the tree, so only a checkpoint authenticates
+        // the root the inclusion proof was checked against
+        let checkpoint = proof.checkpoint.as_deref().ok_or_else(|| {
+            log_error("Record has no signed checkpoint to authenticate its root hash".to_string())
+        })?;
+        let (tree_size, checkpoint_root) = verify_checkpoint(checkpoint, log_key)?;
+        if tree_size != proof.tree_size || checkpoint_root != *root_hash {
+            return Err(log_error(format!(
+                "Checkpoint is for tree {} of size {}, not root {} of size {}",
+                hex::encode(checkpoint_root),
+                tree_size,
+                proof.root_hash,
+                proof.tree_size
+            )));
+        }
+
+        if let Some(timestamp) = &self.signed_entry_timestamp {
+            // The SET signs the canonical JSON of the entry's log metadata
+            let payload = jcs::canonicalize(&serde_json::json!({
+                "body": self.body,
+                "integratedTime": self.integrated_time,
+                "logID": log_key.log_id(),
+                "logIndex": self.log_index,
+            }));
+            let signature = base64_decode("signed_entry_timestamp", timestamp)?;
+            if !log_key.verifies(payload.as_bytes(), &signature) {
+                return Err(log_error(format!(
+                    "Signed entry timestamp is not signed by log {}",
+                    log_key.log_id()
+                )));
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Submit `document`, signed by `keypair`, to the log at `log_url`
+///
+/// A document the log already holds is looked up instead. Only available
+/// with the `transparency-log` feature.
+#[cfg(feature = "transparency-log")]
+pub fn submit(log_url: &str, document: &[u8], keypair: &Ed25519Keypair) -> Result<LogRecord> {
+    let log_url = log_url.trim_end_matches('/');
+    let entry = rekord_entry(document, keypair)?;
+    let response = match ureq::post(&format!("{}/api/v1/log/entries", log_url))
+        .set("Content-Type", "application/json")
+        .send_string(&entry.to_string())
+    {
+        Ok(response) => response,
+        // 409 Conflict: already logged, at the entry in Location
+        Err(ureq::Error::Status(409, response)) => {
+            let location = response.header("Location").map(str::to_string);
+            let location = location.ok_or_else(|| {
+                log_error("Entry already logged, but the log did not say where".to_string())
+            })?;
+            ureq::get(&format!("{}{}", log_url, location))
+                .call()
+                .map_err(|e| log_error(format!("Failed to fetch {}: {}", location, e)))?
+        }
+        Err(ureq::Error::Status(status, response)) => {
+            let message = response.into_string().unwrap_or_default();
+            return Err(log_error(format!(
+                "{} rejected the entry ({}): {}",
+                log_url,
+                status,
+                message.trim()
+            )));
+        }
+        Err(e) => return Err(log_error(format!("Failed to reach {}: {}", log_url, e))),
+    };
+    let json = response
+        .into_string()
+        .map_err(|e| log_error(format!("Failed to read response: {}", e)))?;
+    LogRecord::from_response(log_url, &json)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use p256::ecdsa::{signature::Signer, SigningKey};
+
+    /// RFC 6962 Merkle tree hash of `leaves`
+    fn tree_hash(leaves: &[[u8; 32]]) -> [u8; 32] {
+        match leaves.len() {
+            0 => Sha256::digest([]).into(),
+            1 => leaves[0],
+            n => {
+                let k = n.next_power_of_two() / 2;
+                node_hash(&tree_hash(&leaves[..k]), &tree_hash(&leaves[k..]))
+            }
+        }
+    }
+
+    /// RFC 6962 audit path for leaf `m`
+    fn audit_path(m: usize, leaves: &[[u8; 32]]) -> Vec<[u8; 32]> {
+        if leaves.len() <= 1 {
+            return Vec::new();
+        }
+        let k = leaves.len().next_power_of_two() / 2;
+        if m < k {
+            let mut path = audit_path(m, &leaves[..k]);
+            path.push(tree_hash(&leaves[k..]));
+            path
+        } else {
+            let mut path = audit_path(m - k, &leaves[k..]);
+            path.push(tree_hash(&leaves[..k]));
+            path
+        }
+    }
+
+    #[test]
+    fn test_verify_inclusion() {
+        let leaves: Vec<[u8; 32]> = (0u8..9).map(|i| leaf_hash(&[i])).collect();
+        let outsider = leaf_hash(b"not in the tree");
+        for size in 1..=leaves.len() {
+            let tree = &leaves[..size];
+            let root = tree_hash(tree);
+            for (index, leaf) in tree.iter().enumerate() {
+                let path = audit_path(index, tree);
+                let (index, size) = (index as u64, size as u64);
+                assert!(verify_inclusion(leaf, index, size, &path, &root));
+
+                if size > 1 {
+                    let other = (index + 1) % size;
+                    assert!(!verify_inclusion(leaf, other, size, &path, &root));
+                }
+                assert!(!verify_inclusion(&outsider, index, size, &path, &root));
+                if let Some((_, rest)) = path.split_first() {
+                    assert!(!verify_inclusion(leaf, index, size, rest, &root));
+                }
+            }
+        }
+        assert!(!verify_inclusion(&leaves[0], 1, 1, &[], &leaves[0]));
+    }
+
+    fn log_signing_key(seed: u8) -> (SigningKey, LogPublicKey) {
+        let signing_key = SigningKey::from_slice(&[seed; 32]).unwrap();
+        let pem = signing_key
+            .verifying_key()
+            .to_public_key_pem(p256::pkcs8::LineEnding::LF)
+            .unwrap();
+        (signing_key, LogPublicKey::from_pem(&pem).unwrap())
+    }
+
+    /// A checkpoint for the tree of `size` with `root`, signed with `log_key`
+    fn checkpoint(size: u64, root: &[u8; 32], log_key: &(SigningKey, LogPublicKey)) -> String {
+        let text = format!(
+            "rekor.example - 1193050959916656506\n{}\n{}\n",
+            size,
+            base64_encode(root)
+        );
+        let signature: DerSignature = log_key.0.sign(text.as_bytes());
+        let mut note_signature = log_key.1.log_id[..4].to_vec();
+        note_signature.extend_from_slice(signature.as_bytes());
+        format!(
+            "{}\n\u{2014} rekor.example {}\n",
+            text,
+            base64_encode(&note_signature)
+        )
+    }
+
+    /// A log holding `document` as its only entry, signed with `log_key`
+    fn logged(
+        document: &[u8],
+        keypair: &Ed25519Keypair,
+        log_key: &(SigningKey, LogPublicKey),
+    ) -> LogRecord {
+        // Rekor replaces the submitted content with its hash
+        let mut entry = rekord_entry(document, keypair).unwrap();
+        entry["spec"]["data"] = serde_json::json!({
+            "hash": {"algorithm": "sha256", "value": hex::encode(Sha256::digest(document))}
+        });
+        let body = base64_encode(entry.to_string().as_bytes());
+        let leaf = leaf_hash(entry.to_string().as_bytes());
+        let set_payload = jcs::canonicalize(&serde_json::json!({
+            "body": body,
+            "integratedTime": 1_760_000_000,
+            "logID": log_key.1.log_id(),
+            "logIndex": 42,
+        }));
+        let set: DerSignature = log_key.0.sign(set_payload.as_bytes());
+        let response = serde_json::json!({
+            format!("24296fb24b8ad77a{}", hex::encode(leaf)): {
+                "body": body,
+                "integratedTime": 1_760_000_000,
+                "logID": log_key.1.log_id(),
+                "logIndex": 42,
+                "verification": {
+                    "inclusionProof": {
+                        "logIndex": 0,
+                        "treeSize": 1,
+                        "rootHash": hex::encode(leaf),
+                        "hashes": [],
+                        "checkpoint": checkpoint(1, &leaf, log_key)
+                    },
+                    "signedEntryTimestamp": base64_encode(set.as_bytes())
+                }
+            }
+        });
+        LogRecord::from_response("https://rekor.example/", &response.to_string()).unwrap()
+    }
+
+    #[test]
+    fn test_sigstore_log_key() {
+        assert_eq!(
+            LogPublicKey::sigstore().log_id(),
+            "c0d23d6ad406973f9559f3ba2d1ca01f84147d8ffc5b8445c224f98b9591801d"
+        );
+        assert!(LogPublicKey::from_pem("not a key").is_err());
+    }
+
+    #[test]
+    fn test_log_record_verifies() {
+        let keypair = Ed25519Keypair::from_seed([7u8; 32]);
+        let log_key = log_signing_key(1);
+        let document = b"{\"keys\":[]}\n";
+        let record = logged(document, &keypair, &log_key);
+        assert_eq!(record.log_url, "https://rekor.example");
+        assert_eq!(record.log_index, 42);
+
+        let round_tripped = LogRecord::from_json(&serde_json::to_string(&record).unwrap()).unwrap();
+        assert_eq!(
+            round_tripped.verify(document, &log_key.1).unwrap(),
+            keypair.public_key_bytes()
+        );
+        assert!(record.verify(b"{\"keys\":[1]}\n", &log_key.1).is_err());
+
+        let mut wrong_root = record.clone();
+        wrong_root.inclusion_proof.root_hash = hex::encode([0u8; 32]);
+        assert!(wrong_root.verify(document, &log_key.1).is_err());
+
+        // A body swapped for another document's no longer matches the UUID
+        let mut swapped = record.clone();
+        swapped.body = logged(b"other", &keypair, &log_key).body;
+        assert!(swapped.verify(b"other", &log_key.1).is_err());
+    }
+
+    #[test]
+    fn test_log_signatures_required() {
+        let keypair = Ed25519Keypair::from_seed([7u8; 32]);
+        let log_key = log_signing_key(1);
+        let document = b"{\"keys\":[]}\n";
+        let record = logged(document, &keypair, &log_key);
+
+        // The checkpoint authenticates the root; the SET is optional
+        let mut checkpoint_only = record.clone();
+        checkpoint_only.signed_entry_timestamp = None;
+        assert!(checkpoint_only.verify(document, &log_key.1).is_ok());
+
+        // A SET does not sign the root, so it is not enough on its own
+        let mut set_only = record.clone();
+        set_only.inclusion_proof.checkpoint = None;
+        let error = set_only.verify(document, &log_key.1).unwrap_err();
+        assert!(error.to_string().contains("no signed checkpoint"));
+
+        // Signed by a different log
+        let other_log = log_signing_key(2);
+        assert!(record.verify(document, &other_log.1).is_err());
+        assert!(checkpoint_only.verify(document, &other_log.1).is_err());
+
+        // A timestamp for another log index
+        let mut moved = record.clone();
+        moved.log_index = 43;
+        assert!(moved.verify(document, &log_key.1).is_err());
+    }
+
+    #[test]
+    fn test_tampered_root_rejected() {
+        let keypair = Ed25519Keypair::from_seed([7u8; 32]);
+        let log_key = log_signing_key(1);
+        let document = b"{\"keys\":[]}\n";
+        let record = logged(document, &keypair, &log_key);
+
+        // A consistent proof into a tree the log never signed: the entry
+        // plus one forged leaf
+        let leaf = leaf_hash(&base64_decode("body", &record.body).unwrap());
+        let forged = [leaf, leaf_hash(b"forged")];
+        let mut tampered = record.clone();
+        tampered.signed_entry_timestamp = None;
+        tampered.inclusion_proof.tree_size = 2;
+        tampered.inclusion_proof.root_hash = hex::encode(tree_hash(&forged));
+        tampered.inclusion_proof.hashes = vec![hex::encode(forged[1])];
+        let error = tampered.verify(document, &log_key.1).unwrap_err();
+        assert!(error.to_string().contains("Checkpoint is for tree"));
+
+        // The entry's genuine SET does not vouch for the forged root
+        let mut set_only = tampered.clone();
+        set_only.signed_entry_timestamp = record.signed_entry_timestamp.clone();
+        set_only.inclusion_proof.checkpoint = None;
+        assert!(set_only.verify(document, &log_key.1).is_err());
+
+        // Re-signing the forged root needs the log's key
+        let other_log = log_signing_key(2);
+        tampered.inclusion_proof.checkpoint = Some(checkpoint(2, &tree_hash(&forged), &other_log));
+        assert!(tampered.verify(document, &log_key.1).is_err());
+        tampered.inclusion_proof.checkpoint = Some(checkpoint(2, &tree_hash(&forged), &log_key));
+        assert!(tampered.verify(document, &log_key.1).is_ok());
+    }
+}
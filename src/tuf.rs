@@ -0,0 +1,98 @@
+//! TUF and in-toto key metadata
+//!
+//! Formats a derived Ed25519 key as the key objects used in The Update
+//! Framework's `root.json` and in-toto layouts (securesystemslib's
+//! `ed25519` key type), so a supply chain's signing roots can be recreated
+//! from the seed:
+//!
+//! ```json
+//! {"keytype": "ed25519", "scheme": "ed25519", "keyval": {"public": "<hex>"}}
+//! ```
+//!
+//! The key ID is the SHA-256 of the public key object's canonical JSON, as
+//! computed by securesystemslib.
+
+use crate::jcs::canonicalize;
+use crate::output::Ed25519Keypair;
+use serde_json::{json, Value};
+use sha2::{Digest, Sha256};
+use zeroize::Zeroizing;
+
+/// An Ed25519 key in TUF / in-toto metadata
+pub struct TufKey<'a> {
+    keypair: &'a Ed25519Keypair,
+}
+
+impl<'a> TufKey<'a> {
+    /// Describe `keypair`
+    pub fn new(keypair: &'a Ed25519Keypair) -> Self {
+        Self { keypair }
+    }
+
+    /// Public key object, as listed under `keys` in metadata
+    pub fn public_key(&self) -> Value {
+        json!({
+            "keytype": "ed25519",
+            "scheme": "ed25519",
+            "keyval": {"public": hex::encode(self.keypair.public_key_bytes())},
+        })
+    }
+
+    /// Key ID: hex SHA-256 of the canonical public key object
+    pub fn keyid(&self) -> String {
+        hex::encode(Sha256::digest(canonicalize(&self.public_key()).as_bytes()))
+    }
+
+    /// Public key object with its `keyid`, as in in-toto layouts
+    pub fn to_json(&self) -> String {
+        let mut key = self.public_key();
+        key["keyid"] = self.keyid().into();
+        canonicalize(&key)
+    }
+
+    /// Key object with the private key (`keyval.private`, hex seed), as
+    /// written by securesystemslib for signing
+    pub fn to_private_json(&self) -> Zeroizing<String> {
+        let mut key = self.public_key();
+        key["keyid"] = self.keyid().into();
+        key["keyval"]["private"] = hex::encode(*self.keypair.private_key_bytes()).into();
+        let json = Zeroizing::new(canonicalize(&key));
+        // Overwrite the copy of the private key held by the Value
+        if let Some(Value::String(private)) = key["keyval"].get_mut("private") {
+            zeroize::Zeroize::zeroize(private);
+        }
+        json
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_key_objects() {
+        let keypair = Ed25519Keypair::from_seed([1u8; 32]);
+        let key = TufKey::new(&keypair);
+        let public = hex::encode(keypair.public_key_bytes());
+
+        // Canonical form hashed for the key ID
+        let canonical = format!(
+            r#"{{"keytype":"ed25519","keyval":{{"public":"{}"}},"scheme":"ed25519"}}"#,
+            public
+        );
+        assert_eq!(canonicalize(&key.public_key()), canonical);
+        assert_eq!(key.keyid(), hex::encode(Sha256::digest(canonical)));
+
+        assert_eq!(
+            key.to_json(),
+            format!(
+                r#"{{"keyid":"{}","keytype":"ed25519","keyval":{{"public":"{}"}},"scheme":"ed25519"}}"#,
+                key.keyid(),
+                public
+            )
+        );
+        let private: Value = serde_json::from_str(&key.to_private_json()).unwrap();
+        assert_eq!(private["keyval"]["private"], hex::encode([1u8; 32]));
+        assert_eq!(private["keyid"], key.keyid());
+    }
+}
@@ -0,0 +1,230 @@
+//! Uniform Resources (UR, BCR-2020-005)
+//!
+//! Single-part `ur:<type>/<bytewords>` strings carrying CBOR, as used for
+//! envelopes and for moving data between wallets over QR codes. Payloads
+//! use minimal Bytewords (BCR-2020-012) with a CRC-32 checksum; standard
+//! Bytewords (whole words) carry the same payload on paper.
+//!
+//! Multi-part (fountain-coded) URs are not supported.
+
+use crate::error::{BipKeychainError, Result};
+
+/// Bytewords word list (BCR-2020-012); minimal encoding uses the first and
+/// last letter of each word
+const BYTEWORDS: [&str; 256] = [
+    "able", "acid", "also", "apex", "aqua", "arch", "atom", "aunt", "away", "axis", "back", "bald",
+    "barn", "belt", "beta", "bias", "blue", "body", "brag", "brew", "bulb", "buzz", "calm", "cash",
+    "cats", "chef", "city", "claw", "code", "cola", "cook", "cost", "crux", "curl", "cusp", "cyan",
+    "dark", "data", "days", "deli", "dice", "diet", "door", "down", "draw", "drop", "drum", "dull",
+    "duty", "each", "easy", "echo", "edge", "epic", "even", "exam", "exit", "eyes", "fact", "fair",
+    "fern", "figs", "film", "fish", "fizz", "flap", "flew", "flux", "foxy", "free", "frog", "fuel",
+    "fund", "gala", "game", "gear", "gems", "gift", "girl", "glow", "good", "gray", "grim", "guru",
+    "gush", "gyro", "half", "hang", "hard", "hawk", "heat", "help", "high", "hill", "holy", "hope",
+    "horn", "huts", "iced", "idea", "idle", "inch", "inky", "into", "iris", "iron", "item", "jade",
+    "jazz", "join", "jolt", "jowl", "judo", "jugs", "jump", "junk", "jury", "keep", "keno", "kept",
+    "keys", "kick", "kiln", "king", "kite", "kiwi", "knob", "lamb", "lava", "lazy", "leaf", "legs",
+    "liar", "limp", "lion", "list", "logo", "loud", "love", "luau", "luck", "lung", "main", "many",
+    "math", "maze", "memo", "menu", "meow", "mild", "mint", "miss", "monk", "nail", "navy", "need",
+    "news", "next", "noon", "note", "numb", "obey", "oboe", "omit", "onyx", "open", "oval", "owls",
+    "paid", "part", "peck", "play", "plus", "poem", "pool", "pose", "puff", "puma", "purr", "quad",
+    "quiz", "race", "ramp", "real", "redo", "rich", "road", "rock", "roof", "ruby", "ruin", "runs",
+    "rust", "safe", "saga", "scar", "sets", "silk", "skew", "slot", "soap", "solo", "song", "stub",
+    "surf", "swan", "taco", "task", "taxi", "tent", "tied", "time", "tiny", "toil", "tomb", "toys",
+    "trip", "tuna", "twin", "ugly", "undo", "unit", "urge", "user", "vast", "very", "veto", "vial",
+    "vibe", "view", "visa", "void", "vows", "wall", "wand", "warm", "wasp", "wave", "waxy", "webs",
+    "what", "when", "whiz", "wolf", "work", "yank", "yawn", "yell", "yoga", "yurt", "zaps", "zero",
+    "zest", "zinc", "zone", "zoom",
+];
+
+/// Encode CBOR as a single-part UR string
+///
+/// `ur_type` must be lowercase letters, digits, and hyphens (e.g. `envelope`).
+pub fn encode(ur_type: &str, cbor: &[u8]) -> String {
+    format!("ur:{}/{}", ur_type, bytewords_minimal(cbor))
+}
+
+/// Decode a single-part UR string into its type and CBOR payload
+///
+/// Accepts upper- or lowercase input (QR codes use uppercase) and verifies
+/// the Bytewords checksum.
+pub fn decode(ur: &str) -> Result<(String, Vec<u8>)> {
+    let ur = ur.trim().to_ascii_lowercase();
+
+    let rest = ur
+        .strip_prefix("ur:")
+        .ok_or_else(|| BipKeychainError::UrError("missing 'ur:' prefix".to_string()))?;
+    let (ur_type, payload) = rest
+        .split_once('/')
+        .ok_or_else(|| BipKeychainError::UrError("missing '/' after the UR type".to_string()))?;
+
+    if ur_type.is_empty()
+        || !ur_type
+            .bytes()
+            .all(|b| b.is_ascii_lowercase() || b.is_ascii_digit() || b == b'-')
+    {
+        return Err(BipKeychainError::UrError(format!(
+            "invalid UR type '{}'",
+            ur_type
+        )));
+    }
+    if payload.contains('/') {
+        return Err(BipKeychainError::UrError(
+            "multi-part URs are not supported".to_string(),
+        ));
+    }
+
+    let data = bytewords_minimal_decode(payload)?;
+    Ok((ur_type.to_string(), data))
+}
+
+/// Minimal Bytewords encoding with the CRC-32 checksum appended
+fn bytewords_minimal(data: &[u8]) -> String {
+    let checksum = crc32fast::hash(data).to_be_bytes();
+
+    data.iter()
+        .chain(checksum.iter())
+        .flat_map(|&byte| {
+            let word = BYTEWORDS[byte as usize].as_bytes();
+            [word[0] as char, word[3] as char]
+        })
+        .collect()
+}
+
+/// Standard Bytewords (full words separated by spaces) with the CRC-32
+/// checksum appended, for writing data down by hand
+pub fn bytewords(data: &[u8]) -> String {
+    let checksum = crc32fast::hash(data).to_be_bytes();
+
+    data.iter()
+        .chain(checksum.iter())
+        .map(|&byte| BYTEWORDS[byte as usize])
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Decode standard Bytewords and verify the trailing CRC-32
+///
+/// Words may be in any case and separated by whitespace or hyphens.
+pub fn bytewords_decode(text: &str) -> Result<Vec<u8>> {
+    let bytes = text
+        .split(|c: char| c.is_whitespace() || c == '-')
+        .filter(|word| !word.is_empty())
+        .map(|word| {
+            let word = word.to_ascii_lowercase();
+            BYTEWORDS
+                .iter()
+                .position(|byteword| *byteword == word)
+                .map(|byte| byte as u8)
+                .ok_or_else(|| BipKeychainError::UrError(format!("'{}' is not a Byteword", word)))
+        })
+        .collect::<Result<Vec<u8>>>()?;
+    strip_checksum(bytes)
+}
+
+/// Whether `word` is a standard Byteword (any case)
+pub fn is_byteword(word: &str) -> bool {
+    BYTEWORDS
+        .iter()
+        .any(|byteword| byteword.eq_ignore_ascii_case(word))
+}
+
+/// Decode minimal Bytewords and verify the trailing CRC-32
+fn bytewords_minimal_decode(encoded: &str) -> Result<Vec<u8>> {
+    let letters = encoded.as_bytes();
+    if !letters.len().is_multiple_of(2) {
+        return Err(BipKeychainError::UrError(
+            "Bytewords payload has an odd number of letters".to_string(),
+        ));
+    }
+
+    let bytes = letters
+        .chunks(2)
+        .map(|pair| {
+            BYTEWORDS
+                .iter()
+                .position(|word| word.as_bytes()[0] == pair[0] && word.as_bytes()[3] == pair[1])
+                .map(|byte| byte as u8)
+                .ok_or_else(|| {
+                    BipKeychainError::UrError(format!(
+                        "'{}' is not a minimal Byteword",
+                        String::from_utf8_lossy(pair)
+                    ))
+                })
+        })
+        .collect::<Result<Vec<u8>>>()?;
+    strip_checksum(bytes)
+}
+
+/// Split off and verify the CRC-32 that ends every Bytewords payload
+fn strip_checksum(mut bytes: Vec<u8>) -> Result<Vec<u8>> {
+    if bytes.len() < 4 {
+        return Err(BipKeychainError::UrError(
+            "Bytewords payload is too short for a checksum".to_string(),
+        ));
+    }
+    let checksum = bytes.split_off(bytes.len() - 4);
+    if crc32fast::hash(&bytes).to_be_bytes()[..] != checksum[..] {
+        return Err(BipKeychainError::UrError("checksum mismatch".to_string()));
+    }
+
+    Ok(bytes)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_bytewords_minimal_pairs_are_unique() {
+        let mut pairs: Vec<(u8, u8)> = BYTEWORDS
+            .iter()
+            .map(|w| (w.as_bytes()[0], w.as_bytes()[3]))
+            .collect();
+        pairs.sort();
+        pairs.dedup();
+        assert_eq!(pairs.len(), 256);
+    }
+
+    #[test]
+    fn test_decode_reference_envelope() {
+        // Blockchain Commons "Hello." envelope (see envelope.rs)
+        let (ur_type, cbor) = decode("UR:ENVELOPE/TPSOIYFDIHJZJZJLDMKSBAOEDE").unwrap();
+        assert_eq!(ur_type, "envelope");
+        assert_eq!(hex::encode(&cbor), "d8c96648656c6c6f2e");
+        assert_eq!(
+            encode(&ur_type, &cbor),
+            "ur:envelope/tpsoiyfdihjzjzjldmksbaoede"
+        );
+    }
+
+    #[test]
+    fn test_standard_bytewords() {
+        let cbor = hex::decode("d8c96648656c6c6f2e").unwrap();
+        let words = bytewords(&cbor);
+        assert!(words.starts_with("trip solo inky fund"));
+        assert_eq!(words.split(' ').count(), cbor.len() + 4);
+
+        // Each word's first and last letters are the minimal encoding
+        let minimal: String = words
+            .split(' ')
+            .flat_map(|word| [word.as_bytes()[0] as char, word.as_bytes()[3] as char])
+            .collect();
+        assert_eq!(minimal, "tpsoiyfdihjzjzjldmksbaoede");
+
+        assert_eq!(bytewords_decode(&words.to_uppercase()).unwrap(), cbor);
+        assert_eq!(bytewords_decode(&words.replace(' ', "-")).unwrap(), cbor);
+        assert!(bytewords_decode(&words.replacen("trip", "able", 1)).is_err());
+        assert!(bytewords_decode("tuna solo zzzz").is_err());
+        assert!(is_byteword("TUNA") && !is_byteword("tune"));
+    }
+
+    #[test]
+    fn test_decode_rejects_corruption() {
+        // Last checksum byte changed
+        assert!(decode("ur:envelope/tpsoiyfdihjzjzjldmksbaoeda").is_err());
+        assert!(decode("ur:envelope/tpsoiyfdihjzjzjldmksbaoed").is_err());
+        assert!(decode("envelope/tpsoiyfdihjzjzjldmksbaoede").is_err());
+        assert!(decode("ur:envelope/1-3/tpsoiyfdihjzjzjldmksbaoede").is_err());
+        assert!(decode("ur:Bad_Type/tpsoiyfdihjzjzjldmksbaoede").is_err());
+    }
+}
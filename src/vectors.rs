@@ -0,0 +1,459 @@
+//! Published derivation test vectors
+//!
+//! `vectors/bip-keychain.json` lists, for a fixed set of mnemonics and
+//! entities, every intermediate value of the derivation so that other
+//! implementations can check compatibility step by step:
+//!
+//! ```text
+//! mnemonic + entity → canonical bytes → hash → indices → path → seed → public key
+//! ```
+//!
+//! The file is generated by [`generate_vectors`] (`bip-keychain
+//! gen-vectors`) and checked by the `vectors_test` integration test, so a
+//! change that alters any derived key fails the test suite until the
+//! vectors are deliberately regenerated.
+
+use crate::{
+    bip32_wrapper::Keychain,
+    curve_key::CurveKey,
+    derivation::{
+        derive_key_from_entity, entity_derivation_path, entity_hash, entity_indices,
+        DEFAULT_PARENT_ENTROPY,
+    },
+    entity::KeyDerivation,
+    error::{BipKeychainError, Result},
+};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+/// Version of the test vector file format
+pub const VECTORS_VERSION: u32 = 1;
+
+/// The mnemonic used by most vectors (BIP-39 test vector, entropy all zeros)
+const ABANDON_MNEMONIC: &str =
+    "abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon about";
+
+/// A second BIP-39 test vector mnemonic (entropy 0x7f repeated)
+const LEGAL_MNEMONIC: &str =
+    "legal winner thank year wave sausage worth useful legal winner thank yellow";
+
+/// Inputs of each published vector: description, mnemonic, parent
+/// entropy, and entity file
+const VECTOR_CASES: &[(&str, &str, &[u8], &str)] = &[
+    (
+        "hmac_sha512, hardened (the default configuration)",
+        ABANDON_MNEMONIC,
+        DEFAULT_PARENT_ENTROPY,
+        r#"{"schema_type": "schema_org", "entity": {"@context": "https://schema.org", "@type": "SoftwareSourceCode", "codeRepository": "https://github.com/DAOgora-xyz/bip-keychain-core", "name": "BIP-Keychain Core"}, "derivation_config": {"hash_function": "hmac_sha512", "hardened": true}, "purpose": "Git commit signing key"}"#,
+    ),
+    (
+        "hmac_sha512 with a different mnemonic and parent entropy",
+        LEGAL_MNEMONIC,
+        b"example parent entropy",
+        r#"{"schema_type": "schema_org", "entity": {"@context": "https://schema.org", "@type": "SoftwareSourceCode", "codeRepository": "https://github.com/DAOgora-xyz/bip-keychain-core", "name": "BIP-Keychain Core"}, "derivation_config": {"hash_function": "hmac_sha512", "hardened": true}}"#,
+    ),
+    (
+        "blake2b (ignores parent entropy)",
+        ABANDON_MNEMONIC,
+        DEFAULT_PARENT_ENTROPY,
+        r#"{"schema_type": "schema_org", "entity": {"@type": "Organization", "name": "Example Org"}, "derivation_config": {"hash_function": "blake2b", "hardened": true}}"#,
+    ),
+    (
+        "sha256",
+        ABANDON_MNEMONIC,
+        DEFAULT_PARENT_ENTROPY,
+        r#"{"schema_type": "schema_org", "entity": {"@type": "Organization", "name": "Example Org"}, "derivation_config": {"hash_function": "sha256", "hardened": true}}"#,
+    ),
+    (
+        "blake3 keyed with parent entropy",
+        ABANDON_MNEMONIC,
+        DEFAULT_PARENT_ENTROPY,
+        r#"{"schema_type": "schema_org", "entity": {"@type": "Organization", "name": "Example Org"}, "derivation_config": {"hash_function": "blake3", "hardened": true}}"#,
+    ),
+    (
+        "argon2id with parent entropy as salt",
+        ABANDON_MNEMONIC,
+        DEFAULT_PARENT_ENTROPY,
+        r#"{"schema_type": "dns", "entity": {"hostname": "server.example.com"}, "derivation_config": {"hash_function": "argon2id", "hardened": true}}"#,
+    ),
+    (
+        "jcs canonicalization",
+        ABANDON_MNEMONIC,
+        DEFAULT_PARENT_ENTROPY,
+        r#"{"schema_type": "schema_org", "entity": {"@type": "Organization", "name": "Example Org", "foundingDate": 2019.5}, "derivation_config": {"hash_function": "hmac_sha512", "hardened": true, "canonicalization": "jcs"}}"#,
+    ),
+    (
+        "dcbor canonicalization",
+        ABANDON_MNEMONIC,
+        DEFAULT_PARENT_ENTROPY,
+        r#"{"schema_type": "schema_org", "entity": {"@type": "Organization", "name": "Example Org", "foundingDate": 2019.5}, "derivation_config": {"hash_function": "hmac_sha512", "hardened": true, "canonicalization": "dcbor"}}"#,
+    ),
+    (
+        "non-hardened (index clamped to 31 bits)",
+        ABANDON_MNEMONIC,
+        DEFAULT_PARENT_ENTROPY,
+        r#"{"schema_type": "schema_org", "entity": {"@type": "Organization", "name": "Example Org"}, "derivation_config": {"hash_function": "hmac_sha512", "hardened": false}}"#,
+    ),
+    (
+        "three index levels after HKDF-SHA512 expansion",
+        ABANDON_MNEMONIC,
+        DEFAULT_PARENT_ENTROPY,
+        r#"{"schema_type": "schema_org", "entity": {"@type": "Organization", "name": "Example Org"}, "derivation_config": {"hash_function": "hmac_sha512", "hardened": true, "index_levels": 3, "index_expansion": {"type": "hkdf_sha512"}}}"#,
+    ),
+    (
+        "application level",
+        ABANDON_MNEMONIC,
+        DEFAULT_PARENT_ENTROPY,
+        r#"{"schema_type": "schema_org", "entity": {"@type": "Organization", "name": "Example Org"}, "derivation_config": {"hash_function": "hmac_sha512", "hardened": true, "application": 1}}"#,
+    ),
+    (
+        "application and tenant levels",
+        ABANDON_MNEMONIC,
+        DEFAULT_PARENT_ENTROPY,
+        r#"{"schema_type": "schema_org", "entity": {"@type": "Organization", "name": "Example Org"}, "derivation_config": {"hash_function": "hmac_sha512", "hardened": true, "application": 1, "tenant": "acme"}}"#,
+    ),
+    (
+        "key index",
+        ABANDON_MNEMONIC,
+        DEFAULT_PARENT_ENTROPY,
+        r#"{"schema_type": "schema_org", "entity": {"@type": "Organization", "name": "Example Org"}, "derivation_config": {"hash_function": "hmac_sha512", "hardened": true, "key_index": 2}}"#,
+    ),
+    (
+        "BIP-85 key entropy",
+        ABANDON_MNEMONIC,
+        DEFAULT_PARENT_ENTROPY,
+        r#"{"schema_type": "schema_org", "entity": {"@type": "Organization", "name": "Example Org"}, "derivation_config": {"hash_function": "hmac_sha512", "hardened": true, "key_entropy": "bip85"}}"#,
+    ),
+    (
+        "secp256k1 key type",
+        ABANDON_MNEMONIC,
+        DEFAULT_PARENT_ENTROPY,
+        r#"{"schema_type": "schema_org", "entity": {"@type": "Organization", "name": "Example Org"}, "derivation_config": {"hash_function": "hmac_sha512", "hardened": true, "key_type": "secp256k1"}}"#,
+    ),
+    (
+        "P-256 key type",
+        ABANDON_MNEMONIC,
+        DEFAULT_PARENT_ENTROPY,
+        r#"{"schema_type": "schema_org", "entity": {"@type": "Organization", "name": "Example Org"}, "derivation_config": {"hash_function": "hmac_sha512", "hardened": true, "key_type": "p256"}}"#,
+    ),
+    (
+        "hash_exclude",
+        ABANDON_MNEMONIC,
+        DEFAULT_PARENT_ENTROPY,
+        r#"{"schema_type": "schema_org", "entity": {"@type": "Organization", "name": "Example Org", "dateModified": "2026-01-01"}, "derivation_config": {"hash_function": "hmac_sha512", "hardened": true, "hash_exclude": ["/dateModified"]}}"#,
+    ),
+    (
+        "hash_include",
+        ABANDON_MNEMONIC,
+        DEFAULT_PARENT_ENTROPY,
+        r#"{"schema_type": "schema_org", "entity": {"@type": "Organization", "name": "Example Org", "url": "https://example.org"}, "derivation_config": {"hash_function": "hmac_sha512", "hardened": true, "hash_include": ["/@type", "/name"]}}"#,
+    ),
+    (
+        "salt",
+        ABANDON_MNEMONIC,
+        DEFAULT_PARENT_ENTROPY,
+        r#"{"schema_type": "schema_org", "entity": {"@type": "Organization", "name": "Example Org"}, "derivation_config": {"hash_function": "hmac_sha512", "hardened": true, "salt": "deployment-a"}}"#,
+    ),
+    (
+        "version 2 entity, hash input tagged BIP-KEYCHAIN/v1",
+        ABANDON_MNEMONIC,
+        DEFAULT_PARENT_ENTROPY,
+        r#"{"version": 2, "schema_type": "schema_org", "entity": {"@type": "Organization", "name": "Example Org"}, "derivation_config": {"hash_function": "hmac_sha512", "hardened": true}}"#,
+    ),
+    (
+        "version 2 entity with domain_tag none",
+        ABANDON_MNEMONIC,
+        DEFAULT_PARENT_ENTROPY,
+        r#"{"version": 2, "schema_type": "schema_org", "entity": {"@type": "Organization", "name": "Example Org"}, "derivation_config": {"hash_function": "hmac_sha512", "hardened": true, "domain_tag": "none"}}"#,
+    ),
+];
+
+/// A published test vector file
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+pub struct TestVectors {
+    /// File format version ([`VECTORS_VERSION`])
+    pub version: u32,
+
+    /// The vectors, one per configuration
+    pub vectors: Vec<TestVector>,
+}
+
+/// Inputs and every intermediate value of one derivation
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+pub struct TestVector {
+    /// What the vector exercises
+    pub description: String,
+
+    /// BIP-39 mnemonic (no passphrase)
+    pub mnemonic: String,
+
+    /// Parent entropy as hex
+    pub parent_entropy: String,
+
+    /// The entity file, as read by `derive`
+    pub entity: Value,
+
+    /// Bytes that are hashed (the canonical entity, after the domain tag and
+    /// any `salt`), as hex
+    pub canonical: String,
+
+    /// Entity hash after any index expansion, as hex
+    pub hash: String,
+
+    /// Big-endian u32 read from each 4-byte group of the hash
+    pub indices: Vec<u32>,
+
+    /// BIP-32 derivation path
+    pub path: String,
+
+    /// Ed25519 seed derived at the path, as hex
+    pub seed: String,
+
+    /// Public key of the entity's `key_type` (Ed25519 by default), as hex
+    pub public_key: String,
+}
+
+impl TestVector {
+    /// Compute a vector by deriving `entity_json` from `mnemonic`
+    pub fn compute(
+        description: &str,
+        mnemonic: &str,
+        parent_entropy: &[u8],
+        entity_json: &str,
+    ) -> Result<Self> {
+        let key_derivation = KeyDerivation::from_json(entity_json)?;
+        let keychain = Keychain::from_mnemonic(mnemonic)?;
+        let derived = derive_key_from_entity(&keychain, &key_derivation, parent_entropy)?;
+        let seed = derived.to_seed();
+
+        Ok(Self {
+            description: description.to_string(),
+            mnemonic: mnemonic.to_string(),
+            parent_entropy: hex::encode(parent_entropy),
+            entity: serde_json::from_str(entity_json)?,
+            canonical: hex::encode(key_derivation.hash_input()?),
+            hash: hex::encode(entity_hash(&key_derivation, parent_entropy)?),
+            indices: entity_indices(&key_derivation, parent_entropy)?,
+            path: entity_derivation_path(&key_derivation, parent_entropy)?.to_string(),
+            seed: hex::encode(*seed),
+            public_key: hex::encode(
+                CurveKey::from_seed(&seed, key_derivation.derivation_config.key_type)?
+                    .public_key_bytes(),
+            ),
+        })
+    }
+
+    /// Recompute this vector from its inputs
+    pub fn recompute(&self) -> Result<Self> {
+        Self::compute(
+            &self.description,
+            &self.mnemonic,
+            &decode_parent_entropy(&self.parent_entropy)?,
+            &self.entity.to_string(),
+        )
+    }
+}
+
+fn decode_parent_entropy(parent_entropy: &str) -> Result<Vec<u8>> {
+    hex::decode(parent_entropy)
+        .map_err(|e| BipKeychainError::HashError(format!("Invalid parent entropy hex: {}", e)))
+}
+
+/// Generate the published test vectors
+pub fn generate_vectors() -> Result<TestVectors> {
+    let vectors = VECTOR_CASES
+        .iter()
+        .map(|(description, mnemonic, parent_entropy, entity_json)| {
+            TestVector::compute(description, mnemonic, parent_entropy, entity_json)
+        })
+        .collect::<Result<_>>()?;
+
+    Ok(TestVectors {
+        version: VECTORS_VERSION,
+        vectors,
+    })
+}
+
+/// Outputs of another BIP-Keychain implementation, such as the reference
+/// implementation, for comparison with this one
+///
+/// The format is that of the published vectors with every output optional,
+/// so the published file is itself a valid reference file. Reference
+/// implementations without some step (e.g. no Ed25519 keys) simply omit it.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct ReferenceVectors {
+    /// Name and version of the implementation that produced the outputs
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub implementation: Option<String>,
+
+    /// The reference outputs, one per derivation
+    pub vectors: Vec<ReferenceVector>,
+}
+
+/// One derivation as computed by another implementation
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct ReferenceVector {
+    /// What the vector exercises
+    #[serde(default)]
+    pub description: String,
+
+    /// BIP-39 mnemonic, required to compare `seed` and `public_key`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub mnemonic: Option<String>,
+
+    /// Parent entropy as hex
+    pub parent_entropy: String,
+
+    /// The entity file, as read by `derive`
+    pub entity: Value,
+
+    /// Bytes that are hashed, as hex
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub canonical: Option<String>,
+
+    /// Entity hash, as hex
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub hash: Option<String>,
+
+    /// Extracted indices
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub indices: Option<Vec<u32>>,
+
+    /// BIP-32 derivation path
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub path: Option<String>,
+
+    /// Ed25519 seed, as hex
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub seed: Option<String>,
+
+    /// Ed25519 public key, as hex
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub public_key: Option<String>,
+
+    /// Steps (`canonical`, `indices`, ...) where this implementation is
+    /// known to differ from the reference, each documented with the fixture
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub known_divergences: Vec<String>,
+}
+
+/// A step where this implementation disagrees with a reference output
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Divergence {
+    /// Field of the vector that differs (`canonical`, `hash`, `indices`, ...)
+    pub step: &'static str,
+
+    /// The reference implementation's value
+    pub expected: String,
+
+    /// This implementation's value
+    pub actual: String,
+}
+
+impl ReferenceVector {
+    /// Derive this vector's inputs and compare every output it lists
+    ///
+    /// Divergences are returned in pipeline order, so the first one is the
+    /// earliest step that differs: a `canonical` divergence points at entity
+    /// encoding, a `hash` divergence with matching canonical bytes at the
+    /// hash function, and an `indices` divergence with a matching hash at
+    /// index extraction. Only the path is computed without a mnemonic.
+    pub fn compare(&self) -> Result<Vec<Divergence>> {
+        let parent_entropy = decode_parent_entropy(&self.parent_entropy)?;
+        let key_derivation = KeyDerivation::from_json(&self.entity.to_string())?;
+
+        let mut divergences = Vec::new();
+        let format_indices = |indices: &[u32]| {
+            indices
+                .iter()
+                .map(u32::to_string)
+                .collect::<Vec<_>>()
+                .join(", ")
+        };
+
+        check_step(
+            &mut divergences,
+            "canonical",
+            self.canonical.clone(),
+            hex::encode(key_derivation.hash_input()?),
+        );
+        check_step(
+            &mut divergences,
+            "hash",
+            self.hash.clone(),
+            hex::encode(entity_hash(&key_derivation, &parent_entropy)?),
+        );
+        check_step(
+            &mut divergences,
+            "indices",
+            self.indices.as_deref().map(format_indices),
+            format_indices(&entity_indices(&key_derivation, &parent_entropy)?),
+        );
+        check_step(
+            &mut divergences,
+            "path",
+            self.path.clone(),
+            entity_derivation_path(&key_derivation, &parent_entropy)?.to_string(),
+        );
+
+        if self.seed.is_none() && self.public_key.is_none() {
+            return Ok(divergences);
+        }
+        let mnemonic = self.mnemonic.as_deref().ok_or_else(|| {
+            BipKeychainError::Bip32Error(
+                "Reference vector lists keys but no mnemonic to derive them from".to_string(),
+            )
+        })?;
+        let derived = TestVector::compute(
+            &self.description,
+            mnemonic,
+            &parent_entropy,
+            &self.entity.to_string(),
+        )?;
+        check_step(&mut divergences, "seed", self.seed.clone(), derived.seed);
+        check_step(
+            &mut divergences,
+            "public_key",
+            self.public_key.clone(),
+            derived.public_key,
+        );
+
+        Ok(divergences)
+    }
+}
+
+/// Record a divergence if the reference lists `step` with a different value
+fn check_step(
+    divergences: &mut Vec<Divergence>,
+    step: &'static str,
+    expected: Option<String>,
+    actual: String,
+) {
+    if let Some(expected) = expected.filter(|expected| !expected.eq_ignore_ascii_case(&actual)) {
+        divergences.push(Divergence {
+            step,
+            expected,
+            actual,
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_vectors_cover_every_hash_function() {
+        let vectors = generate_vectors().unwrap();
+        for hash_function in ["hmac_sha512", "blake2b", "sha256", "blake3", "argon2id"] {
+            assert!(
+                vectors
+                    .vectors
+                    .iter()
+                    .any(|v| v.entity["derivation_config"]["hash_function"] == hash_function),
+                "no vector for {}",
+                hash_function
+            );
+        }
+    }
+}
@@ -0,0 +1,219 @@
+//! WebAssembly bindings (`wasm` feature)
+//!
+//! Exposes BIP-Keychain derivation to JavaScript so web wallets and browser
+//! extensions can derive keys client-side:
+//!
+//! ```js
+//! import { Keychain, KeyDerivation, formatKey } from "bip-keychain";
+//!
+//! const keychain = Keychain.fromMnemonic(phrase);
+//! const entity = KeyDerivation.fromJson(entityJson);
+//! const key = keychain.derive(entity);
+//! console.log(formatKey(key, entity, "ssh"));
+//! ```
+//!
+//! Parent entropy arguments are optional and default to
+//! [`DEFAULT_PARENT_ENTROPY`], so keys match the CLI's defaults. Build with
+//! `wasm-pack build --features wasm`.
+
+use crate::{
+    bip32_wrapper::{DerivedKey, Keychain},
+    derivation::{derive_key_from_entity, entity_derivation_path, DEFAULT_PARENT_ENTROPY},
+    entity::KeyDerivation,
+    error::{BipKeychainError, Result},
+    output::{self, OutputFormat},
+};
+use wasm_bindgen::prelude::*;
+
+/// Output format names, as accepted by the CLI's `--format`
+fn parse_output_format(name: &str) -> Result<OutputFormat> {
+    match name {
+        "seed" => Ok(OutputFormat::HexSeed),
+        "public-key" => Ok(OutputFormat::Ed25519PublicHex),
+        "private-key" => Ok(OutputFormat::Ed25519PrivateHex),
+        "public-key-pem" => Ok(OutputFormat::Ed25519PublicPem),
+        "private-key-pem" => Ok(OutputFormat::Ed25519PrivatePem),
+        "ssh" => Ok(OutputFormat::SshPublicKey),
+        "sshfp" => Ok(OutputFormat::SshfpRecord),
+        "tlsa" => Ok(OutputFormat::TlsaRecord),
+        "csr" => Ok(OutputFormat::Csr),
+        "jwk" => Ok(OutputFormat::Jwk),
+        "jwk-private" => Ok(OutputFormat::JwkPrivate),
+        "jwk-thumbprint" => Ok(OutputFormat::JwkThumbprint),
+        "tuf-key" => Ok(OutputFormat::TufKey),
+        "tuf-key-private" => Ok(OutputFormat::TufKeyPrivate),
+        "tuf-keyid" => Ok(OutputFormat::TufKeyid),
+        "omemo" => Ok(OutputFormat::OmemoIdentity),
+        "omemo-private" => Ok(OutputFormat::OmemoPrivate),
+        "tor-hidden-service" => Ok(OutputFormat::TorHiddenService),
+        "peer-id" => Ok(OutputFormat::Libp2pPeerId),
+        "libp2p-private" => Ok(OutputFormat::Libp2pPrivate),
+        "dnskey" => Ok(OutputFormat::DnssecDnskey),
+        "ds" => Ok(OutputFormat::DnssecDs),
+        "dnssec-private" => Ok(OutputFormat::DnssecPrivate),
+        "gpg" => Ok(OutputFormat::GpgPublicKey),
+        "json" => Ok(OutputFormat::Json),
+        "password" => Ok(OutputFormat::Password),
+        "otpauth" => Ok(OutputFormat::Otpauth),
+        other => Err(BipKeychainError::OutputError(format!(
+            "Unknown output format '{}': expected seed, public-key, private-key, public-key-pem, private-key-pem, ssh, sshfp, tlsa, csr, jwk, jwk-private, jwk-thumbprint, tuf-key, tuf-key-private, tuf-keyid, omemo, omemo-private, tor-hidden-service, peer-id, libp2p-private, dnskey, ds, dnssec-private, gpg, json, password, or otpauth",
+            other
+        ))),
+    }
+}
+
+/// A BIP-32 keychain built from a seed
+#[wasm_bindgen(js_name = Keychain)]
+pub struct WasmKeychain {
+    inner: Keychain,
+}
+
+#[wasm_bindgen(js_class = Keychain)]
+impl WasmKeychain {
+    /// Create a keychain from a BIP-39 mnemonic phrase
+    #[wasm_bindgen(js_name = fromMnemonic)]
+    pub fn from_mnemonic(phrase: &str) -> std::result::Result<WasmKeychain, JsError> {
+        Ok(Self {
+            inner: Keychain::from_mnemonic(phrase)?,
+        })
+    }
+
+    /// Create a keychain from a raw 16-64 byte BIP-32 seed
+    #[wasm_bindgen(js_name = fromSeed)]
+    pub fn from_seed(seed: &[u8]) -> std::result::Result<WasmKeychain, JsError> {
+        Ok(Self {
+            inner: Keychain::from_seed_bytes(seed)?,
+        })
+    }
+
+    /// Create a keychain from a base58 `xprv` string
+    #[wasm_bindgen(js_name = fromXprv)]
+    pub fn from_xprv(xprv: &str) -> std::result::Result<WasmKeychain, JsError> {
+        Ok(Self {
+            inner: Keychain::from_xprv(xprv)?,
+        })
+    }
+
+    /// Master key fingerprint as 8 hex characters
+    pub fn fingerprint(&self) -> String {
+        hex::encode(self.inner.fingerprint())
+    }
+
+    /// Derive the key for an entity
+    pub fn derive(
+        &self,
+        entity: &WasmKeyDerivation,
+        parent_entropy: Option<Vec<u8>>,
+    ) -> std::result::Result<WasmDerivedKey, JsError> {
+        let parent_entropy = parent_entropy.as_deref().unwrap_or(DEFAULT_PARENT_ENTROPY);
+        Ok(WasmDerivedKey {
+            inner: derive_key_from_entity(&self.inner, &entity.inner, parent_entropy)?,
+        })
+    }
+}
+
+/// An entity plus its derivation configuration
+#[wasm_bindgen(js_name = KeyDerivation)]
+pub struct WasmKeyDerivation {
+    inner: KeyDerivation,
+}
+
+#[wasm_bindgen(js_class = KeyDerivation)]
+impl WasmKeyDerivation {
+    /// Parse an entity file's JSON
+    #[wasm_bindgen(js_name = fromJson)]
+    pub fn from_json(json: &str) -> std::result::Result<WasmKeyDerivation, JsError> {
+        Ok(Self {
+            inner: KeyDerivation::from_json(json)?,
+        })
+    }
+
+    /// The entity's `purpose`, if any
+    #[wasm_bindgen(getter)]
+    pub fn purpose(&self) -> Option<String> {
+        self.inner.purpose.clone()
+    }
+
+    /// BIP-32 path the entity derives at, e.g. `m/83696968'/67797668'/1234'`
+    #[wasm_bindgen(js_name = derivationPath)]
+    pub fn derivation_path(
+        &self,
+        parent_entropy: Option<Vec<u8>>,
+    ) -> std::result::Result<String, JsError> {
+        let parent_entropy = parent_entropy.as_deref().unwrap_or(DEFAULT_PARENT_ENTROPY);
+        Ok(entity_derivation_path(&self.inner, parent_entropy)?.to_string())
+    }
+}
+
+/// A key derived from an entity
+///
+/// Secret material stays in wasm memory; use [`format_key`] to export it.
+#[wasm_bindgen(js_name = DerivedKey)]
+pub struct WasmDerivedKey {
+    inner: DerivedKey,
+}
+
+#[wasm_bindgen(js_class = DerivedKey)]
+impl WasmDerivedKey {
+    /// BIP-32 fingerprint of the derived key as 8 hex characters
+    pub fn fingerprint(&self) -> String {
+        hex::encode(self.inner.fingerprint())
+    }
+}
+
+/// Format a derived key (`seed`, `public-key`, `private-key`, `ssh`, `gpg`, or `json`)
+#[wasm_bindgen(js_name = formatKey)]
+pub fn format_key(
+    derived: &WasmDerivedKey,
+    entity: &WasmKeyDerivation,
+    format: &str,
+) -> std::result::Result<String, JsError> {
+    let format = parse_output_format(format)?;
+    Ok(output::format_key(&derived.inner, &entity.inner, format)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TEST_MNEMONIC: &str = "abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon about";
+
+    #[test]
+    fn test_bindings_match_library() {
+        let entity_json = r#"{
+            "schema_type": "schema_org",
+            "entity": {"@type": "SoftwareSourceCode", "name": "bip-keychain"},
+            "derivation_config": {"hash_function": "hmac_sha512", "hardened": true}
+        }"#;
+
+        let keychain = WasmKeychain::from_mnemonic(TEST_MNEMONIC).unwrap();
+        let entity = WasmKeyDerivation::from_json(entity_json).unwrap();
+        let derived = keychain.derive(&entity, None).unwrap();
+
+        let expected = derive_key_from_entity(
+            &Keychain::from_mnemonic(TEST_MNEMONIC).unwrap(),
+            &KeyDerivation::from_json(entity_json).unwrap(),
+            DEFAULT_PARENT_ENTROPY,
+        )
+        .unwrap();
+
+        assert_eq!(
+            format_key(&derived, &entity, "ssh").unwrap(),
+            output::format_key(
+                &expected,
+                &KeyDerivation::from_json(entity_json).unwrap(),
+                OutputFormat::SshPublicKey
+            )
+            .unwrap()
+        );
+    }
+
+    #[test]
+    fn test_parse_output_format() {
+        assert_eq!(
+            parse_output_format("public-key").unwrap(),
+            OutputFormat::Ed25519PublicHex
+        );
+        assert!(parse_output_format("qr").is_err());
+    }
+}
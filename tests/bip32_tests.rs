@@ -70,6 +70,66 @@ fn test_invalid_mnemonic() {
     assert!(result.is_err());
 }
 
+#[test]
+fn test_from_seed_bytes_bip32_vector_1() {
+    // BIP-32 Test Vector 1 master key
+    use bip32::Prefix;
+
+    let seed = hex::decode("000102030405060708090a0b0c0d0e0f").unwrap();
+    let keychain = Keychain::from_seed_bytes(&seed).expect("Should accept 16-byte seed");
+
+    assert_eq!(
+        keychain.master_key().to_string(Prefix::XPRV).as_str(),
+        "xprv9s21ZrQH143K3QTDL4LXw2F7HEK3wJUD2nW2nRk4stbPy6cq3jPPqjiChkVvvNKmPGJxWUtg6LnF5kejMRNNU3TGtRBeJgk33yuGBxrMPHi"
+    );
+}
+
+#[test]
+fn test_master_fingerprint_bip32_vector_1() {
+    // BIP-32 Test Vector 1: chain m/0H has parent fingerprint 3442193e
+    let seed = hex::decode("000102030405060708090a0b0c0d0e0f").unwrap();
+    let keychain = Keychain::from_seed_bytes(&seed).unwrap();
+
+    assert_eq!(hex::encode(keychain.fingerprint()), "3442193e");
+}
+
+#[test]
+fn test_from_xprv_matches_seed() {
+    let seed = hex::decode("000102030405060708090a0b0c0d0e0f").unwrap();
+    let from_seed = Keychain::from_seed_bytes(&seed).unwrap();
+    let from_xprv = Keychain::from_xprv(
+        "xprv9s21ZrQH143K3QTDL4LXw2F7HEK3wJUD2nW2nRk4stbPy6cq3jPPqjiChkVvvNKmPGJxWUtg6LnF5kejMRNNU3TGtRBeJgk33yuGBxrMPHi",
+    )
+    .expect("Should parse BIP-32 test vector xprv");
+
+    assert_eq!(
+        from_seed.derive_bip_keychain_path(42).unwrap().to_bytes(),
+        from_xprv.derive_bip_keychain_path(42).unwrap().to_bytes()
+    );
+
+    assert!(Keychain::from_xprv("xprv-not-base58").is_err());
+}
+
+#[test]
+fn test_from_entropy_hex_matches_mnemonic() {
+    // "abandon ... about" encodes 16 zero bytes of entropy
+    let mnemonic = "abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon about";
+
+    let from_mnemonic = Keychain::from_mnemonic(mnemonic).unwrap();
+    let from_entropy = Keychain::from_entropy_hex("00000000000000000000000000000000").unwrap();
+
+    assert_eq!(
+        from_mnemonic
+            .derive_bip_keychain_path(7)
+            .unwrap()
+            .to_bytes(),
+        from_entropy.derive_bip_keychain_path(7).unwrap().to_bytes()
+    );
+
+    assert!(Keychain::from_entropy_hex("not hex").is_err());
+    assert!(Keychain::from_entropy_hex("0000").is_err());
+}
+
 #[test]
 fn test_bip32_constants() {
     // Verify the BIP-Keychain path constants
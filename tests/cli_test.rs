@@ -0,0 +1,524 @@
+//! Command-line behaviour of the bip-keychain binary
+//!
+//! Each test runs the binary in its own scratch directory, with the seed
+//! given through the environment and the user's config file hidden.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Output};
+
+const TEST_MNEMONIC: &str =
+    "abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon about";
+
+const ENTITY: &str = r#"{
+  "schema_type": "dns",
+  "entity": {"name": "prod.example.com"},
+  "derivation_config": {"hash_function": "hmac_sha512", "hardened": true},
+  "purpose": "Production server"
+}"#;
+
+/// A scratch directory, removed when dropped
+struct TempDir(PathBuf);
+
+impl TempDir {
+    fn new(name: &str) -> Self {
+        let path =
+            std::env::temp_dir().join(format!("bip-keychain-cli-{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&path);
+        fs::create_dir_all(&path).unwrap();
+        Self(path)
+    }
+
+    fn path(&self) -> &Path {
+        &self.0
+    }
+
+    fn write(&self, name: &str, contents: &str) -> PathBuf {
+        let path = self.0.join(name);
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent).unwrap();
+        }
+        fs::write(&path, contents).unwrap();
+        path
+    }
+}
+
+impl Drop for TempDir {
+    fn drop(&mut self) {
+        let _ = fs::remove_dir_all(&self.0);
+    }
+}
+
+/// The binary, run in `dir` with the test seed and no config file
+fn bip_keychain(dir: &TempDir) -> Command {
+    let mut command = Command::new(env!("CARGO_BIN_EXE_bip-keychain"));
+    command
+        .current_dir(dir.path())
+        .env("BIP_KEYCHAIN_SEED", TEST_MNEMONIC)
+        .env("HOME", dir.path())
+        .env("XDG_CONFIG_HOME", dir.path().join("config"))
+        .env_remove("BIP_KEYCHAIN_SEED_PASSPHRASE");
+    command
+}
+
+fn run(command: &mut Command) -> Output {
+    command.output().expect("bip-keychain should run")
+}
+
+fn stdout(output: &Output) -> String {
+    String::from_utf8_lossy(&output.stdout).into_owned()
+}
+
+fn stderr(output: &Output) -> String {
+    String::from_utf8_lossy(&output.stderr).into_owned()
+}
+
+/// Assert the command failed with `code`
+fn assert_exit(output: &Output, code: i32) {
+    assert_eq!(
+        output.status.code(),
+        Some(code),
+        "stdout: {}\nstderr: {}",
+        stdout(output),
+        stderr(output)
+    );
+}
+
+#[cfg(unix)]
+#[test]
+fn test_agent_refuses_revoked_key() {
+    let dir = TempDir::new("agent-revoked");
+    dir.write("server.json", ENTITY);
+    dir.write(
+        "manifest.json",
+        r#"{"entities": [{"file": "server.json"}]}"#,
+    );
+    assert_exit(
+        &run(bip_keychain(&dir).args(["revoke", "add", "server.json", "--reason", "retired"])),
+        0,
+    );
+
+    let socket = dir.path().join("agent.sock");
+    let output = run(bip_keychain(&dir)
+        .args(["agent", "manifest.json", "--socket"])
+        .arg(&socket));
+    assert_exit(&output, 22);
+    assert!(stderr(&output).contains("revoked"));
+    assert!(!socket.exists(), "the agent must not start");
+}
+
+#[cfg(unix)]
+#[test]
+fn test_agent_refuses_expired_entity() {
+    let dir = TempDir::new("agent-expired");
+    dir.write(
+        "old.json",
+        &ENTITY.replace(
+            r#""purpose""#,
+            r#""metadata": {"not_after": "2020-01-01"}, "purpose""#,
+        ),
+    );
+    dir.write("manifest.json", r#"{"entities": [{"file": "old.json"}]}"#);
+
+    let socket = dir.path().join("agent.sock");
+    let output = run(bip_keychain(&dir)
+        .args(["agent", "manifest.json", "--socket"])
+        .arg(&socket));
+    assert_exit(&output, 27);
+    assert!(stderr(&output).contains("expired after 2020-01-01"));
+    assert!(!socket.exists(), "the agent must not start");
+}
+
+#[test]
+fn test_seed_source_failures_exit_15() {
+    let dir = TempDir::new("seed-source");
+    dir.write("server.json", ENTITY);
+    let derive = |command: &mut Command| run(command.args(["derive", "server.json"]));
+
+    let output = derive(bip_keychain(&dir).env_remove("BIP_KEYCHAIN_SEED"));
+    assert_exit(&output, 15);
+    assert!(stderr(&output).contains("BIP_KEYCHAIN_SEED environment variable not set"));
+
+    let output = derive(bip_keychain(&dir).args(["--seed-file", "missing.txt"]));
+    assert_exit(&output, 15);
+    assert!(stderr(&output).contains("missing.txt"));
+
+    dir.write("empty.txt", "\n");
+    assert_exit(
+        &derive(bip_keychain(&dir).args(["--seed-file", "empty.txt"])),
+        15,
+    );
+
+    #[cfg(unix)]
+    {
+        assert_exit(&derive(bip_keychain(&dir).args(["--seed-fd", "97"])), 15);
+        assert_exit(&derive(bip_keychain(&dir).args(["--seed-fd", "2"])), 15);
+    }
+
+    if !cfg!(feature = "os-keychain") {
+        let output = derive(bip_keychain(&dir).args(["--seed-source", "os-keychain"]));
+        assert_exit(&output, 15);
+        assert!(stderr(&output).contains("OS keychain support"));
+    }
+}
+
+#[cfg(unix)]
+#[test]
+fn test_output_does_not_follow_symlinks() {
+    let dir = TempDir::new("output-symlink");
+    dir.write("server.json", ENTITY);
+    let target = dir.write("target.txt", "untouched\n");
+    std::os::unix::fs::symlink(&target, dir.path().join("server.key")).unwrap();
+
+    let output = run(bip_keychain(&dir).args([
+        "derive",
+        "server.json",
+        "--format",
+        "private-key",
+        "-o",
+        "server.key",
+    ]));
+    assert_exit(&output, 1);
+    assert!(stderr(&output).contains("already exists (pass --force to overwrite)"));
+    assert_eq!(fs::read_to_string(&target).unwrap(), "untouched\n");
+
+    // A dangling link would otherwise create its target
+    let planted = dir.path().join("planted.key");
+    std::os::unix::fs::symlink(&planted, dir.path().join("dangling.key")).unwrap();
+    let output = run(bip_keychain(&dir).args([
+        "derive",
+        "server.json",
+        "--format",
+        "private-key",
+        "-o",
+        "dangling.key",
+    ]));
+    assert_exit(&output, 1);
+    assert!(!planted.exists());
+}
+
+#[test]
+fn test_derive_dir_skips_non_entities() {
+    let dir = TempDir::new("derive-dir-mixed");
+    dir.write("ents/server.json", ENTITY);
+    dir.write(
+        "ents/sub/web.json",
+        &ENTITY.replace("prod.example.com", "web.example.com"),
+    );
+    dir.write("ents/manifest.json", r#"{"entities": []}"#);
+    dir.write("ents/junk.json", "not json\n");
+
+    let output = run(bip_keychain(&dir).args(["derive-dir", "ents", "--out", "keys"]));
+    assert_exit(&output, 0);
+    let messages = stderr(&output);
+    assert!(messages.contains("skipping ents/junk.json"), "{}", messages);
+    assert!(
+        messages.contains("skipping ents/manifest.json"),
+        "{}",
+        messages
+    );
+    assert!(messages.contains("2 entities: 2 written, 0 unchanged, 2 skipped"));
+    assert!(dir.path().join("keys/server.ssh").exists());
+    assert!(dir.path().join("keys/sub/web.ssh").exists());
+    assert!(!dir.path().join("keys/junk.ssh").exists());
+
+    // Nothing but non-entities is still an error
+    let only_junk = TempDir::new("derive-dir-junk");
+    only_junk.write("ents/junk.json", "not json\n");
+    let output = run(bip_keychain(&only_junk).args(["derive-dir", "ents", "--out", "keys"]));
+    assert_exit(&output, 1);
+    assert!(stderr(&output).contains("1 JSON file(s) skipped"));
+}
+
+const OTHER_MNEMONIC: &str =
+    "legal winner thank year wave sausage worth useful legal winner thank yellow";
+
+fn derive_stdout(command: &mut Command) -> String {
+    let output = run(command);
+    assert_exit(&output, 0);
+    stdout(&output).trim().to_string()
+}
+
+#[test]
+fn test_config_precedence() {
+    let dir = TempDir::new("config");
+    dir.write("server.json", ENTITY);
+    let public_key =
+        derive_stdout(bip_keychain(&dir).args(["derive", "server.json", "--format", "public-key"]));
+    let ssh = derive_stdout(bip_keychain(&dir).args(["derive", "server.json"]));
+    assert!(ssh.starts_with("ssh-ed25519 "));
+
+    // The default config file supplies the format; the command line wins
+    dir.write(
+        "config/bip-keychain/config.toml",
+        "format = \"public-key\"\n",
+    );
+    assert_eq!(
+        derive_stdout(bip_keychain(&dir).args(["derive", "server.json"])),
+        public_key
+    );
+    assert_eq!(
+        derive_stdout(bip_keychain(&dir).args(["derive", "server.json", "--format", "ssh"])),
+        ssh
+    );
+
+    // The entity's output_format comes before the config file's format
+    dir.write(
+        "preferred.json",
+        &ENTITY.replace(r#""purpose""#, r#""output_format": "ssh", "purpose""#),
+    );
+    assert_eq!(
+        derive_stdout(bip_keychain(&dir).args(["derive", "preferred.json"])),
+        ssh
+    );
+
+    // --config replaces the default file
+    dir.write("other.toml", "format = \"ssh\"\n");
+    assert_eq!(
+        derive_stdout(bip_keychain(&dir).args(["--config", "other.toml", "derive", "server.json"])),
+        ssh
+    );
+
+    // A configured seed file is used ahead of BIP_KEYCHAIN_SEED, unless a
+    // seed option is given on the command line
+    dir.write("other-seed.txt", OTHER_MNEMONIC);
+    dir.write(
+        "seeded.toml",
+        "format = \"public-key\"\nseed-file = \"other-seed.txt\"\n",
+    );
+    let other_key = derive_stdout(bip_keychain(&dir).args([
+        "--config",
+        "seeded.toml",
+        "derive",
+        "server.json",
+    ]));
+    assert_ne!(other_key, public_key);
+    assert_eq!(
+        derive_stdout(bip_keychain(&dir).args([
+            "--config",
+            "seeded.toml",
+            "--seed-source",
+            "env",
+            "derive",
+            "server.json",
+        ])),
+        public_key
+    );
+
+    // Unknown keys are a config error
+    dir.write("bad.toml", "no-such-option = true\n");
+    assert_exit(
+        &run(bip_keychain(&dir).args(["--config", "bad.toml", "derive", "server.json"])),
+        24,
+    );
+}
+
+#[cfg(unix)]
+#[test]
+fn test_output_permissions_and_force() {
+    use std::os::unix::fs::PermissionsExt;
+
+    let dir = TempDir::new("output");
+    dir.write("server.json", ENTITY);
+    let mode = |name: &str| {
+        fs::metadata(dir.path().join(name))
+            .unwrap()
+            .permissions()
+            .mode()
+            & 0o777
+    };
+
+    let derive = |format: &str, path: &str, force: bool| {
+        let mut command = bip_keychain(&dir);
+        command.args(["derive", "server.json", "--format", format, "-o", path]);
+        if force {
+            command.arg("--force");
+        }
+        run(&mut command)
+    };
+    assert_exit(&derive("ssh", "server.pub", false), 0);
+    assert_eq!(mode("server.pub"), 0o644);
+    assert_exit(&derive("private-key", "server.key", false), 0);
+    assert_eq!(mode("server.key"), 0o600);
+    // Any secret format makes the file private
+    assert_exit(&derive("ssh,seed", "both.json", false), 0);
+    assert_eq!(mode("both.json"), 0o600);
+
+    // Existing files are kept unless --force is given
+    let key = fs::read_to_string(dir.path().join("server.key")).unwrap();
+    assert_exit(&derive("ssh", "server.key", false), 1);
+    assert_eq!(
+        fs::read_to_string(dir.path().join("server.key")).unwrap(),
+        key
+    );
+    assert_exit(&derive("ssh", "server.key", true), 0);
+    assert!(fs::read_to_string(dir.path().join("server.key"))
+        .unwrap()
+        .starts_with("ssh-ed25519 "));
+
+    // --force tightens a public file that becomes secret
+    assert_exit(&derive("private-key", "server.pub", true), 0);
+    assert_eq!(mode("server.pub"), 0o600);
+
+    // Relative paths land in the configured output-dir, itself relative to
+    // the config file
+    dir.write("config/bip-keychain/config.toml", "output-dir = \"keys\"\n");
+    assert_exit(&derive("ssh", "server.pub", false), 0);
+    assert_eq!(mode("config/bip-keychain/keys/server.pub"), 0o644);
+}
+
+#[test]
+fn test_seed_file_and_fd() {
+    let dir = TempDir::new("seed-file");
+    dir.write("server.json", ENTITY);
+    let from_env = derive_stdout(bip_keychain(&dir).args(["derive", "server.json"]));
+
+    // Extra whitespace and line breaks are normalized
+    dir.write("seed.txt", &TEST_MNEMONIC.replace(' ', "\n  "));
+    assert_eq!(
+        derive_stdout(bip_keychain(&dir).env_remove("BIP_KEYCHAIN_SEED").args([
+            "--seed-file",
+            "seed.txt",
+            "derive",
+            "server.json"
+        ])),
+        from_env
+    );
+
+    #[cfg(unix)]
+    {
+        use std::io::Write;
+        use std::process::Stdio;
+
+        let mut child = bip_keychain(&dir)
+            .env_remove("BIP_KEYCHAIN_SEED")
+            .args(["--seed-fd", "0", "derive", "server.json"])
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()
+            .unwrap();
+        child
+            .stdin
+            .take()
+            .unwrap()
+            .write_all(OTHER_MNEMONIC.as_bytes())
+            .unwrap();
+        let output = child.wait_with_output().unwrap();
+        assert_exit(&output, 0);
+        let from_fd = stdout(&output).trim().to_string();
+        assert!(from_fd.starts_with("ssh-ed25519 "));
+        assert_ne!(from_fd, from_env);
+    }
+
+    // Only one seed option may be given
+    assert_exit(
+        &run(bip_keychain(&dir).args([
+            "--seed-file",
+            "seed.txt",
+            "--seed-fd",
+            "0",
+            "derive",
+            "server.json",
+        ])),
+        2,
+    );
+}
+
+#[test]
+fn test_exit_codes() {
+    let dir = TempDir::new("exit-codes");
+    dir.write("server.json", ENTITY);
+    dir.write(
+        "bad.json",
+        &ENTITY.replace(r#""hmac_sha512""#, r#""no_such_hash""#),
+    );
+    dir.write(
+        "expired.json",
+        &ENTITY.replace(
+            r#""purpose""#,
+            r#""metadata": {"not_after": "2020-01-01"}, "purpose""#,
+        ),
+    );
+
+    let cases: &[(&[&str], Option<&str>, i32)] = &[
+        (&["derive", "server.json", "--no-such-flag"], None, 2),
+        (&["derive", "bad.json"], None, 10),
+        (&["derive", "server.json"], Some("not a valid mnemonic"), 13),
+        (&["derive", "missing.json"], None, 25),
+        (&["derive", "expired.json"], None, 27),
+        (&["derive", "expired.json", "--allow-expired"], None, 0),
+    ];
+    for (args, seed, code) in cases {
+        let mut command = bip_keychain(&dir);
+        if let Some(seed) = seed {
+            command.env("BIP_KEYCHAIN_SEED", seed);
+        }
+        let output = run(command.args(*args));
+        assert_eq!(
+            output.status.code(),
+            Some(*code),
+            "{:?}: {}",
+            args,
+            stderr(&output)
+        );
+    }
+}
+
+#[test]
+fn test_exit_code_table_is_documented() {
+    // Every ErrorKind appears in CLI-USAGE.md with its exit code
+    let usage = include_str!("../CLI-USAGE.md");
+    for kind in bip_keychain::ErrorKind::ALL {
+        let row = format!("| {} | `{}` |", kind.exit_code(), kind.code());
+        assert!(usage.contains(&row), "CLI-USAGE.md lacks {}", row);
+    }
+}
+
+#[test]
+fn test_derive_dir_mirrors_tree() {
+    let dir = TempDir::new("derive-dir-tree");
+    dir.write("ents/a/server.json", ENTITY);
+    dir.write(
+        "ents/b/c/web.json",
+        &ENTITY.replace("prod.example.com", "web.example.com"),
+    );
+    dir.write("ents/.hidden/skip.json", ENTITY);
+    let derive_dir = |extra: &[&str]| {
+        run(bip_keychain(&dir)
+            .args(["derive-dir", "ents", "--out", "keys"])
+            .args(extra))
+    };
+
+    let output = derive_dir(&[]);
+    assert_exit(&output, 0);
+    assert!(stderr(&output).contains("2 entities: 2 written, 0 unchanged, 0 skipped"));
+    let server = dir.path().join("keys/a/server.ssh");
+    let web = dir.path().join("keys/b/c/web.ssh");
+    assert_eq!(
+        fs::read_to_string(&server).unwrap().trim(),
+        derive_stdout(bip_keychain(&dir).args(["derive", "ents/a/server.json"]))
+    );
+    assert!(web.exists());
+    assert!(!dir.path().join("keys/.hidden").exists());
+
+    // A second run changes nothing
+    let output = derive_dir(&[]);
+    assert_exit(&output, 0);
+    assert!(stderr(&output).contains("2 entities: 0 written, 2 unchanged, 0 skipped"));
+
+    // A changed output is only replaced with --force, and a refused run
+    // writes nothing
+    fs::write(&server, "edited\n").unwrap();
+    fs::remove_file(&web).unwrap();
+    assert_exit(&derive_dir(&[]), 1);
+    assert_eq!(fs::read_to_string(&server).unwrap(), "edited\n");
+    assert!(!web.exists());
+    let output = derive_dir(&["--force"]);
+    assert_exit(&output, 0);
+    assert!(stderr(&output).contains("2 entities: 2 written, 0 unchanged, 0 skipped"));
+
+    // --format sets every file's format and extension
+    assert_exit(&derive_dir(&["--format", "public-key"]), 0);
+    assert!(dir.path().join("keys/a/server.public-key").exists());
+}
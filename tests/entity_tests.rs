@@ -2,7 +2,7 @@
 //!
 //! Tests parsing of Nickel-exported JSON entities into Rust structs.
 
-use bip_keychain::{HashFunctionConfig, KeyDerivation};
+use bip_keychain::{Entity, HashFunctionConfig, KeyDerivation};
 
 #[test]
 fn test_parse_schema_org_entity() {
@@ -42,8 +42,11 @@ fn test_parse_schema_org_entity() {
     );
 
     // Verify entity fields
-    assert_eq!(key_derivation.entity["@type"], "SoftwareSourceCode");
-    assert_eq!(key_derivation.entity["name"], "BIP-Keychain Core");
+    let Entity::SchemaOrg(entity) = &key_derivation.entity else {
+        panic!("expected a schema.org entity");
+    };
+    assert_eq!(entity.type_, "SoftwareSourceCode");
+    assert_eq!(entity.properties["name"], "BIP-Keychain Core");
 }
 
 #[test]
@@ -75,7 +78,7 @@ fn test_parse_entity_with_blake2b() {
     let json = r#"{
   "schema_type": "gordian_envelope",
   "entity": {
-    "envelope": "ur:envelope/example123",
+    "envelope": "ur:envelope/tpsoiyfdihjzjzjldmksbaoede",
     "format": "ur:envelope"
   },
   "derivation_config": {
@@ -97,3 +100,138 @@ fn test_parse_entity_with_blake2b() {
         "Selective disclosure credentials"
     );
 }
+
+#[test]
+fn test_did_entity_requires_method() {
+    let json = r#"{
+  "schema_type": "did",
+  "entity": {"did": "did:github:alice", "identifier": "alice"},
+  "derivation_config": {"hash_function": "hmac_sha512", "hardened": true}
+}"#;
+
+    let err = KeyDerivation::from_json(json).expect_err("DID without method should be rejected");
+    assert!(err.to_string().contains("method"));
+}
+
+#[test]
+fn test_schema_org_entity_requires_string_type() {
+    for entity in [
+        r#"{"name": "Untyped"}"#,
+        r#"{"@type": 42, "name": "Numeric"}"#,
+    ] {
+        let json = format!(
+            r#"{{"schema_type": "schema_org", "entity": {}, "derivation_config": {{"hash_function": "hmac_sha512", "hardened": true}}}}"#,
+            entity
+        );
+        assert!(
+            KeyDerivation::from_json(&json).is_err(),
+            "accepted {}",
+            entity
+        );
+    }
+}
+
+#[test]
+fn test_gordian_envelope_entity_requires_envelope() {
+    let json = r#"{
+  "schema_type": "gordian_envelope",
+  "entity": {"format": "ur:envelope"},
+  "derivation_config": {"hash_function": "blake2b", "hardened": true}
+}"#;
+
+    assert!(KeyDerivation::from_json(json).is_err());
+}
+
+#[test]
+fn test_unknown_schema_type_is_custom() {
+    let json = r#"{
+  "schema_type": "x509",
+  "entity": {"subject": "CN=example"},
+  "derivation_config": {"hash_function": "sha256", "hardened": true}
+}"#;
+
+    let key_derivation = KeyDerivation::from_json(json).expect("Should parse custom entity");
+    assert!(matches!(key_derivation.entity, Entity::Custom(_)));
+}
+
+#[test]
+fn test_typed_entity_round_trips_to_same_json() {
+    let entity =
+        r#"{"did":"did:github:alice","method":"github","identifier":"alice","service":"git"}"#;
+    let json = format!(
+        r#"{{"schema_type": "did", "entity": {}, "derivation_config": {{"hash_function": "hmac_sha512", "hardened": true}}}}"#,
+        entity
+    );
+
+    let key_derivation = KeyDerivation::from_json(&json).unwrap();
+    assert!(matches!(key_derivation.entity, Entity::Did(_)));
+    assert_eq!(
+        key_derivation.entity.to_value(),
+        serde_json::from_str::<serde_json::Value>(entity).unwrap()
+    );
+}
+
+#[test]
+fn test_did_entity_must_match_did() {
+    let with_entity = |entity: &str, extra: &str| {
+        format!(
+            r#"{{"schema_type": "did", "entity": {}, "derivation_config": {{"hash_function": "hmac_sha512", "hardened": true{}}}}}"#,
+            entity, extra
+        )
+    };
+
+    let mismatched = r#"{"did": "did:github:alice", "method": "github", "identifier": "bob"}"#;
+    assert!(KeyDerivation::from_json(&with_entity(mismatched, "")).is_err());
+
+    let malformed =
+        r#"{"did": "did:github:alice#key-1", "method": "github", "identifier": "alice#key-1"}"#;
+    assert!(KeyDerivation::from_json(&with_entity(malformed, "")).is_err());
+
+    let uppercase = r#"{"did": "did:GitHub:alice", "method": "GitHub", "identifier": "alice"}"#;
+    assert!(KeyDerivation::from_json(&with_entity(uppercase, "")).is_err());
+
+    let normalized =
+        KeyDerivation::from_json(&with_entity(uppercase, r#", "lowercase_did_method": true"#))
+            .unwrap();
+    let lowercase = KeyDerivation::from_json(&with_entity(
+        r#"{"did": "did:github:alice", "method": "github", "identifier": "alice"}"#,
+        "",
+    ))
+    .unwrap();
+    assert_eq!(
+        normalized.canonical_entity().unwrap(),
+        lowercase.canonical_entity().unwrap()
+    );
+}
+
+#[test]
+fn test_gordian_envelope_entity_hashes_envelope_cbor() {
+    let with_entity = |entity: &str| {
+        format!(
+            r#"{{"schema_type": "gordian_envelope", "entity": {}, "derivation_config": {{"hash_function": "blake2b", "hardened": true}}}}"#,
+            entity
+        )
+    };
+
+    let key_derivation = KeyDerivation::from_json(&with_entity(
+        r#"{"envelope": "ur:envelope/tpsoiyfdihjzjzjldmksbaoede", "format": "ur:envelope"}"#,
+    ))
+    .unwrap();
+    // #6.200(#6.201("Hello."))
+    assert_eq!(
+        hex::encode(key_derivation.canonical_entity().unwrap()),
+        "d8c8d8c96648656c6c6f2e"
+    );
+
+    // Checksum failure
+    assert!(KeyDerivation::from_json(&with_entity(
+        r#"{"envelope": "ur:envelope/tpsoiyfdihjzjzjldmksbaoeda"}"#
+    ))
+    .is_err());
+    // Digest mismatch
+    assert!(KeyDerivation::from_json(&with_entity(&format!(
+        r#"{{"envelope": "ur:envelope/tpsoiyfdihjzjzjldmksbaoede", "digest": "{}"}}"#,
+        "00".repeat(32)
+    )))
+    .is_err());
+}
@@ -161,6 +161,23 @@ fn test_blake2b_with_json_entity() {
     assert_eq!(result1.len(), 64, "BLAKE2b should produce 64 bytes");
 }
 
+#[test]
+fn test_blake2b_matches_pure_rust_fallback() {
+    // wasm32 builds use the blake2 crate instead of libsodium; both must
+    // yield the same entity indices
+    use blake2::{Blake2b512, Digest};
+
+    for data in [
+        "",
+        "abc",
+        r#"{"@type":"Organization","name":"Blockchain Commons"}"#,
+    ] {
+        let libsodium = hash_entity(data, &[0u8; 32], HashFunction::Blake2b).unwrap();
+        let pure_rust: [u8; 64] = Blake2b512::digest(data.as_bytes()).into();
+        assert_eq!(libsodium, pure_rust, "mismatch for {:?}", data);
+    }
+}
+
 // SHA-256 tests
 
 #[test]
@@ -258,3 +275,108 @@ fn test_sha256_with_json_entity() {
         "Last 32 bytes should be zero padding"
     );
 }
+
+// BLAKE3 tests
+
+#[test]
+fn test_blake3_matches_keyed_hash() {
+    // With a 32-byte parent entropy the key is used directly, so the first
+    // 32 bytes of the XOF output equal BLAKE3's standard keyed hash.
+    // Key from the official BLAKE3 test vectors.
+    let key = *b"whats the Elvish word for friend";
+    let data = "Hi There";
+
+    let expected = blake3::keyed_hash(&key, data.as_bytes());
+
+    let result = hash_entity(data, &key, HashFunction::Blake3).expect("BLAKE3 should succeed");
+
+    assert_eq!(
+        &result[..32],
+        expected.as_bytes(),
+        "BLAKE3 output should start with the keyed hash"
+    );
+    assert!(
+        result[32..].iter().any(|&b| b != 0),
+        "Last 32 bytes should be XOF output, not padding"
+    );
+}
+
+#[test]
+fn test_blake3_uses_parent_entropy() {
+    let entity_json = r#"{"@context":"https://schema.org","@type":"Thing","name":"Test"}"#;
+
+    let result1 = hash_entity(entity_json, &[1u8; 32], HashFunction::Blake3).unwrap();
+    let result2 = hash_entity(entity_json, &[1u8; 32], HashFunction::Blake3).unwrap();
+    let result3 = hash_entity(entity_json, &[2u8; 32], HashFunction::Blake3).unwrap();
+
+    assert_eq!(
+        result1, result2,
+        "Same input should produce same output (determinism)"
+    );
+    assert_ne!(
+        result1, result3,
+        "Different parent entropy should produce different output"
+    );
+}
+
+#[test]
+fn test_blake3_accepts_any_entropy_length() {
+    let entity_json = r#"{"name":"Test"}"#;
+
+    // 64-byte entropy (e.g. BIP-85 output) is compressed to a 32-byte key
+    let result64 = hash_entity(entity_json, &[7u8; 64], HashFunction::Blake3).unwrap();
+    let result16 = hash_entity(entity_json, &[7u8; 16], HashFunction::Blake3).unwrap();
+    assert_ne!(result64, result16);
+
+    assert!(
+        hash_entity(entity_json, &[], HashFunction::Blake3).is_err(),
+        "Empty parent entropy should be rejected"
+    );
+}
+
+// HKDF expansion tests
+
+#[test]
+fn test_hkdf_sha512_expand_domain_separation() {
+    use bip_keychain::hash::hkdf_sha512_expand;
+
+    let hash = [0x42u8; 64];
+
+    let ssh = hkdf_sha512_expand(&hash, b"ssh").unwrap();
+    let gpg = hkdf_sha512_expand(&hash, b"gpg").unwrap();
+
+    assert_eq!(ssh, hkdf_sha512_expand(&hash, b"ssh").unwrap());
+    assert_ne!(
+        ssh, gpg,
+        "Different info strings should give different output"
+    );
+    assert_ne!(ssh, hash, "Expansion should not return the input");
+}
+
+// Argon2id tests
+
+#[test]
+fn test_argon2id_uses_parent_entropy_as_salt() {
+    let entity_json = r#"{"fqdn":"backup.example.com"}"#;
+
+    let result1 = hash_entity(entity_json, b"salt-one-16bytes", HashFunction::Argon2id).unwrap();
+    let result2 = hash_entity(entity_json, b"salt-one-16bytes", HashFunction::Argon2id).unwrap();
+    let result3 = hash_entity(entity_json, b"salt-two-16bytes", HashFunction::Argon2id).unwrap();
+
+    assert_eq!(
+        result1, result2,
+        "Same input should produce same output (determinism)"
+    );
+    assert_ne!(
+        result1, result3,
+        "Different parent entropy should produce different output"
+    );
+}
+
+#[test]
+fn test_argon2id_rejects_short_salt() {
+    assert!(
+        hash_entity("{}", b"short", HashFunction::Argon2id).is_err(),
+        "Argon2id requires at least 8 bytes of parent entropy"
+    );
+}
@@ -224,3 +224,25 @@ proptest! {
         prop_assert_ne!(keypair1.public_key_bytes(), keypair2.public_key_bytes());
     }
 }
+
+// Property test: parsers of untrusted input return errors instead of panicking
+// (the same entry points as the cargo-fuzz targets in fuzz/)
+proptest! {
+    #[test]
+    fn prop_parsers_do_not_panic(
+        text in "\\PC{0,200}",
+        bytes in proptest::collection::vec(any::<u8>(), 0..200),
+    ) {
+        use bip_keychain::{canonicalize, dcbor, sskr, ur, Canonicalization};
+
+        let _ = KeyDerivation::from_json(&text);
+        for mode in [Canonicalization::Serde, Canonicalization::Jcs, Canonicalization::Dcbor] {
+            let _ = canonicalize(&text, mode, true);
+        }
+        let _ = ur::decode(&text);
+        let _ = dcbor::decode_json(&bytes);
+        let _ = KeyDerivation::from_ur_string(&ur::encode("crypto-entity", &bytes));
+        let _ = sskr::inspect_share(&bytes);
+        let _ = sskr::combine_shares(&[bytes.clone(), bytes]);
+    }
+}
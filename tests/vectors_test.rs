@@ -0,0 +1,119 @@
+//! Published test vectors (vectors/bip-keychain.json)
+//!
+//! Every vector is re-derived from its inputs, so a change that alters any
+//! derived key or intermediate value fails here. Regenerate the file with
+//! `bip-keychain gen-vectors -o vectors/bip-keychain.json` only for
+//! deliberate, documented changes.
+
+use bip_keychain::{generate_vectors, ReferenceVectors, TestVectors};
+
+fn published() -> TestVectors {
+    serde_json::from_str(include_str!("../vectors/bip-keychain.json"))
+        .expect("vectors file should parse")
+}
+
+#[test]
+fn test_published_vectors_derive() {
+    let published = published();
+    assert_eq!(published.version, bip_keychain::vectors::VECTORS_VERSION);
+
+    for vector in &published.vectors {
+        let derived = vector.recompute().expect("vector should derive");
+        assert_eq!(
+            derived.canonical, vector.canonical,
+            "{}",
+            vector.description
+        );
+        assert_eq!(derived.hash, vector.hash, "{}", vector.description);
+        assert_eq!(derived.indices, vector.indices, "{}", vector.description);
+        assert_eq!(derived.path, vector.path, "{}", vector.description);
+        assert_eq!(derived.seed, vector.seed, "{}", vector.description);
+        assert_eq!(
+            derived.public_key, vector.public_key,
+            "{}",
+            vector.description
+        );
+    }
+}
+
+#[test]
+fn test_published_vectors_are_current() {
+    // gen-vectors and the published file must list the same vectors
+    assert_eq!(generate_vectors().unwrap(), published());
+}
+
+#[test]
+fn test_default_vector_matches_known_key() {
+    // Pinned independently of the file, so regenerating it cannot hide a
+    // change to the default configuration
+    let vector = &published().vectors[0];
+    assert_eq!(vector.path, "m/83696968'/67797668'/738678499'");
+    assert_eq!(
+        vector.public_key,
+        "f5819b2fa7016de2fce768114bd0198d372a700ab8b33bb82d2ed8585ad780a3"
+    );
+}
+
+/// Outputs of the akarve reference implementation, once vendored
+///
+/// Every vector must match exactly, except at the steps it lists in
+/// `known_divergences`.
+#[test]
+#[ignore = "vectors/akarve.json has not been generated yet (see vectors/README.md)"]
+fn test_akarve_reference() {
+    let path = concat!(env!("CARGO_MANIFEST_DIR"), "/vectors/akarve.json");
+    let reference: ReferenceVectors =
+        serde_json::from_str(&std::fs::read_to_string(path).expect("vectors/akarve.json"))
+            .expect("reference file should parse");
+    assert!(!reference.vectors.is_empty());
+
+    for vector in &reference.vectors {
+        let steps: Vec<_> = vector
+            .compare()
+            .unwrap()
+            .into_iter()
+            .map(|divergence| divergence.step)
+            .collect();
+        assert_eq!(steps, vector.known_divergences, "{}", vector.description);
+    }
+}
+
+#[test]
+fn test_reference_divergence_is_located() {
+    let published = &published().vectors[0];
+    let hash = hex::decode(&published.hash).unwrap();
+    let little_endian = u32::from_le_bytes(hash[..4].try_into().unwrap());
+
+    // Same canonical bytes and hash, but the index read little-endian
+    let reference: ReferenceVectors = serde_json::from_value(serde_json::json!({
+        "implementation": "example",
+        "vectors": [{
+            "parent_entropy": published.parent_entropy,
+            "entity": published.entity,
+            "canonical": published.canonical.to_uppercase(),
+            "hash": published.hash,
+            "indices": [little_endian],
+        }]
+    }))
+    .unwrap();
+    let divergences = reference.vectors[0].compare().unwrap();
+    assert_eq!(divergences.len(), 1);
+    assert_eq!(divergences[0].step, "indices");
+    assert_eq!(divergences[0].expected, little_endian.to_string());
+
+    // An implementation encoding the entity differently diverges at canonical
+    let mut pretty_printed = reference.vectors[0].clone();
+    let pretty = serde_json::to_string_pretty(&published.entity["entity"]).unwrap();
+    pretty_printed.canonical = Some(hex::encode(pretty));
+    pretty_printed.indices = None;
+    pretty_printed.public_key = Some(published.public_key.clone());
+    assert!(pretty_printed.compare().is_err(), "keys need a mnemonic");
+    pretty_printed.mnemonic = Some(published.mnemonic.clone());
+    let steps: Vec<_> = pretty_printed
+        .compare()
+        .unwrap()
+        .into_iter()
+        .map(|divergence| divergence.step)
+        .collect();
+    assert_eq!(steps, ["canonical"]);
+}